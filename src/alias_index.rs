@@ -0,0 +1,387 @@
+//! Every place an alias occurs in the vault, where it is defined, linked, tagged and mentioned,
+//! see [`AliasIndex`]
+//!
+//! The definitions come from the first pass, and [`AliasIndexVisitor`] adds the rest of each file
+//! during the second, so rules in the second pass query one index instead of each keeping its
+//! own table of aliases
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use aho_corasick::{AhoCorasick, BuildError};
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use hashbrown::{HashMap, HashSet};
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    aliases::AliasSource,
+    file::content::{
+        html::is_html,
+        source_map::SourceMap,
+        table::{cell_offset, is_in_table},
+        wikilink::{Alias, WikilinkVisitor},
+    },
+    rules::ErrorCode,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// How an alias occurs in a file
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OccurrenceKind {
+    /// The file defines the alias
+    Definition(AliasSource),
+    /// A wikilink, like `[[alias]]`
+    Wikilink,
+    /// A tag, like `#alias` or `#[[alias]]`
+    Tag,
+    /// The alias as a whole word in text, outside of a wikilink, see [`AliasIndex::mentions`]
+    Mention,
+}
+
+/// One place an alias occurs
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    pub alias: Alias,
+    pub file: PathBuf,
+    pub kind: OccurrenceKind,
+    /// The bytes of the file it occurs at, [`None`] for definitions
+    pub range: Option<Range<usize>>,
+}
+
+/// The alias as a whole word in some text, see [`AliasIndex::mentions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    pub alias: Alias,
+    /// The bytes of the text it is at, including the inflection
+    pub range: Range<usize>,
+    /// The plural or possessive after the alias, like the `s` in `projects`, or empty
+    pub inflection: &'static str,
+}
+
+/// Finds aliases in text, ignoring ascii case
+#[derive(Debug, Clone)]
+struct AliasMatcher {
+    /// The alias of each pattern of the automaton
+    aliases: Vec<Alias>,
+    automaton: AhoCorasick,
+}
+
+impl AliasMatcher {
+    fn new<'a>(aliases: impl Iterator<Item = &'a Alias>) -> Result<Self, BuildError> {
+        let mut aliases: Vec<Alias> = aliases.cloned().collect();
+        aliases.sort();
+        let automaton = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(aliases.iter().map(ToString::to_string))?;
+        Ok(Self { aliases, automaton })
+    }
+
+    /// Make sure the match starts and ends on a word boundary, so a part of a word isn't matched
+    fn find(&self, text: &str, inflections: bool) -> Vec<Mention> {
+        let boundaries = word_boundaries(text);
+        self.automaton
+            .find_iter(text)
+            .filter_map(|found| {
+                let (start, end) = (found.start(), found.end());
+                let inflection = if is_whole_word_match(text, &boundaries, start, end) {
+                    ""
+                } else if inflections {
+                    inflection(text, &boundaries, start, end)?
+                } else {
+                    return None;
+                };
+                Some(Mention {
+                    alias: self.aliases[found.pattern().as_usize()].clone(),
+                    range: start..end + inflection.len(),
+                    inflection,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Default for AliasMatcher {
+    fn default() -> Self {
+        Self::new(std::iter::empty()).expect("No patterns")
+    }
+}
+
+/// The byte offsets in `text` where a word starts or ends, using unicode word boundaries
+fn word_boundaries(text: &str) -> HashSet<usize> {
+    text.split_word_bound_indices()
+        .map(|(offset, _)| offset)
+        .chain([text.len()])
+        .collect()
+}
+
+/// Checks if the match at the given start and end indices is a whole word match.
+///
+/// Punctuation like commas, periods, parentheses and quotes ends a word,
+/// but hyphens between words do not, so `foo` is not matched in `foo-bar`
+fn is_whole_word_match(text: &str, boundaries: &HashSet<usize>, start: usize, end: usize) -> bool {
+    boundaries.contains(&start)
+        && boundaries.contains(&end)
+        && !is_start_hashtag(text, start)
+        && !is_hyphenated(text, start, end)
+}
+
+/// The suffixes of plurals and possessives, see [`crate::config::Config::unlinked_text_inflections`]
+const INFLECTIONS: [&str; 3] = ["'s", "es", "s"];
+
+/// The inflection after the match which makes it a whole word, like the `s` in `projects`
+fn inflection(
+    text: &str,
+    boundaries: &HashSet<usize>,
+    start: usize,
+    end: usize,
+) -> Option<&'static str> {
+    INFLECTIONS.into_iter().find(|inflection| {
+        text[end..].starts_with(inflection)
+            && is_whole_word_match(text, boundaries, start, end + inflection.len())
+    })
+}
+
+/// Checks if the character before the start index is a hashtag.
+fn is_start_hashtag(text: &str, start: usize) -> bool {
+    text[..start].ends_with('#')
+}
+
+/// Checks if the match is joined to a word before or after it by a hyphen.
+fn is_hyphenated(text: &str, start: usize, end: usize) -> bool {
+    let mut before = text[..start].chars().rev();
+    let mut after = text[end..].chars();
+    let joined = |hyphen: Option<char>, word: Option<char>| {
+        hyphen == Some('-') && word.is_some_and(char::is_alphanumeric)
+    };
+    joined(before.next(), before.next()) || joined(after.next(), after.next())
+}
+
+/// Every alias, the file it resolves to, and where it occurs in each file
+#[derive(Debug, Default, Clone)]
+pub struct AliasIndex {
+    /// See [`crate::rules::duplicate_alias::DuplicateAliasVisitor::alias_table`]
+    alias_table: HashMap<Alias, PathBuf>,
+    /// The occurrences in each file, definitions first, then in the order they appear
+    occurrences: BTreeMap<PathBuf, Vec<Occurrence>>,
+    /// Every alias
+    matcher: AliasMatcher,
+    /// The aliases which aren't defined by a file in `no_autolink`, see
+    /// [`crate::rules::duplicate_alias::DuplicateAliasVisitor::no_autolink`]
+    autolink_matcher: AliasMatcher,
+}
+
+impl AliasIndex {
+    /// An index of the `definitions` found in the first pass, without the other occurrences yet
+    ///
+    /// # Errors
+    ///
+    /// If the aliases are too many to match
+    pub fn new(
+        alias_table: HashMap<Alias, PathBuf>,
+        no_autolink: &HashSet<PathBuf>,
+        definitions: &[(Alias, PathBuf, AliasSource)],
+    ) -> Result<Self, BuildError> {
+        let matcher = AliasMatcher::new(alias_table.keys())?;
+        let autolink_matcher = AliasMatcher::new(
+            alias_table
+                .iter()
+                .filter(|(_, file)| !no_autolink.contains(*file))
+                .map(|(alias, _)| alias),
+        )?;
+        let mut occurrences: BTreeMap<PathBuf, Vec<Occurrence>> = BTreeMap::new();
+        for (alias, file, source) in definitions {
+            occurrences
+                .entry(file.clone())
+                .or_default()
+                .push(Occurrence {
+                    alias: alias.clone(),
+                    file: file.clone(),
+                    kind: OccurrenceKind::Definition(*source),
+                    range: None,
+                });
+        }
+        Ok(Self {
+            alias_table,
+            occurrences,
+            matcher,
+            autolink_matcher,
+        })
+    }
+
+    /// Every alias, and the file wikilinks to it resolve to
+    #[must_use]
+    pub fn alias_table(&self) -> &HashMap<Alias, PathBuf> {
+        &self.alias_table
+    }
+
+    /// The file wikilinks to the alias resolve to
+    #[must_use]
+    pub fn resolve(&self, alias: &Alias) -> Option<&PathBuf> {
+        self.alias_table.get(alias)
+    }
+
+    /// Every alias as a whole word in `text`, ignoring ascii case
+    /// With `inflections`, plurals and possessives are whole words too, see
+    /// [`crate::config::Config::unlinked_text_inflections`]
+    #[must_use]
+    pub fn mentions(&self, text: &str, inflections: bool) -> Vec<Mention> {
+        self.matcher.find(text, inflections)
+    }
+
+    /// [`AliasIndex::mentions`] of the aliases which may be linked automatically, those not
+    /// defined by a file with [`crate::file::content::front_matter::YamlFrontMatter::no_autolink`]
+    #[must_use]
+    pub fn autolink_mentions(&self, text: &str, inflections: bool) -> Vec<Mention> {
+        self.autolink_matcher.find(text, inflections)
+    }
+
+    /// The occurrences in a file, empty if it hasn't been indexed
+    #[must_use]
+    pub fn in_file(&self, file: &Path) -> &[Occurrence] {
+        self.occurrences.get(file).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every occurrence of the alias, by file
+    pub fn occurrences<'a>(&'a self, alias: &'a Alias) -> impl Iterator<Item = &'a Occurrence> {
+        self.occurrences
+            .values()
+            .flatten()
+            .filter(move |occurrence| &occurrence.alias == alias)
+    }
+
+    /// Every definition of an alias, by file
+    pub fn definitions(&self) -> impl Iterator<Item = &Occurrence> {
+        self.occurrences
+            .values()
+            .flatten()
+            .filter(|occurrence| matches!(occurrence.kind, OccurrenceKind::Definition(_)))
+    }
+
+    /// The wikilinks and tags which resolve to `file`, including those in the file itself
+    pub fn links_to<'a>(&'a self, file: &'a Path) -> impl Iterator<Item = &'a Occurrence> {
+        self.occurrences
+            .values()
+            .flatten()
+            .filter(move |occurrence| {
+                matches!(
+                    occurrence.kind,
+                    OccurrenceKind::Wikilink | OccurrenceKind::Tag
+                ) && self.resolve(&occurrence.alias).map(PathBuf::as_path) == Some(file)
+            })
+    }
+
+    /// Add the occurrences found in a file after its definitions
+    fn insert(&mut self, file: &Path, mut occurrences: Vec<Occurrence>) {
+        occurrences.sort_by_key(|occurrence| occurrence.range.as_ref().map(|range| range.start));
+        self.occurrences
+            .entry(file.to_path_buf())
+            .or_default()
+            .extend(occurrences);
+    }
+}
+
+/// Adds the wikilinks, tags and mentions of each file to a shared [`AliasIndex`]
+/// Visitors which read a file's occurrences from the index in [`Visitor::finalize_file`] have
+/// to come after this one
+#[derive(Debug)]
+pub struct AliasIndexVisitor {
+    pub index: Rc<RefCell<AliasIndex>>,
+    /// See [`crate::config::Config::skip_html`]
+    skip_html: bool,
+    /// See [`crate::config::Config::unlinked_text_inflections`]
+    inflections: bool,
+    wikilinks_visitor: WikilinkVisitor,
+    /// The aliases mentioned in the current file, and where
+    mentions: Vec<(Alias, Range<usize>)>,
+}
+
+impl AliasIndexVisitor {
+    #[must_use]
+    pub fn new(index: Rc<RefCell<AliasIndex>>, skip_html: bool, inflections: bool) -> Self {
+        Self {
+            index,
+            skip_html,
+            inflections,
+            wikilinks_visitor: WikilinkVisitor::new(skip_html),
+            mentions: Vec::new(),
+        }
+    }
+}
+
+impl Visitor for AliasIndexVisitor {
+    fn name(&self) -> &'static str {
+        "AliasIndexVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        if self.skip_html && is_html(node) {
+            return Ok(());
+        }
+        let data_ref = node.data.borrow();
+        let NodeValue::Text(text) = &data_ref.value else {
+            return Ok(());
+        };
+        let in_wikilink = node
+            .parent()
+            .is_some_and(|parent| matches!(parent.data.borrow().value, NodeValue::WikiLink(_)));
+        if in_wikilink {
+            return Ok(());
+        }
+        let in_table = is_in_table(node);
+        let start = source.start(data_ref.sourcepos);
+        for mention in self.index.borrow().mentions(text, self.inflections) {
+            let range = if in_table {
+                cell_offset(text, mention.range.start)..cell_offset(text, mention.range.end)
+            } else {
+                mention.range
+            };
+            self.mentions
+                .push((mention.alias, start + range.start..start + range.end));
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let links = std::mem::take(&mut self.wikilinks_visitor.wikilinks)
+            .into_iter()
+            .map(|wikilink| {
+                let kind = if wikilink.tag {
+                    OccurrenceKind::Tag
+                } else {
+                    OccurrenceKind::Wikilink
+                };
+                let start = wikilink.span.offset();
+                (wikilink.alias, kind, start..start + wikilink.span.len())
+            });
+        let mentions = std::mem::take(&mut self.mentions)
+            .into_iter()
+            .map(|(alias, range)| (alias, OccurrenceKind::Mention, range));
+        let occurrences = links
+            .chain(mentions)
+            .map(|(alias, kind, range)| Occurrence {
+                alias,
+                file: path.to_path_buf(),
+                kind,
+                range: Some(range),
+            })
+            .collect();
+        self.index.borrow_mut().insert(path, occurrences);
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+    fn _finalize(&mut self, _excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
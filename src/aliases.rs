@@ -0,0 +1,101 @@
+//! Every alias and the files which define it, see `mdlinker aliases`
+use std::{
+    fmt::{Display, Formatter, Write},
+    path::PathBuf,
+};
+
+use hashbrown::{HashMap, HashSet};
+use serde::Serialize;
+
+use crate::file::content::wikilink::Alias;
+
+/// Where a file defines an alias
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AliasSource {
+    /// The file is named after the alias, see [`crate::config::Config::filename_to_alias`]
+    Filename,
+    /// The alias is in the front matter of the file
+    FrontMatter,
+}
+
+impl Display for AliasSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Filename => write!(f, "filename"),
+            Self::FrontMatter => write!(f, "front_matter"),
+        }
+    }
+}
+
+/// A file which defines an alias
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AliasEntry {
+    pub alias: Alias,
+    pub file: PathBuf,
+    pub source: AliasSource,
+    /// Wikilinks to the alias resolve to this file, only one file is resolved per alias
+    pub resolved: bool,
+    /// Other files also define the alias, see [`crate::rules::duplicate_alias::DuplicateAlias`]
+    pub duplicate: bool,
+}
+
+/// Every alias definition, sorted by alias, then file
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct Aliases(pub Vec<AliasEntry>);
+
+impl Aliases {
+    /// Flags the `definitions` found by
+    /// [`crate::rules::duplicate_alias::DuplicateAliasVisitor`] against the final `alias_table`
+    #[must_use]
+    pub fn new(
+        definitions: &[(Alias, PathBuf, AliasSource)],
+        alias_table: &HashMap<Alias, PathBuf>,
+    ) -> Self {
+        let mut files: HashMap<&Alias, HashSet<&PathBuf>> = HashMap::new();
+        for (alias, file, _) in definitions {
+            files.entry(alias).or_default().insert(file);
+        }
+        let mut entries: Vec<AliasEntry> = definitions
+            .iter()
+            .map(|(alias, file, source)| AliasEntry {
+                alias: alias.clone(),
+                file: file.clone(),
+                source: *source,
+                resolved: alias_table.get(alias) == Some(file),
+                duplicate: files.get(alias).is_some_and(|files| files.len() > 1),
+            })
+            .collect();
+        entries.sort();
+        entries.dedup();
+        Self(entries)
+    }
+
+    /// Serialize as pretty printed json
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails, which it shouldn't
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// One line per definition, with a header
+    #[must_use]
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("alias\tfile\tsource\tresolved\tduplicate\n");
+        for entry in &self.0 {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                entry.alias,
+                entry.file.display(),
+                entry.source,
+                entry.resolved,
+                entry.duplicate,
+            );
+        }
+        out
+    }
+}
@@ -0,0 +1,130 @@
+//! An index of the pages linking to each page, see `mdlinker backlinks`
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashMap;
+use serde::Serialize;
+
+use crate::{
+    file::content::{
+        line_column,
+        source_map::SourceMap,
+        wikilink::{Alias, WikilinkVisitor},
+    },
+    rules::ErrorCode,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// A wikilink (or tag) in one page which links to another
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Backlink {
+    /// The file containing the wikilink
+    pub source: PathBuf,
+    /// The alias in the wikilink, lowercase
+    pub alias: String,
+    /// The byte offset of the wikilink in the file
+    pub offset: usize,
+    /// The length of the wikilink in bytes
+    pub length: usize,
+    /// The line of the wikilink, starting from 1
+    pub line: usize,
+    /// The column of the wikilink in characters, starting from 1
+    pub column: usize,
+}
+
+/// Every page, and the wikilinks to it from other pages
+/// Pages nothing links to have an empty list
+#[derive(Serialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct Backlinks(pub BTreeMap<PathBuf, Vec<Backlink>>);
+
+impl Backlinks {
+    /// The backlinks of a page
+    #[must_use]
+    pub fn get(&self, page: &Path) -> Option<&Vec<Backlink>> {
+        self.0.get(page)
+    }
+
+    /// Serialize as pretty printed json
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails, which it shouldn't
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct BacklinkVisitor {
+    pub alias_table: HashMap<Alias, PathBuf>,
+    pub wikilinks_visitor: WikilinkVisitor,
+    pub backlinks: Backlinks,
+}
+
+impl BacklinkVisitor {
+    #[must_use]
+    pub fn new(
+        all_files: &[PathBuf],
+        alias_table: HashMap<Alias, PathBuf>,
+        skip_html: bool,
+    ) -> Self {
+        Self {
+            alias_table,
+            wikilinks_visitor: WikilinkVisitor::new(skip_html),
+            backlinks: Backlinks(
+                all_files
+                    .iter()
+                    .map(|file| (file.clone(), Vec::new()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Visitor for BacklinkVisitor {
+    fn name(&self) -> &'static str {
+        "BacklinkVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        for wikilink in &self.wikilinks_visitor.wikilinks {
+            // Broken wikilinks have nothing to link back to, and pages don't backlink themselves
+            let Some(target) = self.alias_table.get(&wikilink.alias) else {
+                continue;
+            };
+            if target == path {
+                continue;
+            }
+            let (line, column) = line_column(source, wikilink.span.offset());
+            self.backlinks
+                .0
+                .entry(target.clone())
+                .or_default()
+                .push(Backlink {
+                    source: path.to_path_buf(),
+                    alias: wikilink.alias.to_string(),
+                    offset: wikilink.span.offset(),
+                    length: wikilink.span.len(),
+                    line,
+                    column,
+                });
+        }
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+    fn _finalize(&mut self, _excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
@@ -0,0 +1,283 @@
+//! How long each rule may run, see [`crate::config::Config::rule_timeout`], and how much file
+//! content the reports may keep, see [`SourceBudget`]
+use std::{
+    cell::RefCell,
+    path::Path,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    file::content::source_map::SourceMap,
+    rules::{ErrorCode, KeepsSource},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// The time a rule has spent, and how much it may
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budget {
+    /// [`None`] if the rule may run until it is done
+    timeout: Option<Duration>,
+    spent: Duration,
+    timed_out: bool,
+}
+
+impl Budget {
+    #[must_use]
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            spent: Duration::ZERO,
+            timed_out: false,
+        }
+    }
+
+    /// When a unit of work starts, to [`Budget::charge`] it
+    /// [`None`] if the rule may run until it is done, so the clock is never read, as reading it
+    /// panics on targets without one, like `wasm32-unknown-unknown`
+    #[must_use]
+    pub fn start(&self) -> Option<Instant> {
+        self.timeout.map(|_| Instant::now())
+    }
+
+    /// Count the time since `started`, from [`Budget::start`], as spent
+    pub fn charge(&mut self, started: Option<Instant>) {
+        if let Some(started) = started {
+            self.spent += started.elapsed();
+        }
+    }
+
+    /// Whether there is time left for more work, call it before each unit of work, like a file
+    /// If not, the rule timed out and the work is skipped
+    pub fn has_time(&mut self) -> bool {
+        if self.timeout.is_some_and(|timeout| self.spent > timeout) {
+            self.timed_out = true;
+        }
+        !self.timed_out
+    }
+
+    /// Whether work was skipped, so the results of the rule are partial
+    #[must_use]
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// The [`TimedOut`] note of the rule, if it timed out
+    #[must_use]
+    pub fn note(&self, rule: &str) -> Option<TimedOut> {
+        let timeout = self.timeout.filter(|_| self.timed_out)?;
+        Some(TimedOut {
+            rule: rule.to_owned(),
+            timeout,
+        })
+    }
+}
+
+/// A rule which ran out of time and was stopped, so it may have missed some reports
+#[derive(Error, Diagnostic, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[error("The rule {rule} timed out after {}s, its results are partial", timeout.as_secs())]
+#[diagnostic(
+    code(rule::timed_out),
+    severity(Warning),
+    help("Raise or unset `rule_timeout` to get every report of the rule")
+)]
+pub struct TimedOut {
+    pub rule: String,
+    pub timeout: Duration,
+}
+
+/// How much file content the reports of a run keep, see
+/// [`crate::config::Config::source_memory_limit`]
+///
+/// Each report keeps a copy of its file to show its snippet, so a vault with very many reports
+/// could run out of memory. The visitors give each report to the budget as they make it, and
+/// once the limit is reached, the next reports are kept without it, see
+/// [`KeepsSource::elide_source`]
+#[derive(Debug, Clone, Copy)]
+pub struct SourceBudget {
+    /// In bytes
+    limit: usize,
+    kept: usize,
+    elided: usize,
+}
+
+impl SourceBudget {
+    /// A budget of `megabytes`
+    #[must_use]
+    pub fn new(megabytes: usize) -> Self {
+        Self::bytes(megabytes.saturating_mul(1024 * 1024))
+    }
+
+    #[must_use]
+    pub fn bytes(limit: usize) -> Self {
+        Self {
+            limit,
+            kept: 0,
+            elided: 0,
+        }
+    }
+
+    /// The report, without its source if keeping it would go over the limit
+    /// Call it as soon as the report is made, so only the kept sources stay in memory
+    #[must_use]
+    pub fn keep<T: KeepsSource>(&mut self, mut report: T) -> T {
+        let len = report.source_len();
+        if self.kept.saturating_add(len) <= self.limit {
+            self.kept += len;
+            return report;
+        }
+        report.elide_source();
+        self.elided += 1;
+        report
+    }
+
+    /// How many reports were kept without their source
+    #[must_use]
+    pub fn elided(&self) -> usize {
+        self.elided
+    }
+}
+
+/// Every source is kept, for reports which are not part of a run, like those of
+/// [`crate::query`]
+impl Default for SourceBudget {
+    fn default() -> Self {
+        Self::bytes(usize::MAX)
+    }
+}
+
+/// Runs a visitor while its [`Budget`] lasts, then skips it for the rest of the files
+///
+/// Whole files are skipped, so the visitor never sees part of a file
+pub struct BudgetedVisitor {
+    visitor: Rc<RefCell<dyn Visitor>>,
+    /// The name of the rule, for [`TimedOut`]
+    rule: String,
+    budget: Budget,
+    /// Whether the current file is skipped, decided when it is first visited
+    skipping: Option<bool>,
+}
+
+impl BudgetedVisitor {
+    #[must_use]
+    pub fn new(visitor: Rc<RefCell<dyn Visitor>>, rule: &str, timeout: Option<Duration>) -> Self {
+        Self {
+            visitor,
+            rule: rule.to_owned(),
+            budget: Budget::new(timeout),
+            skipping: None,
+        }
+    }
+
+    /// See [`Budget::note`]
+    #[must_use]
+    pub fn note(&self) -> Option<TimedOut> {
+        self.budget.note(&self.rule)
+    }
+}
+
+impl Visitor for BudgetedVisitor {
+    fn name(&self) -> &'static str {
+        "BudgetedVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let budget = &mut self.budget;
+        if *self.skipping.get_or_insert_with(|| !budget.has_time()) {
+            return Ok(());
+        }
+        let started = self.budget.start();
+        let visited = self.visitor.borrow_mut().visit(node, source);
+        self.budget.charge(started);
+        visited
+    }
+    fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+        if self.skipping.take() == Some(true) {
+            return Ok(());
+        }
+        let started = self.budget.start();
+        let finalized = self.visitor.borrow_mut().finalize_file(source, path);
+        self.budget.charge(started);
+        finalized
+    }
+    fn _finalize(&mut self, exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        self.visitor.borrow_mut().finalize(exclude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use miette::{NamedSource, SourceSpan};
+
+    use crate::rules::{
+        code_wikilink::CodeWikilink, ErrorCode, Fingerprint, Report, ThirdPassReport,
+    };
+
+    use super::{Budget, SourceBudget};
+
+    #[test]
+    fn unlimited() {
+        let mut budget = Budget::new(None);
+        budget.charge(Some(Instant::now() - Duration::from_secs(60)));
+        assert_eq!(budget.start(), None);
+        assert!(budget.has_time());
+        assert!(!budget.timed_out());
+        assert_eq!(budget.note("Lorem"), None);
+    }
+
+    /// The first unit of work always runs, and the rule only times out if work was left
+    #[test]
+    fn spent() {
+        let mut budget = Budget::new(Some(Duration::from_secs(1)));
+        assert!(budget.has_time());
+        budget.charge(Some(Instant::now() - Duration::from_secs(2)));
+        assert!(!budget.timed_out());
+        assert!(!budget.has_time());
+        assert!(budget.timed_out());
+        let note = budget.note("Lorem").expect("It timed out");
+        assert_eq!(note.rule, "Lorem");
+        assert_eq!(
+            note.to_string(),
+            "The rule Lorem timed out after 1s, its results are partial"
+        );
+    }
+
+    fn report(source: &str) -> Report {
+        Report::ThirdPass(ThirdPassReport::CodeWikilink(
+            CodeWikilink::builder()
+                .id(ErrorCode::new("lorem".to_owned()))
+                .fingerprint(Fingerprint::default())
+                .wikilink("[[ipsum]]".to_owned())
+                .src(NamedSource::new("lorem.md", source.to_owned()))
+                .span(SourceSpan::from(0..9))
+                .advice(String::new())
+                .build(),
+        ))
+    }
+
+    #[test]
+    fn source_len() {
+        assert_eq!(report("`[[ipsum]]`\n- dolor\n").source_len(), 20);
+        assert_eq!(report("`[[ipsum]]`").without_source().source_len(), 0);
+    }
+
+    /// Sources are kept while they fit in the limit
+    #[test]
+    fn sources() {
+        let mut sources = SourceBudget::bytes(25);
+        assert_eq!(sources.keep(report("`[[ipsum]]` dolor")).source_len(), 17);
+        let elided = sources.keep(report("`[[ipsum]]`"));
+        assert_eq!(elided.source_len(), 0);
+        assert_eq!(elided.path().to_str(), Some("lorem.md"));
+        assert_eq!(elided.span(), Some(SourceSpan::from(0..9)));
+        // A smaller one still fits
+        assert_eq!(sources.keep(report("[[ipsum]")).source_len(), 8);
+        assert_eq!(sources.elided(), 1);
+    }
+}
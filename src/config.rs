@@ -1,18 +1,27 @@
 pub mod cli;
 pub mod file;
-use std::path::PathBuf;
+pub mod obsidian;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use crate::{
     file::{
+        case::fold,
         content::wikilink::Alias,
-        name::{Filename, FilenameLowercase},
+        name::{get_filename, Filename, FilenameLowercase},
+        provider::FileProvider,
     },
-    rules::{ErrorCode, ReportTrait},
+    rules::{ErrorCode, Exclude, ExcludeError, Report, ReportTrait},
     sed::{ReplacePair, ReplacePairCompilationError},
 };
 use bon::Builder;
 use clap::Parser;
-use miette::Diagnostic;
+use glob::Pattern;
+use miette::{Diagnostic, Severity};
+use serde::{Deserialize, Serialize};
 use std::io;
 use thiserror;
 use toml;
@@ -28,22 +37,504 @@ pub enum NewConfigError {
     FileDoesNotParseError(#[from] toml::de::Error),
     #[error("ReplacePair compilation error")]
     ReplacePairCompilationError(#[from] ReplacePairCompilationError),
+    #[error("The config file does not have expected values")]
+    YamlDoesNotParseError(#[source] serde_yaml::Error),
+    #[error("The config file does not have expected values")]
+    JsonDoesNotParseError(#[source] serde_json::Error),
+    #[error("The global config file at {path} does not have expected values")]
+    GlobalFileDoesNotParseError {
+        path: PathBuf,
+        #[source]
+        source: Box<NewConfigError>,
+    },
+    #[error("Failed to parse the obsidian settings at {path}")]
+    ObsidianParseError {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ExcludeError(#[from] ExcludeError),
     #[error("Pages directory missing")]
     #[help("Please provide a pages directory argument in either your cli or config file")]
     PagesDirectoryMissing,
 }
 
+/// Logseq's separator between levels of a hierarchy in filenames,
+/// the file `lorem___ipsum.md` is the page `[[lorem/ipsum]]`
+pub const LOGSEQ_HIERARCHY_SEPARATOR: &str = "___";
+
+/// The separator between levels of a hierarchy in wikilinks for a given separator in filenames
+///
+/// Logseq uses `/` in wikilinks, but others like Dendron (`lang.rust.traits.md` is the page
+/// `[[lang.rust.traits]]`) use the same separator in both
+fn wikilink_hierarchy_separator(hierarchy_separator: &str) -> &str {
+    if hierarchy_separator == LOGSEQ_HIERARCHY_SEPARATOR {
+        "/"
+    } else {
+        hierarchy_separator
+    }
+}
+
+/// Which path-style wikilinks, like `[[folder/note]]`, resolve to a file
+/// Aliases (including those derived from filenames) always resolve
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkResolution {
+    /// Only aliases resolve
+    #[default]
+    AliasOnly,
+    /// Any trailing part of a file's path resolves, like Obsidian's "shortest path when possible"
+    ShortestPath,
+    /// Paths relative to the directory of the file containing the link resolve
+    Relative,
+    /// Paths relative to [`Config::root_directory`] resolve
+    Absolute,
+}
+
+/// Which mentions of an alias in a file `--fix` wraps, when they are grouped into one
+/// [`crate::rules::unlinked_text::UnlinkedText`] by [`Config::group_unlinked_text`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlinkedTextFix {
+    /// Every mention
+    #[default]
+    All,
+    /// Only the first mention, as a page usually links a word once
+    First,
+}
+
+/// Only the first mention of an alias in each of these is reported as
+/// [`crate::rules::unlinked_text::UnlinkedText`], and none if it is linked before,
+/// as wikis usually link a term only where it is first mentioned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkOncePer {
+    File,
+    /// The text under a heading, up to the next heading
+    Section,
+    /// A paragraph, or a heading, which in Logseq is a single block
+    Paragraph,
+}
+
+/// How [`crate::rules::similar_filename::SimilarFilename`] scores two ngrams,
+/// compared against [`Config::filename_match_threshold`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityAlgorithm {
+    /// The fuzzy matcher of skim, which scores longer ngrams higher and has no maximum
+    #[default]
+    Skim,
+    /// The edit distance, from 0 to 100
+    Levenshtein,
+    /// Jaro-Winkler similarity, which favors a common prefix, from 0 to 100
+    JaroWinkler,
+    /// The best Levenshtein score between the words in common and each ngram,
+    /// so the order and repeats of words don't matter, from 0 to 100
+    TokenSet,
+}
+
+/// Front matter keys which mean the same thing, like `tag` and `tags`, only one of which
+/// should be used, see [`crate::rules::front_matter_key::FrontMatterKey`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySynonyms {
+    pub keys: Vec<String>,
+    /// The key the others are rewritten to, the one most files use if this is not set
+    #[serde(default)]
+    pub canonical: Option<String>,
+}
+
+impl KeySynonyms {
+    fn new(keys: &[&str]) -> Self {
+        Self {
+            keys: keys.iter().map(|key| (*key).to_owned()).collect(),
+            canonical: None,
+        }
+    }
+}
+
+/// Which kind of link to pages is used, see [`crate::rules::wrong_link_style::WrongLinkStyle`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStyle {
+    /// `[[page]]`, or `[[text|page]]`
+    Wikilink,
+    /// `[text](page.md)`
+    Markdown,
+}
+
+impl std::fmt::Display for LinkStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wikilink => write!(f, "wikilink"),
+            Self::Markdown => write!(f, "markdown link"),
+        }
+    }
+}
+
+/// Where files should be usable, see [`crate::rules::illegal_filename::IllegalFilename`]
+/// Linux only forbids `/`, which is never in a filename, so it is not one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    /// No `<>:"/\|?*` or control characters, no trailing spaces or dots,
+    /// and no reserved names like `CON` or `LPT1`
+    Windows,
+    /// No `:`, which Finder shows as `/`
+    Macos,
+    /// No `?` or `#`, which end the path of a url, like when a vault is published
+    Url,
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Windows => write!(f, "windows"),
+            Self::Macos => write!(f, "macos"),
+            Self::Url => write!(f, "url"),
+        }
+    }
+}
+
+/// Where [`crate::rules::broken_wikilink::BrokenWikilink`] creates the page for a broken
+/// wikilink in the files matching a glob, instead of [`Config::new_files_directory`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NewFilesDirectory {
+    /// Matched against the path of the file with the wikilink, relative to
+    /// [`Config::root_directory`], like `journals/**`
+    pub glob: String,
+    /// Next to the file with the wikilink if this is not set
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+}
+
+/// The number of pages which must link to each of the files matching a glob, see
+/// [`crate::rules::underlinked_page::UnderlinkedPage`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinInboundLinks {
+    /// Matched against the path of the file, relative to [`Config::root_directory`],
+    /// like `published/**`
+    pub glob: String,
+    /// Links from the file itself are not counted, nor more than one from the same file
+    pub min: usize,
+}
+
+/// How serious a report is, see [`SeverityOverride`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    /// Fails the run
+    Error,
+    Warning,
+    Advice,
+}
+
+impl From<Level> for Severity {
+    fn from(value: Level) -> Self {
+        match value {
+            Level::Error => Severity::Error,
+            Level::Warning => Severity::Warning,
+            Level::Advice => Severity::Advice,
+        }
+    }
+}
+
+/// The severity of the reports of a rule, in the files matching a glob, instead of the
+/// severity of the rule, see [`Config::severity`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeverityOverride {
+    /// A glob matched against the code of the rule, like `content::alias::unlinked`,
+    /// every rule if this is not set
+    #[serde(default)]
+    pub rule: Option<String>,
+    /// A glob matched against the path of the report, relative to [`Config::root_directory`],
+    /// like `published/**`, every file if this is not set
+    #[serde(default)]
+    pub path: Option<String>,
+    pub level: Level,
+}
+
+impl SeverityOverride {
+    fn matches(&self, rule: &str, path: &Path) -> bool {
+        let matches = |glob: &Option<String>, f: &dyn Fn(&Pattern) -> bool| {
+            glob.as_ref()
+                .is_none_or(|glob| Pattern::new(glob).is_ok_and(|pattern| f(&pattern)))
+        };
+        matches(&self.rule, &|pattern| pattern.matches(rule))
+            && matches(&self.path, &|pattern| pattern.matches_path(path))
+    }
+}
+
+/// What a rule does in a [`Namespace`], or what reports inside a completed task are, see
+/// [`Config::completed_tasks`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NamespaceRule {
+    /// Reports nothing
+    Off,
+    Error,
+    Warning,
+    Advice,
+}
+
+/// Rules for the pages of a namespace, like Logseq's `people___*.md` pages, see
+/// [`Config::namespace`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Namespace {
+    /// The namespace as written in filenames, like `people`, which is `people.md` and every
+    /// filename starting with it and [`Config::hierarchy_separator`], like `people___lorem.md`
+    pub prefix: String,
+    /// Keyed by a glob matched against the code of the rule, like `name::similar`
+    /// Reports about several files, like [`crate::rules::similar_filename::SimilarFilename`],
+    /// are only in the namespace if all their files are
+    #[serde(default)]
+    pub rules: BTreeMap<String, NamespaceRule>,
+}
+
+impl NamespaceRule {
+    /// [`None`] if it is [`NamespaceRule::Off`]
+    #[must_use]
+    pub fn severity(self) -> Option<Severity> {
+        match self {
+            Self::Off => None,
+            Self::Error => Some(Severity::Error),
+            Self::Warning => Some(Severity::Warning),
+            Self::Advice => Some(Severity::Advice),
+        }
+    }
+}
+
+impl Namespace {
+    fn contains(&self, path: &Path, hierarchy_separator: &str) -> bool {
+        let filename = get_filename(path).lowercase().0;
+        let prefix = fold(&self.prefix);
+        filename == prefix || filename.starts_with(&format!("{prefix}{hierarchy_separator}"))
+    }
+}
+
+/// The markdown extensions files are parsed with, like `[markdown]` and `footnotes = true`
+/// Wikilinks are always parsed, and the rest are off unless set, other than front matter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct MarkdownExtensions {
+    /// GitHub tables, like `| lorem | ipsum |`
+    pub table: bool,
+    /// Footnote references like `[^1]`, and their definitions like `[^1]: Lorem`, which are
+    /// otherwise text, and may be reported as unlinked text
+    pub footnotes: bool,
+    /// Task list items, like `- [ ] lorem` and `- [x] lorem`
+    pub tasklist: bool,
+    /// Struck through text, like `~~lorem~~`
+    pub strikethrough: bool,
+    /// Urls and emails which are not in a link, like `www.example.com`, are links
+    pub autolink: bool,
+    /// The line before and after the front matter, which is not parsed if this is empty
+    /// The front matter is read as yaml, whatever its delimiter
+    pub front_matter_delimiter: String,
+}
+
+impl Default for MarkdownExtensions {
+    fn default() -> Self {
+        Self {
+            table: false,
+            footnotes: false,
+            tasklist: false,
+            strikethrough: false,
+            autolink: false,
+            front_matter_delimiter: "---".to_owned(),
+        }
+    }
+}
+
+/// Where [`crate::rules::unlinked_text::UnlinkedText`] looks for unlinked text, like
+/// `[unlinked_text]` and `skip_callouts = true`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UnlinkedTextOptions {
+    /// Skip the text in blockquotes, like `> lorem`, callouts included
+    pub skip_blockquotes: bool,
+    /// Skip the text in Obsidian's callouts, like `> [!note]`, but not in other blockquotes
+    pub skip_callouts: bool,
+    /// Skip the text in tables, which are only parsed with
+    /// [`MarkdownExtensions::table`], as wikilinks widen their cells
+    pub skip_tables: bool,
+}
+
+/// A day, written like `2025-06-01`, see [`ExcludeEntry::Annotated::expires`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    pub year: i64,
+    pub month: u8,
+    pub day: u8,
+}
+
+/// A [`Date`] which is not like `2025-06-01`
+#[derive(thiserror::Error, Debug, Diagnostic)]
+#[error("{0:?} is not a date like 2025-06-01")]
+pub struct DateError(String);
+
+impl Date {
+    /// The day it is now, in UTC
+    #[must_use]
+    pub fn today() -> Self {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        Self::from_days(i64::try_from(seconds / 86_400).unwrap_or_default())
+    }
+
+    /// The day `days` after 1970-01-01, see <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn from_days(days: i64) -> Self {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u8;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = DateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || DateError(s.to_owned());
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(error);
+        let year = next()?.parse().map_err(|_| error())?;
+        let month = next()?.parse().map_err(|_| error())?;
+        let day = next()?.parse().map_err(|_| error())?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(error());
+        }
+        Ok(Self { year, month, day })
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = DateError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(value: Date) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// An entry of [`file::Config::exclude`], either just the pattern, like `"name::similar::*"`,
+/// or a table which says why it is there and until when, like
+/// `{ code = "name::similar::*", reason = "Renaming after the migration", expires = "2025-06-01" }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExcludeEntry {
+    Pattern(String),
+    Annotated {
+        /// The pattern, see [`Exclude`]
+        code: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+        /// The exclude still excludes after this day, but is reported by
+        /// [`crate::rules::expired_exclude::ExpiredExclude`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires: Option<Date>,
+    },
+}
+
+impl ExcludeEntry {
+    /// The pattern, see [`Exclude`]
+    #[must_use]
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Pattern(code) | Self::Annotated { code, .. } => code,
+        }
+    }
+
+    /// Why the exclude is there, if it says
+    #[must_use]
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            Self::Pattern(_) => None,
+            Self::Annotated { reason, .. } => reason.as_deref(),
+        }
+    }
+
+    /// See [`ExcludeEntry::Annotated::expires`]
+    #[must_use]
+    pub fn expires(&self) -> Option<Date> {
+        match self {
+            Self::Pattern(_) => None,
+            Self::Annotated { expires, .. } => *expires,
+        }
+    }
+
+    /// Whether the exclude expired before `today`
+    #[must_use]
+    pub fn is_expired(&self, today: Date) -> bool {
+        self.expires().is_some_and(|expires| expires < today)
+    }
+}
+
+impl From<String> for ExcludeEntry {
+    fn from(value: String) -> Self {
+        Self::Pattern(value)
+    }
+}
+
+/// The code of the rule of a report, like `name::similar`
+fn rule_code(report: &Report) -> String {
+    report
+        .diagnostic()
+        .code()
+        .map(|code| code.to_string())
+        .unwrap_or_default()
+}
+
+/// Whether `path` matches one of the `globs`, globs which don't compile match nothing
+fn matches_any(globs: &[String], path: &Path) -> bool {
+    globs
+        .iter()
+        .any(|glob| Pattern::new(glob).is_ok_and(|pattern| pattern.matches_path(path)))
+}
+
 /// Config which contains both the cli and the config file
 /// Used to reconcile the two
-#[derive(Builder)]
+#[derive(Builder, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
+    #[builder(default)]
     file_config: file::Config,
+    #[builder(default)]
     cli_config: cli::Config,
     /// See [`self::cli::Config::pages_directory`]
     pub pages_directory: PathBuf,
     /// See [`self::cli::Config::other_directories`]
     #[builder(default=vec![])]
     pub other_directories: Vec<PathBuf>,
+    /// See [`self::cli::Config::hierarchy_separator`]
+    #[builder(default = LOGSEQ_HIERARCHY_SEPARATOR.to_owned())]
+    pub hierarchy_separator: String,
     /// See [`self::cli::Config::ngram_size`]
     #[builder(default = 2)]
     pub ngram_size: usize,
@@ -53,6 +544,13 @@ pub struct Config {
     /// See [`self::cli::Config::filename_spacing_pattern`]
     #[builder(default=r"-|_|\s".to_owned())]
     pub filename_spacing_pattern: String,
+    /// See [`self::cli::Config::ngram_ignore_pattern`]
+    /// Defaults to numbers, so dates like `2024_11_01` are not similar to each other
+    #[builder(default=r"^\d+$".to_owned())]
+    pub ngram_ignore_pattern: String,
+    /// See [`self::file::Config::split_camel_case`]
+    #[builder(default = false)]
+    pub split_camel_case: bool,
     /// See [`self::cli::Config::filename_match_threshold`]
     #[builder(default = 100)]
     pub filename_match_threshold: i64,
@@ -68,15 +566,137 @@ pub struct Config {
     /// See [`self::cli::Config::fix`]
     #[builder(default = false)]
     pub fix: bool,
+    /// See [`self::cli::Config::dry_run`]
+    #[builder(default = false)]
+    pub dry_run: bool,
+    /// See [`self::cli::Config::verify`]
+    #[builder(default = false)]
+    pub verify: bool,
     /// See [`self::cli::Config::allow_dirty`]
     #[builder(default = false)]
     pub allow_dirty: bool,
+    /// See [`self::cli::Config::no_vcs`]
+    #[builder(default = false)]
+    pub no_vcs: bool,
+    /// See [`self::cli::Config::write_index`]
+    #[builder(default = false)]
+    pub write_index: bool,
+    /// See [`self::cli::Config::rule_timeout`]
+    pub rule_timeout: Option<Duration>,
+    /// See [`self::cli::Config::source_memory_limit`], in megabytes
+    #[builder(default = 512)]
+    pub source_memory_limit: usize,
     /// See [`self::file::Config::ignore_word_pairs`]
     #[builder(default = vec![])]
     pub ignore_word_pairs: Vec<(String, String)>,
     /// See [`self::cli::Config::ignore_remaining`]
+    /// [`None`] if remaining reports are not ignored
+    pub ignore_remaining: Option<ErrorCode>,
+    /// See [`self::file::Config::markdown`]
+    #[builder(default)]
+    pub markdown: MarkdownExtensions,
+    /// See [`self::file::Config::skip_html`]
+    #[builder(default = true)]
+    pub skip_html: bool,
+    /// See [`self::file::Config::unlinked_text`]
+    #[builder(default)]
+    pub unlinked_text: UnlinkedTextOptions,
+    /// The root of the vault, the directory of the config file
+    /// This is where `.obsidian` is looked for, see [`self::obsidian`]
+    #[builder(default = PathBuf::from("."))]
+    pub root_directory: PathBuf,
+    /// See [`self::file::Config::new_files_directory`]
+    pub new_files_directory: Option<PathBuf>,
+    /// See [`NewFilesDirectory`], the first which matches is used
+    #[builder(default = vec![])]
+    pub new_files_directories: Vec<NewFilesDirectory>,
+    /// See [`self::file::Config::new_file_template`]
+    pub new_file_template: Option<PathBuf>,
+    /// See [`self::file::Config::attachments_directory`]
+    pub attachments_directory: Option<PathBuf>,
+    /// See [`LinkResolution`]
+    #[builder(default)]
+    pub link_resolution: LinkResolution,
+    /// See [`self::file::Config::relref`]
+    #[builder(default = false)]
+    pub relref: bool,
+    /// See [`self::file::Config::code_wikilinks`]
+    #[builder(default = false)]
+    pub code_wikilinks: bool,
+    /// See [`self::file::Config::unlinked_text_inflections`]
+    #[builder(default = false)]
+    pub unlinked_text_inflections: bool,
+    /// See [`self::file::Config::group_unlinked_text`]
+    #[builder(default = false)]
+    pub group_unlinked_text: bool,
+    /// See [`UnlinkedTextFix`]
+    #[builder(default)]
+    pub unlinked_text_fix: UnlinkedTextFix,
+    /// See [`LinkOncePer`], every mention is reported if this is not set
+    pub link_once_per: Option<LinkOncePer>,
+    /// See [`SimilarityAlgorithm`]
+    #[builder(default)]
+    pub similarity_algorithm: SimilarityAlgorithm,
+    /// See [`self::file::Config::similar_filename_same_directory_only`]
     #[builder(default = false)]
-    pub ignore_remaining: bool,
+    pub similar_filename_same_directory_only: bool,
+    /// See [`SeverityOverride`], the last which matches a report is used
+    #[builder(default = vec![])]
+    pub severity: Vec<SeverityOverride>,
+    /// See [`Namespace`], the first which has a rule matching a report is used
+    #[builder(default = vec![])]
+    pub namespace: Vec<Namespace>,
+    /// See [`self::file::Config::completed_tasks`]
+    /// [`None`] if reports inside completed tasks are like any other
+    pub completed_tasks: Option<NamespaceRule>,
+    /// See [`self::file::Config::ignore_paths`]
+    #[builder(default = vec![])]
+    pub ignore_paths: Vec<String>,
+    /// See [`self::file::Config::allow_links_to_ignored`]
+    #[builder(default = vec![])]
+    pub allow_links_to_ignored: Vec<String>,
+    /// See [`self::file::Config::max_outbound_links`]
+    pub max_outbound_links: Option<usize>,
+    /// See [`MinInboundLinks`], the first which matches is used
+    #[builder(default = vec![])]
+    pub min_inbound_links: Vec<MinInboundLinks>,
+    /// See [`self::file::Config::key_synonyms`]
+    #[builder(default = vec![
+        KeySynonyms::new(&["tag", "tags"]),
+        KeySynonyms::new(&["alias", "aliases"]),
+        KeySynonyms::new(&["created", "date-created"]),
+    ])]
+    pub key_synonyms: Vec<KeySynonyms>,
+    /// See [`self::file::Config::link_style`]
+    pub link_style: Option<LinkStyle>,
+    /// See [`self::file::Config::filename_platforms`]
+    #[builder(default = vec![Platform::Windows, Platform::Macos, Platform::Url])]
+    pub filename_platforms: Vec<Platform>,
+    /// See [`self::file::Config::known_tags`]
+    #[builder(default = vec![])]
+    pub known_tags: Vec<String>,
+    /// See [`self::file::Config::tags_page`]
+    pub tags_page: Option<String>,
+    /// See [`self::cli::Config::command`]
+    pub command: Option<cli::Command>,
+    /// See [`self::cli::Config::color`]
+    #[builder(default)]
+    pub color: cli::ColorChoice,
+    /// See [`self::cli::Config::diff_base`]
+    pub diff_base: Option<String>,
+    /// See [`self::cli::Config::show_unused_excludes`]
+    #[builder(default)]
+    pub show_unused_excludes: bool,
+    /// See [`self::cli::Config::log_format`]
+    #[builder(default)]
+    pub log_format: cli::LogFormat,
+    /// See [`self::cli::Config::output_format`]
+    #[builder(default)]
+    pub output_format: cli::ReportFormat,
+    /// Whether the vault links with wikilinks, if not, text won't be suggested to be wrapped
+    /// in them by [`crate::rules::unlinked_text::UnlinkedText`]
+    #[builder(default = true)]
+    pub wikilinks: bool,
 }
 
 /// Things which implement the partial config trait
@@ -86,9 +706,12 @@ pub struct Config {
 pub trait Partial {
     fn pages_directory(&self) -> Option<PathBuf>;
     fn other_directories(&self) -> Option<Vec<PathBuf>>;
+    fn hierarchy_separator(&self) -> Option<String>;
     fn ngram_size(&self) -> Option<usize>;
     fn boundary_pattern(&self) -> Option<String>;
     fn filename_spacing_pattern(&self) -> Option<String>;
+    fn ngram_ignore_pattern(&self) -> Option<String>;
+    fn split_camel_case(&self) -> Option<bool>;
     fn filename_match_threshold(&self) -> Option<i64>;
     fn exclude(&self) -> Option<Vec<ErrorCode>>;
     fn filename_to_alias(
@@ -99,8 +722,43 @@ pub trait Partial {
     ) -> Option<Result<ReplacePair<Alias, FilenameLowercase>, ReplacePairCompilationError>>;
     fn fix(&self) -> Option<bool>;
     fn allow_dirty(&self) -> Option<bool>;
+    fn dry_run(&self) -> Option<bool>;
+    fn verify(&self) -> Option<bool>;
+    fn no_vcs(&self) -> Option<bool>;
+    fn write_index(&self) -> Option<bool>;
+    fn rule_timeout(&self) -> Option<u64>;
+    fn source_memory_limit(&self) -> Option<usize>;
     fn ignore_word_pairs(&self) -> Option<Vec<(String, String)>>;
-    fn ignore_remaining(&self) -> Option<bool>;
+    fn ignore_remaining(&self) -> Option<String>;
+    fn skip_html(&self) -> Option<bool>;
+    fn markdown(&self) -> Option<MarkdownExtensions>;
+    fn unlinked_text(&self) -> Option<UnlinkedTextOptions>;
+    fn new_files_directory(&self) -> Option<PathBuf>;
+    fn new_files_directories(&self) -> Option<Vec<NewFilesDirectory>>;
+    fn new_file_template(&self) -> Option<PathBuf>;
+    fn attachments_directory(&self) -> Option<PathBuf>;
+    fn link_resolution(&self) -> Option<LinkResolution>;
+    fn wikilinks(&self) -> Option<bool>;
+    fn relref(&self) -> Option<bool>;
+    fn code_wikilinks(&self) -> Option<bool>;
+    fn unlinked_text_inflections(&self) -> Option<bool>;
+    fn group_unlinked_text(&self) -> Option<bool>;
+    fn similar_filename_same_directory_only(&self) -> Option<bool>;
+    fn similarity_algorithm(&self) -> Option<SimilarityAlgorithm>;
+    fn unlinked_text_fix(&self) -> Option<UnlinkedTextFix>;
+    fn link_once_per(&self) -> Option<LinkOncePer>;
+    fn known_tags(&self) -> Option<Vec<String>>;
+    fn tags_page(&self) -> Option<String>;
+    fn ignore_paths(&self) -> Option<Vec<String>>;
+    fn allow_links_to_ignored(&self) -> Option<Vec<String>>;
+    fn max_outbound_links(&self) -> Option<usize>;
+    fn min_inbound_links(&self) -> Option<Vec<MinInboundLinks>>;
+    fn filename_platforms(&self) -> Option<Vec<Platform>>;
+    fn key_synonyms(&self) -> Option<Vec<KeySynonyms>>;
+    fn link_style(&self) -> Option<LinkStyle>;
+    fn namespace(&self) -> Option<Vec<Namespace>>;
+    fn severity(&self) -> Option<Vec<SeverityOverride>>;
+    fn completed_tasks(&self) -> Option<NamespaceRule>;
 }
 
 /// Now we implement a combine function for patrial configs which
@@ -108,30 +766,60 @@ pub trait Partial {
 /// config.
 ///
 /// Note: This makes last elements in the input slice first priority
-fn combine_partials(
+pub(crate) fn combine_partials(
+    root_directory: PathBuf,
+    obsidian_config: Option<&obsidian::Config>,
     file_config: &file::Config,
     cli_config: &cli::Config,
 ) -> Result<Config, NewConfigError> {
+    // The hierarchy separator provides the defaults for the patterns which deal with hierarchies
+    let hierarchy_separator = cli_config
+        .hierarchy_separator()
+        .or(file_config.hierarchy_separator())
+        .unwrap_or_else(|| LOGSEQ_HIERARCHY_SEPARATOR.to_owned());
+    let wikilink_separator = wikilink_hierarchy_separator(&hierarchy_separator);
+    let exclude = cli_config.exclude().or(file_config.exclude());
+    for exclude in exclude.iter().flatten() {
+        Exclude::new(exclude)?;
+    }
+    let ignore_remaining = cli_config
+        .ignore_remaining()
+        .or(file_config.ignore_remaining())
+        .map(ErrorCode);
+    if let Some(ignore_remaining) = &ignore_remaining {
+        Exclude::new(ignore_remaining)?;
+    }
     Ok(Config::builder()
         .file_config(file_config.clone())
         .cli_config(cli_config.clone())
         .maybe_ngram_size(cli_config.ngram_size().or(file_config.ngram_size()))
-        .maybe_boundary_pattern(
+        .boundary_pattern(
             cli_config
                 .boundary_pattern()
-                .or(file_config.boundary_pattern()),
+                .or(file_config.boundary_pattern())
+                .unwrap_or_else(|| regex::escape(&hierarchy_separator)),
         )
         .maybe_filename_spacing_pattern(
             cli_config
                 .filename_spacing_pattern()
                 .or(file_config.filename_spacing_pattern()),
         )
+        .maybe_ngram_ignore_pattern(
+            cli_config
+                .ngram_ignore_pattern()
+                .or(file_config.ngram_ignore_pattern()),
+        )
+        .maybe_split_camel_case(
+            cli_config
+                .split_camel_case()
+                .or(file_config.split_camel_case()),
+        )
         .maybe_filename_match_threshold(
             cli_config
                 .filename_match_threshold()
                 .or(file_config.filename_match_threshold()),
         )
-        .maybe_exclude(cli_config.exclude().or(file_config.exclude()))
+        .maybe_exclude(exclude)
         .maybe_filename_to_alias({
             match (
                 cli_config.filename_to_alias(),
@@ -143,7 +831,10 @@ fn combine_partials(
                 (_, Some(Err(e))) | (Some(Err(e)), _) => {
                     return Err(NewConfigError::ReplacePairCompilationError(e))
                 }
-                (None, None) => None,
+                (None, None) => Some(ReplacePair::new(
+                    &regex::escape(&hierarchy_separator),
+                    wikilink_separator,
+                )?),
             }
         })
         .maybe_alias_to_filename({
@@ -157,38 +848,151 @@ fn combine_partials(
                 (_, Some(Err(e))) | (Some(Err(e)), _) => {
                     return Err(NewConfigError::ReplacePairCompilationError(e))
                 }
-                (None, None) => None,
+                (None, None) => Some(ReplacePair::new(
+                    &regex::escape(wikilink_separator),
+                    &hierarchy_separator,
+                )?),
             }
         })
         .maybe_fix(cli_config.fix().or(file_config.fix()))
         .maybe_allow_dirty(cli_config.allow_dirty().or(file_config.allow_dirty()))
+        .maybe_dry_run(cli_config.dry_run().or(file_config.dry_run()))
+        .maybe_verify(cli_config.verify().or(file_config.verify()))
+        .maybe_no_vcs(cli_config.no_vcs().or(file_config.no_vcs()))
+        .maybe_write_index(cli_config.write_index().or(file_config.write_index()))
+        .maybe_rule_timeout(
+            cli_config
+                .rule_timeout()
+                .or(file_config.rule_timeout())
+                .map(Duration::from_secs),
+        )
+        .maybe_source_memory_limit(
+            cli_config
+                .source_memory_limit()
+                .or(file_config.source_memory_limit()),
+        )
         .pages_directory(
             cli_config
                 .pages_directory()
                 .or(file_config.pages_directory())
                 .expect("A default is set"),
         )
-        .maybe_other_directories(Some(
+        .maybe_other_directories(
             cli_config
                 .other_directories()
-                .or(file_config.other_directories())
-                .expect("A default is set"),
-        ))
+                .or(file_config.other_directories()),
+        )
         .maybe_ignore_word_pairs(
             cli_config
                 .ignore_word_pairs()
                 .or(file_config.ignore_word_pairs()),
         )
-        .maybe_ignore_remaining(
+        .maybe_ignore_remaining(ignore_remaining)
+        .hierarchy_separator(hierarchy_separator.clone())
+        .maybe_skip_html(cli_config.skip_html().or(file_config.skip_html()))
+        .maybe_markdown(cli_config.markdown().or(file_config.markdown()))
+        .maybe_unlinked_text(cli_config.unlinked_text().or(file_config.unlinked_text()))
+        .root_directory(root_directory)
+        .maybe_new_files_directory(
+            cli_config
+                .new_files_directory()
+                .or(file_config.new_files_directory())
+                .or(obsidian_config.and_then(Partial::new_files_directory)),
+        )
+        .maybe_new_files_directories(
+            cli_config
+                .new_files_directories()
+                .or(file_config.new_files_directories()),
+        )
+        .maybe_new_file_template(
+            cli_config
+                .new_file_template()
+                .or(file_config.new_file_template()),
+        )
+        .maybe_attachments_directory(
+            cli_config
+                .attachments_directory()
+                .or(file_config.attachments_directory())
+                .or(obsidian_config.and_then(Partial::attachments_directory)),
+        )
+        .maybe_link_resolution(
+            cli_config
+                .link_resolution()
+                .or(file_config.link_resolution())
+                .or(obsidian_config.and_then(Partial::link_resolution)),
+        )
+        .maybe_relref(cli_config.relref().or(file_config.relref()))
+        .maybe_code_wikilinks(cli_config.code_wikilinks().or(file_config.code_wikilinks()))
+        .maybe_unlinked_text_inflections(
+            cli_config
+                .unlinked_text_inflections()
+                .or(file_config.unlinked_text_inflections()),
+        )
+        .maybe_group_unlinked_text(
+            cli_config
+                .group_unlinked_text()
+                .or(file_config.group_unlinked_text()),
+        )
+        .maybe_unlinked_text_fix(
+            cli_config
+                .unlinked_text_fix()
+                .or(file_config.unlinked_text_fix()),
+        )
+        .maybe_link_once_per(cli_config.link_once_per().or(file_config.link_once_per()))
+        .maybe_similar_filename_same_directory_only(
+            cli_config
+                .similar_filename_same_directory_only()
+                .or(file_config.similar_filename_same_directory_only()),
+        )
+        .maybe_similarity_algorithm(
+            cli_config
+                .similarity_algorithm()
+                .or(file_config.similarity_algorithm()),
+        )
+        .maybe_known_tags(cli_config.known_tags().or(file_config.known_tags()))
+        .maybe_tags_page(cli_config.tags_page().or(file_config.tags_page()))
+        .maybe_ignore_paths(cli_config.ignore_paths().or(file_config.ignore_paths()))
+        .maybe_allow_links_to_ignored(
+            cli_config
+                .allow_links_to_ignored()
+                .or(file_config.allow_links_to_ignored()),
+        )
+        .maybe_max_outbound_links(
+            cli_config
+                .max_outbound_links()
+                .or(file_config.max_outbound_links()),
+        )
+        .maybe_min_inbound_links(
+            cli_config
+                .min_inbound_links()
+                .or(file_config.min_inbound_links()),
+        )
+        .maybe_filename_platforms(
             cli_config
-                .ignore_remaining()
-                .or(file_config.ignore_remaining()),
+                .filename_platforms()
+                .or(file_config.filename_platforms()),
+        )
+        .maybe_key_synonyms(cli_config.key_synonyms().or(file_config.key_synonyms()))
+        .maybe_link_style(cli_config.link_style().or(file_config.link_style()))
+        .maybe_severity(cli_config.severity().or(file_config.severity()))
+        .maybe_namespace(cli_config.namespace().or(file_config.namespace()))
+        .maybe_completed_tasks(
+            cli_config
+                .completed_tasks()
+                .or(file_config.completed_tasks()),
+        )
+        .maybe_wikilinks(
+            cli_config
+                .wikilinks()
+                .or(file_config.wikilinks())
+                .or(obsidian_config.and_then(Partial::wikilinks)),
         )
         .build())
 }
 
 impl Config {
-    /// Creates a new [`Config`] from the default location at "mdlinker.toml"
+    /// Creates a new [`Config`] from the default location at "mdlinker.toml", or
+    /// "mdlinker.yaml", "mdlinker.yml" or "mdlinker.json", see [`file::Format`]
     ///
     /// # Errors
     ///
@@ -197,11 +1001,12 @@ impl Config {
     ///     expected format
     ///
     pub fn new() -> Result<Self, NewConfigError> {
-        let cli = cli::Config::parse();
+        let mut cli = cli::Config::parse();
+        cli.config_path = file::find(&cli.config_path);
 
         // If the config file doesn't exist, and it's not the default, error out
         let file = if cli.config_path.is_file() {
-            match file::Config::new(&cli.config_path) {
+            match file::Config::with_global(&cli.config_path, file::global_path().as_deref()) {
                 Ok(file) => file,
                 Err(report) => Err(report)?,
             }
@@ -211,11 +1016,24 @@ impl Config {
             })?
         };
 
+        // The vault root is wherever the config file is
+        let root_directory = match cli.config_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let obsidian = obsidian::Config::new(&root_directory)?;
+
         // CLI has priority over file by being last
-        let mut out = combine_partials(&file, &cli);
+        let mut out = combine_partials(root_directory, obsidian.as_ref(), &file, &cli);
 
         // Match on a ref to out, so we do NOT move the config out of `out`
         if let Ok(ref mut config) = out {
+            config.command.clone_from(&cli.command);
+            config.log_format = cli.log_format;
+            config.output_format = cli.output_format;
+            config.color = cli.color.or(file.color).unwrap_or_default();
+            config.diff_base.clone_from(&cli.diff_base);
+            config.show_unused_excludes = cli.show_unused_excludes;
             config.cli_config = cli;
             config.file_config = file;
         }
@@ -224,6 +1042,15 @@ impl Config {
         out
     }
 
+    /// Get the files in the [`Self::attachments_directory`] from `files`
+    #[must_use]
+    pub fn attachments(&self, files: &dyn FileProvider) -> Vec<PathBuf> {
+        match &self.attachments_directory {
+            Some(directory) => files.files(std::slice::from_ref(directory)),
+            None => vec![],
+        }
+    }
+
     /// Legacy directories function
     /// Gets all the directories into one vec
     #[must_use]
@@ -234,29 +1061,421 @@ impl Config {
         out
     }
 
-    pub fn add_report_to_ignore(&mut self, report: &impl ReportTrait) {
+    /// The path relative to [`Config::root_directory`], or the path as it is if it is elsewhere
+    #[must_use]
+    pub fn relative_to_root<'a>(&self, path: &'a Path) -> &'a Path {
+        path.strip_prefix(&self.root_directory).unwrap_or(path)
+    }
+
+    /// Whether `path` matches one of [`Config::ignore_paths`], so it is not linted
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        matches_any(&self.ignore_paths, self.relative_to_root(path))
+    }
+
+    /// Whether every file of the report is in [`Config::ignore_paths`], so it is not reported
+    /// A report about several files, like two similar filenames, is kept if one of them is linted
+    #[must_use]
+    pub fn ignores_report(&self, report: &Report) -> bool {
+        report
+            .inner()
+            .paths()
+            .iter()
+            .all(|path| self.is_ignored(path))
+    }
+
+    /// The minimum number of pages which must link to `path`, see [`MinInboundLinks`]
+    #[must_use]
+    pub fn min_inbound_links(&self, path: &Path) -> Option<usize> {
+        let path = self.relative_to_root(path);
+        self.min_inbound_links
+            .iter()
+            .find(|min| Pattern::new(&min.glob).is_ok_and(|pattern| pattern.matches_path(path)))
+            .map(|min| min.min)
+    }
+
+    /// Whether wikilinks may point to `path`, which is when it is not one of
+    /// [`Config::ignore_paths`], or it is one of [`Config::allow_links_to_ignored`]
+    #[must_use]
+    pub fn may_link_to(&self, path: &Path) -> bool {
+        !self.is_ignored(path)
+            || matches_any(&self.allow_links_to_ignored, self.relative_to_root(path))
+    }
+
+    /// What the rule of the report does in the first of [`Config::namespace`] which has all
+    /// its files and a rule matching it
+    fn namespace_rule(&self, report: &Report) -> Option<NamespaceRule> {
+        let rule = rule_code(report);
+        let paths = report.inner().paths();
+        self.namespace
+            .iter()
+            .filter(|namespace| {
+                paths
+                    .iter()
+                    .all(|path| namespace.contains(path, &self.hierarchy_separator))
+            })
+            .find_map(|namespace| {
+                namespace
+                    .rules
+                    .iter()
+                    .find(|(glob, _)| Pattern::new(glob).is_ok_and(|glob| glob.matches(&rule)))
+                    .map(|(_, rule)| *rule)
+            })
+    }
+
+    /// Whether the rule of the report is off in its namespace, see [`Config::namespace`]
+    #[must_use]
+    pub fn is_disabled(&self, report: &Report) -> bool {
+        self.namespace_rule(report) == Some(NamespaceRule::Off)
+    }
+
+    /// The severity of the report, from the last of [`Config::severity`] which matches it,
+    /// or else its namespace's, see [`Config::namespace`], or else its rule's
+    #[must_use]
+    pub fn severity(&self, report: &Report) -> Severity {
+        let rule = rule_code(report);
+        let path = report.path();
+        let path = self.relative_to_root(&path);
+        let namespace_level = || self.namespace_rule(report)?.severity();
+        self.severity
+            .iter()
+            .rev()
+            .find(|severity| severity.matches(&rule, path))
+            .map(|severity| severity.level.into())
+            .or_else(namespace_level)
+            .unwrap_or_else(|| report.severity())
+    }
+
+    /// Whether [`Config::ignore_remaining`] matches the id of the report, or the code of its rule
+    #[must_use]
+    pub fn ignores_remaining(&self, report: &Report) -> bool {
+        let Some(exclude) = self
+            .ignore_remaining
+            .as_ref()
+            .and_then(|pattern| Exclude::new(pattern).ok())
+        else {
+            return false;
+        };
+        exclude.matches(&report.id())
+            || report
+                .diagnostic()
+                .code()
+                .is_some_and(|code| exclude.matches(&ErrorCode(code.to_string())))
+    }
+
+    /// See [`self::cli::Config::config_path`]
+    #[must_use]
+    pub fn config_path(&self) -> &Path {
+        &self.cli_config.config_path
+    }
+
+    /// The entries of [`file::Config::exclude`] which expired before `today`, see
+    /// [`ExcludeEntry::is_expired`]
+    pub fn expired_excludes(&self, today: Date) -> impl Iterator<Item = &ExcludeEntry> {
+        self.file_config
+            .exclude
+            .iter()
+            .filter(move |entry| entry.is_expired(today))
+    }
+
+    /// Whether any exclude expires, so the day is only needed then, see
+    /// [`Config::expired_excludes`]
+    #[must_use]
+    pub fn excludes_expire(&self) -> bool {
+        self.file_config
+            .exclude
+            .iter()
+            .any(|entry| entry.expires().is_some())
+    }
+
+    pub fn add_report_to_ignore(&mut self, report: &(impl ReportTrait + ?Sized)) {
         report.ignore(&mut self.file_config);
     }
 
+    /// Adds the [`file::Config::exclude`] and [`file::Config::ignore_word_pairs`] which are not in
+    /// the config file yet to the end of them, creating the file if needed
+    ///
+    /// The rest of the file, with its comments and formatting, is left as it is, unless it is
+    /// yaml or json, which are written again without their comments
+    ///
+    /// # Errors
+    ///
+    /// If the config file can't be read, written or parsed, or `exclude` or `ignore_word_pairs`
+    /// in it are not arrays
     pub fn save_config(&self) -> Result<(), SaveConfigError> {
-        let toml_str =
-            toml::to_string(&self.file_config).map_err(|e| SaveConfigError::Toml { source: e })?;
-        std::fs::write(self.cli_config.config_path.clone(), toml_str)
-            .map_err(|e| SaveConfigError::Io { source: e })?;
+        let path = &self.cli_config.config_path;
+        let format = file::Format::from_path(path);
+        if format != file::Format::Toml {
+            return self.save_config_rewrite(path, format);
+        }
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            // The whole config is written, as the pages directory is required
+            Err(e) if e.kind() == io::ErrorKind::NotFound => toml::to_string(&self.file_config)
+                .map_err(|source| SaveConfigError::Toml { source })?,
+            Err(source) => return Err(SaveConfigError::Io { source }),
+        };
+        let mut document: toml_edit::DocumentMut = source
+            .parse()
+            .map_err(|source| SaveConfigError::Parse { source })?;
+        let excludes = self.saved_excludes();
+        if !excludes.is_empty() {
+            let exclude = config_array(&mut document, "exclude")?;
+            for entry in excludes {
+                let saved = exclude.iter().any(|x| {
+                    x.as_str()
+                        .or_else(|| x.as_inline_table()?.get("code")?.as_str())
+                        == Some(entry.code())
+                });
+                if !saved {
+                    push_line(exclude, exclude_value(entry));
+                }
+            }
+        }
+        let word_pairs = self.saved_ignore_word_pairs();
+        if !word_pairs.is_empty() {
+            let ignore_word_pairs = config_array(&mut document, "ignore_word_pairs")?;
+            for (a, b) in word_pairs {
+                let saved = ignore_word_pairs.iter().any(|x| {
+                    x.as_array().is_some_and(|pair| {
+                        let pair: Vec<_> =
+                            pair.iter().filter_map(toml_edit::Value::as_str).collect();
+                        pair == [a.as_str(), b.as_str()]
+                    })
+                });
+                if !saved {
+                    push_line(
+                        ignore_word_pairs,
+                        toml_edit::Array::from_iter([a.as_str(), b.as_str()]),
+                    );
+                }
+            }
+        }
+        std::fs::write(path, document.to_string())
+            .map_err(|source| SaveConfigError::Io { source })?;
         Ok(())
     }
+
+    /// The excludes which are saved, those taken from the global config stay there
+    fn saved_excludes(&self) -> Vec<&ExcludeEntry> {
+        self.file_config
+            .exclude
+            .iter()
+            .filter(|entry| !self.file_config.is_global("exclude", *entry))
+            .collect()
+    }
+
+    /// The ignored word pairs which are saved, see [`Config::saved_excludes`]
+    fn saved_ignore_word_pairs(&self) -> Vec<&(String, String)> {
+        self.file_config
+            .ignore_word_pairs
+            .iter()
+            .filter(|pair| !self.file_config.is_global("ignore_word_pairs", *pair))
+            .collect()
+    }
+
+    /// [`Config::save_config`] for yaml and json, which are read and written again, keeping the
+    /// order of the keys but not the comments or formatting
+    fn save_config_rewrite(
+        &self,
+        path: &Path,
+        format: file::Format,
+    ) -> Result<(), SaveConfigError> {
+        let yaml = |source| SaveConfigError::Yaml { source };
+        let json = |source| SaveConfigError::Json { source };
+        let mut document: serde_yaml::Value = match std::fs::read_to_string(path) {
+            Ok(source) if format == file::Format::Yaml => {
+                serde_yaml::from_str(&source).map_err(yaml)?
+            }
+            Ok(source) => serde_json::from_str(&source).map_err(json)?,
+            // The whole config is written, as the pages directory is required
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                serde_yaml::to_value(&self.file_config).map_err(yaml)?
+            }
+            Err(source) => return Err(SaveConfigError::Io { source }),
+        };
+        let mapping = document
+            .as_mapping_mut()
+            .ok_or(SaveConfigError::NotATable)?;
+        let excludes = self.saved_excludes();
+        if !excludes.is_empty() {
+            let exclude = config_sequence(mapping, "exclude")?;
+            for entry in excludes {
+                let saved = exclude
+                    .iter()
+                    .any(|x| x.as_str().or_else(|| x.get("code")?.as_str()) == Some(entry.code()));
+                if !saved {
+                    exclude.push(serde_yaml::to_value(entry).map_err(yaml)?);
+                }
+            }
+        }
+        let word_pairs = self.saved_ignore_word_pairs();
+        if !word_pairs.is_empty() {
+            let ignore_word_pairs = config_sequence(mapping, "ignore_word_pairs")?;
+            for pair in word_pairs {
+                let pair = serde_yaml::to_value(pair).map_err(yaml)?;
+                if !ignore_word_pairs.contains(&pair) {
+                    ignore_word_pairs.push(pair);
+                }
+            }
+        }
+        let out = if format == file::Format::Yaml {
+            serde_yaml::to_string(&document).map_err(yaml)?
+        } else {
+            serde_json::to_string_pretty(&document).map_err(json)? + "\n"
+        };
+        std::fs::write(path, out).map_err(|source| SaveConfigError::Io { source })?;
+        Ok(())
+    }
+}
+
+/// The sequence at `key` in a yaml or json config file, an empty one is added if there is none
+fn config_sequence<'a>(
+    mapping: &'a mut serde_yaml::Mapping,
+    key: &'static str,
+) -> Result<&'a mut serde_yaml::Sequence, SaveConfigError> {
+    if !mapping.contains_key(key) {
+        mapping.insert(key.into(), serde_yaml::Value::Sequence(vec![]));
+    }
+    mapping
+        .get_mut(key)
+        .and_then(serde_yaml::Value::as_sequence_mut)
+        .ok_or(SaveConfigError::NotAnArray { key })
+}
+
+/// The array at `key` in the config file, an empty one is added if there is none
+fn config_array<'a>(
+    document: &'a mut toml_edit::DocumentMut,
+    key: &'static str,
+) -> Result<&'a mut toml_edit::Array, SaveConfigError> {
+    document
+        .entry(key)
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .ok_or(SaveConfigError::NotAnArray { key })
+}
+
+/// The entry as written in the config file, a string or an inline table
+fn exclude_value(entry: &ExcludeEntry) -> toml_edit::Value {
+    match entry {
+        ExcludeEntry::Pattern(code) => code.as_str().into(),
+        ExcludeEntry::Annotated {
+            code,
+            reason,
+            expires,
+        } => {
+            let mut table = toml_edit::InlineTable::new();
+            table.insert("code", code.as_str().into());
+            if let Some(reason) = reason {
+                table.insert("reason", reason.as_str().into());
+            }
+            if let Some(expires) = expires {
+                table.insert("expires", expires.to_string().into());
+            }
+            table.into()
+        }
+    }
+}
+
+/// Push `value` to `array`, on its own line if the array is written one value per line
+/// A comment after the last value stays with it
+fn push_line(array: &mut toml_edit::Array, value: impl Into<toml_edit::Value>) {
+    let mut value = value.into();
+    let trailing = array.trailing().as_str().unwrap_or_default().to_owned();
+    if trailing.contains('\n') {
+        let indent = array
+            .iter()
+            .last()
+            .and_then(|last| last.decor().prefix()?.as_str())
+            .and_then(|prefix| prefix.rsplit('\n').next())
+            .unwrap_or("    ")
+            .to_owned();
+        value
+            .decor_mut()
+            .set_prefix(format!("{}\n{indent}", trailing.trim_end()));
+        array.set_trailing("\n");
+        array.set_trailing_comma(true);
+    }
+    array.push_formatted(value);
 }
 
 #[derive(thiserror::Error, Debug, Diagnostic)]
 pub enum SaveConfigError {
     #[error(transparent)]
     Io {
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: io::Error,
     },
     #[error(transparent)]
     Toml {
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: toml::ser::Error,
     },
+    #[error(transparent)]
+    Parse {
+        #[cfg_attr(feature = "nightly", backtrace)]
+        source: toml_edit::TomlError,
+    },
+    #[error(transparent)]
+    Yaml {
+        #[cfg_attr(feature = "nightly", backtrace)]
+        source: serde_yaml::Error,
+    },
+    #[error(transparent)]
+    Json {
+        #[cfg_attr(feature = "nightly", backtrace)]
+        source: serde_json::Error,
+    },
+    #[error("{key} in the config file is not an array")]
+    NotAnArray { key: &'static str },
+    #[error("The config file is not a table of keys and values")]
+    NotATable,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::file::{content::wikilink::Alias, name::Filename};
+
+    use super::{cli, combine_partials, file};
+
+    #[test]
+    fn test_dendron_hierarchy_separator() {
+        let file_config = file::Config {
+            hierarchy_separator: Some(".".to_owned()),
+            ..Default::default()
+        };
+        let config = combine_partials(
+            PathBuf::from("."),
+            None,
+            &file_config,
+            &cli::Config::default(),
+        )
+        .expect("The separator is escaped");
+        assert_eq!(config.boundary_pattern, r"\.");
+        assert_eq!(
+            Alias::from_filename(
+                &Filename::new("lang.rust.traits"),
+                &config.filename_to_alias
+            ),
+            Alias::new("lang.rust.traits")
+        );
+    }
+
+    #[test]
+    fn test_logseq_hierarchy_separator() {
+        let config = combine_partials(
+            PathBuf::from("."),
+            None,
+            &file::Config::default(),
+            &cli::Config::default(),
+        )
+        .expect("The defaults are valid");
+        assert_eq!(config.boundary_pattern, "___");
+        assert_eq!(
+            Alias::from_filename(&Filename::new("lorem___ipsum"), &config.filename_to_alias),
+            Alias::new("lorem/ipsum")
+        );
+    }
 }
@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use crate::{
@@ -10,10 +11,15 @@ use crate::{
     sed::{ReplacePair, ReplacePairCompilationError},
 };
 
-use super::Partial;
+use super::{
+    KeySynonyms, LinkOncePer, LinkResolution, LinkStyle, MarkdownExtensions, MinInboundLinks,
+    Namespace, NamespaceRule, NewFilesDirectory, Partial, Platform, SeverityOverride,
+    SimilarityAlgorithm, UnlinkedTextFix, UnlinkedTextOptions,
+};
 
 #[derive(Parser, Default, Clone)]
 #[command(version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Config {
     /// The pages directory is the directory where pages are named for their alias
     /// and where new pages should be created when running --fix
@@ -29,6 +35,13 @@ pub struct Config {
     #[allow(clippy::struct_field_names)]
     pub config_path: PathBuf,
 
+    /// The separator between levels of a hierarchy in filenames
+    /// Logseq uses ___ (`lorem___ipsum.md` is `[[lorem/ipsum]]`) and
+    /// Dendron uses . (`lorem.ipsum.md` is `[[lorem.ipsum]]`)
+    /// Provides the defaults for the boundary pattern, and the filename to alias conversions
+    #[clap(long = "hierarchy")]
+    pub hierarchy_separator: Option<String>,
+
     /// Size of the n-grams to generate from filenames
     /// Will generate n-grams UP TO and INCLUDING this size
     #[clap(short = 'n', long = "ngram")]
@@ -42,13 +55,19 @@ pub struct Config {
     #[clap(short = 's', long = "space")]
     pub filename_spacing_pattern: Option<String>,
 
+    /// Regex pattern of words in filenames to leave out of n-grams, like dates or numbers
+    /// An n-gram with any of these words is not generated
+    #[clap(long = "ngram-ignore")]
+    pub ngram_ignore_pattern: Option<String>,
+
     /// The minimum score to consider a match for filename ngrams
     #[clap(short = 'm', long = "score")]
     pub filename_match_threshold: Option<i64>,
 
-    /// Exclude certain error codes
-    /// If an error code **starts with** this string, it will be excluded
-    /// This accepts glob patterns
+    /// Exclude certain error codes, ignoring case
+    /// A glob pattern like `content::wikilink::broken::*` has to match the whole error code,
+    /// `prefix:content::wikilink` excludes error codes which start with it, and `regex:<regex>`
+    /// those the regex matches in. The fingerprint of a report also excludes it
     #[clap(short = 'e', long = "exclude")]
     pub exclude: Vec<String>,
 
@@ -61,9 +80,217 @@ pub struct Config {
     #[clap(long = "allow-dirty")]
     pub allow_dirty: bool,
 
+    /// With --fix, print how many files the fixes would create, modify and delete, and how many
+    /// lines they would insert and delete, then put every file back like it was
+    /// The fixes are still made and undone, so this is as careful as --fix about the git repo
+    #[clap(long = "dry-run", requires = "fix")]
+    pub dry_run: bool,
+
+    /// With --fix, check the fixes are idempotent: no report which was fixed is found again,
+    /// and making the same fixes again changes nothing
+    /// Any fix which is not is reported as an error
+    #[clap(long = "verify", requires = "fix", conflicts_with = "dry_run")]
+    pub verify: bool,
+
+    /// Don't use git to check it is safe to fix, for notes which are not in a git repo
+    /// Instead, files are copied into a hidden `.mdlinker-backup` directory next to them before they are fixed
+    #[clap(long = "no-vcs")]
+    pub no_vcs: bool,
+
+    /// Write the aliases and links of every file to `.mdlinker/index.json` in the root directory
+    /// after linting, for other tools, and so `mdlinker links` can read it instead of parsing
+    /// every file while none has changed
+    #[clap(long = "write-index")]
+    pub write_index: bool,
+
+    /// Stop a rule which runs for longer than this many seconds, keeping what it found so far
+    /// A note says which rules timed out, so their results are known to be partial
+    #[clap(long = "rule-timeout", value_name = "SECS")]
+    pub rule_timeout: Option<u64>,
+
+    /// How many megabytes of file content the reports may keep for their snippets, 512 by default
+    /// Past it, the next reports keep only their file and span, and a note says how many did
+    #[clap(long = "source-memory-limit", value_name = "MB")]
+    pub source_memory_limit: Option<usize>,
+
     /// Ignore remaining errors by adding them to the config
-    #[clap(long = "ignore-remaining")]
-    pub ignore_remaining: bool,
+    /// Give it an exclude pattern, like `content::alias::unlinked*`, to only ignore the reports whose
+    /// error code or rule matches it, the others still fail the run
+    #[clap(long = "ignore-remaining", num_args = 0..=1, default_missing_value = "*")]
+    pub ignore_remaining: Option<String>,
+
+    /// Only reports on lines changed since this git ref, like `main`, fail the run
+    /// Other reports are still printed, so issues which were already there don't block a pull request
+    #[clap(long = "diff-base")]
+    pub diff_base: Option<String>,
+
+    /// After linting, warn about excludes and `ignore_word_pairs` which matched no report,
+    /// so they can be removed from the config once the issues are fixed
+    /// Only the rules which run are checked, so the excludes of rules which are off are listed
+    #[clap(long = "show-unused-excludes")]
+    pub show_unused_excludes: bool,
+
+    /// How log lines, which are turned on with `RUST_LOG`, are written to stderr
+    #[clap(long = "log-format", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+
+    /// How the reports are printed, `json` and `sarif` print them to stdout along with the edits
+    /// their fixes make, so editors and bots can make them without mdlinker
+    #[clap(long = "output-format", value_enum, default_value_t = ReportFormat::Text)]
+    pub output_format: ReportFormat,
+
+    /// Whether to color the output, `auto` colors it in a terminal unless `NO_COLOR` is set
+    /// Emojis are left out with `never`
+    /// Defaults to `auto`
+    #[clap(long = "color", value_enum)]
+    pub color: Option<ColorChoice>,
+
+    /// Instead of linting, run one of these commands
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands which use the same parsing as the linter, but output something else
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// For every page, print the wikilinks to it from other pages
+    Backlinks {
+        #[clap(long = "format", value_enum, default_value_t = BacklinkFormat::Json)]
+        format: BacklinkFormat,
+    },
+    /// Print every wikilink and tag, with the file it is in, where, and the file it resolves to
+    Links {
+        /// Only the links in this file
+        #[clap(long = "file")]
+        file: Option<PathBuf>,
+        #[clap(long = "format", value_enum, default_value_t = LinkFormat::Tsv)]
+        format: LinkFormat,
+    },
+    /// Print every alias, the files named after or declaring it, the file it resolves to, and
+    /// whether it is defined more than once
+    Aliases {
+        #[clap(long = "format", value_enum, default_value_t = AliasFormat::Tsv)]
+        format: AliasFormat,
+    },
+    /// Print where an alias is defined, and every wikilink, tag, and unlinked mention of it
+    Query {
+        /// The alias, case insensitive
+        alias: String,
+    },
+    /// Print the graph of pages (nodes) and the wikilinks and tags between them (edges)
+    Graph {
+        #[clap(long = "format", value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Label edges with the aliases used in the links
+        #[clap(long = "labels")]
+        labels: bool,
+        /// Only include pages in this namespace, like `lorem` or `lorem/ipsum`
+        #[clap(long = "namespace")]
+        namespace: Option<String>,
+    },
+    /// Create a page, named after its title with `alias_to_filename` and from the
+    /// `new_file_template`, where the broken wikilink fix would create it
+    New {
+        /// The title of the page
+        title: String,
+        /// Add a wikilink to the page to the end of this file, like today's journal,
+        /// which is created if it doesn't exist
+        #[clap(long = "link-from")]
+        link_from: Option<PathBuf>,
+    },
+    /// Merge the page `a` into the page `b`: add its body to the end of `b`, add its name and
+    /// aliases to the aliases of `b`, point the wikilinks and tags to `a` at `b`, then delete `a`
+    /// Like `--fix`, this refuses to run in a dirty git repo unless `--allow-dirty` is given
+    Merge {
+        /// The page to merge, which is deleted
+        a: PathBuf,
+        /// The page to merge it into
+        b: PathBuf,
+    },
+    /// Move a directory, and rewrite the path-style wikilinks and relative markdown links
+    /// into and out of it
+    /// Like `--fix`, this refuses to run in a dirty git repo unless `--allow-dirty` is given
+    Mv {
+        /// The directory to move
+        src: PathBuf,
+        /// Where to move it, which must not exist yet
+        dst: PathBuf,
+    },
+    /// Go through the reports in the terminal, with the source of each, to fix, ignore
+    /// (exclude in the config file) or edit them in `$EDITOR` one at a time
+    #[cfg(feature = "review")]
+    Review,
+    /// Print how the scores of similar filenames are distributed, and the pairs scored near
+    /// the threshold, to pick `filename_match_threshold`
+    TuneSimilarity {
+        /// Only score this many evenly spaced pairs of ngrams, for large vaults
+        #[clap(long = "max-pairs", default_value_t = 100_000)]
+        max_pairs: usize,
+    },
+}
+
+/// The formats of [`Config::log_format`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `tracing_subscriber`'s human readable format
+    #[default]
+    Text,
+    /// One json object per line, with the time, level, target, fields and spans
+    Json,
+}
+
+/// The formats of [`Config::output_format`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// miette's rendering of each report, to stderr
+    #[default]
+    Text,
+    /// A json array of the reports, see [`crate::export::ExportedReport`]
+    Json,
+    /// A SARIF 2.1.0 log
+    Sarif,
+}
+
+/// The choices of [`Config::color`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// The output formats of [`Command::Backlinks`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BacklinkFormat {
+    #[default]
+    Json,
+}
+
+/// The output formats of [`Command::Links`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkFormat {
+    #[default]
+    Tsv,
+    Json,
+}
+
+/// The output formats of [`Command::Aliases`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AliasFormat {
+    #[default]
+    Tsv,
+    Json,
+}
+
+/// The output formats of [`Command::Graph`]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Graphml,
+    Json,
 }
 
 impl Partial for Config {
@@ -78,6 +305,9 @@ impl Partial for Config {
             Some(out)
         }
     }
+    fn hierarchy_separator(&self) -> Option<String> {
+        self.hierarchy_separator.clone()
+    }
     fn ngram_size(&self) -> Option<usize> {
         self.ngram_size
     }
@@ -87,6 +317,12 @@ impl Partial for Config {
     fn filename_spacing_pattern(&self) -> Option<String> {
         self.filename_spacing_pattern.clone()
     }
+    fn ngram_ignore_pattern(&self) -> Option<String> {
+        self.ngram_ignore_pattern.clone()
+    }
+    fn split_camel_case(&self) -> Option<bool> {
+        None
+    }
     fn filename_match_threshold(&self) -> Option<i64> {
         self.filename_match_threshold
     }
@@ -114,10 +350,116 @@ impl Partial for Config {
     fn allow_dirty(&self) -> Option<bool> {
         Some(self.allow_dirty)
     }
+    fn dry_run(&self) -> Option<bool> {
+        Some(self.dry_run)
+    }
+    fn verify(&self) -> Option<bool> {
+        Some(self.verify)
+    }
+    /// Not passing the flag leaves it to the config file
+    fn no_vcs(&self) -> Option<bool> {
+        self.no_vcs.then_some(true)
+    }
+    fn write_index(&self) -> Option<bool> {
+        self.write_index.then_some(true)
+    }
+    fn rule_timeout(&self) -> Option<u64> {
+        self.rule_timeout
+    }
+    fn source_memory_limit(&self) -> Option<usize> {
+        self.source_memory_limit
+    }
     fn ignore_word_pairs(&self) -> Option<Vec<(String, String)>> {
         None
     }
-    fn ignore_remaining(&self) -> Option<bool> {
-        Some(self.ignore_remaining)
+    fn ignore_remaining(&self) -> Option<String> {
+        self.ignore_remaining.clone()
+    }
+    fn skip_html(&self) -> Option<bool> {
+        None
+    }
+    fn markdown(&self) -> Option<MarkdownExtensions> {
+        None
+    }
+    fn unlinked_text(&self) -> Option<UnlinkedTextOptions> {
+        None
+    }
+    fn new_files_directory(&self) -> Option<PathBuf> {
+        None
+    }
+    fn new_file_template(&self) -> Option<PathBuf> {
+        None
+    }
+    fn attachments_directory(&self) -> Option<PathBuf> {
+        None
+    }
+    fn link_resolution(&self) -> Option<LinkResolution> {
+        None
+    }
+    fn relref(&self) -> Option<bool> {
+        None
+    }
+    fn code_wikilinks(&self) -> Option<bool> {
+        None
+    }
+    fn unlinked_text_inflections(&self) -> Option<bool> {
+        None
+    }
+    fn group_unlinked_text(&self) -> Option<bool> {
+        None
+    }
+    fn unlinked_text_fix(&self) -> Option<UnlinkedTextFix> {
+        None
+    }
+    fn link_once_per(&self) -> Option<LinkOncePer> {
+        None
+    }
+    fn similar_filename_same_directory_only(&self) -> Option<bool> {
+        None
+    }
+    fn similarity_algorithm(&self) -> Option<SimilarityAlgorithm> {
+        None
+    }
+    fn known_tags(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn new_files_directories(&self) -> Option<Vec<NewFilesDirectory>> {
+        None
+    }
+    fn ignore_paths(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn allow_links_to_ignored(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn max_outbound_links(&self) -> Option<usize> {
+        None
+    }
+    fn min_inbound_links(&self) -> Option<Vec<MinInboundLinks>> {
+        None
+    }
+    fn filename_platforms(&self) -> Option<Vec<Platform>> {
+        None
+    }
+    fn key_synonyms(&self) -> Option<Vec<KeySynonyms>> {
+        None
+    }
+    fn link_style(&self) -> Option<LinkStyle> {
+        None
+    }
+    fn namespace(&self) -> Option<Vec<Namespace>> {
+        None
+    }
+    fn severity(&self) -> Option<Vec<SeverityOverride>> {
+        None
+    }
+    fn completed_tasks(&self) -> Option<NamespaceRule> {
+        None
+    }
+    fn tags_page(&self) -> Option<String> {
+        None
+    }
+    fn wikilinks(&self) -> Option<bool> {
+        None
     }
 }
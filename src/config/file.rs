@@ -11,7 +11,12 @@ use crate::{
     sed::{ReplacePair, ReplacePairCompilationError},
 };
 
-use super::{Config as MasterConfig, NewConfigError, Partial};
+use super::{
+    cli::ColorChoice, Config as MasterConfig, ExcludeEntry, KeySynonyms, LinkOncePer,
+    LinkResolution, LinkStyle, MarkdownExtensions, MinInboundLinks, Namespace, NamespaceRule,
+    NewConfigError, NewFilesDirectory, Partial, Platform, SeverityOverride, SimilarityAlgorithm,
+    UnlinkedTextFix, UnlinkedTextOptions,
+};
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct Config {
@@ -21,6 +26,10 @@ pub struct Config {
     /// See [`super::cli::Config::other_directories`]
     pub other_directories: Vec<PathBuf>,
 
+    /// See [`super::cli::Config::hierarchy_separator`]
+    #[serde(default)]
+    pub hierarchy_separator: Option<String>,
+
     /// See [`super::cli::Config::ngram_size`]
     #[serde(default)]
     pub ngram_size: Option<usize>,
@@ -33,13 +42,25 @@ pub struct Config {
     #[serde(default)]
     pub filename_spacing_pattern: Option<String>,
 
+    /// See [`super::cli::Config::ngram_ignore_pattern`]
+    #[serde(default)]
+    pub ngram_ignore_pattern: Option<String>,
+
+    /// Also split the words of filenames where their case changes and between letters and
+    /// digits, so `MyProjectNotes2024` has the words `my`, `project`, `notes` and `2024`,
+    /// see [`crate::ngrams::split_camel_case`]
+    /// Defaults to false
+    #[serde(default)]
+    pub split_camel_case: Option<bool>,
+
     /// See [`super::cli::Config::filename_match_threshold`]
     #[serde(default)]
     pub filename_match_threshold: Option<i64>,
 
     /// See [`super::cli::Config::exclude`]
+    /// Entries can also say why they are there and when they expire, see [`ExcludeEntry`]
     #[serde(default)]
-    pub exclude: Vec<String>,
+    pub exclude: Vec<ExcludeEntry>,
 
     /// In the [`crate::rules::similar_filename::SimilarFilename`] rule, ignore certain word pairs
     /// Prevents some annoying and frequent false positives
@@ -55,13 +76,318 @@ pub struct Config {
     /// Kinda like a sed command
     #[serde(default)]
     pub filename_to_alias: (String, String),
+
+    /// See [`MarkdownExtensions`], like
+    /// `markdown = { footnotes = true, tasklist = true }`, or a `[markdown]` table
+    #[serde(default)]
+    pub markdown: Option<MarkdownExtensions>,
+
+    /// Skip raw html (and MDX/JSX) when looking for tags and unlinked text,
+    /// including the text inside inline html elements like `<a>lorem</a>`
+    /// Defaults to true
+    #[serde(default)]
+    pub skip_html: Option<bool>,
+
+    /// See [`UnlinkedTextOptions`], like
+    /// `unlinked_text = { skip_callouts = true }`, or an `[unlinked_text]` table
+    #[serde(default)]
+    pub unlinked_text: Option<UnlinkedTextOptions>,
+
+    /// The directory where [`crate::rules::broken_wikilink::BrokenWikilink`] creates new pages
+    /// when running --fix
+    /// Defaults to the pages directory
+    #[serde(default)]
+    pub new_files_directory: Option<PathBuf>,
+
+    /// See [`NewFilesDirectory`], like
+    /// `new_files_directories = [{ glob = "journals/**", directory = "pages" }]`
+    /// The first which matches the file with the broken wikilink is used
+    #[serde(default)]
+    pub new_files_directories: Vec<NewFilesDirectory>,
+
+    /// A file whose content new pages are created with, by the
+    /// [`crate::rules::broken_wikilink::BrokenWikilink`] fix and `mdlinker new`,
+    /// with `{{title}}` replaced by the title of the page
+    /// New pages are empty without it
+    #[serde(default)]
+    pub new_file_template: Option<PathBuf>,
+
+    /// A directory of attachments, like images, which are not parsed as markdown
+    /// but can be linked to by their full file name, like `[[diagram.png]]`
+    #[serde(default)]
+    pub attachments_directory: Option<PathBuf>,
+
+    /// See [`LinkResolution`]
+    /// Defaults to Obsidian's "new link format" in `.obsidian/app.json`, or alias only
+    #[serde(default)]
+    pub link_resolution: Option<LinkResolution>,
+
+    /// Check the targets of static site links, Hugo's `{{< relref "post.md" >}}` and
+    /// `{{< ref "post.md" >}}` shortcodes and Zola's `[post](@/blog/post.md)`,
+    /// against the pages directory, which should be the site's content directory
+    /// Defaults to false
+    #[serde(default)]
+    pub relref: Option<bool>,
+
+    /// Report wikilinks inside inline code and code blocks, which don't render as links, see
+    /// [`crate::rules::code_wikilink::CodeWikilink`]
+    /// Defaults to false
+    #[serde(default)]
+    pub code_wikilinks: Option<bool>,
+
+    /// Also look for plurals and possessives of aliases as unlinked text, like `projects`,
+    /// `classes` and `project's` for the alias `project`
+    /// When fixed, only the alias is wrapped, like `[[project]]s`
+    /// Defaults to false
+    #[serde(default)]
+    pub unlinked_text_inflections: Option<bool>,
+
+    /// Report every mention of an alias in a file as one unlinked text, with a label on each
+    /// mention, instead of one report per mention
+    /// Defaults to false
+    #[serde(default)]
+    pub group_unlinked_text: Option<bool>,
+
+    /// See [`UnlinkedTextFix`]
+    /// Defaults to all
+    #[serde(default)]
+    pub unlinked_text_fix: Option<UnlinkedTextFix>,
+
+    /// See [`LinkOncePer`]
+    /// Defaults to reporting every mention
+    #[serde(default)]
+    pub link_once_per: Option<LinkOncePer>,
+
+    /// In the [`crate::rules::similar_filename::SimilarFilename`] rule, only compare files
+    /// in the same one of [`Config::pages_directory`] and [`Config::other_directories`],
+    /// so pages are not compared to journals
+    /// Defaults to false
+    #[serde(default)]
+    pub similar_filename_same_directory_only: Option<bool>,
+
+    /// See [`SimilarityAlgorithm`]
+    /// Defaults to skim
+    #[serde(default)]
+    pub similarity_algorithm: Option<SimilarityAlgorithm>,
+
+    /// The tags which may be used, like `#project`, without the `#`
+    /// Any other tag is reported by [`crate::rules::unknown_tag::UnknownTag`], which only runs
+    /// if this or [`Config::tags_page`] is set
+    #[serde(default)]
+    pub known_tags: Vec<String>,
+
+    /// The alias of a page whose tags and wikilinks are all known tags, see [`Config::known_tags`]
+    #[serde(default)]
+    pub tags_page: Option<String>,
+
+    /// Globs of files, relative to the root, which are read so wikilinks to them resolve, but are
+    /// not linted, like `templates/**` or `archive/**`
+    /// Wikilinks to them are reported by [`crate::rules::ignored_wikilink::IgnoredWikilink`]
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+
+    /// Globs of the files in [`Config::ignore_paths`] which may be linked to, like `templates/**`
+    #[serde(default)]
+    pub allow_links_to_ignored: Vec<String>,
+
+    /// Pages with more wikilinks to other pages than this are reported by
+    /// [`crate::rules::hub_page::HubPage`], which only runs if this is set
+    #[serde(default)]
+    pub max_outbound_links: Option<usize>,
+
+    /// See [`MinInboundLinks`], like `min_inbound_links = [{ glob = "published/**", min = 2 }]`
+    /// The first which matches a file is used, and
+    /// [`crate::rules::underlinked_page::UnderlinkedPage`] only runs if there are any
+    #[serde(default)]
+    pub min_inbound_links: Vec<MinInboundLinks>,
+
+    /// See [`KeySynonyms`], like `key_synonyms = [{ keys = ["tag", "tags"], canonical = "tags" }]`
+    /// `tag` and `tags`, `alias` and `aliases`, and `created` and `date-created` if this is empty
+    #[serde(default)]
+    pub key_synonyms: Vec<KeySynonyms>,
+
+    /// Links to pages written the other way, `"wikilink"` or `"markdown"`, are reported by
+    /// [`crate::rules::wrong_link_style::WrongLinkStyle`], which only runs if this is set
+    #[serde(default)]
+    pub link_style: Option<LinkStyle>,
+
+    /// The platforms filenames must work on, see [`Platform`], all of them if this is empty
+    /// Filenames which don't are reported by [`crate::rules::illegal_filename::IllegalFilename`]
+    #[serde(default)]
+    pub filename_platforms: Vec<Platform>,
+
+    /// See [`SeverityOverride`], like
+    /// `severity = [{ rule = "content::alias::unlinked", level = "warning" }]`
+    /// The last which matches a report is used, so later entries can be about fewer files
+    #[serde(default)]
+    pub severity: Vec<SeverityOverride>,
+
+    /// See [`Namespace`], like
+    /// `namespace = [{ prefix = "people", rules = { "name::similar" = "off" } }]`, or
+    /// `[[namespace]]` tables
+    /// The first which has all the files of a report, and a rule matching it, is used
+    #[serde(default)]
+    pub namespace: Vec<Namespace>,
+
+    /// What reports inside a completed task, like `- [x] Call [[Alice]]` or Logseq's
+    /// `- DONE Call [[Alice]]`, are: `"off"` to drop them, or a severity
+    /// Items nested under a completed task are inside it. Unset, they are like any other
+    #[serde(default)]
+    pub completed_tasks: Option<NamespaceRule>,
+
+    /// See [`super::cli::Config::no_vcs`]
+    #[serde(default)]
+    pub no_vcs: Option<bool>,
+
+    /// See [`super::cli::Config::write_index`]
+    #[serde(default)]
+    pub write_index: Option<bool>,
+
+    /// See [`super::cli::Config::rule_timeout`]
+    #[serde(default)]
+    pub rule_timeout: Option<u64>,
+
+    /// See [`super::cli::Config::source_memory_limit`]
+    #[serde(default)]
+    pub source_memory_limit: Option<usize>,
+
+    /// See [`super::cli::Config::color`]
+    #[serde(default)]
+    pub color: Option<ColorChoice>,
+
+    /// The keys taken from the global config by [`Config::with_global`], and their values there,
+    /// so saving the config doesn't write them to the vault's file, see
+    /// [`MasterConfig::save_config`]
+    #[serde(skip)]
+    pub global: toml::Table,
+}
+
+/// The formats a config file can be written in, known by its extension, all with the same keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    /// `.yaml` or `.yml`
+    Yaml,
+    Json,
+}
+
+impl Format {
+    /// Toml unless the extension is one of the others
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .as_deref()
+        {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// The keys and values of a config file in this format
+    fn parse(self, contents: &str) -> Result<toml::Table, NewConfigError> {
+        match self {
+            Self::Toml => toml::from_str(contents).map_err(NewConfigError::FileDoesNotParseError),
+            Self::Yaml => {
+                serde_yaml::from_str(contents).map_err(NewConfigError::YamlDoesNotParseError)
+            }
+            Self::Json => {
+                serde_json::from_str(contents).map_err(NewConfigError::JsonDoesNotParseError)
+            }
+        }
+    }
+}
+
+/// `path` if it exists, or else the same file in another [`Format`] if that exists, so
+/// `mdlinker.toml` finds `mdlinker.yaml`, `mdlinker.yml` or `mdlinker.json`
+#[must_use]
+pub fn find(path: &Path) -> PathBuf {
+    if path.is_file() {
+        return path.to_path_buf();
+    }
+    ["toml", "yaml", "yml", "json"]
+        .into_iter()
+        .map(|extension| path.with_extension(extension))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// The user's own config, `$XDG_CONFIG_HOME/mdlinker/config.toml`, or
+/// `~/.config/mdlinker/config.toml` if that isn't set, in any [`Format`], see
+/// [`Config::with_global`]
+#[must_use]
+pub fn global_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        // Relative paths are to be ignored, see https://specifications.freedesktop.org/basedir-spec/latest/
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")))?;
+    Some(find(&config_home.join("mdlinker").join("config.toml")))
+}
+
+/// The keys and values of the config file at `path`, in its [`Format`]
+fn read_table(path: &Path) -> Result<toml::Table, NewConfigError> {
+    let contents = std::fs::read_to_string(path).map_err(NewConfigError::FileDoesNotReadError)?;
+    Format::from_path(path).parse(&contents)
 }
 
 impl Config {
     pub fn new(path: &Path) -> Result<Self, NewConfigError> {
-        let contents =
-            std::fs::read_to_string(path).map_err(NewConfigError::FileDoesNotReadError)?;
-        toml::from_str(&contents).map_err(NewConfigError::FileDoesNotParseError)
+        Self::with_global(path, None)
+    }
+
+    /// Reads the config file at `path`, with the keys it doesn't have taken from the config at
+    /// `global`, if it exists, so personal defaults don't have to be in every vault
+    /// Either can be in any [`Format`]
+    ///
+    /// The [`Config::severity`] overrides of both are kept, those of `path` last so they win
+    pub fn with_global(path: &Path, global: Option<&Path>) -> Result<Self, NewConfigError> {
+        let mut table = read_table(path)?;
+        let mut global_keys = toml::Table::new();
+        if let Some(global) = global.filter(|global| global.is_file()) {
+            let global_table = read_table(global).map_err(|source| {
+                NewConfigError::GlobalFileDoesNotParseError {
+                    path: global.to_path_buf(),
+                    source: Box::new(source),
+                }
+            })?;
+            for (key, value) in global_table {
+                match (table.get_mut(&key), value) {
+                    (Some(toml::Value::Array(severity)), toml::Value::Array(mut global))
+                        if key == "severity" =>
+                    {
+                        global.append(severity);
+                        *severity = global;
+                    }
+                    (Some(_), _) => {}
+                    (None, value) => {
+                        global_keys.insert(key.clone(), value.clone());
+                        table.insert(key, value);
+                    }
+                }
+            }
+        }
+        let mut config: Self = table
+            .try_into()
+            .map_err(NewConfigError::FileDoesNotParseError)?;
+        config.global = global_keys;
+        Ok(config)
+    }
+
+    /// Whether `value` is one of the values of `key` taken from the global config, like an
+    /// exclude, see [`Config::global`]
+    #[must_use]
+    pub fn is_global<T: serde::de::DeserializeOwned + PartialEq>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> bool {
+        self.global
+            .get(key)
+            .and_then(|values| values.clone().try_into::<Vec<T>>().ok())
+            .is_some_and(|values| values.contains(value))
     }
 }
 
@@ -70,14 +396,55 @@ impl From<MasterConfig> for Config {
         Self {
             pages_directory: value.pages_directory,
             other_directories: value.other_directories,
+            hierarchy_separator: Some(value.hierarchy_separator),
             ngram_size: Some(value.ngram_size),
             boundary_pattern: Some(value.boundary_pattern),
             filename_spacing_pattern: Some(value.filename_spacing_pattern),
+            ngram_ignore_pattern: Some(value.ngram_ignore_pattern),
+            split_camel_case: Some(value.split_camel_case),
             filename_match_threshold: Some(value.filename_match_threshold),
-            exclude: value.exclude.into_iter().map(|x| x.0).collect(),
+            exclude: value
+                .exclude
+                .into_iter()
+                .map(|x| ExcludeEntry::Pattern(x.0))
+                .collect(),
             ignore_word_pairs: value.ignore_word_pairs,
             alias_to_filename: value.alias_to_filename.into(),
             filename_to_alias: value.filename_to_alias.into(),
+            skip_html: Some(value.skip_html),
+            markdown: Some(value.markdown),
+            unlinked_text: Some(value.unlinked_text),
+            new_files_directory: value.new_files_directory,
+            new_files_directories: value.new_files_directories,
+            new_file_template: value.new_file_template,
+            attachments_directory: value.attachments_directory,
+            link_resolution: Some(value.link_resolution),
+            relref: Some(value.relref),
+            code_wikilinks: Some(value.code_wikilinks),
+            unlinked_text_inflections: Some(value.unlinked_text_inflections),
+            group_unlinked_text: Some(value.group_unlinked_text),
+            unlinked_text_fix: Some(value.unlinked_text_fix),
+            link_once_per: value.link_once_per,
+            similar_filename_same_directory_only: Some(value.similar_filename_same_directory_only),
+            similarity_algorithm: Some(value.similarity_algorithm),
+            known_tags: value.known_tags,
+            tags_page: value.tags_page,
+            ignore_paths: value.ignore_paths,
+            allow_links_to_ignored: value.allow_links_to_ignored,
+            max_outbound_links: value.max_outbound_links,
+            min_inbound_links: value.min_inbound_links,
+            filename_platforms: value.filename_platforms,
+            key_synonyms: value.key_synonyms,
+            link_style: value.link_style,
+            severity: value.severity,
+            namespace: value.namespace,
+            completed_tasks: value.completed_tasks,
+            no_vcs: Some(value.no_vcs),
+            write_index: Some(value.write_index),
+            rule_timeout: value.rule_timeout.map(|timeout| timeout.as_secs()),
+            source_memory_limit: Some(value.source_memory_limit),
+            color: Some(value.color),
+            global: toml::Table::new(),
         }
     }
 }
@@ -95,6 +462,10 @@ impl Partial for Config {
         }
     }
 
+    fn hierarchy_separator(&self) -> Option<String> {
+        self.hierarchy_separator.clone()
+    }
+
     fn ngram_size(&self) -> Option<usize> {
         self.ngram_size
     }
@@ -107,16 +478,28 @@ impl Partial for Config {
         self.filename_spacing_pattern.clone()
     }
 
+    fn ngram_ignore_pattern(&self) -> Option<String> {
+        self.ngram_ignore_pattern.clone()
+    }
+
+    fn split_camel_case(&self) -> Option<bool> {
+        self.split_camel_case
+    }
+
     fn filename_match_threshold(&self) -> Option<i64> {
         self.filename_match_threshold
     }
 
     fn exclude(&self) -> Option<Vec<ErrorCode>> {
-        let out = self.exclude.clone();
-        if out.is_empty() {
+        if self.exclude.is_empty() {
             None
         } else {
-            Some(out.into_iter().map(ErrorCode::new).collect())
+            Some(
+                self.exclude
+                    .iter()
+                    .map(|entry| ErrorCode::new(entry.code().to_owned()))
+                    .collect(),
+            )
         }
     }
 
@@ -157,6 +540,24 @@ impl Partial for Config {
     fn allow_dirty(&self) -> Option<bool> {
         None
     }
+    fn dry_run(&self) -> Option<bool> {
+        None
+    }
+    fn verify(&self) -> Option<bool> {
+        None
+    }
+    fn no_vcs(&self) -> Option<bool> {
+        self.no_vcs
+    }
+    fn write_index(&self) -> Option<bool> {
+        self.write_index
+    }
+    fn rule_timeout(&self) -> Option<u64> {
+        self.rule_timeout
+    }
+    fn source_memory_limit(&self) -> Option<usize> {
+        self.source_memory_limit
+    }
     fn ignore_word_pairs(&self) -> Option<Vec<(String, String)>> {
         if self.ignore_word_pairs.is_empty() {
             None
@@ -165,7 +566,138 @@ impl Partial for Config {
         }
     }
 
-    fn ignore_remaining(&self) -> Option<bool> {
+    fn ignore_remaining(&self) -> Option<String> {
+        None
+    }
+
+    fn skip_html(&self) -> Option<bool> {
+        self.skip_html
+    }
+
+    fn markdown(&self) -> Option<MarkdownExtensions> {
+        self.markdown.clone()
+    }
+
+    fn unlinked_text(&self) -> Option<UnlinkedTextOptions> {
+        self.unlinked_text.clone()
+    }
+
+    fn new_files_directory(&self) -> Option<PathBuf> {
+        self.new_files_directory.clone()
+    }
+
+    fn new_file_template(&self) -> Option<PathBuf> {
+        self.new_file_template.clone()
+    }
+
+    fn attachments_directory(&self) -> Option<PathBuf> {
+        self.attachments_directory.clone()
+    }
+
+    fn link_resolution(&self) -> Option<LinkResolution> {
+        self.link_resolution
+    }
+
+    fn wikilinks(&self) -> Option<bool> {
         None
     }
+    fn relref(&self) -> Option<bool> {
+        self.relref
+    }
+    fn code_wikilinks(&self) -> Option<bool> {
+        self.code_wikilinks
+    }
+    fn unlinked_text_inflections(&self) -> Option<bool> {
+        self.unlinked_text_inflections
+    }
+    fn group_unlinked_text(&self) -> Option<bool> {
+        self.group_unlinked_text
+    }
+    fn unlinked_text_fix(&self) -> Option<UnlinkedTextFix> {
+        self.unlinked_text_fix
+    }
+    fn link_once_per(&self) -> Option<LinkOncePer> {
+        self.link_once_per
+    }
+    fn similar_filename_same_directory_only(&self) -> Option<bool> {
+        self.similar_filename_same_directory_only
+    }
+    fn similarity_algorithm(&self) -> Option<SimilarityAlgorithm> {
+        self.similarity_algorithm
+    }
+    fn known_tags(&self) -> Option<Vec<String>> {
+        if self.known_tags.is_empty() {
+            None
+        } else {
+            Some(self.known_tags.clone())
+        }
+    }
+    fn new_files_directories(&self) -> Option<Vec<NewFilesDirectory>> {
+        if self.new_files_directories.is_empty() {
+            None
+        } else {
+            Some(self.new_files_directories.clone())
+        }
+    }
+    fn ignore_paths(&self) -> Option<Vec<String>> {
+        if self.ignore_paths.is_empty() {
+            None
+        } else {
+            Some(self.ignore_paths.clone())
+        }
+    }
+    fn allow_links_to_ignored(&self) -> Option<Vec<String>> {
+        if self.allow_links_to_ignored.is_empty() {
+            None
+        } else {
+            Some(self.allow_links_to_ignored.clone())
+        }
+    }
+    fn max_outbound_links(&self) -> Option<usize> {
+        self.max_outbound_links
+    }
+    fn link_style(&self) -> Option<LinkStyle> {
+        self.link_style
+    }
+    fn key_synonyms(&self) -> Option<Vec<KeySynonyms>> {
+        if self.key_synonyms.is_empty() {
+            None
+        } else {
+            Some(self.key_synonyms.clone())
+        }
+    }
+    fn filename_platforms(&self) -> Option<Vec<Platform>> {
+        if self.filename_platforms.is_empty() {
+            None
+        } else {
+            Some(self.filename_platforms.clone())
+        }
+    }
+    fn min_inbound_links(&self) -> Option<Vec<MinInboundLinks>> {
+        if self.min_inbound_links.is_empty() {
+            None
+        } else {
+            Some(self.min_inbound_links.clone())
+        }
+    }
+    fn namespace(&self) -> Option<Vec<Namespace>> {
+        if self.namespace.is_empty() {
+            None
+        } else {
+            Some(self.namespace.clone())
+        }
+    }
+    fn completed_tasks(&self) -> Option<NamespaceRule> {
+        self.completed_tasks
+    }
+    fn severity(&self) -> Option<Vec<SeverityOverride>> {
+        if self.severity.is_empty() {
+            None
+        } else {
+            Some(self.severity.clone())
+        }
+    }
+    fn tags_page(&self) -> Option<String> {
+        self.tags_page.clone()
+    }
 }
@@ -0,0 +1,298 @@
+//! Settings read from an [Obsidian](https://obsidian.md/) vault's `.obsidian/app.json`
+//!
+//! These have the lowest priority, below both the config file and the cli, so anything
+//! can still be overridden.
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::{
+    file::{
+        content::wikilink::Alias,
+        name::{Filename, FilenameLowercase},
+    },
+    rules::ErrorCode,
+    sed::{ReplacePair, ReplacePairCompilationError},
+};
+
+use super::{
+    KeySynonyms, LinkOncePer, LinkResolution, LinkStyle, MarkdownExtensions, MinInboundLinks,
+    Namespace, NamespaceRule, NewConfigError, NewFilesDirectory, Partial, Platform,
+    SeverityOverride, SimilarityAlgorithm, UnlinkedTextFix, UnlinkedTextOptions,
+};
+
+/// The directory Obsidian keeps its settings in, at the root of the vault
+pub const DIRECTORY: &str = ".obsidian";
+
+/// Obsidian's "New link format" setting
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NewLinkFormat {
+    #[default]
+    Shortest,
+    Relative,
+    Absolute,
+}
+
+/// Obsidian's "Default location for new notes" setting
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NewFileLocation {
+    #[default]
+    Root,
+    Current,
+    Folder,
+}
+
+/// The parts of `.obsidian/app.json` we care about
+/// Obsidian only writes settings which differ from its defaults
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppJson {
+    pub attachment_folder_path: String,
+    pub new_link_format: NewLinkFormat,
+    pub use_markdown_links: bool,
+    pub new_file_location: NewFileLocation,
+    pub new_file_folder_path: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The root of the vault, the directory containing `.obsidian`
+    pub root_directory: PathBuf,
+    pub app: AppJson,
+}
+
+impl Config {
+    /// Reads the settings of the vault at `root_directory`
+    /// Returns [`None`] if it is not an Obsidian vault
+    pub fn new(root_directory: &Path) -> Result<Option<Self>, NewConfigError> {
+        let path = root_directory.join(DIRECTORY).join("app.json");
+        if !path.is_file() {
+            return Ok(None);
+        }
+        debug!("Reading obsidian settings from {}", path.display());
+        let contents = std::fs::read_to_string(&path)?;
+        let app = serde_json::from_str(&contents)
+            .map_err(|source| NewConfigError::ObsidianParseError { path, source })?;
+        Ok(Some(Self {
+            root_directory: root_directory.to_path_buf(),
+            app,
+        }))
+    }
+}
+
+impl Partial for Config {
+    fn pages_directory(&self) -> Option<PathBuf> {
+        None
+    }
+    fn other_directories(&self) -> Option<Vec<PathBuf>> {
+        None
+    }
+    fn hierarchy_separator(&self) -> Option<String> {
+        None
+    }
+    fn ngram_size(&self) -> Option<usize> {
+        None
+    }
+    fn boundary_pattern(&self) -> Option<String> {
+        None
+    }
+    fn filename_spacing_pattern(&self) -> Option<String> {
+        None
+    }
+    fn ngram_ignore_pattern(&self) -> Option<String> {
+        None
+    }
+    fn split_camel_case(&self) -> Option<bool> {
+        None
+    }
+    fn filename_match_threshold(&self) -> Option<i64> {
+        None
+    }
+    fn exclude(&self) -> Option<Vec<ErrorCode>> {
+        None
+    }
+    fn filename_to_alias(
+        &self,
+    ) -> Option<Result<ReplacePair<Filename, Alias>, ReplacePairCompilationError>> {
+        None
+    }
+    fn alias_to_filename(
+        &self,
+    ) -> Option<Result<ReplacePair<Alias, FilenameLowercase>, ReplacePairCompilationError>> {
+        None
+    }
+    fn fix(&self) -> Option<bool> {
+        None
+    }
+    fn allow_dirty(&self) -> Option<bool> {
+        None
+    }
+    fn dry_run(&self) -> Option<bool> {
+        None
+    }
+    fn verify(&self) -> Option<bool> {
+        None
+    }
+    fn no_vcs(&self) -> Option<bool> {
+        None
+    }
+    fn write_index(&self) -> Option<bool> {
+        None
+    }
+    fn rule_timeout(&self) -> Option<u64> {
+        None
+    }
+    fn source_memory_limit(&self) -> Option<usize> {
+        None
+    }
+    fn ignore_word_pairs(&self) -> Option<Vec<(String, String)>> {
+        None
+    }
+    fn ignore_remaining(&self) -> Option<String> {
+        None
+    }
+    fn skip_html(&self) -> Option<bool> {
+        None
+    }
+    fn markdown(&self) -> Option<MarkdownExtensions> {
+        None
+    }
+    fn unlinked_text(&self) -> Option<UnlinkedTextOptions> {
+        None
+    }
+    fn new_files_directory(&self) -> Option<PathBuf> {
+        match self.app.new_file_location {
+            NewFileLocation::Root => Some(self.root_directory.clone()),
+            NewFileLocation::Folder => Some(
+                self.root_directory
+                    .join(self.app.new_file_folder_path.trim_start_matches('/')),
+            ),
+            NewFileLocation::Current => {
+                warn!("Obsidian's newFileLocation \"current\" is not supported, new files will be created in the pages directory");
+                None
+            }
+        }
+    }
+    fn new_file_template(&self) -> Option<PathBuf> {
+        None
+    }
+    /// Attachments in the root of the vault or next to each note ("./") are not supported,
+    /// as every file in those folders would become an attachment
+    fn attachments_directory(&self) -> Option<PathBuf> {
+        let folder = self.app.attachment_folder_path.trim_start_matches('/');
+        if folder.is_empty() || folder.starts_with("./") || folder == "." {
+            None
+        } else {
+            Some(self.root_directory.join(folder))
+        }
+    }
+    fn link_resolution(&self) -> Option<LinkResolution> {
+        Some(match self.app.new_link_format {
+            NewLinkFormat::Shortest => LinkResolution::ShortestPath,
+            NewLinkFormat::Relative => LinkResolution::Relative,
+            NewLinkFormat::Absolute => LinkResolution::Absolute,
+        })
+    }
+    fn relref(&self) -> Option<bool> {
+        None
+    }
+    fn code_wikilinks(&self) -> Option<bool> {
+        None
+    }
+    fn unlinked_text_inflections(&self) -> Option<bool> {
+        None
+    }
+    fn group_unlinked_text(&self) -> Option<bool> {
+        None
+    }
+    fn unlinked_text_fix(&self) -> Option<UnlinkedTextFix> {
+        None
+    }
+    fn link_once_per(&self) -> Option<LinkOncePer> {
+        None
+    }
+    fn similar_filename_same_directory_only(&self) -> Option<bool> {
+        None
+    }
+    fn similarity_algorithm(&self) -> Option<SimilarityAlgorithm> {
+        None
+    }
+    fn known_tags(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn new_files_directories(&self) -> Option<Vec<NewFilesDirectory>> {
+        None
+    }
+    fn ignore_paths(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn allow_links_to_ignored(&self) -> Option<Vec<String>> {
+        None
+    }
+    fn max_outbound_links(&self) -> Option<usize> {
+        None
+    }
+    fn min_inbound_links(&self) -> Option<Vec<MinInboundLinks>> {
+        None
+    }
+    fn filename_platforms(&self) -> Option<Vec<Platform>> {
+        None
+    }
+    fn key_synonyms(&self) -> Option<Vec<KeySynonyms>> {
+        None
+    }
+    fn link_style(&self) -> Option<LinkStyle> {
+        None
+    }
+    fn namespace(&self) -> Option<Vec<Namespace>> {
+        None
+    }
+    fn severity(&self) -> Option<Vec<SeverityOverride>> {
+        None
+    }
+    fn completed_tasks(&self) -> Option<NamespaceRule> {
+        None
+    }
+    fn tags_page(&self) -> Option<String> {
+        None
+    }
+    fn wikilinks(&self) -> Option<bool> {
+        Some(!self.app.use_markdown_links)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::config::{LinkResolution, Partial};
+
+    use super::Config;
+
+    #[test]
+    fn test_read_app_json() {
+        let root = Path::new("./tests/logseq/obsidian/assets");
+        let config = Config::new(root)
+            .expect("This is a valid app.json")
+            .expect("This is an obsidian vault");
+        assert_eq!(
+            config.attachments_directory(),
+            Some(PathBuf::from("./tests/logseq/obsidian/assets/attachments"))
+        );
+        assert_eq!(
+            config.new_files_directory(),
+            Some(PathBuf::from("./tests/logseq/obsidian/assets/notes"))
+        );
+        assert_eq!(config.link_resolution(), Some(LinkResolution::Absolute));
+        assert_eq!(config.wikilinks(), Some(true));
+    }
+
+    #[test]
+    fn test_not_a_vault() {
+        let root = Path::new("./tests/logseq/broken_wikilink/assets");
+        assert!(Config::new(root).expect("No app.json").is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! The lines changed since a git ref, see `mdlinker --diff-base`
+//!
+//! Only reports which touch these lines fail the run, so issues which were already there don't
+//! block a pull request
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+
+use crate::rules::Report;
+
+/// The ranges of lines, starting from 1, which changed in each file
+#[derive(Debug, Clone, Default)]
+pub struct ChangedLines {
+    /// Keyed by canonical paths, so they can be compared with the paths of reports
+    lines: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedLines {
+    /// Diff the working directory, staged changes and untracked files included, against `base`
+    /// in the git repo `directory` is in. `base` is anything `git rev-parse` takes, like `main`
+    #[cfg(feature = "git")]
+    pub fn new(directory: &Path, base: &str) -> Result<Self, git2::Error> {
+        let repo = git2::Repository::discover(directory)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| git2::Error::from_str("A bare repo has no changed lines"))?;
+        let workdir = workdir
+            .canonicalize()
+            .unwrap_or_else(|_| workdir.to_owned());
+        let tree = repo.revparse_single(base)?.peel_to_tree()?;
+        let mut options = git2::DiffOptions::new();
+        options
+            .context_lines(0)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+        let mut out = Self::default();
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path() {
+                out.lines.entry(workdir.join(path)).or_default();
+            }
+        }
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            Some(&mut |delta, hunk| {
+                if let Some(path) = delta.new_file().path() {
+                    let start = hunk.new_start() as usize;
+                    let lines = match hunk.new_lines() as usize {
+                        // A hunk which only removes lines starts at the line before them, and
+                        // touches the line after them
+                        0 => start + 1..=start + 1,
+                        new_lines => start..=start + new_lines - 1,
+                    };
+                    out.lines.entry(workdir.join(path)).or_default().push(lines);
+                }
+                true
+            }),
+            None,
+        )?;
+        Ok(out)
+    }
+
+    /// Whether the lines of the report intersect the changed lines of its file
+    /// Reports about filenames, without a span, touch any file which changed, as do the other
+    /// files of reports about several files, like the file a duplicate alias is the name of
+    #[must_use]
+    pub fn touches(&self, report: &Report) -> bool {
+        let mut paths = report.inner().paths().into_iter();
+        let Some(path) = paths.next() else {
+            return false;
+        };
+        if paths.any(|path| self.ranges(&path).is_some()) {
+            return true;
+        }
+        let Some(ranges) = self.ranges(&path) else {
+            return false;
+        };
+        let (Some(span), Some((line, _))) = (report.span(), report.line_column()) else {
+            return true;
+        };
+        // Spans can go over several lines, like the front matter of duplicate aliases
+        // The report's own copy of the file is used, which is gone if it was elided
+        let nb_lines = report
+            .diagnostic()
+            .source_code()
+            .and_then(|source| source.read_span(&span, 0, 0).ok())
+            .map_or(1, |contents| contents.line_count());
+        let lines = line..=line + nb_lines.max(1) - 1;
+        ranges
+            .iter()
+            .any(|range| range.start() <= lines.end() && lines.start() <= range.end())
+    }
+
+    /// The changed lines of the file at `path`, [`None`] if it didn't change
+    fn ranges(&self, path: &Path) -> Option<&Vec<RangeInclusive<usize>>> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        self.lines.get(&path)
+    }
+}
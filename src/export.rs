@@ -0,0 +1,218 @@
+//! The reports of a run as json or SARIF, for editors, CI and bots, along with the edits of their
+//! fixes so they can make them without mdlinker, see [`Config::output_format`]
+use std::{collections::BTreeMap, ops::Range, path::Path, path::PathBuf};
+
+use miette::Severity;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    config::Config,
+    file::content::{
+        line_column,
+        notebook::{is_notebook, NotebookMarkdown},
+    },
+    rules::{ErrorCode, Fingerprint, Report, TextEdit},
+    OutputReport,
+};
+
+/// A report as it is written in json
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExportedReport {
+    pub id: ErrorCode,
+    /// See [`crate::rules::ReportTrait::fingerprint`], which can be excluded like the id
+    pub fingerprint: Fingerprint,
+    /// The code of the rule, like `content::alias::unlinked`
+    pub rule: String,
+    /// `error`, `warning` or `advice`, see [`OutputReport::severity`]
+    pub severity: String,
+    pub message: String,
+    pub help: Option<String>,
+    pub path: PathBuf,
+    /// The bytes of the file the report is about
+    /// For Jupyter notebooks these are bytes of the markdown of their cells, see
+    /// [`NotebookMarkdown`]
+    pub range: Option<Range<usize>>,
+    /// The index of the cell the range starts in, for Jupyter notebooks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// The line and column (in characters) of the start of the range, both starting from 1
+    /// In Jupyter notebooks they are within [`ExportedReport::cell`], like editors show them
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// See [`crate::rules::ReportTrait::quick_fix`]
+    pub fix: Vec<TextEdit>,
+}
+
+impl ExportedReport {
+    #[must_use]
+    pub fn new(output_report: &OutputReport, report: &Report, config: &Config) -> Self {
+        let diagnostic = report.diagnostic();
+        let id = report.id();
+        let severity = match output_report.severity(config, report) {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Advice => "advice",
+        };
+        let path = report.path();
+        let (cell, (line, column)) = match notebook_position(&path, report) {
+            Some((cell, line, column)) => (Some(cell), (Some(line), Some(column))),
+            None => (None, report.line_column().unzip()),
+        };
+        Self {
+            rule: diagnostic
+                .code()
+                .map_or_else(|| id.0.clone(), |code| code.to_string()),
+            id,
+            fingerprint: report.fingerprint(),
+            severity: severity.to_owned(),
+            message: diagnostic.to_string(),
+            help: diagnostic.help().map(|help| help.to_string()),
+            path,
+            range: report
+                .span()
+                .map(|span| span.offset()..span.offset() + span.len()),
+            cell,
+            line,
+            column,
+            fix: report.inner().quick_fix(config),
+        }
+    }
+}
+
+/// The cell of a report in a Jupyter notebook, and its line and column within the cell
+/// [`None`] for other files, reports without a span, or if the notebook can't be read again
+fn notebook_position(path: &Path, report: &Report) -> Option<(usize, usize, usize)> {
+    if !is_notebook(path) {
+        return None;
+    }
+    let offset = report.span()?.offset();
+    let notebook = NotebookMarkdown::new(&std::fs::read_to_string(path).ok()?).ok()?;
+    let (cell, offset) = notebook.locate(offset)?;
+    let (line, column) = line_column(notebook.cell_source(cell)?, offset);
+    Some((cell, line, column))
+}
+
+/// Every report of `output_report`, see [`ExportedReport`]
+#[must_use]
+pub fn exported_reports(output_report: &OutputReport, config: &Config) -> Vec<ExportedReport> {
+    output_report
+        .reports
+        .iter()
+        .map(|report| ExportedReport::new(output_report, report, config))
+        .collect()
+}
+
+/// Serialize the reports as pretty printed json
+///
+/// # Errors
+///
+/// If serialization fails, which it shouldn't
+pub fn to_json(output_report: &OutputReport, config: &Config) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&exported_reports(output_report, config))
+}
+
+/// A path as a relative SARIF uri, with forward slashes
+fn uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    path.strip_prefix("./").unwrap_or(&path).to_owned()
+}
+
+/// The name of [`ExportedReport::fingerprint`] in the `partialFingerprints` of SARIF results,
+/// versioned as SARIF asks, so it can change without matching the old ones
+const FINGERPRINT: &str = "mdlinker/v1";
+
+/// A SARIF result, with the fix as one change per file
+fn sarif_result(report: &ExportedReport) -> Value {
+    let level = match report.severity.as_str() {
+        "advice" => "note",
+        level => level,
+    };
+    let mut region = json!({});
+    if let Some(range) = &report.range {
+        region["byteOffset"] = json!(range.start);
+        region["byteLength"] = json!(range.len());
+    }
+    if let (Some(line), Some(column)) = (report.line, report.column) {
+        region["startLine"] = json!(line);
+        region["startColumn"] = json!(column);
+    }
+    let mut physical_location = json!({ "artifactLocation": { "uri": uri(&report.path) } });
+    // Reports about filenames are about the whole file
+    if region.as_object().is_some_and(|region| !region.is_empty()) {
+        physical_location["region"] = region;
+    }
+    let mut result = json!({
+        "ruleId": report.rule,
+        "level": level,
+        "message": { "text": report.message },
+        "locations": [{ "physicalLocation": physical_location }],
+        "partialFingerprints": { FINGERPRINT: report.fingerprint },
+        "properties": { "id": report.id },
+    });
+    if let Some(cell) = report.cell {
+        result["properties"]["cell"] = json!(cell);
+    }
+    if !report.fix.is_empty() {
+        let mut by_file: BTreeMap<&Path, Vec<&TextEdit>> = BTreeMap::new();
+        for edit in &report.fix {
+            by_file.entry(&edit.path).or_default().push(edit);
+        }
+        let changes: Vec<Value> = by_file
+            .into_iter()
+            .map(|(path, edits)| {
+                let replacements: Vec<Value> = edits
+                    .into_iter()
+                    .map(|edit| {
+                        json!({
+                            "deletedRegion": {
+                                "byteOffset": edit.range.start,
+                                "byteLength": edit.range.len(),
+                            },
+                            "insertedContent": { "text": edit.replacement },
+                        })
+                    })
+                    .collect();
+                json!({
+                    "artifactLocation": { "uri": uri(path) },
+                    "replacements": replacements,
+                })
+            })
+            .collect();
+        result["fixes"] = json!([{
+            "description": { "text": report.help.as_deref().unwrap_or(&report.message) },
+            "artifactChanges": changes,
+        }]);
+    }
+    result
+}
+
+/// Serialize the reports as a pretty printed SARIF 2.1.0 log, with one run
+///
+/// # Errors
+///
+/// If serialization fails, which it shouldn't
+pub fn to_sarif(
+    output_report: &OutputReport,
+    config: &Config,
+) -> Result<String, serde_json::Error> {
+    let reports = exported_reports(output_report, config);
+    let mut rules: Vec<&str> = reports.iter().map(|report| report.rule.as_str()).collect();
+    rules.sort_unstable();
+    rules.dedup();
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|rule| json!({ "id": rule })).collect::<Vec<_>>(),
+                }
+            },
+            "results": reports.iter().map(sarif_result).collect::<Vec<_>>(),
+        }]
+    });
+    serde_json::to_string_pretty(&sarif)
+}
@@ -1,22 +1,47 @@
+#[cfg(feature = "fs")]
 use std::path::PathBuf;
 
+#[cfg(feature = "fs")]
+use hashbrown::HashSet;
+#[cfg(feature = "fs")]
 use walkdir::WalkDir;
 
 use thiserror::Error;
 
-use std;
-
+pub mod backup;
+pub mod case;
 pub mod content;
 pub mod name;
+pub mod provider;
+pub mod snapshot;
 
 /// Walk the directories and get just the files
+///
+/// Symlinks are followed, and every file is deduplicated by its canonical path,
+/// so a note reachable through a symlink or through overlapping directories is
+/// only returned once (the first path it was found under is kept).
+///
+/// Hidden files and directories, like `.obsidian` or `.git`, are skipped.
+#[cfg(feature = "fs")]
 pub fn get_files(dirs: &Vec<PathBuf>) -> Vec<PathBuf> {
     let mut out = Vec::new();
+    let mut seen = HashSet::new();
     for path in dirs {
-        let walk = WalkDir::new(path);
-        for entry in walk.into_iter().filter_map(Result::ok) {
+        let walk = WalkDir::new(path)
+            .follow_links(true)
+            .into_iter()
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+            });
+        for entry in walk.filter_map(Result::ok) {
             if entry.file_type().is_file() {
-                out.push(entry.into_path());
+                let canonical = entry
+                    .path()
+                    .canonicalize()
+                    .unwrap_or_else(|_| entry.path().to_path_buf());
+                if seen.insert(canonical) {
+                    out.push(entry.into_path());
+                }
             }
         }
     }
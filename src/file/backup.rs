@@ -0,0 +1,74 @@
+//! Copies of files made before `--fix` edits them, so fixes can be undone without git
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use hashbrown::HashSet;
+
+use crate::rules::FixError;
+
+/// Made next to each file which is backed up, it is hidden so it is never linted
+pub const BACKUP_DIRECTORY: &str = ".mdlinker-backup";
+
+/// Backs up each file once per run, before its first fix
+#[derive(Debug)]
+pub struct Backup {
+    /// Every run gets its own directory, named after when it started, so runs don't overwrite each other
+    run: String,
+    backed_up: HashSet<PathBuf>,
+}
+
+impl Default for Backup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backup {
+    #[must_use]
+    pub fn new() -> Self {
+        let run = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        Self {
+            run: run.to_string(),
+            backed_up: HashSet::new(),
+        }
+    }
+
+    /// Where `path` is backed up to, `.mdlinker-backup/<run>/<filename>` in its directory
+    #[must_use]
+    pub fn path_of(&self, path: &Path) -> PathBuf {
+        path.parent()
+            .unwrap_or(Path::new(""))
+            .join(BACKUP_DIRECTORY)
+            .join(&self.run)
+            .join(path.file_name().unwrap_or_default())
+    }
+
+    /// Copy the file to [`Backup::path_of`], unless it was already backed up in this run
+    /// so the backup is always of the file before any fixes
+    /// A backup made by another [`Backup`] of the same run, like that of
+    /// [`crate::mv::rename`], is kept too
+    pub fn save(&mut self, path: &Path) -> Result<(), FixError> {
+        if !self.backed_up.insert(path.to_path_buf()) {
+            return Ok(());
+        }
+        let backup = self.path_of(path);
+        if backup.exists() {
+            return Ok(());
+        }
+        let io_error = |source| FixError::IOError {
+            source,
+            backtrace: std::backtrace::Backtrace::force_capture(),
+            file: backup.to_string_lossy().to_string(),
+        };
+        if let Some(directory) = backup.parent() {
+            std::fs::create_dir_all(directory).map_err(io_error)?;
+        }
+        std::fs::copy(path, &backup).map_err(io_error)?;
+        Ok(())
+    }
+}
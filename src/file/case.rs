@@ -0,0 +1,178 @@
+//! Case folding and lengths of text, so aliases, filenames and ngrams compare and measure
+//! their text the same way
+//!
+//! Texts are compared with Unicode's full case folding, which also folds characters
+//! `to_lowercase` leaves apart, like `ß` and `ss`. Folding can change the byte length of a text,
+//! so spans are always taken in the original text, see [`find_folded`]
+use std::ops::Range;
+
+use caseless::Caseless;
+
+/// The text with its case folded, so texts which only differ in case are equal
+#[must_use]
+pub fn fold(text: &str) -> String {
+    text.chars().default_case_fold().collect()
+}
+
+/// Whether the texts are equal once their case is folded, see [`fold`]
+#[must_use]
+pub fn eq_folded(text: &str, other: &str) -> bool {
+    text.chars()
+        .default_case_fold()
+        .eq(other.chars().default_case_fold())
+}
+
+/// The number of characters in the text, its length for people
+/// Spans and offsets count bytes, which is [`str::len`]
+#[must_use]
+pub fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// The byte range in `text` of the first place which folds to the same as `needle` folds to,
+/// see [`fold`]
+/// The range covers whole characters of `text`, even if `needle` only matches part of what
+/// one folds to
+#[must_use]
+pub fn find_folded(text: &str, needle: &str) -> Option<Range<usize>> {
+    let needle = fold(needle);
+    if needle.is_empty() {
+        return Some(0..0);
+    }
+    // The folded text, and the byte offset in `text` of the character each of its bytes came from
+    let mut folded = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (offset, character) in text.char_indices() {
+        let before = folded.len();
+        folded.extend(std::iter::once(character).default_case_fold());
+        origins.resize(origins.len() + folded.len() - before, offset);
+    }
+    let found = folded.find(&needle)?;
+    let start = origins[found];
+    let last = origins[found + needle.len() - 1];
+    let end = last + text[last..].chars().next().map_or(0, char::len_utf8);
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file::{content::wikilink::Alias, name::FilenameLowercase};
+
+    use super::{char_len, eq_folded, find_folded, fold};
+
+    /// Texts with ascii, accents, characters which fold to several, and characters whose
+    /// lowercase has another byte length
+    const TEXTS: &[&str] = &[
+        "",
+        "lorem",
+        "Lorem Ipsum",
+        "LOREM-IPSUM_2",
+        "café",
+        "CAFÉ",
+        "straße",
+        "STRASSE",
+        "İstanbul",
+        "ǅemal",
+        "ΣΊΣΥΦΟΣ",
+        "σίσυφος",
+        "ﬁle",
+        "Ⅻ",
+        "日本語",
+    ];
+
+    /// Every character of the first planes, and the texts
+    fn texts() -> impl Iterator<Item = String> {
+        (0..0x3000)
+            .filter_map(char::from_u32)
+            .map(String::from)
+            .chain(TEXTS.iter().map(|text| (*text).to_owned()))
+    }
+
+    #[test]
+    fn folds() {
+        assert_eq!(fold("Lorem"), "lorem");
+        assert_eq!(fold("STRASSE"), fold("straße"));
+        assert_eq!(fold("ΣΊΣΥΦΟΣ"), fold("σίσυφος"));
+        assert!(eq_folded("CAFÉ", "café"));
+        assert!(!eq_folded("cafe", "café"));
+    }
+
+    /// Folding again changes nothing
+    #[test]
+    fn idempotent() {
+        for text in texts() {
+            assert_eq!(fold(&fold(&text)), fold(&text), "{text:?}");
+        }
+    }
+
+    /// [`eq_folded`] agrees with [`fold`]
+    #[test]
+    fn case_insensitive() {
+        for text in texts() {
+            assert_eq!(
+                eq_folded(&text, &text.to_uppercase()),
+                fold(&text) == fold(&text.to_uppercase()),
+                "{text:?}"
+            );
+            assert!(eq_folded(&text, &fold(&text)), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn char_lens() {
+        assert_eq!(char_len("lorem"), 5);
+        assert_eq!(char_len("café"), 4);
+        assert_eq!("café".len(), 5);
+        for text in texts() {
+            assert!(char_len(&text) <= text.len(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn finds() {
+        assert_eq!(find_folded("- Lorem ipsum", "lorem"), Some(2..7));
+        assert_eq!(find_folded("aliases: [Straße]", "STRASSE"), Some(10..17));
+        assert_eq!(
+            find_folded("İstanbul", "i̇stanbul"),
+            Some(0.."İstanbul".len())
+        );
+        assert_eq!(find_folded("lorem", "ipsum"), None);
+        // Part of what `ß` folds to covers all of it
+        assert_eq!(find_folded("aßb", "as"), Some(0..3));
+    }
+
+    /// The range found is in the text, on character boundaries, and folds to contain the needle
+    #[test]
+    fn finds_in_the_original() {
+        for text in texts() {
+            for prefix in ["", "x ", "日本 ", "ß"] {
+                let haystack = format!("{prefix}{text} lorem");
+                // Not every uppercase folds back, like that of the dotless `ı`
+                let uppercase = text.to_uppercase();
+                let needles = [&text, &uppercase];
+                for needle in needles.iter().filter(|needle| eq_folded(needle, &text)) {
+                    let range = find_folded(&haystack, needle)
+                        .unwrap_or_else(|| panic!("{needle:?} should be found in {haystack:?}"));
+                    assert!(haystack.is_char_boundary(range.start), "{haystack:?}");
+                    assert!(haystack.is_char_boundary(range.end), "{haystack:?}");
+                    assert!(
+                        fold(&haystack[range.clone()]).contains(&fold(needle)),
+                        "{haystack:?} {range:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Aliases and filenames compare with the same folding
+    #[test]
+    fn aliases_and_filenames() {
+        assert_eq!(Alias::new("Straße"), Alias::new("STRASSE"));
+        assert_eq!(Alias::new("ΣΊΣΥΦΟΣ"), Alias::new("σίσυφος"));
+        assert_eq!(
+            FilenameLowercase::new("Straße"),
+            FilenameLowercase::new("strasse")
+        );
+        assert_eq!(Alias::new("Straße").char_len(), 7);
+    }
+}
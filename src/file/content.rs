@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use wikilink::{Alias, Wikilink};
 
@@ -7,6 +7,12 @@ use crate::sed::ReplacePairCompilationError;
 use super::Error;
 
 pub mod front_matter;
+pub mod html;
+pub mod notebook;
+pub mod quote;
+pub mod source_map;
+pub mod table;
+pub mod task;
 pub mod wikilink;
 
 #[derive(Clone)]
@@ -25,4 +31,42 @@ pub enum FromFileError {
         e: ReplacePairCompilationError,
         path: String,
     },
+    #[error("Failed to read the notebook: {0}")]
+    NotebookError(#[from] serde_json::Error),
+}
+
+/// Read the markdown source of a file
+///
+/// For most files this is just their content, but for Jupyter notebooks it is their
+/// markdown cells, see [`notebook::NotebookMarkdown`]
+pub fn read_source(path: &Path) -> Result<String, FromFileError> {
+    source_from_content(path, std::fs::read_to_string(path)?)
+}
+
+/// The markdown source of a file at `path` with `content`, see [`read_source`]
+pub fn source_from_content(path: &Path, content: String) -> Result<String, FromFileError> {
+    if notebook::is_notebook(path) {
+        Ok(notebook::NotebookMarkdown::new(&content)?.source)
+    } else {
+        Ok(content)
+    }
+}
+
+/// The line and column of a byte offset in the source, both starting from 1
+/// The column is counted in characters, see [`source_map::SourceMap::line_column`]
+#[must_use]
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    source_map::SourceMap::new(source).line_column(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::line_column;
+
+    #[test]
+    fn test_line_column() {
+        let source = "- lorem\n- [[ipsum]]";
+        assert_eq!(line_column(source, 0), (1, 1));
+        assert_eq!(line_column(source, 10), (2, 3));
+    }
 }
@@ -1,7 +1,8 @@
-use std::{cell::RefCell, path::Path};
+use std::{cell::RefCell, collections::BTreeMap, ops::Range, path::Path};
 
 use crate::{
-    rules::{ErrorCode, Report},
+    file::content::source_map::SourceMap,
+    rules::ErrorCode,
     visitor::{VisitError, Visitor},
 };
 use comrak::{
@@ -12,17 +13,286 @@ use serde::Deserialize;
 
 use super::wikilink::Alias;
 
+/// The aliases in a front matter field, either a comma separated string like
+/// `alias: lorem, ipsum` or a list like `aliases: [lorem, ipsum]`
+/// Tags are written the same way, and are aliases of the pages they link to
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AliasField {
+    Csv(String),
+    /// Each entry is a whole alias, even with commas in it
+    List(Vec<String>),
+}
+
+impl Default for AliasField {
+    fn default() -> Self {
+        Self::Csv(String::new())
+    }
+}
+
+impl AliasField {
+    /// The aliases as written, trimmed, without empty ones
+    #[must_use]
+    pub fn entries(&self) -> Vec<&str> {
+        let aliases: Vec<&str> = match self {
+            Self::Csv(aliases) => aliases.split(',').collect(),
+            Self::List(aliases) => aliases.iter().map(String::as_str).collect(),
+        };
+        aliases
+            .into_iter()
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .collect()
+    }
+
+    /// The aliases, trimmed, without empty ones
+    #[must_use]
+    pub fn aliases(&self) -> Vec<Alias> {
+        self.entries().into_iter().map(Alias::new).collect()
+    }
+}
+
 #[derive(Deserialize, Debug, Default)]
 #[serde(default)]
 pub struct YamlFrontMatter {
+    /// Logseq's aliases
+    #[serde(default)]
+    pub alias: AliasField,
+    /// Obsidian's aliases
     #[serde(default)]
-    pub alias: String,
+    pub aliases: AliasField,
+    #[serde(default)]
+    pub tags: AliasField,
+    #[serde(default)]
+    pub id: Option<serde_yaml::Value>,
+    #[serde(default)]
+    pub date: Option<serde_yaml::Value>,
+    /// Never suggest linking the aliases of this page as unlinked text,
+    /// for pages named after very common words
+    #[serde(default, rename = "no-autolink", alias = "no_autolink")]
+    pub no_autolink: bool,
+    /// Every other key
+    #[serde(flatten)]
+    pub properties: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// The front matter of a file, see [`FrontMatterVisitor::front_matter`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrontMatter {
+    /// From both `alias` and `aliases`, without repeats
+    pub aliases: Vec<Alias>,
+    pub tags: Vec<Alias>,
+    /// As written, even if it is a number
+    pub id: Option<String>,
+    /// As written, it is not parsed as a date
+    pub date: Option<String>,
+    /// See [`YamlFrontMatter::no_autolink`]
+    pub no_autolink: bool,
+    /// Every other key, like `title` or `status`, for rules which check them
+    pub properties: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl From<YamlFrontMatter> for FrontMatter {
+    fn from(value: YamlFrontMatter) -> Self {
+        let mut aliases: Vec<Alias> = Vec::new();
+        for alias in value
+            .alias
+            .aliases()
+            .into_iter()
+            .chain(value.aliases.aliases())
+        {
+            if !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+        Self {
+            aliases,
+            tags: value.tags.aliases(),
+            id: value.id.as_ref().and_then(scalar),
+            date: value.date.as_ref().and_then(scalar),
+            no_autolink: value.no_autolink,
+            properties: value.properties,
+        }
+    }
+}
+
+/// A string, number or bool as a string
+fn scalar(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// The front matter of `source`, between its `---` lines, and where it starts
+#[must_use]
+pub fn raw_front_matter(source: &str) -> Option<(usize, &str)> {
+    let start = source.find('\n')? + 1;
+    if source[..start].trim_end() != "---" {
+        return None;
+    }
+    let len: usize = source[start..]
+        .split_inclusive('\n')
+        .take_while(|line| line.trim_end() != "---")
+        .map(str::len)
+        .sum();
+    Some((start, &source[start..start + len]))
+}
+
+/// The key of a line like `key: value`, if the line is at the top level of the yaml
+fn top_level_key(line: &str) -> Option<&str> {
+    if line.starts_with(|c: char| c.is_whitespace() || c == '-' || c == '#') {
+        return None;
+    }
+    let (key, rest) = line.split_once(':')?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(key.trim_end())
+    } else {
+        None
+    }
+}
+
+/// The top level keys of some yaml, and the byte range of each key's lines, up to the next key
+#[must_use]
+pub fn top_level_keys(yaml: &str) -> Vec<(&str, Range<usize>)> {
+    let mut keys: Vec<(&str, Range<usize>)> = Vec::new();
+    let mut offset = 0;
+    for line in yaml.split_inclusive('\n') {
+        if let Some(key) = top_level_key(line) {
+            keys.push((key, offset..offset));
+        }
+        if let Some((_, range)) = keys.last_mut() {
+            range.end = offset + line.len();
+        }
+        offset += line.len();
+    }
+    keys
+}
+
+/// The byte range of each alias in the front matter of `source`, like that of `ipsum` in
+/// `aliases: [lorem, ipsum]`, the first if an alias is written more than once
+///
+/// The entries of `alias` and `aliases` are split like [`AliasField::entries`] splits them, in
+/// all the ways [`AliasField`] is written. An entry is only found if it is written as is, without
+/// escapes, as its range would not be that of the alias
+#[must_use]
+pub fn alias_spans(source: &str) -> BTreeMap<Alias, Range<usize>> {
+    let mut spans = BTreeMap::new();
+    let Some((start, yaml)) = raw_front_matter(source) else {
+        return spans;
+    };
+    let keys = top_level_keys(yaml);
+    for (i, (key, range)) in keys.iter().enumerate() {
+        // Only the first value of a key is used, see `without_repeated_keys`
+        let first = !keys[..i].iter().any(|(other, _)| other == key);
+        if !first || !matches!(*key, "alias" | "aliases") {
+            continue;
+        }
+        for entry in alias_entries(yaml, range.clone()) {
+            let alias = Alias::new(&yaml[entry.clone()]);
+            if !alias.is_empty() {
+                spans
+                    .entry(alias)
+                    .or_insert(start + entry.start..start + entry.end);
+            }
+        }
+    }
+    spans
+}
+
+/// The ranges in `yaml` of the entries of the alias field on the lines of `range`, trimmed and
+/// unquoted
+/// The value is a comma separated string like `lorem, ipsum`, or a list like `[lorem, ipsum]` or
+/// `- lorem` on each of the next lines
+fn alias_entries(yaml: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let Some(colon) = yaml[range.clone()].find(':') else {
+        return vec![];
+    };
+    let value_start = range.start + colon + 1;
+    let line_end = yaml[value_start..range.end]
+        .find('\n')
+        .map_or(range.end, |i| value_start + i);
+    let value = trimmed(yaml, value_start..line_end);
+    if value.is_empty() {
+        let mut entries = vec![];
+        let mut offset = line_end;
+        for line in yaml[line_end..range.end].split_inclusive('\n') {
+            if let Some(item) = line.trim_start().strip_prefix('-') {
+                let item_start = offset + line.len() - item.len();
+                entries.extend(unquoted(yaml, item_start..offset + line.len()));
+            }
+            offset += line.len();
+        }
+        entries
+    } else if yaml[value.clone()].starts_with('[') {
+        // A flow list may go over several lines
+        let end = yaml[value.start..range.end]
+            .find(']')
+            .map_or(range.end, |i| value.start + i);
+        split_entries(yaml, value.start + 1..end)
+    } else {
+        unquoted(yaml, value).map_or_else(Vec::new, |value| split_entries(yaml, value))
+    }
+}
+
+/// The entries of `range` split at commas
+fn split_entries(yaml: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut entries = vec![];
+    let mut offset = range.start;
+    for entry in yaml[range].split(',') {
+        entries.extend(unquoted(yaml, offset..offset + entry.len()));
+        offset += entry.len() + 1;
+    }
+    entries
+}
+
+/// The range without the whitespace around it
+fn trimmed(text: &str, range: Range<usize>) -> Range<usize> {
+    let slice = &text[range.clone()];
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    let end = range.end - (slice.len() - slice.trim_end().len());
+    start..end.max(start)
+}
+
+/// The range trimmed, without the quotes around it
+/// [`None`] if it is quoted and has escapes, like `"ips\u0075m"` or `'it''s'`
+fn unquoted(text: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let range = trimmed(text, range);
+    let slice = &text[range.clone()];
+    for (quote, escape) in [('"', "\\"), ('\'', "''")] {
+        if slice.len() >= 2 && slice.starts_with(quote) && slice.ends_with(quote) {
+            let inner = range.start + 1..range.end - 1;
+            return (!text[inner.clone()].contains(escape)).then_some(inner);
+        }
+    }
+    Some(range)
+}
+
+/// The yaml without the lines of keys which are repeated, so the first value of a key is kept
+/// like Logseq does, see [`crate::rules::duplicate_property::DuplicateProperty`]
+pub(crate) fn without_repeated_keys(yaml: &str) -> String {
+    let keys = top_level_keys(yaml);
+    let mut seen = Vec::new();
+    let mut out = String::new();
+    let mut end = keys.first().map_or(yaml.len(), |(_, range)| range.start);
+    out.push_str(&yaml[..end]);
+    for (key, range) in keys {
+        if !seen.contains(&key) {
+            seen.push(key);
+            out.push_str(&yaml[range.clone()]);
+        }
+        end = range.end;
+    }
+    out.push_str(&yaml[end..]);
+    out
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct FrontMatterVisitor {
-    /// The aliases of the file
-    pub aliases: Vec<Alias>,
+    /// The front matter of the current file, until [`Visitor::finalize_file`]
+    pub front_matter: FrontMatter,
 }
 
 impl FrontMatterVisitor {
@@ -36,7 +306,7 @@ impl Visitor for FrontMatterVisitor {
     fn name(&self) -> &'static str {
         "FrontMatterVisitor"
     }
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, _source: &str) -> Result<(), VisitError> {
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, _source: &SourceMap) -> Result<(), VisitError> {
         if let NodeValue::FrontMatter(text) = &node.data.borrow().value {
             // Strip off first and last line for --- delimeters
             let lines: Vec<&str> = text.trim().lines().collect();
@@ -45,13 +315,8 @@ impl Visitor for FrontMatterVisitor {
             if text.is_empty() {
                 return Ok(());
             }
-            let YamlFrontMatter { alias } = serde_yaml::from_str::<YamlFrontMatter>(&text)?;
-            if alias.is_empty() {
-                return Ok(());
-            }
-            for alias in alias.split(',') {
-                self.aliases.push(Alias::new(alias.trim()));
-            }
+            self.front_matter =
+                serde_yaml::from_str::<YamlFrontMatter>(&without_repeated_keys(&text))?.into();
         }
         Ok(())
     }
@@ -60,14 +325,14 @@ impl Visitor for FrontMatterVisitor {
         _source: &str,
         _path: &Path,
     ) -> Result<(), crate::visitor::FinalizeError> {
-        self.aliases.clear();
+        self.front_matter = FrontMatter::default();
         Ok(())
     }
     fn _finalize(
         &mut self,
         _exclude: &[ErrorCode],
-    ) -> Result<Vec<Report>, crate::visitor::FinalizeError> {
-        self.aliases.clear();
-        Ok(vec![])
+    ) -> Result<crate::visitor::Finalized, crate::visitor::FinalizeError> {
+        self.front_matter = FrontMatter::default();
+        Ok(crate::visitor::Finalized::default())
     }
 }
@@ -0,0 +1,87 @@
+//! Helpers for raw HTML (and MDX/JSX) embedded in markdown
+//!
+//! comrak parses raw HTML into [`NodeValue::HtmlBlock`] and [`NodeValue::HtmlInline`] nodes,
+//! but the text between an inline opening and closing tag is still a regular
+//! [`NodeValue::Text`] sibling, so it needs to be detected by walking the siblings.
+use std::cell::RefCell;
+
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+
+/// Elements which never have a closing tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// The kind of an inline html tag
+#[derive(Debug, PartialEq, Eq)]
+enum Tag {
+    Open,
+    Close,
+    /// Self closing tags, void elements, comments, declarations, etc.
+    Other,
+}
+
+fn tag_kind(html: &str) -> Tag {
+    let html = html.trim();
+    if html.starts_with("</") {
+        return Tag::Close;
+    }
+    if html.starts_with("<!") || html.starts_with("<?") || html.ends_with("/>") {
+        return Tag::Other;
+    }
+    let name: String = html
+        .trim_start_matches('<')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '.')
+        .collect();
+    if name.is_empty() || VOID_ELEMENTS.contains(&name.to_lowercase().as_str()) {
+        Tag::Other
+    } else {
+        Tag::Open
+    }
+}
+
+/// True if this node is between an inline opening tag and its closing tag
+fn is_between_inline_tags(node: &Node<RefCell<Ast>>) -> bool {
+    let mut nb_closed = 0usize;
+    let mut sibling = node.previous_sibling();
+    while let Some(this) = sibling {
+        if let NodeValue::HtmlInline(html) = &this.data.borrow().value {
+            match tag_kind(html) {
+                Tag::Close => nb_closed += 1,
+                Tag::Open if nb_closed == 0 => return true,
+                Tag::Open => nb_closed -= 1,
+                Tag::Other => {}
+            }
+        }
+        sibling = this.previous_sibling();
+    }
+    false
+}
+
+/// True if the node is raw html, or is text rendered inside an inline html element
+/// (like the text of an `<a>` tag, or the children of a JSX component)
+#[must_use]
+pub fn is_html(node: &Node<RefCell<Ast>>) -> bool {
+    if matches!(
+        node.data.borrow().value,
+        NodeValue::HtmlBlock(_) | NodeValue::HtmlInline(_)
+    ) {
+        return true;
+    }
+    if is_between_inline_tags(node) {
+        return true;
+    }
+    let mut ancestor = node.parent();
+    while let Some(this) = ancestor {
+        if is_between_inline_tags(this) {
+            return true;
+        }
+        ancestor = this.parent();
+    }
+    false
+}
@@ -0,0 +1,157 @@
+//! Support for the markdown cells of Jupyter notebooks
+//!
+//! The markdown cells of a notebook are joined into a single markdown document,
+//! with a comment marking the start of each cell, so every visitor and rule can treat a
+//! notebook like any other markdown file. Spans in that document can be mapped back to
+//! a cell with [`NotebookMarkdown::locate`], like
+//! [`crate::export::ExportedReport::cell`] is.
+use std::{fmt::Write, path::Path};
+
+use serde::Deserialize;
+
+/// The file extension of Jupyter notebooks
+pub const EXTENSION: &str = "ipynb";
+
+#[derive(Deserialize, Debug)]
+struct Notebook {
+    cells: Vec<Cell>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Cell {
+    cell_type: String,
+    #[serde(default)]
+    source: CellSource,
+}
+
+/// nbformat allows the source of a cell to be either a string or a list of lines
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum CellSource {
+    Lines(Vec<String>),
+    Text(String),
+}
+
+impl Default for CellSource {
+    fn default() -> Self {
+        CellSource::Text(String::new())
+    }
+}
+
+impl CellSource {
+    fn text(&self) -> String {
+        match self {
+            CellSource::Lines(lines) => lines.concat(),
+            CellSource::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// Where a markdown cell was placed in [`NotebookMarkdown::source`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CellPosition {
+    /// The index of the cell in the notebook, counting all cell types
+    index: usize,
+    /// The byte offset of the start of the cell in the markdown document
+    offset: usize,
+    /// The byte length of the cell
+    len: usize,
+}
+
+/// The markdown cells of a notebook as a single markdown document
+#[derive(Debug, Clone)]
+pub struct NotebookMarkdown {
+    /// The markdown document
+    pub source: String,
+    cells: Vec<CellPosition>,
+}
+
+impl NotebookMarkdown {
+    /// Parse a notebook from its json contents
+    pub fn new(json: &str) -> Result<Self, serde_json::Error> {
+        let notebook: Notebook = serde_json::from_str(json)?;
+        let mut source = String::new();
+        let mut cells = Vec::new();
+        for (index, cell) in notebook.cells.iter().enumerate() {
+            if cell.cell_type != "markdown" {
+                continue;
+            }
+            // The first cell is not marked so it can still hold front matter
+            if !cells.is_empty() {
+                write!(source, "\n\n<!-- cell {index} -->\n\n")
+                    .expect("Writing to a String never fails");
+            }
+            let text = cell.source.text();
+            cells.push(CellPosition {
+                index,
+                offset: source.len(),
+                len: text.len(),
+            });
+            source.push_str(&text);
+        }
+        Ok(Self { source, cells })
+    }
+
+    /// Map a byte offset in [`Self::source`] back to the index of the cell in the notebook
+    /// and the byte offset within that cell's source
+    #[must_use]
+    pub fn locate(&self, offset: usize) -> Option<(usize, usize)> {
+        self.cells
+            .iter()
+            .find(|cell| cell.offset <= offset && offset <= cell.offset + cell.len)
+            .map(|cell| (cell.index, offset - cell.offset))
+    }
+
+    /// The source of the cell at `index` in the notebook, if it is a markdown cell
+    #[must_use]
+    pub fn cell_source(&self, index: usize) -> Option<&str> {
+        self.cells
+            .iter()
+            .find(|cell| cell.index == index)
+            .map(|cell| &self.source[cell.offset..cell.offset + cell.len])
+    }
+}
+
+/// Is this file a Jupyter notebook
+#[must_use]
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NotebookMarkdown;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Lorem\n", "ipsum [[dolor]]"]},
+  {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["[[sit]]"]},
+  {"cell_type": "markdown", "metadata": {}, "source": "amet"}
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_only_markdown_cells() {
+        let notebook = NotebookMarkdown::new(NOTEBOOK).expect("Just a test");
+        assert_eq!(
+            notebook.source,
+            "# Lorem\nipsum [[dolor]]\n\n<!-- cell 2 -->\n\namet"
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let notebook = NotebookMarkdown::new(NOTEBOOK).expect("Just a test");
+        let dolor = notebook.source.find("dolor").expect("Just a test");
+        assert_eq!(notebook.locate(dolor), Some((0, 16)));
+        let amet = notebook.source.find("amet").expect("Just a test");
+        assert_eq!(notebook.locate(amet), Some((2, 0)));
+        let marker = notebook.source.find("<!--").expect("Just a test");
+        assert_eq!(notebook.locate(marker), None);
+        assert_eq!(notebook.cell_source(2), Some("amet"));
+        assert_eq!(notebook.cell_source(1), None);
+    }
+}
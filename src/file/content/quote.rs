@@ -0,0 +1,46 @@
+//! Helpers for blockquotes, and Obsidian's callouts, which are blockquotes whose first line is
+//! like `> [!note]`
+use std::cell::RefCell;
+
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+
+use super::source_map::SourceMap;
+
+/// Whether the blockquote starts with a callout marker, like `> [!note] Lorem`
+fn is_callout(blockquote: &Node<RefCell<Ast>>, source: &SourceMap) -> bool {
+    let sourcepos = blockquote.data.borrow().sourcepos;
+    let start = source.start(sourcepos);
+    source[start..]
+        .trim_start_matches(['>', ' ', '\t'])
+        .starts_with("[!")
+}
+
+/// True if a blockquote around the node matches `filter`
+fn is_in_blockquote_where(
+    node: &Node<RefCell<Ast>>,
+    filter: impl Fn(&Node<RefCell<Ast>>) -> bool,
+) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(this) = ancestor {
+        if matches!(this.data.borrow().value, NodeValue::BlockQuote) && filter(this) {
+            return true;
+        }
+        ancestor = this.parent();
+    }
+    false
+}
+
+/// True if the node is inside a blockquote, callouts included
+#[must_use]
+pub fn is_in_blockquote(node: &Node<RefCell<Ast>>) -> bool {
+    is_in_blockquote_where(node, |_| true)
+}
+
+/// True if the node is inside a callout, like the `lorem` in `> [!note]\n> lorem`
+#[must_use]
+pub fn is_in_callout(node: &Node<RefCell<Ast>>, source: &SourceMap) -> bool {
+    is_in_blockquote_where(node, |blockquote| is_callout(blockquote, source))
+}
@@ -0,0 +1,221 @@
+//! Converts between the byte offsets of a source and the lines and columns of comrak's nodes,
+//! see [`SourceMap`]
+use std::ops::{Deref, Range};
+
+use comrak::nodes::{LineColumn, Sourcepos};
+
+/// A source and the byte offsets its lines start at, made once per file and given to every
+/// [`crate::visitor::Visitor`]
+///
+/// The columns of comrak's [`Sourcepos`] count bytes, and the lines count `\n`, so a `\r\n`
+/// source gives the same offsets as the `\n` one comrak parsed
+/// It derefs to the source, so it can be sliced and searched like it
+#[derive(Debug)]
+pub struct SourceMap<'a> {
+    source: &'a str,
+    /// The byte offset of the start of each line, the first is 0
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    #[must_use]
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+        }
+    }
+
+    /// The source
+    #[must_use]
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The byte offset of a line and (byte) column, both starting from 1
+    /// A column of 0, which comrak gives some empty nodes, is the start of the line, and
+    /// positions past the end are the end of the source
+    #[must_use]
+    pub fn offset(&self, position: LineColumn) -> usize {
+        let Some(line_start) = position
+            .line
+            .checked_sub(1)
+            .map_or(Some(0), |line| self.line_starts.get(line).copied())
+        else {
+            return self.source.len();
+        };
+        (line_start + position.column.saturating_sub(1)).min(self.source.len())
+    }
+
+    /// The byte offset of the first byte of a node
+    #[must_use]
+    pub fn start(&self, sourcepos: Sourcepos) -> usize {
+        self.offset(sourcepos.start)
+    }
+
+    /// The byte offset just after the last byte of a node
+    #[must_use]
+    pub fn end(&self, sourcepos: Sourcepos) -> usize {
+        (self.offset(sourcepos.end) + 1).min(self.source.len())
+    }
+
+    /// The bytes of a node, see [`SourceMap::start`] and [`SourceMap::end`]
+    #[must_use]
+    pub fn range(&self, sourcepos: Sourcepos) -> Range<usize> {
+        let start = self.start(sourcepos);
+        start..self.end(sourcepos).max(start)
+    }
+
+    /// The line and column of a byte offset, both starting from 1
+    /// The column is counted in characters, for people rather than comrak
+    #[must_use]
+    pub fn line_column(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self
+            .source
+            .get(line_start..offset)
+            .map_or(offset - line_start, |text| text.chars().count());
+        (line, column + 1)
+    }
+}
+
+impl Deref for SourceMap<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.source
+    }
+}
+
+impl AsRef<str> for SourceMap<'_> {
+    fn as_ref(&self) -> &str {
+        self.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comrak::nodes::{LineColumn, Sourcepos};
+    use miette::SourceOffset;
+
+    use super::SourceMap;
+
+    fn position(line: usize, column: usize) -> LineColumn {
+        LineColumn { line, column }
+    }
+
+    /// Every position in the source of an ascii file is where miette puts it too
+    #[test]
+    fn offset_agrees_with_miette() {
+        for source in [
+            "",
+            "\n",
+            "a",
+            "- lorem\n- [[ipsum]]",
+            "a\n\nbc\n",
+            "a\r\nbc\r\n",
+        ] {
+            let map = SourceMap::new(source);
+            for (line, text) in source.split('\n').enumerate() {
+                for column in 1..=text.len() + 1 {
+                    assert_eq!(
+                        map.offset(position(line + 1, column)),
+                        SourceOffset::from_location(source, line + 1, column).offset(),
+                        "{source:?} {line} {column}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset() {
+        let map = SourceMap::new("- lorem\n- [[ipsum]]\n");
+        assert_eq!(map.offset(position(1, 1)), 0);
+        assert_eq!(map.offset(position(1, 3)), 2);
+        assert_eq!(map.offset(position(2, 1)), 8);
+        assert_eq!(map.offset(position(2, 3)), 10);
+        // Empty nodes can have a column of 0
+        assert_eq!(map.offset(position(2, 0)), 8);
+        assert_eq!(map.offset(position(0, 0)), 0);
+        // Past the end
+        assert_eq!(map.offset(position(3, 1)), 20);
+        assert_eq!(map.offset(position(3, 5)), 20);
+        assert_eq!(map.offset(position(9, 1)), 20);
+    }
+
+    /// Columns count bytes, like comrak
+    #[test]
+    fn offset_multibyte() {
+        let map = SourceMap::new("é [[lorem]]\nà");
+        assert_eq!(map.offset(position(1, 4)), 3);
+        assert_eq!(map.offset(position(2, 1)), 13);
+        assert_eq!(map.offset(position(2, 3)), 15);
+    }
+
+    /// `\r` is the last byte of its line, comrak's columns are the same without it
+    #[test]
+    fn offset_crlf() {
+        let map = SourceMap::new("- lorem\r\n- [[ipsum]]\r\n");
+        assert_eq!(map.offset(position(2, 3)), 11);
+        assert_eq!(&map[map.offset(position(2, 3))..][..9], "[[ipsum]]");
+    }
+
+    #[test]
+    fn range() {
+        let source = "- lorem\n- [[ipsum]]";
+        let map = SourceMap::new(source);
+        let sourcepos = Sourcepos {
+            start: position(2, 3),
+            end: position(2, 11),
+        };
+        assert_eq!(&source[map.range(sourcepos)], "[[ipsum]]");
+        // Ending past the end of the source
+        let sourcepos = Sourcepos {
+            start: position(2, 3),
+            end: position(2, 40),
+        };
+        assert_eq!(&source[map.range(sourcepos)], "[[ipsum]]");
+        // Ending before it starts, like some empty nodes
+        let sourcepos = Sourcepos {
+            start: position(2, 3),
+            end: position(2, 0),
+        };
+        assert_eq!(map.range(sourcepos), 10..10);
+    }
+
+    #[test]
+    fn line_column() {
+        let map = SourceMap::new("- lorem\n- [[ipsum]]\n");
+        assert_eq!(map.line_column(0), (1, 1));
+        assert_eq!(map.line_column(7), (1, 8));
+        assert_eq!(map.line_column(8), (2, 1));
+        assert_eq!(map.line_column(10), (2, 3));
+        assert_eq!(map.line_column(20), (3, 1));
+        assert_eq!(map.line_column(99), (3, 1));
+    }
+
+    /// Columns count characters, for people
+    #[test]
+    fn line_column_multibyte() {
+        let map = SourceMap::new("é [[lorem]]\nà");
+        assert_eq!(map.line_column(3), (1, 3));
+        assert_eq!(map.line_column(14), (2, 2));
+    }
+
+    /// Each offset goes back to the line and column it came from
+    #[test]
+    fn round_trip() {
+        let source = "---\nalias: lorem\n---\n\n- [[ipsum]] and #dolor\n  - sit\n";
+        let map = SourceMap::new(source);
+        for offset in 0..=source.len() {
+            let (line, column) = map.line_column(offset);
+            assert_eq!(map.offset(position(line, column)), offset, "{offset}");
+        }
+    }
+}
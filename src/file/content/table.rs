@@ -0,0 +1,30 @@
+//! Helpers for GitHub tables, see [`crate::config::MarkdownExtensions::table`]
+//!
+//! Pipes in a cell are escaped like `\|` in the source, but not in the text of the cell,
+//! so offsets in the text need to be moved past them to be offsets in the source
+use std::cell::RefCell;
+
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+
+/// True if the node is inside a table, in its header or any other row
+#[must_use]
+pub fn is_in_table(node: &Node<RefCell<Ast>>) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(this) = ancestor {
+        if matches!(this.data.borrow().value, NodeValue::Table(_)) {
+            return true;
+        }
+        ancestor = this.parent();
+    }
+    false
+}
+
+/// The offset from the start of the text of a node in a table cell, of the byte at `offset` in
+/// its text, counting the backslashes of the escaped pipes before it
+#[must_use]
+pub fn cell_offset(text: &str, offset: usize) -> usize {
+    offset + text[..offset].matches('|').count()
+}
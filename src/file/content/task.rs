@@ -0,0 +1,96 @@
+//! Completed task items, like `- [x] Call [[Alice]]` or Logseq's `- DONE Call [[Alice]]`,
+//! see [`crate::config::Config::completed_tasks`]
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+
+use crate::{
+    file::content::source_map::SourceMap,
+    rules::ErrorCode,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// Markers after the list marker which make an item a completed task
+const COMPLETED: &[&str] = &["[x]", "[X]", "DONE"];
+
+/// Whether the first line of a list item, starting at its list marker, is a completed task
+fn is_completed(line: &str) -> bool {
+    let line = line.trim_start();
+    let rest = line
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            (digits > 0).then(|| line[digits..].strip_prefix(['.', ')']))?
+        })
+        .unwrap_or(line)
+        .trim_start();
+    COMPLETED.iter().any(|marker| {
+        rest.strip_prefix(marker)
+            .is_some_and(|after| after.is_empty() || after.starts_with(char::is_whitespace))
+    })
+}
+
+/// The byte ranges of the completed task items of each file, children included
+#[derive(Debug, Default, Clone)]
+pub struct CompletedTasks(pub BTreeMap<PathBuf, Vec<Range<usize>>>);
+
+impl CompletedTasks {
+    /// Whether `offset` in `path` is inside a completed task item
+    #[must_use]
+    pub fn contains(&self, path: &Path, offset: usize) -> bool {
+        self.0
+            .get(path)
+            .is_some_and(|ranges| ranges.iter().any(|range| range.contains(&offset)))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CompletedTaskVisitor {
+    pub completed_tasks: CompletedTasks,
+    ranges: Vec<Range<usize>>,
+}
+
+impl CompletedTaskVisitor {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Visitor for CompletedTaskVisitor {
+    fn name(&self) -> &'static str {
+        "CompletedTaskVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let sourcepos = data_ref.sourcepos;
+        // Items are task items when the tasklist extension is on
+        if let NodeValue::Item(_) | NodeValue::TaskItem(..) = data_ref.value {
+            let start = source.start(sourcepos);
+            let line = source[start..].lines().next().unwrap_or_default();
+            if is_completed(line) {
+                let end = source.end(sourcepos);
+                self.ranges.push(start..end.max(start + line.len()));
+            }
+        }
+        Ok(())
+    }
+    fn _finalize_file(&mut self, _source: &str, path: &Path) -> Result<(), FinalizeError> {
+        let ranges = std::mem::take(&mut self.ranges);
+        if !ranges.is_empty() {
+            self.completed_tasks.0.insert(path.to_path_buf(), ranges);
+        }
+        Ok(())
+    }
+    fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
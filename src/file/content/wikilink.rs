@@ -4,8 +4,15 @@ use std::{
 };
 
 use crate::{
-    file::name::Filename,
-    rules::Report,
+    file::{
+        case::{self, fold},
+        content::{
+            html::is_html,
+            source_map::SourceMap,
+            table::{cell_offset, is_in_table},
+        },
+        name::Filename,
+    },
     sed::ReplacePair,
     visitor::{VisitError, Visitor},
 };
@@ -14,26 +21,29 @@ use comrak::{
     arena_tree::Node,
     nodes::{Ast, NodeValue, NodeWikiLink},
 };
-use miette::{SourceOffset, SourceSpan};
+use miette::SourceSpan;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 /// A linkable string, like that in a wikilink, or its corresponding filename
-/// Aliases are always lowercase
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+/// Aliases are always case folded, see [`crate::file::case`]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Alias(String);
 
 impl Alias {
     #[must_use]
     pub fn new(alias: &str) -> Self {
-        Self(alias.to_lowercase())
+        Self(fold(alias))
     }
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+    /// See [`case::char_len`], the written alias it came from may have another length
     #[must_use]
     pub fn char_len(&self) -> usize {
-        self.0.chars().count()
+        case::char_len(&self.0)
     }
 }
 
@@ -63,12 +73,17 @@ impl Alias {
 pub struct Wikilink {
     pub alias: Alias,
     pub span: SourceSpan,
+    /// Written as a tag, like `#tag` or `#[[tag]]`
+    #[builder(default)]
+    pub tag: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct WikilinkVisitor {
     pub wikilinks: Vec<Wikilink>,
     tag_pattern: Regex,
+    /// Don't look for tags in text inside raw html, see [`crate::config::Config::skip_html`]
+    skip_html: bool,
 }
 
 impl Default for WikilinkVisitor {
@@ -76,66 +91,65 @@ impl Default for WikilinkVisitor {
         Self {
             wikilinks: Vec::new(),
             tag_pattern: Regex::new(r"#([A-Za-z0-9_/-]+)").expect("Constant"),
+            skip_html: true,
         }
     }
 }
 
 impl WikilinkVisitor {
     #[must_use]
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(skip_html: bool) -> Self {
+        Self {
+            skip_html,
+            ..Self::default()
+        }
     }
 }
 impl Visitor for WikilinkVisitor {
     fn name(&self) -> &'static str {
         "WikilinkVisitor"
     }
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError> {
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
         let data_ref = node.data.borrow();
         let data = &data_ref.value;
         let sourcepos = data_ref.sourcepos;
         let mut get_tags = |text: &str| {
+            let in_table = is_in_table(node);
             for captures in self.tag_pattern.captures_iter(text) {
-                let alias = Alias::new(
-                    captures
-                        .get(1)
-                        .expect("Otherwise the regex wouldn't match")
-                        .as_str(),
-                );
-                let capture_start_byte = captures
-                    .get(1)
-                    .expect("The regex has 2 capture groups")
-                    .start();
-                let sourcepos_start_offset_bytes = SourceOffset::from_location(
-                    source,
-                    sourcepos.start.line,
-                    sourcepos.start.column,
-                )
-                .offset();
+                let capture = captures.get(1).expect("The regex has 2 capture groups");
+                let alias = Alias::new(capture.as_str());
+                let mut capture_start_byte = capture.start();
+                if in_table {
+                    capture_start_byte = cell_offset(text, capture_start_byte);
+                }
+                let sourcepos_start_offset_bytes = source.start(sourcepos);
+                // The bytes of the tag as written, folding can change its length
                 let span = SourceSpan::new(
                     (sourcepos_start_offset_bytes + capture_start_byte).into(),
-                    alias.char_len(),
+                    capture.len(),
+                );
+                self.wikilinks.push(
+                    Wikilink::builder()
+                        .alias(alias.clone())
+                        .span(span)
+                        .tag(true)
+                        .build(),
                 );
-                self.wikilinks
-                    .push(Wikilink::builder().alias(alias.clone()).span(span).build());
             }
         };
         match data {
+            NodeValue::Text(_) if self.skip_html && is_html(node) => {}
             NodeValue::Text(text) => {
                 get_tags(text);
             }
             NodeValue::WikiLink(NodeWikiLink { url }) => {
+                let offset = source.start(sourcepos);
                 self.wikilinks.push(
                     Wikilink::builder()
                         .alias(Alias::new(url))
-                        .span(SourceSpan::new(
-                            SourceOffset::from_location(
-                                source,
-                                sourcepos.start.line,
-                                sourcepos.start.column,
-                            ),
-                            url.len() + 4,
-                        ))
+                        .span(SourceSpan::new(offset.into(), url.len() + 4))
+                        // Like #[[tag]]
+                        .tag(source[..offset].ends_with('#'))
                         .build(),
                 );
             }
@@ -158,8 +172,8 @@ impl Visitor for WikilinkVisitor {
     fn _finalize(
         &mut self,
         _exclude: &[crate::rules::ErrorCode],
-    ) -> Result<Vec<Report>, crate::visitor::FinalizeError> {
+    ) -> Result<crate::visitor::Finalized, crate::visitor::FinalizeError> {
         self.wikilinks.clear();
-        Ok(vec![])
+        Ok(crate::visitor::Finalized::default())
     }
 }
@@ -5,13 +5,15 @@ use std::{
 };
 
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     config::Config,
-    ngrams::{up_to_n, Ngram},
+    ngrams::{Ngram, UpToN},
+    sed::ReplacePair,
 };
 
-use super::content::wikilink::Alias;
+use super::{case::fold, content::wikilink::Alias};
 
 /// A filename is a representation of the file name in its original casing
 /// And with its original seperators
@@ -19,7 +21,8 @@ use super::content::wikilink::Alias;
 ///
 /// # Example
 /// `asdf/Foo___Bar.md` -> `Foo___Bar`
-#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Filename(pub String);
 
 impl Filename {
@@ -31,6 +34,17 @@ impl Filename {
     pub fn lowercase(&self) -> FilenameLowercase {
         FilenameLowercase::new(&self.0)
     }
+
+    /// The filename of a page titled `title`, keeping its case, unlike
+    /// [`FilenameLowercase::from_alias`], see [`Config::alias_to_filename`]
+    #[must_use]
+    pub fn from_title(title: &str, config: &Config) -> Filename {
+        let (from, to): (String, String) = config.alias_to_filename.clone().into();
+        ReplacePair::<String, Filename>::new(&from, &to).map_or_else(
+            |_| Filename::new(title),
+            |pair| pair.apply(&title.to_owned()),
+        )
+    }
 }
 
 impl Display for Filename {
@@ -52,7 +66,7 @@ pub struct FilenameLowercase(pub String);
 impl FilenameLowercase {
     #[must_use]
     pub fn new(filename: &str) -> Self {
-        Self(filename.to_owned().to_lowercase())
+        Self(fold(filename))
     }
 
     #[must_use]
@@ -74,40 +88,88 @@ impl From<String> for FilenameLowercase {
 }
 
 /// Get the filename from a path
-/// Does not include the file extension
+/// Does not include the file extension, but keeps any other dots,
+/// like in Dendron's hierarchies `lang.rust.traits.md` -> `lang.rust.traits`
 #[must_use]
 pub fn get_filename(path: &Path) -> Filename {
-    let fname = path
-        .file_name()
-        .expect("We were given a guaranteed file path, not a directory")
-        .to_string_lossy();
     Filename::new(
-        fname
-            .split('.')
-            .next()
-            .expect("File paths will either have a file extension or not, it makes no difference"),
+        &path
+            .file_stem()
+            .expect("We were given a guaranteed file path, not a directory")
+            .to_string_lossy(),
     )
 }
 
-/// Generate n-grams from the filenames found in the directories
+/// A path relative to `root`, lowercase, `/` separated and without its extension
+/// like `folder/note`, `root` should already be canonical
+#[must_use]
+pub fn path_key(path: &Path, root: &Path) -> Option<String> {
+    let relative = path.canonicalize().ok()?;
+    let relative = relative.strip_prefix(root).ok()?;
+    let mut parts = path_parts(relative);
+    parts.pop()?;
+    parts.push(get_filename(path).lowercase().0);
+    Some(parts.join("/"))
+}
+
+/// The case folded components of a path
+fn path_parts(path: &Path) -> Vec<String> {
+    path.components()
+        .map(|c| fold(&c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// Resolve a path-style link against some base directory given as its parts
+/// Returns [`None`] if the link leaves the root
+#[must_use]
+pub fn join_link(mut base: Vec<String>, link: &str) -> Option<String> {
+    let link = link.strip_suffix(".md").unwrap_or(link);
+    for part in link.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                base.pop()?;
+            }
+            part => base.push(part.to_owned()),
+        }
+    }
+    Some(base.join("/"))
+}
+
+/// Generate n-grams from the filenames found in the directories, and the files which have them
+/// Leaves out n-grams with a word matching `ignore_regex`
+/// See [`UpToN`] for `camel_case`
 #[must_use]
 pub fn ngrams(
     files: &Vec<PathBuf>,
     ngram_size: usize,
     boundary_regex: &Regex,
     filename_spacing_regex: &Regex,
-) -> HashMap<Ngram, PathBuf> {
+    ignore_regex: &Regex,
+    camel_case: bool,
+) -> HashMap<Ngram, Vec<PathBuf>> {
     let mut file_name_ngrams = HashMap::new();
     for filepath in files {
-        let filename = get_filename(filepath);
-        let ngrams = up_to_n(
-            &filename.to_string(),
+        let filename = get_filename(filepath).to_string();
+        let ngrams = UpToN::new(
+            &filename,
             ngram_size,
             boundary_regex,
             filename_spacing_regex,
+            camel_case,
         );
         for ngram in ngrams {
-            file_name_ngrams.insert(ngram, filepath.clone());
+            if ngram
+                .to_vec()
+                .iter()
+                .any(|word| ignore_regex.is_match(word))
+            {
+                continue;
+            }
+            let filepaths: &mut Vec<PathBuf> = file_name_ngrams.entry(ngram).or_default();
+            if !filepaths.contains(filepath) {
+                filepaths.push(filepath.clone());
+            }
         }
     }
     file_name_ngrams
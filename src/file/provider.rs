@@ -0,0 +1,112 @@
+//! Where the files to lint come from, so linting can run without a filesystem, like in a browser
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
+
+use super::content::{source_from_content, FromFileError};
+
+/// Lists and reads the files to lint
+///
+/// Fixes, backups and the config file still use the filesystem
+pub trait FileProvider: Send + Sync + Debug {
+    /// Every file under `directories`, skipping hidden files and directories
+    fn files(&self, directories: &[PathBuf]) -> Vec<PathBuf>;
+
+    /// The content of the file at `path`
+    fn read(&self, path: &Path) -> std::io::Result<String>;
+
+    /// The markdown source of the file at `path`, see [`super::content::read_source`]
+    fn read_source(&self, path: &Path) -> Result<String, FromFileError> {
+        source_from_content(path, self.read(path)?)
+    }
+}
+
+/// The files on disk, found with [`super::get_files`]
+#[cfg(feature = "fs")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystem;
+
+#[cfg(feature = "fs")]
+impl FileProvider for FileSystem {
+    fn files(&self, directories: &[PathBuf]) -> Vec<PathBuf> {
+        super::get_files(&directories.to_vec())
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// Files kept in memory, keyed by their path under the configured directories
+///
+/// Paths are compared as they are given, they are never canonicalized.
+/// Rules which resolve links by path, rather than by alias, only work on the filesystem
+#[derive(Debug, Default, Clone)]
+pub struct InMemory {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl InMemory {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file, replacing any file already at `path`
+    #[must_use]
+    pub fn with_file(mut self, path: impl Into<PathBuf>, content: impl Into<String>) -> Self {
+        self.insert(path, content);
+        self
+    }
+
+    /// Add a file, returning the content of any file already at `path`
+    pub fn insert(
+        &mut self,
+        path: impl Into<PathBuf>,
+        content: impl Into<String>,
+    ) -> Option<String> {
+        self.files.insert(path.into(), content.into())
+    }
+}
+
+impl<P: Into<PathBuf>, C: Into<String>> FromIterator<(P, C)> for InMemory {
+    fn from_iter<T: IntoIterator<Item = (P, C)>>(iter: T) -> Self {
+        Self {
+            files: iter
+                .into_iter()
+                .map(|(path, content)| (path.into(), content.into()))
+                .collect(),
+        }
+    }
+}
+
+impl FileProvider for InMemory {
+    fn files(&self, directories: &[PathBuf]) -> Vec<PathBuf> {
+        let mut out = Vec::new();
+        for directory in directories {
+            for path in self.files.keys() {
+                let Ok(relative) = path.strip_prefix(directory) else {
+                    continue;
+                };
+                let hidden = relative
+                    .components()
+                    .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+                if !hidden && !out.contains(path) {
+                    out.push(path.clone());
+                }
+            }
+        }
+        out
+    }
+
+    fn read(&self, path: &Path) -> std::io::Result<String> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("{} is not in memory", path.display()),
+            )
+        })
+    }
+}
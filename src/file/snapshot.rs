@@ -0,0 +1,191 @@
+//! The files of a vault before and after `--fix --dry-run`, and what the fixes changed, see
+//! [`FixStats`]
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::FixError;
+
+fn io_error(path: &Path) -> impl FnOnce(std::io::Error) -> FixError + '_ {
+    move |source| FixError::IOError {
+        source,
+        backtrace: std::backtrace::Backtrace::force_capture(),
+        file: path.to_string_lossy().to_string(),
+    }
+}
+
+/// The contents of every file under some directories
+/// Hidden files and directories, like `.git` or backups, are skipped like when linting
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Snapshot(BTreeMap<PathBuf, Vec<u8>>);
+
+impl Snapshot {
+    /// Read every file under `directories`, which may not exist yet
+    ///
+    /// # Errors
+    ///
+    /// If a file or directory can't be read
+    pub fn take(directories: &[PathBuf]) -> Result<Self, FixError> {
+        let mut snapshot = Self::default();
+        let mut stack: Vec<PathBuf> = directories
+            .iter()
+            .filter(|directory| directory.is_dir())
+            .cloned()
+            .collect();
+        while let Some(directory) = stack.pop() {
+            for entry in std::fs::read_dir(&directory).map_err(io_error(&directory))? {
+                let path = entry.map_err(io_error(&directory))?.path();
+                let hidden = path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+                if hidden {
+                    continue;
+                }
+                if path.is_dir() {
+                    stack.push(path);
+                } else if let Entry::Vacant(entry) = snapshot.0.entry(path) {
+                    let contents = std::fs::read(entry.key()).map_err(io_error(entry.key()))?;
+                    entry.insert(contents);
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// The files which are different in `after`, including those created or removed
+    #[must_use]
+    pub fn changed(&self, after: &Self) -> BTreeSet<PathBuf> {
+        let removed_or_modified = self
+            .0
+            .iter()
+            .filter(|(path, contents)| after.0.get(*path) != Some(*contents))
+            .map(|(path, _)| path.clone());
+        let created = after
+            .0
+            .keys()
+            .filter(|path| !self.0.contains_key(*path))
+            .cloned();
+        removed_or_modified.chain(created).collect()
+    }
+
+    /// Put the files back like they were in this snapshot, given how they are now in `after`
+    /// Files in `after` which are not in this are removed, the directories made for them are kept
+    ///
+    /// # Errors
+    ///
+    /// If a file can't be written or removed
+    pub fn restore(&self, after: &Self) -> Result<(), FixError> {
+        for path in after.0.keys().filter(|path| !self.0.contains_key(*path)) {
+            std::fs::remove_file(path).map_err(io_error(path))?;
+        }
+        for (path, contents) in &self.0 {
+            if after.0.get(path) == Some(contents) {
+                continue;
+            }
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory).map_err(io_error(directory))?;
+            }
+            std::fs::write(path, contents).map_err(io_error(path))?;
+        }
+        Ok(())
+    }
+}
+
+/// How many files fixes created, modified and deleted, and how many lines they inserted and
+/// deleted, like `git diff --shortstat`
+/// A renamed file is one created and one deleted
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FixStats {
+    pub created: usize,
+    pub modified: usize,
+    pub deleted: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl FixStats {
+    /// What changed from `before` to `after`
+    #[must_use]
+    pub fn new(before: &Snapshot, after: &Snapshot) -> Self {
+        let mut stats = Self::default();
+        for (path, old) in &before.0 {
+            match after.0.get(path) {
+                None => {
+                    stats.deleted += 1;
+                    stats.deletions += lines(old).len();
+                }
+                Some(new) if new != old => {
+                    stats.modified += 1;
+                    let (insertions, deletions) = changed_lines(&lines(old), &lines(new));
+                    stats.insertions += insertions;
+                    stats.deletions += deletions;
+                }
+                Some(_) => {}
+            }
+        }
+        for (path, new) in &after.0 {
+            if !before.0.contains_key(path) {
+                stats.created += 1;
+                stats.insertions += lines(new).len();
+            }
+        }
+        stats
+    }
+
+    /// Whether the fixes changed nothing
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl Display for FixStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} files created, {} modified, {} deleted, {} insertions(+), {} deletions(-)",
+            self.created, self.modified, self.deleted, self.insertions, self.deletions
+        )
+    }
+}
+
+fn lines(contents: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(contents)
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+/// The lines inserted and deleted from `old` to `new`, which are the lines not in their longest
+/// common subsequence
+fn changed_lines(old: &[String], new: &[String]) -> (usize, usize) {
+    // Fixes usually change a few lines, so only the lines between those which are the same at the
+    // start and end are compared
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old, new) = (&old[prefix..], &new[prefix..]);
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old, new) = (&old[..old.len() - suffix], &new[..new.len() - suffix]);
+    let mut previous = vec![0; new.len() + 1];
+    for a in old {
+        let mut current = vec![0; new.len() + 1];
+        for (j, b) in new.iter().enumerate() {
+            current[j + 1] = if a == b {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        previous = current;
+    }
+    let common = previous[new.len()];
+    (new.len() - common, old.len() - common)
+}
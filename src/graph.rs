@@ -0,0 +1,189 @@
+//! The graph of pages linking to each other, see `mdlinker graph`
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    backlinks::Backlinks,
+    config::Config,
+    file::{
+        content::wikilink::Alias,
+        name::{get_filename, FilenameLowercase},
+    },
+};
+
+/// A page in the [`Graph`]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    /// The path of the page, unique in the graph
+    pub id: PathBuf,
+    /// The filename of the page, without its extension
+    pub label: String,
+}
+
+/// One or more wikilinks (or tags) from one page to another
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    /// The distinct aliases the links used, empty unless labels were asked for
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+/// Nodes are pages, edges are the wikilinks and tags between them
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Escape a string to put it in double quotes in dot
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape a string to put it in xml text or attributes
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Graph {
+    /// Builds the graph from the [`Backlinks`] of every page
+    ///
+    /// If `labels` is set, edges keep the aliases used to link the pages.
+    /// If a `namespace` is given, like `lorem` or `lorem/ipsum`, only pages in it (and the namespace page itself)
+    /// and the edges between them are kept
+    #[must_use]
+    pub fn new(
+        backlinks: &Backlinks,
+        config: &Config,
+        labels: bool,
+        namespace: Option<&str>,
+    ) -> Self {
+        let namespace = namespace
+            .map(|namespace| FilenameLowercase::from_alias(&Alias::new(namespace), config).0);
+        let in_namespace = |page: &PathBuf| match &namespace {
+            None => true,
+            Some(namespace) => {
+                let filename = get_filename(page).lowercase().0;
+                filename == *namespace
+                    || filename.starts_with(&format!("{namespace}{}", config.hierarchy_separator))
+            }
+        };
+        let nodes = backlinks
+            .0
+            .keys()
+            .filter(|page| in_namespace(page))
+            .map(|page| GraphNode {
+                id: page.clone(),
+                label: get_filename(page).0,
+            })
+            .collect();
+        let mut edges: BTreeMap<(PathBuf, PathBuf), Vec<String>> = BTreeMap::new();
+        for (target, links) in &backlinks.0 {
+            if !in_namespace(target) {
+                continue;
+            }
+            for link in links.iter().filter(|link| in_namespace(&link.source)) {
+                let aliases = edges
+                    .entry((link.source.clone(), target.clone()))
+                    .or_default();
+                if labels && !aliases.contains(&link.alias) {
+                    aliases.push(link.alias.clone());
+                }
+            }
+        }
+        Self {
+            nodes,
+            edges: edges
+                .into_iter()
+                .map(|((source, target), aliases)| GraphEdge {
+                    source,
+                    target,
+                    aliases,
+                })
+                .collect(),
+        }
+    }
+
+    /// Serialize as a [Graphviz](https://graphviz.org/) dot digraph
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph mdlinker {\n");
+        for node in &self.nodes {
+            writeln!(
+                out,
+                "  \"{}\" [label=\"{}\"];",
+                escape_dot(&node.id.to_string_lossy()),
+                escape_dot(&node.label)
+            )
+            .expect("Writing to a String never fails");
+        }
+        for edge in &self.edges {
+            let label = if edge.aliases.is_empty() {
+                String::new()
+            } else {
+                format!(" [label=\"{}\"]", escape_dot(&edge.aliases.join(", ")))
+            };
+            writeln!(
+                out,
+                "  \"{}\" -> \"{}\"{label};",
+                escape_dot(&edge.source.to_string_lossy()),
+                escape_dot(&edge.target.to_string_lossy())
+            )
+            .expect("Writing to a String never fails");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Serialize as [GraphML](http://graphml.graphdrawing.org/), which Gephi can open
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"aliases\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"mdlinker\" edgedefault=\"directed\">\n",
+        ));
+        for node in &self.nodes {
+            writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"label\">{}</data></node>",
+                escape_xml(&node.id.to_string_lossy()),
+                escape_xml(&node.label)
+            )
+            .expect("Writing to a String never fails");
+        }
+        for edge in &self.edges {
+            let source = escape_xml(&edge.source.to_string_lossy());
+            let target = escape_xml(&edge.target.to_string_lossy());
+            if edge.aliases.is_empty() {
+                writeln!(out, "    <edge source=\"{source}\" target=\"{target}\"/>")
+            } else {
+                writeln!(
+                    out,
+                    "    <edge source=\"{source}\" target=\"{target}\"><data key=\"aliases\">{}</data></edge>",
+                    escape_xml(&edge.aliases.join(", "))
+                )
+            }
+            .expect("Writing to a String never fails");
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Serialize as pretty printed json
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails, which it shouldn't
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
@@ -0,0 +1,161 @@
+//! The aliases and links of a vault, saved to `.mdlinker/index.json` in the root directory for
+//! other tools, and read back while no file has changed, see [`Config::write_index`]
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    config::{file, Config},
+    file::content::wikilink::Alias,
+    link_index::LinkIndex,
+    rules::Fingerprint,
+    OutputReport,
+};
+
+/// The directory of the index in the root directory
+pub const INDEX_DIRECTORY: &str = ".mdlinker";
+
+/// The name of the index in [`INDEX_DIRECTORY`]
+pub const INDEX_FILE: &str = "index.json";
+
+#[derive(Error, Debug, Diagnostic)]
+pub enum IndexFileError {
+    #[error("Failed to write the index to {path}")]
+    IOError {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Failed to serialize the index")]
+    SerdeError(#[from] serde_json::Error),
+}
+
+/// The size and modification time of a file, to tell if it changed since the index was written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub len: u64,
+    /// Nanoseconds since the unix epoch, if the platform has modification times
+    pub modified: Option<u128>,
+}
+
+impl FileStamp {
+    /// The stamp of the file now, [`None`] if it can't be read
+    #[must_use]
+    pub fn new(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_nanos()),
+        })
+    }
+}
+
+/// The alias table and link index of a run, and the files they were made from
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexFile {
+    /// The version of mdlinker which wrote it, an index from another version is not read
+    pub version: String,
+    /// The hash of the options the index was made with, see [`config_hash`]
+    pub config: Fingerprint,
+    /// Every linted file and the config files, when the index was written
+    pub files: BTreeMap<PathBuf, FileStamp>,
+    /// See [`OutputReport::alias_table`]
+    pub alias_table: BTreeMap<Alias, PathBuf>,
+    /// See [`OutputReport::link_index`]
+    pub link_index: LinkIndex,
+}
+
+impl IndexFile {
+    /// The index of `report`, whose link index has every linted file
+    #[must_use]
+    pub fn new(report: &OutputReport, config: &Config) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            config: config_hash(config),
+            files: stamps(config, report.link_index().0.keys()),
+            alias_table: report.alias_table().clone(),
+            link_index: report.link_index().clone(),
+        }
+    }
+
+    /// Where the index of the vault is, `.mdlinker/index.json` in the root directory
+    #[must_use]
+    pub fn path(config: &Config) -> PathBuf {
+        config.root_directory.join(INDEX_DIRECTORY).join(INDEX_FILE)
+    }
+
+    /// Write the index to [`IndexFile::path`], making its directory if needed
+    ///
+    /// # Errors
+    ///
+    /// If the index can't be written
+    pub fn write(&self, config: &Config) -> Result<(), IndexFileError> {
+        let path = Self::path(config);
+        let io_error = |source| IndexFileError::IOError {
+            path: path.clone(),
+            source,
+        };
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(io_error)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?).map_err(io_error)
+    }
+
+    /// The index at [`IndexFile::path`], if this version of mdlinker wrote it with the same
+    /// options, and neither `all_files` nor the config files changed since
+    #[must_use]
+    pub fn read(config: &Config, all_files: &[PathBuf]) -> Option<Self> {
+        let index: Self =
+            serde_json::from_str(&std::fs::read_to_string(Self::path(config)).ok()?).ok()?;
+        let fresh = index.version == env!("CARGO_PKG_VERSION")
+            && index.config == config_hash(config)
+            && index.files == stamps(config, all_files.iter());
+        fresh.then_some(index)
+    }
+}
+
+/// The hash of the options of `config` which change the files which are linted and what is found
+/// in them, however they were given, so an index made with other options is not read
+/// Options of the run, like `fix` or the output format, don't change the index and are left out
+fn config_hash(config: &Config) -> Fingerprint {
+    let options: [&dyn Debug; 6] = [
+        &config.pages_directory,
+        &config.other_directories,
+        &config.ignore_paths,
+        &config.filename_to_alias,
+        &config.markdown,
+        &config.skip_html,
+    ];
+    let options: Vec<String> = options.iter().map(|option| format!("{option:?}")).collect();
+    Fingerprint::new(
+        "config",
+        &options.iter().map(String::as_str).collect::<Vec<_>>(),
+    )
+}
+
+/// The stamps of `files` and the config files which exist, files which can't be read are left out
+fn stamps<'a>(
+    config: &Config,
+    files: impl Iterator<Item = &'a PathBuf>,
+) -> BTreeMap<PathBuf, FileStamp> {
+    let config_files = [
+        Some(config.config_path().to_path_buf()),
+        file::global_path(),
+    ];
+    files
+        .cloned()
+        .chain(config_files.into_iter().flatten())
+        .filter_map(|path| FileStamp::new(&path).map(|stamp| (path, stamp)))
+        .collect()
+}
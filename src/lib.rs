@@ -1,47 +1,199 @@
-#![feature(error_generic_member_access)]
+#![cfg_attr(feature = "nightly", feature(error_generic_member_access))]
 
+pub mod alias_index;
+pub mod aliases;
+pub mod backlinks;
+pub mod budget;
 pub mod config;
+pub mod diff;
+pub mod export;
 pub mod file;
+pub mod graph;
+#[cfg(feature = "fs")]
+pub mod index_file;
+pub mod link_index;
+pub mod linter;
+#[cfg(feature = "fs")]
+pub mod merge;
+#[cfg(feature = "fs")]
+pub mod mv;
+#[cfg(feature = "fs")]
+pub mod new;
 pub mod ngrams;
+pub mod plan;
+pub mod progress;
+pub mod query;
+#[cfg(feature = "review")]
+pub mod review;
 pub mod rules;
 pub mod sed;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+pub mod tune;
 pub mod visitor;
 
-use console::{style, Emoji};
-use file::{get_files, name::ngrams};
-use indicatif::ProgressBar;
-use miette::{Diagnostic, Result};
+use alias_index::{AliasIndex, AliasIndexVisitor};
+use budget::{Budget, BudgetedVisitor, SourceBudget};
+use file::content::{task::CompletedTaskVisitor, wikilink::Alias};
+use file::{
+    backup::Backup,
+    name::ngrams,
+    provider::FileProvider,
+    snapshot::{FixStats, Snapshot},
+};
+use link_index::{LinkIndex, LinkIndexVisitor};
+use linter::Linter;
+use miette::{Diagnostic, Result, Severity};
 use ngrams::CalculateError;
+use plan::{Input, Inputs, Plan};
+use progress::{Logged, Pass, ProgressSink};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rules::{
-    broken_wikilink::BrokenWikilinkVisitor, duplicate_alias::DuplicateAliasVisitor,
-    similar_filename::SimilarFilename, Report, ReportTrait, ThirdPassRule,
+    duplicate_alias::DuplicateAliasVisitor, expired_exclude::ExpiredExclude,
+    similar_filename::SimilarFilename, ErrorCode, Report,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::PathBuf,
+    rc::Rc,
+    sync::Arc,
 };
-use std::{backtrace::Backtrace, cell::RefCell, env, rc::Rc};
-use strum::IntoEnumIterator;
 use thiserror::Error;
-use visitor::{parse, FinalizeError, ParseError, Visitor};
+use visitor::{parse, FinalizeError, Finalized, ParseError, Visitor};
 
 use crate::rules::VecHasIdExtensions;
 
+/// A [`Backtrace`] kept in an error
+///
+/// thiserror provides fields it recognizes as backtraces through `Error::provide`, which needs nightly.
+/// Under this name it only does so when the `nightly` feature marks them with `#[backtrace]`
+pub type CapturedBacktrace = Backtrace;
+
 /// A miette diagnostic that controls the printout of errors to the user
 /// Put a vector of all outputs in a new field with a #[related] macro above it
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 pub struct OutputReport {
     pub reports: Vec<Report>,
+    /// See [`OutputReport::alias_table`]
+    #[serde(default)]
+    alias_table: BTreeMap<Alias, PathBuf>,
+    /// See [`OutputReport::link_index`]
+    #[serde(default)]
+    link_index: LinkIndex,
+    /// See [`OutputReport::alias_index`], made again by each run rather than saved
+    #[serde(skip)]
+    alias_index: AliasIndex,
+    /// The ids of the reports inside a completed task, see [`config::Config::completed_tasks`]
+    #[serde(default)]
+    completed_tasks: BTreeSet<ErrorCode>,
+    /// See [`OutputReport::fix_stats`]
+    #[serde(default)]
+    fix_stats: Option<FixStats>,
+    /// See [`OutputReport::fix_conflicts`]
+    #[serde(default)]
+    fix_conflicts: Vec<rules::FixConflict>,
+    /// See [`OutputReport::non_idempotent_fixes`]
+    #[serde(default)]
+    non_idempotent_fixes: Vec<rules::NonIdempotentFix>,
+    /// See [`OutputReport::timed_out`]
+    #[serde(default)]
+    timed_out: Vec<budget::TimedOut>,
+    /// See [`OutputReport::elided_sources`]
+    #[serde(default)]
+    elided_sources: usize,
+    /// See [`OutputReport::matched_excludes`]
+    #[serde(default)]
+    matched_excludes: rules::MatchedExcludes,
 }
 
-static FIRST_PASS: Emoji<'_, '_> = Emoji("📃  ", "");
-static SECOND_PASS: Emoji<'_, '_> = Emoji("🔗  ", "");
-static CHECK: Emoji<'_, '_> = Emoji("🔍  ", "");
-static FIXES: Emoji<'_, '_> = Emoji("🔧  ", "");
-static CHECK_AGAIN: Emoji<'_, '_> = Emoji("💡  ", "");
-static NO_FIXES: Emoji<'_, '_> = Emoji("🎉  ", "");
-
 impl OutputReport {
     /// Get if this is empty
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.reports.is_empty()
     }
+    /// See [`Report::without_source`]
+    #[must_use]
+    pub fn without_sources(self) -> Self {
+        Self {
+            reports: self
+                .reports
+                .into_iter()
+                .map(Report::without_source)
+                .collect(),
+            ..self
+        }
+    }
+    /// Every alias, and the file wikilinks to it resolve to
+    /// This includes the aliases of filenames as well as those declared in front matter
+    #[must_use]
+    pub fn alias_table(&self) -> &BTreeMap<Alias, PathBuf> {
+        &self.alias_table
+    }
+    /// Every file, and the wikilinks and tags out of it
+    #[must_use]
+    pub fn link_index(&self) -> &LinkIndex {
+        &self.link_index
+    }
+    /// Where each alias is defined, linked, tagged and mentioned
+    #[must_use]
+    pub fn alias_index(&self) -> &AliasIndex {
+        &self.alias_index
+    }
+    /// What the fixes would have changed, with [`config::Config::dry_run`]
+    /// The reports are those before fixing, as nothing was changed
+    #[must_use]
+    pub fn fix_stats(&self) -> Option<FixStats> {
+        self.fix_stats
+    }
+    /// The fixes which were not made, as they conflict with others, see
+    /// [`rules::without_conflicts`]
+    #[must_use]
+    pub fn fix_conflicts(&self) -> &[rules::FixConflict] {
+        &self.fix_conflicts
+    }
+    /// The fixes which are not idempotent, with [`config::Config::verify`]
+    #[must_use]
+    pub fn non_idempotent_fixes(&self) -> &[rules::NonIdempotentFix] {
+        &self.non_idempotent_fixes
+    }
+    /// The rules which were stopped as they ran out of time, see [`config::Config::rule_timeout`]
+    #[must_use]
+    pub fn timed_out(&self) -> &[budget::TimedOut] {
+        &self.timed_out
+    }
+    /// How many reports were kept without the content of their file, as the others had already
+    /// kept [`config::Config::source_memory_limit`] of it, see [`budget::SourceBudget`]
+    #[must_use]
+    pub fn elided_sources(&self) -> usize {
+        self.elided_sources
+    }
+    /// The excludes and word pairs of the config which matched a report, see
+    /// [`linter::Linter::unused_excludes`]
+    #[must_use]
+    pub fn matched_excludes(&self) -> &rules::MatchedExcludes {
+        &self.matched_excludes
+    }
+    /// Whether the report is inside a completed task, like `- [x] Call [[Alice]]`
+    /// Only known when [`config::Config::completed_tasks`] is set
+    #[must_use]
+    pub fn in_completed_task(&self, report: &Report) -> bool {
+        self.completed_tasks.contains(&report.inner().id())
+    }
+    /// The severity of the report, see [`config::Config::severity`], or that of
+    /// [`config::Config::completed_tasks`] if it is inside a completed task
+    #[must_use]
+    pub fn severity(&self, config: &config::Config, report: &Report) -> Severity {
+        config
+            .completed_tasks
+            .filter(|_| self.in_completed_task(report))
+            .and_then(config::NamespaceRule::severity)
+            .unwrap_or_else(|| config.severity(report))
+    }
     #[must_use]
     pub fn broken_wikilinks(&self) -> Vec<rules::broken_wikilink::BrokenWikilink> {
         self.reports
@@ -53,6 +205,178 @@ impl OutputReport {
             .collect()
     }
     #[must_use]
+    pub fn broken_relrefs(&self) -> Vec<rules::broken_relref::BrokenRelref> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::BrokenRelref(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn ambiguous_wikilinks(&self) -> Vec<rules::ambiguous_wikilink::AmbiguousWikilink> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::AmbiguousWikilink(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn relative_wikilinks(&self) -> Vec<rules::relative_wikilink::RelativeWikilink> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::RelativeWikilink(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn duplicate_properties(&self) -> Vec<rules::duplicate_property::DuplicateProperty> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::DuplicateProperty(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn duplicate_contents(&self) -> Vec<rules::duplicate_content::DuplicateContent> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::DuplicateContent(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn ignored_wikilinks(&self) -> Vec<rules::ignored_wikilink::IgnoredWikilink> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::IgnoredWikilink(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn hub_pages(&self) -> Vec<rules::hub_page::HubPage> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::HubPage(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn underlinked_pages(&self) -> Vec<rules::underlinked_page::UnderlinkedPage> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::UnderlinkedPage(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn alias_casings(&self) -> Vec<rules::alias_casing::AliasCasing> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::AliasCasing(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn confusable_characters(&self) -> Vec<rules::confusable_character::ConfusableCharacter> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::ConfusableCharacter(x)) => {
+                    Some(x.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn illegal_filenames(&self) -> Vec<rules::illegal_filename::IllegalFilename> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::IllegalFilename(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn front_matter_keys(&self) -> Vec<rules::front_matter_key::FrontMatterKey> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::FrontMatterKey(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn wrong_link_styles(&self) -> Vec<rules::wrong_link_style::WrongLinkStyle> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::WrongLinkStyle(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn code_wikilinks(&self) -> Vec<rules::code_wikilink::CodeWikilink> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::CodeWikilink(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn broken_references(&self) -> Vec<rules::broken_reference::BrokenReference> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::BrokenReference(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn expired_excludes(&self) -> Vec<rules::expired_exclude::ExpiredExclude> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::ExpiredExclude(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
+    pub fn unknown_tags(&self) -> Vec<rules::unknown_tag::UnknownTag> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::ThirdPass(rules::ThirdPassReport::UnknownTag(x)) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
     pub fn unlinked_texts(&self) -> Vec<rules::unlinked_text::UnlinkedText> {
         self.reports
             .iter()
@@ -73,6 +397,16 @@ impl OutputReport {
             .collect()
     }
     #[must_use]
+    pub fn custom_reports(&self) -> Vec<rules::custom::CustomReport> {
+        self.reports
+            .iter()
+            .filter_map(|x| match x {
+                Report::Custom(x) => Some(x.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+    #[must_use]
     pub fn similar_filenames(&self) -> Vec<rules::similar_filename::SimilarFilename> {
         self.reports
             .iter()
@@ -96,10 +430,14 @@ pub enum OutputErrors {
     FinalizeError(#[from] FinalizeError),
     #[error(transparent)]
     FixError(#[from] rules::FixError),
+    #[error(transparent)]
+    AhoBuildError(#[from] aho_corasick::BuildError),
 }
 
+#[cfg(feature = "git")]
 use git2::{Error, Repository, StatusOptions};
 
+#[cfg(feature = "git")]
 fn is_repo_dirty(repo: &Repository) -> Result<bool, Error> {
     let mut options = StatusOptions::new();
     options
@@ -113,9 +451,9 @@ fn is_repo_dirty(repo: &Repository) -> Result<bool, Error> {
     Ok(!statuses.is_empty())
 }
 
-/// Runs [`check`] in a loop until no more fixes can be made
-fn fix(config: &config::Config) -> Result<OutputReport, OutputErrors> {
-    // Check if the git repo is dirty
+/// Refuse to fix if the git repo has changes which fixes would get mixed up with
+#[cfg(feature = "git")]
+fn check_repo(config: &config::Config) -> Result<(), OutputErrors> {
     match git2::Repository::open_from_env() {
         Ok(git) => match is_repo_dirty(&git) {
             Ok(is_dirty) => {
@@ -139,184 +477,491 @@ fn fix(config: &config::Config) -> Result<OutputReport, OutputErrors> {
             }));
         }
     }
-    if env::var("RUNNING_TESTS").is_err() {
-        println!(
-            "{} {}Generating Error Reports...",
-            style("[1/3]").bold().dim(),
-            CHECK
-        );
-    };
-
-    let mut output_report = check(config)?;
+    Ok(())
+}
 
-    let bar: Option<ProgressBar> = if env::var("RUNNING_TESTS").is_ok() {
-        None
+/// Fixes are made in a clean git repo, so they can be reviewed and undone with git.
+/// With [`config::Config::no_vcs`], or without the `git` feature, files are backed up instead,
+/// so this returns where
+fn prepare_fixes(config: &config::Config) -> Result<Option<Backup>, OutputErrors> {
+    if config.no_vcs || cfg!(not(feature = "git")) {
+        Ok(Some(Backup::new()))
     } else {
-        println!(
-            "{} {}Performing Fixes...",
-            style("[2/3]").bold().dim(),
-            FIXES
-        );
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        Some(ProgressBar::new(output_report.reports.len() as u64))
-    };
+        #[cfg(feature = "git")]
+        check_repo(config)?;
+        Ok(None)
+    }
+}
 
-    let mut any_fixes = false;
-    for report in output_report.reports.clone() {
-        if let Some(()) = match report {
-            Report::DuplicateAlias(report) => report.fix(config)?,
-            Report::SimilarFilename(report) => report.fix(config)?,
-            Report::ThirdPass(rules::ThirdPassReport::BrokenWikilink(report)) => {
-                report.fix(config)?
-            }
-            Report::ThirdPass(rules::ThirdPassReport::UnlinkedText(report)) => {
-                report.fix(config)?
-            }
-        } {
-            any_fixes = true;
-        }
-        if let Some(bar) = &bar {
-            bar.inc(1);
+/// Runs [`check`] in a loop until no more fixes can be made, see [`prepare_fixes`]
+fn fix(linter: &Linter) -> Result<OutputReport, OutputErrors> {
+    let config = linter.config();
+    let mut backup = prepare_fixes(config)?;
+    let progress = &Logged::new(linter.progress());
+    progress.pass_started(Pass::Check, None);
+    let mut output_report = check(linter)?;
+    progress.pass_finished(Pass::Check);
+
+    progress.pass_started(Pass::Fixes, Some(output_report.reports.len() as u64));
+
+    let (to_fix, fix_conflicts) = rules::without_conflicts(&output_report.reports);
+    if config.dry_run {
+        let fix_stats = dry_run(&to_fix, config, progress)?;
+        output_report.fix_stats = Some(fix_stats);
+        output_report.fix_conflicts = fix_conflicts;
+        progress.pass_finished(Pass::Fixes);
+        return Ok(output_report);
+    }
+    if let Some(backup) = &mut backup {
+        for path in to_fix.iter().filter_map(|report| report.inner().edits()) {
+            backup.save(&path)?;
         }
     }
-    if let Some(bar) = bar {
-        bar.finish_and_clear();
+    let fixed = apply_fixes(&to_fix, config, progress)?;
+    progress.pass_finished(Pass::Fixes);
+
+    if fixed.is_empty() {
+        progress.pass_started(Pass::NoFixes, None);
+        progress.pass_finished(Pass::NoFixes);
+    } else {
+        progress.pass_started(Pass::CheckAgain, None);
+        output_report = check(linter)?;
+        progress.pass_finished(Pass::CheckAgain);
+    }
+    if config.verify {
+        output_report.non_idempotent_fixes =
+            verify(&fixed, &output_report.reports, config, progress)?;
     }
+    output_report.fix_conflicts = fix_conflicts;
+
+    Ok(output_report)
+}
 
-    if any_fixes {
-        if env::var("RUNNING_TESTS").is_err() {
-            println!(
-                "{} {}Generating Error Reports After Fixes Applied...",
-                style("[3/3]").bold().dim(),
-                CHECK_AGAIN
-            );
+/// The directories fixes can change, which are those linted and those new files are made in
+fn fix_directories(config: &config::Config) -> Vec<PathBuf> {
+    let mut directories = config.directories();
+    directories.extend(config.new_files_directory.clone());
+    directories.extend(
+        config
+            .new_files_directories
+            .iter()
+            .filter_map(|new_files_directory| new_files_directory.directory.clone()),
+    );
+    directories
+}
+
+/// Finds the fixes which are not idempotent, see [`config::Config::verify`]
+/// `fixed` are the ids of the reports which were just fixed, and `remaining` the reports found
+/// after fixing
+/// The remaining reports which were fixed are fixed a second time, which should change nothing,
+/// then every file is put back
+fn verify(
+    fixed: &BTreeSet<ErrorCode>,
+    remaining: &[Report],
+    config: &config::Config,
+    progress: &dyn ProgressSink,
+) -> Result<Vec<rules::NonIdempotentFix>, rules::FixError> {
+    let reappeared: BTreeSet<ErrorCode> = remaining
+        .iter()
+        .map(|report| report.inner().id())
+        .filter(|id| fixed.contains(id))
+        .collect();
+    let mut non_idempotent_fixes: Vec<rules::NonIdempotentFix> = reappeared
+        .into_iter()
+        .map(|code| rules::NonIdempotentFix {
+            code,
+            reason: rules::NonIdempotence::Reappeared,
+        })
+        .collect();
+
+    // Reports which first appeared after fixing, or were skipped due to a conflict, would be
+    // fixed for the first time, which may change files without a fix being wrong
+    let (to_fix, _) = rules::without_conflicts(remaining);
+    let to_fix: Vec<&Report> = to_fix
+        .into_iter()
+        .filter(|report| fixed.contains(&report.inner().id()))
+        .collect();
+    let directories = fix_directories(config);
+    let before = Snapshot::take(&directories)?;
+    // Put the files back even if a fix failed
+    let fixed_again = apply_fixes(&to_fix, config, progress);
+    let after = Snapshot::take(&directories)?;
+    before.restore(&after)?;
+    let fixed_again = fixed_again?;
+    for path in before.changed(&after) {
+        let refixed = to_fix
+            .iter()
+            .filter(|report| fixed_again.contains(&report.inner().id()));
+        // Fixes which only create, rename or delete files may have changed any file
+        let edited: Vec<&&Report> = refixed
+            .clone()
+            .filter(|report| report.inner().edits().as_ref() == Some(&path))
+            .collect();
+        let culprits = if edited.is_empty() {
+            refixed
+                .filter(|report| report.inner().edits().is_none())
+                .collect()
+        } else {
+            edited
         };
-        output_report = check(config)?;
-    } else if env::var("RUNNING_TESTS").is_err() {
-        println!(
-            "{} {}No Fixes Found...",
-            style("[3/3]").bold().dim(),
-            NO_FIXES
-        );
+        non_idempotent_fixes.extend(culprits.into_iter().map(|report| rules::NonIdempotentFix {
+            code: report.inner().id(),
+            reason: rules::NonIdempotence::ChangedAgain(path.clone()),
+        }));
     }
+    Ok(non_idempotent_fixes)
+}
 
-    Ok(output_report)
+/// Applies the fixes, counts what they changed in the directories they can change, then puts
+/// every file back, see [`config::Config::dry_run`]
+fn dry_run(
+    reports: &[&Report],
+    config: &config::Config,
+    progress: &dyn ProgressSink,
+) -> Result<FixStats, rules::FixError> {
+    let directories = fix_directories(config);
+    let before = Snapshot::take(&directories)?;
+    // Put the files back even if a fix failed
+    let fixed = apply_fixes(reports, config, progress);
+    let after = Snapshot::take(&directories)?;
+    before.restore(&after)?;
+    fixed?;
+    Ok(FixStats::new(&before, &after))
 }
 
-fn check(config: &config::Config) -> Result<OutputReport, OutputErrors> {
-    // Compile our regex patterns
-    let boundary_regex = regex::Regex::new(&config.boundary_pattern)?;
-    let filename_spacing_regex = regex::Regex::new(&config.filename_spacing_pattern)?;
+/// Fixes the reports which edit the same file one after another, in the order they are in,
+/// and those of different files in parallel
+/// The reports which only create, rename or delete files, see [`rules::ReportTrait::edits`], are fixed
+/// one after another at the end, as they may touch any file
+/// Returns the ids of the reports which were fixed
+fn apply_fixes(
+    reports: &[&Report],
+    config: &config::Config,
+    progress: &dyn ProgressSink,
+) -> Result<BTreeSet<ErrorCode>, rules::FixError> {
+    let mut by_file: BTreeMap<PathBuf, Vec<&Report>> = BTreeMap::new();
+    let mut others = vec![];
+    for &report in reports {
+        match report.inner().edits() {
+            Some(path) => by_file.entry(path).or_default().push(report),
+            None => others.push(report),
+        }
+    }
+    let fix_all = |reports: Vec<&Report>| -> Result<BTreeSet<ErrorCode>, rules::FixError> {
+        let mut fixed = BTreeSet::new();
+        for report in reports {
+            if report.inner().fix(config)?.is_some() {
+                fixed.insert(report.inner().id());
+            }
+            progress.progressed();
+        }
+        Ok(fixed)
+    };
+    let fixed_files = by_file
+        .into_par_iter()
+        .map(|(_, reports)| fix_all(reports))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut fixed = fix_all(others)?;
+    fixed.extend(fixed_files.into_iter().flatten());
+    Ok(fixed)
+}
 
-    let all_files = get_files(&config.directories());
-    let file_ngrams = ngrams(
-        &all_files,
-        config.ngram_size,
-        &boundary_regex,
-        &filename_spacing_regex,
-    );
+/// Get all the files to parse from the configured directories in `files`
+/// Attachments are never parsed, even if they are under one of our directories
+fn get_all_files(config: &config::Config, files: &dyn FileProvider) -> Vec<PathBuf> {
+    let attachments_directory = config
+        .attachments_directory
+        .as_ref()
+        .and_then(|directory| directory.canonicalize().ok());
+    files
+        .files(&config.directories())
+        .into_iter()
+        .filter(|file| match (&attachments_directory, file.canonicalize()) {
+            (Some(directory), Ok(file)) => !file.starts_with(directory),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Runs the [`DuplicateAliasVisitor`] over every file
+/// This gives us the table of aliases which other passes need, its reports are given to `sources`
+fn first_pass(
+    all_files: &[PathBuf],
+    config: &config::Config,
+    files: &Arc<dyn FileProvider>,
+    progress: &dyn ProgressSink,
+    sources: Rc<RefCell<SourceBudget>>,
+) -> Result<DuplicateAliasVisitor, OutputErrors> {
+    let duplicate_alias_visitor = Rc::new(RefCell::new(DuplicateAliasVisitor::new(
+        all_files,
+        &config.filename_to_alias,
+        files.clone(),
+        sources,
+    )));
+    for file in all_files {
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![duplicate_alias_visitor.clone()];
+        parse(files.as_ref(), file, &config.markdown, visitors)?;
+        progress.file_parsed(file);
+    }
+    Ok(Rc::try_unwrap(duplicate_alias_visitor)
+        .expect("parse is done")
+        .into_inner())
+}
+
+fn check(linter: &Linter) -> Result<OutputReport, OutputErrors> {
+    let config = linter.config();
+    let progress = &Logged::new(linter.progress());
+    let plan = Plan::new(linter);
+
+    let files = linter.files();
+    let all_files = get_all_files(config, files.as_ref());
 
     let mut reports: Vec<Report> = vec![];
+    let mut timed_out = vec![];
+    let mut matched_excludes = rules::MatchedExcludes::default();
+    let sources = Rc::new(RefCell::new(SourceBudget::new(config.source_memory_limit)));
 
     // Filename pass
     // Just over filenames
     // NOTE: Always use `filter_by_excludes` and `dedupe_by_code` on the reports
-    let similar_filenames = SimilarFilename::calculate(
-        &file_ngrams,
-        config.filename_match_threshold,
-        &filename_spacing_regex,
-        config,
-    )?
-    .finalize(&config.exclude);
-    reports.extend(
-        similar_filenames
-            .iter()
-            .map(|x| Report::SimilarFilename(x.clone())),
-    );
+    if plan.runs(Pass::SimilarFilenames) {
+        // Compile our regex patterns
+        let boundary_regex = regex::Regex::new(&config.boundary_pattern)?;
+        let filename_spacing_regex = regex::Regex::new(&config.filename_spacing_pattern)?;
+        let ngram_ignore_regex = regex::Regex::new(&config.ngram_ignore_pattern)?;
+        let file_ngrams = ngrams(
+            &all_files,
+            config.ngram_size,
+            &boundary_regex,
+            &filename_spacing_regex,
+            &ngram_ignore_regex,
+            config.split_camel_case,
+        );
+        let mut budget = Budget::new(config.rule_timeout);
+        let (similar_filenames, matched_word_pairs) = SimilarFilename::calculate(
+            &file_ngrams,
+            config.filename_match_threshold,
+            &filename_spacing_regex,
+            config,
+            progress,
+            &mut budget,
+        )?;
+        let (similar_filenames, matched) = similar_filenames.finalize(&config.exclude);
+        matched_excludes.extend(matched_word_pairs);
+        matched_excludes.extend(matched);
+        timed_out.extend(budget.note("SimilarFilename"));
+        for similar_filename in similar_filenames {
+            let report = Report::SimilarFilename(similar_filename);
+            if config.ignores_report(&report) || config.is_disabled(&report) {
+                continue;
+            }
+            progress.report_emitted(&report);
+            reports.push(report);
+        }
+    }
+
+    // Config pass
+    // Just over the config file
+    if linter.expired_exclude && config.excludes_expire() {
+        let today = linter.today().unwrap_or_else(config::Date::today);
+        let (expired_excludes, matched) =
+            ExpiredExclude::calculate(config, today, &mut sources.borrow_mut())
+                .finalize(&config.exclude);
+        matched_excludes.extend(matched);
+        for expired_exclude in expired_excludes {
+            let report = Report::ThirdPass(rules::ThirdPassReport::ExpiredExclude(expired_exclude));
+            if config.is_disabled(&report) {
+                continue;
+            }
+            progress.report_emitted(&report);
+            reports.push(report);
+        }
+    }
+
+    let mut output_report = if plan.runs(Pass::Aliases) {
+        content_passes(linter, &plan, &all_files, &mut reports, &sources)?
+    } else {
+        OutputReport::default()
+    };
+    output_report.reports = rules::correlate(reports);
+    timed_out.append(&mut output_report.timed_out);
+    output_report.timed_out = timed_out;
+    matched_excludes.extend(std::mem::take(&mut output_report.matched_excludes));
+    output_report.matched_excludes = matched_excludes;
+    output_report.elided_sources = sources.borrow().elided();
+    Ok(output_report)
+}
+
+/// The passes over the content of every file, which push their reports to `reports`
+/// The visitors give each report to `sources` as they make it
+/// The second pass is skipped when no rule of the [`Plan`] runs in it, leaving the link index empty
+fn content_passes(
+    linter: &Linter,
+    plan: &Plan,
+    all_files: &[PathBuf],
+    reports: &mut Vec<Report>,
+    sources: &Rc<RefCell<SourceBudget>>,
+) -> Result<OutputReport, OutputErrors> {
+    let config = linter.config();
+    let progress = &Logged::new(linter.progress());
+    let files = linter.files();
 
     // First pass
     // This gives us metadata we need for all other rules from the content of files
     //  The duplicate alias visitor has to run first to get the table of aliases
-    let first_pass_bar: Option<ProgressBar> = if env::var("RUNNING_TESTS").is_ok() {
-        None
-    } else {
-        println!(
-            "  {} {}Getting Aliases O(n)...",
-            style("[2/3]").bold().dim(),
-            FIRST_PASS
-        );
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        Some(ProgressBar::new(all_files.len() as u64))
-    };
-    let duplicate_alias_visitor = Rc::new(RefCell::new(DuplicateAliasVisitor::new(
-        &all_files,
-        &config.filename_to_alias,
-    )));
-    for file in &all_files {
-        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![duplicate_alias_visitor.clone()];
-        parse(file, visitors)?;
-        if let Some(bar) = &first_pass_bar {
-            bar.inc(1);
+    progress.pass_started(Pass::Aliases, Some(all_files.len() as u64));
+    let mut duplicate_alias_visitor =
+        first_pass(all_files, config, files, progress, sources.clone())?;
+    let Finalized {
+        reports: duplicate_aliases,
+        mut matched_excludes,
+    } = duplicate_alias_visitor.finalize(&config.exclude)?;
+    if linter.duplicate_alias {
+        for report in duplicate_aliases {
+            if config.ignores_report(&report) || config.is_disabled(&report) {
+                continue;
+            }
+            progress.report_emitted(&report);
+            reports.push(report);
         }
     }
-    let mut duplicate_alias_visitor: DuplicateAliasVisitor =
-        Rc::try_unwrap(duplicate_alias_visitor)
-            .expect("parse is done")
-            .into_inner();
-    reports.extend(duplicate_alias_visitor.finalize(&config.exclude)?);
-    if let Some(bar) = &first_pass_bar {
-        bar.finish_and_clear();
+    progress.pass_finished(Pass::Aliases);
+    if !plan.runs(Pass::Links) {
+        return Ok(OutputReport {
+            alias_table: duplicate_alias_visitor.alias_table.into_iter().collect(),
+            matched_excludes,
+            ..OutputReport::default()
+        });
     }
 
     // Second Pass
-    let second_pass_bar: Option<ProgressBar> = if env::var("RUNNING_TESTS").is_ok() {
-        None
+    progress.pass_started(Pass::Links, Some(all_files.len() as u64));
+    let alias_index = Rc::new(RefCell::new(if plan.needs(Input::AliasIndex) {
+        AliasIndex::new(
+            duplicate_alias_visitor.alias_table.clone(),
+            &duplicate_alias_visitor.no_autolink,
+            &duplicate_alias_visitor.definitions,
+        )?
     } else {
-        println!(
-            "  {} {}Checking Links O(n)...",
-            style("[3/3]").bold().dim(),
-            SECOND_PASS
-        );
-        #[allow(clippy::cast_sign_loss)]
-        #[allow(clippy::cast_possible_truncation)]
-        Some(ProgressBar::new(all_files.len() as u64))
-    };
+        AliasIndex::default()
+    }));
+    let link_index_visitor = Rc::new(RefCell::new(LinkIndexVisitor::new(
+        duplicate_alias_visitor.alias_table.clone(),
+        config.skip_html,
+    )));
+    let completed_task_visitor = Rc::new(RefCell::new(CompletedTaskVisitor::new()));
     let mut visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![];
-    for rule in ThirdPassRule::iter() {
-        visitors.push(match rule {
-            ThirdPassRule::UnlinkedText => Rc::new(RefCell::new(
-                rules::unlinked_text::UnlinkedTextVisitor::new(
-                    &all_files,
-                    &config.filename_to_alias,
-                    duplicate_alias_visitor.alias_table.clone(),
-                ),
-            )),
-            ThirdPassRule::BrokenWikilink => Rc::new(RefCell::new(BrokenWikilinkVisitor::new(
-                &all_files,
-                &config.filename_to_alias,
-                duplicate_alias_visitor.alias_table.clone(),
-            ))),
-        });
+    if plan.needs(Input::AliasIndex) {
+        // First, so the other visitors can read the occurrences of each file from the index
+        visitors.push(Rc::new(RefCell::new(AliasIndexVisitor::new(
+            alias_index.clone(),
+            config.skip_html,
+            config.unlinked_text_inflections,
+        ))));
     }
-
-    for file in &all_files {
-        parse(file, visitors.clone())?;
-        if let Some(bar) = &second_pass_bar {
-            bar.inc(1);
+    visitors.push(link_index_visitor.clone());
+    if config.completed_tasks.is_some() {
+        visitors.push(completed_task_visitor.clone());
+    }
+    let attachments = config.attachments(files.as_ref());
+    let inputs = Inputs {
+        config,
+        all_files,
+        attachments: &attachments,
+        alias_table: &duplicate_alias_visitor.alias_table,
+        alias_index: &alias_index,
+        sources,
+    };
+    let mut rule_visitors: Vec<(String, Rc<RefCell<dyn Visitor>>)> = plan
+        .rules
+        .iter()
+        .filter_map(|rule| Some((format!("{rule:?}"), rule.visitor(&inputs)?)))
+        .collect();
+    for rule in linter.rules() {
+        let visitor = rule.visitor(config, &duplicate_alias_visitor.alias_table);
+        rule_visitors.push((visitor.name().to_owned(), Rc::new(RefCell::new(visitor))));
+    }
+    // Only timed when there is a timeout, as timing every node has a cost
+    let mut budgeted = vec![];
+    for (rule, visitor) in rule_visitors {
+        if config.rule_timeout.is_some() {
+            let visitor = Rc::new(RefCell::new(BudgetedVisitor::new(
+                visitor,
+                &rule,
+                config.rule_timeout,
+            )));
+            budgeted.push(visitor.clone());
+            visitors.push(visitor);
+        } else {
+            visitors.push(visitor);
         }
     }
 
+    for file in all_files {
+        parse(files.as_ref(), file, &config.markdown, visitors.clone())?;
+        progress.file_parsed(file);
+    }
+
+    let excludes = rules::Exclude::all(&config.exclude);
+    let completed_tasks = std::mem::take(&mut completed_task_visitor.borrow_mut().completed_tasks);
+    let mut completed_task_ids = BTreeSet::new();
     for visitor in visitors {
         let mut visitor_cell = (*visitor).borrow_mut();
-        reports.extend(visitor_cell.finalize(&config.exclude)?);
-    }
-    if let Some(bar) = &second_pass_bar {
-        bar.finish_and_clear();
+        let finalized = visitor_cell.finalize(&config.exclude)?;
+        matched_excludes.extend(finalized.matched_excludes);
+        for report in finalized.reports {
+            // Files in `ignore_paths` are parsed, so wikilinks to them resolve, but not linted,
+            // a report is dropped when all of its files are ignored
+            // Rules which are off in a namespace still run, as they may report other files
+            if config.ignores_report(&report) || config.is_disabled(&report) {
+                continue;
+            }
+            // Built in rules exclude their own reports, custom rules are left to us
+            if matches!(report, Report::Custom(_)) {
+                if let Some(exclude) = report.inner().excluded_by(&excludes) {
+                    matched_excludes.exclude.insert(exclude.raw().clone());
+                    continue;
+                }
+            }
+            if report
+                .inner()
+                .span()
+                .is_some_and(|span| completed_tasks.contains(&report.path(), span.offset()))
+            {
+                if config.completed_tasks == Some(config::NamespaceRule::Off) {
+                    continue;
+                }
+                completed_task_ids.insert(report.inner().id());
+            }
+            progress.report_emitted(&report);
+            reports.push(report);
+        }
     }
+    progress.pass_finished(Pass::Links);
+    // The budgeted visitors hold the others, which hold the indexes
+    let timed_out = budgeted
+        .into_iter()
+        .filter_map(|visitor| visitor.borrow().note())
+        .collect();
 
-    Ok(OutputReport { reports })
+    Ok(OutputReport {
+        reports: vec![],
+        alias_table: duplicate_alias_visitor.alias_table.into_iter().collect(),
+        link_index: Rc::try_unwrap(link_index_visitor)
+            .expect("parse is done")
+            .into_inner()
+            .link_index,
+        alias_index: Rc::try_unwrap(alias_index)
+            .expect("parse is done")
+            .into_inner(),
+        completed_tasks: completed_task_ids,
+        fix_stats: None,
+        fix_conflicts: vec![],
+        non_idempotent_fixes: vec![],
+        timed_out,
+        elided_sources: 0,
+        matched_excludes,
+    })
 }
 
 /// The main library function that takes a configuration and returns a Result
@@ -330,9 +975,292 @@ fn check(config: &config::Config) -> Result<OutputReport, OutputErrors> {
 /// Basically if this library fails, this returns an Err
 /// but if this library runs, even if it finds linting violations, this returns an Ok
 pub fn lib(config: &config::Config) -> Result<OutputReport, OutputErrors> {
-    if config.fix {
-        fix(config)
+    Linter::from_config(config.clone()).run()
+}
+
+/// Runs the rules over the content of files which `config` enables on one document, `contents`
+/// at `name` in the pages directory, without the filesystem
+///
+/// `aliases` is the alias table of the other pages, like [`OutputReport::alias_table`] of a
+/// vault, so links to them resolve. Each of its pages is made in memory with its aliases in its
+/// front matter, and only the reports about the document are returned.
+/// This is made for fuzzing the spans of the rules, where arbitrary text can't be parsed, so
+/// documents with multibyte characters or front matter which is not YAML have no reports, see
+/// [`try_lint_source`] for every error
+///
+/// # Panics
+///
+/// On any other error, as it is a bug fuzzing should find
+#[must_use]
+pub fn lint_source(
+    name: &str,
+    contents: &str,
+    aliases: &BTreeMap<Alias, PathBuf>,
+    config: &config::Config,
+) -> Vec<Report> {
+    match try_lint_source(name, contents, aliases, config) {
+        Ok(reports) => reports,
+        Err(OutputErrors::ParseError(
+            visitor::ParseError::MultibyteError { .. }
+            | visitor::ParseError::VisitError {
+                source: visitor::VisitError::FrontMatterDeserializeError { .. },
+                ..
+            },
+        )) => vec![],
+        reports => reports.expect("Only documents which can't be parsed are expected to fail"),
+    }
+}
+
+/// [`lint_source`], with the error which stopped the document from being linted
+///
+/// # Errors
+///
+/// The same errors as [`lib`], like a parsing error
+#[allow(clippy::result_large_err)]
+pub fn try_lint_source(
+    name: &str,
+    contents: &str,
+    aliases: &BTreeMap<Alias, PathBuf>,
+    config: &config::Config,
+) -> Result<Vec<Report>, OutputErrors> {
+    let path = config.pages_directory.join(name);
+    let mut pages: BTreeMap<&PathBuf, Vec<&Alias>> = BTreeMap::new();
+    for (alias, page) in aliases {
+        if *page != path {
+            pages.entry(page).or_default().push(alias);
+        }
+    }
+    let mut files = file::provider::InMemory::new().with_file(&path, contents);
+    for (page, page_aliases) in pages {
+        files.insert(page, synthetic_page(&page_aliases));
+    }
+    let mut config = config.clone();
+    config.fix = false;
+    let mut linter = Linter::from_config(config).with_files(Arc::new(files));
+    // Not about the content of the document
+    linter.similar_filename = false;
+    linter.expired_exclude = false;
+    let reports = check(&linter)?.reports;
+    Ok(reports
+        .into_iter()
+        .filter(|report| report.inner().paths().contains(&path))
+        .collect())
+}
+
+/// A page of [`try_lint_source`] with only `aliases` in its front matter
+/// Every alias is a YAML double quoted string with its non ASCII characters escaped, as pages
+/// with multibyte characters are not parsed
+fn synthetic_page(aliases: &[&Alias]) -> String {
+    let mut page = String::from("---\naliases:\n");
+    for alias in aliases {
+        page.push_str("  - \"");
+        for c in alias.to_string().chars() {
+            match c {
+                '"' | '\\' => {
+                    page.push('\\');
+                    page.push(c);
+                }
+                c if c.is_ascii() && !c.is_ascii_control() => page.push(c),
+                c => write!(page, "\\U{:08x}", u32::from(c))
+                    .expect("Writing to a String never fails"),
+            }
+        }
+        page.push_str("\"\n");
+    }
+    page.push_str("---\n");
+    page
+}
+
+/// Builds the [`backlinks::Backlinks`] index of every page, used by `mdlinker backlinks`
+///
+/// # Errors
+///
+/// The same non-linter errors as [`lib`], like a parsing error
+#[cfg(feature = "fs")]
+pub fn backlinks(config: &config::Config) -> Result<backlinks::Backlinks, OutputErrors> {
+    let files: Arc<dyn FileProvider> = Arc::new(file::provider::FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let duplicate_alias_visitor = first_pass(
+        &all_files,
+        config,
+        &files,
+        &progress::NoProgress,
+        Rc::default(),
+    )?;
+    let backlink_visitor = Rc::new(RefCell::new(backlinks::BacklinkVisitor::new(
+        &all_files,
+        duplicate_alias_visitor.alias_table,
+        config.skip_html,
+    )));
+    for file in &all_files {
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![backlink_visitor.clone()];
+        parse(files.as_ref(), file, &config.markdown, visitors)?;
+    }
+    Ok(Rc::try_unwrap(backlink_visitor)
+        .expect("parse is done")
+        .into_inner()
+        .backlinks)
+}
+
+/// Builds the [`LinkIndex`] of every file, or only of `file`, used by `mdlinker links`
+/// With [`config::Config::write_index`], the saved [`index_file::IndexFile`] is read instead
+/// while it is fresh
+///
+/// # Errors
+///
+/// The same non-linter errors as [`lib`], like a parsing error
+#[cfg(feature = "fs")]
+pub fn links(
+    config: &config::Config,
+    file: Option<&std::path::Path>,
+) -> Result<LinkIndex, OutputErrors> {
+    let files: Arc<dyn FileProvider> = Arc::new(file::provider::FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let saved = config
+        .write_index
+        .then(|| index_file::IndexFile::read(config, &all_files))
+        .flatten();
+    let link_index = if let Some(saved) = saved {
+        saved.link_index
     } else {
-        check(config)
+        let duplicate_alias_visitor = first_pass(
+            &all_files,
+            config,
+            &files,
+            &progress::NoProgress,
+            Rc::default(),
+        )?;
+        let link_index_visitor = Rc::new(RefCell::new(LinkIndexVisitor::new(
+            duplicate_alias_visitor.alias_table,
+            config.skip_html,
+        )));
+        for file in &all_files {
+            let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![link_index_visitor.clone()];
+            parse(files.as_ref(), file, &config.markdown, visitors)?;
+        }
+        Rc::try_unwrap(link_index_visitor)
+            .expect("parse is done")
+            .into_inner()
+            .link_index
+    };
+    Ok(match file {
+        Some(file) => link_index.only(file),
+        None => link_index,
+    })
+}
+
+/// Every alias, the files which define it, and the file it resolves to, used by `mdlinker aliases`
+///
+/// # Errors
+///
+/// The same non-linter errors as [`lib`], like a parsing error
+#[cfg(feature = "fs")]
+pub fn aliases(config: &config::Config) -> Result<aliases::Aliases, OutputErrors> {
+    let files: Arc<dyn FileProvider> = Arc::new(file::provider::FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let duplicate_alias_visitor = first_pass(
+        &all_files,
+        config,
+        &files,
+        &progress::NoProgress,
+        Rc::default(),
+    )?;
+    Ok(aliases::Aliases::new(
+        &duplicate_alias_visitor.definitions,
+        &duplicate_alias_visitor.alias_table,
+    ))
+}
+
+/// Builds the [`graph::Graph`] of pages, used by `mdlinker graph`
+/// See [`graph::Graph::new`] for `labels` and `namespace`
+///
+/// # Errors
+///
+/// The same non-linter errors as [`lib`], like a parsing error
+#[cfg(feature = "fs")]
+pub fn graph(
+    config: &config::Config,
+    labels: bool,
+    namespace: Option<&str>,
+) -> Result<graph::Graph, OutputErrors> {
+    Ok(graph::Graph::new(
+        &backlinks(config)?,
+        config,
+        labels,
+        namespace,
+    ))
+}
+
+/// Finds where an alias is defined and referenced, used by `mdlinker query`
+///
+/// # Errors
+///
+/// The same non-linter errors as [`lib`], like a parsing error
+#[cfg(feature = "fs")]
+pub fn query(config: &config::Config, alias: &str) -> Result<query::Query, OutputErrors> {
+    let alias = file::content::wikilink::Alias::new(alias);
+    let files: Arc<dyn FileProvider> = Arc::new(file::provider::FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let duplicate_alias_visitor = first_pass(
+        &all_files,
+        config,
+        &files,
+        &progress::NoProgress,
+        Rc::default(),
+    )?;
+    let definition = duplicate_alias_visitor.alias_table.get(&alias).cloned();
+    let query_visitor = Rc::new(RefCell::new(query::QueryVisitor::new(
+        alias.clone(),
+        &config.filename_to_alias,
+        config.skip_html,
+        config.unlinked_text_inflections,
+    )));
+    for file in &all_files {
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![query_visitor.clone()];
+        parse(files.as_ref(), file, &config.markdown, visitors)?;
     }
+    let mut query_visitor = Rc::try_unwrap(query_visitor)
+        .expect("parse is done")
+        .into_inner();
+    query_visitor.finalize(&config.exclude)?;
+    Ok(query::Query {
+        alias,
+        duplicates: query_visitor
+            .definitions
+            .into_iter()
+            .filter(|path| Some(path) != definition.as_ref())
+            .collect(),
+        definition,
+        references: query_visitor.references,
+    })
+}
+
+/// Scores pairs of filename ngrams, used by `mdlinker tune-similarity`
+/// See [`tune::ScoreDistribution::new`] for `max_pairs`
+///
+/// # Errors
+///
+/// If the regex patterns of the config don't compile
+#[cfg(feature = "fs")]
+pub fn tune_similarity(
+    config: &config::Config,
+    max_pairs: usize,
+) -> Result<tune::ScoreDistribution, OutputErrors> {
+    let boundary_regex = regex::Regex::new(&config.boundary_pattern)?;
+    let filename_spacing_regex = regex::Regex::new(&config.filename_spacing_pattern)?;
+    let ngram_ignore_regex = regex::Regex::new(&config.ngram_ignore_pattern)?;
+    let all_files = get_all_files(config, &file::provider::FileSystem);
+    let file_ngrams = ngrams(
+        &all_files,
+        config.ngram_size,
+        &boundary_regex,
+        &filename_spacing_regex,
+        &ngram_ignore_regex,
+        config.split_camel_case,
+    );
+    Ok(tune::ScoreDistribution::new(
+        &file_ngrams,
+        config,
+        max_pairs,
+    ))
 }
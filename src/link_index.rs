@@ -0,0 +1,180 @@
+//! Every link out of each page, see [`crate::OutputReport::link_index`]
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::content::{
+        line_column,
+        source_map::SourceMap,
+        wikilink::{Alias, WikilinkVisitor},
+    },
+    rules::ErrorCode,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// The target of a broken link in [`LinkIndex::to_tsv`]
+pub const UNRESOLVED: &str = "UNRESOLVED";
+
+/// A wikilink (or tag) out of a page
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The alias in the wikilink, lowercase
+    pub alias: Alias,
+    /// The file the alias resolves to, [`None`] if the link is broken
+    pub target: Option<PathBuf>,
+    /// The byte offset of the wikilink in the file
+    pub offset: usize,
+    /// The length of the wikilink in bytes
+    pub length: usize,
+    /// The line of the wikilink, starting from 1
+    pub line: usize,
+    /// The column of the wikilink in characters, starting from 1
+    pub column: usize,
+    /// Written as a tag, like `#tag` or `#[[tag]]`
+    #[serde(default)]
+    pub tag: bool,
+}
+
+/// Every file, and the links in it in the order they appear
+/// Files without links have an empty list
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(transparent)]
+pub struct LinkIndex(pub BTreeMap<PathBuf, Vec<Link>>);
+
+impl LinkIndex {
+    /// The links out of a file
+    #[must_use]
+    pub fn get(&self, file: &Path) -> Option<&Vec<Link>> {
+        self.0.get(file)
+    }
+
+    /// The files which link to each file, links from a file to itself are not counted
+    #[must_use]
+    pub fn inbound(&self) -> HashMap<PathBuf, HashSet<PathBuf>> {
+        let mut inbound: HashMap<PathBuf, HashSet<PathBuf>> = HashMap::new();
+        for (file, links) in &self.0 {
+            for target in links.iter().filter_map(|link| link.target.as_ref()) {
+                if target != file {
+                    inbound
+                        .entry(target.clone())
+                        .or_default()
+                        .insert(file.clone());
+                }
+            }
+        }
+        inbound
+    }
+
+    /// Only the links out of `file`, which is compared by its canonical path
+    #[must_use]
+    pub fn only(self, file: &Path) -> Self {
+        let file = file.canonicalize().ok();
+        Self(
+            self.0
+                .into_iter()
+                .filter(|(path, _)| file.is_some() && path.canonicalize().ok() == file)
+                .collect(),
+        )
+    }
+
+    /// Serialize as pretty printed json
+    ///
+    /// # Errors
+    ///
+    /// If serialization fails, which it shouldn't
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// One line per link, with a header, and `UNRESOLVED` as the target of broken links
+    #[must_use]
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("source\tline\tcolumn\toffset\tlength\talias\ttag\ttarget\n");
+        for (file, links) in &self.0 {
+            for link in links {
+                let _ = writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    file.display(),
+                    link.line,
+                    link.column,
+                    link.offset,
+                    link.length,
+                    link.alias,
+                    link.tag,
+                    link.target.as_ref().map_or_else(
+                        || UNRESOLVED.to_owned(),
+                        |target| target.display().to_string()
+                    ),
+                );
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug)]
+pub struct LinkIndexVisitor {
+    pub alias_table: HashMap<Alias, PathBuf>,
+    pub wikilinks_visitor: WikilinkVisitor,
+    pub link_index: LinkIndex,
+}
+
+impl LinkIndexVisitor {
+    #[must_use]
+    pub fn new(alias_table: HashMap<Alias, PathBuf>, skip_html: bool) -> Self {
+        Self {
+            alias_table,
+            wikilinks_visitor: WikilinkVisitor::new(skip_html),
+            link_index: LinkIndex::default(),
+        }
+    }
+}
+
+impl Visitor for LinkIndexVisitor {
+    fn name(&self) -> &'static str {
+        "LinkIndexVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let mut links: Vec<Link> = self
+            .wikilinks_visitor
+            .wikilinks
+            .iter()
+            .map(|wikilink| {
+                let (line, column) = line_column(source, wikilink.span.offset());
+                Link {
+                    alias: wikilink.alias.clone(),
+                    target: self.alias_table.get(&wikilink.alias).cloned(),
+                    offset: wikilink.span.offset(),
+                    length: wikilink.span.len(),
+                    line,
+                    column,
+                    tag: wikilink.tag,
+                }
+            })
+            .collect();
+        links.sort_by_key(|link| link.offset);
+        self.link_index.0.insert(path.to_path_buf(), links);
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+    fn _finalize(&mut self, _excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
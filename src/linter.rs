@@ -0,0 +1,484 @@
+//! A programmatic entrypoint to the linter, for embedding it without a cli or config file
+use std::{path::PathBuf, sync::Arc};
+
+use bon::bon;
+
+use crate::{
+    check,
+    config::{
+        self, cli, combine_partials, file, Config, Date, ExcludeEntry, KeySynonyms, LinkOncePer,
+        LinkResolution, LinkStyle, MarkdownExtensions, MinInboundLinks, Namespace, NamespaceRule,
+        NewConfigError, NewFilesDirectory, Platform, SeverityOverride, SimilarityAlgorithm,
+        UnlinkedTextFix, UnlinkedTextOptions,
+    },
+    file::provider::FileProvider,
+    fix,
+    progress::{NoProgress, ProgressSink},
+    rules::{custom::CustomRule, ErrorCode},
+    OutputErrors, OutputReport,
+};
+
+/// Runs the rules over a set of directories
+///
+/// Create it from plain values with [`Linter::builder`], or from a loaded [`Config`] with
+/// [`Linter::from_config`]
+#[derive(Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Linter {
+    config: Config,
+    /// Run [`crate::rules::similar_filename::SimilarFilename`]
+    pub similar_filename: bool,
+    /// Report [`crate::rules::duplicate_alias::DuplicateAlias`]
+    /// Aliases are always collected, other rules need them
+    pub duplicate_alias: bool,
+    /// Run [`crate::rules::broken_wikilink::BrokenWikilink`]
+    pub broken_wikilink: bool,
+    /// Run [`crate::rules::unlinked_text::UnlinkedText`]
+    pub unlinked_text: bool,
+    /// Run [`crate::rules::broken_relref::BrokenRelref`]
+    pub broken_relref: bool,
+    /// Run [`crate::rules::unknown_tag::UnknownTag`]
+    pub unknown_tag: bool,
+    /// Run [`crate::rules::duplicate_property::DuplicateProperty`]
+    pub duplicate_property: bool,
+    /// Run [`crate::rules::duplicate_content::DuplicateContent`]
+    pub duplicate_content: bool,
+    /// Run [`crate::rules::ignored_wikilink::IgnoredWikilink`]
+    pub ignored_wikilink: bool,
+    /// Run [`crate::rules::hub_page::HubPage`]
+    pub hub_page: bool,
+    /// Run [`crate::rules::underlinked_page::UnderlinkedPage`]
+    pub underlinked_page: bool,
+    /// Run [`crate::rules::alias_casing::AliasCasing`]
+    pub alias_casing: bool,
+    /// Run [`crate::rules::confusable_character::ConfusableCharacter`]
+    pub confusable_character: bool,
+    /// Run [`crate::rules::illegal_filename::IllegalFilename`]
+    pub illegal_filename: bool,
+    /// Run [`crate::rules::front_matter_key::FrontMatterKey`]
+    pub front_matter_key: bool,
+    /// Run [`crate::rules::wrong_link_style::WrongLinkStyle`]
+    pub wrong_link_style: bool,
+    /// Run [`crate::rules::code_wikilink::CodeWikilink`]
+    pub code_wikilink: bool,
+    /// Run [`crate::rules::broken_reference::BrokenReference`]
+    pub broken_reference: bool,
+    /// Run [`crate::rules::expired_exclude::ExpiredExclude`]
+    pub expired_exclude: bool,
+    /// Told about each pass, file and report, see [`ProgressSink`]
+    progress: Arc<dyn ProgressSink>,
+    /// Rules from outside this crate, see [`Linter::with_rule`]
+    rules: Vec<Arc<dyn CustomRule>>,
+    /// Where the files to lint are listed and read from, see [`FileProvider`]
+    files: Arc<dyn FileProvider>,
+    /// The day excludes expire by, see [`Linter::with_today`]
+    today: Option<Date>,
+}
+
+/// The filesystem, or nothing without the `fs` feature
+fn default_files() -> Arc<dyn FileProvider> {
+    #[cfg(feature = "fs")]
+    return Arc::new(crate::file::provider::FileSystem);
+    #[cfg(not(feature = "fs"))]
+    return Arc::new(crate::file::provider::InMemory::new());
+}
+
+#[bon]
+impl Linter {
+    /// Anything not given has the same default as in `mdlinker.toml`
+    ///
+    /// # Errors
+    ///
+    /// If the filename to alias conversions made from the hierarchy separator are not valid regex
+    #[builder]
+    #[allow(clippy::too_many_arguments)]
+    #[allow(clippy::fn_params_excessive_bools)]
+    pub fn new(
+        /// See [`cli::Config::pages_directory`]
+        pages_directory: PathBuf,
+        /// See [`cli::Config::other_directories`]
+        #[builder(default)]
+        other_directories: Vec<PathBuf>,
+        /// See [`cli::Config::hierarchy_separator`]
+        hierarchy_separator: Option<String>,
+        /// See [`cli::Config::ngram_size`]
+        ngram_size: Option<usize>,
+        /// See [`cli::Config::boundary_pattern`]
+        boundary_pattern: Option<String>,
+        /// See [`cli::Config::filename_spacing_pattern`]
+        filename_spacing_pattern: Option<String>,
+        /// See [`cli::Config::ngram_ignore_pattern`]
+        ngram_ignore_pattern: Option<String>,
+        /// See [`file::Config::split_camel_case`]
+        split_camel_case: Option<bool>,
+        /// See [`cli::Config::filename_match_threshold`]
+        filename_match_threshold: Option<i64>,
+        /// See [`cli::Config::exclude`]
+        #[builder(default)]
+        exclude: Vec<String>,
+        /// See [`file::Config::ignore_word_pairs`]
+        #[builder(default)]
+        ignore_word_pairs: Vec<(String, String)>,
+        /// See [`file::Config::skip_html`]
+        skip_html: Option<bool>,
+        /// See [`file::Config::markdown`]
+        markdown: Option<MarkdownExtensions>,
+        /// See [`file::Config::unlinked_text`]
+        unlinked_text_options: Option<UnlinkedTextOptions>,
+        /// See [`file::Config::unlinked_text_inflections`]
+        unlinked_text_inflections: Option<bool>,
+        /// See [`file::Config::group_unlinked_text`]
+        group_unlinked_text: Option<bool>,
+        /// See [`file::Config::unlinked_text_fix`]
+        unlinked_text_fix: Option<UnlinkedTextFix>,
+        /// See [`file::Config::link_once_per`]
+        link_once_per: Option<LinkOncePer>,
+        /// See [`file::Config::similar_filename_same_directory_only`]
+        similar_filename_same_directory_only: Option<bool>,
+        /// See [`SimilarityAlgorithm`]
+        similarity_algorithm: Option<SimilarityAlgorithm>,
+        /// See [`file::Config::new_files_directory`]
+        new_files_directory: Option<PathBuf>,
+        /// See [`file::Config::new_files_directories`]
+        #[builder(default)]
+        new_files_directories: Vec<NewFilesDirectory>,
+        /// See [`file::Config::new_file_template`]
+        new_file_template: Option<PathBuf>,
+        /// See [`file::Config::ignore_paths`]
+        #[builder(default)]
+        ignore_paths: Vec<String>,
+        /// See [`file::Config::allow_links_to_ignored`]
+        #[builder(default)]
+        allow_links_to_ignored: Vec<String>,
+        /// See [`file::Config::max_outbound_links`]
+        max_outbound_links: Option<usize>,
+        /// See [`file::Config::min_inbound_links`]
+        #[builder(default)]
+        min_inbound_links: Vec<MinInboundLinks>,
+        /// See [`file::Config::key_synonyms`]
+        #[builder(default)]
+        key_synonyms: Vec<KeySynonyms>,
+        /// See [`file::Config::link_style`]
+        link_style: Option<LinkStyle>,
+        /// See [`file::Config::filename_platforms`]
+        #[builder(default)]
+        filename_platforms: Vec<Platform>,
+        /// See [`file::Config::severity`]
+        #[builder(default)]
+        severity: Vec<SeverityOverride>,
+        /// See [`file::Config::namespace`]
+        #[builder(default)]
+        namespace: Vec<Namespace>,
+        /// See [`file::Config::completed_tasks`]
+        completed_tasks: Option<NamespaceRule>,
+        /// See [`file::Config::attachments_directory`]
+        attachments_directory: Option<PathBuf>,
+        /// See [`file::Config::known_tags`]
+        #[builder(default)]
+        known_tags: Vec<String>,
+        /// See [`file::Config::tags_page`]
+        tags_page: Option<String>,
+        /// See [`LinkResolution`]
+        #[builder(default)]
+        link_resolution: LinkResolution,
+        /// See [`cli::Config::fix`]
+        #[builder(default)]
+        fix: bool,
+        /// See [`cli::Config::allow_dirty`]
+        #[builder(default)]
+        allow_dirty: bool,
+        /// See [`cli::Config::dry_run`]
+        #[builder(default)]
+        dry_run: bool,
+        /// See [`cli::Config::verify`]
+        #[builder(default)]
+        verify: bool,
+        /// See [`cli::Config::no_vcs`]
+        #[builder(default)]
+        no_vcs: bool,
+        /// See [`cli::Config::rule_timeout`]
+        rule_timeout: Option<u64>,
+        /// See [`cli::Config::source_memory_limit`]
+        source_memory_limit: Option<usize>,
+        /// See [`Linter::similar_filename`]
+        #[builder(default = true)]
+        similar_filename: bool,
+        /// See [`Linter::duplicate_alias`]
+        #[builder(default = true)]
+        duplicate_alias: bool,
+        /// See [`Linter::broken_wikilink`]
+        #[builder(default = true)]
+        broken_wikilink: bool,
+        /// See [`Linter::unlinked_text`]
+        #[builder(default = true)]
+        unlinked_text: bool,
+        /// See [`Linter::broken_relref`]
+        #[builder(default = false)]
+        broken_relref: bool,
+        /// See [`Linter::code_wikilink`]
+        #[builder(default = false)]
+        code_wikilink: bool,
+        /// See [`Linter::duplicate_property`]
+        #[builder(default = true)]
+        duplicate_property: bool,
+        /// See [`Linter::duplicate_content`]
+        #[builder(default = true)]
+        duplicate_content: bool,
+        /// See [`Linter::alias_casing`]
+        #[builder(default = true)]
+        alias_casing: bool,
+        /// See [`Linter::confusable_character`]
+        #[builder(default = true)]
+        confusable_character: bool,
+        /// See [`Linter::illegal_filename`]
+        #[builder(default = true)]
+        illegal_filename: bool,
+        /// See [`Linter::front_matter_key`]
+        #[builder(default = true)]
+        front_matter_key: bool,
+        /// See [`Linter::broken_reference`]
+        #[builder(default = true)]
+        broken_reference: bool,
+        /// See [`Linter::expired_exclude`]
+        #[builder(default = true)]
+        expired_exclude: bool,
+        /// See [`Linter::progress`]
+        #[builder(default = Arc::new(NoProgress))]
+        progress: Arc<dyn ProgressSink>,
+        /// See [`Linter::files`]
+        #[builder(default = default_files())]
+        files: Arc<dyn FileProvider>,
+        /// See [`Linter::with_today`]
+        today: Option<Date>,
+    ) -> Result<Self, NewConfigError> {
+        let file_config = file::Config {
+            pages_directory,
+            other_directories,
+            hierarchy_separator,
+            ngram_size,
+            boundary_pattern,
+            filename_spacing_pattern,
+            ngram_ignore_pattern,
+            split_camel_case,
+            filename_match_threshold,
+            exclude: exclude.into_iter().map(ExcludeEntry::Pattern).collect(),
+            ignore_word_pairs,
+            skip_html,
+            markdown,
+            unlinked_text: unlinked_text_options,
+            unlinked_text_inflections,
+            group_unlinked_text,
+            unlinked_text_fix,
+            link_once_per,
+            similar_filename_same_directory_only,
+            similarity_algorithm,
+            new_files_directory,
+            new_files_directories,
+            new_file_template,
+            ignore_paths,
+            allow_links_to_ignored,
+            max_outbound_links,
+            min_inbound_links,
+            filename_platforms,
+            key_synonyms,
+            link_style,
+            severity,
+            namespace,
+            completed_tasks,
+            attachments_directory,
+            known_tags,
+            tags_page,
+            link_resolution: Some(link_resolution),
+            relref: Some(broken_relref),
+            code_wikilinks: Some(code_wikilink),
+            no_vcs: Some(no_vcs),
+            rule_timeout,
+            source_memory_limit,
+            ..Default::default()
+        };
+        let cli_config = cli::Config {
+            fix,
+            allow_dirty,
+            dry_run,
+            verify,
+            ..Default::default()
+        };
+        let config = combine_partials(PathBuf::from("."), None, &file_config, &cli_config)?;
+        Ok(Self {
+            similar_filename,
+            duplicate_alias,
+            broken_wikilink,
+            unlinked_text,
+            broken_relref,
+            unknown_tag: !config.known_tags.is_empty() || config.tags_page.is_some(),
+            duplicate_property,
+            duplicate_content,
+            ignored_wikilink: !config.ignore_paths.is_empty(),
+            hub_page: config.max_outbound_links.is_some(),
+            underlinked_page: !config.min_inbound_links.is_empty(),
+            alias_casing,
+            confusable_character,
+            illegal_filename,
+            front_matter_key,
+            wrong_link_style: config.link_style.is_some(),
+            code_wikilink,
+            broken_reference,
+            expired_exclude,
+            config,
+            progress,
+            rules: vec![],
+            files,
+            today,
+        })
+    }
+
+    /// Runs the rules the [`Config`] enables, which is all of them except
+    /// [`Config::relref`], [`Config::wikilinks`] and [`Config::code_wikilinks`] when those are off,
+    /// [`crate::rules::unknown_tag::UnknownTag`] when there are no known tags,
+    /// [`crate::rules::ignored_wikilink::IgnoredWikilink`] when nothing is ignored,
+    /// [`crate::rules::hub_page::HubPage`] when there is no maximum,
+    /// [`crate::rules::underlinked_page::UnderlinkedPage`] when there are no minimums, and
+    /// [`crate::rules::wrong_link_style::WrongLinkStyle`] when there is no link style
+    #[must_use]
+    pub fn from_config(config: Config) -> Self {
+        Self {
+            similar_filename: true,
+            duplicate_alias: true,
+            broken_wikilink: true,
+            // Unlinked text is fixed by inserting wikilinks
+            unlinked_text: config.wikilinks,
+            broken_relref: config.relref,
+            unknown_tag: !config.known_tags.is_empty() || config.tags_page.is_some(),
+            duplicate_property: true,
+            duplicate_content: true,
+            ignored_wikilink: !config.ignore_paths.is_empty(),
+            hub_page: config.max_outbound_links.is_some(),
+            underlinked_page: !config.min_inbound_links.is_empty(),
+            alias_casing: true,
+            confusable_character: true,
+            illegal_filename: true,
+            front_matter_key: true,
+            wrong_link_style: config.link_style.is_some(),
+            code_wikilink: config.code_wikilinks,
+            broken_reference: true,
+            expired_exclude: true,
+            config,
+            progress: Arc::new(NoProgress),
+            rules: vec![],
+            files: default_files(),
+            today: None,
+        }
+    }
+
+    /// Report progress to `progress` instead of nowhere
+    #[must_use]
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Where progress is reported, nowhere by default, see [`crate::progress::TerminalProgress`]
+    #[must_use]
+    pub fn progress(&self) -> &dyn ProgressSink {
+        self.progress.as_ref()
+    }
+
+    /// Lint the files in `files` instead of those on the filesystem
+    #[must_use]
+    pub fn with_files(mut self, files: Arc<dyn FileProvider>) -> Self {
+        self.files = files;
+        self
+    }
+
+    /// Where the files to lint come from, the filesystem by default
+    #[must_use]
+    pub fn files(&self) -> &Arc<dyn FileProvider> {
+        &self.files
+    }
+
+    /// Check the excludes which expire against `today` instead of the clock, like to get the
+    /// same reports on every day, or on targets without a clock like `wasm32-unknown-unknown`
+    #[must_use]
+    pub fn with_today(mut self, today: Date) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    /// The day excludes expire by, see [`Linter::with_today`], [`None`] to read the clock, which
+    /// is only read when an exclude expires
+    #[must_use]
+    pub fn today(&self) -> Option<Date> {
+        self.today
+    }
+
+    /// Also run `rule`, whose reports are [`crate::rules::Report::Custom`]
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl CustomRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// The rules added with [`Linter::with_rule`]
+    #[must_use]
+    pub fn rules(&self) -> &[Arc<dyn CustomRule>] {
+        &self.rules
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &config::Config {
+        &self.config
+    }
+
+    /// Lints, and also fixes if [`Config::fix`] is set, see [`crate::lib`]
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::lib`]
+    #[allow(clippy::result_large_err)]
+    pub fn run(&self) -> Result<OutputReport, OutputErrors> {
+        if self.config.fix {
+            fix(self)
+        } else {
+            check(self)
+        }
+    }
+
+    /// The entries of [`Config::exclude`] and [`Config::ignore_word_pairs`] which matched no
+    /// report in the run which gave `report`, see [`cli::Config::show_unused_excludes`]
+    #[must_use]
+    pub fn unused_excludes(&self, report: &OutputReport) -> UnusedExcludes {
+        let matched = report.matched_excludes();
+        let exclude = self
+            .config
+            .exclude
+            .iter()
+            .filter(|exclude| !matched.exclude.contains(*exclude))
+            .cloned()
+            .collect();
+        let ignore_word_pairs = self
+            .config
+            .ignore_word_pairs
+            .iter()
+            .filter(|pair| !matched.ignore_word_pairs.contains(*pair))
+            .cloned()
+            .collect();
+        UnusedExcludes {
+            exclude,
+            ignore_word_pairs,
+        }
+    }
+}
+
+/// Entries of the config which match no report, see [`Linter::unused_excludes`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UnusedExcludes {
+    pub exclude: Vec<ErrorCode>,
+    pub ignore_word_pairs: Vec<(String, String)>,
+}
+
+impl UnusedExcludes {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty() && self.ignore_word_pairs.is_empty()
+    }
+}
@@ -1,67 +1,362 @@
-use mdlinker::config;
-use mdlinker::lib;
-use mdlinker::rules::Report as MdReport;
-use mdlinker::rules::ThirdPassReport;
-use miette::{miette, Report, Result};
+use mdlinker::config::{
+    self,
+    cli::{
+        AliasFormat, BacklinkFormat, ColorChoice, Command, GraphFormat, LinkFormat, LogFormat,
+        ReportFormat,
+    },
+};
+use mdlinker::diff::ChangedLines;
+use mdlinker::index_file::IndexFile;
+use mdlinker::{
+    aliases, backlinks, export, graph, links,
+    linter::{Linter, UnusedExcludes},
+    progress::TerminalProgress,
+    query, tune_similarity,
+};
+use miette::{miette, MietteHandlerOpts, Report, Result, Severity};
+use std::{process::ExitCode, sync::Arc};
+use tracing_subscriber::EnvFilter;
 
-/// Really just a wrapper that loads the config and passes it to the main library function
-fn main() -> Result<()> {
-    env_logger::init();
+/// The exit codes of `mdlinker`, so scripts can tell a failed lint from a crash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    /// No report failed the run
+    Clean = 0,
+    /// Some reports failed the run, see [`config::Config::severity`]
+    Violations = 1,
+    /// The arguments or the config are invalid, clap also exits with this
+    Usage = 2,
+    /// Linting or a command failed, or mdlinker panicked
+    Internal = 3,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+/// An error to print, and how to exit after it
+struct Failure(Exit, Report);
+
+impl Failure {
+    fn usage(e: impl Into<Report>) -> Self {
+        Self(Exit::Usage, e.into())
+    }
+    fn internal(e: impl Into<Report>) -> Self {
+        Self(Exit::Internal, e.into())
+    }
+}
+
+fn main() -> ExitCode {
+    // The panic itself is printed by the default hook
+    match std::panic::catch_unwind(run) {
+        Ok(Ok(())) => Exit::Clean.into(),
+        Ok(Err(Failure(exit, report))) => {
+            eprintln!("{report:?}");
+            exit.into()
+        }
+        Err(_) => Exit::Internal.into(),
+    }
+}
 
+/// Really just a wrapper that loads the config and passes it to the main library function
+fn run() -> Result<(), Failure> {
     // Load the configuration
-    let mut config = config::Config::new().map_err(|e| miette!(e))?;
+    let mut config = config::Config::new().map_err(Failure::usage)?;
+    let colors = init_colors(config.color);
+    init_logger(config.log_format, colors);
+
+    if let Some(command) = &config.command {
+        return run_command(&config, command).map_err(Failure::internal);
+    }
 
+    let changed_lines = changed_lines(&config).map_err(Failure::usage)?;
     let mut nb_errors = 0;
-    match lib(&config) {
+    let mut nb_warnings = 0;
+    let mut nb_unchanged = 0;
+    let mut nb_ignored = 0;
+    let fix_stats;
+    let fix_conflicts;
+    let non_idempotent_fixes;
+    let timed_out;
+    let elided_sources;
+    let unused_excludes;
+    // The reports are printed to stdout in machine formats, so nothing else is
+    let machine = config.output_format != ReportFormat::Text;
+    let mut linter = Linter::from_config(config.clone());
+    if !machine {
+        linter = linter.with_progress(Arc::new(
+            TerminalProgress::new().plain(colors == Some(false)),
+        ));
+    }
+    match linter.run() {
         Err(e) => {
-            return Err(Report::from(e));
+            return Err(Failure::internal(e));
         }
-        Ok(e) => {
-            println!();
-            for report in e.reports {
-                match report {
-                    MdReport::SimilarFilename(e) => {
-                        nb_errors += 1;
-                        eprintln!("{:?}", Report::from(e.clone()));
-                        if config.ignore_remaining {
-                            config.add_report_to_ignore(&e);
-                        }
-                    }
-                    MdReport::DuplicateAlias(e) => {
-                        nb_errors += 1;
-                        eprintln!("{:?}", Report::from(e.clone()));
-                        if config.ignore_remaining {
-                            config.add_report_to_ignore(&e);
-                        }
-                    }
-                    MdReport::ThirdPass(ThirdPassReport::BrokenWikilink(e)) => {
-                        nb_errors += 1;
-                        eprintln!("{:?}", Report::from(e.clone()));
-                        if config.ignore_remaining {
-                            config.add_report_to_ignore(&e);
-                        }
-                    }
-                    MdReport::ThirdPass(ThirdPassReport::UnlinkedText(e)) => {
-                        nb_errors += 1;
-                        eprintln!("{:?}", Report::from(e.clone()));
-                        if config.ignore_remaining {
-                            config.add_report_to_ignore(&e);
-                        }
-                    }
+        Ok(mut e) => {
+            if config.write_index {
+                IndexFile::new(&e, &config)
+                    .write(&config)
+                    .map_err(Failure::internal)?;
+            }
+            match config.output_format {
+                ReportFormat::Text => println!(),
+                ReportFormat::Json => println!(
+                    "{}",
+                    export::to_json(&e, &config).map_err(|e| Failure::internal(miette!(e)))?
+                ),
+                ReportFormat::Sarif => println!(
+                    "{}",
+                    export::to_sarif(&e, &config).map_err(|e| Failure::internal(miette!(e)))?
+                ),
+            }
+            fix_stats = e.fix_stats();
+            fix_conflicts = e.fix_conflicts().to_vec();
+            non_idempotent_fixes = e.non_idempotent_fixes().to_vec();
+            timed_out = e.timed_out().to_vec();
+            elided_sources = e.elided_sources();
+            unused_excludes = linter.unused_excludes(&e);
+            for report in std::mem::take(&mut e.reports) {
+                let ignored = config.ignores_remaining(&report);
+                if ignored {
+                    nb_ignored += 1;
+                } else if changed_lines
+                    .as_ref()
+                    .is_some_and(|changed_lines| !changed_lines.touches(&report))
+                {
+                    nb_unchanged += 1;
+                } else if e.severity(&config, &report) == Severity::Error {
+                    nb_errors += 1;
+                } else {
+                    nb_warnings += 1;
+                }
+                if ignored {
+                    config.add_report_to_ignore(report.inner());
+                }
+                if !machine {
+                    eprintln!("{:?}", Report::new(report));
                 }
             }
         }
     }
 
-    if nb_errors > 0 && !config.ignore_remaining {
-        Err(miette!("Lint rules violated: {nb_errors}"))
-    } else if nb_errors > 0 {
-        println!("Lint rules ignored: {nb_errors}");
-        if config.ignore_remaining {
-            config.save_config()?;
+    for fix_conflict in &fix_conflicts {
+        status(machine, &format!("{fix_conflict}"));
+    }
+    for non_idempotent_fix in &non_idempotent_fixes {
+        eprintln!("{non_idempotent_fix}");
+    }
+    nb_errors += non_idempotent_fixes.len();
+    for timed_out in timed_out {
+        if machine {
+            eprintln!("{timed_out}");
+        } else {
+            eprintln!("{:?}", Report::from(timed_out));
         }
-        Ok(())
+        nb_warnings += 1;
+    }
+    if elided_sources > 0 {
+        status(
+            machine,
+            &format!(
+                "The reports kept {} MB of file content, so {elided_sources} more show no snippet, raise `source_memory_limit` to see them",
+                config.source_memory_limit
+            ),
+        );
+    }
+    if let Some(fix_stats) = fix_stats {
+        status(
+            machine,
+            &format!("Dry run, the fixes would make: {fix_stats}"),
+        );
+    }
+
+    if config.show_unused_excludes {
+        print_unused_excludes(&unused_excludes);
+    }
+
+    if nb_warnings > 0 {
+        status(machine, &format!("Lint warnings: {nb_warnings}"));
+    }
+    if nb_unchanged > 0 {
+        status(
+            machine,
+            &format!("Lint reports outside of the changed lines: {nb_unchanged}"),
+        );
+    }
+    if nb_ignored > 0 {
+        status(machine, &format!("Lint rules ignored: {nb_ignored}"));
+        config.save_config().map_err(Failure::internal)?;
+    }
+    if nb_errors > 0 {
+        Err(Failure(
+            Exit::Violations,
+            miette!("Lint rules violated: {nb_errors}"),
+        ))
     } else {
         Ok(())
     }
 }
+
+/// Prints a line about the run, to stderr if the reports are printed to stdout, see
+/// [`config::Config::output_format`]
+fn status(machine: bool, line: &str) {
+    if machine {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+/// See [`config::Config::show_unused_excludes`]
+/// They don't fail the run, as they are only a chore
+fn print_unused_excludes(unused: &UnusedExcludes) {
+    if unused.is_empty() {
+        return;
+    }
+    let mut entries: Vec<String> = unused
+        .exclude
+        .iter()
+        .map(|exclude| format!("  exclude: {:?}", exclude.0))
+        .collect();
+    entries.extend(
+        unused
+            .ignore_word_pairs
+            .iter()
+            .map(|(a, b)| format!("  ignore_word_pairs: [{a:?}, {b:?}]")),
+    );
+    eprintln!(
+        "{:?}",
+        miette!(
+            severity = Severity::Advice,
+            help = "Remove them from the config, the issues they silenced are gone",
+            "These match no report:\n{}",
+            entries.join("\n")
+        )
+    );
+}
+
+/// See [`config::Config::diff_base`]
+#[cfg(feature = "git")]
+fn changed_lines(config: &config::Config) -> Result<Option<ChangedLines>> {
+    config
+        .diff_base
+        .as_deref()
+        .map(|base| ChangedLines::new(&config.root_directory, base).map_err(|e| miette!(e)))
+        .transpose()
+}
+
+/// See [`config::Config::diff_base`]
+#[cfg(not(feature = "git"))]
+fn changed_lines(config: &config::Config) -> Result<Option<ChangedLines>> {
+    match &config.diff_base {
+        Some(_) => Err(miette!("--diff-base needs the git feature")),
+        None => Ok(None),
+    }
+}
+
+/// Turns colors on or off in console, and so indicatif, and in miette, see [`ColorChoice`]
+/// Returns [`None`] if each of them detects it
+fn init_colors(color: ColorChoice) -> Option<bool> {
+    let colors = match color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // See https://no-color.org, miette already follows it
+        ColorChoice::Auto if std::env::var_os("NO_COLOR").is_some_and(|no| !no.is_empty()) => false,
+        ColorChoice::Auto => return None,
+    };
+    console::set_colors_enabled(colors);
+    console::set_colors_enabled_stderr(colors);
+    miette::set_hook(Box::new(move |_| {
+        Box::new(MietteHandlerOpts::new().color(colors).build())
+    }))
+    .expect("The hook is only set here, once");
+    Some(colors)
+}
+
+/// Log to stderr in `format`, filtered by `RUST_LOG`, in color if `colors`, or else if stderr
+/// supports it
+fn init_logger(format: LogFormat, colors: Option<bool>) {
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_ansi(colors.unwrap_or_else(console::colors_enabled_stderr))
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+/// Runs a [`Command`] instead of the linter
+fn run_command(config: &config::Config, command: &Command) -> Result<()> {
+    match command {
+        Command::Backlinks { format } => {
+            let backlinks = backlinks(config).map_err(Report::from)?;
+            match format {
+                BacklinkFormat::Json => {
+                    println!("{}", backlinks.to_json().map_err(|e| miette!(e))?);
+                }
+            }
+        }
+        Command::Links { file, format } => {
+            let links = links(config, file.as_deref()).map_err(Report::from)?;
+            match format {
+                LinkFormat::Tsv => print!("{}", links.to_tsv()),
+                LinkFormat::Json => println!("{}", links.to_json().map_err(|e| miette!(e))?),
+            }
+        }
+        Command::Aliases { format } => {
+            let aliases = aliases(config).map_err(Report::from)?;
+            match format {
+                AliasFormat::Tsv => print!("{}", aliases.to_tsv()),
+                AliasFormat::Json => println!("{}", aliases.to_json().map_err(|e| miette!(e))?),
+            }
+        }
+        Command::Query { alias } => {
+            print!("{}", query(config, alias).map_err(Report::from)?);
+        }
+        Command::Graph {
+            format,
+            labels,
+            namespace,
+        } => {
+            let graph = graph(config, *labels, namespace.as_deref()).map_err(Report::from)?;
+            match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot()),
+                GraphFormat::Graphml => print!("{}", graph.to_graphml()),
+                GraphFormat::Json => println!("{}", graph.to_json().map_err(|e| miette!(e))?),
+            }
+        }
+        Command::New { title, link_from } => {
+            print!(
+                "{}",
+                mdlinker::new::new_page(config, title, link_from.as_deref())
+                    .map_err(Report::from)?
+            );
+        }
+        Command::Merge { a, b } => {
+            print!(
+                "{}",
+                mdlinker::merge::merge(config, a, b).map_err(Report::from)?
+            );
+        }
+        Command::Mv { src, dst } => {
+            print!(
+                "{}",
+                mdlinker::mv::mv(config, src, dst).map_err(Report::from)?
+            );
+        }
+        #[cfg(feature = "review")]
+        Command::Review => {
+            mdlinker::review::review(config.clone()).map_err(Report::from)?;
+        }
+        Command::TuneSimilarity { max_pairs } => {
+            print!(
+                "{}",
+                tune_similarity(config, *max_pairs).map_err(Report::from)?
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,416 @@
+//! Merge one page into another, see `mdlinker merge`
+//!
+//! This is what [`crate::rules::similar_filename::SimilarFilename`] and
+//! [`crate::rules::duplicate_content::DuplicateContent`] suggest. The body of the first page is
+//! added to the end of the second, the first page's name and aliases become aliases of the
+//! second, then the first page is deleted.
+//! Wikilinks and tags to the first page are found with the [`LinkIndex`] and rewritten to the
+//! second, keeping the text they show
+// Like the linter, these return its errors, which are large
+#![allow(clippy::result_large_err)]
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    file::{
+        case::eq_folded,
+        content::{
+            front_matter::{
+                raw_front_matter, top_level_keys, without_repeated_keys, AliasField,
+                YamlFrontMatter,
+            },
+            line_column,
+            notebook::is_notebook,
+            wikilink::Alias,
+        },
+        name::{get_filename, Filename},
+        provider::{FileProvider, FileSystem},
+    },
+    first_pass, get_all_files,
+    link_index::{LinkIndex, LinkIndexVisitor},
+    mv::Rewrite,
+    prepare_fixes, progress,
+    rules::FixError,
+    sed::ReplacePair,
+    visitor::{parse, Visitor},
+    OutputErrors,
+};
+
+/// The key aliases are written to, when neither page has one
+const ALIAS_KEY: &str = "alias";
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum MergeError {
+    #[error(transparent)]
+    OutputErrors(#[from] OutputErrors),
+    #[error(transparent)]
+    FixError(#[from] FixError),
+    #[error("{0:?} is not one of the pages being linted")]
+    NotAPage(PathBuf),
+    #[error("A page can't be merged into itself")]
+    SamePage,
+    #[error("{0:?} is a notebook, which can't be merged")]
+    Notebook(PathBuf),
+    #[error("The front matter of {file:?} is not valid yaml")]
+    YamlError {
+        file: PathBuf,
+        source: serde_yaml::Error,
+    },
+    #[error("There was an IOError on {file:?}: {source}")]
+    IoError {
+        file: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// The answer to `mdlinker merge <a> <b>`
+#[derive(Debug, Clone)]
+pub struct Merged {
+    /// The page which was merged and deleted
+    pub a: PathBuf,
+    /// The page it was merged into
+    pub b: PathBuf,
+    /// The aliases `b` didn't have before, as written
+    pub aliases: Vec<String>,
+    /// Every wikilink and tag rewritten from `a` to `b`, sorted by file and position
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl Display for Merged {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} -> {}", self.a.display(), self.b.display())?;
+        for alias in &self.aliases {
+            writeln!(f, "  alias: {alias}")?;
+        }
+        for rewrite in &self.rewrites {
+            writeln!(
+                f,
+                "  {}:{}:{}: {} -> {}",
+                rewrite.path.display(),
+                rewrite.line,
+                rewrite.column,
+                rewrite.from,
+                rewrite.to
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The name of a page, in its original case, as it is written in wikilinks
+fn page_name(path: &Path, filename_to_alias: &ReplacePair<Filename, Alias>) -> String {
+    let (from, to): (String, String) = filename_to_alias.clone().into();
+    ReplacePair::<Filename, Filename>::new(&from, &to).map_or_else(
+        |_| get_filename(path).0,
+        |pair| pair.apply(&get_filename(path)).0,
+    )
+}
+
+/// The front matter of `source`, and where its body starts, after the closing `---`
+fn split(source: &str) -> (Option<(usize, &str)>, usize) {
+    let Some((start, yaml)) = raw_front_matter(source) else {
+        return (None, 0);
+    };
+    let end = start + yaml.len();
+    let body = source[end..]
+        .find('\n')
+        .map_or(source.len(), |i| end + i + 1);
+    (Some((start, yaml)), body)
+}
+
+/// The parsed front matter of `yaml`, which may be empty
+fn parse_yaml(yaml: &str, file: &Path) -> Result<YamlFrontMatter, MergeError> {
+    if yaml.trim().is_empty() {
+        return Ok(YamlFrontMatter::default());
+    }
+    serde_yaml::from_str(&without_repeated_keys(yaml)).map_err(|source| MergeError::YamlError {
+        file: file.to_path_buf(),
+        source,
+    })
+}
+
+/// The alias field under `key`, if `front_matter` has it
+fn alias_field<'a>(front_matter: &'a YamlFrontMatter, key: &str) -> Option<&'a AliasField> {
+    match key {
+        "alias" => Some(&front_matter.alias),
+        "aliases" => Some(&front_matter.aliases),
+        _ => None,
+    }
+}
+
+/// `key` with `aliases` as its value, written the way `like` is
+fn alias_lines(key: &str, aliases: &[String], like: Option<&AliasField>) -> String {
+    match like {
+        Some(AliasField::List(_)) => {
+            let mut out = format!("{key}:\n");
+            for alias in aliases {
+                let _ = writeln!(out, "  - {alias}");
+            }
+            out
+        }
+        _ => format!("{key}: {}\n", aliases.join(", ")),
+    }
+}
+
+/// The wikilink or tag at `offset` in `source`, pointed at `to` instead, keeping the text it shows
+/// Returns what was written, and what to write instead
+fn rewrite_link(
+    source: &str,
+    offset: usize,
+    length: usize,
+    to: &str,
+) -> Option<(usize, String, String)> {
+    let rest = source.get(offset..)?;
+    if rest.starts_with("[[") {
+        let end = rest.find("]]")? + 2;
+        let written = &rest[..end];
+        let inner = &written[2..end - 2];
+        // The title comes first, in `[[title|url]]`
+        let title = inner.split_once('|').map_or(inner, |(title, _)| title);
+        if title == to {
+            return None;
+        }
+        let tag = source[..offset].ends_with('#');
+        let replacement = if tag {
+            format!("[[{to}]]")
+        } else {
+            format!("[[{title}|{to}]]")
+        };
+        Some((end, written.to_owned(), replacement))
+    } else {
+        // A tag, like #lorem, which is pointed at by its name
+        let written = rest.get(..length)?;
+        Some((length, written.to_owned(), format!("[[{to}]]")))
+    }
+}
+
+/// Merge the page `a` into the page `b`, and delete `a`
+/// Like `--fix`, this refuses to run in a dirty git repo, or backs files up with `--no-vcs`
+///
+/// # Errors
+///
+/// If either is not one of the pages being linted or is a notebook, they are the same page,
+/// or the files can't be parsed or written
+pub fn merge(config: &Config, a: &Path, b: &Path) -> Result<Merged, MergeError> {
+    let io_error = |file: &Path| {
+        let file = file.to_path_buf();
+        move |source| MergeError::IoError { file, source }
+    };
+    let files: Arc<dyn FileProvider> = Arc::new(FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    // The pages as they are named in the alias table and link index
+    let find = |page: &Path| {
+        let canonical = page.canonicalize().ok();
+        all_files
+            .iter()
+            .find(|file| canonical.is_some() && file.canonicalize().ok() == canonical)
+            .cloned()
+            .ok_or_else(|| MergeError::NotAPage(page.to_path_buf()))
+    };
+    let (a, b) = (find(a)?, find(b)?);
+    if a == b {
+        return Err(MergeError::SamePage);
+    }
+    for page in [&a, &b] {
+        if is_notebook(page) {
+            return Err(MergeError::Notebook(page.clone()));
+        }
+    }
+    let mut backup = prepare_fixes(config)?;
+
+    let duplicate_alias_visitor = first_pass(
+        &all_files,
+        config,
+        &files,
+        &progress::NoProgress,
+        Rc::default(),
+    )?;
+    let link_index_visitor = Rc::new(RefCell::new(LinkIndexVisitor::new(
+        duplicate_alias_visitor.alias_table,
+        config.skip_html,
+    )));
+    for file in &all_files {
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![link_index_visitor.clone()];
+        parse(files.as_ref(), file, &config.markdown, visitors).map_err(OutputErrors::from)?;
+    }
+    let link_index: LinkIndex = Rc::try_unwrap(link_index_visitor)
+        .expect("parse is done")
+        .into_inner()
+        .link_index;
+
+    // Point the links at `b`, in every file which links to `a`, `a` and `b` included
+    let b_name = page_name(&b, &config.filename_to_alias);
+    let mut rewrites = Vec::new();
+    let mut a_source = std::fs::read_to_string(&a).map_err(io_error(&a))?;
+    let mut b_source = std::fs::read_to_string(&b).map_err(io_error(&b))?;
+    for (path, links) in &link_index.0 {
+        if is_notebook(path) {
+            continue;
+        }
+        let links: Vec<_> = links
+            .iter()
+            .filter(|link| link.target.as_ref() == Some(&a))
+            .collect();
+        if links.is_empty() {
+            continue;
+        }
+        let mut source = if *path == a {
+            a_source.clone()
+        } else if *path == b {
+            b_source.clone()
+        } else {
+            std::fs::read_to_string(path).map_err(io_error(path))?
+        };
+        let before = rewrites.len();
+        for link in links.iter().rev() {
+            let Some((length, from, to)) = rewrite_link(&source, link.offset, link.length, &b_name)
+            else {
+                continue;
+            };
+            let (line, column) = line_column(&source, link.offset);
+            source.replace_range(link.offset..link.offset + length, &to);
+            rewrites.push(Rewrite {
+                path: path.clone(),
+                line,
+                column,
+                offset: link.offset,
+                from,
+                to,
+            });
+        }
+        if rewrites.len() == before {
+            continue;
+        }
+        if *path == a {
+            a_source = source;
+        } else if *path == b {
+            b_source = source;
+        } else {
+            if let Some(backup) = &mut backup {
+                backup.save(path)?;
+            }
+            std::fs::write(path, source).map_err(io_error(path))?;
+        }
+    }
+
+    // The aliases of `a`, and its name, become aliases of `b`
+    let (a_front_matter, a_body) = split(&a_source);
+    let (b_front_matter, b_body) = split(&b_source);
+    let a_yaml = a_front_matter.map_or("", |(_, yaml)| yaml);
+    let b_yaml = b_front_matter.map_or("", |(_, yaml)| yaml);
+    let a_parsed = parse_yaml(a_yaml, &a)?;
+    let b_parsed = parse_yaml(b_yaml, &b)?;
+    let a_keys = top_level_keys(a_yaml);
+    let b_keys = top_level_keys(b_yaml);
+    let has =
+        |keys: &[(&str, std::ops::Range<usize>)], key: &str| keys.iter().any(|(k, _)| *k == key);
+    let key = ["alias", "aliases"]
+        .into_iter()
+        .find(|key| has(&b_keys, key))
+        .or_else(|| {
+            ["alias", "aliases"]
+                .into_iter()
+                .find(|key| has(&a_keys, key))
+        })
+        .unwrap_or(ALIAS_KEY);
+    let mut existing: Vec<String> = b_parsed
+        .alias
+        .entries()
+        .into_iter()
+        .chain(b_parsed.aliases.entries())
+        .map(ToOwned::to_owned)
+        .collect();
+    existing.push(b_name);
+    let mut added: Vec<String> = Vec::new();
+    for alias in std::iter::once(page_name(&a, &config.filename_to_alias)).chain(
+        a_parsed
+            .alias
+            .entries()
+            .into_iter()
+            .chain(a_parsed.aliases.entries())
+            .map(ToOwned::to_owned),
+    ) {
+        let known = |other: &String| eq_folded(other, &alias);
+        if !existing.iter().any(known) && !added.iter().any(known) {
+            added.push(alias);
+        }
+    }
+
+    // The front matter of `b`, with the aliases, and the keys only `a` has
+    let mut yaml = String::new();
+    let mut replaced = false;
+    let mut end = b_keys
+        .first()
+        .map_or(b_yaml.len(), |(_, range)| range.start);
+    yaml.push_str(&b_yaml[..end]);
+    for (k, range) in &b_keys {
+        if *k == key && !replaced && !added.is_empty() {
+            let field = alias_field(&b_parsed, key);
+            let mut aliases: Vec<String> = field
+                .map(|field| field.entries().into_iter().map(ToOwned::to_owned).collect())
+                .unwrap_or_default();
+            aliases.extend(added.iter().cloned());
+            yaml.push_str(&alias_lines(key, &aliases, field));
+            replaced = true;
+        } else {
+            yaml.push_str(&b_yaml[range.clone()]);
+        }
+        end = range.end;
+    }
+    yaml.push_str(&b_yaml[end..]);
+    if !yaml.is_empty() && !yaml.ends_with('\n') {
+        yaml.push('\n');
+    }
+    if !replaced && !added.is_empty() {
+        let like = has(&a_keys, key)
+            .then(|| alias_field(&a_parsed, key))
+            .flatten();
+        yaml.push_str(&alias_lines(key, &added, like));
+    }
+    for (k, range) in &a_keys {
+        if !["alias", "aliases"].contains(k) && !has(&b_keys, k) {
+            yaml.push_str(&a_yaml[range.clone()]);
+            if !yaml.ends_with('\n') {
+                yaml.push('\n');
+            }
+        }
+    }
+
+    let a_text = a_source[a_body..].trim();
+    let b_text = b_source[b_body..].trim_end();
+    let mut out = String::new();
+    if !yaml.is_empty() {
+        let _ = write!(out, "---\n{yaml}---\n");
+    }
+    out.push_str(b_text);
+    if !a_text.is_empty() {
+        if !b_text.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(a_text);
+    }
+    out.push('\n');
+
+    if let Some(backup) = &mut backup {
+        backup.save(&a)?;
+        backup.save(&b)?;
+    }
+    std::fs::write(&b, out).map_err(io_error(&b))?;
+    std::fs::remove_file(&a).map_err(io_error(&a))?;
+    rewrites.sort();
+    Ok(Merged {
+        a,
+        b,
+        aliases: added,
+        rewrites,
+    })
+}
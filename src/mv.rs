@@ -0,0 +1,501 @@
+//! Move a directory of pages, and rewrite the links the move would break, see `mdlinker mv`
+//!
+//! Wikilinks to aliases don't change when a file moves, so only path-style wikilinks, like
+//! `[[projects/lorem]]`, which resolve with [`LinkResolution`], and relative markdown links,
+//! like `[lorem](../projects/lorem.md)`, are rewritten.
+//! Links into the directory are rewritten to where the files end up, and relative links out of
+//! it are rewritten from where they end up
+//!
+//! A single file can be renamed too, see [`rename`], which also rewrites the wikilinks to the
+//! alias of its filename
+// Like the linter, these return its errors, which are large
+#![allow(clippy::result_large_err)]
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeLink, NodeValue, NodeWikiLink},
+};
+use hashbrown::HashMap;
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    config::{Config, LinkResolution},
+    file::{
+        backup::Backup,
+        case::fold,
+        content::wikilink::Alias,
+        content::{line_column, notebook::is_notebook, source_map::SourceMap},
+        name::{get_filename, join_link, path_key, Filename},
+        provider::{FileProvider, FileSystem},
+    },
+    get_all_files, prepare_fixes,
+    rules::{wrong_link_style::relative_link, ErrorCode, FixError},
+    sed::ReplacePair,
+    visitor::{parse, FinalizeError, Finalized, VisitError, Visitor},
+    OutputErrors,
+};
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum MoveError {
+    #[error(transparent)]
+    OutputErrors(#[from] OutputErrors),
+    #[error(transparent)]
+    FixError(#[from] FixError),
+    #[error("{0:?} is not a directory")]
+    NotADirectory(PathBuf),
+    #[error("{0:?} is not a file")]
+    NotAFile(PathBuf),
+    #[error("{0:?} already exists")]
+    AlreadyExists(PathBuf),
+    #[error("There was an IOError on {file:?}: {source}")]
+    IoError {
+        file: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+/// A link rewritten by [`mv`]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rewrite {
+    /// The file containing the link, where it was before the move
+    pub path: PathBuf,
+    /// Starting from 1
+    pub line: usize,
+    /// In characters, starting from 1
+    pub column: usize,
+    /// The byte offset of the path in the link
+    pub offset: usize,
+    /// The path in the link, as written
+    pub from: String,
+    /// The path in the link after the move
+    pub to: String,
+}
+
+/// The answer to `mdlinker mv <src> <dst>`
+#[derive(Debug, Clone)]
+pub struct Moved {
+    pub src: PathBuf,
+    pub dst: PathBuf,
+    /// Every link rewritten, sorted by file and position
+    pub rewrites: Vec<Rewrite>,
+}
+
+impl Display for Moved {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} -> {}", self.src.display(), self.dst.display())?;
+        for rewrite in &self.rewrites {
+            writeln!(
+                f,
+                "  {}:{}:{}: {} -> {}",
+                rewrite.path.display(),
+                rewrite.line,
+                rewrite.column,
+                rewrite.from,
+                rewrite.to
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// How a link found by [`MoveVisitor`] is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkKind {
+    /// A path-style wikilink, like `[[projects/lorem]]`
+    Wikilink,
+    /// A relative markdown link, like `[lorem](../projects/lorem.md#anchor)`
+    Markdown,
+    /// A wikilink to an alias, like `[[lorem]]`, only rewritten by [`rename`]
+    Alias,
+}
+
+/// A link which may need to be rewritten
+#[derive(Debug, Clone)]
+struct Link {
+    kind: LinkKind,
+    /// The byte offset of the path in the link
+    offset: usize,
+    /// The path in the link, as written, without any `#anchor`
+    path: String,
+}
+
+/// Finds every link in each file which a move could break
+#[derive(Debug, Default)]
+pub struct MoveVisitor {
+    /// Keyed by the file containing the links
+    links: BTreeMap<PathBuf, Vec<Link>>,
+    /// The links in the current file
+    current: Vec<Link>,
+}
+
+impl MoveVisitor {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Visitor for MoveVisitor {
+    fn name(&self) -> &'static str {
+        "MoveVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let sourcepos = data_ref.sourcepos;
+        let start = source.start(sourcepos);
+        let end = source.end(sourcepos);
+        let Some(written) = source.get(start..end) else {
+            return Ok(());
+        };
+        let link = match &data_ref.value {
+            NodeValue::WikiLink(NodeWikiLink { url }) => {
+                // The url comes last, in both `[[url]]` and `[[title|url]]`
+                let Some(before) = written.strip_suffix("]]").and_then(|x| x.strip_suffix(url))
+                else {
+                    return Ok(());
+                };
+                Link {
+                    kind: if url.contains('/') {
+                        LinkKind::Wikilink
+                    } else {
+                        LinkKind::Alias
+                    },
+                    offset: start + before.len(),
+                    path: url.clone(),
+                }
+            }
+            NodeValue::Link(NodeLink { .. }) => {
+                // Only inline links, not autolinks or reference links, and not `<url>` ones
+                let Some((inner, destination)) = written
+                    .strip_suffix(')')
+                    .and_then(|inner| Some((inner, inner.rfind("](")? + 2)))
+                else {
+                    return Ok(());
+                };
+                let rest = &inner[destination..];
+                let offset = destination + (rest.len() - rest.trim_start().len());
+                let url = inner[offset..]
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default();
+                let path = url.split('#').next().unwrap_or_default();
+                // Like https:// or mailto:, Zola's @/ links, and links from the root
+                if path.is_empty() || path.contains(':') || path.starts_with(['@', '/', '<']) {
+                    return Ok(());
+                }
+                Link {
+                    kind: LinkKind::Markdown,
+                    offset: start + offset,
+                    path: path.to_owned(),
+                }
+            }
+            _ => return Ok(()),
+        };
+        self.current.push(link);
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        _source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let links = std::mem::take(&mut self.current);
+        // A notebook's source is its markdown cells, so offsets into it are not offsets into the file
+        if !is_notebook(path) {
+            self.links.insert(path.to_path_buf(), links);
+        }
+        Ok(())
+    }
+    fn _finalize(&mut self, _excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
+
+/// Where files are before and after a move, and where path-style wikilinks resolve
+struct Plan {
+    /// Canonical
+    src: PathBuf,
+    /// Canonical, though it doesn't exist yet
+    dst: PathBuf,
+    /// Canonical, see [`Config::root_directory`]
+    root: Option<PathBuf>,
+    link_resolution: LinkResolution,
+    /// The paths which resolve to a file, see [`path_key`], only unambiguous ones are kept
+    paths: HashMap<String, PathBuf>,
+    /// For [`LinkResolution::ShortestPath`], every trailing part of each path
+    suffixes: HashMap<String, Vec<PathBuf>>,
+    /// When [`rename`] changes the alias of the filename, the old alias and the new one as
+    /// written
+    renamed_alias: Option<(Alias, String)>,
+}
+
+impl Plan {
+    /// Where a canonical path ends up
+    fn moved(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.src) {
+            // Joining nothing would add a trailing separator
+            Ok(rest) if rest.as_os_str().is_empty() => self.dst.clone(),
+            Ok(rest) => self.dst.join(rest),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    /// The canonical file a path-style wikilink in the canonical file `source` resolves to
+    fn resolve_wikilink(&self, link: &str, source: &Path) -> Option<PathBuf> {
+        let root = self.root.as_ref()?;
+        let key = match self.link_resolution {
+            LinkResolution::AliasOnly => return None,
+            LinkResolution::ShortestPath | LinkResolution::Absolute => {
+                join_link(vec![], &fold(link))?
+            }
+            LinkResolution::Relative => {
+                let mut base: Vec<String> = path_key(source, root)?
+                    .split('/')
+                    .map(ToOwned::to_owned)
+                    .collect();
+                base.pop();
+                join_link(base, &fold(link))?
+            }
+        };
+        if let Some(file) = self.paths.get(&key) {
+            return Some(file.clone());
+        }
+        match self.suffixes.get(&key)?.as_slice() {
+            [file] => Some(file.clone()),
+            _ => None,
+        }
+    }
+
+    /// A path-style wikilink from `source` to `target`, both canonical and after the move,
+    /// written like `link`
+    fn wikilink(&self, link: &str, source: &Path, target: &Path) -> Option<String> {
+        let root = self.root.as_ref()?;
+        let extension = if link.to_lowercase().ends_with(".md") {
+            target
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let without_extension = target.with_extension("");
+        let parts: Vec<String> = without_extension
+            .strip_prefix(root)
+            .ok()?
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let out = match self.link_resolution {
+            LinkResolution::AliasOnly => return None,
+            LinkResolution::Absolute => parts.join("/"),
+            LinkResolution::ShortestPath => {
+                let length = link
+                    .split('/')
+                    .filter(|part| !part.is_empty() && *part != ".")
+                    .count();
+                parts[parts.len().saturating_sub(length)..].join("/")
+            }
+            LinkResolution::Relative => {
+                relative_link(source, &without_extension).replace("%20", " ")
+            }
+        };
+        Some(out + &extension)
+    }
+
+    /// The new path in `link`, in the canonical file `source`, or [`None`] if it doesn't change
+    fn rewrite(&self, link: &Link, source: &Path) -> Option<String> {
+        if let Some((alias, to)) = &self.renamed_alias {
+            if link.kind != LinkKind::Markdown && Alias::new(&link.path) == *alias {
+                return Some(to.clone());
+            }
+        }
+        let target = match link.kind {
+            LinkKind::Alias => return None,
+            LinkKind::Wikilink => self.resolve_wikilink(&link.path, source)?,
+            LinkKind::Markdown => source
+                .parent()?
+                .join(link.path.replace("%20", " "))
+                .canonicalize()
+                .ok()?,
+        };
+        let (new_source, new_target) = (self.moved(source), self.moved(&target));
+        if new_source == source && new_target == target {
+            return None;
+        }
+        let out = match link.kind {
+            LinkKind::Alias => return None,
+            LinkKind::Wikilink => self.wikilink(&link.path, &new_source, &new_target)?,
+            LinkKind::Markdown => relative_link(&new_source, &new_target),
+        };
+        (out != link.path).then_some(out)
+    }
+}
+
+/// Move the directory `src` to `dst`, rewriting the links the move would break
+/// Like `--fix`, this refuses to run in a dirty git repo, or backs files up with `--no-vcs`
+///
+/// # Errors
+///
+/// If `src` is not a directory, `dst` already exists, or the files can't be parsed or written
+pub fn mv(config: &Config, src: &Path, dst: &Path) -> Result<Moved, MoveError> {
+    if !src.is_dir() {
+        return Err(MoveError::NotADirectory(src.to_path_buf()));
+    }
+    let mut backup = prepare_fixes(config)?;
+    move_path(config, src, dst, None, backup.as_mut())
+}
+
+/// Rename the file `src` to `dst`, rewriting the links the rename would break like [`mv`], and
+/// the wikilinks to the alias of its filename, see [`Config::filename_to_alias`]
+/// This is how fixes rename files, so unlike [`mv`] it does not check the git repo, which the
+/// earlier fixes have changed, and with `--no-vcs` it backs files up on its own
+///
+/// # Errors
+///
+/// If `src` is not a file, `dst` already exists, or the files can't be parsed or written
+pub fn rename(config: &Config, src: &Path, dst: &Path) -> Result<Moved, MoveError> {
+    if !src.is_file() {
+        return Err(MoveError::NotAFile(src.to_path_buf()));
+    }
+    let mut backup = (config.no_vcs || cfg!(not(feature = "git"))).then(Backup::new);
+    let (from, to) = (written_alias(src, config), written_alias(dst, config));
+    let renamed_alias = (Alias::new(&from) != Alias::new(&to)).then(|| (Alias::new(&from), to));
+    move_path(config, src, dst, renamed_alias, backup.as_mut())
+}
+
+/// The alias of the filename of `path` as written, keeping its case, see
+/// [`Config::filename_to_alias`]
+fn written_alias(path: &Path, config: &Config) -> String {
+    let (from, to): (String, String) = config.filename_to_alias.clone().into();
+    let filename = get_filename(path);
+    ReplacePair::<Filename, String>::new(&from, &to)
+        .map_or_else(|_| filename.to_string(), |pair| pair.apply(&filename))
+}
+
+/// Moves `src` to `dst` and rewrites the links, see [`mv`] and [`rename`]
+fn move_path(
+    config: &Config,
+    src: &Path,
+    dst: &Path,
+    renamed_alias: Option<(Alias, String)>,
+    mut backup: Option<&mut Backup>,
+) -> Result<Moved, MoveError> {
+    if dst.exists() {
+        return Err(MoveError::AlreadyExists(dst.to_path_buf()));
+    }
+    let io_error = |file: &Path| {
+        let file = file.to_path_buf();
+        move |source| MoveError::IoError { file, source }
+    };
+    let canonical_src = src.canonicalize().map_err(io_error(src))?;
+    let parent = dst
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent).map_err(io_error(parent))?;
+    let canonical_dst = parent
+        .canonicalize()
+        .map_err(io_error(parent))?
+        .join(dst.file_name().unwrap_or_default());
+    if canonical_dst.starts_with(&canonical_src) {
+        return Err(MoveError::IoError {
+            file: dst.to_path_buf(),
+            source: std::io::Error::other("A directory can't be moved into itself"),
+        });
+    }
+
+    let files: Arc<dyn FileProvider> = Arc::new(FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let move_visitor = Rc::new(RefCell::new(MoveVisitor::new()));
+    for file in &all_files {
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![move_visitor.clone()];
+        parse(files.as_ref(), file, &config.markdown, visitors).map_err(OutputErrors::from)?;
+    }
+    let links = Rc::try_unwrap(move_visitor)
+        .expect("parse is done")
+        .into_inner()
+        .links;
+
+    let root = config.root_directory.canonicalize().ok();
+    let mut paths: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    let mut suffixes: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    if let Some(root) = &root {
+        for file in &all_files {
+            let (Some(key), Ok(canonical)) = (path_key(file, root), file.canonicalize()) else {
+                continue;
+            };
+            if config.link_resolution == LinkResolution::ShortestPath {
+                let parts: Vec<&str> = key.split('/').collect();
+                for i in 1..parts.len() {
+                    suffixes
+                        .entry(parts[i..].join("/"))
+                        .or_default()
+                        .push(canonical.clone());
+                }
+            }
+            paths.entry(key).or_default().push(canonical);
+        }
+    }
+    let plan = Plan {
+        src: canonical_src,
+        dst: canonical_dst,
+        root,
+        link_resolution: config.link_resolution,
+        paths: paths
+            .into_iter()
+            .filter_map(|(key, files)| match files.as_slice() {
+                [file] => Some((key, file.clone())),
+                _ => None,
+            })
+            .collect(),
+        suffixes,
+        renamed_alias,
+    };
+
+    let mut rewrites = Vec::new();
+    for (path, links) in &links {
+        let Ok(source) = path.canonicalize() else {
+            continue;
+        };
+        let file_rewrites: Vec<(&Link, String)> = links
+            .iter()
+            .filter_map(|link| Some((link, plan.rewrite(link, &source)?)))
+            .collect();
+        if file_rewrites.is_empty() {
+            continue;
+        }
+        if let Some(backup) = backup.as_deref_mut() {
+            backup.save(path)?;
+        }
+        let mut content = std::fs::read_to_string(path).map_err(io_error(path))?;
+        for (link, to) in file_rewrites.iter().rev() {
+            let (line, column) = line_column(&content, link.offset);
+            content.replace_range(link.offset..link.offset + link.path.len(), to);
+            rewrites.push(Rewrite {
+                path: path.clone(),
+                line,
+                column,
+                offset: link.offset,
+                from: link.path.clone(),
+                to: to.clone(),
+            });
+        }
+        std::fs::write(path, content).map_err(io_error(path))?;
+    }
+    std::fs::rename(src, dst).map_err(io_error(src))?;
+    rewrites.sort();
+    Ok(Moved {
+        src: src.to_path_buf(),
+        dst: dst.to_path_buf(),
+        rewrites,
+    })
+}
@@ -0,0 +1,138 @@
+//! Create a page, see `mdlinker new`
+//!
+//! The page is named and created like the [`crate::rules::broken_wikilink::BrokenWikilink`] fix
+//! creates pages, and a wikilink to it can be added to another file, like today's journal
+// Like the linter, these return its errors, which are large
+#![allow(clippy::result_large_err)]
+use std::{
+    backtrace::Backtrace,
+    fmt::{Display, Formatter},
+    io::Write,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    file::{
+        content::wikilink::Alias,
+        name::Filename,
+        provider::{FileProvider, FileSystem},
+    },
+    first_pass, get_all_files, prepare_fixes, progress,
+    rules::{
+        broken_wikilink::{create_page, new_files_directory},
+        FixError,
+    },
+    OutputErrors,
+};
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum NewPageError {
+    #[error(transparent)]
+    OutputErrors(#[from] OutputErrors),
+    #[error(transparent)]
+    FixError(#[from] FixError),
+    #[error("The page {alias} already exists at {path:?}")]
+    AlreadyExists { alias: Alias, path: PathBuf },
+}
+
+/// The answer to `mdlinker new <title>`
+#[derive(Debug, Clone)]
+pub struct NewPage {
+    pub path: PathBuf,
+    /// The file a wikilink to the page was added to
+    pub linked_from: Option<PathBuf>,
+}
+
+impl Display for NewPage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.path.display())?;
+        if let Some(linked_from) = &self.linked_from {
+            writeln!(f, "  linked from: {}", linked_from.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Add `- [[title]]` to the end of `path`, which is created if it doesn't exist
+fn add_wikilink(path: &Path, title: &str) -> Result<(), FixError> {
+    let io_error = |source| FixError::IOError {
+        source,
+        backtrace: Backtrace::force_capture(),
+        file: path.to_string_lossy().to_string(),
+    };
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(io_error)?;
+    }
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error)?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{separator}- [[{title}]]").map_err(io_error)
+}
+
+/// Create a page titled `title`, named with [`Config::alias_to_filename`] in
+/// [`new_files_directory`], and add a wikilink to it to the end of `link_from`
+/// Like `--fix`, this refuses to run in a dirty git repo, or backs files up with `--no-vcs`
+///
+/// # Errors
+///
+/// If a page already has the title as its name or an alias, or the files can't be written
+pub fn new_page(
+    config: &Config,
+    title: &str,
+    link_from: Option<&Path>,
+) -> Result<NewPage, NewPageError> {
+    let files: Arc<dyn FileProvider> = Arc::new(FileSystem);
+    let all_files = get_all_files(config, files.as_ref());
+    let alias = Alias::new(title.trim());
+    let duplicate_alias_visitor = first_pass(
+        &all_files,
+        config,
+        &files,
+        &progress::NoProgress,
+        Rc::default(),
+    )?;
+    if let Some(path) = duplicate_alias_visitor.alias_table.get(&alias) {
+        return Err(NewPageError::AlreadyExists {
+            alias,
+            path: path.clone(),
+        });
+    }
+    let mut backup = prepare_fixes(config)?;
+    let directory = new_files_directory(config, link_from);
+    let path = directory.join(format!("{}.md", Filename::from_title(title.trim(), config)));
+    if path.exists() {
+        return Err(NewPageError::AlreadyExists { alias, path });
+    }
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory).map_err(|source| FixError::IOError {
+            source,
+            backtrace: Backtrace::force_capture(),
+            file: directory.to_string_lossy().to_string(),
+        })?;
+    }
+    create_page(config, title.trim(), &path)?;
+    if let Some(link_from) = link_from {
+        if let (Some(backup), true) = (&mut backup, link_from.exists()) {
+            backup.save(link_from)?;
+        }
+        add_wikilink(link_from, title.trim())?;
+    }
+    Ok(NewPage {
+        path,
+        linked_from: link_from.map(Path::to_path_buf),
+    })
+}
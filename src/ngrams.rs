@@ -1,9 +1,14 @@
+//! Ngrams, runs of up to n words, which filenames are compared by in
+//! [`crate::rules::similar_filename::SimilarFilename`], see [`UpToN`] to make them from any text
 use std::{
     fmt::{Display, Formatter},
     path::PathBuf,
 };
 
 use regex::{Error as RegexError, Regex};
+
+use crate::file::case::fold;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -12,27 +17,30 @@ pub enum CalculateError {
     MissingSubstringError {
         path: PathBuf,
         ngram: String,
-        backtrace: std::backtrace::Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
     #[error("'{compilation_string}' regex did not compile: {source:?}")]
     RegexCompilationError {
         source: RegexError,
         compilation_string: String,
-        backtrace: std::backtrace::Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
 }
 
-/// An ngram, " " seperated, lowercase
-#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+/// An ngram, " " seperated, case folded, see [`crate::file::case`]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct Ngram(String);
 
 impl Ngram {
     #[must_use]
-    pub fn new(ngram: &[&str]) -> Self {
+    pub fn new<S: AsRef<str>>(ngram: &[S]) -> Self {
         Self(
             ngram
                 .iter()
-                .map(|s| s.to_lowercase())
+                .map(|s| fold(s.as_ref()))
                 .collect::<Vec<_>>()
                 .join(" "),
         )
@@ -48,6 +56,7 @@ impl Ngram {
             .map(std::borrow::ToOwned::to_owned)
             .collect()
     }
+    /// The length in bytes
     #[must_use]
     pub fn len(&self) -> usize {
         self.0.len()
@@ -66,40 +75,143 @@ impl Display for Ngram {
 
 impl PartialEq<&str> for Ngram {
     fn eq(&self, other: &&str) -> bool {
-        self.0 == *other.to_lowercase()
+        self.0 == fold(other)
     }
 }
 
+/// Split a word where its case changes, like `MyProjectNotes` into `My`, `Project` and `Notes`,
+/// keeping acronyms together, like `HTMLParser` into `HTML` and `Parser`,
+/// and between letters and digits, like `Notes2024` into `Notes` and `2024`
+#[must_use]
+pub fn split_camel_case(word: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut out = Vec::new();
+    let mut start = 0;
+    for (i, &(offset, c)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let boundary = (previous.is_lowercase() && c.is_uppercase())
+            || (previous.is_uppercase()
+                && c.is_uppercase()
+                && next.is_some_and(char::is_lowercase))
+            || (previous.is_alphabetic() && c.is_numeric())
+            || (previous.is_numeric() && c.is_alphabetic());
+        if boundary {
+            out.push(&word[start..offset]);
+            start = offset;
+        }
+    }
+    out.push(&word[start..]);
+    out
+}
+
 /// Gives you ngrams of size 1..=n
 /// Stops on boundary pattern
+/// Words are also split with [`split_camel_case`] if `camel_case` is true
+/// See [`UpToN`] to go through them without collecting them all
 #[must_use]
-pub fn up_to_n(text: &str, n: usize, boundary_regex: &Regex, spacing_regex: &Regex) -> Vec<Ngram> {
-    let mut ngrams = Vec::new();
+pub fn up_to_n(
+    text: &str,
+    n: usize,
+    boundary_regex: &Regex,
+    spacing_regex: &Regex,
+    camel_case: bool,
+) -> Vec<Ngram> {
+    UpToN::new(text, n, boundary_regex, spacing_regex, camel_case).collect()
+}
 
-    // Split the text into segments based on the boundaries (i.e., sentences/phrases)
-    let segments: Vec<&str> = boundary_regex.split(text).collect();
+/// An iterator over the ngrams of size 1..=n of a text, in the same order as [`up_to_n`]
+///
+/// The text is split into segments on the boundary pattern, and the segments into words on the
+/// spacing pattern. Only the words of the current segment are kept, so any amount of text,
+/// like the content of every file, can be gone through
+///
+/// # Example
+/// ```
+/// use mdlinker::ngrams::{Ngram, UpToN};
+/// use regex::Regex;
+///
+/// let boundary_regex = Regex::new(r"[,.]").unwrap();
+/// let spacing_regex = Regex::new(r"\s").unwrap();
+/// let ngrams: Vec<Ngram> =
+///     UpToN::new("lorem ipsum, dolor", 2, &boundary_regex, &spacing_regex, false).collect();
+/// assert_eq!(ngrams, vec!["lorem", "ipsum", "lorem ipsum", "dolor"]);
+/// ```
+#[derive(Debug)]
+pub struct UpToN<'r, 'h> {
+    segments: regex::Split<'r, 'h>,
+    spacing_regex: &'r Regex,
+    camel_case: bool,
+    n: usize,
+    /// The words of the current segment
+    words: Vec<String>,
+    /// The size of the next ngram
+    size: usize,
+    /// The index of the first word of the next ngram
+    start: usize,
+}
 
-    // Generate n-grams for each segment
-    for segment in segments {
-        // Replace the spacing pattern with a single space
-        let segment = spacing_regex.replace_all(segment, " ");
-        let words: Vec<&str> = segment
-            .split_whitespace()
-            .filter(|&word| !word.is_empty())
-            .collect();
+impl<'r, 'h> UpToN<'r, 'h> {
+    /// See [`up_to_n`]
+    #[must_use]
+    pub fn new(
+        text: &'h str,
+        n: usize,
+        boundary_regex: &'r Regex,
+        spacing_regex: &'r Regex,
+        camel_case: bool,
+    ) -> Self {
+        Self {
+            segments: boundary_regex.split(text),
+            spacing_regex,
+            camel_case,
+            n,
+            words: Vec::new(),
+            size: 1,
+            start: 0,
+        }
+    }
 
-        // Only attempt to create n-grams if there are enough words
-        for n in 1..=n {
-            if words.len() >= n {
-                for i in 0..=words.len().saturating_sub(n) {
-                    let ngram = Ngram::new(&words[i..i + n]);
-                    ngrams.push(ngram);
+    /// The words of a segment, after replacing the spacing pattern with a single space
+    fn words(&self, segment: &str) -> Vec<String> {
+        self.spacing_regex
+            .replace_all(segment, " ")
+            .split_whitespace()
+            .flat_map(|word| {
+                if self.camel_case {
+                    split_camel_case(word)
+                } else {
+                    vec![word]
                 }
+            })
+            .filter(|word| !word.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+}
+
+impl Iterator for UpToN<'_, '_> {
+    type Item = Ngram;
+
+    fn next(&mut self) -> Option<Ngram> {
+        loop {
+            if self.size <= self.n && self.start + self.size <= self.words.len() {
+                let ngram = Ngram::new(&self.words[self.start..self.start + self.size]);
+                self.start += 1;
+                return Some(ngram);
+            }
+            // Only attempt to create bigger n-grams if there are enough words
+            if self.size < self.n && self.size < self.words.len() {
+                self.size += 1;
+                self.start = 0;
+                continue;
             }
+            let segment = self.segments.next()?;
+            self.words = self.words(segment);
+            self.size = 1;
+            self.start = 0;
         }
     }
-
-    ngrams
 }
 
 #[cfg(test)]
@@ -278,8 +390,13 @@ mod tests {
             let beoundary_regex = Regex::new(r"[,.]").expect("Just a test");
             let spacing_regex = Regex::new(r" ").expect("Just a test");
             for n in (1..=3).rev() {
-                let up_to_out =
-                    HashSet::from_iter(up_to_n(LOREM_IPSUM, n, &beoundary_regex, &spacing_regex));
+                let up_to_out = HashSet::from_iter(up_to_n(
+                    LOREM_IPSUM,
+                    n,
+                    &beoundary_regex,
+                    &spacing_regex,
+                    false,
+                ));
                 let mut out = HashSet::new();
                 for m in 1..=n {
                     let to = ngrams(LOREM_IPSUM, m, r"[,.]");
@@ -289,4 +406,54 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_split_camel_case() {
+        use super::split_camel_case;
+        assert_eq!(
+            split_camel_case("MyProjectNotes"),
+            vec!["My", "Project", "Notes"]
+        );
+        assert_eq!(split_camel_case("HTMLParser"), vec!["HTML", "Parser"]);
+        assert_eq!(
+            split_camel_case("notes2024v2"),
+            vec!["notes", "2024", "v", "2"]
+        );
+        assert_eq!(split_camel_case("lorem"), vec!["lorem"]);
+    }
+
+    #[test]
+    fn test_up_to_camel_case() {
+        let boundary_regex = Regex::new(r"___").expect("Just a test");
+        let spacing_regex = Regex::new(r"-|_|\s").expect("Just a test");
+        let out = up_to_n("MyProject-notes", 2, &boundary_regex, &spacing_regex, true);
+        assert_eq!(
+            out,
+            vec![
+                Ngram::new(&["my"]),
+                Ngram::new(&["project"]),
+                Ngram::new(&["notes"]),
+                Ngram::new(&["my", "project"]),
+                Ngram::new(&["project", "notes"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_up_to_n_sizes() {
+        use super::UpToN;
+        let boundary_regex = Regex::new(r"[,.]").expect("Just a test");
+        let spacing_regex = Regex::new(r" ").expect("Just a test");
+        let ngrams = |text, n| {
+            UpToN::new(text, n, &boundary_regex, &spacing_regex, false)
+                .map(|ngram| ngram.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(ngrams("lorem ipsum", 0).is_empty());
+        assert_eq!(
+            ngrams("lorem ipsum", 5),
+            vec!["lorem", "ipsum", "lorem ipsum"]
+        );
+        assert_eq!(ngrams(", lorem,, ipsum.", 2), vec!["lorem", "ipsum"]);
+    }
 }
@@ -0,0 +1,285 @@
+//! Which passes a check runs, worked out from what the rules which are on need, see [`Plan`]
+//!
+//! A new rule of the second pass declares its [`Input`]s in [`ThirdPassRule::requires`] and its
+//! visitor in [`ThirdPassRule::visitor`], and the passes it depends on are run for it
+use std::{cell::RefCell, collections::BTreeSet, path::PathBuf, rc::Rc};
+
+use hashbrown::HashMap;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use crate::{
+    alias_index::AliasIndex,
+    budget::SourceBudget,
+    config::Config,
+    file::content::wikilink::Alias,
+    linter::Linter,
+    progress::Pass,
+    rules::{
+        alias_casing::AliasCasingVisitor, broken_reference::BrokenReferenceVisitor,
+        broken_relref::BrokenRelrefVisitor, broken_wikilink::BrokenWikilinkVisitor,
+        code_wikilink::CodeWikilinkVisitor, confusable_character::ConfusableCharacterVisitor,
+        duplicate_content::DuplicateContentVisitor, duplicate_property::DuplicatePropertyVisitor,
+        front_matter_key::FrontMatterKeyVisitor, hub_page::HubPageVisitor,
+        ignored_wikilink::IgnoredWikilinkVisitor, illegal_filename::IllegalFilenameVisitor,
+        underlinked_page::UnderlinkedPageVisitor, unknown_tag::UnknownTagVisitor,
+        unlinked_text::UnlinkedTextVisitor, wrong_link_style::WrongLinkStyleVisitor, ThirdPassRule,
+    },
+    visitor::Visitor,
+};
+
+/// Something a rule needs which is made before it runs, by the pass of [`Input::pass`]
+///
+/// Every rule is given the config and the list of files, which cost nothing to make
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum Input {
+    /// The ngrams of every filename, see [`crate::file::name::ngrams`]
+    FilenameNgrams,
+    /// The table of every alias and the file it resolves to
+    AliasTable,
+    /// The [`AliasIndex`], filled in by a visitor which runs before the other visitors of the
+    /// second pass
+    AliasIndex,
+}
+
+impl Input {
+    /// The pass which makes it
+    #[must_use]
+    pub fn pass(self) -> Pass {
+        match self {
+            Input::FilenameNgrams => Pass::SimilarFilenames,
+            Input::AliasTable => Pass::Aliases,
+            Input::AliasIndex => Pass::Links,
+        }
+    }
+}
+
+/// What the visitors of the second pass are made from, see [`ThirdPassRule::visitor`]
+pub struct Inputs<'a> {
+    pub config: &'a Config,
+    /// Every file which is linted
+    pub all_files: &'a [PathBuf],
+    /// Every file in [`Config::attachments_directory`]
+    pub attachments: &'a [PathBuf],
+    /// See [`Input::AliasTable`]
+    pub alias_table: &'a HashMap<Alias, PathBuf>,
+    /// See [`Input::AliasIndex`], empty unless a rule which is on needs it
+    pub alias_index: &'a Rc<RefCell<AliasIndex>>,
+    /// Shared by every visitor, see [`SourceBudget::keep`]
+    pub sources: &'a Rc<RefCell<SourceBudget>>,
+}
+
+impl ThirdPassRule {
+    /// What the rule needs before the second pass
+    #[must_use]
+    pub fn requires(self) -> &'static [Input] {
+        match self {
+            ThirdPassRule::UnlinkedText | ThirdPassRule::BrokenWikilink => {
+                &[Input::AliasTable, Input::AliasIndex]
+            }
+            ThirdPassRule::IgnoredWikilink
+            | ThirdPassRule::UnderlinkedPage
+            | ThirdPassRule::WrongLinkStyle
+            | ThirdPassRule::BrokenReference
+            | ThirdPassRule::ConfusableCharacter => &[Input::AliasTable],
+            ThirdPassRule::BrokenRelref
+            | ThirdPassRule::UnknownTag
+            | ThirdPassRule::DuplicateProperty
+            | ThirdPassRule::DuplicateContent
+            | ThirdPassRule::HubPage
+            | ThirdPassRule::AliasCasing
+            | ThirdPassRule::IllegalFilename
+            | ThirdPassRule::FrontMatterKey
+            | ThirdPassRule::CodeWikilink
+            | ThirdPassRule::RelativeWikilink
+            | ThirdPassRule::AmbiguousWikilink
+            | ThirdPassRule::ExpiredExclude => &[],
+        }
+    }
+
+    /// Whether the linter runs the rule
+    #[must_use]
+    pub fn is_on(self, linter: &Linter) -> bool {
+        match self {
+            ThirdPassRule::UnlinkedText => linter.unlinked_text,
+            ThirdPassRule::BrokenWikilink
+            | ThirdPassRule::RelativeWikilink
+            | ThirdPassRule::AmbiguousWikilink => linter.broken_wikilink,
+            ThirdPassRule::BrokenRelref => linter.broken_relref,
+            ThirdPassRule::UnknownTag => linter.unknown_tag,
+            ThirdPassRule::DuplicateProperty => linter.duplicate_property,
+            ThirdPassRule::DuplicateContent => linter.duplicate_content,
+            ThirdPassRule::IgnoredWikilink => linter.ignored_wikilink,
+            ThirdPassRule::HubPage => linter.hub_page,
+            ThirdPassRule::UnderlinkedPage => linter.underlinked_page,
+            ThirdPassRule::AliasCasing => linter.alias_casing,
+            ThirdPassRule::ConfusableCharacter => linter.confusable_character,
+            ThirdPassRule::IllegalFilename => linter.illegal_filename,
+            ThirdPassRule::FrontMatterKey => linter.front_matter_key,
+            ThirdPassRule::WrongLinkStyle => linter.wrong_link_style,
+            ThirdPassRule::CodeWikilink => linter.code_wikilink,
+            ThirdPassRule::BrokenReference => linter.broken_reference,
+            ThirdPassRule::ExpiredExclude => linter.expired_exclude,
+        }
+    }
+
+    /// The visitor which finds the rule's reports, [`None`] for the rules found by another rule's
+    /// visitor or from the config before the passes
+    #[must_use]
+    pub fn visitor(self, inputs: &Inputs) -> Option<Rc<RefCell<dyn Visitor>>> {
+        let Inputs {
+            config,
+            all_files,
+            attachments,
+            alias_table,
+            alias_index,
+            sources,
+        } = *inputs;
+        Some(match self {
+            ThirdPassRule::UnlinkedText => Rc::new(RefCell::new(UnlinkedTextVisitor::new(
+                alias_index.clone(),
+                config.skip_html,
+                config.unlinked_text_inflections,
+                config.group_unlinked_text,
+                config.link_once_per,
+                config.unlinked_text.clone(),
+                sources.clone(),
+            ))),
+            ThirdPassRule::BrokenWikilink => Rc::new(RefCell::new(BrokenWikilinkVisitor::new(
+                all_files,
+                attachments,
+                alias_index.clone(),
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::BrokenRelref => Rc::new(RefCell::new(BrokenRelrefVisitor::new(
+                all_files,
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::UnknownTag => Rc::new(RefCell::new(UnknownTagVisitor::new(
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::DuplicateProperty => {
+                Rc::new(RefCell::new(DuplicatePropertyVisitor::new(sources.clone())))
+            }
+            ThirdPassRule::DuplicateContent => {
+                Rc::new(RefCell::new(DuplicateContentVisitor::new(sources.clone())))
+            }
+            ThirdPassRule::IgnoredWikilink => Rc::new(RefCell::new(IgnoredWikilinkVisitor::new(
+                alias_table,
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::HubPage => {
+                Rc::new(RefCell::new(HubPageVisitor::new(config, sources.clone())))
+            }
+            ThirdPassRule::UnderlinkedPage => Rc::new(RefCell::new(UnderlinkedPageVisitor::new(
+                alias_table,
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::AliasCasing => Rc::new(RefCell::new(AliasCasingVisitor::new(
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::ConfusableCharacter => Rc::new(RefCell::new(
+                ConfusableCharacterVisitor::new(alias_table, config, sources.clone()),
+            )),
+            ThirdPassRule::IllegalFilename => {
+                Rc::new(RefCell::new(IllegalFilenameVisitor::new(config)))
+            }
+            ThirdPassRule::FrontMatterKey => Rc::new(RefCell::new(FrontMatterKeyVisitor::new(
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::WrongLinkStyle => Rc::new(RefCell::new(WrongLinkStyleVisitor::new(
+                alias_table,
+                config,
+                sources.clone(),
+            ))),
+            ThirdPassRule::CodeWikilink => {
+                Rc::new(RefCell::new(CodeWikilinkVisitor::new(sources.clone())))
+            }
+            ThirdPassRule::BrokenReference => Rc::new(RefCell::new(BrokenReferenceVisitor::new(
+                alias_table,
+                sources.clone(),
+            ))),
+            // Found by the BrokenWikilinkVisitor, and from the config before the passes
+            ThirdPassRule::RelativeWikilink
+            | ThirdPassRule::AmbiguousWikilink
+            | ThirdPassRule::ExpiredExclude => return None,
+        })
+    }
+
+    /// Whether [`ThirdPassRule::visitor`] makes a visitor for the rule
+    #[must_use]
+    pub fn has_visitor(self) -> bool {
+        !matches!(
+            self,
+            ThirdPassRule::RelativeWikilink
+                | ThirdPassRule::AmbiguousWikilink
+                | ThirdPassRule::ExpiredExclude
+        )
+    }
+}
+
+/// The rules a check runs, and the inputs made for them
+///
+/// A pass runs only if a rule which is on needs it, so turning rules off skips their work
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// The rules which are on and have a visitor in the second pass, in the order they run
+    pub rules: Vec<ThirdPassRule>,
+    /// Whether there are [`crate::rules::custom::CustomRule`]s, which run in the second pass
+    /// and are given the alias table
+    pub custom_rules: bool,
+    /// Every input needed by a rule which is on
+    pub inputs: BTreeSet<Input>,
+}
+
+impl Plan {
+    #[must_use]
+    pub fn new(linter: &Linter) -> Self {
+        let rules: Vec<ThirdPassRule> = ThirdPassRule::iter()
+            .filter(|rule| rule.has_visitor() && rule.is_on(linter))
+            .collect();
+        let custom_rules = !linter.rules().is_empty();
+        let mut inputs: BTreeSet<Input> = rules
+            .iter()
+            .flat_map(|rule| rule.requires())
+            .copied()
+            .collect();
+        if linter.similar_filename {
+            inputs.insert(Input::FilenameNgrams);
+        }
+        // The duplicate aliases are found while the table is made, and the second pass resolves
+        // links with it for the link index
+        if linter.duplicate_alias || custom_rules || !rules.is_empty() {
+            inputs.insert(Input::AliasTable);
+        }
+        Self {
+            rules,
+            custom_rules,
+            inputs,
+        }
+    }
+
+    /// Whether a rule which is on needs `input`
+    #[must_use]
+    pub fn needs(&self, input: Input) -> bool {
+        self.inputs.contains(&input)
+    }
+
+    /// Whether the pass runs, the passes which aren't part of a check always do
+    #[must_use]
+    pub fn runs(&self, pass: Pass) -> bool {
+        match pass {
+            Pass::SimilarFilenames => self.needs(Input::FilenameNgrams),
+            Pass::Aliases => self.needs(Input::AliasTable),
+            Pass::Links => self.custom_rules || !self.rules.is_empty(),
+            Pass::Check | Pass::Fixes | Pass::CheckAgain | Pass::NoFixes => true,
+        }
+    }
+}
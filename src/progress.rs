@@ -0,0 +1,196 @@
+//! Progress of a run, so the caller decides how (or whether) to show it
+use std::{path::Path, sync::Mutex};
+
+use tracing::{debug, info, info_span, trace, Span};
+
+#[cfg(feature = "terminal")]
+use console::{style, Emoji};
+#[cfg(feature = "terminal")]
+use indicatif::ProgressBar;
+
+use crate::rules::Report;
+
+#[cfg(feature = "terminal")]
+static CHECK: Emoji<'_, '_> = Emoji("🔍  ", "");
+#[cfg(feature = "terminal")]
+static SIMILAR: Emoji<'_, '_> = Emoji("🤝  ", "");
+#[cfg(feature = "terminal")]
+static FIRST_PASS: Emoji<'_, '_> = Emoji("📃  ", "");
+#[cfg(feature = "terminal")]
+static SECOND_PASS: Emoji<'_, '_> = Emoji("🔗  ", "");
+#[cfg(feature = "terminal")]
+static FIXES: Emoji<'_, '_> = Emoji("🔧  ", "");
+#[cfg(feature = "terminal")]
+static CHECK_AGAIN: Emoji<'_, '_> = Emoji("💡  ", "");
+#[cfg(feature = "terminal")]
+static NO_FIXES: Emoji<'_, '_> = Emoji("🎉  ", "");
+
+/// The steps of a run, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Linting before fixing, made of [`Pass::SimilarFilenames`], [`Pass::Aliases`] and [`Pass::Links`]
+    Check,
+    /// Comparing every pair of filename ngrams
+    SimilarFilenames,
+    /// Collecting aliases from every file
+    Aliases,
+    /// Running the content rules over every file
+    Links,
+    /// Fixing every report that can be fixed
+    Fixes,
+    /// Linting again, because fixes were made
+    CheckAgain,
+    /// Nothing was fixed, so there is nothing left to do
+    NoFixes,
+}
+
+/// Receives events as [`crate::lib`] runs
+///
+/// Every method does nothing by default
+pub trait ProgressSink: Send + Sync {
+    /// A pass started, `total` is how many steps it has if that is known
+    fn pass_started(&self, _pass: Pass, _total: Option<u64>) {}
+
+    /// A step of the current pass is done
+    fn progressed(&self) {}
+
+    /// A file was parsed, which is a step of the current pass
+    fn file_parsed(&self, _path: &Path) {
+        self.progressed();
+    }
+
+    /// A report was found
+    fn report_emitted(&self, _report: &Report) {}
+
+    /// The pass is over
+    fn pass_finished(&self, _pass: Pass) {}
+}
+
+/// Ignores every event
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {}
+
+/// Logs every event, with the file or report as fields, in a span for each pass, before passing
+/// it on
+pub(crate) struct Logged<'a> {
+    progress: &'a dyn ProgressSink,
+    /// The passes which started and did not finish yet, innermost last
+    /// Each is entered from when it starts to when it finishes, on the thread which runs it, so
+    /// everything logged in the meantime is in it
+    spans: Mutex<Vec<Span>>,
+}
+
+impl<'a> Logged<'a> {
+    pub(crate) fn new(progress: &'a dyn ProgressSink) -> Self {
+        Self {
+            progress,
+            spans: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl ProgressSink for Logged<'_> {
+    fn pass_started(&self, pass: Pass, total: Option<u64>) {
+        let span = info_span!("pass", pass = ?pass, total);
+        span.with_subscriber(|(id, dispatch)| dispatch.enter(id));
+        info!("Pass started");
+        self.spans.lock().expect("Never poisoned").push(span);
+        self.progress.pass_started(pass, total);
+    }
+
+    fn progressed(&self) {
+        self.progress.progressed();
+    }
+
+    fn file_parsed(&self, path: &Path) {
+        trace!(file = ?path, "File parsed");
+        self.progress.file_parsed(path);
+    }
+
+    fn report_emitted(&self, report: &Report) {
+        debug!(id = report.id().0.as_str(), file = ?report.path(), "Report emitted");
+        self.progress.report_emitted(report);
+    }
+
+    fn pass_finished(&self, pass: Pass) {
+        info!("Pass finished");
+        if let Some(span) = self.spans.lock().expect("Never poisoned").pop() {
+            span.with_subscriber(|(id, dispatch)| dispatch.exit(id));
+        }
+        self.progress.pass_finished(pass);
+    }
+}
+
+/// Leaves the passes which did not finish, like one which failed
+impl Drop for Logged<'_> {
+    fn drop(&mut self) {
+        let spans = self.spans.get_mut().expect("Never poisoned");
+        while let Some(span) = spans.pop() {
+            span.with_subscriber(|(id, dispatch)| dispatch.exit(id));
+        }
+    }
+}
+
+/// Prints a banner for every pass and draws a progress bar under it
+#[cfg(feature = "terminal")]
+#[derive(Debug, Default)]
+pub struct TerminalProgress {
+    bar: Mutex<Option<ProgressBar>>,
+    /// Leave out the emojis, like when colors are turned off
+    plain: bool,
+}
+
+#[cfg(feature = "terminal")]
+impl TerminalProgress {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// See [`TerminalProgress::plain`]
+    #[must_use]
+    pub fn plain(mut self, plain: bool) -> Self {
+        self.plain = plain;
+        self
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl ProgressSink for TerminalProgress {
+    fn pass_started(&self, pass: Pass, total: Option<u64>) {
+        let (step, emoji, message) = match pass {
+            Pass::Check => ("[1/3]", CHECK, "Generating Error Reports..."),
+            Pass::SimilarFilenames => (
+                "  [1/3]",
+                SIMILAR,
+                "Searching for Similar Filenames O(n^2)...",
+            ),
+            Pass::Aliases => ("  [2/3]", FIRST_PASS, "Getting Aliases O(n)..."),
+            Pass::Links => ("  [3/3]", SECOND_PASS, "Checking Links O(n)..."),
+            Pass::Fixes => ("[2/3]", FIXES, "Performing Fixes..."),
+            Pass::CheckAgain => (
+                "[3/3]",
+                CHECK_AGAIN,
+                "Generating Error Reports After Fixes Applied...",
+            ),
+            Pass::NoFixes => ("[3/3]", NO_FIXES, "No Fixes Found..."),
+        };
+        let emoji = if self.plain { emoji.1 } else { emoji.0 };
+        println!("{} {emoji}{message}", style(step).bold().dim());
+        *self.bar.lock().expect("Never poisoned") = total.map(ProgressBar::new);
+    }
+
+    fn progressed(&self) {
+        if let Some(bar) = &*self.bar.lock().expect("Never poisoned") {
+            bar.inc(1);
+        }
+    }
+
+    fn pass_finished(&self, _pass: Pass) {
+        if let Some(bar) = self.bar.lock().expect("Never poisoned").take() {
+            bar.finish_and_clear();
+        }
+    }
+}
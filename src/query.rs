@@ -0,0 +1,208 @@
+//! Where an alias is defined and used, see `mdlinker query`
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::{HashMap, HashSet};
+
+use crate::{
+    alias_index::AliasIndex,
+    config::UnlinkedTextOptions,
+    file::{
+        content::{
+            front_matter::FrontMatterVisitor,
+            line_column,
+            source_map::SourceMap,
+            wikilink::{Alias, WikilinkVisitor},
+        },
+        name::{get_filename, Filename},
+    },
+    rules::{unlinked_text::UnlinkedTextVisitor, ErrorCode},
+    sed::ReplacePair,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+
+/// How an alias is referenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReferenceKind {
+    /// `[[alias]]`
+    Wikilink,
+    /// `#alias` or `#[[alias]]`
+    Tag,
+    /// Text which could be wrapped in a wikilink, see [`crate::rules::unlinked_text`]
+    Unlinked,
+}
+
+impl Display for ReferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wikilink => write!(f, "wikilink"),
+            Self::Tag => write!(f, "tag"),
+            Self::Unlinked => write!(f, "unlinked"),
+        }
+    }
+}
+
+/// A place an alias is referenced
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Reference {
+    pub path: PathBuf,
+    /// Starting from 1
+    pub line: usize,
+    /// In characters, starting from 1
+    pub column: usize,
+    pub kind: ReferenceKind,
+}
+
+/// The answer to `mdlinker query <alias>`
+#[derive(Debug, Clone)]
+pub struct Query {
+    pub alias: Alias,
+    /// The file wikilinks to the alias resolve to
+    pub definition: Option<PathBuf>,
+    /// Other files which are named after or declare the alias, see
+    /// [`crate::rules::duplicate_alias::DuplicateAlias`]
+    pub duplicates: Vec<PathBuf>,
+    /// Every reference, sorted by file and position
+    pub references: Vec<Reference>,
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.alias)?;
+        match &self.definition {
+            Some(definition) => writeln!(f, "  defined in: {}", definition.display())?,
+            None => writeln!(f, "  not defined")?,
+        }
+        for duplicate in &self.duplicates {
+            writeln!(f, "  also defined in: {}", duplicate.display())?;
+        }
+        for reference in &self.references {
+            writeln!(
+                f,
+                "  {}: {}:{}:{}",
+                reference.kind,
+                reference.path.display(),
+                reference.line,
+                reference.column
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct QueryVisitor {
+    pub alias: Alias,
+    /// Every file named after or declaring the alias in its front matter
+    pub definitions: Vec<PathBuf>,
+    pub references: Vec<Reference>,
+    filename_to_alias: ReplacePair<Filename, Alias>,
+    front_matter_visitor: FrontMatterVisitor,
+    wikilinks_visitor: WikilinkVisitor,
+    /// Only has the queried alias in its alias table
+    unlinked_text_visitor: UnlinkedTextVisitor,
+}
+
+impl QueryVisitor {
+    #[must_use]
+    pub fn new(
+        alias: Alias,
+        filename_to_alias: &ReplacePair<Filename, Alias>,
+        skip_html: bool,
+        inflections: bool,
+    ) -> Self {
+        let alias_table = HashMap::from([(alias.clone(), PathBuf::new())]);
+        let index = AliasIndex::new(alias_table, &HashSet::new(), &[])
+            .expect("One alias is few enough to match");
+        Self {
+            alias,
+            definitions: Vec::new(),
+            references: Vec::new(),
+            filename_to_alias: filename_to_alias.clone(),
+            front_matter_visitor: FrontMatterVisitor::new(),
+            wikilinks_visitor: WikilinkVisitor::new(skip_html),
+            unlinked_text_visitor: UnlinkedTextVisitor::new(
+                Rc::new(RefCell::new(index)),
+                skip_html,
+                inflections,
+                // Every mention is a reference
+                false,
+                None,
+                UnlinkedTextOptions::default(),
+                Rc::default(),
+            ),
+        }
+    }
+
+    fn push_reference(&mut self, source: &str, path: &Path, offset: usize, kind: ReferenceKind) {
+        let (line, column) = line_column(source, offset);
+        self.references.push(Reference {
+            path: path.to_path_buf(),
+            line,
+            column,
+            kind,
+        });
+    }
+}
+
+impl Visitor for QueryVisitor {
+    fn name(&self) -> &'static str {
+        "QueryVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.front_matter_visitor.visit(node, source)?;
+        self.wikilinks_visitor.visit(node, source)?;
+        self.unlinked_text_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        if Alias::from_filename(&get_filename(path), &self.filename_to_alias) == self.alias
+            || self
+                .front_matter_visitor
+                .front_matter
+                .aliases
+                .contains(&self.alias)
+        {
+            self.definitions.push(path.to_path_buf());
+        }
+        let wikilinks = std::mem::take(&mut self.wikilinks_visitor.wikilinks);
+        for wikilink in wikilinks {
+            if wikilink.alias != self.alias {
+                continue;
+            }
+            let offset = wikilink.span.offset();
+            let kind = if wikilink.tag {
+                ReferenceKind::Tag
+            } else {
+                ReferenceKind::Wikilink
+            };
+            self.push_reference(source, path, offset, kind);
+        }
+        self.unlinked_text_visitor.finalize_file(source, path)?;
+        let unlinked_texts = std::mem::take(&mut self.unlinked_text_visitor.unlinked_texts);
+        for unlinked_text in unlinked_texts {
+            self.push_reference(
+                source,
+                path,
+                unlinked_text.span.offset(),
+                ReferenceKind::Unlinked,
+            );
+        }
+        self.front_matter_visitor.finalize_file(source, path)?;
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+    fn _finalize(&mut self, _excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        self.references.sort();
+        Ok(Finalized::default())
+    }
+}
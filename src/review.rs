@@ -0,0 +1,379 @@
+//! Triage reports one at a time in the terminal, see `mdlinker review`
+//!
+//! [`Review`] holds the reports and what is selected, so it can be driven without a terminal,
+//! and [`review`] draws it with ratatui and maps keys onto it
+// Like the linter, these return its errors, which are large
+#![allow(clippy::result_large_err)]
+use std::{
+    path::{Path, PathBuf},
+    process,
+};
+
+use miette::Diagnostic;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use thiserror::Error;
+
+use crate::{
+    config::{Config, SaveConfigError},
+    file::backup::Backup,
+    linter::Linter,
+    prepare_fixes,
+    rules::{FixError, Report},
+    OutputErrors,
+};
+
+/// The number of lines shown before the report in the preview
+const CONTEXT_LINES: usize = 5;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum ReviewError {
+    #[error(transparent)]
+    OutputErrors(#[from] OutputErrors),
+    #[error(transparent)]
+    FixError(#[from] FixError),
+    #[error(transparent)]
+    SaveConfigError(#[from] SaveConfigError),
+    #[error("The terminal failed")]
+    IoError(#[from] std::io::Error),
+    #[error("$EDITOR is not a valid command")]
+    EditorError(#[from] shell_words::ParseError),
+}
+
+/// A row of the list of reports, which are grouped by rule, then by file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Row {
+    Rule(String),
+    File(PathBuf),
+    /// The index of the report in [`Review::reports`]
+    Report(usize),
+}
+
+/// The reports left to review, and the one which is selected
+pub struct Review {
+    config: Config,
+    reports: Vec<Report>,
+    selected: usize,
+    /// See [`Review::status`]
+    status: String,
+    /// See [`crate::prepare_fixes`], this is only done before the first fix
+    fixes_prepared: bool,
+    backup: Option<Backup>,
+}
+
+/// The rule of a report, as written in its code
+fn rule(report: &Report) -> String {
+    report
+        .diagnostic()
+        .code()
+        .map(|code| code.to_string())
+        .unwrap_or_default()
+}
+
+impl Review {
+    /// Lint with `config`, without fixing anything
+    pub fn new(mut config: Config) -> Result<Self, ReviewError> {
+        config.fix = false;
+        let mut review = Self {
+            config,
+            reports: Vec::new(),
+            selected: 0,
+            status: String::new(),
+            fixes_prepared: false,
+            backup: None,
+        };
+        review.lint()?;
+        Ok(review)
+    }
+
+    /// Lint again, as fixes and edits move or remove reports
+    fn lint(&mut self) -> Result<(), ReviewError> {
+        let mut reports = Linter::from_config(self.config.clone()).run()?.reports;
+        reports.sort_by_cached_key(|report| (rule(report), report.path(), report.line_column()));
+        self.reports = reports;
+        self.selected = self.selected.min(self.reports.len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// The reports, in the order of [`Review::rows`]
+    #[must_use]
+    pub fn reports(&self) -> &[Report] {
+        &self.reports
+    }
+
+    #[must_use]
+    pub fn selected(&self) -> Option<&Report> {
+        self.reports.get(self.selected)
+    }
+
+    /// What the last action did, shown under the reports
+    #[must_use]
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.reports.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Every report, with a row for each rule and each file before its reports
+    #[must_use]
+    pub fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut last: Option<(String, PathBuf)> = None;
+        for (i, report) in self.reports.iter().enumerate() {
+            let (rule, path) = (rule(report), report.path());
+            if last.as_ref().map(|(last_rule, _)| last_rule) != Some(&rule) {
+                rows.push(Row::Rule(rule.clone()));
+                rows.push(Row::File(path.clone()));
+            } else if last.as_ref().map(|(_, last_path)| last_path) != Some(&path) {
+                rows.push(Row::File(path.clone()));
+            }
+            rows.push(Row::Report(i));
+            last = Some((rule, path));
+        }
+        rows
+    }
+
+    /// Fix the selected report, then lint again
+    /// Like `--fix`, this refuses to fix in a dirty git repo, or backs files up with `--no-vcs`
+    pub fn fix(&mut self) -> Result<(), ReviewError> {
+        let Some(report) = self.selected().cloned() else {
+            return Ok(());
+        };
+        if !self.fixes_prepared {
+            self.backup = prepare_fixes(&self.config)?;
+            self.fixes_prepared = true;
+        }
+        if let (Some(backup), Some(path)) = (&mut self.backup, report.inner().edits()) {
+            backup.save(&path)?;
+        }
+        self.status = match report.inner().fix(&self.config)? {
+            Some(()) => format!("Fixed {}", report.id().0),
+            None => format!("There is no fix for {}", report.id().0),
+        };
+        self.lint()
+    }
+
+    /// Exclude the selected report in the config file, and stop showing it
+    /// If the config can't be saved, nothing changes
+    pub fn ignore(&mut self) -> Result<(), ReviewError> {
+        let Some(report) = self.reports.get(self.selected) else {
+            return Ok(());
+        };
+        let mut config = self.config.clone();
+        config.add_report_to_ignore(report.inner());
+        config.exclude.push(report.id());
+        config.save_config()?;
+        self.config = config;
+        let report = self.reports.remove(self.selected);
+        self.status = format!("Excluded {}", report.id().0);
+        self.selected = self.selected.min(self.reports.len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// The lines around the selected report, with its span highlighted, then its message and help
+    fn preview(&self) -> Text<'static> {
+        let Some(report) = self.selected() else {
+            return Text::from("No reports left");
+        };
+        let diagnostic = report.diagnostic();
+        let mut lines = vec![
+            Line::from(diagnostic.to_string()).bold(),
+            Line::from(format!("{}", report.path().display())).dim(),
+            Line::default(),
+        ];
+        if let (Ok(source), Some(span)) = (std::fs::read_to_string(report.path()), report.span()) {
+            lines.extend(highlighted(&source, span.offset(), span.len()));
+            lines.push(Line::default());
+        }
+        if let Some(help) = diagnostic.help() {
+            lines.extend(
+                help.to_string()
+                    .lines()
+                    .map(|line| Line::from(line.to_owned())),
+            );
+        }
+        Text::from(lines)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(frame.area());
+        let [list, preview] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let rows = self.rows();
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|row| match row {
+                Row::Rule(rule) => ListItem::new(rule.clone()).bold(),
+                Row::File(path) => ListItem::new(format!(" {}", path.display())).dim(),
+                Row::Report(i) => {
+                    let report = &self.reports[*i];
+                    let (line, column) = report.line_column().unwrap_or((1, 1));
+                    ListItem::new(format!("   {line}:{column} {}", report.id().0))
+                }
+            })
+            .collect();
+        let mut state = ListState::default().with_selected(
+            rows.iter()
+                .position(|row| matches!(row, Row::Report(i) if *i == self.selected)),
+        );
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(format!(" Reports ({}) ", self.reports.len())))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut state,
+        );
+        frame.render_widget(
+            Paragraph::new(self.preview())
+                .block(Block::bordered().title(" Preview "))
+                .wrap(Wrap { trim: false }),
+            preview,
+        );
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(" j/k move  f fix  i ignore  e edit  q quit  ").reversed(),
+                Span::from(format!(" {}", self.status)),
+            ]),
+            status,
+        );
+    }
+}
+
+/// The line of `source` with the bytes from `offset` to `offset + len` in it, and
+/// [`CONTEXT_LINES`] before it, with those bytes highlighted
+fn highlighted(source: &str, offset: usize, len: usize) -> Vec<Line<'static>> {
+    let offset = offset.min(source.len());
+    let end = (offset + len).min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+    let before: Vec<&str> = source[..line_start].lines().collect();
+    let mut lines: Vec<Line> = before[before.len().saturating_sub(CONTEXT_LINES)..]
+        .iter()
+        .map(|line| Line::from((*line).to_owned()).dim())
+        .collect();
+    let (Some(start_text), Some(text), Some(end_text)) = (
+        source.get(line_start..offset),
+        source.get(offset..end),
+        source.get(end..line_end),
+    ) else {
+        return lines;
+    };
+    lines.push(Line::from(vec![
+        Span::from(start_text.to_owned()),
+        Span::from(text.to_owned()).red().bold().underlined(),
+        Span::from(end_text.to_owned()),
+    ]));
+    lines
+}
+
+/// The program and arguments of `$EDITOR`, which is split into words like a shell does, so it
+/// can be a command like `code --wait`. `vi` if it is not set
+fn editor_command(editor: Option<&str>) -> Result<Vec<String>, ReviewError> {
+    let words = shell_words::split(editor.unwrap_or_default())?;
+    Ok(if words.is_empty() {
+        vec!["vi".to_owned()]
+    } else {
+        words
+    })
+}
+
+/// Open `path` at `line` in `$EDITOR`, see [`editor_command`]
+fn edit(path: &Path, line: usize) -> Result<(), ReviewError> {
+    let editor = std::env::var("EDITOR").ok();
+    let command = editor_command(editor.as_deref())?;
+    process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg(format!("+{line}"))
+        .arg(path)
+        .status()?;
+    Ok(())
+}
+
+/// Handle keys until `q` or `Esc`
+fn run(terminal: &mut DefaultTerminal, review: &mut Review) -> Result<(), ReviewError> {
+    loop {
+        terminal.draw(|frame| review.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('j') | KeyCode::Down => review.next(),
+            KeyCode::Char('k') | KeyCode::Up => review.previous(),
+            KeyCode::Char('f') => {
+                // A fix which fails is shown, so the others can still be made
+                if let Err(e) = review.fix() {
+                    review.status = e.to_string();
+                }
+            }
+            KeyCode::Char('i') => {
+                // Like fixes, a config which can't be saved is shown rather than quitting
+                if let Err(e) = review.ignore() {
+                    review.status = e.to_string();
+                }
+            }
+            KeyCode::Char('e') => {
+                let Some(report) = review.selected() else {
+                    continue;
+                };
+                let (path, (line, _)) = (report.path(), report.line_column().unwrap_or((1, 1)));
+                ratatui::restore();
+                let edited = edit(&path, line);
+                *terminal = ratatui::init();
+                edited?;
+                review.lint()?;
+                review.status = format!("Edited {}", path.display());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Lint with `config`, then review the reports in the terminal until `q` is pressed
+pub fn review(config: Config) -> Result<(), ReviewError> {
+    let mut review = Review::new(config)?;
+    let mut terminal = ratatui::init();
+    let out = run(&mut terminal, &mut review);
+    ratatui::restore();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::editor_command;
+
+    #[test]
+    fn test_editor_command() {
+        assert_eq!(editor_command(None).unwrap(), vec!["vi"]);
+        assert_eq!(editor_command(Some("  ")).unwrap(), vec!["vi"]);
+        assert_eq!(
+            editor_command(Some("code --wait")).unwrap(),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            editor_command(Some("'/opt/my editor/bin/edit' -n")).unwrap(),
+            vec!["/opt/my editor/bin/edit", "-n"]
+        );
+        assert!(editor_command(Some("'unclosed")).is_err());
+    }
+}
@@ -7,82 +7,705 @@
 //! * A [`Report`] is the result of a rule, like "These two filenames are similar".
 //!   Some [`Report`]s are [`Fixable`], meaning they can be auto-handled with the cli argument
 //!   `--fix`
-//!   Reports all implement [`crate::rules::HasId`].
+//!   Reports all implement [`crate::rules::ReportTrait`].
 
-use std::backtrace::Backtrace;
+use std::{collections::BTreeSet, fmt::Display, ops::Range, path::PathBuf};
 
-use crate::config::file::Config as FileConfig;
+use crate::{
+    config::file::Config as FileConfig,
+    file::{
+        case::{eq_folded, fold},
+        content::{line_column, wikilink::Alias},
+        name::FilenameLowercase,
+    },
+};
 use derive_more::derive::{Constructor, From, Into};
 use glob::Pattern;
-use miette::Diagnostic;
+use hashbrown::{HashMap, HashSet};
+use miette::{Diagnostic, LabeledSpan, NamedSource, Severity, SourceCode, SourceSpan};
+use serde::{Deserialize, Serialize};
 use strum_macros::{EnumDiscriminants, EnumIter};
 use thiserror::Error;
+use tracing::{debug, warn};
 
 use crate::config::Config;
 
-#[derive(Debug, EnumDiscriminants, Clone)]
+#[derive(Debug, EnumDiscriminants, Clone, Serialize, Deserialize)]
 #[strum_discriminants(derive(EnumIter))]
 #[strum_discriminants(name(Rule))]
 pub enum Report {
     SimilarFilename(similar_filename::SimilarFilename),
     DuplicateAlias(duplicate_alias::DuplicateAlias),
     ThirdPass(ThirdPassReport),
+    /// From a rule outside this crate, see [`custom::CustomRule`]
+    Custom(custom::CustomReport),
+}
+
+impl Report {
+    /// The report inside, whatever its rule
+    #[must_use]
+    pub fn inner(&self) -> &dyn ReportTrait {
+        match self {
+            Report::SimilarFilename(report) => report,
+            Report::DuplicateAlias(report) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnlinkedText(report)) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenRelref(report)) => report,
+            Report::ThirdPass(ThirdPassReport::RelativeWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::AmbiguousWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnknownTag(report)) => report,
+            Report::ThirdPass(ThirdPassReport::DuplicateProperty(report)) => report,
+            Report::ThirdPass(ThirdPassReport::DuplicateContent(report)) => report,
+            Report::ThirdPass(ThirdPassReport::IgnoredWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::HubPage(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnderlinkedPage(report)) => report,
+            Report::ThirdPass(ThirdPassReport::AliasCasing(report)) => report,
+            Report::ThirdPass(ThirdPassReport::ConfusableCharacter(report)) => report,
+            Report::ThirdPass(ThirdPassReport::IllegalFilename(report)) => report,
+            Report::ThirdPass(ThirdPassReport::FrontMatterKey(report)) => report,
+            Report::ThirdPass(ThirdPassReport::WrongLinkStyle(report)) => report,
+            Report::ThirdPass(ThirdPassReport::CodeWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenReference(report)) => report,
+            Report::ThirdPass(ThirdPassReport::ExpiredExclude(report)) => report,
+            Report::Custom(report) => report,
+        }
+    }
+
+    /// The report as a [`Diagnostic`], for rendering it
+    #[must_use]
+    pub fn diagnostic(&self) -> &dyn Diagnostic {
+        match self {
+            Report::SimilarFilename(report) => report,
+            Report::DuplicateAlias(report) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnlinkedText(report)) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenRelref(report)) => report,
+            Report::ThirdPass(ThirdPassReport::RelativeWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::AmbiguousWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnknownTag(report)) => report,
+            Report::ThirdPass(ThirdPassReport::DuplicateProperty(report)) => report,
+            Report::ThirdPass(ThirdPassReport::DuplicateContent(report)) => report,
+            Report::ThirdPass(ThirdPassReport::IgnoredWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::HubPage(report)) => report,
+            Report::ThirdPass(ThirdPassReport::UnderlinkedPage(report)) => report,
+            Report::ThirdPass(ThirdPassReport::AliasCasing(report)) => report,
+            Report::ThirdPass(ThirdPassReport::ConfusableCharacter(report)) => report,
+            Report::ThirdPass(ThirdPassReport::IllegalFilename(report)) => report,
+            Report::ThirdPass(ThirdPassReport::FrontMatterKey(report)) => report,
+            Report::ThirdPass(ThirdPassReport::WrongLinkStyle(report)) => report,
+            Report::ThirdPass(ThirdPassReport::CodeWikilink(report)) => report,
+            Report::ThirdPass(ThirdPassReport::BrokenReference(report)) => report,
+            Report::ThirdPass(ThirdPassReport::ExpiredExclude(report)) => report,
+            Report::Custom(report) => report,
+        }
+    }
+
+    /// See [`ReportTrait::id`]
+    #[must_use]
+    pub fn id(&self) -> ErrorCode {
+        self.inner().id()
+    }
+
+    /// See [`ReportTrait::fingerprint`]
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.inner().fingerprint()
+    }
+
+    /// See [`ReportTrait::path`]
+    #[must_use]
+    pub fn path(&self) -> PathBuf {
+        self.inner().path()
+    }
+
+    /// See [`ReportTrait::span`]
+    #[must_use]
+    pub fn span(&self) -> Option<SourceSpan> {
+        self.inner().span()
+    }
+
+    /// See [`ReportTrait::line_column`]
+    #[must_use]
+    pub fn line_column(&self) -> Option<(usize, usize)> {
+        self.inner().line_column()
+    }
+
+    /// How serious the report is, reports without a severity are errors
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        self.diagnostic().severity().unwrap_or(Severity::Error)
+    }
+
+    /// Drop the content of the file from the report, so it is small to serialize
+    /// Spans still point into the file, and [`Report::line_column`] is kept
+    #[must_use]
+    pub fn without_source(mut self) -> Self {
+        self.elide_source();
+        self
+    }
+
+    /// The bytes of file content the report keeps, none once [`Report::without_source`] dropped
+    /// it, nor for the reports whose source is not the content of a file
+    #[must_use]
+    pub fn source_len(&self) -> usize {
+        self.keeps_source().map_or(0, KeepsSource::source_len)
+    }
+
+    /// The source kept by the report, [`None`] for the reports whose source is not the content
+    /// of a file
+    fn keeps_source(&self) -> Option<&dyn KeepsSource> {
+        match self {
+            // The source of similar and illegal filenames is just file paths,
+            // and the source of custom reports belongs to their diagnostic, which is not ours to change
+            Report::SimilarFilename(_)
+            | Report::ThirdPass(ThirdPassReport::IllegalFilename(_))
+            | Report::Custom(_) => None,
+            Report::ThirdPass(ThirdPassReport::ConfusableCharacter(report)) => Some(report),
+            Report::DuplicateAlias(report) => Some(report),
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::UnlinkedText(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::BrokenRelref(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::RelativeWikilink(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::AmbiguousWikilink(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::UnknownTag(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::DuplicateProperty(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::DuplicateContent(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::IgnoredWikilink(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::HubPage(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::UnderlinkedPage(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::AliasCasing(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::FrontMatterKey(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::WrongLinkStyle(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::CodeWikilink(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::BrokenReference(report)) => Some(report),
+            Report::ThirdPass(ThirdPassReport::ExpiredExclude(report)) => Some(report),
+        }
+    }
+}
+
+impl KeepsSource for Report {
+    fn source_len(&self) -> usize {
+        Report::source_len(self)
+    }
+    fn elide_source(&mut self) {
+        match self {
+            Report::SimilarFilename(_)
+            | Report::ThirdPass(ThirdPassReport::IllegalFilename(_))
+            | Report::Custom(_) => {}
+            Report::ThirdPass(ThirdPassReport::ConfusableCharacter(report)) => {
+                report.elide_source();
+            }
+            Report::DuplicateAlias(report) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::UnlinkedText(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::BrokenRelref(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::RelativeWikilink(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::AmbiguousWikilink(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::UnknownTag(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::DuplicateProperty(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::DuplicateContent(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::IgnoredWikilink(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::HubPage(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::UnderlinkedPage(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::AliasCasing(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::FrontMatterKey(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::WrongLinkStyle(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::CodeWikilink(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::BrokenReference(report)) => report.elide_source(),
+            Report::ThirdPass(ThirdPassReport::ExpiredExclude(report)) => report.elide_source(),
+        }
+    }
+}
+
+/// Rendered as the report inside, see [`Report::diagnostic`]
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self.diagnostic(), f)
+    }
 }
 
-#[derive(Debug, EnumDiscriminants, Clone)]
+impl std::error::Error for Report {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.diagnostic().source()
+    }
+}
+
+impl Diagnostic for Report {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().code()
+    }
+    fn severity(&self) -> Option<Severity> {
+        self.diagnostic().severity()
+    }
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().help()
+    }
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.diagnostic().url()
+    }
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.diagnostic().source_code()
+    }
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.diagnostic().labels()
+    }
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.diagnostic().related()
+    }
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.diagnostic().diagnostic_source()
+    }
+}
+
+#[derive(Debug, EnumDiscriminants, Clone, Serialize, Deserialize)]
 #[strum_discriminants(derive(EnumIter))]
 #[strum_discriminants(name(ThirdPassRule))]
 pub enum ThirdPassReport {
     BrokenWikilink(crate::rules::broken_wikilink::BrokenWikilink),
     UnlinkedText(crate::rules::unlinked_text::UnlinkedText),
+    BrokenRelref(crate::rules::broken_relref::BrokenRelref),
+    /// Found by the [`crate::rules::broken_wikilink::BrokenWikilinkVisitor`]
+    RelativeWikilink(crate::rules::relative_wikilink::RelativeWikilink),
+    /// Found by the [`crate::rules::broken_wikilink::BrokenWikilinkVisitor`]
+    AmbiguousWikilink(crate::rules::ambiguous_wikilink::AmbiguousWikilink),
+    UnknownTag(crate::rules::unknown_tag::UnknownTag),
+    DuplicateProperty(crate::rules::duplicate_property::DuplicateProperty),
+    DuplicateContent(crate::rules::duplicate_content::DuplicateContent),
+    IgnoredWikilink(crate::rules::ignored_wikilink::IgnoredWikilink),
+    HubPage(crate::rules::hub_page::HubPage),
+    UnderlinkedPage(crate::rules::underlinked_page::UnderlinkedPage),
+    AliasCasing(crate::rules::alias_casing::AliasCasing),
+    ConfusableCharacter(crate::rules::confusable_character::ConfusableCharacter),
+    IllegalFilename(crate::rules::illegal_filename::IllegalFilename),
+    FrontMatterKey(crate::rules::front_matter_key::FrontMatterKey),
+    WrongLinkStyle(crate::rules::wrong_link_style::WrongLinkStyle),
+    CodeWikilink(crate::rules::code_wikilink::CodeWikilink),
+    BrokenReference(crate::rules::broken_reference::BrokenReference),
+    /// Found from the config, not by a visitor
+    ExpiredExclude(crate::rules::expired_exclude::ExpiredExclude),
 }
 
 /// A Reports error code, usually like `asdf::asdf::asdf`
 /// Uniquely identifies a violation of a rule, and can be deduped by Eq
-#[derive(Debug, Constructor, PartialEq, Eq, PartialOrd, Ord, Clone, From, Into)]
+///
+/// The reports of every rule have ids like `rule::file::detail`, see [`ErrorCode::in_file`], so an
+/// exclude like `content::wikilink::broken::lorem::*` excludes a rule in a single file
+#[derive(
+    Debug, Constructor, PartialEq, Eq, PartialOrd, Ord, Clone, From, Into, Serialize, Deserialize,
+)]
+#[serde(transparent)]
 pub struct ErrorCode(pub String);
 
+impl ErrorCode {
+    /// The id of a report of the rule `code` in the file named `filename`, followed by the
+    /// `detail` of the report, like the alias of a broken wikilink, all separated by `::`
+    #[must_use]
+    pub fn in_file(code: &str, filename: &FilenameLowercase, detail: &[&dyn Display]) -> Self {
+        let parts = [code.to_owned(), filename.to_string()]
+            .into_iter()
+            .chain(detail.iter().map(ToString::to_string));
+        Self(parts.collect::<Vec<_>>().join("::"))
+    }
+
+    /// Whether the id matches `pattern`, an entry of [`Config::exclude`], see [`Exclude`]
+    /// An invalid pattern matches nothing
+    #[must_use]
+    pub fn matches(&self, pattern: &ErrorCode) -> bool {
+        Exclude::new(pattern).is_ok_and(|exclude| exclude.matches(self))
+    }
+}
+
+/// A compiled entry of [`Config::exclude`], which matches ids ignoring case
+///
+/// * `prefix:content::wikilink::broken` matches ids which start with it
+/// * `regex:^content::wikilink::broken::(lorem|ipsum)::` matches ids the regex finds a match in
+/// * `glob:content::wikilink::broken::*`, or the same without `glob:`, matches whole ids
+///
+/// Any entry also matches the report whose [`ReportTrait::fingerprint`] it equals
+#[derive(Debug, Clone)]
+pub struct Exclude {
+    raw: ErrorCode,
+    matcher: ExcludeMatcher,
+}
+
+#[derive(Debug, Clone)]
+enum ExcludeMatcher {
+    Prefix(String),
+    Glob(Pattern),
+    Regex(regex::Regex),
+}
+
+/// An entry of [`Config::exclude`] which doesn't compile, see [`Exclude::new`]
+#[derive(Error, Debug, Diagnostic)]
+pub enum ExcludeError {
+    #[error("The exclude {exclude:?} is not a valid glob")]
+    #[diagnostic(help(
+        "Escape [ and ] like [[] and []], or write it as prefix:<id> to exclude ids which start with it"
+    ))]
+    Glob {
+        exclude: String,
+        source: glob::PatternError,
+    },
+    #[error("The exclude {exclude:?} is not a valid regex")]
+    Regex {
+        exclude: String,
+        source: regex::Error,
+    },
+}
+
+impl Exclude {
+    /// Compiles `exclude`, which is a glob unless it starts with `prefix:`, `glob:` or `regex:`
+    ///
+    /// # Errors
+    ///
+    /// If it is not a valid glob or regex
+    pub fn new(exclude: &ErrorCode) -> Result<Self, ExcludeError> {
+        let raw = &exclude.0;
+        let matcher = if let Some(prefix) = raw.strip_prefix("prefix:") {
+            ExcludeMatcher::Prefix(fold(prefix))
+        } else if let Some(regex) = raw.strip_prefix("regex:") {
+            ExcludeMatcher::Regex(
+                regex::RegexBuilder::new(regex)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|source| ExcludeError::Regex {
+                        exclude: raw.clone(),
+                        source,
+                    })?,
+            )
+        } else {
+            let glob = raw.strip_prefix("glob:").unwrap_or(raw);
+            ExcludeMatcher::Glob(Pattern::new(&fold(glob)).map_err(|source| {
+                ExcludeError::Glob {
+                    exclude: raw.clone(),
+                    source,
+                }
+            })?)
+        };
+        Ok(Self {
+            raw: exclude.clone(),
+            matcher,
+        })
+    }
+
+    /// The entry as written in the config
+    #[must_use]
+    pub fn raw(&self) -> &ErrorCode {
+        &self.raw
+    }
+
+    /// Compiles every entry of `excludes`, leaving out those which don't compile, which
+    /// loading the config already refused
+    #[must_use]
+    pub fn all(excludes: &[ErrorCode]) -> Vec<Self> {
+        excludes
+            .iter()
+            .filter_map(|exclude| Self::new(exclude).ok())
+            .collect()
+    }
+
+    /// Whether the exclude matches the id
+    #[must_use]
+    pub fn matches(&self, id: &ErrorCode) -> bool {
+        let id = fold(&id.0);
+        match &self.matcher {
+            ExcludeMatcher::Prefix(prefix) => id.starts_with(prefix),
+            ExcludeMatcher::Glob(pattern) => pattern.matches(&id),
+            ExcludeMatcher::Regex(regex) => regex.is_match(&id),
+        }
+    }
+}
+
+/// Identifies a report by its rule and the text around it, rather than by file names like
+/// [`ErrorCode`], so it survives renaming files and moving text around, see [`ReportTrait::fingerprint`]
+///
+/// It is a 64 bit FNV-1a hash in hex, which is the same on every platform and version
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Fingerprint(pub String);
+
+impl Fingerprint {
+    /// Hash the `rule` code along with the `context` it was found in
+    #[must_use]
+    pub fn new(rule: &str, context: &[&str]) -> Self {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        let mut hash = OFFSET_BASIS;
+        for part in std::iter::once(rule).chain(context.iter().copied()) {
+            // Separate the parts, so moving text between them changes the hash
+            for byte in part.bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(PRIME);
+            }
+        }
+        Self(format!("{hash:016x}"))
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The line of `source` at `offset`, lowercase and with its whitespace collapsed,
+/// for [`Fingerprint::new`]
+#[must_use]
+pub fn line_context(source: &str, offset: usize) -> String {
+    let offset = offset.min(source.len());
+    let start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i);
+    fold(
+        &source[start..end]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+/// The items whose id starts with `code`, like the exclude `prefix:code` would match them
 #[must_use]
 pub fn filter_code<T: ReportTrait>(errors: Vec<T>, code: &ErrorCode) -> Vec<T> {
+    let pattern = ErrorCode(format!("prefix:{}", code.0));
     errors
         .into_iter()
-        .filter(|item| item.id().0.starts_with(&code.0))
+        .filter(|item| item.id().matches(&pattern))
         .collect()
 }
 
-/// Implemented for all vectors of items that implement [`HasId`]
+/// Implemented for all vectors of items that implement [`ReportTrait`]
 pub trait VecHasIdExtensions<T>
 where
     T: ReportTrait + PartialOrd,
 {
+    /// Filters with [`filter_by_excludes`] and dedupes, returning the excludes which matched
     #[must_use]
-    fn finalize(self, excludes: &[ErrorCode]) -> Self;
+    fn finalize(self, excludes: &[ErrorCode]) -> (Self, MatchedExcludes)
+    where
+        Self: Sized;
 }
 
-fn filter_by_excludes<T: ReportTrait>(mut this: Vec<T>, excludes: &[ErrorCode]) -> Vec<T> {
-    this.retain(|item| {
-        !excludes.iter().any(|exclude| {
-            Pattern::new(&exclude.0.to_lowercase())
-                .map(|pattern| pattern.matches(&item.id().0.to_lowercase()))
-                .unwrap_or(false)
-        })
+/// Drops the items which one of `excludes` matches, see [`ReportTrait::excluded_by`]
+/// Returns the excludes which matched, see [`MatchedExcludes`]
+#[must_use]
+pub fn filter_by_excludes<T: ReportTrait>(
+    mut this: Vec<T>,
+    excludes: &[ErrorCode],
+) -> (Vec<T>, MatchedExcludes) {
+    let excludes = Exclude::all(excludes);
+    let mut matched = MatchedExcludes::default();
+    this.retain(|item| match item.excluded_by(&excludes) {
+        Some(exclude) => {
+            matched.exclude.insert(exclude.raw.clone());
+            false
+        }
+        None => true,
     });
-    this
+    (this, matched)
+}
+
+/// The entries of [`Config::exclude`] and [`Config::ignore_word_pairs`] which matched a report
+/// during a check, see [`crate::linter::Linter::unused_excludes`]
+/// Returned alongside the reports by [`filter_by_excludes`] and [`crate::visitor::Visitor::finalize`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchedExcludes {
+    pub exclude: BTreeSet<ErrorCode>,
+    /// As written in the config
+    pub ignore_word_pairs: BTreeSet<(String, String)>,
+}
+
+impl MatchedExcludes {
+    /// Adds what `other` matched
+    pub fn extend(&mut self, other: MatchedExcludes) {
+        self.exclude.extend(other.exclude);
+        self.ignore_word_pairs.extend(other.ignore_word_pairs);
+    }
 }
 
 fn dedupe_by_code<T: ReportTrait + PartialOrd>(mut this: Vec<T>) -> Vec<T> {
     // Make sure things with
     // a higher "value" are first before deduping
     this.sort_by(|b, a| a.partial_cmp(b).expect("This never fails"));
-    this.dedup_by(|a, b| a.id().0.to_lowercase() == b.id().0.to_lowercase());
+    this.dedup_by(|a, b| eq_folded(&a.id().0, &b.id().0));
     this
 }
 
+/// A fix which was not made, as it changes the same text as the fix of an earlier report,
+/// see [`without_conflicts`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FixConflict {
+    /// The file both fixes edit
+    pub path: PathBuf,
+    /// The report which was not fixed
+    pub skipped: ErrorCode,
+    /// The earlier report which was fixed
+    pub fixed: ErrorCode,
+}
+
+impl Display for FixConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Skipped the fix of {} due to a conflict with the fix of {} in {}",
+            self.skipped.0,
+            self.fixed.0,
+            self.path.display()
+        )
+    }
+}
+
+/// Replace the bytes in `range` of the file at `path` with `replacement`, an insertion if the
+/// range is empty, see [`ReportTrait::quick_fix`]
+/// The ranges of the edits of a report are in the file before any of them is made, and don't overlap
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub path: PathBuf,
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    /// Replace the text under `span` of the file at `path`
+    #[must_use]
+    pub fn new(path: PathBuf, span: SourceSpan, replacement: impl Into<String>) -> Self {
+        Self {
+            path,
+            range: span.offset()..span.offset() + span.len(),
+            replacement: replacement.into(),
+        }
+    }
+}
+
+/// How a fix was found not to be idempotent, see [`NonIdempotentFix`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum NonIdempotence {
+    /// The report was found again after it was fixed
+    Reappeared,
+    /// Making the fix a second time changed the file again
+    ChangedAgain(PathBuf),
+}
+
+/// A fix which does not settle after being made once, found with
+/// [`crate::config::Config::verify`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NonIdempotentFix {
+    /// The report whose fix is not idempotent
+    pub code: ErrorCode,
+    pub reason: NonIdempotence,
+}
+
+impl Display for NonIdempotentFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            NonIdempotence::Reappeared => write!(
+                f,
+                "The fix of {} is not idempotent, the report was found again after fixing it",
+                self.code.0
+            ),
+            NonIdempotence::ChangedAgain(path) => write!(
+                f,
+                "The fix of {} is not idempotent, fixing it again changed {}",
+                self.code.0,
+                path.display()
+            ),
+        }
+    }
+}
+
+/// The reports to fix, without those whose [`ReportTrait::fix_spans`] overlap those of an earlier
+/// report of another rule in the same file, and the conflicts which left them out
+/// Reports of the same rule are kept, as their fixes account for each other, like
+/// [`wrong_link_style::WrongLinkStyle`] rewriting every such link in the file
+#[must_use]
+pub fn without_conflicts(reports: &[Report]) -> (Vec<&Report>, Vec<FixConflict>) {
+    let mut fixed: HashMap<PathBuf, Vec<(Range<usize>, &Report)>> = HashMap::new();
+    let mut to_fix = vec![];
+    let mut conflicts = vec![];
+    for report in reports {
+        let Some(path) = report.inner().edits() else {
+            to_fix.push(report);
+            continue;
+        };
+        let ranges: Vec<Range<usize>> = report
+            .inner()
+            .fix_spans()
+            .iter()
+            .map(|span| span.offset()..span.offset() + span.len())
+            .collect();
+        let rule = report.diagnostic().code().map(|code| code.to_string());
+        let fixed_in_file = fixed.entry(path.clone()).or_default();
+        let conflict = fixed_in_file.iter().find(|(range, other)| {
+            other.diagnostic().code().map(|code| code.to_string()) != rule
+                && ranges
+                    .iter()
+                    .any(|this| this.start < range.end && range.start < this.end)
+        });
+        if let Some((_, other)) = conflict {
+            conflicts.push(FixConflict {
+                path,
+                skipped: report.inner().id(),
+                fixed: other.inner().id(),
+            });
+            continue;
+        }
+        fixed_in_file.extend(ranges.into_iter().map(|range| (range, report)));
+        to_fix.push(report);
+    }
+    (to_fix, conflicts)
+}
+
+/// Drops reports which another rule already reports about the same alias in the same file,
+/// so one problem isn't counted more than once
+///
+/// * An [`unlinked_text::UnlinkedText`] of an alias with a [`broken_wikilink::BrokenWikilink`] in
+///   the same file is dropped, as creating the page fixes both
+/// * A [`broken_wikilink::BrokenWikilink`] of a tag which is an [`unknown_tag::UnknownTag`] is
+///   dropped, as the tag is likely a typo of a known tag, which the unknown tag suggests
+///
+/// Run after excludes, so excluding the report which is kept brings the other back
+pub(crate) fn correlate(mut reports: Vec<Report>) -> Vec<Report> {
+    let mut broken: HashSet<(PathBuf, Alias)> = HashSet::new();
+    let mut unknown_tags: HashSet<(PathBuf, usize)> = HashSet::new();
+    for report in &reports {
+        match report {
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => {
+                broken.insert((report.path(), report.alias().clone()));
+            }
+            Report::ThirdPass(ThirdPassReport::UnknownTag(report)) => {
+                unknown_tags.insert((report.path(), report.span.offset()));
+            }
+            _ => {}
+        }
+    }
+    reports.retain(|report| {
+        let related = match report {
+            Report::ThirdPass(ThirdPassReport::UnlinkedText(report)) => {
+                broken.contains(&(report.path(), report.alias().clone()))
+            }
+            Report::ThirdPass(ThirdPassReport::BrokenWikilink(report)) => {
+                unknown_tags.contains(&(report.path(), report.wikilink.offset()))
+            }
+            _ => false,
+        };
+        if related {
+            debug!(
+                "Dropping {} as another rule reports it in the same file",
+                report.id().0
+            );
+        }
+        !related
+    });
+    reports
+}
+
 /// Used for filtering out items that start with the exclude code
 impl<T: ReportTrait + PartialOrd> VecHasIdExtensions<T> for Vec<T> {
     #[must_use]
-    fn finalize(self, excludes: &[ErrorCode]) -> Self {
-        dedupe_by_code(filter_by_excludes(self, excludes))
+    fn finalize(self, excludes: &[ErrorCode]) -> (Self, MatchedExcludes) {
+        let (this, matched) = filter_by_excludes(self, excludes);
+        (dedupe_by_code(this), matched)
     }
 }
 
@@ -92,20 +715,21 @@ pub enum FixError {
     #[error("The git repo is dirty")]
     #[help("Please commit or stash your changes")]
     DirtyRepo {
-        #[backtrace]
-        backtrace: Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
+    #[cfg(feature = "git")]
     #[error("There was an error checking the git status: {source}")]
     GitError {
         source: git2::Error,
-        #[backtrace]
-        backtrace: Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
     #[error("There was an IOError on file {file}: {source}")]
     IOError {
         source: std::io::Error,
-        #[backtrace]
-        backtrace: Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
         file: String,
     },
 }
@@ -120,14 +744,167 @@ pub trait ReportTrait {
     /// Returns [`None`] if it did not even try to fix things
     fn fix(&self, config: &Config) -> Result<Option<()>, FixError>;
 
+    /// The existing file [`ReportTrait::fix`] edits, so it can be backed up first
+    /// Returns [`None`] if fixing only creates files, or there is no fix
+    fn edits(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// The bytes of [`ReportTrait::edits`] the fix changes, to find fixes which conflict, see
+    /// [`without_conflicts`]
+    /// Defaults to [`ReportTrait::span`]
+    fn fix_spans(&self) -> Vec<SourceSpan> {
+        self.span().into_iter().collect()
+    }
+
+    /// The edits [`ReportTrait::fix`] makes to [`ReportTrait::edits`], so editors and bots can make
+    /// them without mdlinker, see [`TextEdit`]
+    /// Empty if the fix only creates, renames or deletes files, or there is no fix
+    fn quick_fix(&self, _config: &Config) -> Vec<TextEdit> {
+        vec![]
+    }
+
     /// Adds the id to the config file as an ignore
     /// This has a default implementation
     fn ignore(&self, config: &mut FileConfig) {
-        config.exclude.push(self.id().0);
+        config.exclude.push(self.id().0.into());
     }
+
+    /// The id this report had before ids were all like [`ErrorCode::in_file`], so excludes
+    /// written with it still exclude the report
+    fn legacy_id(&self) -> Option<ErrorCode> {
+        None
+    }
+
+    /// Identifies the report by its rule and the text around it, for baselines which should
+    /// survive renaming files, an exclude which equals it also excludes the report
+    fn fingerprint(&self) -> Fingerprint;
+
+    /// Whether one of `excludes` equals the [`ReportTrait::fingerprint`], or matches the
+    /// [`ReportTrait::id`] (or [`ReportTrait::legacy_id`]), see [`Exclude`]
+    fn is_excluded(&self, excludes: &[Exclude]) -> bool {
+        self.excluded_by(excludes).is_some()
+    }
+
+    /// The first of `excludes` which excludes the report, see [`ReportTrait::is_excluded`]
+    fn excluded_by<'a>(&self, excludes: &'a [Exclude]) -> Option<&'a Exclude> {
+        excludes.iter().find(|exclude| {
+            if exclude.raw.0 == self.fingerprint().0 || exclude.matches(&self.id()) {
+                return true;
+            }
+            let Some(legacy_id) = self.legacy_id() else {
+                return false;
+            };
+            if !exclude.matches(&legacy_id) {
+                return false;
+            }
+            warn!(
+                "The exclude {:?} matches the old id {:?}, replace it with the new id {:?}",
+                exclude.raw.0,
+                legacy_id.0,
+                self.id().0
+            );
+            true
+        })
+    }
+
+    /// The file the report is about
+    fn path(&self) -> PathBuf;
+
+    /// Every file the report is about, like both files of a
+    /// [`crate::rules::similar_filename::SimilarFilename`]
+    fn paths(&self) -> Vec<PathBuf> {
+        vec![self.path()]
+    }
+
+    /// The bytes the report points at in the content of [`ReportTrait::path`]
+    /// Returns [`None`] if the report is about filenames, not content
+    fn span(&self) -> Option<SourceSpan>;
+
+    /// The line and column (in characters) of [`ReportTrait::span`], both starting from 1
+    fn line_column(&self) -> Option<(usize, usize)>;
+}
+
+/// A report which keeps a copy of the content of its file to show its snippet, see
+/// [`crate::budget::SourceBudget`]
+pub trait KeepsSource {
+    /// The bytes of file content the report keeps, none once [`KeepsSource::elide_source`]
+    /// dropped it
+    fn source_len(&self) -> usize;
+
+    /// Drop the content of the file, keeping the name of the file and the
+    /// [`ReportTrait::line_column`] of the report, which needs the content
+    fn elide_source(&mut self);
+}
+
+/// Serializes a [`NamedSource`] as its name and content
+/// The content is left out if it was elided, see [`Report::without_source`]
+mod named_source {
+    use miette::NamedSource;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct NamedSourceRef<'a> {
+        name: &'a str,
+        #[serde(skip_serializing_if = "str::is_empty")]
+        content: &'a str,
+    }
+
+    #[derive(Deserialize)]
+    struct NamedSourceOwned {
+        name: String,
+        #[serde(default)]
+        content: String,
+    }
+
+    pub fn serialize<S: Serializer>(
+        src: &NamedSource<String>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        NamedSourceRef {
+            name: src.name(),
+            content: src.inner(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<NamedSource<String>, D::Error> {
+        let src = NamedSourceOwned::deserialize(deserializer)?;
+        Ok(NamedSource::new(src.name, src.content))
+    }
+}
+
+/// Replace the content of a [`NamedSource`] with nothing, keeping its name
+fn elided(src: &NamedSource<String>) -> NamedSource<String> {
+    NamedSource::new(src.name(), String::new())
+}
+
+/// The line and column of a span in the content of a file
+fn span_line_column(src: &NamedSource<String>, span: SourceSpan) -> (usize, usize) {
+    line_column(src.inner(), span.offset())
 }
 
+pub mod alias_casing;
+pub mod ambiguous_wikilink;
+pub mod broken_reference;
+pub mod broken_relref;
 pub mod broken_wikilink;
+pub mod code_wikilink;
+pub mod confusable_character;
+pub mod custom;
 pub mod duplicate_alias;
+pub mod duplicate_content;
+pub mod duplicate_property;
+pub mod expired_exclude;
+pub mod front_matter_key;
+pub mod hub_page;
+pub mod ignored_wikilink;
+pub mod illegal_filename;
+pub mod relative_wikilink;
 pub mod similar_filename;
+pub mod underlinked_page;
+pub mod unknown_tag;
 pub mod unlinked_text;
+pub mod wrong_link_style;
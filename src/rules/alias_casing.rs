@@ -0,0 +1,380 @@
+//! Aliases written in a different case than most of the wikilinks and front matter aliases for
+//! them, like one `[[Github]]` among many `[[GitHub]]`
+//!
+//! Aliases are compared lowercase everywhere else, so this counts how each is written. Tags are
+//! not counted, as they are often all lowercase on purpose
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    cmp::Reverse,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        case::eq_folded,
+        content::{
+            front_matter::FrontMatterVisitor,
+            notebook::is_notebook,
+            source_map::SourceMap,
+            wikilink::{Alias, WikilinkVisitor},
+        },
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use hashbrown::HashMap;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, TextEdit, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::alias::casing";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("An alias is written in a different case than usual")]
+#[diagnostic(code("content::alias::casing"), severity(Warning))]
+pub struct AliasCasing {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The alias, as written here
+    written: String,
+
+    /// The way the alias is written most often
+    usual: String,
+
+    /// How many times the alias is written each way, most often first
+    counts: Vec<(String, usize)>,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Written '{usual}' elsewhere")]
+    pub span: SourceSpan,
+
+    /// The other places the alias is written the same way in the file
+    #[label(collection, "Also here")]
+    #[builder(default)]
+    #[serde(default)]
+    other_spans: Vec<SourceSpan>,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for AliasCasing {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Rewrites the alias the usual way, everywhere it is written this way in the file
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        let file = self.src.name().to_owned();
+        // Changing the case of some characters changes their length, which would move the spans
+        if self.edits().is_none() || self.written.len() != self.usual.len() {
+            return Ok(None);
+        }
+        let mut source = std::fs::read_to_string(&file).map_err(|src| FixError::IOError {
+            source: src,
+            file: file.clone(),
+            backtrace: Backtrace::force_capture(),
+        })?;
+        for span in std::iter::once(self.span).chain(self.other_spans.iter().copied()) {
+            let range = span.offset()..span.offset() + span.len();
+            if source.get(range.clone()) == Some(self.written.as_str()) {
+                source.replace_range(range, &self.usual);
+            }
+        }
+        std::fs::write(&file, source).map_err(|source| FixError::IOError {
+            source,
+            file,
+            backtrace: Backtrace::force_capture(),
+        })?;
+        Ok(Some(()))
+    }
+    fn edits(&self) -> Option<PathBuf> {
+        let path = PathBuf::from(self.src.name());
+        (!is_notebook(&path)).then_some(path)
+    }
+    fn fix_spans(&self) -> Vec<SourceSpan> {
+        std::iter::once(self.span)
+            .chain(self.other_spans.iter().copied())
+            .collect()
+    }
+    fn quick_fix(&self, _config: &Config) -> Vec<TextEdit> {
+        let Some(path) = self.edits() else {
+            return vec![];
+        };
+        if self.written.len() != self.usual.len() {
+            return vec![];
+        }
+        self.fix_spans()
+            .into_iter()
+            .map(|span| TextEdit::new(path.clone(), span, &self.usual))
+            .collect()
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl AliasCasing {
+    /// See [`AliasCasing::written`]
+    #[must_use]
+    pub fn written(&self) -> &str {
+        &self.written
+    }
+
+    /// See [`AliasCasing::usual`]
+    #[must_use]
+    pub fn usual(&self) -> &str {
+        &self.usual
+    }
+
+    /// See [`AliasCasing::counts`]
+    #[must_use]
+    pub fn counts(&self) -> &[(String, usize)] {
+        &self.counts
+    }
+}
+
+impl KeepsSource for AliasCasing {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for AliasCasing {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for AliasCasing {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// Where an alias is written, and how
+#[derive(Debug)]
+struct Written {
+    text: String,
+    src: NamedSource<String>,
+    span: SourceSpan,
+}
+
+/// The offset of the first whole word match of `alias` in `text`, ignoring case
+/// [`None`] if it is not written as is, like when it is escaped in the front matter
+fn find_written(text: &str, alias: &Alias) -> Option<usize> {
+    let alias = alias.to_string();
+    text.char_indices()
+        .map(|(i, _)| i)
+        .filter(|&i| {
+            text.get(i..i + alias.len())
+                .is_some_and(|found| eq_folded(found, &alias))
+        })
+        .find(|&i| {
+            let before = text[..i].chars().next_back();
+            let after = text[i + alias.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+}
+
+#[derive(Debug)]
+pub struct AliasCasingVisitor {
+    pub alias_casings: Vec<AliasCasing>,
+    wikilinks_visitor: WikilinkVisitor,
+    front_matter_visitor: FrontMatterVisitor,
+    /// The bytes of the front matter in the current file
+    front_matter: Option<SourceSpan>,
+    /// Every place each alias is written, the case preserving counterpart of the alias table
+    written: HashMap<Alias, Vec<Written>>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl AliasCasingVisitor {
+    #[must_use]
+    pub fn new(config: &Config, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            alias_casings: Vec::new(),
+            wikilinks_visitor: WikilinkVisitor::new(config.skip_html),
+            front_matter_visitor: FrontMatterVisitor::new(),
+            front_matter: None,
+            written: HashMap::new(),
+        }
+    }
+}
+
+impl Visitor for AliasCasingVisitor {
+    fn name(&self) -> &'static str {
+        "AliasCasingVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        self.front_matter_visitor.visit(node, source)?;
+        let data_ref = node.data.borrow();
+        if let NodeValue::FrontMatter(text) = &data_ref.value {
+            let sourcepos = data_ref.sourcepos;
+            self.front_matter = Some(SourceSpan::new(source.start(sourcepos).into(), text.len()));
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let src = NamedSource::new(path.to_string_lossy(), source.to_string());
+        for wikilink in std::mem::take(&mut self.wikilinks_visitor.wikilinks) {
+            if wikilink.tag {
+                continue;
+            }
+            // Inside the brackets
+            let start = wikilink.span.offset() + 2;
+            let Some(text) = source
+                .get(start..(wikilink.span.offset() + wikilink.span.len()).saturating_sub(2))
+                .filter(|text| eq_folded(text, &wikilink.alias.to_string()))
+            else {
+                continue;
+            };
+            self.written
+                .entry(wikilink.alias)
+                .or_default()
+                .push(Written {
+                    text: text.to_owned(),
+                    src: src.clone(),
+                    span: SourceSpan::new(start.into(), text.len()),
+                });
+        }
+        if let Some(front_matter) = self.front_matter.take() {
+            let start = front_matter.offset();
+            let text = source
+                .get(start..start + front_matter.len())
+                .unwrap_or_default();
+            for alias in std::mem::take(&mut self.front_matter_visitor.front_matter.aliases) {
+                let Some(offset) = find_written(text, &alias) else {
+                    continue;
+                };
+                let len = alias.to_string().len();
+                self.written.entry(alias).or_default().push(Written {
+                    text: text[offset..offset + len].to_owned(),
+                    src: src.clone(),
+                    span: SourceSpan::new((start + offset).into(), len),
+                });
+            }
+        }
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        self.front_matter_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        for (_, written) in std::mem::take(&mut self.written) {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for w in &written {
+                *counts.entry(w.text.as_str()).or_default() += 1;
+            }
+            let mut counts: Vec<(String, usize)> = counts
+                .into_iter()
+                .map(|(text, count)| (text.to_owned(), count))
+                .collect();
+            counts.sort_by_key(|(_, count)| Reverse(*count));
+            // There is no usual way when the two most common are as common
+            let [(usual, most), (_, second), ..] = counts.as_slice() else {
+                continue;
+            };
+            if most == second {
+                continue;
+            }
+            // Grouped by file, then by how it is written
+            let mut minority: BTreeMap<(String, String), Vec<&Written>> = BTreeMap::new();
+            for w in written.iter().filter(|w| &w.text != usual) {
+                minority
+                    .entry((w.src.name().to_owned(), w.text.clone()))
+                    .or_default()
+                    .push(w);
+            }
+            let listed = counts
+                .iter()
+                .map(|(text, count)| format!("  '{text}': {count}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            for ((name, text), mut spans) in minority {
+                spans.sort_by_key(|w| w.span.offset());
+                let first = spans[0];
+                let filename = get_filename(Path::new(&name)).lowercase();
+                let id = ErrorCode::in_file(CODE, &filename, &[&text]).0;
+                let context = line_context(first.src.inner(), first.span.offset());
+                self.alias_casings.push(
+                    self.sources.borrow_mut().keep(
+                        AliasCasing::builder()
+                            .advice(format!(
+                                "Write it '{usual}', like everywhere else:\n{listed}\nid: {id:?}"
+                            ))
+                            .id(id.into())
+                            .fingerprint(Fingerprint::new(CODE, &[&text, &context]))
+                            .src(first.src.clone())
+                            .span(first.span)
+                            .other_spans(spans[1..].iter().map(|w| w.span).collect())
+                            .written(text)
+                            .usual(usual.clone())
+                            .counts(counts.clone())
+                            .build(),
+                    ),
+                );
+            }
+        }
+        // We can "take" this because we are putting it right back
+        let (alias_casings, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.alias_casings), excludes);
+        self.alias_casings = dedupe_by_code(alias_casings);
+        Ok(Finalized {
+            reports: self
+                .alias_casings
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::AliasCasing(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
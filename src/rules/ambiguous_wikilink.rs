@@ -0,0 +1,108 @@
+//! Path-style wikilinks, like `[[projects/lorem]]`, which match more than one file
+//!
+//! With [`crate::config::LinkResolution::ShortestPath`] two files like `work/projects/lorem.md`
+//! and `home/projects/lorem.md` both end in `projects/lorem`, and paths which only differ in
+//! case, like `Notes/Foo.md` and `notes/foo.md`, match the same link.
+//! These are found by [`crate::rules::broken_wikilink::BrokenWikilinkVisitor`]
+use std::path::PathBuf;
+
+use crate::{config::Config, file::content::wikilink::Alias};
+use bon::Builder;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{elided, span_line_column, ErrorCode, Fingerprint, FixError, KeepsSource, ReportTrait};
+
+pub const CODE: &str = "content::wikilink::ambiguous";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A wikilink matches more than one file")]
+#[diagnostic(code("content::wikilink::ambiguous"))]
+pub struct AmbiguousWikilink {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    alias: Alias,
+
+    /// The files the wikilink matches
+    candidates: Vec<PathBuf>,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Ambiguous wikilink")]
+    pub wikilink: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for AmbiguousWikilink {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// There is no telling which file was meant, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.wikilink)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.wikilink)))
+    }
+}
+
+impl AmbiguousWikilink {
+    /// The alias in the wikilink, as written
+    #[must_use]
+    pub fn alias(&self) -> &Alias {
+        &self.alias
+    }
+
+    /// The files the wikilink matches, sorted
+    #[must_use]
+    pub fn candidates(&self) -> &[PathBuf] {
+        &self.candidates
+    }
+}
+
+impl KeepsSource for AmbiguousWikilink {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for AmbiguousWikilink {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for AmbiguousWikilink {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
@@ -0,0 +1,318 @@
+//! Logseq block references, like `((64f1c2a0-...))` or `{{embed ((64f1c2a0-...))}}`, to a block
+//! id no block has, and pages in queries, like `{{query (page "lorem")}}`, which don't exist
+//!
+//! Logseq's macros are plain text to the markdown parser, so they are found in the source, like
+//! [`crate::rules::broken_relref::BrokenRelref`] finds shortcodes. Wikilinks in macros, like
+//! `{{embed [[lorem]]}}`, are parsed as wikilinks, and reported by
+//! [`crate::rules::broken_wikilink::BrokenWikilink`]
+use std::{
+    cell::RefCell,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        case::fold,
+        content::{source_map::SourceMap, wikilink::Alias},
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use hashbrown::{HashMap, HashSet};
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::reference::broken";
+
+/// What a [`BrokenReference`] refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// A block id, written `((id))`, which blocks have as an `id::` property
+    Block,
+    /// A page name in a query, like `(page "lorem")`
+    Page,
+}
+
+impl std::fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Block => write!(f, "block"),
+            Self::Page => write!(f, "page"),
+        }
+    }
+}
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A {kind} reference does not have a corresponding {kind}")]
+#[diagnostic(code("content::reference::broken"))]
+pub struct BrokenReference {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    kind: ReferenceKind,
+
+    /// The block id or page name, as written
+    target: String,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Not found")]
+    pub span: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for BrokenReference {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// There is no telling which block or page was meant, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl BrokenReference {
+    /// See [`ReferenceKind`]
+    #[must_use]
+    pub fn kind(&self) -> ReferenceKind {
+        self.kind
+    }
+
+    /// The block id or page name, as written
+    #[must_use]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+impl KeepsSource for BrokenReference {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for BrokenReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for BrokenReference {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// A reference, which is only known to be broken once every block id has been found
+#[derive(Debug)]
+struct Reference {
+    kind: ReferenceKind,
+    target: String,
+    src: NamedSource<String>,
+    span: SourceSpan,
+    context: String,
+}
+
+#[derive(Debug)]
+pub struct BrokenReferenceVisitor {
+    pub broken_references: Vec<BrokenReference>,
+    /// Matches the id of `((id))` block references, which are uuids
+    block_ref_pattern: Regex,
+    /// Matches the id of `id:: id` block properties
+    block_id_pattern: Regex,
+    /// Matches the arguments of `{{query ...}}` macros
+    query_pattern: Regex,
+    /// Matches the page of `(page "lorem")` and `(page lorem)` filters in a query
+    page_pattern: Regex,
+    /// The pages which can be referred to
+    aliases: HashSet<Alias>,
+    /// The block ids in every file parsed so far, lowercase
+    block_ids: HashSet<String>,
+    references: Vec<Reference>,
+    /// Byte ranges of code in the current file, references in them are not checked
+    code: Vec<Range<usize>>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl BrokenReferenceVisitor {
+    #[must_use]
+    pub fn new(alias_table: &HashMap<Alias, PathBuf>, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            broken_references: Vec::new(),
+            block_ref_pattern: Regex::new(
+                r"\(\(([0-9a-fA-F]{8}(?:-[0-9a-fA-F]{4}){3}-[0-9a-fA-F]{12})\)\)",
+            )
+            .expect("Constant"),
+            block_id_pattern: Regex::new(r"(?m)^[ \t]*(?:- )?id::[ \t]*([0-9a-fA-F-]+)[ \t]*\r?$")
+                .expect("Constant"),
+            query_pattern: Regex::new(r"\{\{query\s+(.*?)\}\}").expect("Constant"),
+            page_pattern: Regex::new(r#"\(page\s+(?:"([^"]*)"|([^\s()"]+))\s*\)"#)
+                .expect("Constant"),
+            aliases: alias_table.keys().cloned().collect(),
+            block_ids: HashSet::new(),
+            references: Vec::new(),
+            code: Vec::new(),
+        }
+    }
+}
+
+impl Visitor for BrokenReferenceVisitor {
+    fn name(&self) -> &'static str {
+        "BrokenReferenceVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let sourcepos = data_ref.sourcepos;
+        if let NodeValue::Code(_) | NodeValue::CodeBlock(_) = data_ref.value {
+            let start = source.start(sourcepos);
+            let end = source.end(sourcepos);
+            self.code.push(start..end);
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let code = std::mem::take(&mut self.code);
+        let in_code = |offset: usize| code.iter().any(|code| code.contains(&offset));
+        self.block_ids.extend(
+            self.block_id_pattern
+                .captures_iter(source)
+                .filter_map(|captures| captures.get(1))
+                .map(|id| id.as_str().to_lowercase()),
+        );
+        let mut targets: Vec<(ReferenceKind, usize, &str)> = self
+            .block_ref_pattern
+            .captures_iter(source)
+            .filter_map(|captures| captures.get(1))
+            .map(|target| (ReferenceKind::Block, target.start(), target.as_str()))
+            .collect();
+        for query in self
+            .query_pattern
+            .captures_iter(source)
+            .filter_map(|captures| captures.get(1))
+        {
+            targets.extend(
+                self.page_pattern
+                    .captures_iter(query.as_str())
+                    .filter_map(|captures| captures.get(1).or(captures.get(2)))
+                    .map(|page| {
+                        (
+                            ReferenceKind::Page,
+                            query.start() + page.start(),
+                            page.as_str(),
+                        )
+                    }),
+            );
+        }
+        let src = NamedSource::new(path.to_string_lossy(), source.to_string());
+        let references: Vec<Reference> = targets
+            .into_iter()
+            .filter(|(_, offset, _)| !in_code(*offset))
+            .map(|(kind, offset, target)| Reference {
+                kind,
+                target: target.to_owned(),
+                src: src.clone(),
+                span: SourceSpan::new(offset.into(), target.len()),
+                context: line_context(source, offset),
+            })
+            .collect();
+        self.references.extend(references);
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        for reference in std::mem::take(&mut self.references) {
+            let found = match reference.kind {
+                ReferenceKind::Block => self.block_ids.contains(&reference.target.to_lowercase()),
+                ReferenceKind::Page => self.aliases.contains(&Alias::new(&reference.target)),
+            };
+            if found {
+                continue;
+            }
+            let target = fold(&reference.target);
+            let filename = get_filename(Path::new(reference.src.name())).lowercase();
+            let id = ErrorCode::in_file(CODE, &filename, &[&reference.kind, &target]).0;
+            let advice = match reference.kind {
+                ReferenceKind::Block => "No block has this id:: property, it may have been deleted or be in another graph",
+                ReferenceKind::Page => "Create the page, or fix its name in the query",
+            };
+            self.broken_references.push(
+                self.sources.borrow_mut().keep(
+                    BrokenReference::builder()
+                        .advice(format!("{advice}.\nid: {id:?}"))
+                        .id(id.into())
+                        .fingerprint(Fingerprint::new(CODE, &[&target, &reference.context]))
+                        .kind(reference.kind)
+                        .src(reference.src)
+                        .span(reference.span)
+                        .target(reference.target)
+                        .build(),
+                ),
+            );
+        }
+        // We can "take" this because we are putting it right back
+        let (broken_references, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.broken_references), excludes);
+        self.broken_references = dedupe_by_code(broken_references);
+        Ok(Finalized {
+            reports: self
+                .broken_references
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::BrokenReference(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -0,0 +1,277 @@
+//! Links between pages of a static site, like Hugo's `{{< relref "post.md" >}}` shortcodes
+//! and Zola's `[post](@/blog/post.md)` internal links
+use std::{
+    cell::RefCell,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        case::fold,
+        content::source_map::SourceMap,
+        name::{get_filename, join_link, path_key},
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use hashbrown::HashSet;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::relref::broken";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A static site link does not have a corresponding page")]
+#[diagnostic(code("content::relref::broken"))]
+pub struct BrokenRelref {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The path as written in the link, without any `#anchor`
+    target: String,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Link target")]
+    pub span: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for BrokenRelref {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// There is no telling which page was meant, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl BrokenRelref {
+    /// The path as written in the link, without any `#anchor`
+    #[must_use]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+impl KeepsSource for BrokenRelref {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for BrokenRelref {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for BrokenRelref {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// Where a link target is resolved from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    /// Hugo's `ref` and `relref`, relative to the page first, then the content root
+    Hugo,
+    /// Zola's `@/`, always relative to the content root
+    Zola,
+}
+
+#[derive(Debug)]
+pub struct BrokenRelrefVisitor {
+    pub broken_relrefs: Vec<BrokenRelref>,
+    /// Matches `{{< relref "path" >}}` and `{{% ref "path" %}}` shortcodes
+    shortcode_pattern: Regex,
+    /// Matches the destination of `[text](@/path.md)` links
+    zola_pattern: Regex,
+    /// The canonical content root, the pages directory
+    content_directory: Option<PathBuf>,
+    /// The pages which can be linked to, see [`path_key`]
+    paths: HashSet<String>,
+    /// Byte ranges of code in the current file, links in them are not checked
+    code: Vec<Range<usize>>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl BrokenRelrefVisitor {
+    #[must_use]
+    pub fn new(all_files: &[PathBuf], config: &Config, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        let content_directory = config.pages_directory.canonicalize().ok();
+        let paths = match &content_directory {
+            Some(root) => all_files
+                .iter()
+                .filter_map(|file| path_key(file, root))
+                .collect(),
+            None => HashSet::new(),
+        };
+        Self {
+            sources,
+            broken_relrefs: Vec::new(),
+            shortcode_pattern: Regex::new(
+                r#"\{\{[<%]\s*(?:rel)?ref\s+(?:"([^"]*)"|([^\s"%>]+))\s*[>%]\}\}"#,
+            )
+            .expect("Constant"),
+            zola_pattern: Regex::new(r"\]\(@/([^)\s]*)\)").expect("Constant"),
+            content_directory,
+            paths,
+            code: Vec::new(),
+        }
+    }
+
+    /// Check if the page, or the section or bundle index of that name exists
+    fn exists(&self, key: &str) -> bool {
+        self.paths.contains(key)
+            || self.paths.contains(&format!("{key}/index"))
+            || self.paths.contains(&format!("{key}/_index"))
+    }
+
+    /// Check if a target, without its anchor, resolves to a page when linked from `path`
+    fn resolves(&self, target: &str, origin: Origin, path: &Path) -> bool {
+        let Some(root) = &self.content_directory else {
+            return true;
+        };
+        let target = fold(target);
+        if target.is_empty() {
+            // A link to an anchor on the same page
+            return true;
+        }
+        let from_root = join_link(vec![], &target).is_some_and(|key| self.exists(&key));
+        if origin == Origin::Zola || target.starts_with('/') {
+            return from_root;
+        }
+        let from_page = path_key(path, root).is_some_and(|key| {
+            let mut base: Vec<String> = key.split('/').map(ToOwned::to_owned).collect();
+            base.pop();
+            join_link(base, &target).is_some_and(|key| self.exists(&key))
+        });
+        from_page || from_root
+    }
+}
+
+impl Visitor for BrokenRelrefVisitor {
+    fn name(&self) -> &'static str {
+        "BrokenRelrefVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let sourcepos = data_ref.sourcepos;
+        if let NodeValue::Code(_) | NodeValue::CodeBlock(_) = data_ref.value {
+            let start = source.start(sourcepos);
+            let end = source.end(sourcepos);
+            self.code.push(start..end);
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path).lowercase();
+        let shortcodes = self
+            .shortcode_pattern
+            .captures_iter(source)
+            .filter_map(|captures| captures.get(1).or(captures.get(2)))
+            .map(|target| (target, Origin::Hugo));
+        let zola_links = self
+            .zola_pattern
+            .captures_iter(source)
+            .filter_map(|captures| captures.get(1))
+            .map(|target| (target, Origin::Zola));
+        let mut broken = Vec::new();
+        for (target, origin) in shortcodes.chain(zola_links) {
+            if self.code.iter().any(|code| code.contains(&target.start())) {
+                continue;
+            }
+            let link = target.as_str();
+            let page = link.split('#').next().unwrap_or_default();
+            if self.resolves(page, origin, path) {
+                continue;
+            }
+            let id = ErrorCode::in_file(CODE, &filename, &[&fold(page)]).0;
+            let report = BrokenRelref::builder()
+                .advice(format!(
+                    "Create the page '{page}', or fix the path. Paths starting with / are relative to the pages directory.\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(
+                    CODE,
+                    &[&fold(page), &line_context(source, target.start())],
+                ))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .span(SourceSpan::new(target.start().into(), link.len()))
+                .target(page.to_owned())
+                .build();
+            broken.push(self.sources.borrow_mut().keep(report));
+        }
+        self.broken_relrefs.extend(broken);
+        self.code.clear();
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (broken_relrefs, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.broken_relrefs), excludes);
+        self.broken_relrefs = dedupe_by_code(broken_relrefs);
+        Ok(Finalized {
+            reports: self
+                .broken_relrefs
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::BrokenRelref(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
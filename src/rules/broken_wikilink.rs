@@ -1,43 +1,69 @@
 use std::{
     backtrace::Backtrace,
     cell::RefCell,
+    cmp::Reverse,
     path::{Path, PathBuf},
+    rc::Rc,
 };
 
 use crate::{
-    config::Config,
+    alias_index::{AliasIndex, OccurrenceKind},
+    budget::SourceBudget,
+    config::{Config, LinkResolution},
     file::{
-        content::wikilink::{Alias, WikilinkVisitor},
-        name::{get_filename, Filename, FilenameLowercase},
+        content::{source_map::SourceMap, wikilink::Alias},
+        name::{get_filename, join_link, path_key, FilenameLowercase},
     },
-    sed::ReplacePair,
-    visitor::{FinalizeError, VisitError, Visitor},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
 };
 use bon::Builder;
 use comrak::{arena_tree::Node, nodes::Ast};
-use hashbrown::HashMap;
-use log::trace;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use glob::Pattern;
+use hashbrown::{HashMap, HashSet};
+use itertools::Itertools;
 use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tracing::trace;
 
 use super::{
-    dedupe_by_code, filter_by_excludes, ErrorCode, FixError, Report, ReportTrait, ThirdPassReport,
+    ambiguous_wikilink::{self, AmbiguousWikilink},
+    dedupe_by_code, elided, filter_by_excludes, line_context,
+    relative_wikilink::{self, looks_relative, RelativeWikilink},
+    span_line_column, ErrorCode, Fingerprint, FixError, KeepsSource, Report, ReportTrait,
+    ThirdPassReport,
 };
 
 pub const CODE: &str = "content::wikilink::broken";
 
-#[derive(Error, Debug, Diagnostic, Builder, Clone)]
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
 #[error("A wikilink does not have a corresponding page")]
 #[diagnostic(code("content::wikilink::broken"))]
 pub struct BrokenWikilink {
     /// Used to identify the diagnostic and exclude it if needed
     id: ErrorCode,
 
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
     alias: Alias,
 
+    /// The closest existing aliases, to tell a typo from a missing page
+    #[builder(default)]
+    #[serde(default)]
+    suggestions: Vec<Alias>,
+
     #[source_code]
+    #[serde(with = "super::named_source")]
     src: NamedSource<String>,
 
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
     #[label("Wikilink")]
     pub wikilink: SourceSpan,
 
@@ -49,6 +75,9 @@ impl ReportTrait for BrokenWikilink {
     fn id(&self) -> ErrorCode {
         self.id.clone()
     }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
     /// Create a new file called the text under the span
     fn fix(&self, config: &Config) -> Result<Option<()>, FixError> {
         trace!(
@@ -57,14 +86,44 @@ impl ReportTrait for BrokenWikilink {
             self.src.name()
         );
         let filename = format!("{}.md", FilenameLowercase::from_alias(&self.alias, config));
-        let path = config.pages_directory.join(filename);
-        std::fs::write(path.clone(), "").map_err(|source| FixError::IOError {
-            source,
-            backtrace: Backtrace::force_capture(),
-            file: path.to_string_lossy().to_string(),
-        })?;
+        let path = new_files_directory(config, Some(&self.path())).join(filename);
+        create_page(config, &self.alias.to_string(), &path)?;
         Ok(Some(()))
     }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.wikilink)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.wikilink)))
+    }
+}
+
+impl BrokenWikilink {
+    /// The alias in the wikilink which has no page
+    #[must_use]
+    pub fn alias(&self) -> &Alias {
+        &self.alias
+    }
+
+    /// The closest existing aliases to [`BrokenWikilink::alias`], best first
+    #[must_use]
+    pub fn suggestions(&self) -> &[Alias] {
+        &self.suggestions
+    }
+}
+
+impl KeepsSource for BrokenWikilink {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
 }
 
 impl PartialEq for BrokenWikilink {
@@ -79,34 +138,211 @@ impl PartialOrd for BrokenWikilink {
     }
 }
 
+/// Reads the wikilinks and tags of each file from the index, so it comes after the
+/// [`crate::alias_index::AliasIndexVisitor`] which adds them
 #[derive(Debug)]
 pub struct BrokenWikilinkVisitor {
-    pub alias_table: HashMap<Alias, PathBuf>,
-    pub wikilinks_visitor: WikilinkVisitor,
+    pub index: Rc<RefCell<AliasIndex>>,
     pub broken_wikilinks: Vec<BrokenWikilink>,
+    /// Broken wikilinks which look like relative paths, see [`looks_relative`]
+    pub relative_wikilinks: Vec<RelativeWikilink>,
+    /// Path-style wikilinks which match more than one file
+    pub ambiguous_wikilinks: Vec<AmbiguousWikilink>,
+    /// The full file names of attachments, like `diagram.png`, which can be linked to
+    attachments: HashSet<Alias>,
+    /// See [`LinkResolution`]
+    link_resolution: LinkResolution,
+    /// The canonical root directory that paths are relative to
+    root_directory: Option<PathBuf>,
+    /// The paths which resolve to a file, see [`path_key`], and the files with that path
+    /// There is more than one file when their paths only differ in case
+    paths: HashMap<String, Vec<PathBuf>>,
+    /// For [`LinkResolution::ShortestPath`], every trailing part of each path,
+    /// and the files which end in it
+    suffixes: HashMap<String, Vec<PathBuf>>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+/// What a wikilink points to, see [`BrokenWikilinkVisitor::resolve`]
+enum Resolution {
+    /// A page, alias, or attachment
+    Resolved,
+    /// More than one file, sorted
+    Ambiguous(Vec<PathBuf>),
+    Broken,
 }
 
 impl BrokenWikilinkVisitor {
     #[must_use]
     pub fn new(
-        _all_files: &[PathBuf],
-        _filename_to_alias: &ReplacePair<Filename, Alias>,
-        alias_table: HashMap<Alias, PathBuf>,
+        all_files: &[PathBuf],
+        attachments: &[PathBuf],
+        index: Rc<RefCell<AliasIndex>>,
+        config: &Config,
+        sources: Rc<RefCell<SourceBudget>>,
     ) -> Self {
+        let attachments = attachments
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| Alias::new(&name.to_string_lossy()))
+            .collect();
+        let root_directory = config.root_directory.canonicalize().ok();
+        let mut paths: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        let mut suffixes: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        if let Some(root) = &root_directory {
+            if config.link_resolution != LinkResolution::AliasOnly {
+                for file in all_files {
+                    let Some(key) = path_key(file, root) else {
+                        continue;
+                    };
+                    if config.link_resolution == LinkResolution::ShortestPath {
+                        let parts: Vec<&str> = key.split('/').collect();
+                        for i in 1..parts.len() {
+                            suffixes
+                                .entry(parts[i..].join("/"))
+                                .or_default()
+                                .push(file.clone());
+                        }
+                    }
+                    paths.entry(key).or_default().push(file.clone());
+                }
+            }
+        }
         Self {
-            alias_table,
-            wikilinks_visitor: WikilinkVisitor::new(),
+            sources,
+            index,
             broken_wikilinks: Vec::new(),
+            relative_wikilinks: Vec::new(),
+            attachments,
+            link_resolution: config.link_resolution,
+            root_directory,
+            paths,
+            suffixes,
+            ambiguous_wikilinks: Vec::new(),
+        }
+    }
+
+    /// What a wikilink in the file at `path` points to
+    ///
+    /// A full path from the root wins over the trailing part of a longer path,
+    /// and aliases win over both
+    fn resolve(&self, alias: &Alias, path: &Path) -> Resolution {
+        if self.index.borrow().resolve(alias).is_some() || self.attachments.contains(alias) {
+            return Resolution::Resolved;
+        }
+        let Some(root) = &self.root_directory else {
+            return Resolution::Broken;
+        };
+        let link = alias.to_string();
+        let key = match self.link_resolution {
+            LinkResolution::AliasOnly => return Resolution::Broken,
+            LinkResolution::ShortestPath | LinkResolution::Absolute => join_link(vec![], &link),
+            LinkResolution::Relative => path_key(path, root).and_then(|key| {
+                let mut base: Vec<String> = key.split('/').map(ToOwned::to_owned).collect();
+                base.pop();
+                join_link(base, &link)
+            }),
+        };
+        let Some(files) = key.and_then(|key| {
+            self.paths
+                .get(&key)
+                .or_else(|| self.suffixes.get(&key))
+                .cloned()
+        }) else {
+            return Resolution::Broken;
+        };
+        if files.len() == 1 {
+            Resolution::Resolved
+        } else {
+            Resolution::Ambiguous(files.into_iter().sorted().collect())
+        }
+    }
+}
+
+/// Where a page linked to from `linked_from` is created, the directory of the first of
+/// [`Config::new_files_directories`] whose glob matches `linked_from`,
+/// or else [`Config::new_files_directory`]
+#[must_use]
+pub fn new_files_directory(config: &Config, linked_from: Option<&Path>) -> PathBuf {
+    if let Some(path) = linked_from {
+        let relative = config.relative_to_root(path);
+        for new_files_directory in &config.new_files_directories {
+            let matches = Pattern::new(&new_files_directory.glob)
+                .is_ok_and(|pattern| pattern.matches_path(relative));
+            if !matches {
+                continue;
+            }
+            return match &new_files_directory.directory {
+                Some(directory) => directory.clone(),
+                None => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            };
         }
     }
+    config
+        .new_files_directory
+        .as_ref()
+        .unwrap_or(&config.pages_directory)
+        .clone()
+}
+
+/// Write a new page titled `title` at `path`, from [`Config::new_file_template`] if there is one
+///
+/// # Errors
+///
+/// If the template can't be read, or the page can't be written
+pub fn create_page(config: &Config, title: &str, path: &Path) -> Result<(), FixError> {
+    let io_error = |file: &Path| {
+        let file = file.to_string_lossy().to_string();
+        move |source| FixError::IOError {
+            source,
+            backtrace: Backtrace::force_capture(),
+            file,
+        }
+    };
+    let content = match &config.new_file_template {
+        Some(template) => std::fs::read_to_string(template)
+            .map_err(io_error(template))?
+            .replace("{{title}}", title),
+        None => String::new(),
+    };
+    std::fs::write(path, content).map_err(io_error(path))
+}
+
+/// How many aliases are suggested for a broken wikilink
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The aliases in `alias_table` which fuzzy match `alias`, best first
+///
+/// A fuzzy match needs the letters of one in order in the other, so both ways are tried,
+/// which finds typos with missing letters, like `lorm`, and extra letters, like `loremm`
+fn suggestions(alias: &Alias, alias_table: &HashMap<Alias, PathBuf>) -> Vec<Alias> {
+    let matcher = SkimMatcherV2::default();
+    let alias = alias.to_string();
+    alias_table
+        .keys()
+        .filter_map(|candidate| {
+            let other = candidate.to_string();
+            let score = matcher
+                .fuzzy_match(&other, &alias)
+                .max(matcher.fuzzy_match(&alias, &other))?;
+            Some((Reverse(score), candidate))
+        })
+        .sorted()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
 }
 
 impl Visitor for BrokenWikilinkVisitor {
     fn name(&self) -> &'static str {
         "BrokenWikilinkVisitor"
     }
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError> {
-        self.wikilinks_visitor.visit(node, source)?;
+    fn _visit(
+        &mut self,
+        _node: &Node<RefCell<Ast>>,
+        _source: &SourceMap,
+    ) -> Result<(), VisitError> {
         Ok(())
     }
     fn _finalize_file(
@@ -115,40 +351,133 @@ impl Visitor for BrokenWikilinkVisitor {
         path: &Path,
     ) -> std::result::Result<(), FinalizeError> {
         let filename = get_filename(path).lowercase();
-        let wikilinks = self.wikilinks_visitor.wikilinks.clone();
-        for wikilink in wikilinks {
-            let alias = wikilink.alias;
-            let id = format!("{CODE}::{filename}::{alias}");
-            if !self.alias_table.contains_key(&alias) {
-                self.broken_wikilinks.push(
-                    BrokenWikilink::builder()
-                        .advice(format!(
-                            "Create a page or alias on an existing page for '{alias}' (case insensitive), or fix the wikilinks spelling.\nid: {id:?}"
-                        ))
-                        .id(id.into())
-                        .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
-                        .wikilink(wikilink.span)
-                        .alias(alias)
-                        .build(),
-                );
+        let wikilinks: Vec<(Alias, SourceSpan)> = self
+            .index
+            .borrow()
+            .in_file(path)
+            .iter()
+            .filter(|occurrence| {
+                matches!(
+                    occurrence.kind,
+                    OccurrenceKind::Wikilink | OccurrenceKind::Tag
+                )
+            })
+            .filter_map(|occurrence| {
+                let range = occurrence.range.clone()?;
+                Some((occurrence.alias.clone(), (range.start, range.len()).into()))
+            })
+            .collect();
+        for (alias, span) in wikilinks {
+            let resolution = self.resolve(&alias, path);
+            if let Resolution::Ambiguous(candidates) = resolution {
+                let id = ErrorCode::in_file(ambiguous_wikilink::CODE, &filename, &[&alias]).0;
+                let report = AmbiguousWikilink::builder()
+                    .advice(format!(
+                        "Use a longer path, or rename one of the files, so the wikilink matches only one of:\n{}\nid: {id:?}",
+                        candidates
+                            .iter()
+                            .map(|candidate| format!("  - {}", candidate.display()))
+                            .join("\n")
+                    ))
+                    .id(id.into())
+                    .fingerprint(Fingerprint::new(
+                        ambiguous_wikilink::CODE,
+                        &[&alias.to_string(), &line_context(source, span.offset())],
+                    ))
+                    .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                    .wikilink(span)
+                    .alias(alias)
+                    .candidates(candidates)
+                    .build();
+                self.ambiguous_wikilinks
+                    .push(self.sources.borrow_mut().keep(report));
+                continue;
+            }
+            let broken = matches!(resolution, Resolution::Broken);
+            if broken && looks_relative(&alias) {
+                let id = ErrorCode::in_file(relative_wikilink::CODE, &filename, &[&alias]).0;
+                let report = RelativeWikilink::builder()
+                    .advice(format!(
+                        "Wikilinks resolve by alias, not by path, link to the page like [[page]] instead.\nid: {id:?}"
+                    ))
+                    .id(id.into())
+                    .fingerprint(Fingerprint::new(
+                        relative_wikilink::CODE,
+                        &[&alias.to_string(), &line_context(source, span.offset())],
+                    ))
+                    .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                    .wikilink(span)
+                    .alias(alias)
+                    .build();
+                self.relative_wikilinks
+                    .push(self.sources.borrow_mut().keep(report));
+                continue;
+            }
+            let id = ErrorCode::in_file(CODE, &filename, &[&alias]).0;
+            if broken {
+                let suggestions = suggestions(&alias, self.index.borrow().alias_table());
+                let did_you_mean = if suggestions.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        "\nDid you mean: {}?",
+                        suggestions
+                            .iter()
+                            .map(|alias| format!("[[{alias}]]"))
+                            .join(", ")
+                    )
+                };
+                let report = BrokenWikilink::builder()
+                    .advice(format!(
+                        "Create a page or alias on an existing page for '{alias}' (case insensitive), or fix the wikilinks spelling.{did_you_mean}\nid: {id:?}"
+                    ))
+                    .id(id.into())
+                    .fingerprint(Fingerprint::new(
+                        CODE,
+                        &[&alias.to_string(), &line_context(source, span.offset())],
+                    ))
+                    .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                    .wikilink(span)
+                    .alias(alias)
+                    .suggestions(suggestions)
+                    .build();
+                self.broken_wikilinks
+                    .push(self.sources.borrow_mut().keep(report));
             }
         }
-
-        self.wikilinks_visitor.finalize_file(source, path)?;
         Ok(())
     }
 
-    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Vec<Report>, FinalizeError> {
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
         // We can "take" this because we are putting it right back
-        self.broken_wikilinks = dedupe_by_code(filter_by_excludes(
-            std::mem::take(&mut self.broken_wikilinks),
-            excludes,
-        ));
-        self.wikilinks_visitor.finalize(excludes)?;
-        Ok(self
-            .broken_wikilinks
-            .iter()
-            .map(|x| Report::ThirdPass(ThirdPassReport::BrokenWikilink(x.clone())))
-            .collect())
+        let (broken_wikilinks, mut matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.broken_wikilinks), excludes);
+        self.broken_wikilinks = dedupe_by_code(broken_wikilinks);
+        let (relative_wikilinks, matched) =
+            filter_by_excludes(std::mem::take(&mut self.relative_wikilinks), excludes);
+        matched_excludes.extend(matched);
+        self.relative_wikilinks = dedupe_by_code(relative_wikilinks);
+        let (ambiguous_wikilinks, matched) =
+            filter_by_excludes(std::mem::take(&mut self.ambiguous_wikilinks), excludes);
+        matched_excludes.extend(matched);
+        self.ambiguous_wikilinks = dedupe_by_code(ambiguous_wikilinks);
+        Ok(Finalized {
+            reports: self
+                .broken_wikilinks
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::BrokenWikilink(x.clone())))
+                .chain(
+                    self.relative_wikilinks
+                        .iter()
+                        .map(|x| Report::ThirdPass(ThirdPassReport::RelativeWikilink(x.clone()))),
+                )
+                .chain(
+                    self.ambiguous_wikilinks
+                        .iter()
+                        .map(|x| Report::ThirdPass(ThirdPassReport::AmbiguousWikilink(x.clone()))),
+                )
+                .collect(),
+            matched_excludes,
+        })
     }
 }
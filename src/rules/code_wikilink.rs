@@ -0,0 +1,216 @@
+//! Wikilinks inside inline code or code blocks, like `` `[[lorem]]` ``, which don't render as
+//! links
+//!
+//! This is sometimes on purpose, like in docs about wikilinks, so it only runs with
+//! [`Config::code_wikilinks`], and reports are excluded like any other
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{case::fold, content::source_map::SourceMap, name::get_filename},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::wikilink::code";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A wikilink is inside code, so it is not a link")]
+#[diagnostic(code("content::wikilink::code"), severity(Warning))]
+pub struct CodeWikilink {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The wikilink, as written
+    wikilink: String,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Inside code")]
+    pub span: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for CodeWikilink {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// The code may be on purpose, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl CodeWikilink {
+    /// The wikilink, as written
+    #[must_use]
+    pub fn wikilink(&self) -> &str {
+        &self.wikilink
+    }
+}
+
+impl KeepsSource for CodeWikilink {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for CodeWikilink {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for CodeWikilink {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct CodeWikilinkVisitor {
+    pub code_wikilinks: Vec<CodeWikilink>,
+    /// Matches `[[lorem]]` and `[[text|lorem]]` on one line
+    wikilink_pattern: Regex,
+    /// The wikilinks in code in the current file
+    spans: Vec<SourceSpan>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl Default for CodeWikilinkVisitor {
+    fn default() -> Self {
+        Self {
+            code_wikilinks: Vec::new(),
+            wikilink_pattern: Regex::new(r"\[\[[^\[\]\n]+\]\]").expect("Constant"),
+            spans: Vec::new(),
+            sources: Rc::default(),
+        }
+    }
+}
+
+impl CodeWikilinkVisitor {
+    #[must_use]
+    pub fn new(sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            ..Self::default()
+        }
+    }
+}
+
+impl Visitor for CodeWikilinkVisitor {
+    fn name(&self) -> &'static str {
+        "CodeWikilinkVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        if !matches!(data_ref.value, NodeValue::Code(_) | NodeValue::CodeBlock(_)) {
+            return Ok(());
+        }
+        let sourcepos = data_ref.sourcepos;
+        let start = source.start(sourcepos);
+        let end = source.end(sourcepos);
+        // The backticks and fences are searched too, but never match
+        let Some(code) = source.get(start..end.min(source.len())) else {
+            return Ok(());
+        };
+        for wikilink in self.wikilink_pattern.find_iter(code) {
+            self.spans.push(SourceSpan::new(
+                (start + wikilink.start()).into(),
+                wikilink.len(),
+            ));
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path).lowercase();
+        for span in std::mem::take(&mut self.spans) {
+            let wikilink = source[span.offset()..span.offset() + span.len()].to_owned();
+            let id = ErrorCode::in_file(CODE, &filename, &[&fold(&wikilink)]).0;
+            let report = CodeWikilink::builder()
+                .advice(format!(
+                    "Move {wikilink} out of the code to link it, or exclude this if it is on purpose.\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(
+                    CODE,
+                    &[&fold(&wikilink), &line_context(source, span.offset())],
+                ))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .span(span)
+                .wikilink(wikilink)
+                .build();
+            self.code_wikilinks
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (code_wikilinks, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.code_wikilinks), excludes);
+        self.code_wikilinks = dedupe_by_code(code_wikilinks);
+        Ok(Finalized {
+            reports: self
+                .code_wikilinks
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::CodeWikilink(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
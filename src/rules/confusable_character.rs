@@ -0,0 +1,417 @@
+//! Filenames and aliases with characters which look like others, like a Cyrillic `а` among
+//! Latin letters, or which can't be seen, like zero width and non-breaking spaces
+//!
+//! These render the same as the name they look like, so wikilinks to that name never resolve.
+//! The aliases of a file are those written in its front matter, and those of the alias table
+//! which resolve to it. The alias made from the filename is only reported if the filename isn't.
+//!
+//! Skipped:
+//! - Words all in Cyrillic or Greek, which are not lookalikes
+//! - The rest of the content, like the text of wikilinks
+//! - The front matter of files with these characters anywhere in their content, as for now they
+//!   are not parsed, see [`crate::visitor::ParseError::MultibyteError`]
+use std::{
+    cell::RefCell,
+    ops::Range,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{front_matter::alias_spans, source_map::SourceMap, wikilink::Alias},
+        name::{get_filename, Filename},
+    },
+    sed::ReplacePair,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashMap;
+use miette::{Diagnostic, LabeledSpan, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "name::confusable";
+
+/// Characters which can't be seen, what they are, and what they should be instead
+const INVISIBLE: &[(char, &str, &str)] = &[
+    ('\u{00A0}', "a non-breaking space", " "),
+    ('\u{00AD}', "a soft hyphen", ""),
+    ('\u{2007}', "a figure space", " "),
+    ('\u{200B}', "a zero width space", ""),
+    ('\u{200C}', "a zero width non-joiner", ""),
+    ('\u{200D}', "a zero width joiner", ""),
+    ('\u{2060}', "a word joiner", ""),
+    ('\u{202F}', "a narrow non-breaking space", " "),
+    ('\u{FEFF}', "a zero width no-break space", ""),
+];
+
+/// Cyrillic and Greek letters, and the Latin letters they look like
+const HOMOGLYPHS: &[(char, &str)] = &[
+    ('а', "a"),
+    ('е', "e"),
+    ('о', "o"),
+    ('р', "p"),
+    ('с', "c"),
+    ('у', "y"),
+    ('х', "x"),
+    ('і', "i"),
+    ('ј', "j"),
+    ('ѕ', "s"),
+    ('А', "A"),
+    ('В', "B"),
+    ('Е', "E"),
+    ('К', "K"),
+    ('М', "M"),
+    ('Н', "H"),
+    ('О', "O"),
+    ('Р', "P"),
+    ('С', "C"),
+    ('Т', "T"),
+    ('Х', "X"),
+    ('ο', "o"),
+    ('Α', "A"),
+    ('Β', "B"),
+    ('Ε', "E"),
+    ('Η', "H"),
+    ('Ι', "I"),
+    ('Κ', "K"),
+    ('Μ', "M"),
+    ('Ν', "N"),
+    ('Ο', "O"),
+    ('Ρ', "P"),
+    ('Τ', "T"),
+    ('Χ', "X"),
+    ('Ζ', "Z"),
+];
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A filename or alias has characters which look like others, or can't be seen")]
+#[diagnostic(code("name::confusable"))]
+pub struct ConfusableCharacter {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The filename, as written
+    filename: String,
+
+    /// The alias with the characters, as written in the front matter, or as in the alias table
+    /// when it is not found there, [`None`] when it is the filename which has them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    alias: Option<String>,
+
+    /// The filename or alias with the characters replaced by what they look like, and those which
+    /// can't be seen removed, or replaced by plain spaces
+    suggestion: String,
+
+    /// The path of the file, with the filename labeled, or for an alias the content of the file
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    /// Where the alias is written in the front matter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span: Option<SourceSpan>,
+
+    /// Each character, labeled with what it is
+    #[label(collection)]
+    characters: Vec<LabeledSpan>,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for ConfusableCharacter {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Not fixed, as the wikilinks written with the same characters resolve to the file now, and
+    /// would break if it was renamed or the alias was changed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span?)))
+    }
+}
+
+/// Only the reports of aliases keep the content of the file, the source of the others is its path
+impl KeepsSource for ConfusableCharacter {
+    fn source_len(&self) -> usize {
+        if self.alias.is_some() {
+            self.src.inner().len()
+        } else {
+            0
+        }
+    }
+    fn elide_source(&mut self) {
+        if self.alias.is_some() {
+            self.line_column = self.line_column();
+            self.src = elided(&self.src);
+        }
+    }
+}
+
+impl ConfusableCharacter {
+    /// The name of the file the characters are in, or which has the alias they are in
+    #[must_use]
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// The alias the characters are in, [`None`] when they are in the filename
+    #[must_use]
+    pub fn alias(&self) -> Option<&str> {
+        self.alias.as_deref()
+    }
+
+    /// What the filename or alias was likely meant to be
+    #[must_use]
+    pub fn suggestion(&self) -> &str {
+        &self.suggestion
+    }
+}
+
+impl PartialEq for ConfusableCharacter {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for ConfusableCharacter {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// The byte offsets in `filename` of the characters which can't be seen, and of the lookalikes in
+/// words which also have Latin letters, with what each is and what it should be instead
+/// A word all in Cyrillic or Greek is not reported
+fn confusables(filename: &str) -> Vec<(usize, char, String, &'static str)> {
+    let mut out = Vec::new();
+    let mut word_start = 0;
+    let mut words: Vec<(usize, &str)> = Vec::new();
+    for (i, c) in filename.char_indices() {
+        if !c.is_alphanumeric() {
+            words.push((word_start, &filename[word_start..i]));
+            word_start = i + c.len_utf8();
+        }
+        if let Some((_, what, instead)) = INVISIBLE.iter().find(|(invisible, ..)| *invisible == c) {
+            out.push((i, c, format!("U+{:04X}, {what}", c as u32), *instead));
+        }
+    }
+    words.push((word_start, &filename[word_start..]));
+    for (start, word) in words {
+        if !word.chars().any(|c| c.is_ascii_alphabetic()) {
+            continue;
+        }
+        for (i, c) in word.char_indices() {
+            if let Some((_, latin)) = HOMOGLYPHS.iter().find(|(homoglyph, _)| *homoglyph == c) {
+                let what = format!("U+{:04X}, which looks like '{latin}'", c as u32);
+                out.push((start + i, c, what, *latin));
+            }
+        }
+    }
+    out.sort_by_key(|(i, ..)| *i);
+    out
+}
+
+/// `name` with each of the `found` characters replaced by what it should be instead
+fn suggestion(name: &str, found: &[(usize, char, String, &'static str)]) -> String {
+    let mut suggestion = name.to_owned();
+    for (i, c, _, instead) in found.iter().rev() {
+        suggestion.replace_range(*i..i + c.len_utf8(), instead);
+    }
+    suggestion
+}
+
+/// The `found` characters labeled with what they are, where `name` starts at `offset`
+fn labels(found: Vec<(usize, char, String, &'static str)>, offset: usize) -> Vec<LabeledSpan> {
+    found
+        .into_iter()
+        .map(|(i, c, what, _)| {
+            LabeledSpan::new_with_span(
+                Some(what),
+                SourceSpan::new((offset + i).into(), c.len_utf8()),
+            )
+        })
+        .collect()
+}
+
+/// The advice of a report of the `found` characters
+fn advice(found: &[(usize, char, String, &'static str)], suggestion: &str, id: &str) -> String {
+    let listed = found
+        .iter()
+        .map(|(_, _, what, _)| format!("  {what}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("It has:\n{listed}\nDid you mean '{suggestion}'?\nid: {id:?}")
+}
+
+#[derive(Debug)]
+pub struct ConfusableCharacterVisitor {
+    pub confusable_characters: Vec<ConfusableCharacter>,
+    /// The aliases of the alias table, by the file they resolve to
+    aliases: HashMap<PathBuf, Vec<Alias>>,
+    /// To tell the alias made from a filename, see [`Config::filename_to_alias`]
+    filename_to_alias: ReplacePair<Filename, Alias>,
+    /// Takes the source of each report of an alias as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl ConfusableCharacterVisitor {
+    #[must_use]
+    pub fn new(
+        alias_table: &HashMap<Alias, PathBuf>,
+        config: &Config,
+        sources: Rc<RefCell<SourceBudget>>,
+    ) -> Self {
+        let mut aliases: HashMap<PathBuf, Vec<Alias>> = HashMap::new();
+        for (alias, path) in alias_table {
+            aliases.entry(path.clone()).or_default().push(alias.clone());
+        }
+        Self {
+            confusable_characters: Vec::new(),
+            aliases,
+            filename_to_alias: config.filename_to_alias.clone(),
+            sources,
+        }
+    }
+
+    /// Report the characters in the filename of `path`, labeled in the path
+    /// Whether there were any
+    fn check_filename(&mut self, path: &Path) -> bool {
+        let filename = get_filename(path).to_string();
+        let found = confusables(&filename);
+        if found.is_empty() {
+            return false;
+        }
+        let suggestion = suggestion(&filename, &found);
+        let source = path.to_string_lossy().to_string();
+        let offset = source.rfind(&filename).unwrap_or_default();
+        let id = ErrorCode::in_file(CODE, &get_filename(path).lowercase(), &[]).0;
+        self.confusable_characters.push(
+            ConfusableCharacter::builder()
+                .advice(advice(&found, &suggestion, &id))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&filename]))
+                .src(NamedSource::new(&source, source.clone()))
+                .characters(labels(found, offset))
+                .filename(filename)
+                .suggestion(suggestion)
+                .build(),
+        );
+        true
+    }
+
+    /// Report the characters in `alias`, labeled where it is written in the front matter of
+    /// `source`, at `range`, if it was found there
+    fn check_alias(&mut self, source: &str, path: &Path, alias: &str, range: Option<Range<usize>>) {
+        let found = confusables(alias);
+        if found.is_empty() {
+            return;
+        }
+        let filename = get_filename(path);
+        let suggestion = suggestion(alias, &found);
+        let id = ErrorCode::in_file(CODE, &filename.lowercase(), &[&Alias::new(alias)]).0;
+        let report = ConfusableCharacter::builder()
+            .advice(advice(&found, &suggestion, &id))
+            .id(id.into())
+            .fingerprint(Fingerprint::new(CODE, &[&filename.to_string(), alias]))
+            .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+            .characters(
+                range
+                    .as_ref()
+                    .map_or_else(Vec::new, |range| labels(found, range.start)),
+            )
+            .maybe_span(range.map(|range| SourceSpan::new(range.start.into(), range.len())))
+            .filename(filename.to_string())
+            .alias(alias.to_owned())
+            .suggestion(suggestion)
+            .build();
+        self.confusable_characters
+            .push(self.sources.borrow_mut().keep(report));
+    }
+}
+
+impl Visitor for ConfusableCharacterVisitor {
+    fn name(&self) -> &'static str {
+        "ConfusableCharacterVisitor"
+    }
+    fn _visit(
+        &mut self,
+        _node: &Node<RefCell<Ast>>,
+        _source: &SourceMap,
+    ) -> Result<(), VisitError> {
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename_reported = self.check_filename(path);
+        let spans = alias_spans(source);
+        for range in spans.values() {
+            self.check_alias(source, path, &source[range.clone()], Some(range.clone()));
+        }
+        // The alias made from the filename has the characters of the filename, and the others
+        // are written in the front matter with escapes, so are not found in it
+        let filename_alias = Alias::from_filename(&get_filename(path), &self.filename_to_alias);
+        let unwritten: Vec<Alias> = self
+            .aliases
+            .get(path)
+            .into_iter()
+            .flatten()
+            .filter(|alias| !spans.contains_key(*alias))
+            .filter(|alias| !(filename_reported && **alias == filename_alias))
+            .cloned()
+            .collect();
+        for alias in unwritten {
+            self.check_alias(source, path, &alias.to_string(), None);
+        }
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (confusable_characters, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.confusable_characters), excludes);
+        self.confusable_characters = dedupe_by_code(confusable_characters);
+        Ok(Finalized {
+            reports: self
+                .confusable_characters
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::ConfusableCharacter(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! Rules from outside this crate, registered with [`crate::linter::Linter::with_rule`]
+//!
+//! A [`CustomRule`] makes a [`Visitor`] for every check, which returns [`Report::Custom`]s
+//! of [`CustomReport`]s from [`Visitor::finalize`]
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use bon::bon;
+use hashbrown::HashMap;
+use miette::{Diagnostic, LabeledSpan, MietteDiagnostic, Severity, SourceCode, SourceSpan};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    config::Config,
+    file::content::{line_column, wikilink::Alias},
+    visitor::Visitor,
+};
+
+use super::{line_context, ErrorCode, Fingerprint, FixError, Report, ReportTrait};
+
+/// Makes the [`Visitor`] for a rule
+///
+/// Visitors keep state between files, so a fresh one is made for every check, which happens twice
+/// when fixing. It runs with the built in rules which check links, after aliases are collected
+pub trait CustomRule: Send + Sync {
+    /// `alias_table` has every alias, and the file wikilinks to it resolve to
+    fn visitor(&self, config: &Config, alias_table: &HashMap<Alias, PathBuf>) -> Box<dyn Visitor>;
+}
+
+impl<F> CustomRule for F
+where
+    F: Fn(&Config, &HashMap<Alias, PathBuf>) -> Box<dyn Visitor> + Send + Sync,
+{
+    fn visitor(&self, config: &Config, alias_table: &HashMap<Alias, PathBuf>) -> Box<dyn Visitor> {
+        self(config, alias_table)
+    }
+}
+
+/// A report made by a [`CustomRule`], which renders as the diagnostic it carries
+///
+/// Custom reports are never fixed. When deserialized, the diagnostic is a [`MietteDiagnostic`]
+/// with the same message, code, severity, help, url and labels, but no source code
+#[derive(Debug, Clone)]
+pub struct CustomReport {
+    /// Used to identify the report and exclude it if needed
+    id: ErrorCode,
+    fingerprint: Fingerprint,
+    path: PathBuf,
+    span: Option<SourceSpan>,
+    line_column: Option<(usize, usize)>,
+    diagnostic: Arc<dyn Diagnostic + Send + Sync>,
+}
+
+#[bon]
+impl CustomReport {
+    #[builder]
+    pub fn new(
+        /// See [`ReportTrait::id`], which is matched against [`Config::exclude`]
+        id: ErrorCode,
+        /// See [`ReportTrait::path`]
+        path: PathBuf,
+        /// See [`ReportTrait::span`]
+        span: Option<SourceSpan>,
+        /// The content of `path`, to find the line and column of `span`
+        /// The line is also used for [`ReportTrait::fingerprint`]
+        source: Option<&str>,
+        /// What is rendered for the user
+        diagnostic: Arc<dyn Diagnostic + Send + Sync>,
+    ) -> Self {
+        let context = span
+            .zip(source)
+            .map(|(span, source)| line_context(source, span.offset()))
+            .unwrap_or_default();
+        Self {
+            fingerprint: Fingerprint::new(&id.0, &[&context]),
+            id,
+            path,
+            span,
+            line_column: span
+                .zip(source)
+                .map(|(span, source)| line_column(source, span.offset())),
+            diagnostic,
+        }
+    }
+
+    /// Wrap the report in a [`Report`], for returning it from [`Visitor::finalize`]
+    #[must_use]
+    pub fn into_report(self) -> Report {
+        Report::Custom(self)
+    }
+}
+
+impl ReportTrait for CustomReport {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    /// The id, and the line of the span if the source was given
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        self.path.clone()
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        self.span
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+    }
+}
+
+impl fmt::Display for CustomReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.diagnostic.fmt(f)
+    }
+}
+
+impl std::error::Error for CustomReport {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.diagnostic.source()
+    }
+}
+
+impl Diagnostic for CustomReport {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.diagnostic.code()
+    }
+    fn severity(&self) -> Option<Severity> {
+        self.diagnostic.severity()
+    }
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.diagnostic.help()
+    }
+    fn url<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.diagnostic.url()
+    }
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.diagnostic.source_code()
+    }
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.diagnostic.labels()
+    }
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.diagnostic.related()
+    }
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.diagnostic.diagnostic_source()
+    }
+}
+
+/// How a [`CustomReport`] is serialized, the diagnostic is turned into a [`MietteDiagnostic`]
+#[derive(Serialize, Deserialize)]
+struct SerializedCustomReport {
+    id: ErrorCode,
+    #[serde(default)]
+    fingerprint: Fingerprint,
+    path: PathBuf,
+    span: Option<SourceSpan>,
+    line_column: Option<(usize, usize)>,
+    diagnostic: MietteDiagnostic,
+}
+
+impl Serialize for CustomReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let diagnostic = &self.diagnostic;
+        let mut miette_diagnostic = MietteDiagnostic::new(diagnostic.to_string());
+        miette_diagnostic.code = diagnostic.code().map(|code| code.to_string());
+        miette_diagnostic.severity = diagnostic.severity();
+        miette_diagnostic.help = diagnostic.help().map(|help| help.to_string());
+        miette_diagnostic.url = diagnostic.url().map(|url| url.to_string());
+        miette_diagnostic.labels = diagnostic.labels().map(Iterator::collect);
+        SerializedCustomReport {
+            id: self.id.clone(),
+            fingerprint: self.fingerprint.clone(),
+            path: self.path.clone(),
+            span: self.span,
+            line_column: self.line_column,
+            diagnostic: miette_diagnostic,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CustomReport {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let report = SerializedCustomReport::deserialize(deserializer)?;
+        Ok(Self {
+            id: report.id,
+            fingerprint: report.fingerprint,
+            path: report.path,
+            span: report.span,
+            line_column: report.line_column,
+            diagnostic: Arc::new(report.diagnostic),
+        })
+    }
+}
@@ -1,27 +1,80 @@
 use crate::{
+    aliases::AliasSource,
+    budget::SourceBudget,
     config::Config,
     file::{
-        content::{front_matter::FrontMatterVisitor, wikilink::Alias},
+        case::find_folded,
+        content::{
+            front_matter::{alias_spans, raw_front_matter, FrontMatterVisitor},
+            read_source,
+            source_map::SourceMap,
+            wikilink::Alias,
+        },
         name::{get_filename, Filename},
+        provider::FileProvider,
     },
     ngrams::CalculateError,
     sed::{ReplacePair, ReplacePairCompilationError},
-    visitor::{FinalizeError, VisitError, Visitor},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
 };
 use comrak::{arena_tree::Node, nodes::Ast};
 use hashbrown::{HashMap, HashSet};
 use miette::{Diagnostic, NamedSource, SourceOffset, SourceSpan};
+use serde::{Deserialize, Serialize};
 use std::{
     cell::RefCell,
+    ops::Range,
     path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
 };
 use thiserror::Error;
+use tracing::warn;
 
-use super::{dedupe_by_code, filter_by_excludes, ErrorCode, FixError, Report, ReportTrait};
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait,
+};
 
 pub const CODE: &str = "name::alias::duplicate";
 
-#[derive(Error, Debug, Diagnostic, Clone)]
+/// The file named after an alias, related to [`DuplicateAlias::FileNameContentDuplicate`] and
+/// [`DuplicateAlias::FileNameFileNameDuplicate`]
+/// Its content does not mention the alias, so its source is its path, with the filename labeled
+#[derive(Error, Debug, Diagnostic, Clone, Serialize, Deserialize)]
+#[error("The file named after the alias")]
+pub struct AliasFilename {
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    #[label("Named '{filename}'")]
+    span: SourceSpan,
+
+    filename: Filename,
+}
+
+impl AliasFilename {
+    #[must_use]
+    pub fn new(path: &Path) -> Self {
+        let filename = get_filename(path);
+        let source = path.to_string_lossy().to_string();
+        let offset = source.rfind(&filename.to_string()).unwrap_or_default();
+        Self {
+            span: SourceSpan::new(offset.into(), filename.to_string().len()),
+            src: NamedSource::new(&source, source.clone()),
+            filename,
+        }
+    }
+
+    /// The file named after the alias
+    #[must_use]
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+}
+
+#[derive(Error, Debug, Diagnostic, Clone, Serialize, Deserialize)]
 #[error("A wikilink does not have a corresponding page")]
 #[diagnostic(code("name::alias::duplicate"))]
 pub enum DuplicateAlias {
@@ -29,16 +82,32 @@ pub enum DuplicateAlias {
         /// Used to identify the diagnostic and exclude it if needed
         id: ErrorCode,
 
+        /// See [`ReportTrait::fingerprint`]
+        #[serde(default)]
+        fingerprint: Fingerprint,
+
         /// The filename the alias contradicts with
         other_filename: Filename,
 
         /// The content of the file with the alias
         #[source_code]
+        #[serde(with = "super::named_source")]
         src: NamedSource<String>,
 
-        /// The alias span in the content of the file with the alias
+        /// Kept when [`KeepsSource::elide_source`] drops the source
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_column: Option<(usize, usize)>,
+
+        /// The span of the entry of the alias in the front matter of the file with the alias
+        /// [`None`] if the entry could not be found, like when it is escaped in the
+        /// front matter, then the report is about the whole file
         #[label("Contradicts with the file named '{other_filename}' (case insensitive)")]
-        alias: SourceSpan,
+        alias: Option<SourceSpan>,
+
+        /// The file named after the alias, so both files are shown
+        #[related]
+        #[serde(default)]
+        other: Vec<AliasFilename>,
 
         /// Just some advice
         #[help]
@@ -48,21 +117,60 @@ pub enum DuplicateAlias {
         /// Used to identify the diagnostic and exclude it if needed
         id: ErrorCode,
 
+        /// See [`ReportTrait::fingerprint`]
+        #[serde(default)]
+        fingerprint: Fingerprint,
+
         /// The filename which contains the other duplicate alias
         other_filename: Filename,
 
         /// The content of the file with the alias
         #[source_code]
+        #[serde(with = "super::named_source")]
         src: NamedSource<String>,
 
-        /// The alias span in the content of the file with the
+        /// See [`DuplicateAlias::FileNameContentDuplicate::line_column`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        line_column: Option<(usize, usize)>,
+
+        /// The alias span in the content of the file with the alias, see
+        /// [`DuplicateAlias::FileNameContentDuplicate::alias`]
         #[label("Contradicts with aliases within '{other_filename}' (case insensitive)")]
-        alias: SourceSpan,
+        alias: Option<SourceSpan>,
 
         /// Put an exact copy but using the other file in src
         #[related]
         other: Vec<Self>,
 
+        /// Just some advice
+        #[help]
+        advice: String,
+    },
+    /// Two files are named after the same alias, like `pages/lorem.md` and `journals/lorem.md`
+    FileNameFileNameDuplicate {
+        /// Used to identify the diagnostic and exclude it if needed
+        id: ErrorCode,
+
+        /// See [`ReportTrait::fingerprint`]
+        #[serde(default)]
+        fingerprint: Fingerprint,
+
+        /// The filename of the other file named after the alias
+        other_filename: Filename,
+
+        /// The path of the file, neither content mentions the alias
+        #[source_code]
+        #[serde(with = "super::named_source")]
+        src: NamedSource<String>,
+
+        /// The filename in the path
+        #[label("Named after the same alias as '{other_filename}' (case insensitive)")]
+        filename: SourceSpan,
+
+        /// The other file named after the alias
+        #[related]
+        other: Vec<AliasFilename>,
+
         /// Just some advice
         #[help]
         advice: String,
@@ -72,12 +180,120 @@ impl ReportTrait for DuplicateAlias {
     fn id(&self) -> ErrorCode {
         match self {
             DuplicateAlias::FileNameContentDuplicate { id: code, .. }
-            | DuplicateAlias::FileContentContentDuplicate { id: code, .. } => code.clone(),
+            | DuplicateAlias::FileContentContentDuplicate { id: code, .. }
+            | DuplicateAlias::FileNameFileNameDuplicate { id: code, .. } => code.clone(),
+        }
+    }
+    /// The alias is the only context, the two files have to be different pages
+    fn fingerprint(&self) -> Fingerprint {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate { fingerprint, .. }
+            | DuplicateAlias::FileContentContentDuplicate { fingerprint, .. }
+            | DuplicateAlias::FileNameFileNameDuplicate { fingerprint, .. } => fingerprint.clone(),
         }
     }
     fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
         Ok(None)
     }
+    /// The code followed by the alias, without the filename
+    fn legacy_id(&self) -> Option<ErrorCode> {
+        Some(format!("{CODE}::{}", self.alias()).into())
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src().name())
+    }
+    /// [`None`] for [`DuplicateAlias::FileNameFileNameDuplicate`],
+    /// as there is nothing to point at in the content
+    fn span(&self) -> Option<SourceSpan> {
+        self.alias_span()
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate {
+                line_column: Some(line_column),
+                ..
+            }
+            | DuplicateAlias::FileContentContentDuplicate {
+                line_column: Some(line_column),
+                ..
+            } => Some(*line_column),
+            _ => self
+                .alias_span()
+                .map(|span| span_line_column(self.src(), span)),
+        }
+    }
+}
+
+impl DuplicateAlias {
+    fn src(&self) -> &NamedSource<String> {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate { src, .. }
+            | DuplicateAlias::FileContentContentDuplicate { src, .. }
+            | DuplicateAlias::FileNameFileNameDuplicate { src, .. } => src,
+        }
+    }
+
+    fn alias_span(&self) -> Option<SourceSpan> {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate { alias, .. }
+            | DuplicateAlias::FileContentContentDuplicate { alias, .. } => *alias,
+            DuplicateAlias::FileNameFileNameDuplicate { .. } => None,
+        }
+    }
+
+    /// The duplicated alias, lowercase
+    #[must_use]
+    pub fn alias(&self) -> Alias {
+        // The id is always the code and the filename followed by the alias
+        let id = self.id().0;
+        let prefix = ErrorCode::in_file(CODE, &get_filename(&self.path()).lowercase(), &[]).0;
+        Alias::new(id.strip_prefix(&format!("{prefix}::")).unwrap_or(&id))
+    }
+
+    /// The other file named after or declaring the alias
+    #[must_use]
+    pub fn other_filename(&self) -> &Filename {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate { other_filename, .. }
+            | DuplicateAlias::FileContentContentDuplicate { other_filename, .. }
+            | DuplicateAlias::FileNameFileNameDuplicate { other_filename, .. } => other_filename,
+        }
+    }
+}
+
+impl KeepsSource for DuplicateAlias {
+    fn source_len(&self) -> usize {
+        match self {
+            DuplicateAlias::FileNameContentDuplicate { src, .. } => src.inner().len(),
+            DuplicateAlias::FileContentContentDuplicate { src, other, .. } => {
+                src.inner().len() + other.iter().map(Self::source_len).sum::<usize>()
+            }
+            // The source is just the path
+            DuplicateAlias::FileNameFileNameDuplicate { .. } => 0,
+        }
+    }
+    fn elide_source(&mut self) {
+        let kept = self.line_column();
+        match self {
+            DuplicateAlias::FileNameContentDuplicate {
+                src, line_column, ..
+            } => {
+                *line_column = kept;
+                *src = elided(src);
+            }
+            DuplicateAlias::FileContentContentDuplicate {
+                src,
+                line_column,
+                other,
+                ..
+            } => {
+                *line_column = kept;
+                *src = elided(src);
+                other.iter_mut().for_each(Self::elide_source);
+            }
+            DuplicateAlias::FileNameFileNameDuplicate { .. } => {}
+        }
+    }
 }
 
 impl PartialEq for DuplicateAlias {
@@ -92,6 +308,43 @@ impl PartialOrd for DuplicateAlias {
     }
 }
 
+/// The span of the entry of `alias` in the front matter of the `content` of the file at `path`,
+/// see [`alias_spans`]
+/// An alias under a key [`alias_spans`] doesn't read, like a quoted `"aliases":`, is looked for
+/// in the values of the front matter instead, see [`find_folded`]
+/// [`None`] if it is not written as is, like when it is escaped in the front matter,
+/// the alias is still a duplicate, so it is reported without a span rather than stopping the run
+fn find_alias(alias: &Alias, path: &Path, content: &str) -> Option<SourceSpan> {
+    let found = alias_spans(content)
+        .remove(alias)
+        .or_else(|| find_in_values(alias, content));
+    if found.is_none() {
+        warn!(
+            "Could not find the alias {alias:?} in {}, reporting the whole file",
+            path.display()
+        );
+    }
+    found.map(|range| SourceSpan::new(SourceOffset::from(range.start), range.len()))
+}
+
+/// The first place `alias` is written in the front matter of `content`, outside the keys
+fn find_in_values(alias: &Alias, content: &str) -> Option<Range<usize>> {
+    let (start, yaml) = raw_front_matter(content)?;
+    let mut offset = start;
+    for line in yaml.split_inclusive('\n') {
+        // The value of a line like `key: value`, or the whole line for an item or a continuation
+        let value = match line.split_once(": ") {
+            Some((key, _)) if !line.starts_with(char::is_whitespace) => key.len() + 2,
+            _ => 0,
+        };
+        if let Some(range) = find_folded(&line[value..], &alias.to_string()) {
+            return Some(offset + value + range.start..offset + value + range.end);
+        }
+        offset += line.len();
+    }
+    None
+}
+
 #[derive(Debug)]
 pub struct DuplicateAliasVisitor {
     /// Put an alias in get a file that contains that alias (or is named after the alias) out
@@ -101,33 +354,59 @@ pub struct DuplicateAliasVisitor {
     pub duplicate_alias_errors: Vec<DuplicateAlias>,
     /// This is just the duplicate aliases themselves, useful for downstream tasks
     pub duplicate_aliases: HashSet<Alias>,
+    /// The files whose aliases are never suggested as unlinked text,
+    /// see [`crate::file::content::front_matter::YamlFrontMatter::no_autolink`]
+    pub no_autolink: HashSet<PathBuf>,
+    /// Every alias, the file which defines it, and how, in the order they are found
+    pub definitions: Vec<(Alias, PathBuf, AliasSource)>,
     /// Our main visitor, helps us get aliases from files, needs to be reset each file
     front_matter_visitor: FrontMatterVisitor,
     /// Just need to strore this for later to get aliases from filenames
     filename_to_alias: ReplacePair<Filename, Alias>,
+    /// Reads the other file of a duplicate
+    files: Arc<dyn FileProvider>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
 }
 
 impl DuplicateAliasVisitor {
     pub const NODE_KIND: &'static str = "alias";
 
     #[must_use]
-    pub fn new(all_files: &Vec<PathBuf>, filename_to_alias: &ReplacePair<Filename, Alias>) -> Self {
+    pub fn new(
+        all_files: &[PathBuf],
+        filename_to_alias: &ReplacePair<Filename, Alias>,
+        files: Arc<dyn FileProvider>,
+        sources: Rc<RefCell<SourceBudget>>,
+    ) -> Self {
         // First collect the files in the directories as aliases
         let mut alias_table = HashMap::new();
+        let mut duplicate_alias_errors = Vec::new();
+        let mut duplicate_aliases = HashSet::new();
+        let mut definitions = Vec::new();
         for file in all_files {
             let filename = get_filename(file.as_path());
             let alias = Alias::from_filename(&filename, filename_to_alias);
             if alias.is_empty() {
                 continue;
             }
-            alias_table.insert(alias, file.clone());
+            definitions.push((alias.clone(), file.clone(), AliasSource::Filename));
+            // Like aliases in the content, the last file wins
+            if let Some(out) = alias_table.insert(alias.clone(), file.clone()) {
+                duplicate_alias_errors.push(DuplicateAlias::from_filenames(&alias, file, &out));
+                duplicate_aliases.insert(alias);
+            }
         }
         Self {
             alias_table,
-            duplicate_alias_errors: Vec::new(),
-            duplicate_aliases: HashSet::new(),
+            duplicate_alias_errors,
+            duplicate_aliases,
+            no_autolink: HashSet::new(),
+            definitions,
             front_matter_visitor: FrontMatterVisitor::new(),
             filename_to_alias: filename_to_alias.clone(),
+            files,
+            sources,
         }
     }
 }
@@ -135,29 +414,36 @@ impl Visitor for DuplicateAliasVisitor {
     fn name(&self) -> &'static str {
         "DuplicateAliasVisitor"
     }
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError> {
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
         self.front_matter_visitor.visit(node, source)?;
         Ok(())
     }
     fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+        if self.front_matter_visitor.front_matter.no_autolink {
+            self.no_autolink.insert(path.to_path_buf());
+        }
         // We can "take" the aliases from the front_matter_visitor since we are going to clear them
-        let aliases = std::mem::take(&mut self.front_matter_visitor.aliases);
+        let aliases = std::mem::take(&mut self.front_matter_visitor.front_matter.aliases);
         for alias in aliases {
+            self.definitions
+                .push((alias.clone(), path.to_path_buf(), AliasSource::FrontMatter));
             // This inserts the alias into the table and returns the previous value if it existed
             // If it did exist, we have a duplicate
             // If it did not exist, we have a new alias in our table
             if let Some(out) = self.alias_table.insert(alias.clone(), path.into()) {
                 self.duplicate_aliases.insert(alias.clone());
+                let other_source = self.files.read_source(&out).ok();
                 let found = DuplicateAlias::new(
                     &alias,
                     path,
                     Some(source),
                     &out,
-                    None,
+                    other_source.as_deref(),
                     &self.filename_to_alias,
                 )?;
                 if let Some(found) = found {
-                    self.duplicate_alias_errors.push(found);
+                    self.duplicate_alias_errors
+                        .push(self.sources.borrow_mut().keep(found));
                 }
             }
         }
@@ -166,19 +452,21 @@ impl Visitor for DuplicateAliasVisitor {
         self.front_matter_visitor.finalize_file(source, path)?;
         Ok(())
     }
-    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Vec<Report>, FinalizeError> {
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
         // We can "take" the duplicate from the front_matter_visitor since we are going to put them
         // right back in after some cleaning
-        self.duplicate_alias_errors = dedupe_by_code(filter_by_excludes(
-            std::mem::take(&mut self.duplicate_alias_errors),
-            excludes,
-        ));
+        let (duplicate_alias_errors, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.duplicate_alias_errors), excludes);
+        self.duplicate_alias_errors = dedupe_by_code(duplicate_alias_errors);
         self.front_matter_visitor.finalize(excludes)?;
-        Ok(self
-            .duplicate_alias_errors
-            .iter()
-            .map(|x| Report::DuplicateAlias(x.clone()))
-            .collect())
+        Ok(Finalized {
+            reports: self
+                .duplicate_alias_errors
+                .iter()
+                .map(|x| Report::DuplicateAlias(x.clone()))
+                .collect(),
+            matched_excludes,
+        })
     }
 }
 
@@ -191,6 +479,28 @@ pub enum NewDuplicateAliasError {
 }
 
 impl DuplicateAlias {
+    /// Create a new diagnostic for two files which are both named after `alias`
+    #[must_use]
+    pub fn from_filenames(alias: &Alias, file1_path: &Path, file2_path: &Path) -> Self {
+        // Sorted, so the report is about the same file whichever is found first
+        let (file1_path, file2_path) = if file1_path <= file2_path {
+            (file1_path, file2_path)
+        } else {
+            (file2_path, file1_path)
+        };
+        let id = ErrorCode::in_file(CODE, &get_filename(file1_path).lowercase(), &[alias]).0;
+        let AliasFilename { src, span, .. } = AliasFilename::new(file1_path);
+        DuplicateAlias::FileNameFileNameDuplicate {
+            advice: format!("Rename one of the files, or move it into the other\nid: {id:?}"),
+            id: id.into(),
+            fingerprint: Fingerprint::new(CODE, &[&alias.to_string()]),
+            other_filename: get_filename(file2_path),
+            src,
+            filename: span,
+            other: vec![AliasFilename::new(file2_path)],
+        }
+    }
+
     /// Create a new diagnostic
     /// based on the two filenames and their similar ngrams
     ///
@@ -211,40 +521,28 @@ impl DuplicateAlias {
             return Ok(None);
         }
 
-        // Create the unique id
-        let id = format!("{CODE}::{alias}");
+        let fingerprint = Fingerprint::new(CODE, &[&alias.to_string()]);
 
         let file1_content = match file1_content {
-            None => &std::fs::read_to_string(file1_path).expect("File reported as existing"),
+            None => &read_source(file1_path).expect("File reported as existing"),
             Some(content) => content,
         };
         let file2_content = match file2_content {
-            None => &std::fs::read_to_string(file2_path).expect("File reported as existing"),
+            None => &read_source(file2_path).expect("File reported as existing"),
             Some(content) => content,
         };
 
         if Alias::from_filename(&get_filename(file1_path), filename_to_alias) == *alias {
-            // Find the alias
-            let file2_content_found = file2_content
-                .to_lowercase()
-                .find(&alias.to_string())
-                .ok_or_else(|| CalculateError::MissingSubstringError {
-                    path: file2_path.to_path_buf(),
-                    ngram: alias.to_string(),
-                    backtrace: std::backtrace::Backtrace::capture(),
-                })?;
-
-            // Generate the spans relative to the NamedSource
-            let file2_content_span = SourceSpan::new(
-                SourceOffset::from(file2_content_found),
-                alias.to_string().len(),
-            );
-
+            let id = ErrorCode::in_file(CODE, &get_filename(file2_path).lowercase(), &[alias]).0;
+            let file2_content_span = find_alias(alias, file2_path, file2_content);
             Ok(Some(DuplicateAlias::FileNameContentDuplicate {
                 id: id.into(),
+                fingerprint,
                 other_filename: get_filename(file1_path),
                 src: NamedSource::new(file2_path.to_string_lossy(), file2_content.to_string()),
+                line_column: None,
                 alias: file2_content_span,
+                other: vec![AliasFilename::new(file1_path)],
                 advice: format!("Delete the alias from {}", file2_path.to_string_lossy()),
             }))
         } else if Alias::from_filename(&get_filename(file2_path), filename_to_alias) == *alias {
@@ -256,46 +554,36 @@ impl DuplicateAlias {
                 Some(file1_content),
                 filename_to_alias,
             )
+        } else if file1_path > file2_path {
+            // Sorted, so the report is about the same file whichever is found first
+            Self::new(
+                alias,
+                file2_path,
+                Some(file2_content),
+                file1_path,
+                Some(file1_content),
+                filename_to_alias,
+            )
         } else {
-            // Find the alias
-            let file1_content_found = file1_content
-                .to_lowercase()
-                .find(&alias.to_string())
-                .ok_or_else(|| CalculateError::MissingSubstringError {
-                    path: file1_path.to_path_buf(),
-                    ngram: alias.to_string(),
-                    backtrace: std::backtrace::Backtrace::capture(),
-                })?;
-            let file2_content_found = file2_content
-                .to_lowercase()
-                .find(&alias.to_string())
-                .ok_or_else(|| CalculateError::MissingSubstringError {
-                    path: file2_path.to_path_buf(),
-                    ngram: alias.to_string(),
-                    backtrace: std::backtrace::Backtrace::capture(),
-                })?;
-
-            // Generate the spans relative to the NamedSource
-            let file1_content_span = SourceSpan::new(
-                SourceOffset::from(file1_content_found),
-                alias.to_string().len(),
-            );
-            let file2_content_span = SourceSpan::new(
-                SourceOffset::from(file2_content_found),
-                alias.to_string().len(),
-            );
+            let id = ErrorCode::in_file(CODE, &get_filename(file1_path).lowercase(), &[alias]).0;
+            let file1_content_span = find_alias(alias, file1_path, file1_content);
+            let file2_content_span = find_alias(alias, file2_path, file2_content);
 
             Ok(Some(DuplicateAlias::FileContentContentDuplicate {
                 advice: format!("id: {id:?}"),
                 id: id.clone().into(),
+                fingerprint: fingerprint.clone(),
                 other_filename: get_filename(file2_path),
                 src: NamedSource::new(file1_path.to_string_lossy(), file1_content.to_string()),
+                line_column: None,
                 alias: file1_content_span,
                 other: vec![DuplicateAlias::FileContentContentDuplicate {
                     advice: format!("id: {id:?}"),
                     id: id.into(),
+                    fingerprint,
                     other_filename: get_filename(file1_path),
                     src: NamedSource::new(file2_path.to_string_lossy(), file2_content.to_string()),
+                    line_column: None,
                     alias: file2_content_span,
                     other: vec![],
                 }],
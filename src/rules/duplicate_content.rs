@@ -0,0 +1,231 @@
+//! Files with the same content, after their front matter, like the copies sync conflicts make
+//!
+//! Files which are empty after their front matter, like the pages
+//! [`crate::rules::broken_wikilink::BrokenWikilink`] fixes make, are never duplicates
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{content::source_map::SourceMap, name::get_filename},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use hashbrown::HashMap;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::body::duplicate";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("Files have the same content")]
+#[diagnostic(code("content::body::duplicate"), severity(Warning))]
+pub struct DuplicateContent {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The other files with the same content, sorted
+    others: Vec<PathBuf>,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    /// The content after the front matter
+    #[label("Also the content of {} other files", others.len())]
+    pub body: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for DuplicateContent {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// The files may be linked to by different names, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    /// The first of the files to be parsed
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.body)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.body)))
+    }
+}
+
+impl DuplicateContent {
+    /// See [`DuplicateContent::others`]
+    #[must_use]
+    pub fn others(&self) -> &[PathBuf] {
+        &self.others
+    }
+}
+
+impl KeepsSource for DuplicateContent {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for DuplicateContent {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for DuplicateContent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// The first file with some content, and the others with the same
+#[derive(Debug)]
+struct SameContent {
+    src: NamedSource<String>,
+    body: SourceSpan,
+    others: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+pub struct DuplicateContentVisitor {
+    pub duplicate_contents: Vec<DuplicateContent>,
+    /// Where the content starts in the current file, after the front matter
+    body_start: usize,
+    /// Keyed by the fingerprint of the content
+    contents: HashMap<Fingerprint, SameContent>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl DuplicateContentVisitor {
+    #[must_use]
+    pub fn new(sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            ..Self::default()
+        }
+    }
+}
+
+impl Visitor for DuplicateContentVisitor {
+    fn name(&self) -> &'static str {
+        "DuplicateContentVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        if let NodeValue::FrontMatter(text) = &data_ref.value {
+            let sourcepos = data_ref.sourcepos;
+            self.body_start = source.start(sourcepos) + text.len();
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let start = std::mem::take(&mut self.body_start).min(source.len());
+        let body = source[start..].trim();
+        if body.is_empty() {
+            return Ok(());
+        }
+        let offset = start + (source[start..].len() - source[start..].trim_start().len());
+        self.contents
+            .entry(Fingerprint::new(CODE, &[body]))
+            .and_modify(|same| same.others.push(path.to_path_buf()))
+            .or_insert_with(|| SameContent {
+                src: NamedSource::new(path.to_string_lossy(), source.to_string()),
+                body: SourceSpan::new(offset.into(), body.len()),
+                others: Vec::new(),
+            });
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        for (fingerprint, mut same) in std::mem::take(&mut self.contents) {
+            if same.others.is_empty() {
+                continue;
+            }
+            same.others.sort();
+            let filename = get_filename(Path::new(same.src.name())).lowercase();
+            let others: Vec<_> = same
+                .others
+                .iter()
+                .map(|other| get_filename(other).lowercase())
+                .collect();
+            let detail: Vec<&dyn std::fmt::Display> = others
+                .iter()
+                .map(|other| other as &dyn std::fmt::Display)
+                .collect();
+            let id = ErrorCode::in_file(CODE, &filename, &detail).0;
+            let listed = same
+                .others
+                .iter()
+                .map(|other| format!("  {}", other.display()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let report = DuplicateContent::builder()
+                .advice(format!(
+                    "Merge these files into this one, and link to it instead:\n{listed}\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(fingerprint)
+                .src(same.src)
+                .body(same.body)
+                .others(same.others)
+                .build();
+            self.duplicate_contents
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        // We can "take" this because we are putting it right back
+        let (duplicate_contents, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.duplicate_contents), excludes);
+        self.duplicate_contents = dedupe_by_code(duplicate_contents);
+        Ok(Finalized {
+            reports: self
+                .duplicate_contents
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::DuplicateContent(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -0,0 +1,205 @@
+//! Keys which are written more than once in the front matter of a file, like two `alias:` lines
+//!
+//! Logseq silently uses the first, and so does [`crate::file::content::front_matter::FrontMatterVisitor`]
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{front_matter::top_level_keys, source_map::SourceMap},
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::property::duplicate";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A front matter key is written more than once")]
+#[diagnostic(code("content::property::duplicate"))]
+pub struct DuplicateProperty {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The key, as written
+    key: String,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("First used here")]
+    pub first: SourceSpan,
+
+    #[label("Ignored")]
+    pub duplicate: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for DuplicateProperty {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// There is no telling which value was meant, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.duplicate)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.duplicate)))
+    }
+}
+
+impl DuplicateProperty {
+    /// The key, as written
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+}
+
+impl KeepsSource for DuplicateProperty {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for DuplicateProperty {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for DuplicateProperty {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DuplicatePropertyVisitor {
+    pub duplicate_properties: Vec<DuplicateProperty>,
+    /// The spans of the first and second use of a key, in the current file
+    duplicates: Vec<(String, SourceSpan, SourceSpan)>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl DuplicatePropertyVisitor {
+    #[must_use]
+    pub fn new(sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            ..Self::default()
+        }
+    }
+}
+
+impl Visitor for DuplicatePropertyVisitor {
+    fn name(&self) -> &'static str {
+        "DuplicatePropertyVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let NodeValue::FrontMatter(text) = &data_ref.value else {
+            return Ok(());
+        };
+        let sourcepos = data_ref.sourcepos;
+        // Skip the opening --- delimiter
+        let start = text.find('\n').map_or(text.len(), |i| i + 1);
+        let offset = source.start(sourcepos) + start;
+        let keys = top_level_keys(&text[start..]);
+        for (i, (key, range)) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|(other, _)| other == key) {
+                continue;
+            }
+            let first = SourceSpan::new((offset + range.start).into(), key.len());
+            if let Some((_, duplicate)) = keys[i + 1..].iter().find(|(other, _)| other == key) {
+                let duplicate = SourceSpan::new((offset + duplicate.start).into(), key.len());
+                self.duplicates.push(((*key).to_owned(), first, duplicate));
+            }
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path).lowercase();
+        for (key, first, duplicate) in std::mem::take(&mut self.duplicates) {
+            let id = ErrorCode::in_file(CODE, &filename, &[&key]).0;
+            let report = DuplicateProperty::builder()
+                .advice(format!(
+                    "Only the first '{key}' is used, merge the values into it and remove the others.\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&key]))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .first(first)
+                .duplicate(duplicate)
+                .key(key)
+                .build();
+            self.duplicate_properties
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (duplicate_properties, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.duplicate_properties), excludes);
+        self.duplicate_properties = dedupe_by_code(duplicate_properties);
+        Ok(Finalized {
+            reports: self
+                .duplicate_properties
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::DuplicateProperty(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -0,0 +1,165 @@
+//! Entries of [`crate::config::file::Config::exclude`] whose `expires` day has passed, so
+//! suppressions don't live forever
+//!
+//! The exclude still excludes, this only reminds you to fix what it excludes, or to push the
+//! day back
+use std::{borrow::Cow, path::PathBuf};
+
+use crate::{
+    budget::SourceBudget,
+    config::{Config, Date, ExcludeEntry},
+    file::name::FilenameLowercase,
+};
+use bon::Builder;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{elided, span_line_column, ErrorCode, Fingerprint, FixError, KeepsSource, ReportTrait};
+
+pub const CODE: &str = "config::exclude::expired";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("An exclude expired on {expires}")]
+#[diagnostic(code("config::exclude::expired"), severity(Warning))]
+pub struct ExpiredExclude {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The pattern of the exclude
+    code: String,
+
+    expires: Date,
+
+    /// The config file
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    /// The pattern in the config file, if it is there as written
+    #[label("Expired exclude")]
+    exclude: Option<SourceSpan>,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for ExpiredExclude {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Whether to fix what is excluded or keep excluding it is up to you, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        self.exclude
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column.or_else(|| {
+            self.exclude
+                .map(|exclude| span_line_column(&self.src, exclude))
+        })
+    }
+}
+
+impl ExpiredExclude {
+    /// One report for each exclude of the config file which expired before `today`, given to
+    /// `sources` as it is made
+    #[must_use]
+    pub fn calculate(config: &Config, today: Date, sources: &mut SourceBudget) -> Vec<Self> {
+        let path = config.config_path();
+        // Configs made in code, like by the linter builder, have no file
+        let source = std::fs::read_to_string(path).unwrap_or_default();
+        let filename = FilenameLowercase::new(
+            &path
+                .file_stem()
+                .map_or(Cow::Borrowed("mdlinker"), |stem| stem.to_string_lossy()),
+        );
+        config
+            .expired_excludes(today)
+            .filter_map(|entry| {
+                let expires = entry.expires()?;
+                let code = entry.code();
+                let quoted = format!("{code:?}");
+                let exclude = source
+                    .find(&quoted)
+                    .map(|offset| SourceSpan::new(offset.into(), quoted.len()));
+                Some(
+                    sources.keep(
+                        Self::builder()
+                            .id(ErrorCode::in_file(CODE, &filename, &[&code]))
+                            .fingerprint(Fingerprint::new(CODE, &[code]))
+                            .code(code.to_owned())
+                            .expires(expires)
+                            .src(NamedSource::new(path.to_string_lossy(), source.clone()))
+                            .maybe_exclude(exclude)
+                            .advice(advice(entry))
+                            .build(),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// See [`ExpiredExclude::code`]
+    #[must_use]
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// See [`ExpiredExclude::expires`]
+    #[must_use]
+    pub fn expires(&self) -> Date {
+        self.expires
+    }
+}
+
+/// Says what the exclude was for, if it says
+fn advice(entry: &ExcludeEntry) -> String {
+    let advice = format!(
+        "Fix what {:?} excludes and remove it, or push back the day it expires",
+        entry.code()
+    );
+    match entry.reason() {
+        Some(reason) => format!("{advice}\nIt was excluded because: {reason}"),
+        None => advice,
+    }
+}
+
+impl KeepsSource for ExpiredExclude {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for ExpiredExclude {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for ExpiredExclude {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
@@ -0,0 +1,319 @@
+//! Front matter keys which mean the same as a key other files use, like `tag` when most files
+//! use `tags`, see [`Config::key_synonyms`]
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    cmp::Reverse,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::{Config, KeySynonyms},
+    file::{
+        content::{
+            front_matter::{raw_front_matter, top_level_keys},
+            notebook::is_notebook,
+            source_map::SourceMap,
+        },
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeValue},
+};
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, TextEdit, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::property::inconsistent";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A front matter key is not the one used for it elsewhere")]
+#[diagnostic(code("content::property::inconsistent"), severity(Warning))]
+pub struct FrontMatterKey {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The key, as written
+    key: String,
+
+    /// See [`KeySynonyms::canonical`]
+    canonical: String,
+
+    /// How many files use each of the synonyms, most first
+    counts: Vec<(String, usize)>,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Use '{canonical}'")]
+    pub span: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for FrontMatterKey {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Renames the key, unless the file also has the canonical key, which would then be repeated
+    /// The key is found again, as other fixes in the file may have moved it
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        let file = self.src.name().to_owned();
+        if self.edits().is_none() {
+            return Ok(None);
+        }
+        let mut source = std::fs::read_to_string(&file).map_err(|src| FixError::IOError {
+            source: src,
+            file: file.clone(),
+            backtrace: Backtrace::force_capture(),
+        })?;
+        let Some((start, yaml)) = raw_front_matter(&source) else {
+            return Ok(None);
+        };
+        let keys = top_level_keys(yaml);
+        if keys.iter().any(|(key, _)| *key == self.canonical) {
+            return Ok(None);
+        }
+        let Some((_, range)) = keys.iter().find(|(key, _)| *key == self.key) else {
+            return Ok(None);
+        };
+        let offset = start + range.start;
+        source.replace_range(offset..offset + self.key.len(), &self.canonical);
+        std::fs::write(&file, source).map_err(|source| FixError::IOError {
+            source,
+            file,
+            backtrace: Backtrace::force_capture(),
+        })?;
+        Ok(Some(()))
+    }
+    fn edits(&self) -> Option<PathBuf> {
+        let path = PathBuf::from(self.src.name());
+        (!is_notebook(&path)).then_some(path)
+    }
+    fn quick_fix(&self, _config: &Config) -> Vec<TextEdit> {
+        let Some(path) = self.edits() else {
+            return vec![];
+        };
+        // Like the fix, the key is not renamed if the canonical one is already there
+        let repeated = raw_front_matter(self.src.inner()).is_some_and(|(_, yaml)| {
+            top_level_keys(yaml)
+                .iter()
+                .any(|(key, _)| *key == self.canonical)
+        });
+        if repeated {
+            return vec![];
+        }
+        vec![TextEdit::new(path, self.span, &self.canonical)]
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl FrontMatterKey {
+    /// See [`FrontMatterKey::key`]
+    #[must_use]
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// See [`FrontMatterKey::canonical`]
+    #[must_use]
+    pub fn canonical(&self) -> &str {
+        &self.canonical
+    }
+
+    /// See [`FrontMatterKey::counts`]
+    #[must_use]
+    pub fn counts(&self) -> &[(String, usize)] {
+        &self.counts
+    }
+}
+
+impl KeepsSource for FrontMatterKey {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for FrontMatterKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for FrontMatterKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// A key of one of the [`KeySynonyms`] in a file
+#[derive(Debug)]
+struct Used {
+    key: String,
+    src: NamedSource<String>,
+    span: SourceSpan,
+}
+
+#[derive(Debug)]
+pub struct FrontMatterKeyVisitor {
+    pub front_matter_keys: Vec<FrontMatterKey>,
+    /// See [`Config::key_synonyms`]
+    key_synonyms: Vec<KeySynonyms>,
+    /// The keys of each of [`FrontMatterKeyVisitor::key_synonyms`] used in the current file
+    keys: Vec<(usize, String, SourceSpan)>,
+    /// Where the keys of each of [`FrontMatterKeyVisitor::key_synonyms`] are used
+    used: Vec<Vec<Used>>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl FrontMatterKeyVisitor {
+    #[must_use]
+    pub fn new(config: &Config, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            front_matter_keys: Vec::new(),
+            key_synonyms: config.key_synonyms.clone(),
+            keys: Vec::new(),
+            used: config.key_synonyms.iter().map(|_| Vec::new()).collect(),
+        }
+    }
+}
+
+impl Visitor for FrontMatterKeyVisitor {
+    fn name(&self) -> &'static str {
+        "FrontMatterKeyVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let NodeValue::FrontMatter(text) = &data_ref.value else {
+            return Ok(());
+        };
+        let sourcepos = data_ref.sourcepos;
+        // Skip the opening --- delimiter
+        let start = text.find('\n').map_or(text.len(), |i| i + 1);
+        let offset = source.start(sourcepos) + start;
+        for (key, range) in top_level_keys(&text[start..]) {
+            if let Some(group) = self
+                .key_synonyms
+                .iter()
+                .position(|synonyms| synonyms.keys.iter().any(|synonym| synonym == key))
+            {
+                let span = SourceSpan::new((offset + range.start).into(), key.len());
+                self.keys.push((group, key.to_owned(), span));
+            }
+        }
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let src = NamedSource::new(path.to_string_lossy(), source.to_string());
+        for (group, key, span) in std::mem::take(&mut self.keys) {
+            self.used[group].push(Used {
+                key,
+                src: src.clone(),
+                span,
+            });
+        }
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        for (synonyms, used) in self.key_synonyms.iter().zip(std::mem::take(&mut self.used)) {
+            let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for u in &used {
+                *counts.entry(u.key.as_str()).or_default() += 1;
+            }
+            let mut counts: Vec<(String, usize)> = counts
+                .into_iter()
+                .map(|(key, count)| (key.to_owned(), count))
+                .collect();
+            counts.sort_by_key(|(_, count)| Reverse(*count));
+            let canonical = match (&synonyms.canonical, counts.as_slice()) {
+                (Some(canonical), _) => canonical.clone(),
+                // There is no usual key when the two most used are as common
+                (None, [(_, most), (_, second), ..]) if most == second => continue,
+                (None, [(key, _), _, ..]) => key.clone(),
+                (None, _) => continue,
+            };
+            let listed = counts
+                .iter()
+                .map(|(key, count)| format!("  '{key}': {count}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            for u in used.into_iter().filter(|u| u.key != canonical) {
+                let filename = get_filename(Path::new(u.src.name())).lowercase();
+                let id = ErrorCode::in_file(CODE, &filename, &[&u.key]).0;
+                let report = FrontMatterKey::builder()
+                    .advice(format!(
+                        "Rename '{}' to '{canonical}', like the other files:\n{listed}\nid: {id:?}",
+                        u.key
+                    ))
+                    .id(id.into())
+                    .fingerprint(Fingerprint::new(CODE, &[&u.key, &canonical]))
+                    .src(u.src)
+                    .span(u.span)
+                    .key(u.key)
+                    .canonical(canonical.clone())
+                    .counts(counts.clone())
+                    .build();
+                self.front_matter_keys
+                    .push(self.sources.borrow_mut().keep(report));
+            }
+        }
+        // We can "take" this because we are putting it right back
+        let (front_matter_keys, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.front_matter_keys), excludes);
+        self.front_matter_keys = dedupe_by_code(front_matter_keys);
+        Ok(Finalized {
+            reports: self
+                .front_matter_keys
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::FrontMatterKey(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
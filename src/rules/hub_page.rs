@@ -0,0 +1,201 @@
+//! Pages with more wikilinks to other pages than [`Config::max_outbound_links`], which are
+//! often index pages that should be split, or turned into a namespace
+//!
+//! Tags are not counted, and neither are wikilinks to the same page more than once
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{source_map::SourceMap, wikilink::WikilinkVisitor},
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashSet;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::page::hub";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A page links to too many other pages")]
+#[diagnostic(code("content::page::hub"), severity(Warning))]
+pub struct HubPage {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The number of pages linked to
+    nb_links: usize,
+
+    /// See [`Config::max_outbound_links`]
+    max_links: usize,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    /// The first wikilink over the maximum
+    #[label("Link {} of {nb_links}", max_links + 1)]
+    pub wikilink: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for HubPage {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Splitting a page takes judgement, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.wikilink)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.wikilink)))
+    }
+}
+
+impl HubPage {
+    /// See [`HubPage::nb_links`]
+    #[must_use]
+    pub fn nb_links(&self) -> usize {
+        self.nb_links
+    }
+}
+
+impl KeepsSource for HubPage {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for HubPage {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for HubPage {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct HubPageVisitor {
+    pub hub_pages: Vec<HubPage>,
+    wikilinks_visitor: WikilinkVisitor,
+    /// See [`Config::max_outbound_links`]
+    max_links: usize,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl HubPageVisitor {
+    #[must_use]
+    pub fn new(config: &Config, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            hub_pages: Vec::new(),
+            wikilinks_visitor: WikilinkVisitor::new(config.skip_html),
+            max_links: config.max_outbound_links.unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl Visitor for HubPageVisitor {
+    fn name(&self) -> &'static str {
+        "HubPageVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let mut wikilinks = std::mem::take(&mut self.wikilinks_visitor.wikilinks);
+        wikilinks.sort_by_key(|wikilink| wikilink.span.offset());
+        let mut aliases = HashSet::new();
+        // The first wikilink to each page
+        let links: Vec<SourceSpan> = wikilinks
+            .into_iter()
+            .filter(|wikilink| !wikilink.tag && aliases.insert(wikilink.alias.clone()))
+            .map(|wikilink| wikilink.span)
+            .collect();
+        if let Some(wikilink) = links.get(self.max_links) {
+            let filename = get_filename(path).lowercase();
+            let id = ErrorCode::in_file(CODE, &filename, &[]).0;
+            let nb_links = links.len();
+            let report = HubPage::builder()
+                .advice(format!(
+                    "This page links to {nb_links} pages, at most {} are allowed. Split it into smaller pages, or move the pages under it in a namespace.\nid: {id:?}",
+                    self.max_links
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&filename.to_string()]))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .wikilink(*wikilink)
+                .nb_links(nb_links)
+                .max_links(self.max_links)
+                .build();
+            self.hub_pages.push(self.sources.borrow_mut().keep(report));
+        }
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (hub_pages, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.hub_pages), excludes);
+        self.hub_pages = dedupe_by_code(hub_pages);
+        Ok(Finalized {
+            reports: self
+                .hub_pages
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::HubPage(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
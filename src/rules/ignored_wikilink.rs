@@ -0,0 +1,214 @@
+//! Wikilinks to files in [`Config::ignore_paths`], like a template or an archived page,
+//! which are often unintended
+//!
+//! Files in [`Config::allow_links_to_ignored`] may be linked to
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{
+            source_map::SourceMap,
+            wikilink::{Alias, WikilinkVisitor},
+        },
+        name::get_filename,
+    },
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashMap;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::wikilink::ignored";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A wikilink points to a file which is not linted")]
+#[diagnostic(code("content::wikilink::ignored"), severity(Warning))]
+pub struct IgnoredWikilink {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    alias: Alias,
+
+    /// The ignored file the wikilink points to
+    target: PathBuf,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Points to '{}'", target.display())]
+    pub wikilink: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for IgnoredWikilink {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// The link may be on purpose, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.wikilink)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.wikilink)))
+    }
+}
+
+impl IgnoredWikilink {
+    /// The alias in the wikilink, as written
+    #[must_use]
+    pub fn alias(&self) -> &Alias {
+        &self.alias
+    }
+
+    /// See [`IgnoredWikilink::target`]
+    #[must_use]
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+}
+
+impl KeepsSource for IgnoredWikilink {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for IgnoredWikilink {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for IgnoredWikilink {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct IgnoredWikilinkVisitor {
+    pub ignored_wikilinks: Vec<IgnoredWikilink>,
+    wikilinks_visitor: WikilinkVisitor,
+    /// The aliases which resolve to files which may not be linked to, see [`Config::may_link_to`]
+    ignored: HashMap<Alias, PathBuf>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl IgnoredWikilinkVisitor {
+    #[must_use]
+    pub fn new(
+        alias_table: &HashMap<Alias, PathBuf>,
+        config: &Config,
+        sources: Rc<RefCell<SourceBudget>>,
+    ) -> Self {
+        Self {
+            sources,
+            ignored_wikilinks: Vec::new(),
+            wikilinks_visitor: WikilinkVisitor::new(config.skip_html),
+            ignored: alias_table
+                .iter()
+                .filter(|(_, path)| !config.may_link_to(path))
+                .map(|(alias, path)| (alias.clone(), path.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Visitor for IgnoredWikilinkVisitor {
+    fn name(&self) -> &'static str {
+        "IgnoredWikilinkVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path).lowercase();
+        for wikilink in std::mem::take(&mut self.wikilinks_visitor.wikilinks) {
+            let Some(target) = self.ignored.get(&wikilink.alias) else {
+                continue;
+            };
+            let alias = wikilink.alias;
+            let id = ErrorCode::in_file(CODE, &filename, &[&alias]).0;
+            let report = IgnoredWikilink::builder()
+                .advice(format!(
+                    "'{}' is in ignore_paths, link to another page, or add it to allow_links_to_ignored.\nid: {id:?}",
+                    target.display()
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(
+                    CODE,
+                    &[&alias.to_string(), &line_context(source, wikilink.span.offset())],
+                ))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .wikilink(wikilink.span)
+                .target(target.clone())
+                .alias(alias)
+                .build();
+            self.ignored_wikilinks
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (ignored_wikilinks, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.ignored_wikilinks), excludes);
+        self.ignored_wikilinks = dedupe_by_code(ignored_wikilinks);
+        Ok(Finalized {
+            reports: self
+                .ignored_wikilinks
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::IgnoredWikilink(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
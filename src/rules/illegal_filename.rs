@@ -0,0 +1,280 @@
+//! Filenames with characters which break on some of [`Config::filename_platforms`], like `:` on
+//! Windows and macOS, or `#` in a url, and names Windows reserves, like `CON`
+//! `--fix` renames them like `mdlinker mv` moves directories, so links to them still resolve
+#[cfg(feature = "fs")]
+use std::backtrace::Backtrace;
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "fs")]
+use crate::mv::{rename, MoveError};
+use crate::{
+    config::{Config, Platform},
+    file::{content::source_map::SourceMap, name::get_filename},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use miette::{Diagnostic, LabeledSpan, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, filter_by_excludes, ErrorCode, Fingerprint, FixError, Report, ReportTrait,
+    ThirdPassReport,
+};
+
+pub const CODE: &str = "name::illegal";
+
+/// Names Windows reserves for devices, whatever their extension
+const WINDOWS_RESERVED: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// The platforms `c` is not allowed on, out of `platforms`
+fn forbidden_on(c: char, platforms: &[Platform]) -> Vec<Platform> {
+    platforms
+        .iter()
+        .copied()
+        .filter(|platform| match platform {
+            Platform::Windows => "<>:\"/\\|?*".contains(c) || c.is_control(),
+            Platform::Macos => c == ':',
+            Platform::Url => c == '?' || c == '#',
+        })
+        .collect()
+}
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A filename does not work on {}", platforms.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+#[diagnostic(code("name::illegal"))]
+pub struct IllegalFilename {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The filename, as written
+    filename: String,
+
+    /// The platforms the filename does not work on, sorted
+    platforms: Vec<Platform>,
+
+    /// The filename with the characters replaced by `-`, trailing spaces and dots removed,
+    /// and `_` after reserved names
+    suggestion: String,
+
+    /// The path of the file, with the filename labeled
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Each character, or the whole filename if it is reserved
+    #[label(collection)]
+    characters: Vec<LabeledSpan>,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for IllegalFilename {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Renames the file to [`IllegalFilename::suggestion`], rewriting the links to it and the
+    /// wikilinks to the alias of its name, see [`rename`]
+    /// Not fixed if a file already has that name
+    #[cfg(feature = "fs")]
+    fn fix(&self, config: &Config) -> Result<Option<()>, FixError> {
+        let path = self.path();
+        let mut filename = self.suggestion.clone();
+        if let Some(extension) = path.extension() {
+            filename.push('.');
+            filename.push_str(&extension.to_string_lossy());
+        }
+        let io_error = |source| FixError::IOError {
+            source,
+            backtrace: Backtrace::force_capture(),
+            file: path.to_string_lossy().to_string(),
+        };
+        match rename(config, &path, &path.with_file_name(filename)) {
+            Ok(_) => Ok(Some(())),
+            Err(MoveError::AlreadyExists(_)) => Ok(None),
+            Err(MoveError::FixError(error)) => Err(error),
+            Err(MoveError::IoError { source, .. }) => Err(io_error(source)),
+            Err(error) => Err(io_error(std::io::Error::other(error.to_string()))),
+        }
+    }
+    /// Not fixed without the `fs` feature, which finding the links to the file needs
+    #[cfg(not(feature = "fs"))]
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        None
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+impl IllegalFilename {
+    /// See [`IllegalFilename::filename`]
+    #[must_use]
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// See [`IllegalFilename::platforms`]
+    #[must_use]
+    pub fn platforms(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    /// See [`IllegalFilename::suggestion`]
+    #[must_use]
+    pub fn suggestion(&self) -> &str {
+        &self.suggestion
+    }
+}
+
+impl PartialEq for IllegalFilename {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for IllegalFilename {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct IllegalFilenameVisitor {
+    pub illegal_filenames: Vec<IllegalFilename>,
+    /// See [`Config::filename_platforms`]
+    platforms: Vec<Platform>,
+}
+
+impl IllegalFilenameVisitor {
+    #[must_use]
+    pub fn new(config: &Config) -> Self {
+        Self {
+            illegal_filenames: Vec::new(),
+            platforms: config.filename_platforms.clone(),
+        }
+    }
+}
+
+impl Visitor for IllegalFilenameVisitor {
+    fn name(&self) -> &'static str {
+        "IllegalFilenameVisitor"
+    }
+    fn _visit(
+        &mut self,
+        _node: &Node<RefCell<Ast>>,
+        _source: &SourceMap,
+    ) -> Result<(), VisitError> {
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        _source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path).to_string();
+        let source = path.to_string_lossy().to_string();
+        let offset = source.rfind(&filename).unwrap_or_default();
+        let mut platforms: Vec<Platform> = Vec::new();
+        let mut characters = Vec::new();
+        let mut suggestion = String::new();
+        for (i, c) in filename.char_indices() {
+            let forbidden = forbidden_on(c, &self.platforms);
+            if forbidden.is_empty() {
+                suggestion.push(c);
+                continue;
+            }
+            suggestion.push('-');
+            let on = forbidden
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            characters.push(LabeledSpan::new_with_span(
+                Some(format!("Not allowed on {on}")),
+                SourceSpan::new((offset + i).into(), c.len_utf8()),
+            ));
+            platforms.extend(forbidden);
+        }
+        if self.platforms.contains(&Platform::Windows) {
+            let trimmed = filename.trim_end_matches([' ', '.']);
+            if trimmed.len() < filename.len() {
+                characters.push(LabeledSpan::new_with_span(
+                    Some("Trailing spaces and dots are removed on windows".to_owned()),
+                    SourceSpan::new(
+                        (offset + trimmed.len()).into(),
+                        filename.len() - trimmed.len(),
+                    ),
+                ));
+                suggestion.truncate(suggestion.trim_end_matches([' ', '.']).len());
+                platforms.push(Platform::Windows);
+            }
+            let stem = filename.split('.').next().unwrap_or_default();
+            if WINDOWS_RESERVED.contains(&stem.to_lowercase().as_str()) {
+                characters.push(LabeledSpan::new_with_span(
+                    Some("Reserved on windows".to_owned()),
+                    SourceSpan::new(offset.into(), stem.len()),
+                ));
+                suggestion.insert(stem.len(), '_');
+                platforms.push(Platform::Windows);
+            }
+        }
+        if characters.is_empty() {
+            return Ok(());
+        }
+        platforms.sort();
+        platforms.dedup();
+        let id = ErrorCode::in_file(CODE, &get_filename(path).lowercase(), &[]).0;
+        self.illegal_filenames.push(
+            IllegalFilename::builder()
+                .advice(format!(
+                    "Rename the file, and the wikilinks to it, like to '{suggestion}', or remove these platforms from filename_platforms.\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&filename]))
+                .src(NamedSource::new(&source, source.clone()))
+                .characters(characters)
+                .filename(filename)
+                .platforms(platforms)
+                .suggestion(suggestion)
+                .build(),
+        );
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (illegal_filenames, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.illegal_filenames), excludes);
+        self.illegal_filenames = dedupe_by_code(illegal_filenames);
+        Ok(Finalized {
+            reports: self
+                .illegal_filenames
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::IllegalFilename(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
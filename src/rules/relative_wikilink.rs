@@ -0,0 +1,105 @@
+//! Wikilinks which look like relative paths, like `[[./lorem]]` or `[[../lorem]]`
+//!
+//! These are found by [`crate::rules::broken_wikilink::BrokenWikilinkVisitor`] instead of a
+//! [`crate::rules::broken_wikilink::BrokenWikilink`], as they are often written by hand or by
+//! another tool, and are usually broken, but may work where they are rendered
+use std::path::PathBuf;
+
+use crate::{config::Config, file::content::wikilink::Alias};
+use bon::Builder;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{elided, span_line_column, ErrorCode, Fingerprint, FixError, KeepsSource, ReportTrait};
+
+pub const CODE: &str = "content::wikilink::relative";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A wikilink looks like a relative path and does not have a corresponding page")]
+#[diagnostic(code("content::wikilink::relative"), severity(Warning))]
+pub struct RelativeWikilink {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    alias: Alias,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Relative wikilink")]
+    pub wikilink: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for RelativeWikilink {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// There is no telling which page was meant, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.wikilink)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.wikilink)))
+    }
+}
+
+impl RelativeWikilink {
+    /// The alias in the wikilink, as written
+    #[must_use]
+    pub fn alias(&self) -> &Alias {
+        &self.alias
+    }
+}
+
+impl KeepsSource for RelativeWikilink {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for RelativeWikilink {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for RelativeWikilink {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// Whether the alias of a wikilink looks like a relative path, starting with `.` or containing `..`
+#[must_use]
+pub fn looks_relative(alias: &Alias) -> bool {
+    let alias = alias.to_string();
+    alias.starts_with('.') || alias.contains("..")
+}
@@ -1,34 +1,38 @@
 use crate::{
-    config::{file::Config as FileConfig, Config},
-    file::name::get_filename,
+    budget::Budget,
+    config::{file::Config as FileConfig, Config, SimilarityAlgorithm},
+    file::{case::fold, content::source_map::SourceMap, name::get_filename},
     ngrams::{CalculateError, Ngram},
+    progress::{Pass, ProgressSink},
 };
-use console::{style, Emoji};
+use comrak::nodes::LineColumn;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use hashbrown::{HashMap, HashSet};
-use indicatif::ProgressBar;
-use miette::{Diagnostic, SourceOffset, SourceSpan};
+use miette::{Diagnostic, LabeledSpan, SourceSpan};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::backtrace::Backtrace;
-use std::{
-    env,
-    path::{Path, PathBuf},
-};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
+use tracing::warn;
 
-use super::{ErrorCode, FixError, ReportTrait};
+use super::{ErrorCode, Fingerprint, FixError, MatchedExcludes, ReportTrait};
 
 pub const CODE: &str = "name::similar";
 
-static SIMILAR: Emoji<'_, '_> = Emoji("🤝  ", "");
-
-#[derive(Error, Debug, Diagnostic, Clone)]
+#[derive(Error, Debug, Diagnostic, Clone, Serialize, Deserialize)]
 #[error("Filenames are similar")]
 #[diagnostic(code("name::similar"))]
 pub struct SimilarFilename {
     /// Used to identify the diagnostic and exclude it if needed
     id: ErrorCode,
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+    file1_path: PathBuf,
+    file2_path: PathBuf,
     file1_ngram: Ngram,
     file2_ngram: Ngram,
 
@@ -37,11 +41,23 @@ pub struct SimilarFilename {
     #[source_code]
     filepaths: String,
 
+    /// [`None`] if the ngram could not be found in the path, then only the paths are shown
     #[label("This bit here")]
-    file1_ngram_span: SourceSpan,
+    file1_ngram_span: Option<SourceSpan>,
 
+    /// See [`SimilarFilename::file1_ngram_span`]
     #[label("That bit there")]
-    file2_ngram_span: SourceSpan,
+    file2_ngram_span: Option<SourceSpan>,
+
+    /// Other pairs of similar ngrams in the same two files, merged into this report
+    /// by [`SimilarFilename::merge`] so the files are only reported once
+    #[serde(default)]
+    other_ngrams: Vec<(Ngram, Ngram)>,
+
+    /// The spans of [`SimilarFilename::other_ngrams`]
+    #[label(collection)]
+    #[serde(default)]
+    other_ngram_spans: Vec<LabeledSpan>,
 
     #[help]
     advice: String,
@@ -50,6 +66,10 @@ impl ReportTrait for SimilarFilename {
     fn id(&self) -> ErrorCode {
         self.id.clone()
     }
+    /// The two similar ngrams, in order, so it is the same whichever file is first
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
     fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
         Ok(None)
     }
@@ -57,6 +77,25 @@ impl ReportTrait for SimilarFilename {
         config
             .ignore_word_pairs
             .push((self.file1_ngram.to_string(), self.file2_ngram.to_string()));
+        for (file1_ngram, file2_ngram) in &self.other_ngrams {
+            config
+                .ignore_word_pairs
+                .push((file1_ngram.to_string(), file2_ngram.to_string()));
+        }
+    }
+    /// The first of the two files
+    fn path(&self) -> PathBuf {
+        self.file1_path.clone()
+    }
+    fn paths(&self) -> Vec<PathBuf> {
+        vec![self.file1_path.clone(), self.file2_path.clone()]
+    }
+    /// The report is about filenames, there is nothing to point at in the content
+    fn span(&self) -> Option<SourceSpan> {
+        None
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        None
     }
 }
 
@@ -84,41 +123,51 @@ impl SimilarFilename {
         spacing_regex: &Regex,
         score: i64,
     ) -> Result<Self, CalculateError> {
+        // The files are always in the same order, so a pair found either way around is the same report
+        if file1_path > file2_path {
+            return Self::new(
+                file2_path,
+                file2_ngram,
+                file1_path,
+                file1_ngram,
+                spacing_regex,
+                score,
+            );
+        }
+
         // file paths as strings
-        let file1 = file1_path.to_string_lossy().to_lowercase();
-        let file2 = file2_path.to_string_lossy().to_lowercase();
+        let file1 = fold(&file1_path.to_string_lossy());
+        let file2 = fold(&file2_path.to_string_lossy());
 
         // Assemble the source
         let source = format!("{file1}\n{file2}");
         let filepaths = source.clone();
+        let source_map = SourceMap::new(&source);
 
         // Find the ngrams in each filepath
-        let find1 = spacing_regex
-            .replace_all(&file1, " ")
-            .find(&file1_ngram.to_string())
-            .ok_or_else(|| CalculateError::MissingSubstringError {
-                path: file1_path.to_path_buf(),
-                ngram: file1_ngram.to_string(),
-                backtrace: std::backtrace::Backtrace::capture(),
-            })?;
-        let find2 = spacing_regex
-            .replace_all(&file2, " ")
-            .find(&file2_ngram.to_string())
-            .ok_or_else(|| CalculateError::MissingSubstringError {
-                path: file2_path.to_path_buf(),
-                ngram: file2_ngram.to_string(),
-                backtrace: std::backtrace::Backtrace::capture(),
-            })?;
-
-        // Create the spans
-        let file1_ngram_span = SourceSpan::new(
-            SourceOffset::from_location(&source, 1, find1 + 1),
-            file1_ngram.len(),
-        );
-        let file2_ngram_span = SourceSpan::new(
-            SourceOffset::from_location(&source, 2, find2 + 1),
-            file2_ngram.len(),
-        );
+        // The files are still similar if an ngram can't be found, they are just reported without it
+        let find_ngram = |file: &str, path: &Path, ngram: &Ngram, line: usize| {
+            let spaced = spacing_regex.replace_all(file, " ");
+            // Words split by case are not spaced in the filename
+            let found = [ngram.to_string(), ngram.to_vec().concat()]
+                .into_iter()
+                .find_map(|text| spaced.find(&text).map(|column| (column, text.len())));
+            if found.is_none() {
+                warn!(
+                    "Could not find the ngram {ngram:?} in {}, reporting it without a span",
+                    path.display()
+                );
+            }
+            found.map(|(column, len)| {
+                let position = LineColumn {
+                    line,
+                    column: column + 1,
+                };
+                SourceSpan::new(source_map.offset(position).into(), len)
+            })
+        };
+        let file1_ngram_span = find_ngram(&file1, file1_path, file1_ngram, 1);
+        let file2_ngram_span = find_ngram(&file2, file2_path, file2_ngram, 2);
 
         // filenames sorted
         let filename1 = get_filename(file1_path);
@@ -130,77 +179,158 @@ impl SimilarFilename {
         };
 
         // Create the unique id
-        let id = format!("{CODE}::{filename1}::{filename2}");
+        let id = ErrorCode::in_file(CODE, &filename1.lowercase(), &[&filename2.lowercase()]).0;
 
         // Create the advice
         let advice = format!(
-            "Maybe you should combine them into a single file?\nThe score was: {score:?}\nid: {id:?}"
+            "Maybe you should combine them into a single file, with `mdlinker merge {} {}`?\nThe score was: {score:?}\nid: {id:?}",
+            file2_path.display(),
+            file1_path.display()
         );
+        let mut ngrams = [file1_ngram.to_string(), file2_ngram.to_string()];
+        ngrams.sort();
+        let fingerprint = Fingerprint::new(CODE, &[&ngrams[0], &ngrams[1]]);
         Ok(Self {
             id: id.into(),
+            fingerprint,
+            file1_path: file1_path.to_path_buf(),
+            file2_path: file2_path.to_path_buf(),
             score,
             filepaths,
             file1_ngram_span,
             file2_ngram_span,
+            other_ngrams: vec![],
+            other_ngram_spans: vec![],
             advice,
             file1_ngram: file1_ngram.clone(),
             file2_ngram: file2_ngram.clone(),
         })
     }
 
+    /// Add the ngrams of `other`, a report about the same two files, to this one
+    /// The files are in the same order in both, see [`SimilarFilename::new`]
+    /// The report with the higher score should be kept, as its score and advice are shown
+    fn merge(&mut self, other: SimilarFilename) {
+        let spans = [
+            (other.file1_ngram_span, &other.file1_ngram),
+            (other.file2_ngram_span, &other.file2_ngram),
+        ];
+        for (span, ngram) in spans {
+            let Some(span) = span else {
+                continue;
+            };
+            if Some(span) == self.file1_ngram_span
+                || Some(span) == self.file2_ngram_span
+                || self
+                    .other_ngram_spans
+                    .iter()
+                    .any(|other| *other.inner() == span)
+            {
+                continue;
+            }
+            self.other_ngram_spans.push(LabeledSpan::new_with_span(
+                Some(format!("Also similar: {ngram}")),
+                span,
+            ));
+        }
+        self.other_ngrams
+            .push((other.file1_ngram, other.file2_ngram));
+        self.other_ngrams.extend(other.other_ngrams);
+    }
+
+    /// Other pairs of similar ngrams in [`SimilarFilename::file1_path`] and
+    /// [`SimilarFilename::file2_path`], which were merged into this report
+    #[must_use]
+    pub fn other_ngrams(&self) -> &[(Ngram, Ngram)] {
+        &self.other_ngrams
+    }
+
+    #[must_use]
+    pub fn file1_path(&self) -> &Path {
+        &self.file1_path
+    }
+
+    #[must_use]
+    pub fn file2_path(&self) -> &Path {
+        &self.file2_path
+    }
+
+    /// The words in the first filename which are similar to [`SimilarFilename::file2_ngram`]
+    #[must_use]
+    pub fn file1_ngram(&self) -> &Ngram {
+        &self.file1_ngram
+    }
+
+    /// The words in the second filename which are similar to [`SimilarFilename::file1_ngram`]
+    #[must_use]
+    pub fn file2_ngram(&self) -> &Ngram {
+        &self.file2_ngram
+    }
+
+    /// How similar the ngrams are, see [`crate::config::Config::filename_match_threshold`]
+    #[must_use]
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    /// Stops comparing once `budget` runs out, leaving out the files not compared yet, see
+    /// [`Budget::has_time`]
+    /// Also returns the [`Config::ignore_word_pairs`] which skipped a pair, see [`MatchedExcludes`]
     pub fn calculate(
-        file_ngrams: &HashMap<Ngram, PathBuf>,
+        file_ngrams: &HashMap<Ngram, Vec<PathBuf>>,
         filename_match_threshold: i64,
         spacing_regex: &Regex,
         config: &Config,
-    ) -> Result<Vec<SimilarFilename>, CalculateError> {
+        progress: &dyn ProgressSink,
+        budget: &mut Budget,
+    ) -> Result<(Vec<SimilarFilename>, MatchedExcludes), CalculateError> {
         // Convert all filenames to a single string
         // Check if any two file ngrams fuzzy match
         // TODO: Unfortunately this is O(n^2)
-        #[allow(clippy::cast_precision_loss)]
-        let n = file_ngrams.len() as f64;
-        let file_crosscheck_bar: Option<ProgressBar> = if env::var("RUNNING_TESTS").is_ok() {
-            None
-        } else {
-            println!(
-                "  {} {}Searching for Similar Filenames O(n^2)...",
-                style("[1/3]").bold().dim(),
-                SIMILAR
-            );
-            #[allow(clippy::cast_sign_loss)]
-            #[allow(clippy::cast_possible_truncation)]
-            Some(ProgressBar::new((n * n) as u64))
-        };
+        // An ngram can be in more than one file, each is compared
+        let file_ngrams: Vec<(&Ngram, &PathBuf)> = file_ngrams
+            .iter()
+            .flat_map(|(ngram, filepaths)| filepaths.iter().map(move |path| (ngram, path)))
+            .collect();
+        let n = file_ngrams.len() as u64;
+        progress.pass_started(Pass::SimilarFilenames, Some(n * n));
         let matcher = SkimMatcherV2::default();
         let mut matches: Vec<SimilarFilename> = Vec::new();
-        let mut seen_ngrams = HashSet::<(Ngram, Ngram)>::new();
+        // Indices in `file_ngrams`
+        let mut seen_ngrams = HashSet::<(usize, usize)>::new();
+        let mut matched = MatchedExcludes::default();
         let ignore_word_pairs: HashSet<(String, String)> =
             config.ignore_word_pairs.iter().cloned().collect();
-        for (ngram, filepath) in file_ngrams {
-            for (other_ngram, other_filepath) in file_ngrams {
-                if let Some(bar) = &file_crosscheck_bar {
-                    bar.inc(1);
-                }
+        let directories = config.directories();
+        let directory = |path: &Path| directories.iter().position(|dir| path.starts_with(dir));
+        for (i, &(ngram, filepath)) in file_ngrams.iter().enumerate() {
+            if !budget.has_time() {
+                break;
+            }
+            let started = budget.start();
+            for (j, &(other_ngram, other_filepath)) in file_ngrams.iter().enumerate() {
+                progress.progressed();
 
                 if ngram.nb_words() != other_ngram.nb_words() {
                     continue;
                 }
 
                 // Some guards to make it faster
-                if seen_ngrams.contains(&(ngram.clone(), other_ngram.clone())) {
+                if seen_ngrams.contains(&(i, j)) {
                     continue;
                 }
-                if seen_ngrams.contains(&(other_ngram.clone(), ngram.clone())) {
-                    continue;
-                }
-                seen_ngrams.insert((ngram.clone(), other_ngram.clone()));
-                seen_ngrams.insert((other_ngram.clone(), ngram.clone()));
+                seen_ngrams.insert((i, j));
+                seen_ngrams.insert((j, i));
 
                 // Handle ingnore_word_pairs
-                if ignore_word_pairs.contains(&(ngram.to_string(), other_ngram.to_string())) {
+                let pair = (ngram.to_string(), other_ngram.to_string());
+                if ignore_word_pairs.contains(&pair) {
+                    matched.ignore_word_pairs.insert(pair);
                     continue;
                 }
-                if ignore_word_pairs.contains(&(other_ngram.to_string(), ngram.to_string())) {
+                let pair = (pair.1, pair.0);
+                if ignore_word_pairs.contains(&pair) {
+                    matched.ignore_word_pairs.insert(pair);
                     continue;
                 }
 
@@ -209,15 +339,25 @@ impl SimilarFilename {
                     continue;
                 }
 
+                // Skip files in different directories, like a page and a journal
+                if config.similar_filename_same_directory_only
+                    && directory(filepath) != directory(other_filepath)
+                {
+                    continue;
+                }
+
                 // Each editor will have its own special cases, lets centralize them
                 if SimilarFilename::skip_special_cases(filepath, other_filepath, spacing_regex)? {
                     continue;
                 }
 
                 // Score the ngrams and check if they match
-                let score1 = matcher.fuzzy_match(&ngram.to_string(), &other_ngram.to_string());
-                let score2 = matcher.fuzzy_match(&other_ngram.to_string(), &ngram.to_string());
-                let score = score1.max(score2);
+                let score = similarity(
+                    config.similarity_algorithm,
+                    &matcher,
+                    &ngram.to_string(),
+                    &other_ngram.to_string(),
+                );
                 if let Some(score) = score {
                     if score > filename_match_threshold {
                         matches.push(SimilarFilename::new(
@@ -232,14 +372,71 @@ impl SimilarFilename {
                     }
                 }
             }
+            budget.charge(started);
         }
-        if let Some(bar) = file_crosscheck_bar {
-            bar.finish_and_clear();
+        progress.pass_finished(Pass::SimilarFilenames);
+
+        // Merge the matches between the same two files, keeping the highest score first
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        let mut merged: Vec<SimilarFilename> = Vec::new();
+        for similar_filename in matches {
+            match merged.iter_mut().find(|m| {
+                m.file1_path == similar_filename.file1_path
+                    && m.file2_path == similar_filename.file2_path
+            }) {
+                Some(existing) => existing.merge(similar_filename),
+                None => merged.push(similar_filename),
+            }
         }
-        Ok(matches)
+        Ok((merged, matched))
     }
 }
 
+/// The score of two ngrams with `algorithm`, [`None`] if skim does not match them at all
+/// All but [`SimilarityAlgorithm::Skim`] are from 0 to 100, so thresholds work for any vault
+#[must_use]
+pub fn similarity(
+    algorithm: SimilarityAlgorithm,
+    matcher: &SkimMatcherV2,
+    ngram: &str,
+    other_ngram: &str,
+) -> Option<i64> {
+    #[allow(clippy::cast_possible_truncation)]
+    let percent = |similarity: f64| Some((similarity * 100.0).round() as i64);
+    match algorithm {
+        SimilarityAlgorithm::Skim => {
+            let score1 = matcher.fuzzy_match(ngram, other_ngram);
+            let score2 = matcher.fuzzy_match(other_ngram, ngram);
+            score1.max(score2)
+        }
+        SimilarityAlgorithm::Levenshtein => {
+            percent(strsim::normalized_levenshtein(ngram, other_ngram))
+        }
+        SimilarityAlgorithm::JaroWinkler => percent(strsim::jaro_winkler(ngram, other_ngram)),
+        SimilarityAlgorithm::TokenSet => percent(token_set_ratio(ngram, other_ngram)),
+    }
+}
+
+/// The words both ngrams have, sorted, compared to those followed by the rest of the words
+/// of each ngram, sorted, taking the best
+fn token_set_ratio(ngram: &str, other_ngram: &str) -> f64 {
+    let words: BTreeSet<&str> = ngram.split_whitespace().collect();
+    let other_words: BTreeSet<&str> = other_ngram.split_whitespace().collect();
+    let common: Vec<&str> = words.intersection(&other_words).copied().collect();
+    let mut all = common.clone();
+    all.extend(words.difference(&other_words));
+    let mut other_all = common.clone();
+    other_all.extend(other_words.difference(&words));
+    let (common, all, other_all) = (common.join(" "), all.join(" "), other_all.join(" "));
+    [
+        strsim::normalized_levenshtein(&common, &all),
+        strsim::normalized_levenshtein(&common, &other_all),
+        strsim::normalized_levenshtein(&all, &other_all),
+    ]
+    .into_iter()
+    .fold(0.0, f64::max)
+}
+
 /// Each editor will have its own special cases, lets centralize them
 impl SimilarFilename {
     pub fn skip_special_cases(
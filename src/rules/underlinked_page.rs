@@ -0,0 +1,222 @@
+//! Pages linked to by fewer other pages than [`Config::min_inbound_links`] requires,
+//! like published pages which are hard to find
+//!
+//! Links from the page itself are not counted, nor more than one from the same page
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{source_map::SourceMap, wikilink::Alias},
+        name::get_filename,
+    },
+    link_index::LinkIndexVisitor,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashMap;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, span_line_column, ErrorCode, Fingerprint, FixError,
+    KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::page::underlinked";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("Too few pages link to a page")]
+#[diagnostic(code("content::page::underlinked"), severity(Warning))]
+pub struct UnderlinkedPage {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// The pages which link to this one, sorted
+    linked_from: Vec<PathBuf>,
+
+    /// See [`crate::config::MinInboundLinks::min`]
+    min_links: usize,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    /// The first line of the page
+    #[label("Linked from {} of {min_links} pages", linked_from.len())]
+    pub first_line: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for UnderlinkedPage {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Which pages should link here takes judgement, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.first_line)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.first_line)))
+    }
+}
+
+impl UnderlinkedPage {
+    /// See [`UnderlinkedPage::linked_from`]
+    #[must_use]
+    pub fn linked_from(&self) -> &[PathBuf] {
+        &self.linked_from
+    }
+}
+
+impl KeepsSource for UnderlinkedPage {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for UnderlinkedPage {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for UnderlinkedPage {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+#[derive(Debug)]
+pub struct UnderlinkedPageVisitor {
+    pub underlinked_pages: Vec<UnderlinkedPage>,
+    /// The links out of every file, to find the links into each
+    link_index_visitor: LinkIndexVisitor,
+    /// The files with a minimum, see [`Config::min_inbound_links`]
+    mins: HashMap<PathBuf, usize>,
+    /// The files with a minimum, and the minimum, since links to them are only all known
+    /// once every file has been parsed
+    pages: Vec<(NamedSource<String>, usize)>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl UnderlinkedPageVisitor {
+    #[must_use]
+    pub fn new(
+        alias_table: &HashMap<Alias, PathBuf>,
+        config: &Config,
+        sources: Rc<RefCell<SourceBudget>>,
+    ) -> Self {
+        Self {
+            sources,
+            underlinked_pages: Vec::new(),
+            link_index_visitor: LinkIndexVisitor::new(alias_table.clone(), config.skip_html),
+            mins: alias_table
+                .values()
+                .filter_map(|path| Some((path.clone(), config.min_inbound_links(path)?)))
+                .collect(),
+            pages: Vec::new(),
+        }
+    }
+}
+
+impl Visitor for UnderlinkedPageVisitor {
+    fn name(&self) -> &'static str {
+        "UnderlinkedPageVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.link_index_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        if let Some(&min) = self.mins.get(path) {
+            self.pages.push((
+                NamedSource::new(path.to_string_lossy(), source.to_string()),
+                min,
+            ));
+        }
+        self.link_index_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        let inbound = self.link_index_visitor.link_index.inbound();
+        for (src, min) in std::mem::take(&mut self.pages) {
+            let path = PathBuf::from(src.name());
+            let mut linked_from: Vec<PathBuf> = inbound
+                .get(&path)
+                .map(|linked_from| linked_from.iter().cloned().collect())
+                .unwrap_or_default();
+            if linked_from.len() >= min {
+                continue;
+            }
+            linked_from.sort();
+            let filename = get_filename(&path).lowercase();
+            let id = ErrorCode::in_file(CODE, &filename, &[]).0;
+            let first_line = src.inner().lines().next().map_or(0, str::len);
+            let nb_links = linked_from.len();
+            let report = UnderlinkedPage::builder()
+                .advice(format!(
+                    "This page is linked from {nb_links} other pages, at least {min} should link to it. Link to it from related pages.\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&filename.to_string()]))
+                .src(src)
+                .first_line(SourceSpan::new(0.into(), first_line))
+                .linked_from(linked_from)
+                .min_links(min)
+                .build();
+            self.underlinked_pages
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        // We can "take" this because we are putting it right back
+        let (underlinked_pages, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.underlinked_pages), excludes);
+        self.underlinked_pages = dedupe_by_code(underlinked_pages);
+        Ok(Finalized {
+            reports: self
+                .underlinked_pages
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::UnderlinkedPage(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -0,0 +1,254 @@
+//! Tags, like `#tag` or `#[[tag]]`, which are not in [`Config::known_tags`]
+//! or linked from [`Config::tags_page`]
+//!
+//! This only runs when one of those is set, so the tags of a vault can be kept to a fixed list
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::Config,
+    file::{
+        content::{
+            source_map::SourceMap,
+            wikilink::{Alias, Wikilink, WikilinkVisitor},
+        },
+        name::{get_filename, Filename},
+    },
+    sed::ReplacePair,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{arena_tree::Node, nodes::Ast};
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use hashbrown::HashSet;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::tag::unknown";
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A tag is not one of the known tags")]
+#[diagnostic(code("content::tag::unknown"), severity(Warning))]
+pub struct UnknownTag {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    tag: Alias,
+
+    /// The known tag which fuzzy matches the tag best, if any do
+    #[serde(default)]
+    suggestion: Option<Alias>,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Unknown tag")]
+    pub span: SourceSpan,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for UnknownTag {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// The tag may be new on purpose, so this is not fixed
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        Ok(None)
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl UnknownTag {
+    /// The tag, as written
+    #[must_use]
+    pub fn tag(&self) -> &Alias {
+        &self.tag
+    }
+
+    /// The closest known tag to [`UnknownTag::tag`]
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&Alias> {
+        self.suggestion.as_ref()
+    }
+}
+
+impl KeepsSource for UnknownTag {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for UnknownTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for UnknownTag {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// The known tag which fuzzy matches `tag` best, both ways like
+/// [`crate::rules::broken_wikilink::BrokenWikilink`] suggestions
+fn closest_tag<'a>(tag: &Alias, known_tags: &'a HashSet<Alias>) -> Option<&'a Alias> {
+    let matcher = SkimMatcherV2::default();
+    let tag = tag.to_string();
+    known_tags
+        .iter()
+        .filter_map(|candidate| {
+            let other = candidate.to_string();
+            let score = matcher
+                .fuzzy_match(&other, &tag)
+                .max(matcher.fuzzy_match(&tag, &other))?;
+            Some((Reverse(score), candidate))
+        })
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+#[derive(Debug)]
+pub struct UnknownTagVisitor {
+    pub unknown_tags: Vec<UnknownTag>,
+    wikilinks_visitor: WikilinkVisitor,
+    /// See [`Config::known_tags`], and the tags and wikilinks in the tags page
+    known_tags: HashSet<Alias>,
+    /// See [`Config::tags_page`]
+    tags_page: Option<Alias>,
+    filename_to_alias: ReplacePair<Filename, Alias>,
+    /// Tags are only known to be unknown once the tags page has been parsed
+    tags: Vec<(Wikilink, NamedSource<String>, String)>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl UnknownTagVisitor {
+    #[must_use]
+    pub fn new(config: &Config, sources: Rc<RefCell<SourceBudget>>) -> Self {
+        Self {
+            sources,
+            unknown_tags: Vec::new(),
+            wikilinks_visitor: WikilinkVisitor::new(config.skip_html),
+            known_tags: config
+                .known_tags
+                .iter()
+                .map(|tag| Alias::new(tag))
+                .collect(),
+            tags_page: config.tags_page.as_deref().map(Alias::new),
+            filename_to_alias: config.filename_to_alias.clone(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl Visitor for UnknownTagVisitor {
+    fn name(&self) -> &'static str {
+        "UnknownTagVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.wikilinks_visitor.visit(node, source)?;
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        let filename = get_filename(path);
+        let alias = Alias::from_filename(&filename, &self.filename_to_alias);
+        let wikilinks = std::mem::take(&mut self.wikilinks_visitor.wikilinks);
+        if self.tags_page.as_ref() == Some(&alias) {
+            self.known_tags
+                .extend(wikilinks.into_iter().map(|wikilink| wikilink.alias));
+        } else {
+            let src = NamedSource::new(path.to_string_lossy(), source.to_string());
+            for wikilink in wikilinks.into_iter().filter(|wikilink| wikilink.tag) {
+                let context = line_context(source, wikilink.span.offset());
+                self.tags.push((wikilink, src.clone(), context));
+            }
+        }
+        self.wikilinks_visitor.finalize_file(source, path)?;
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        for (wikilink, src, context) in std::mem::take(&mut self.tags) {
+            let tag = wikilink.alias;
+            if self.known_tags.contains(&tag) {
+                continue;
+            }
+            let filename = get_filename(Path::new(src.name())).lowercase();
+            let id = ErrorCode::in_file(CODE, &filename, &[&tag]).0;
+            let suggestion = closest_tag(&tag, &self.known_tags).cloned();
+            let did_you_mean = suggestion
+                .as_ref()
+                .map(|suggestion| format!("\nDid you mean: #{suggestion}?"))
+                .unwrap_or_default();
+            let report = UnknownTag::builder()
+                .advice(format!(
+                    "Use one of the known tags, or add '{tag}' to them.{did_you_mean}\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(CODE, &[&tag.to_string(), &context]))
+                .src(src)
+                .span(wikilink.span)
+                .tag(tag)
+                .maybe_suggestion(suggestion)
+                .build();
+            self.unknown_tags
+                .push(self.sources.borrow_mut().keep(report));
+        }
+        // We can "take" this because we are putting it right back
+        let (unknown_tags, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.unknown_tags), excludes);
+        self.unknown_tags = dedupe_by_code(unknown_tags);
+        Ok(Finalized {
+            reports: self
+                .unknown_tags
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::UnknownTag(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
@@ -1,49 +1,84 @@
 use crate::{
-    config::Config,
+    alias_index::{AliasIndex, Mention},
+    budget::SourceBudget,
+    config::{Config, LinkOncePer, UnlinkedTextFix, UnlinkedTextOptions},
     file::{
-        content::wikilink::{Alias, WikilinkVisitor},
-        name::{get_filename, Filename},
+        case::eq_folded,
+        content::{
+            html::is_html,
+            notebook::is_notebook,
+            quote::{is_in_blockquote, is_in_callout},
+            source_map::SourceMap,
+            table::{cell_offset, is_in_table},
+            wikilink::{Alias, WikilinkVisitor},
+        },
+        name::get_filename,
     },
-    sed::ReplacePair,
-    visitor::{FinalizeError, VisitError, Visitor},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
 };
-use aho_corasick::AhoCorasick;
 use bon::Builder;
 use comrak::{
     arena_tree::Node,
     nodes::{Ast, NodeValue, Sourcepos},
 };
-use hashbrown::HashMap;
-use log::trace;
-use miette::{Diagnostic, NamedSource, Result, SourceOffset, SourceSpan};
+use hashbrown::{HashMap, HashSet};
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
 use std::{
     backtrace::Backtrace,
     cell::RefCell,
+    cmp::Reverse,
     path::{Path, PathBuf},
+    rc::Rc,
 };
 use thiserror::Error;
+use tracing::trace;
 
 use super::{
-    dedupe_by_code, filter_by_excludes, ErrorCode, FixError, Report, ReportTrait, ThirdPassReport,
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, TextEdit, ThirdPassReport,
 };
 
 pub const CODE: &str = "content::alias::unlinked";
 
-#[derive(Error, Debug, Diagnostic, Builder, Clone)]
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
 #[error("Found text which could probably be put in a wikilink")]
 #[diagnostic(code("content::alias::unlinked"))]
 pub struct UnlinkedText {
     /// Used to identify the diagnostic and exclude it if needed
     id: ErrorCode,
 
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
     alias: Alias,
 
+    /// The plural or possessive suffix after the alias, which is in the span but not wrapped when
+    /// fixed, see [`crate::config::Config::unlinked_text_inflections`]
+    #[builder(default)]
+    #[serde(default)]
+    inflection: String,
+
     #[source_code]
+    #[serde(with = "super::named_source")]
     src: NamedSource<String>,
 
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
     #[label("Alias")]
     pub span: SourceSpan,
 
+    /// The other mentions of the alias in the file, see
+    /// [`crate::config::Config::group_unlinked_text`]
+    #[label(collection, "Also here")]
+    #[builder(default)]
+    #[serde(default)]
+    other_spans: Vec<SourceSpan>,
+
     #[help]
     advice: String,
 }
@@ -52,24 +87,52 @@ impl ReportTrait for UnlinkedText {
     fn id(&self) -> ErrorCode {
         self.id.clone()
     }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
     /// Open the file, surround the span in [[ ]], then save it
-    /// TODO: Be able to handle this in parallel with other reports
-    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+    /// The [[ ]] goes around the alias, leaving any inflection after it, like `[[project]]s`
+    /// When mentions are grouped, all of them or only the first are surrounded,
+    /// see [`crate::config::UnlinkedTextFix`]
+    /// Notebooks are not fixed, the span is in the markdown of their cells, not the json
+    fn fix(&self, config: &Config) -> Result<Option<()>, FixError> {
         let file = self.src.name().to_owned();
+        if is_notebook(Path::new(&file)) {
+            trace!("Not fixing unlinked text in notebook: {file:?}");
+            return Ok(None);
+        }
         trace!("Fixing unlinked text: {:?}", file);
         let mut source = std::fs::read_to_string(&file).map_err(|src| FixError::IOError {
             source: src,
             file: file.clone(),
             backtrace: Backtrace::force_capture(),
         })?;
-        let start = self.span.offset();
-        let end = start + self.span.len();
-        if end >= source.len() {
-            source.push_str("]]"); // Append to the end if `end` is out of bounds
-        } else {
-            source.insert_str(end, "]]"); // Insert at `end` if within bounds
+        let mut spans = match config.unlinked_text_fix {
+            UnlinkedTextFix::All => self.spans(),
+            UnlinkedTextFix::First => vec![self.span],
+        };
+        // Fix from the end of the file, so the offsets of the spans before stay the same
+        spans.sort_by_key(|span| Reverse(span.offset()));
+        // The matched text is the alias up to ascii case, then any inflection
+        let alias = self.alias.to_string();
+        for span in spans {
+            let start = span.offset();
+            let end = start + span.len().min(alias.len());
+            // Like a span which is off in a table, which would put brackets in the wrong place
+            if !source
+                .get(start..end)
+                .is_some_and(|text| eq_folded(text, &alias))
+            {
+                trace!("Not fixing unlinked text which is not the alias: {span:?}");
+                continue;
+            }
+            if end >= source.len() {
+                source.push_str("]]"); // Append to the end if `end` is out of bounds
+            } else {
+                source.insert_str(end, "]]"); // Insert at `end` if within bounds
+            }
+            source.insert_str(start, "[[");
         }
-        source.insert_str(start, "[[");
         std::fs::write(self.src.name(), source).map_err(|source| FixError::IOError {
             source,
             file,
@@ -77,6 +140,88 @@ impl ReportTrait for UnlinkedText {
         })?;
         Ok(Some(()))
     }
+    fn edits(&self) -> Option<PathBuf> {
+        let path = PathBuf::from(self.src.name());
+        (!is_notebook(&path)).then_some(path)
+    }
+    fn fix_spans(&self) -> Vec<SourceSpan> {
+        self.spans()
+    }
+    /// The [[ and ]] around each span [`ReportTrait::fix`] surrounds
+    fn quick_fix(&self, config: &Config) -> Vec<TextEdit> {
+        let Some(path) = self.edits() else {
+            return vec![];
+        };
+        let spans = match config.unlinked_text_fix {
+            UnlinkedTextFix::All => self.spans(),
+            UnlinkedTextFix::First => vec![self.span],
+        };
+        let alias = self.alias.to_string();
+        spans
+            .into_iter()
+            .filter(|span| {
+                let start = span.offset();
+                let end = start + span.len().min(alias.len());
+                // The source is empty if it was elided, then the span is trusted
+                self.src.inner().is_empty()
+                    || self
+                        .src
+                        .inner()
+                        .get(start..end)
+                        .is_some_and(|text| eq_folded(text, &alias))
+            })
+            .flat_map(|span| {
+                let start = span.offset();
+                let end = start + span.len().min(alias.len());
+                [
+                    TextEdit::new(path.clone(), SourceSpan::new(start.into(), 0), "[["),
+                    TextEdit::new(path.clone(), SourceSpan::new(end.into(), 0), "]]"),
+                ]
+            })
+            .collect()
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl UnlinkedText {
+    /// The alias the text matches
+    #[must_use]
+    pub fn alias(&self) -> &Alias {
+        &self.alias
+    }
+
+    /// The plural or possessive suffix after the alias, empty if the text is the alias itself
+    #[must_use]
+    pub fn inflection(&self) -> &str {
+        &self.inflection
+    }
+
+    /// Every mention of the alias in the report, the first is [`UnlinkedText::span`]
+    #[must_use]
+    pub fn spans(&self) -> Vec<SourceSpan> {
+        std::iter::once(self.span)
+            .chain(self.other_spans.iter().copied())
+            .collect()
+    }
+}
+
+impl KeepsSource for UnlinkedText {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
 }
 
 impl PartialEq for UnlinkedText {
@@ -93,110 +238,118 @@ impl PartialOrd for UnlinkedText {
 
 #[derive(Debug)]
 pub struct UnlinkedTextVisitor {
-    pub alias_table: HashMap<Alias, PathBuf>,
-    new_unlinked_texts: Vec<(Alias, SourceSpan, Sourcepos)>,
+    /// Finds the aliases in text, see [`AliasIndex::autolink_mentions`]
+    pub index: Rc<RefCell<AliasIndex>>,
+    /// See [`crate::config::Config::skip_html`]
+    skip_html: bool,
+    /// See [`crate::config::Config::unlinked_text_inflections`]
+    inflections: bool,
+    /// See [`crate::config::Config::group_unlinked_text`]
+    group: bool,
+    /// See [`crate::config::Config::link_once_per`]
+    link_once_per: Option<LinkOncePer>,
+    /// See [`crate::config::Config::unlinked_text`]
+    options: UnlinkedTextOptions,
+    /// The aliases mentioned or linked so far in the current [`LinkOncePer`] scope
+    mentioned: HashSet<Alias>,
+    new_unlinked_texts: Vec<(Alias, SourceSpan, Sourcepos, &'static str)>,
     wikilink_visitor: WikilinkVisitor,
     pub unlinked_texts: Vec<UnlinkedText>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
 }
 
 impl UnlinkedTextVisitor {
     #[must_use]
     pub fn new(
-        _all_files: &[PathBuf],
-        _filename_to_alias: &ReplacePair<Filename, Alias>,
-        alias_table: HashMap<Alias, PathBuf>,
+        index: Rc<RefCell<AliasIndex>>,
+        skip_html: bool,
+        inflections: bool,
+        group: bool,
+        link_once_per: Option<LinkOncePer>,
+        options: UnlinkedTextOptions,
+        sources: Rc<RefCell<SourceBudget>>,
     ) -> Self {
         Self {
-            alias_table,
-            wikilink_visitor: WikilinkVisitor::new(),
+            sources,
+            index,
+            skip_html,
+            inflections,
+            group,
+            link_once_per,
+            options,
+            mentioned: HashSet::new(),
+            wikilink_visitor: WikilinkVisitor::new(skip_html),
             unlinked_texts: Vec::new(),
             new_unlinked_texts: Vec::new(),
         }
     }
 }
 
-/// Checks if the match at the given start and end indices is a whole word match.
-fn is_whole_word_match(text: &str, start: usize, end: usize) -> bool {
-    is_start_boundary(text, start) && is_end_boundary(text, end) && !is_start_hashtag(text, start)
-}
-
-/// Checks if the character before the start index is a word boundary.
-fn is_start_boundary(text: &str, start: usize) -> bool {
-    if start == 0 {
-        true
-    } else {
-        text[..start]
-            .chars()
-            .next_back()
-            .is_none_or(char::is_whitespace)
-    }
-}
-
-/// Checks if the character before the start index is a word boundary.
-fn is_start_hashtag(text: &str, start: usize) -> bool {
-    if start == 0 {
-        false
-    } else {
-        text[..start].ends_with('#')
-    }
-}
-
-/// Checks if the character after the end index is a word boundary.
-fn is_end_boundary(text: &str, end: usize) -> bool {
-    if end == text.len() {
-        true
-    } else {
-        text[end..].chars().next().is_none_or(char::is_whitespace)
-    }
-}
-
 impl Visitor for UnlinkedTextVisitor {
     fn name(&self) -> &'static str {
         "UnlinkedTextVisitor"
     }
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError> {
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
         self.wikilink_visitor.visit(node, source)?;
         let data_ref = node.data.borrow();
         let data = &data_ref.value;
         let sourcepos = data_ref.sourcepos;
         let parent = node.parent();
+        if self.skip_html && is_html(node) {
+            return Ok(());
+        }
+        let in_table = matches!(data, NodeValue::Text(_)) && is_in_table(node);
+        if let NodeValue::Text(_) = data {
+            if (self.options.skip_blockquotes && is_in_blockquote(node))
+                || (self.options.skip_callouts && is_in_callout(node, source))
+                || (self.options.skip_tables && in_table)
+            {
+                return Ok(());
+            }
+        }
+        match (self.link_once_per, data) {
+            (Some(LinkOncePer::Section | LinkOncePer::Paragraph), NodeValue::Heading(_))
+            | (Some(LinkOncePer::Paragraph), NodeValue::Paragraph) => self.mentioned.clear(),
+            _ => {}
+        }
         if let NodeValue::Text(text) = data {
-            let patterns: Vec<String> = self
-                .alias_table
-                .keys()
-                .map(std::string::ToString::to_string)
-                .collect();
-            let ac = AhoCorasick::builder()
-                .ascii_case_insensitive(true)
-                .build(&patterns)?;
-            // Make sure neither the character before or after is a letter
-            // This makes sure you aren't matching a part of a word
-            // This should also handle tags
-            // Check the character before the match
-            for found in ac.find_iter(text) {
-                if !is_whole_word_match(text, found.start(), found.end()) {
-                    continue;
-                }
-                let alias = Alias::new(&patterns[found.pattern().as_usize()]);
-                let sourcepos_start_offset_bytes = SourceOffset::from_location(
-                    source,
-                    sourcepos.start.line,
-                    sourcepos.start.column,
-                )
-                .offset();
-                let byte_length = found.end() - found.start();
-                let offset_bytes = sourcepos_start_offset_bytes + found.start();
+            let mentions = self
+                .index
+                .borrow()
+                .autolink_mentions(text, self.inflections);
+            for Mention {
+                alias,
+                range,
+                inflection,
+            } in mentions
+            {
+                let sourcepos_start_offset_bytes = source.start(sourcepos);
+                let (start, end) = if in_table {
+                    (cell_offset(text, range.start), cell_offset(text, range.end))
+                } else {
+                    (range.start, range.end)
+                };
+                let byte_length = end - start;
+                let offset_bytes = sourcepos_start_offset_bytes + start;
                 let span = SourceSpan::new(offset_bytes.into(), byte_length);
 
                 // Dont match inside wikilinks
                 if let Some(parent) = parent {
                     if let NodeValue::WikiLink(_) = parent.data.borrow().value {
                         // If this is already in a link, skip it
+                        self.mentioned.insert(alias);
                         continue;
                     }
                 }
 
-                self.new_unlinked_texts.push((alias, span, sourcepos));
+                // Only the first mention in the scope
+                if !self.mentioned.insert(alias.clone()) && self.link_once_per.is_some() {
+                    continue;
+                }
+
+                self.new_unlinked_texts
+                    .push((alias, span, sourcepos, inflection));
             }
         }
         Ok(())
@@ -206,41 +359,60 @@ impl Visitor for UnlinkedTextVisitor {
         source: &str,
         path: &Path,
     ) -> std::result::Result<(), FinalizeError> {
-        for (alias, span, sourcepos) in &mut self.new_unlinked_texts {
-            let filename = get_filename(path);
+        // With grouping, the index in `unlinked_texts` of the report for each alias in the file
+        let mut grouped: HashMap<Alias, usize> = HashMap::new();
+        for (alias, span, sourcepos, inflection) in &mut self.new_unlinked_texts {
+            if let Some(&index) = grouped.get(alias) {
+                self.unlinked_texts[index].other_spans.push(*span);
+                continue;
+            }
+            let filename = get_filename(path).lowercase();
             let linenum = sourcepos.start.line;
             let colnum = sourcepos.start.column;
-            let id = format!("{CODE}::{filename}::{alias}::{linenum}::{colnum}");
-            self.unlinked_texts.push(
-                UnlinkedText::builder()
-                    .advice(format!(
-                        "Consider wrapping it in a wikilink, like: [[{alias}]]\nNOTE: If running in --fix, you may need to run fix more than once to fix all unlinked text errors.\n      I recommend doing this one at a time.\nREF: https://github.com/ryanpeach/mdlinker/issues/44\nid: {id:?}"
-                    ))
-                    .id(id.into())
-                    .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
-                    .alias(alias.clone())
-                    .span(*span)
-                    .build(),
-            );
+            let id = if self.group {
+                grouped.insert(alias.clone(), self.unlinked_texts.len());
+                ErrorCode::in_file(CODE, &filename, &[alias]).0
+            } else {
+                ErrorCode::in_file(CODE, &filename, &[alias, &linenum, &colnum]).0
+            };
+            let report = UnlinkedText::builder()
+                .advice(format!(
+                    "Consider wrapping it in a wikilink, like: [[{alias}]]{inflection}\nNOTE: If running in --fix, you may need to run fix more than once to fix all unlinked text errors.\n      I recommend doing this one at a time.\nREF: https://github.com/ryanpeach/mdlinker/issues/44\nid: {id:?}"
+                ))
+                .id(id.into())
+                .fingerprint(Fingerprint::new(
+                    CODE,
+                    &[&alias.to_string(), &line_context(source, span.offset())],
+                ))
+                .src(NamedSource::new(path.to_string_lossy(), source.to_string()))
+                .alias(alias.clone())
+                .inflection((*inflection).to_string())
+                .span(*span)
+                .build();
+            self.unlinked_texts
+                .push(self.sources.borrow_mut().keep(report));
         }
         self.new_unlinked_texts.clear();
+        self.mentioned.clear();
         self.wikilink_visitor.finalize_file(source, path)?;
         Ok(())
     }
 
-    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Vec<Report>, FinalizeError> {
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
         // We can "take" this because we are putting it right back
-        self.unlinked_texts = dedupe_by_code(filter_by_excludes(
-            std::mem::take(&mut self.unlinked_texts),
-            excludes,
-        ));
+        let (unlinked_texts, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.unlinked_texts), excludes);
+        self.unlinked_texts = dedupe_by_code(unlinked_texts);
         self.unlinked_texts.sort_by_key(|item| item.span.offset());
         self.unlinked_texts.reverse();
         self.wikilink_visitor.finalize(excludes)?;
-        Ok(self
-            .unlinked_texts
-            .iter()
-            .map(|x| Report::ThirdPass(ThirdPassReport::UnlinkedText(x.clone())))
-            .collect())
+        Ok(Finalized {
+            reports: self
+                .unlinked_texts
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::UnlinkedText(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
     }
 }
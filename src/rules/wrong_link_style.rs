@@ -0,0 +1,420 @@
+//! Links to pages written the other way than [`Config::link_style`], like `[text](page.md)`
+//! in a vault of wikilinks
+//!
+//! Only markdown links to `.md` files are links to pages, links to websites and attachments are
+//! left alone, as are tags and embeds like `![[page]]`
+use std::{
+    backtrace::Backtrace,
+    cell::RefCell,
+    cmp::Reverse,
+    path::{Component, Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::{
+    budget::SourceBudget,
+    config::{Config, LinkStyle},
+    file::{
+        content::{notebook::is_notebook, source_map::SourceMap, wikilink::Alias},
+        name::{get_filename, Filename},
+    },
+    sed::ReplacePair,
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+};
+use bon::Builder;
+use comrak::{
+    arena_tree::Node,
+    nodes::{Ast, NodeLink, NodeValue, NodeWikiLink},
+};
+use hashbrown::HashMap;
+use miette::{Diagnostic, NamedSource, Result, SourceSpan};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    dedupe_by_code, elided, filter_by_excludes, line_context, span_line_column, ErrorCode,
+    Fingerprint, FixError, KeepsSource, Report, ReportTrait, TextEdit, ThirdPassReport,
+};
+
+pub const CODE: &str = "content::link::style";
+
+/// A link, and what it is rewritten to, if it can be
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conversion {
+    pub span: SourceSpan,
+    /// The link, as written
+    pub written: String,
+    pub replacement: Option<String>,
+}
+
+#[derive(Error, Debug, Diagnostic, Builder, Clone, Serialize, Deserialize)]
+#[error("A link is not written as a {style}")]
+#[diagnostic(code("content::link::style"), severity(Warning))]
+pub struct WrongLinkStyle {
+    /// Used to identify the diagnostic and exclude it if needed
+    id: ErrorCode,
+
+    /// See [`ReportTrait::fingerprint`]
+    #[serde(default)]
+    fingerprint: Fingerprint,
+
+    /// See [`Config::link_style`]
+    style: LinkStyle,
+
+    conversion: Conversion,
+
+    #[source_code]
+    #[serde(with = "super::named_source")]
+    src: NamedSource<String>,
+
+    /// Kept when [`KeepsSource::elide_source`] drops the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    line_column: Option<(usize, usize)>,
+
+    #[label("Should be a {style}")]
+    pub span: SourceSpan,
+
+    /// The other links in the file written the other way, which are fixed with this one
+    #[builder(default)]
+    #[serde(default)]
+    others: Vec<Conversion>,
+
+    /// The spans of [`WrongLinkStyle::others`]
+    #[label(collection, "Also here")]
+    #[builder(default)]
+    #[serde(default)]
+    other_spans: Vec<SourceSpan>,
+
+    #[help]
+    advice: String,
+}
+
+impl ReportTrait for WrongLinkStyle {
+    fn id(&self) -> ErrorCode {
+        self.id.clone()
+    }
+    fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint.clone()
+    }
+    /// Rewrites every link in the file written the other way, as the offsets of the others
+    /// would move if only this one were
+    /// Links which can't be rewritten, like wikilinks to pages which don't exist, are left alone
+    fn fix(&self, _config: &Config) -> Result<Option<()>, FixError> {
+        let file = self.src.name().to_owned();
+        if self.edits().is_none() {
+            return Ok(None);
+        }
+        let mut source = std::fs::read_to_string(&file).map_err(|src| FixError::IOError {
+            source: src,
+            file: file.clone(),
+            backtrace: Backtrace::force_capture(),
+        })?;
+        let mut conversions: Vec<&Conversion> = std::iter::once(&self.conversion)
+            .chain(&self.others)
+            .collect();
+        // Fix from the end of the file, so the offsets of the spans before stay the same
+        conversions.sort_by_key(|conversion| Reverse(conversion.span.offset()));
+        let mut fixed = false;
+        for conversion in conversions {
+            let Some(replacement) = &conversion.replacement else {
+                continue;
+            };
+            let range = conversion.span.offset()..conversion.span.offset() + conversion.span.len();
+            // Already fixed along with another link in the file
+            if source.get(range.clone()) == Some(conversion.written.as_str()) {
+                source.replace_range(range, replacement);
+                fixed = true;
+            }
+        }
+        if !fixed {
+            return Ok(None);
+        }
+        std::fs::write(&file, source).map_err(|source| FixError::IOError {
+            source,
+            file,
+            backtrace: Backtrace::force_capture(),
+        })?;
+        Ok(Some(()))
+    }
+    fn edits(&self) -> Option<PathBuf> {
+        let path = PathBuf::from(self.src.name());
+        (!is_notebook(&path)).then_some(path)
+    }
+    fn fix_spans(&self) -> Vec<SourceSpan> {
+        std::iter::once(&self.conversion)
+            .chain(&self.others)
+            .filter(|conversion| conversion.replacement.is_some())
+            .map(|conversion| conversion.span)
+            .collect()
+    }
+    fn quick_fix(&self, _config: &Config) -> Vec<TextEdit> {
+        let Some(path) = self.edits() else {
+            return vec![];
+        };
+        std::iter::once(&self.conversion)
+            .chain(&self.others)
+            .filter_map(|conversion| {
+                let replacement = conversion.replacement.as_ref()?;
+                Some(TextEdit::new(path.clone(), conversion.span, replacement))
+            })
+            .collect()
+    }
+    fn path(&self) -> PathBuf {
+        PathBuf::from(self.src.name())
+    }
+    fn span(&self) -> Option<SourceSpan> {
+        Some(self.span)
+    }
+    fn line_column(&self) -> Option<(usize, usize)> {
+        self.line_column
+            .or_else(|| Some(span_line_column(&self.src, self.span)))
+    }
+}
+
+impl WrongLinkStyle {
+    /// The link, as written
+    #[must_use]
+    pub fn written(&self) -> &str {
+        &self.conversion.written
+    }
+
+    /// What `--fix` rewrites the link to, if it can
+    #[must_use]
+    pub fn replacement(&self) -> Option<&str> {
+        self.conversion.replacement.as_deref()
+    }
+}
+
+impl KeepsSource for WrongLinkStyle {
+    fn source_len(&self) -> usize {
+        self.src.inner().len()
+    }
+    fn elide_source(&mut self) {
+        self.line_column = self.line_column();
+        self.src = elided(&self.src);
+    }
+}
+
+impl PartialEq for WrongLinkStyle {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl PartialOrd for WrongLinkStyle {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.id.partial_cmp(&other.id)
+    }
+}
+
+/// The target of a markdown link, if it is a page, like `lorem.md` or `../lorem%20ipsum.md#anchor`
+/// Spaces are the only thing decoded, as they are the only thing usually encoded in page names
+fn page_target(url: &str) -> Option<String> {
+    let path = url.split('#').next().unwrap_or_default();
+    let is_page = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        // Like https:// or mailto:, and Zola's @/ links, see crate::rules::broken_relref
+        && !path.contains(':')
+        && !path.starts_with('@');
+    is_page.then(|| path.replace("%20", " "))
+}
+
+/// `to` as a markdown link from the file `from`, with spaces encoded
+pub(crate) fn relative_link(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from
+        .parent()
+        .map_or(vec![], |dir| dir.components().collect());
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_owned(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|part| part.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/").replace(' ', "%20")
+}
+
+/// A link written the other way, as parsed
+#[derive(Debug)]
+enum Link {
+    Markdown { url: String, title: String },
+    Wikilink { url: String },
+}
+
+#[derive(Debug)]
+pub struct WrongLinkStyleVisitor {
+    pub wrong_link_styles: Vec<WrongLinkStyle>,
+    /// See [`Config::link_style`], nothing is reported without it
+    style: Option<LinkStyle>,
+    /// Where wikilinks point, to rewrite them as markdown links
+    alias_table: HashMap<Alias, PathBuf>,
+    filename_to_alias: ReplacePair<Filename, Alias>,
+    /// The links written the other way in the current file, as written, and the context of each
+    links: Vec<(Link, SourceSpan, String, String)>,
+    /// Takes the source of each report as it is made, see [`SourceBudget::keep`]
+    sources: Rc<RefCell<SourceBudget>>,
+}
+
+impl WrongLinkStyleVisitor {
+    #[must_use]
+    pub fn new(
+        alias_table: &HashMap<Alias, PathBuf>,
+        config: &Config,
+        sources: Rc<RefCell<SourceBudget>>,
+    ) -> Self {
+        Self {
+            sources,
+            wrong_link_styles: Vec::new(),
+            style: config.link_style,
+            alias_table: alias_table.clone(),
+            filename_to_alias: config.filename_to_alias.clone(),
+            links: Vec::new(),
+        }
+    }
+
+    /// `[text](page.md)` as `[[text]]`, or `[[text|page]]` if the text is not the page's alias
+    fn to_wikilink(&self, written: &str, url: &str, title: &str) -> Option<String> {
+        // The title would be lost
+        if !title.is_empty() {
+            return None;
+        }
+        let text = &written[1..written.rfind("](")?];
+        let target = page_target(url)?;
+        let alias =
+            Alias::from_filename(&get_filename(Path::new(&target)), &self.filename_to_alias);
+        if Alias::new(text) == alias {
+            Some(format!("[[{text}]]"))
+        } else if text.contains(['[', ']', '|']) {
+            None
+        } else {
+            Some(format!("[[{text}|{alias}]]"))
+        }
+    }
+
+    /// `[[page]]` or `[[text|page]]` as `[text](path/to/page.md)`, relative to `path`
+    fn to_markdown(&self, written: &str, url: &str, path: &Path) -> Option<String> {
+        let target = self.alias_table.get(&Alias::new(url))?;
+        let link = relative_link(path, target);
+        let inner = written.strip_prefix("[[")?.strip_suffix("]]")?;
+        let text = inner.split_once('|').map_or(inner, |(text, _)| text).trim();
+        Some(format!("[{text}]({link})"))
+    }
+}
+
+impl Visitor for WrongLinkStyleVisitor {
+    fn name(&self) -> &'static str {
+        "WrongLinkStyleVisitor"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        let data_ref = node.data.borrow();
+        let sourcepos = data_ref.sourcepos;
+        let start = source.start(sourcepos);
+        let end = source.end(sourcepos);
+        let Some(written) = source.get(start..end) else {
+            return Ok(());
+        };
+        let link = match (&data_ref.value, self.style) {
+            (NodeValue::Link(NodeLink { url, title }), Some(LinkStyle::Wikilink)) => {
+                // Only inline links, not autolinks or reference links
+                if page_target(url).is_none() || !written.ends_with(')') {
+                    return Ok(());
+                }
+                Link::Markdown {
+                    url: url.clone(),
+                    title: title.clone(),
+                }
+            }
+            (NodeValue::WikiLink(NodeWikiLink { url }), Some(LinkStyle::Markdown)) => {
+                // Tags, like #[[tag]], and embeds, like ![[page]]
+                if source[..start].ends_with(['#', '!']) {
+                    return Ok(());
+                }
+                Link::Wikilink { url: url.clone() }
+            }
+            _ => return Ok(()),
+        };
+        self.links.push((
+            link,
+            SourceSpan::new(start.into(), written.len()),
+            written.to_owned(),
+            line_context(source, start),
+        ));
+        Ok(())
+    }
+    fn _finalize_file(
+        &mut self,
+        source: &str,
+        path: &Path,
+    ) -> std::result::Result<(), FinalizeError> {
+        // Links are only found with a style
+        let Some(style) = self.style else {
+            return Ok(());
+        };
+        let conversions: Vec<(Conversion, String)> = std::mem::take(&mut self.links)
+            .into_iter()
+            .map(|(link, span, written, context)| {
+                let replacement = match link {
+                    Link::Markdown { url, title } => self.to_wikilink(&written, &url, &title),
+                    Link::Wikilink { url } => self.to_markdown(&written, &url, path),
+                };
+                let conversion = Conversion {
+                    span,
+                    written,
+                    replacement,
+                };
+                (conversion, context)
+            })
+            .collect();
+        let filename = get_filename(path).lowercase();
+        let src = NamedSource::new(path.to_string_lossy(), source.to_string());
+        for (i, (conversion, context)) in conversions.iter().enumerate() {
+            let id = ErrorCode::in_file(CODE, &filename, &[&conversion.written]).0;
+            let fix = conversion.replacement.as_ref().map_or_else(
+                || "It can't be fixed automatically.".to_owned(),
+                |replacement| format!("Write it as {replacement}"),
+            );
+            let others: Vec<Conversion> = conversions
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, (other, _))| other.clone())
+                .collect();
+            self.wrong_link_styles.push(
+                self.sources.borrow_mut().keep(
+                    WrongLinkStyle::builder()
+                        .advice(format!(
+                            "Links to pages are written as a {style}. {fix}\nid: {id:?}"
+                        ))
+                        .id(id.into())
+                        .fingerprint(Fingerprint::new(CODE, &[&conversion.written, context]))
+                        .style(style)
+                        .src(src.clone())
+                        .span(conversion.span)
+                        .other_spans(others.iter().map(|other| other.span).collect())
+                        .others(others)
+                        .conversion(conversion.clone())
+                        .build(),
+                ),
+            );
+        }
+        Ok(())
+    }
+
+    fn _finalize(&mut self, excludes: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        // We can "take" this because we are putting it right back
+        let (wrong_link_styles, matched_excludes) =
+            filter_by_excludes(std::mem::take(&mut self.wrong_link_styles), excludes);
+        self.wrong_link_styles = dedupe_by_code(wrong_link_styles);
+        Ok(Finalized {
+            reports: self
+                .wrong_link_styles
+                .iter()
+                .map(|x| Report::ThirdPass(ThirdPassReport::WrongLinkStyle(x.clone())))
+                .collect(),
+            matched_excludes,
+        })
+    }
+}
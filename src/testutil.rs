@@ -0,0 +1,256 @@
+//! Vaults for tests, written to a temporary directory from a description of their pages, so
+//! tests of rules, built in or [`crate::rules::custom::CustomRule`]s, need no checked in files
+//!
+//! ```no_run
+//! use mdlinker::testutil::{Page, Vault};
+//!
+//! let vault = Vault::new()
+//!     .with_page(&Page::new("lorem").with_alias("ipsum").with_link("missing"))
+//!     .with_page(&Page::new("dolor").with_link("ipsum"))
+//!     .write()
+//!     .expect("The temporary directory is writable");
+//! let report = vault.run().expect("There should have been no error.");
+//! assert_eq!(report.broken_wikilinks().len(), 1);
+//! ```
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    config::{cli, file, Config},
+    linter::Linter,
+    OutputErrors, OutputReport,
+};
+
+/// The directory of [`Vault::with_page`] in the vault
+pub const PAGES_DIRECTORY: &str = "pages";
+
+/// The directory of [`Vault::with_journal`] in the vault
+pub const JOURNALS_DIRECTORY: &str = "journals";
+
+/// The config file of the vault, see [`TempVault::config`]
+pub const CONFIG_FILE: &str = "mdlinker.toml";
+
+/// A markdown page, made of its front matter and the lines of its body
+#[derive(Debug, Default, Clone)]
+pub struct Page {
+    /// The path of the page in its directory, without `.md`, like `lorem` or `lorem/ipsum`
+    name: String,
+    aliases: Vec<String>,
+    /// Written as `key: value`, in order and without checking the YAML
+    properties: Vec<(String, String)>,
+    lines: Vec<String>,
+}
+
+impl Page {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Add an alias to the `aliases` list of the front matter
+    #[must_use]
+    pub fn with_alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_owned());
+        self
+    }
+
+    /// Add a property to the front matter, `value` is written as it is, so it may be any YAML
+    /// A key may be given twice, like to test [`crate::rules::duplicate_property`]
+    #[must_use]
+    pub fn with_property(mut self, key: &str, value: &str) -> Self {
+        self.properties.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Add a block linking to `alias`, `- [[alias]]`
+    #[must_use]
+    pub fn with_link(self, alias: &str) -> Self {
+        self.with_line(&format!("- [[{alias}]]"))
+    }
+
+    /// Add a line to the body as it is
+    #[must_use]
+    pub fn with_line(mut self, line: &str) -> Self {
+        self.lines.push(line.to_owned());
+        self
+    }
+
+    /// The path of the page in its directory
+    #[must_use]
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.md", self.name))
+    }
+
+    /// The content of the page, with front matter only if there are aliases or properties
+    #[must_use]
+    pub fn content(&self) -> String {
+        let mut lines = vec![];
+        if !self.aliases.is_empty() || !self.properties.is_empty() {
+            lines.push("---".to_owned());
+            if !self.aliases.is_empty() {
+                lines.push(format!("aliases: [{}]", self.aliases.join(", ")));
+            }
+            for (key, value) in &self.properties {
+                lines.push(format!("{key}: {value}"));
+            }
+            lines.push("---".to_owned());
+        }
+        lines.extend(self.lines.iter().cloned());
+        let mut content = String::new();
+        for line in lines {
+            content.push_str(&line);
+            content.push('\n');
+        }
+        content
+    }
+}
+
+/// The description of a vault, written to disk by [`Vault::write`]
+#[derive(Debug, Default, Clone)]
+pub struct Vault {
+    /// Paths relative to the root of the vault, and their content
+    files: Vec<(PathBuf, String)>,
+    /// Whether there is a journal, so [`JOURNALS_DIRECTORY`] is linted
+    journals: bool,
+}
+
+impl Vault {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a page to [`PAGES_DIRECTORY`]
+    #[must_use]
+    pub fn with_page(self, page: &Page) -> Self {
+        let path = Path::new(PAGES_DIRECTORY).join(page.path());
+        self.with_file(path, &page.content())
+    }
+
+    /// Add a page to [`JOURNALS_DIRECTORY`], like `Page::new("2024_01_01")`
+    #[must_use]
+    pub fn with_journal(self, page: &Page) -> Self {
+        let path = Path::new(JOURNALS_DIRECTORY).join(page.path());
+        self.with_file(path, &page.content())
+    }
+
+    /// Add any file, at `path` relative to the root of the vault, like an attachment
+    /// Files outside [`PAGES_DIRECTORY`] and [`JOURNALS_DIRECTORY`] are not linted
+    #[must_use]
+    pub fn with_file(mut self, path: impl Into<PathBuf>, content: &str) -> Self {
+        let path = path.into();
+        self.journals |= path.starts_with(JOURNALS_DIRECTORY);
+        self.files.push((path, content.to_owned()));
+        self
+    }
+
+    /// Write the vault to a new temporary directory, which is removed when the [`TempVault`] is
+    /// dropped
+    ///
+    /// # Errors
+    ///
+    /// If the files can't be written
+    pub fn write(&self) -> std::io::Result<TempVault> {
+        /// Tests run in parallel in one process, so the process id alone is not unique
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "mdlinker-vault-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        let vault = TempVault {
+            root,
+            journals: self.journals,
+        };
+        std::fs::create_dir_all(vault.pages_directory())?;
+        for (path, content) in &self.files {
+            let path = vault.root.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(vault)
+    }
+}
+
+/// A [`Vault`] on disk, removed when dropped
+#[derive(Debug)]
+pub struct TempVault {
+    root: PathBuf,
+    journals: bool,
+}
+
+impl TempVault {
+    /// The root directory of the vault, see [`Config::root_directory`]
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// See [`PAGES_DIRECTORY`]
+    #[must_use]
+    pub fn pages_directory(&self) -> PathBuf {
+        self.root.join(PAGES_DIRECTORY)
+    }
+
+    /// The content of the file at `path` relative to the root, like after fixing
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read
+    pub fn read(&self, path: impl AsRef<Path>) -> std::io::Result<String> {
+        std::fs::read_to_string(self.root.join(path))
+    }
+
+    /// The default config for the vault, change its fields for other options
+    /// Its config file is [`CONFIG_FILE`] at the root, so saving it, like
+    /// ignoring a report in `mdlinker review` does, writes in the vault
+    #[must_use]
+    pub fn config(&self) -> Config {
+        let other_directories = if self.journals {
+            vec![self.root.join(JOURNALS_DIRECTORY)]
+        } else {
+            vec![]
+        };
+        Config::builder()
+            .pages_directory(self.pages_directory())
+            .other_directories(other_directories)
+            .root_directory(self.root.clone())
+            .file_config(file::Config::default())
+            .cli_config(cli::Config {
+                config_path: self.root.join(CONFIG_FILE),
+                ..cli::Config::default()
+            })
+            .build()
+    }
+
+    /// A [`Linter`] with [`TempVault::config`], to add [`crate::rules::custom::CustomRule`]s
+    /// with [`Linter::with_rule`]
+    #[must_use]
+    pub fn linter(&self) -> Linter {
+        Linter::from_config(self.config())
+    }
+
+    /// Lint the vault with [`TempVault::config`]
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::lib`]
+    #[allow(clippy::result_large_err)]
+    pub fn run(&self) -> Result<OutputReport, OutputErrors> {
+        self.linter().run()
+    }
+}
+
+impl Drop for TempVault {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
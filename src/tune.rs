@@ -0,0 +1,200 @@
+//! The scores of every pair of filename ngrams, see `mdlinker tune-similarity`
+use std::{
+    fmt::{Display, Formatter},
+    path::PathBuf,
+};
+
+use fuzzy_matcher::skim::SkimMatcherV2;
+use hashbrown::{HashMap, HashSet};
+
+use crate::{config::Config, ngrams::Ngram, rules::similar_filename::similarity};
+
+/// The number of bars in [`ScoreDistribution::histogram`]
+const NB_BUCKETS: i64 = 10;
+
+/// The percentiles in [`ScoreDistribution::quantiles`]
+const PERCENTILES: [usize; 9] = [0, 10, 25, 50, 75, 90, 95, 99, 100];
+
+/// The number of pairs in [`ScoreDistribution::near_threshold`]
+const NB_NEAR_THRESHOLD: usize = 10;
+
+/// Two ngrams from different files, and their score
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoredPair {
+    pub score: i64,
+    pub ngram: Ngram,
+    pub file: PathBuf,
+    pub other_ngram: Ngram,
+    pub other_file: PathBuf,
+}
+
+/// The scores of pairs of ngrams compared by [`crate::rules::similar_filename::SimilarFilename`],
+/// to pick [`Config::filename_match_threshold`]
+#[derive(Debug, Clone, Default)]
+pub struct ScoreDistribution {
+    /// See [`Config::filename_match_threshold`]
+    pub threshold: i64,
+    /// The number of pairs which could be compared
+    pub nb_pairs: usize,
+    /// The number of pairs which were scored, only some of them on large vaults
+    pub nb_sampled: usize,
+    /// The number of scored pairs which don't match at all, only with skim
+    pub nb_unmatched: usize,
+    /// The number of scores from the lower bound, inclusive, to the upper bound, exclusive
+    pub histogram: Vec<(i64, i64, usize)>,
+    /// Percentiles of the scores, and the score at each
+    pub quantiles: Vec<(usize, i64)>,
+    /// The pairs with the scores closest to the threshold, highest first
+    pub near_threshold: Vec<ScoredPair>,
+}
+
+impl ScoreDistribution {
+    /// Score pairs of ngrams with the same number of words from different files,
+    /// like [`crate::rules::similar_filename::SimilarFilename::calculate`] does, but without
+    /// stopping at the first match. If there are more than `max_pairs`, evenly spaced pairs are
+    /// scored
+    #[must_use]
+    pub fn new(
+        file_ngrams: &HashMap<Ngram, Vec<PathBuf>>,
+        config: &Config,
+        max_pairs: usize,
+    ) -> Self {
+        let mut file_ngrams: Vec<(&Ngram, &PathBuf)> = file_ngrams
+            .iter()
+            .flat_map(|(ngram, files)| files.iter().map(move |file| (ngram, file)))
+            .collect();
+        file_ngrams.sort();
+        let ignore_word_pairs: HashSet<(String, String)> =
+            config.ignore_word_pairs.iter().cloned().collect();
+        let mut pairs = Vec::new();
+        for (i, (ngram, file)) in file_ngrams.iter().enumerate() {
+            for (other_ngram, other_file) in &file_ngrams[i + 1..] {
+                if ngram.nb_words() != other_ngram.nb_words() || file == other_file {
+                    continue;
+                }
+                let words = (ngram.to_string(), other_ngram.to_string());
+                if ignore_word_pairs.contains(&words)
+                    || ignore_word_pairs.contains(&(words.1.clone(), words.0.clone()))
+                {
+                    continue;
+                }
+                pairs.push((*ngram, *file, *other_ngram, *other_file));
+            }
+        }
+
+        let nb_pairs = pairs.len();
+        let step = nb_pairs.div_ceil(max_pairs.max(1)).max(1);
+        let matcher = SkimMatcherV2::default();
+        let mut nb_sampled = 0;
+        let mut scored = Vec::new();
+        for (ngram, file, other_ngram, other_file) in pairs.into_iter().step_by(step) {
+            nb_sampled += 1;
+            let Some(score) = similarity(
+                config.similarity_algorithm,
+                &matcher,
+                &ngram.to_string(),
+                &other_ngram.to_string(),
+            ) else {
+                continue;
+            };
+            scored.push(ScoredPair {
+                score,
+                ngram: ngram.clone(),
+                file: file.clone(),
+                other_ngram: other_ngram.clone(),
+                other_file: other_file.clone(),
+            });
+        }
+        scored.sort_by_key(|pair| std::cmp::Reverse(pair.score));
+
+        let threshold = config.filename_match_threshold;
+        let mut near_threshold = scored.clone();
+        near_threshold.sort_by_key(|pair| (pair.score - threshold).abs());
+        near_threshold.truncate(NB_NEAR_THRESHOLD);
+        near_threshold.sort_by_key(|pair| std::cmp::Reverse(pair.score));
+
+        let scores: Vec<i64> = scored.iter().rev().map(|pair| pair.score).collect();
+        Self {
+            threshold,
+            nb_pairs,
+            nb_sampled,
+            nb_unmatched: nb_sampled - scored.len(),
+            histogram: histogram(&scores),
+            quantiles: quantiles(&scores),
+            near_threshold,
+        }
+    }
+}
+
+/// `scores` must be sorted
+fn histogram(scores: &[i64]) -> Vec<(i64, i64, usize)> {
+    let (Some(&min), Some(&max)) = (scores.first(), scores.last()) else {
+        return Vec::new();
+    };
+    let width = ((max - min) / NB_BUCKETS + 1).max(1);
+    let mut out: Vec<(i64, i64, usize)> = Vec::new();
+    let mut lower = min;
+    while lower <= max {
+        let upper = lower + width;
+        let count = scores
+            .iter()
+            .filter(|&&score| lower <= score && score < upper)
+            .count();
+        out.push((lower, upper, count));
+        lower = upper;
+    }
+    out
+}
+
+/// `scores` must be sorted
+fn quantiles(scores: &[i64]) -> Vec<(usize, i64)> {
+    if scores.is_empty() {
+        return Vec::new();
+    }
+    PERCENTILES
+        .iter()
+        .map(|&percentile| {
+            let index = (scores.len() - 1) * percentile / 100;
+            (percentile, scores[index])
+        })
+        .collect()
+}
+
+impl Display for ScoreDistribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "pairs: {}, scored: {}, unmatched: {}",
+            self.nb_pairs, self.nb_sampled, self.nb_unmatched
+        )?;
+        writeln!(f, "histogram:")?;
+        let most = self
+            .histogram
+            .iter()
+            .map(|(_, _, count)| *count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        for (lower, upper, count) in &self.histogram {
+            let bar = "#".repeat(count * 40 / most);
+            writeln!(f, "  {lower:>6} to {upper:>6}: {count:>8} {bar}")?;
+        }
+        writeln!(f, "quantiles:")?;
+        for (percentile, score) in &self.quantiles {
+            writeln!(f, "  p{percentile:<3} {score}")?;
+        }
+        writeln!(f, "near the threshold of {}:", self.threshold)?;
+        for pair in &self.near_threshold {
+            writeln!(
+                f,
+                "  {}: \"{}\" ({}) and \"{}\" ({})",
+                pair.score,
+                pair.ngram,
+                pair.file.display(),
+                pair.other_ngram,
+                pair.other_file.display()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,30 +1,38 @@
 //! A module for implementing a visitor pattern for ASTs in tree-sitter
 use std::{
+    borrow::Cow,
     cell::RefCell,
     path::{Path, PathBuf},
     rc::Rc,
 };
 
 use comrak::{arena_tree::Node, nodes::Ast, parse_document, Arena, ExtensionOptions, Options};
-use log::{debug, trace};
 use std::backtrace;
 use thiserror::Error;
+use tracing::{debug, trace};
 
-use crate::rules::{duplicate_alias::NewDuplicateAliasError, ErrorCode, Report};
+use crate::{
+    config::MarkdownExtensions,
+    file::{
+        content::{source_map::SourceMap, FromFileError},
+        provider::FileProvider,
+    },
+    rules::{duplicate_alias::NewDuplicateAliasError, ErrorCode, MatchedExcludes, Report},
+};
 
 #[derive(Error, Debug)]
 pub enum VisitError {
     #[error("Error deserializing the node")]
     FrontMatterDeserializeError {
         #[from]
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: serde_yaml::Error,
     },
 
     #[error("Error making patterns from aliases")]
     AhoBuildError {
         #[from]
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: aho_corasick::BuildError,
     },
 }
@@ -34,17 +42,39 @@ pub enum FinalizeError {
     #[error(transparent)]
     NewDuplicateAliasError {
         #[from]
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: NewDuplicateAliasError,
     },
 }
 
+/// What [`Visitor::finalize`] returns
+#[derive(Debug, Default)]
+pub struct Finalized {
+    pub reports: Vec<Report>,
+    /// The excludes which dropped the other reports, see [`crate::rules::filter_by_excludes`]
+    pub matched_excludes: MatchedExcludes,
+}
+
+/// For visitors which don't exclude their own reports, like those of
+/// [`crate::rules::custom::CustomRule`]s
+impl From<Vec<Report>> for Finalized {
+    fn from(reports: Vec<Report>) -> Self {
+        Self {
+            reports,
+            matched_excludes: MatchedExcludes::default(),
+        }
+    }
+}
+
 /// A trait for implementing an AST visitor pattern
 pub trait Visitor {
     /// The function that is called when visiting a node
+    /// `source` is the original source of the file, with its original line endings,
+    /// so spans should be computed from the line and column of the node using it, see
+    /// [`SourceMap::start`] and [`SourceMap::end`]
     /// WARNING: Don't overwrite this, its already written for you.
     /// Implement [`Self::_visit`] instead
-    fn visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError> {
+    fn visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
         trace!(
             "{:?} visiting node type: {:?}",
             self.name(),
@@ -67,93 +97,178 @@ pub trait Visitor {
     /// You have to run this yourself in lib, its not done in any of the funtions in this file for you
     /// WARNING: Don't overwrite this, its already written for you.
     /// Implement [`Self::_finalize`] instead
-    fn finalize(&mut self, exclude: &[ErrorCode]) -> Result<Vec<Report>, FinalizeError> {
+    fn finalize(&mut self, exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
         trace!("{:?} finalizing", self.name());
         #[allow(clippy::used_underscore_items)]
         self._finalize(exclude)
     }
 
-    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &str) -> Result<(), VisitError>;
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError>;
 
     fn _finalize_file(&mut self, _source: &str, _path: &Path) -> Result<(), FinalizeError>;
 
-    fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Vec<Report>, FinalizeError>;
+    fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError>;
 
     /// Get a unique name for the visitor
     fn name(&self) -> &str;
 }
 
+/// So boxed visitors, like those made by [`crate::rules::custom::CustomRule`]s, can be parsed with
+#[allow(clippy::used_underscore_items)]
+impl<V: Visitor + ?Sized> Visitor for Box<V> {
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        (**self)._visit(node, source)
+    }
+
+    fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+        (**self)._finalize_file(source, path)
+    }
+
+    fn _finalize(&mut self, exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        (**self)._finalize(exclude)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[error("Error reading the file {file:?}")]
     IoError {
         file: PathBuf,
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: std::io::Error,
     },
+    #[error("Error reading the notebook {file:?}")]
+    NotebookError {
+        file: PathBuf,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        source: serde_json::Error,
+    },
     #[error("Multibyte characters found in the file {file:?}")]
     MultibyteError {
         file: PathBuf,
-        backtrace: backtrace::Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
     #[error("Error parsing the source code for file {file:?} using tree-sitter")]
     TreeSitter {
         file: PathBuf,
-        backtrace: backtrace::Backtrace,
+        #[cfg_attr(feature = "nightly", backtrace)]
+        backtrace: crate::CapturedBacktrace,
     },
     #[error("Error finalizing the file {file:?}")]
     FinalizeError {
         file: PathBuf,
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: FinalizeError,
     },
     #[error("Error visiting the file {file:?}")]
     VisitError {
         file: PathBuf,
-        #[backtrace]
+        #[cfg_attr(feature = "nightly", backtrace)]
         source: VisitError,
     },
 }
 
-/// Parse the source code and visit all the nodes using tree-sitter
+/// Read the file at `path` from `files` and visit all the nodes of its source using
+/// [`parse_source_with`]
+/// Jupyter notebooks are read as the markdown of their markdown cells, see [`FileProvider::read_source`]
 #[allow(clippy::result_large_err)]
-pub fn parse(path: &PathBuf, visitors: Vec<Rc<RefCell<dyn Visitor>>>) -> Result<(), ParseError> {
-    debug!("Parsing file {:?}", path);
-    let source = std::fs::read_to_string(path).map_err(|source| ParseError::IoError {
-        file: path.clone(),
-        source,
+pub fn parse(
+    files: &dyn FileProvider,
+    path: &Path,
+    markdown: &MarkdownExtensions,
+    visitors: Vec<Rc<RefCell<dyn Visitor>>>,
+) -> Result<(), ParseError> {
+    let source = files.read_source(path).map_err(|source| match source {
+        FromFileError::NotebookError(source) => ParseError::NotebookError {
+            file: path.to_path_buf(),
+            source,
+        },
+        FromFileError::IoError(source) => ParseError::IoError {
+            file: path.to_path_buf(),
+            source,
+        },
+        FromFileError::AliasFromFilenameError { .. } => {
+            unreachable!("read_source does not create aliases")
+        }
     })?;
+    parse_source_with(path, &source, markdown, visitors)
+}
+
+/// Parse the source code and visit all the nodes, with the default [`MarkdownExtensions`]
+///
+/// `path` does not need to exist on the filesystem, it is only used to name the source
+/// in reports, so this can be used to lint in-memory documents
+#[allow(clippy::result_large_err)]
+pub fn parse_source(
+    path: &Path,
+    source: &str,
+    visitors: Vec<Rc<RefCell<dyn Visitor>>>,
+) -> Result<(), ParseError> {
+    parse_source_with(path, source, &MarkdownExtensions::default(), visitors)
+}
+
+/// [`parse_source`] with the `markdown` extensions
+#[allow(clippy::result_large_err)]
+pub fn parse_source_with(
+    path: &Path,
+    source: &str,
+    markdown: &MarkdownExtensions,
+    visitors: Vec<Rc<RefCell<dyn Visitor>>>,
+) -> Result<(), ParseError> {
+    debug!("Parsing file {:?}", path);
 
     // Check for multibyte characters
     if source.chars().count() != source.len() {
         return Err(ParseError::MultibyteError {
-            file: path.clone(),
+            file: path.to_path_buf(),
             backtrace: backtrace::Backtrace::force_capture(),
         });
     }
 
     // Parse the source code
+    // CRLF line endings are normalized before parsing, but the visitors are given the original
+    // source. Visitors convert the line and column of nodes into byte offsets in the original
+    // source, which are the same with either line ending, so spans land on the right bytes.
+    let normalized = if source.contains("\r\n") {
+        Cow::Owned(source.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(source)
+    };
     let arena = Arena::new();
     let options = ExtensionOptions::builder()
-        .front_matter_delimiter("---".to_string())
+        .maybe_front_matter_delimiter(
+            Some(markdown.front_matter_delimiter.clone()).filter(|delimiter| !delimiter.is_empty()),
+        )
         .wikilinks_title_before_pipe(true)
+        .table(markdown.table)
+        .footnotes(markdown.footnotes)
+        .tasklist(markdown.tasklist)
+        .strikethrough(markdown.strikethrough)
+        .autolink(markdown.autolink)
         .build();
     let root = parse_document(
         &arena,
-        &source,
+        &normalized,
         &Options {
             extension: options,
             ..Default::default()
         },
     );
 
+    let source_map = SourceMap::new(source);
+
     // Visit the root
     for visitor in visitors.clone() {
         let mut visitor_cell = (*visitor).borrow_mut();
         visitor_cell
-            .visit(root, &source)
+            .visit(root, &source_map)
             .map_err(|source| ParseError::VisitError {
-                file: path.clone(),
+                file: path.to_path_buf(),
                 source,
             })?;
     }
@@ -163,9 +278,9 @@ pub fn parse(path: &PathBuf, visitors: Vec<Rc<RefCell<dyn Visitor>>>) -> Result<
         for visitor in visitors.clone() {
             let mut visitor_cell = (*visitor).borrow_mut();
             visitor_cell
-                .visit(node, &source)
+                .visit(node, &source_map)
                 .map_err(|source| ParseError::VisitError {
-                    file: path.clone(),
+                    file: path.to_path_buf(),
                     source,
                 })?;
         }
@@ -174,9 +289,9 @@ pub fn parse(path: &PathBuf, visitors: Vec<Rc<RefCell<dyn Visitor>>>) -> Result<
     for visitor in visitors {
         let mut visitor_cell = (*visitor).borrow_mut();
         visitor_cell
-            .finalize_file(&source, path)
+            .finalize_file(source, path)
             .map_err(|source| ParseError::FinalizeError {
-                file: path.clone(),
+                file: path.to_path_buf(),
                 source,
             })?;
     }
@@ -184,3 +299,67 @@ pub fn parse(path: &PathBuf, visitors: Vec<Rc<RefCell<dyn Visitor>>>) -> Result<
     // The visitors are modified in place, no need to return anything
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, path::Path, rc::Rc};
+
+    use comrak::{arena_tree::Node, nodes::Ast};
+
+    use crate::{
+        file::content::{
+            source_map::SourceMap,
+            wikilink::{Alias, Wikilink, WikilinkVisitor},
+        },
+        rules::ErrorCode,
+    };
+
+    use super::{parse_source, FinalizeError, Finalized, VisitError, Visitor};
+
+    /// A [`WikilinkVisitor`] clears itself each file, so record what it found before it does
+    #[derive(Default)]
+    struct RecordingVisitor {
+        wikilink_visitor: WikilinkVisitor,
+        found: Vec<Wikilink>,
+    }
+
+    impl Visitor for RecordingVisitor {
+        fn name(&self) -> &'static str {
+            "RecordingVisitor"
+        }
+        fn _visit(
+            &mut self,
+            node: &Node<RefCell<Ast>>,
+            source: &SourceMap,
+        ) -> Result<(), VisitError> {
+            self.wikilink_visitor.visit(node, source)
+        }
+        fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+            self.found.extend(self.wikilink_visitor.wikilinks.clone());
+            self.wikilink_visitor.finalize_file(source, path)
+        }
+        fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+            Ok(Finalized::default())
+        }
+    }
+
+    /// The source does not need to exist on disk
+    #[test]
+    fn test_parse_source_in_memory() {
+        let visitor = Rc::new(RefCell::new(RecordingVisitor::default()));
+        let visitors: Vec<Rc<RefCell<dyn Visitor>>> = vec![visitor.clone()];
+        parse_source(
+            Path::new("does/not/exist.md"),
+            "- [[Lorem]] ipsum #dolor",
+            visitors,
+        )
+        .expect("Just a test");
+        let aliases: Vec<Alias> = visitor
+            .borrow()
+            .found
+            .iter()
+            .map(|x| x.alias.clone())
+            .collect();
+        assert_eq!(aliases, vec![Alias::new("lorem"), Alias::new("dolor")]);
+    }
+}
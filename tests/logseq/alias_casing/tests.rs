@@ -0,0 +1,79 @@
+//! Aliases written in an unusual case, see [`mdlinker::rules::alias_casing::AliasCasing`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    file::provider::InMemory,
+    linter::Linter,
+    rules::ReportTrait,
+    testutil::{Page, Vault},
+};
+use tracing::info;
+
+fn lint(files: InMemory) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// The front matter alias and the wikilinks are counted, the tag is not
+#[test]
+fn minority_casing() {
+    info!("minority_casing");
+    let files = InMemory::new()
+        .with_file(
+            "pages/lorem.md",
+            "---\nalias: GitHub\n---\n- [[GitHub]] #github\n",
+        )
+        .with_file(
+            "pages/ipsum.md",
+            "- [[GitHub]] and [[Github]]\n- [[Github]]\n",
+        );
+    let alias_casings = lint(files).alias_casings();
+    assert_eq!(alias_casings.len(), 1, "{alias_casings:#?}");
+    let alias_casing = &alias_casings[0];
+    assert_eq!(alias_casing.path(), PathBuf::from("pages/ipsum.md"));
+    assert_eq!(alias_casing.written(), "Github");
+    assert_eq!(alias_casing.usual(), "GitHub");
+    assert_eq!(
+        alias_casing.counts(),
+        [("GitHub".to_owned(), 3), ("Github".to_owned(), 2)]
+    );
+    assert_eq!(alias_casing.line_column(), Some((1, 20)));
+}
+
+/// There is no usual casing when two are as common
+#[test]
+fn tie() {
+    info!("tie");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[GitHub]]\n")
+        .with_file("pages/ipsum.md", "- [[Github]]\n");
+    assert!(lint(files).alias_casings().is_empty());
+}
+
+#[test]
+fn fix() {
+    info!("fix");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_line("- [[GitHub]] [[GitHub]]"))
+        .with_page(&Page::new("ipsum").with_link("Github").with_link("Github"))
+        .with_page(&Page::new("dolor").with_link("GitHub"))
+        .write()
+        .expect("The temporary directory is writable");
+    let linter = vault.linter();
+    let alias_casings = linter
+        .run()
+        .expect("Linting should not fail")
+        .alias_casings();
+    assert_eq!(alias_casings.len(), 1, "{alias_casings:#?}");
+    assert_eq!(alias_casings[0].fix(linter.config()).unwrap(), Some(()));
+    assert_eq!(
+        vault.read("pages/ipsum.md").unwrap(),
+        "- [[GitHub]]\n- [[GitHub]]\n"
+    );
+    assert!(linter.run().unwrap().alias_casings().is_empty());
+}
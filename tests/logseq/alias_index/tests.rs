@@ -0,0 +1,121 @@
+//! Where each alias occurs in the vault, see [`mdlinker::alias_index::AliasIndex`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    alias_index::{Occurrence, OccurrenceKind},
+    aliases::AliasSource,
+    file::{content::wikilink::Alias, provider::InMemory},
+    linter::Linter,
+    OutputReport,
+};
+use tracing::info;
+
+const IPSUM: &str = "- [[lorem]] and #dolor\n- Lorem and sit\n";
+
+fn lint() -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "---\nalias: dolor\n---\n- A page\n")
+        .with_file("pages/sit.md", "---\nno-autolink: true\n---\n- A page\n")
+        .with_file("pages/ipsum.md", IPSUM);
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+fn occurrence(alias: &str, file: &str, kind: OccurrenceKind, range: &str) -> Occurrence {
+    let range = (!range.is_empty()).then(|| {
+        let start = IPSUM.find(range).expect("In the page");
+        start..start + range.len()
+    });
+    Occurrence {
+        alias: Alias::new(alias),
+        file: PathBuf::from(file),
+        kind,
+        range,
+    }
+}
+
+/// Definitions, then wikilinks, tags and mentions in the order they appear
+#[test]
+fn in_file() {
+    info!("in_file");
+    let report = lint();
+    let index = report.alias_index();
+    assert_eq!(
+        index.in_file(&PathBuf::from("pages/ipsum.md")),
+        [
+            occurrence(
+                "ipsum",
+                "pages/ipsum.md",
+                OccurrenceKind::Definition(AliasSource::Filename),
+                ""
+            ),
+            occurrence(
+                "lorem",
+                "pages/ipsum.md",
+                OccurrenceKind::Wikilink,
+                "[[lorem]]"
+            ),
+            occurrence("dolor", "pages/ipsum.md", OccurrenceKind::Tag, "dolor"),
+            occurrence("lorem", "pages/ipsum.md", OccurrenceKind::Mention, "Lorem"),
+            occurrence("sit", "pages/ipsum.md", OccurrenceKind::Mention, "sit"),
+        ]
+    );
+}
+
+/// Every occurrence of an alias across the vault, front matter definitions included
+#[test]
+fn occurrences() {
+    info!("occurrences");
+    let report = lint();
+    let alias = Alias::new("dolor");
+    let kinds: Vec<(PathBuf, OccurrenceKind)> = report
+        .alias_index()
+        .occurrences(&alias)
+        .map(|occurrence| (occurrence.file.clone(), occurrence.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (PathBuf::from("pages/ipsum.md"), OccurrenceKind::Tag),
+            (
+                PathBuf::from("pages/lorem.md"),
+                OccurrenceKind::Definition(AliasSource::FrontMatter)
+            ),
+        ]
+    );
+}
+
+/// Links by any alias of the file
+#[test]
+fn links_to() {
+    info!("links_to");
+    let report = lint();
+    let lorem = PathBuf::from("pages/lorem.md");
+    let links: Vec<&Alias> = report
+        .alias_index()
+        .links_to(&lorem)
+        .map(|occurrence| &occurrence.alias)
+        .collect();
+    assert_eq!(links, [&Alias::new("lorem"), &Alias::new("dolor")]);
+}
+
+/// Aliases of pages with `no-autolink` are still mentioned, but aren't unlinked text
+#[test]
+fn no_autolink() {
+    info!("no_autolink");
+    let report = lint();
+    let sit = Alias::new("sit");
+    assert!(report
+        .alias_index()
+        .occurrences(&sit)
+        .any(|occurrence| occurrence.kind == OccurrenceKind::Mention));
+    assert!(report
+        .unlinked_texts()
+        .iter()
+        .all(|unlinked_text| unlinked_text.alias() != &sit));
+}
@@ -0,0 +1,73 @@
+//! [`mdlinker::aliases`], run over the assets of the query tests
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::aliases::{AliasEntry, AliasSource};
+use mdlinker::{aliases, config, file::content::wikilink::Alias};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/query/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/query/assets/journals/");
+}
+
+fn get_aliases() -> mdlinker::aliases::Aliases {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    let aliases = aliases(&config).expect("There should have been no error.");
+    debug!("{}", aliases.to_tsv());
+    aliases
+}
+
+/// The page named ipsum and the page with the alias ipsum are both listed as duplicates,
+/// and only one of them is resolved
+#[test]
+fn duplicates() {
+    info!("duplicates");
+    let aliases = get_aliases();
+    let ipsum: Vec<&AliasEntry> = aliases
+        .0
+        .iter()
+        .filter(|entry| entry.alias == Alias::new("ipsum"))
+        .collect();
+    assert_eq!(ipsum.len(), 2, "{ipsum:?}");
+    assert!(ipsum.iter().all(|entry| entry.duplicate));
+    assert_eq!(ipsum.iter().filter(|entry| entry.resolved).count(), 1);
+    assert!(
+        ipsum
+            .iter()
+            .any(|entry| entry.file == PAGES.join("ipsum.md")
+                && entry.source == AliasSource::Filename)
+    );
+    assert!(ipsum
+        .iter()
+        .any(|entry| entry.file == PAGES.join("lorem.md")
+            && entry.source == AliasSource::FrontMatter));
+}
+
+/// Aliases defined once resolve to their file
+#[test]
+fn unique() {
+    info!("unique");
+    let aliases = get_aliases();
+    assert!(aliases.0.contains(&AliasEntry {
+        alias: Alias::new("lorem"),
+        file: PAGES.join("lorem.md"),
+        source: AliasSource::Filename,
+        resolved: true,
+        duplicate: false,
+    }));
+    let tsv = aliases.to_tsv();
+    assert!(tsv.starts_with("alias\tfile\tsource\tresolved\tduplicate\n"));
+    assert!(tsv.contains(&format!(
+        "lorem\t{}\tfilename\ttrue\tfalse\n",
+        PAGES.join("lorem.md").display()
+    )));
+    let json = aliases.to_json().expect("Serializes");
+    assert!(json.contains("\"source\": \"front_matter\""), "{json}");
+}
@@ -0,0 +1,118 @@
+//! Path-style wikilinks which match more than one file
+use std::path::PathBuf;
+
+use itertools::Itertools;
+use mdlinker::{
+    config::LinkResolution,
+    testutil::{Page, Vault, PAGES_DIRECTORY},
+    OutputReport,
+};
+use tracing::info;
+
+use crate::common::get_report;
+
+/// A vault with the pages `work/projects/lorem.md`, `home/projects/lorem.md`, `Notes/Ipsum.md`
+/// and `notes/ipsum.md`, and a page linking to them
+fn run(link_resolution: LinkResolution) -> OutputReport {
+    let vault = Vault::new()
+        .with_page(&Page::new("work/projects/lorem").with_line("- Work"))
+        .with_page(&Page::new("home/projects/lorem").with_line("- Home"))
+        .with_page(&Page::new("Notes/Ipsum").with_line("- Ipsum"))
+        .with_page(&Page::new("notes/ipsum").with_line("- ipsum"))
+        .with_page(
+            &Page::new("index")
+                .with_line("- [[projects/lorem]] [[work/projects/lorem]] [[notes/ipsum]]"),
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    // Absolute paths start at the pages directory
+    config.root_directory = vault.pages_directory();
+    config.link_resolution = link_resolution;
+    get_report(&[], Some(config))
+}
+
+#[test]
+fn shortest_path_suffix_is_ambiguous() {
+    info!("shortest_path_suffix_is_ambiguous");
+    let report = run(LinkResolution::ShortestPath);
+    let ambiguous = report
+        .ambiguous_wikilinks()
+        .into_iter()
+        .map(|ambiguous| {
+            (
+                ambiguous.alias().to_string(),
+                ambiguous
+                    .candidates()
+                    .iter()
+                    .map(|candidate| candidate.to_string_lossy().replace('\\', "/"))
+                    .map(|candidate| {
+                        candidate
+                            .split_once(&format!("{PAGES_DIRECTORY}/"))
+                            .map(|(_, path)| path.to_string())
+                            .unwrap_or(candidate.clone())
+                    })
+                    .collect_vec(),
+            )
+        })
+        .sorted()
+        .collect_vec();
+    assert_eq!(
+        ambiguous,
+        vec![
+            (
+                "notes/ipsum".to_string(),
+                vec!["Notes/Ipsum.md".to_string(), "notes/ipsum.md".to_string()]
+            ),
+            (
+                "projects/lorem".to_string(),
+                vec![
+                    "home/projects/lorem.md".to_string(),
+                    "work/projects/lorem.md".to_string()
+                ]
+            ),
+        ]
+    );
+    // The full path is not ambiguous, and ambiguous links are not also broken
+    assert!(report.broken_wikilinks().is_empty());
+}
+
+/// The paths which differ only in case are still ambiguous from the root
+#[test]
+fn absolute_case_only_is_ambiguous() {
+    info!("absolute_case_only_is_ambiguous");
+    let report = run(LinkResolution::Absolute);
+    let aliases = report
+        .ambiguous_wikilinks()
+        .into_iter()
+        .map(|ambiguous| ambiguous.alias().to_string())
+        .collect_vec();
+    assert_eq!(aliases, vec!["notes/ipsum"]);
+    let broken = report
+        .broken_wikilinks()
+        .into_iter()
+        .map(|broken| broken.alias().to_string())
+        .collect_vec();
+    assert_eq!(broken, vec!["projects/lorem"]);
+}
+
+/// The candidates are kept when serialized
+#[test]
+fn json_round_trip() {
+    info!("json_round_trip");
+    let report = run(LinkResolution::ShortestPath);
+    let json = serde_json::to_string(&report).expect("Reports serialize");
+    let deserialized: OutputReport = serde_json::from_str(&json).expect("Reports deserialize");
+    let before: Vec<PathBuf> = report
+        .ambiguous_wikilinks()
+        .iter()
+        .flat_map(|ambiguous| ambiguous.candidates().to_vec())
+        .collect();
+    let after: Vec<PathBuf> = deserialized
+        .ambiguous_wikilinks()
+        .iter()
+        .flat_map(|ambiguous| ambiguous.candidates().to_vec())
+        .collect();
+    assert_eq!(before.len(), 4);
+    assert_eq!(before, after);
+}
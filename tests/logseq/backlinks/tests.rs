@@ -0,0 +1,64 @@
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::{backlinks, config};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/backlinks/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/backlinks/assets/journals/");
+}
+
+fn get_backlinks() -> backlinks::Backlinks {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    backlinks(&config).expect("There should have been no error.")
+}
+
+/// Wikilinks and tags to the page or any of its aliases are backlinks
+#[test]
+fn lorem_backlinks() {
+    info!("lorem_backlinks");
+    let backlinks = get_backlinks();
+    debug!("{backlinks:?}");
+    let lorem = backlinks
+        .get(&PAGES.join("lorem.md"))
+        .expect("Every page is in the index");
+    let mut found: Vec<(PathBuf, String, usize, usize)> = lorem
+        .iter()
+        .map(|x| (x.source.clone(), x.alias.clone(), x.line, x.column))
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (JOURNALS.join("2024_11_01.md"), "ipsum".to_owned(), 1, 24),
+            (JOURNALS.join("2024_11_01.md"), "lorem".to_owned(), 1, 9),
+            (PAGES.join("dolor.md"), "lorem".to_owned(), 1, 7),
+        ]
+    );
+}
+
+/// Pages nothing links to are still listed
+#[test]
+fn dolor_has_no_backlinks() {
+    info!("dolor_has_no_backlinks");
+    let backlinks = get_backlinks();
+    assert_eq!(backlinks.get(&PAGES.join("dolor.md")), Some(&vec![]));
+}
+
+#[test]
+fn backlinks_json() {
+    info!("backlinks_json");
+    let json: serde_json::Value =
+        serde_json::from_str(&get_backlinks().to_json().expect("Serializable"))
+            .expect("Valid json");
+    let lorem = &json[PAGES.join("lorem.md").to_string_lossy().as_ref()];
+    assert_eq!(lorem.as_array().map(Vec::len), Some(3));
+    assert!(lorem[0]["offset"].is_u64());
+}
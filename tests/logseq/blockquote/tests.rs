@@ -0,0 +1,89 @@
+//! Unlinked text in blockquotes and callouts, see [`mdlinker::config::UnlinkedTextOptions`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::UnlinkedTextOptions, file::provider::InMemory, linter::Linter, OutputReport,
+};
+use tracing::info;
+
+const SOURCE: &str = "\
+Ipsum in a paragraph
+
+> Ipsum in a blockquote
+
+> [!note] Ipsum in a callout
+> And ipsum in its body
+";
+
+fn run(options: UnlinkedTextOptions) -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", SOURCE)
+        .with_file("pages/ipsum.md", "- Ipsum\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .unlinked_text_options(options)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// The lines of the unlinked texts of `ipsum` in `lorem.md`
+fn ipsum_lines(report: &OutputReport) -> Vec<usize> {
+    let mut lines: Vec<usize> = report
+        .unlinked_texts()
+        .iter()
+        .filter(|x| mdlinker::rules::ReportTrait::path(*x).ends_with("lorem.md"))
+        .filter_map(|x| mdlinker::rules::ReportTrait::line_column(x).map(|(line, _)| line))
+        .collect();
+    lines.sort_unstable();
+    lines
+}
+
+/// Blockquotes and callouts are linted by default
+#[test]
+fn default() {
+    info!("default");
+    let report = run(UnlinkedTextOptions::default());
+    assert_eq!(ipsum_lines(&report), vec![1, 3, 5, 6]);
+}
+
+/// Skipping blockquotes skips callouts too
+#[test]
+fn skip_blockquotes() {
+    info!("skip_blockquotes");
+    let report = run(UnlinkedTextOptions {
+        skip_blockquotes: true,
+        ..UnlinkedTextOptions::default()
+    });
+    assert_eq!(ipsum_lines(&report), vec![1]);
+}
+
+/// Skipping callouts keeps other blockquotes
+#[test]
+fn skip_callouts() {
+    info!("skip_callouts");
+    let report = run(UnlinkedTextOptions {
+        skip_callouts: true,
+        ..UnlinkedTextOptions::default()
+    });
+    assert_eq!(ipsum_lines(&report), vec![1, 3]);
+}
+
+/// The options are read from an `[unlinked_text]` table
+#[test]
+fn config() {
+    info!("config");
+    let config: mdlinker::config::file::Config = toml::from_str(
+        "pages_directory = \"pages\"\nother_directories = []\n\n[unlinked_text]\nskip_callouts = true\n",
+    )
+    .expect("The config is valid");
+    assert_eq!(
+        config.unlinked_text,
+        Some(UnlinkedTextOptions {
+            skip_callouts: true,
+            ..UnlinkedTextOptions::default()
+        })
+    );
+}
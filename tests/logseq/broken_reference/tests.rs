@@ -0,0 +1,57 @@
+//! Logseq block references and query pages which don't exist, see
+//! [`mdlinker::rules::broken_reference::BrokenReference`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{broken_reference::ReferenceKind, ReportTrait},
+};
+use tracing::info;
+
+const FOUND: &str = "64f1c2a0-1b2c-4d5e-8f90-a1b2c3d4e5f6";
+const MISSING: &str = "00000000-1b2c-4d5e-8f90-a1b2c3d4e5f6";
+
+fn lint(files: InMemory) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// Block ids are found in files parsed after the reference too
+#[test]
+fn block_references() {
+    info!("block_references");
+    let files = InMemory::new()
+        .with_file(
+            "pages/ipsum.md",
+            &format!("- see (({FOUND}))\n- {{{{embed (({MISSING}))}}}}\n- `(({MISSING}))`\n"),
+        )
+        .with_file("pages/lorem.md", &format!("- a block\n  id:: {FOUND}\n"));
+    let broken_references = lint(files).broken_references();
+    assert_eq!(broken_references.len(), 1, "{broken_references:#?}");
+    let broken_reference = &broken_references[0];
+    assert_eq!(broken_reference.kind(), ReferenceKind::Block);
+    assert_eq!(broken_reference.target(), MISSING);
+    assert_eq!(broken_reference.line_column(), Some((2, 13)));
+}
+
+#[test]
+fn query_pages() {
+    info!("query_pages");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- lorem\n")
+        .with_file(
+            "pages/ipsum.md",
+            "- {{query (and (page \"Lorem\") (page dolor))}}\n",
+        );
+    let broken_references = lint(files).broken_references();
+    assert_eq!(broken_references.len(), 1, "{broken_references:#?}");
+    assert_eq!(broken_references[0].kind(), ReferenceKind::Page);
+    assert_eq!(broken_references[0].target(), "dolor");
+    assert_eq!(broken_references[0].line_column(), Some((1, 37)));
+}
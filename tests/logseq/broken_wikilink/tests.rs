@@ -1,9 +1,12 @@
 use lazy_static::lazy_static;
-use mdlinker::rules::broken_wikilink;
+use mdlinker::{config::NewFilesDirectory, linter::Linter, rules::broken_wikilink};
 
 use crate::common::get_report;
-use log::{debug, info};
-use mdlinker::rules::filter_code;
+use mdlinker::rules::{filter_code, ReportTrait};
+use mdlinker::testutil::{Page, Vault};
+use miette::Severity;
+use std::{fs, path::Path};
+use tracing::{debug, info};
 
 use itertools::Itertools;
 
@@ -178,3 +181,141 @@ fn dolor_does_not_exist_and_is_wikilink_in_foo_span() {
     assert_eq!(err.wikilink.offset(), 62);
     assert_eq!(err.wikilink.len(), 5);
 }
+
+#[test]
+fn dolor_does_not_exist_and_is_wikilink_in_foo_getters() {
+    info!("dolor_does_not_exist_and_is_wikilink_in_foo_getters");
+    let report = get_report(PATHS.as_slice(), None);
+    let code = format!("{}::foo::dolor", broken_wikilink::CODE);
+    let err_list = filter_code(report.broken_wikilinks(), &code.clone().into());
+    let err = err_list.iter().exactly_one().unwrap();
+    assert_eq!(
+        err.path(),
+        Path::new("./tests/logseq/broken_wikilink/assets/pages/foo.md")
+    );
+    assert_eq!(err.span(), Some(err.wikilink));
+    assert_eq!(err.line_column(), Some((5, 31)));
+    assert_eq!(err.alias().to_string(), "dolor");
+
+    // The same getters work on any report
+    let report = report
+        .reports
+        .iter()
+        .filter(|report| report.id().0 == code)
+        .exactly_one()
+        .unwrap();
+    assert_eq!(report.line_column(), Some((5, 31)));
+    assert_eq!(report.severity(), Severity::Error);
+}
+
+/// The closest existing aliases are suggested for a typo
+#[test]
+fn suggestions() {
+    info!("suggestions");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/lorem.md", "- Lorem\n")
+        .with_file("pages/lorem ipsum.md", "- Lorem ipsum\n")
+        .with_file("pages/dolor.md", "- Dolor\n")
+        .with_file("pages/notes.md", "- [[lorm]] [[dolorr]] [[zzz]]\n");
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let suggestions = report
+        .broken_wikilinks()
+        .iter()
+        .map(|broken_wikilink| {
+            (
+                broken_wikilink.alias().to_string(),
+                broken_wikilink
+                    .suggestions()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect_vec(),
+            )
+        })
+        .sorted()
+        .collect_vec();
+    assert_eq!(
+        suggestions,
+        vec![
+            ("dolorr".to_string(), vec!["dolor".to_string()]),
+            (
+                "lorm".to_string(),
+                vec!["lorem".to_string(), "lorem ipsum".to_string()]
+            ),
+            ("zzz".to_string(), vec![]),
+        ]
+    );
+    let lorm = report
+        .broken_wikilinks()
+        .into_iter()
+        .find(|broken_wikilink| broken_wikilink.alias().to_string() == "lorm")
+        .expect("lorm is broken");
+    let help = miette::Diagnostic::help(&lorm)
+        .expect("There is advice")
+        .to_string();
+    assert!(
+        help.contains("Did you mean: [[lorem]], [[lorem ipsum]]?"),
+        "{help}"
+    );
+}
+
+/// The page for a nested alias is named with `alias_to_filename`, not put in a directory
+#[test]
+fn fix_nested_alias() {
+    info!("fix_nested_alias");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_line("- Lorem [[foo/bar]]"))
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    let mut linter = Linter::from_config(config);
+    linter.unlinked_text = false;
+    let report = linter.run().expect("There should have been no error.");
+
+    assert!(report.broken_wikilinks().is_empty(), "{:?}", report.reports);
+    assert!(vault.pages_directory().join("foo___bar.md").is_file());
+    assert!(!vault.pages_directory().join("foo").exists());
+}
+
+/// Pages for broken wikilinks in journals go to the pages, and those in projects next to the link
+#[test]
+fn fix_new_files_directories() {
+    info!("fix_new_files_directories");
+    let vault = Vault::new()
+        .with_journal(&Page::new("2024_11_01").with_link("alpha"))
+        .with_file("projects/lorem/plan.md", "- [[beta]]\n")
+        .with_page(&Page::new("ipsum").with_link("gamma"))
+        .write()
+        .expect("The temporary directory is writable");
+    let dir = vault.root();
+    fs::create_dir_all(dir.join("inbox")).expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.other_directories.push(dir.join("projects"));
+    config.new_files_directory = Some(dir.join("inbox"));
+    config.new_files_directories = vec![
+        NewFilesDirectory {
+            glob: "**/journals/**".to_owned(),
+            directory: Some(dir.join("pages")),
+        },
+        NewFilesDirectory {
+            glob: "**/projects/**".to_owned(),
+            directory: None,
+        },
+    ];
+    config.fix = true;
+    config.no_vcs = true;
+    let mut linter = Linter::from_config(config);
+    linter.unlinked_text = false;
+    linter.run().expect("There should have been no error.");
+
+    assert!(dir.join("pages/alpha.md").is_file());
+    assert!(dir.join("projects/lorem/beta.md").is_file());
+    assert!(dir.join("inbox/gamma.md").is_file());
+}
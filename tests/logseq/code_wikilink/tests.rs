@@ -0,0 +1,49 @@
+//! Wikilinks inside code, see [`mdlinker::rules::code_wikilink::CodeWikilink`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+fn lint(files: InMemory, code_wikilink: bool) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .code_wikilink(code_wikilink)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+fn files() -> InMemory {
+    InMemory::new()
+        .with_file("pages/lorem.md", "- lorem\n")
+        .with_file(
+            "pages/ipsum.md",
+            "- [[lorem]] and `[[lorem]]`\n- ```\n  [[dolor|lorem]]\n  ```\n",
+        )
+}
+
+#[test]
+fn inline_code_and_code_blocks() {
+    info!("inline_code_and_code_blocks");
+    let mut code_wikilinks = lint(files(), true).code_wikilinks();
+    code_wikilinks.sort_by_key(ReportTrait::line_column);
+    let found: Vec<_> = code_wikilinks
+        .iter()
+        .map(|report| (report.wikilink(), report.line_column()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("[[lorem]]", Some((1, 18))),
+            ("[[dolor|lorem]]", Some((3, 3)))
+        ]
+    );
+}
+
+#[test]
+fn off_by_default() {
+    info!("off_by_default");
+    assert!(lint(files(), false).code_wikilinks().is_empty());
+}
@@ -7,13 +7,17 @@ use mdlinker::{
 };
 
 use std::sync::Once;
+use tracing_subscriber::EnvFilter;
 
 static INIT: Once = Once::new();
 
 /// Setup function that is only run once, even if called multiple times.
 fn setup() {
     INIT.call_once(|| {
-        env_logger::init();
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_test_writer()
+            .init();
     });
 }
 
@@ -0,0 +1,106 @@
+//! Reports inside completed tasks, see [`mdlinker::config::Config::completed_tasks`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::NamespaceRule, file::provider::InMemory, linter::Linter, rules::Report, OutputReport,
+};
+use miette::Severity;
+use tracing::info;
+
+const SOURCE: &str = "\
+- [x] Call [[alice]]
+- [ ] Call [[bob]]
+- DONE Email [[carol]]
+  - With [[dave]]
+- TODO Email [[erin]]
+";
+
+fn run(completed_tasks: Option<NamespaceRule>) -> (Linter, OutputReport) {
+    let files = InMemory::new().with_file("pages/lorem.md", SOURCE);
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .maybe_completed_tasks(completed_tasks)
+        .build()
+        .expect("The defaults are valid");
+    let report = linter.run().expect("There should have been no error.");
+    (linter, report)
+}
+
+/// The aliases of the broken wikilinks, sorted
+fn broken(report: &OutputReport) -> Vec<String> {
+    let mut aliases: Vec<String> = report
+        .broken_wikilinks()
+        .iter()
+        .map(|x| x.alias().to_string())
+        .collect();
+    aliases.sort();
+    aliases
+}
+
+/// Off drops the reports inside completed tasks, and the items under them
+#[test]
+fn off() {
+    info!("off");
+    let (_, report) = run(Some(NamespaceRule::Off));
+    assert_eq!(broken(&report), vec!["bob", "erin"]);
+}
+
+/// A severity keeps the reports, with that severity
+#[test]
+fn severity() {
+    info!("severity");
+    let (linter, report) = run(Some(NamespaceRule::Advice));
+    assert_eq!(
+        broken(&report),
+        vec!["alice", "bob", "carol", "dave", "erin"]
+    );
+    for x in report.broken_wikilinks() {
+        let alias = x.alias().to_string();
+        let x = Report::ThirdPass(mdlinker::rules::ThirdPassReport::BrokenWikilink(x));
+        let completed = ["alice", "carol", "dave"].contains(&alias.as_str());
+        assert_eq!(report.in_completed_task(&x), completed, "{alias}");
+        let expected = if completed {
+            Severity::Advice
+        } else {
+            Severity::Error
+        };
+        assert_eq!(report.severity(linter.config(), &x), expected, "{alias}");
+    }
+}
+
+/// Unset, reports inside completed tasks are like any other
+#[test]
+fn unset() {
+    info!("unset");
+    let (linter, report) = run(None);
+    assert_eq!(
+        broken(&report),
+        vec!["alice", "bob", "carol", "dave", "erin"]
+    );
+    for x in report.broken_wikilinks() {
+        let x = Report::ThirdPass(mdlinker::rules::ThirdPassReport::BrokenWikilink(x));
+        assert!(!report.in_completed_task(&x));
+        assert_eq!(report.severity(linter.config(), &x), Severity::Error);
+    }
+}
+
+/// Completed task items are found with and without the tasklist extension
+#[test]
+fn tasklist() {
+    info!("tasklist");
+    let files = InMemory::new().with_file("pages/lorem.md", SOURCE);
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .completed_tasks(NamespaceRule::Off)
+        .markdown(mdlinker::config::MarkdownExtensions {
+            tasklist: true,
+            ..mdlinker::config::MarkdownExtensions::default()
+        })
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert_eq!(broken(&report), vec!["bob", "erin"]);
+}
@@ -0,0 +1,101 @@
+//! Config files in yaml and json, see [`mdlinker::config::file::Format`]
+use std::{fs, path::PathBuf};
+
+use mdlinker::{
+    config::{
+        self,
+        cli::Config as CliConfig,
+        file::{self, Config as FileConfig, Format},
+        ExcludeEntry, SimilarityAlgorithm,
+    },
+    testutil::Vault,
+};
+use tracing::info;
+
+/// The default `mdlinker.toml` finds the config in the other formats, which have the same keys
+#[test]
+fn yaml_and_json() {
+    info!("yaml_and_json");
+    let vault = Vault::new()
+        .with_file(
+            "mdlinker.yaml",
+            "pages_directory: pages\nother_directories: [journals]\nsimilarity_algorithm: token_set\nexclude:\n  - name::similar::*\n  - code: content::wikilink::broken::*\n    expires: 2999-01-01\n",
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let dir = vault.root();
+    let path = file::find(&dir.join("mdlinker.toml"));
+    assert_eq!(path, dir.join("mdlinker.yaml"));
+    assert_eq!(Format::from_path(&path), Format::Yaml);
+    let yaml = FileConfig::new(&path).expect("The yaml is valid");
+    assert_eq!(yaml.pages_directory, PathBuf::from("pages"));
+    assert_eq!(
+        yaml.similarity_algorithm,
+        Some(SimilarityAlgorithm::TokenSet)
+    );
+    assert_eq!(yaml.exclude.len(), 2);
+    assert!(yaml.exclude[1].expires().is_some());
+
+    fs::write(
+        dir.join("mdlinker.json"),
+        r#"{"pages_directory": "pages", "other_directories": ["journals"], "similarity_algorithm": "token_set"}"#,
+    )
+    .expect("Temp dir is writable");
+    let json = FileConfig::new(&dir.join("mdlinker.json")).expect("The json is valid");
+    assert_eq!(json.other_directories, yaml.other_directories);
+    assert_eq!(json.similarity_algorithm, yaml.similarity_algorithm);
+}
+
+/// Excludes are added to yaml and json config files, which keep the order of their keys
+#[test]
+fn save_config() {
+    info!("save_config");
+    let vault = Vault::new()
+        .with_file(
+            "mdlinker.yaml",
+            "pages_directory: pages\nother_directories: []\nexclude:\n  - name::similar::*\n",
+        )
+        .with_file(
+            "mdlinker.json",
+            "{\n  \"pages_directory\": \"pages\",\n  \"other_directories\": []\n}\n",
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let dir = vault.root();
+    for file in ["mdlinker.yaml", "mdlinker.json"] {
+        let path = dir.join(file);
+        let mut file_config = FileConfig::new(&path).expect("The config is valid");
+        file_config.exclude.push(ExcludeEntry::Pattern(
+            "content::wikilink::broken::*".to_owned(),
+        ));
+        file_config
+            .ignore_word_pairs
+            .push(("lorem".to_owned(), "ipsum".to_owned()));
+        let config = config::Config::builder()
+            .pages_directory(PathBuf::from("pages"))
+            .file_config(file_config)
+            .cli_config(CliConfig {
+                config_path: path.clone(),
+                ..CliConfig::default()
+            })
+            .build();
+        config.save_config().expect("The config is writable");
+        let saved = fs::read_to_string(&path).expect("The config was saved");
+        assert!(
+            saved.find("pages_directory") < saved.find("exclude"),
+            "{saved}"
+        );
+        let saved = FileConfig::new(&path).expect("The saved config is valid");
+        assert_eq!(
+            saved.exclude.last(),
+            Some(&ExcludeEntry::Pattern(
+                "content::wikilink::broken::*".to_owned()
+            )),
+            "{file}"
+        );
+        assert_eq!(
+            saved.ignore_word_pairs,
+            vec![("lorem".to_owned(), "ipsum".to_owned())]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Filenames with lookalike or invisible characters, see
+//! [`mdlinker::rules::confusable_character::ConfusableCharacter`]
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::Arc};
+
+use hashbrown::HashMap;
+use mdlinker::{
+    budget::SourceBudget,
+    file::{content::wikilink::Alias, provider::InMemory},
+    linter::Linter,
+    rules::{
+        confusable_character::ConfusableCharacterVisitor, Report, ReportTrait, ThirdPassReport,
+    },
+    testutil::Vault,
+    visitor::Visitor,
+};
+use tracing::info;
+
+fn lint(files: InMemory) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// A Cyrillic е in a Latin word
+#[test]
+fn homoglyph() {
+    info!("homoglyph");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[lorem]]\n")
+        .with_file("pages/lorеm.md", "");
+    let confusable_characters = lint(files).confusable_characters();
+    assert_eq!(confusable_characters.len(), 1, "{confusable_characters:#?}");
+    let confusable = &confusable_characters[0];
+    assert_eq!(confusable.path(), PathBuf::from("pages/lorеm.md"));
+    assert_eq!(confusable.filename(), "lorеm");
+    assert_eq!(confusable.alias(), None);
+    assert_eq!(confusable.suggestion(), "lorem");
+    assert_eq!(confusable.span(), None);
+}
+
+/// Zero width spaces are removed, and non-breaking spaces are made plain in the suggestion
+#[test]
+fn invisible() {
+    info!("invisible");
+    let files = InMemory::new().with_file("pages/ipsum\u{00A0}dolor\u{200B}sit.md", "");
+    let confusable_characters = lint(files).confusable_characters();
+    assert_eq!(confusable_characters.len(), 1, "{confusable_characters:#?}");
+    assert_eq!(confusable_characters[0].suggestion(), "ipsum dolorsit");
+}
+
+/// Words all in Cyrillic are not lookalikes
+#[test]
+fn other_script() {
+    info!("other_script");
+    let files = InMemory::new().with_file("pages/hello мир.md", "");
+    assert!(lint(files).confusable_characters().is_empty());
+}
+
+/// The reports of the visitor for a file at `path` with `source`, whose aliases in the alias
+/// table are `aliases`
+/// A file with these characters in its content is not parsed, so the visitor is given it directly
+fn visit(source: &str, path: &str, aliases: &[&str]) -> Vec<Report> {
+    let vault = Vault::new()
+        .write()
+        .expect("The temporary directory is writable");
+    let alias_table: HashMap<Alias, PathBuf> = aliases
+        .iter()
+        .map(|alias| (Alias::new(alias), PathBuf::from(path)))
+        .collect();
+    let mut visitor = ConfusableCharacterVisitor::new(
+        &alias_table,
+        &vault.config(),
+        Rc::new(RefCell::new(SourceBudget::default())),
+    );
+    visitor
+        .finalize_file(source, &PathBuf::from(path))
+        .expect("There should have been no error.");
+    visitor
+        .finalize(&[])
+        .expect("There should have been no error.")
+        .reports
+}
+
+/// An alias in the front matter is labeled where it is written
+#[test]
+fn front_matter_alias() {
+    info!("front_matter_alias");
+    let source = "---\naliases: [ipsum, lorеm]\n---\n";
+    let reports = visit(source, "pages/ipsum.md", &["ipsum", "lorеm"]);
+    assert_eq!(reports.len(), 1, "{reports:#?}");
+    let Report::ThirdPass(ThirdPassReport::ConfusableCharacter(confusable)) = &reports[0] else {
+        panic!("{reports:#?}");
+    };
+    assert_eq!(confusable.filename(), "ipsum");
+    assert_eq!(confusable.alias(), Some("lorеm"));
+    assert_eq!(confusable.suggestion(), "lorem");
+    let offset = source.find("lorеm").expect("The alias is written");
+    assert_eq!(confusable.span().map(|span| span.offset()), Some(offset));
+    assert_eq!(confusable.line_column(), Some((2, 18)));
+}
+
+/// An alias of the alias table which is not found in the front matter, like one written with
+/// escapes, is reported without a label, and the alias of a filename which is reported is not
+#[test]
+fn alias_table() {
+    info!("alias_table");
+    let source = "---\naliases: [\"lor\\u0435m\"]\n---\n";
+    let reports = visit(
+        source,
+        "pages/dolor\u{200B}sit.md",
+        &["lorеm", "dolor\u{200B}sit"],
+    );
+    let mut confusables: Vec<_> = reports
+        .iter()
+        .map(|report| match report {
+            Report::ThirdPass(ThirdPassReport::ConfusableCharacter(confusable)) => confusable,
+            _ => panic!("{reports:#?}"),
+        })
+        .collect();
+    confusables.sort_by_key(|confusable| confusable.alias().map(ToOwned::to_owned));
+    assert_eq!(confusables.len(), 2, "{confusables:#?}");
+    assert_eq!(confusables[0].alias(), None);
+    assert_eq!(confusables[0].suggestion(), "dolorsit");
+    assert_eq!(confusables[1].alias(), Some("lorеm"));
+    assert_eq!(confusables[1].span(), None);
+}
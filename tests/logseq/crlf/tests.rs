@@ -0,0 +1,131 @@
+//! Files with CRLF line endings, as edited on Windows
+//! The assets have a `.gitattributes` so git doesn't convert them
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use mdlinker::rules::duplicate_alias::DuplicateAlias;
+use mdlinker::rules::{broken_wikilink, filter_code, unlinked_text, ReportTrait};
+use mdlinker::testutil::{Vault, JOURNALS_DIRECTORY, PAGES_DIRECTORY};
+use miette::SourceSpan;
+use tracing::{debug, info};
+
+use crate::common::get_report;
+
+lazy_static! {
+    static ref PATHS: Vec<String> = vec![
+        "./tests/logseq/crlf/assets/pages/".to_string(),
+        "./tests/logseq/crlf/assets/journals/".to_string()
+    ];
+}
+
+/// Get the text under a span in a file
+fn spanned(path: &str, span: SourceSpan) -> String {
+    let source = fs::read_to_string(path).expect("This exists at compile time");
+    assert!(
+        source.contains("\r\n"),
+        "{path} should have CRLF line endings"
+    );
+    source[span.offset()..span.offset() + span.len()].to_lowercase()
+}
+
+const JOURNAL: &str = "./tests/logseq/crlf/assets/journals/2024_11_01.md";
+
+#[test]
+fn unlinked_text_spans() {
+    info!("unlinked_text_spans");
+    let report = get_report(PATHS.as_slice(), None);
+    for alias in ["ipsum", "dolor"] {
+        let err = filter_code(
+            report.unlinked_texts(),
+            &format!("{}::2024_11_01::{alias}", unlinked_text::CODE).into(),
+        )
+        .into_iter()
+        .exactly_one()
+        .unwrap();
+        debug!("{err:#?}");
+        assert_eq!(spanned(JOURNAL, err.span), alias);
+    }
+}
+
+#[test]
+fn broken_wikilink_spans() {
+    info!("broken_wikilink_spans");
+    let report = get_report(PATHS.as_slice(), None);
+    assert_eq!(report.broken_wikilinks().len(), 2);
+    for (alias, expected) in [
+        ("consectetur", "consectetur"),
+        ("adipiscing", "[[adipiscing]]"),
+    ] {
+        let err = filter_code(
+            report.broken_wikilinks(),
+            &format!("{}::2024_11_01::{alias}", broken_wikilink::CODE).into(),
+        )
+        .into_iter()
+        .exactly_one()
+        .unwrap();
+        debug!("{err:#?}");
+        assert_eq!(spanned(JOURNAL, err.wikilink), expected);
+    }
+}
+
+#[test]
+fn duplicate_alias_spans() {
+    info!("duplicate_alias_spans");
+    let report = get_report(PATHS.as_slice(), None);
+    assert!(!report.duplicate_aliases().is_empty());
+    for duplicate_alias in &report.duplicate_aliases() {
+        debug!("{duplicate_alias:#?}");
+        match duplicate_alias {
+            DuplicateAlias::FileNameContentDuplicate { src, alias, .. }
+            | DuplicateAlias::FileContentContentDuplicate { src, alias, .. } => {
+                assert_eq!(
+                    spanned(src.name(), alias.expect("The alias is in the file")),
+                    "sit"
+                );
+            }
+            DuplicateAlias::FileNameFileNameDuplicate { .. } => {
+                panic!("No two files are named after the same alias")
+            }
+        }
+    }
+}
+
+/// The fix should wrap exactly the alias, not something one character off per line
+#[test]
+fn unlinked_text_fix() {
+    info!("unlinked_text_fix");
+    let mut vault = Vault::new();
+    for file in ["lorem.md", "amet.md", "elit.md"] {
+        let content =
+            fs::read_to_string(PathBuf::from("./tests/logseq/crlf/assets/pages/").join(file))
+                .expect("This exists at compile time");
+        vault = vault.with_file(Path::new(PAGES_DIRECTORY).join(file), &content);
+    }
+    let journal = Path::new(JOURNALS_DIRECTORY).join("2024_11_01.md");
+    let content = fs::read_to_string(JOURNAL).expect("This exists at compile time");
+    let vault = vault
+        .with_file(&journal, &content)
+        .write()
+        .expect("The temporary directory is writable");
+
+    let config = vault.config();
+    let report = get_report(&[], Some(config.clone()));
+    let err = filter_code(
+        report.unlinked_texts(),
+        &format!("{}::2024_11_01::ipsum", unlinked_text::CODE).into(),
+    )
+    .into_iter()
+    .exactly_one()
+    .unwrap();
+    err.fix(&config).expect("The fix should work");
+
+    let fixed = vault.read(&journal).expect("The journal exists");
+    assert!(
+        fixed.contains("\r\n  second [[ipsum]] line\r\n"),
+        "{fixed:?}"
+    );
+}
@@ -0,0 +1,168 @@
+//! A rule from outside the crate, registered with [`Linter::with_rule`]
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use hashbrown::HashMap;
+use lazy_static::lazy_static;
+use mdlinker::{
+    config::Config,
+    file::content::{source_map::SourceMap, wikilink::Alias},
+    linter::Linter,
+    rules::{custom::CustomReport, ErrorCode, Report, ReportTrait},
+    visitor::{FinalizeError, Finalized, VisitError, Visitor},
+    OutputReport,
+};
+use miette::{Diagnostic, NamedSource, Severity, SourceSpan};
+use thiserror::Error;
+use tracing::info;
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/custom_rule/assets/pages/");
+}
+
+#[derive(Error, Debug, Diagnostic)]
+#[error("Unfinished work")]
+#[diagnostic(code("custom::todo"), severity(Warning))]
+struct Todo {
+    #[source_code]
+    src: NamedSource<String>,
+    #[label("Finish this")]
+    span: SourceSpan,
+}
+
+/// Reports every `TODO` in a file
+#[derive(Default)]
+struct TodoVisitor {
+    reports: Vec<Report>,
+}
+
+impl Visitor for TodoVisitor {
+    fn name(&self) -> &'static str {
+        "TodoVisitor"
+    }
+    fn _visit(
+        &mut self,
+        _node: &Node<RefCell<Ast>>,
+        _source: &SourceMap,
+    ) -> Result<(), VisitError> {
+        Ok(())
+    }
+    fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+        for (offset, todo) in source.match_indices("TODO") {
+            let span = SourceSpan::new(offset.into(), todo.len());
+            let report = CustomReport::builder()
+                .id(ErrorCode::new(format!("custom::todo::{}", path.display())))
+                .path(path.to_path_buf())
+                .span(span)
+                .source(source)
+                .diagnostic(Arc::new(Todo {
+                    src: NamedSource::new(path.to_string_lossy(), source.to_string()),
+                    span,
+                }))
+                .build();
+            self.reports.push(report.into_report());
+        }
+        Ok(())
+    }
+    fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(std::mem::take(&mut self.reports).into())
+    }
+}
+
+fn todo_rule(_config: &Config, _alias_table: &HashMap<Alias, PathBuf>) -> Box<dyn Visitor> {
+    Box::new(TodoVisitor::default())
+}
+
+fn run() -> OutputReport {
+    Linter::builder()
+        .pages_directory(PAGES.clone())
+        .build()
+        .expect("The defaults are valid")
+        .with_rule(todo_rule)
+        .run()
+        .expect("There should have been no error.")
+}
+
+#[test]
+fn reports_from_custom_rule() {
+    info!("reports_from_custom_rule");
+    let report = run();
+    let custom_reports = report.custom_reports();
+    assert_eq!(custom_reports.len(), 1);
+    let todo = &custom_reports[0];
+    assert_eq!(todo.path(), PAGES.join("alpha.md"));
+    assert_eq!(todo.line_column(), Some((2, 3)));
+    assert_eq!(todo.to_string(), "Unfinished work");
+    let todo = report
+        .reports
+        .iter()
+        .find(|report| matches!(report, Report::Custom(_)))
+        .expect("There is a custom report");
+    assert_eq!(todo.severity(), Severity::Warning);
+}
+
+/// Without the rule there are no custom reports
+#[test]
+fn no_custom_rule() {
+    info!("no_custom_rule");
+    let report = Linter::builder()
+        .pages_directory(PAGES.clone())
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert!(report.custom_reports().is_empty());
+}
+
+/// The diagnostic is kept as a [`miette::MietteDiagnostic`] through a round trip
+#[test]
+fn json_round_trip() {
+    info!("json_round_trip");
+    let report = run();
+    let json = serde_json::to_string(&report).expect("Reports serialize");
+    let deserialized: OutputReport = serde_json::from_str(&json).expect("Reports deserialize");
+    let before = &report.custom_reports()[0];
+    let after = &deserialized.custom_reports()[0];
+    assert_eq!(after.id(), before.id());
+    assert_eq!(after.line_column(), before.line_column());
+    assert_eq!(after.to_string(), before.to_string());
+    assert_eq!(
+        after.code().map(|code| code.to_string()),
+        Some("custom::todo".to_string())
+    );
+    assert_eq!(after.labels().map(Iterator::count), Some(1));
+}
+
+/// Custom reports are excluded by id globs like the reports of built in rules
+#[test]
+fn excluded_like_built_in_rules() {
+    info!("excluded_like_built_in_rules");
+    let report = Linter::builder()
+        .pages_directory(PAGES.clone())
+        .exclude(vec!["CUSTOM::TODO::*".to_owned()])
+        .build()
+        .expect("The defaults are valid")
+        .with_rule(todo_rule)
+        .run()
+        .expect("There should have been no error.");
+    assert!(report.custom_reports().is_empty());
+}
+
+/// Excludes match the whole id as a glob, not just its start
+#[test]
+fn exclude_matches_whole_id() {
+    info!("exclude_matches_whole_id");
+    let id = ErrorCode::new("content::wikilink::broken::lorem::ipsum".to_owned());
+    assert!(id.matches(&ErrorCode::new("content::wikilink::broken::*".to_owned())));
+    assert!(id.matches(&ErrorCode::new(
+        "Content::Wikilink::Broken::Lorem::Ipsum".to_owned()
+    )));
+    assert!(!id.matches(&ErrorCode::new("content::wikilink::broken".to_owned())));
+    assert!(!id.matches(&ErrorCode::new(
+        "content::wikilink::broken::ipsum::*".to_owned()
+    )));
+}
@@ -0,0 +1,72 @@
+//! Dendron style hierarchies, where `lang.rust.traits.md` is the page `[[lang.rust.traits]]`
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::config;
+use mdlinker::rules::{broken_wikilink, filter_code};
+use mdlinker::sed::ReplacePair;
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use crate::common::get_report;
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/dendron/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/dendron/assets/journals/");
+}
+
+/// The same settings `hierarchy_separator = "."` gives in `mdlinker.toml`
+fn dendron_config() -> config::Config {
+    config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .hierarchy_separator(".".to_owned())
+        .boundary_pattern(r"\.".to_owned())
+        .filename_to_alias(ReplacePair::new(r"\.", ".").expect("Constant"))
+        .alias_to_filename(ReplacePair::new(r"\.", ".").expect("Constant"))
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build()
+}
+
+#[test]
+fn dotted_wikilinks_resolve() {
+    info!("dotted_wikilinks_resolve");
+    let report = get_report(&[], Some(dendron_config()));
+    for broken_wikilink in &report.broken_wikilinks() {
+        debug!("{broken_wikilink:?}");
+    }
+    assert!(filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::2024_11_01::lang.rust.traits", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+    assert!(filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::lang.rust.traits::lang.rust", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+#[test]
+fn missing_dotted_wikilink_is_broken() {
+    info!("missing_dotted_wikilink_is_broken");
+    let report = get_report(&[], Some(dendron_config()));
+    assert_eq!(report.broken_wikilinks().len(), 1);
+    assert!(!filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::2024_11_01::lang.rust.macros", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+/// The hierarchy boundary stops n-grams, so parents and children aren't similar filenames
+#[test]
+fn hierarchy_levels_are_not_similar() {
+    info!("hierarchy_levels_are_not_similar");
+    let report = get_report(&[], Some(dendron_config()));
+    for similar_filename in &report.similar_filenames() {
+        debug!("{similar_filename:?}");
+    }
+    assert!(report.similar_filenames().is_empty());
+}
@@ -0,0 +1,102 @@
+//! Only failing on reports which touch changed lines, see [`mdlinker::diff::ChangedLines`]
+use std::{fs, path::Path};
+
+use git2::{Repository, Signature};
+use mdlinker::{
+    diff::ChangedLines,
+    testutil::{Page, TempVault, Vault},
+};
+use tracing::info;
+
+/// A page and a journal which mentions it, committed to a new git repo
+fn committed_vault(journal: &[&str]) -> TempVault {
+    let journal = journal
+        .iter()
+        .fold(Page::new("2024_11_01"), |page, line| page.with_line(line));
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_line("- A page"))
+        .with_journal(&journal)
+        .write()
+        .expect("The temporary directory is writable");
+    commit(vault.root());
+    vault
+}
+
+/// Commit every file of the directory to a new git repo
+fn commit(dir: &Path) {
+    let repo = Repository::init(dir).expect("Temp dir is writable");
+    let mut index = repo.index().expect("The repo has an index");
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .expect("The files exist");
+    index.write().expect("The index is writable");
+    let tree = repo
+        .find_tree(index.write_tree().expect("The index is valid"))
+        .expect("The tree was just written");
+    let signature = Signature::now("mdlinker", "mdlinker@example.com").expect("Valid signature");
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])
+        .expect("The first commit has no parents");
+}
+
+/// Only the mention on the edited line touches the changed lines
+#[test]
+fn only_changed_lines() {
+    info!("only_changed_lines");
+    let vault = committed_vault(&["- lorem ipsum", "- dolor"]);
+    fs::write(
+        vault.root().join("journals/2024_11_01.md"),
+        "- lorem ipsum\n- dolor lorem\n",
+    )
+    .expect("Temp dir is writable");
+
+    let report = vault.run().expect("Linting should not fail");
+    assert_eq!(report.unlinked_texts().len(), 2, "{:?}", report.reports);
+    let changed_lines = ChangedLines::new(vault.root(), "HEAD").expect("The repo has a HEAD");
+    let touching: Vec<_> = report
+        .reports
+        .iter()
+        .filter(|report| changed_lines.touches(report))
+        .collect();
+    assert_eq!(touching.len(), 1, "{touching:?}");
+    assert_eq!(touching[0].line_column(), Some((2, 9)));
+}
+
+/// Untracked files changed in every line
+#[test]
+fn untracked_files() {
+    info!("untracked_files");
+    let vault = committed_vault(&["- lorem ipsum", "- dolor"]);
+    let journal = vault.root().join("journals/2024_11_02.md");
+    fs::write(&journal, "- lorem\n").expect("Temp dir is writable");
+
+    let report = vault.run().expect("Linting should not fail");
+    let changed_lines = ChangedLines::new(vault.root(), "HEAD").expect("The repo has a HEAD");
+    let touching: Vec<_> = report
+        .reports
+        .iter()
+        .filter(|report| changed_lines.touches(report))
+        .map(mdlinker::rules::Report::path)
+        .collect();
+    assert_eq!(touching, vec![journal]);
+}
+
+/// Removing lines touches the line after them
+#[test]
+fn removed_lines() {
+    info!("removed_lines");
+    let vault = committed_vault(&["- dolor", "- lorem ipsum"]);
+    fs::write(
+        vault.root().join("journals/2024_11_01.md"),
+        "- lorem ipsum\n",
+    )
+    .expect("Temp dir is writable");
+
+    let report = vault.run().expect("Linting should not fail");
+    let changed_lines = ChangedLines::new(vault.root(), "HEAD").expect("The repo has a HEAD");
+    let unlinked_text = report
+        .reports
+        .iter()
+        .find(|report| report.path().ends_with("2024_11_01.md"))
+        .expect("lorem is unlinked");
+    assert!(changed_lines.touches(unlinked_text));
+}
@@ -2,12 +2,14 @@ use lazy_static::lazy_static;
 use mdlinker::rules::duplicate_alias;
 
 use mdlinker::rules::duplicate_alias::DuplicateAlias;
-use mdlinker::rules::filter_code;
+use mdlinker::rules::{filter_code, ReportTrait};
+use mdlinker::testutil::{Page, Vault};
 
 use crate::common::get_report;
-use log::{debug, info};
+use tracing::{debug, info};
 
 use itertools::Itertools;
+use std::path::Path;
 
 lazy_static! {
     static ref PATHS: Vec<String> = vec![
@@ -35,7 +37,7 @@ fn filename_alias_relation() {
     }
     let duplicate = filter_code(
         report.duplicate_aliases(),
-        &format!("{}::lorem", duplicate_alias::CODE).into(),
+        &format!("{}::ipsum::lorem", duplicate_alias::CODE).into(),
     )
     .into_iter()
     .at_most_one()
@@ -52,7 +54,7 @@ fn filecontent_filecontent_relation() {
     }
     let duplicate = filter_code(
         report.duplicate_aliases(),
-        &format!("{}::dolor", duplicate_alias::CODE).into(),
+        &format!("{}::ipsum::dolor", duplicate_alias::CODE).into(),
     )
     .into_iter()
     .at_most_one()
@@ -69,7 +71,7 @@ fn duplicate_ipsum() {
     }
     let duplicate = filter_code(
         report.duplicate_aliases(),
-        &format!("{}::ipsum", duplicate_alias::CODE).into(),
+        &format!("{}::foo::ipsum", duplicate_alias::CODE).into(),
     )
     .into_iter()
     .at_most_one()
@@ -83,14 +85,229 @@ fn duplicate_ipsum_span() {
     let report = get_report(PATHS.as_slice(), None);
     let err_list = filter_code(
         report.duplicate_aliases(),
-        &format!("{}::ipsum", duplicate_alias::CODE).into(),
+        &format!("{}::foo::ipsum", duplicate_alias::CODE).into(),
     );
     let err = err_list.iter().exactly_one().unwrap();
     match err {
         DuplicateAlias::FileNameContentDuplicate { alias, .. }
         | DuplicateAlias::FileContentContentDuplicate { alias, .. } => {
+            let alias = alias.expect("The alias is in the file");
             assert_eq!(alias.offset(), 11);
             assert_eq!(alias.len(), 5);
         }
+        DuplicateAlias::FileNameFileNameDuplicate { .. } => {
+            panic!("No two files are named after ipsum")
+        }
     }
 }
+
+#[test]
+fn duplicate_ipsum_getters() {
+    info!("duplicate_ipsum_getters");
+    let report = get_report(PATHS.as_slice(), None);
+    let err_list = filter_code(
+        report.duplicate_aliases(),
+        &format!("{}::foo::ipsum", duplicate_alias::CODE).into(),
+    );
+    let err = err_list.iter().exactly_one().unwrap();
+    assert_eq!(
+        err.path(),
+        Path::new("./tests/logseq/duplicate_alias/assets/pages/foo.md")
+    );
+    assert_eq!(err.line_column(), Some((2, 8)));
+    assert_eq!(err.alias().to_string(), "ipsum");
+    assert_eq!(err.other_filename().to_string(), "ipsum");
+}
+
+/// An alias which is escaped in the front matter can't be pointed at, but it is still reported
+#[test]
+fn escaped_alias_without_span() {
+    info!("escaped_alias_without_span");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/ipsum.md", "- Ipsum\n")
+        .with_file(
+            "pages/foo.md",
+            "---\nalias: \"ips\\u0075m\"\n---\n\n- Foo\n",
+        );
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("A missing alias is not an error");
+    let duplicate_aliases = report.duplicate_aliases();
+    assert_eq!(duplicate_aliases.len(), 1);
+    assert_eq!(duplicate_aliases[0].alias().to_string(), "ipsum");
+    assert_eq!(duplicate_aliases[0].path(), Path::new("pages/foo.md"));
+    assert_eq!(duplicate_aliases[0].span(), None);
+    assert_eq!(duplicate_aliases[0].line_column(), None);
+}
+
+/// An alias under a quoted key is still pointed at, where it is written in the values
+#[test]
+fn quoted_key_span() {
+    info!("quoted_key_span");
+    let content = "---\n\"aliases\": [Ipsum]\n---\n- Foo\n";
+    let vault = Vault::new()
+        .with_page(&Page::new("ipsum"))
+        .with_file("pages/foo.md", content)
+        .write()
+        .expect("The temporary directory is writable");
+    let report = vault.run().expect("There should have been no error.");
+    let duplicate = report
+        .duplicate_aliases()
+        .into_iter()
+        .exactly_one()
+        .unwrap();
+    let span = duplicate.span().expect("The alias is in the front matter");
+    assert_eq!(&content[span.offset()..span.offset() + span.len()], "Ipsum");
+}
+
+/// The file named after the alias is shown along with the file which also has it as an alias
+#[test]
+fn filename_alias_related() {
+    info!("filename_alias_related");
+    let report = get_report(PATHS.as_slice(), None);
+    let duplicate = filter_code(
+        report.duplicate_aliases(),
+        &format!("{}::ipsum::lorem", duplicate_alias::CODE).into(),
+    )
+    .into_iter()
+    .exactly_one()
+    .unwrap();
+    let DuplicateAlias::FileNameContentDuplicate { other, .. } = &duplicate else {
+        panic!("lorem.md is named after the alias: {duplicate:#?}");
+    };
+    let other = other.iter().exactly_one().unwrap();
+    assert_eq!(other.path().file_name().unwrap(), "lorem.md");
+    let related = miette::Diagnostic::related(&duplicate)
+        .expect("The other file is related")
+        .count();
+    assert_eq!(related, 1);
+    let label = miette::Diagnostic::labels(other)
+        .expect("The filename is labeled")
+        .collect::<Vec<_>>();
+    let label = label.iter().exactly_one().unwrap();
+    let path = other.path().to_string_lossy().to_string();
+    assert_eq!(&path[label.offset()..label.offset() + label.len()], "lorem");
+}
+
+/// Two files in different directories with the same name have the same alias
+#[test]
+fn filename_filename_duplicate() {
+    info!("filename_filename_duplicate");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/a/foo.md", "- Foo\n")
+        .with_file("pages/b/foo.md", "- Also foo\n");
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("A duplicate filename is not an error");
+    let duplicate = report
+        .duplicate_aliases()
+        .into_iter()
+        .exactly_one()
+        .unwrap();
+    assert_eq!(
+        duplicate.id(),
+        format!("{}::foo::foo", duplicate_alias::CODE).into()
+    );
+    assert_eq!(duplicate.alias().to_string(), "foo");
+    assert_eq!(duplicate.other_filename().to_string(), "foo");
+    assert_eq!(duplicate.span(), None);
+    assert_eq!(duplicate.line_column(), None);
+    let DuplicateAlias::FileNameFileNameDuplicate { other, .. } = &duplicate else {
+        panic!("Both files are named after the alias: {duplicate:#?}");
+    };
+    let mut paths = vec![duplicate.path(), other.iter().exactly_one().unwrap().path()];
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![Path::new("pages/a/foo.md"), Path::new("pages/b/foo.md")]
+    );
+}
+
+/// Aliases can be a comma separated string or a list, under `alias` or `aliases`
+#[test]
+fn alias_forms() {
+    info!("alias_forms");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/lorem.md", "- Lorem\n")
+        .with_file("pages/ipsum.md", "- Ipsum\n")
+        .with_file("pages/dolor.md", "- Dolor\n")
+        .with_file("pages/sit.md", "- Sit\n")
+        .with_file("pages/a.md", "---\nalias: lorem, ipsum\n---\n\n- A\n")
+        .with_file("pages/b.md", "---\naliases: [dolor]\n---\n\n- B\n")
+        .with_file(
+            "pages/c.md",
+            "---\naliases:\n  - sit\n  - amet, consectetur\n---\n\n- C\n",
+        );
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let aliases: Vec<String> = report
+        .duplicate_aliases()
+        .iter()
+        .map(|duplicate| duplicate.alias().to_string())
+        .sorted()
+        .collect();
+    assert_eq!(aliases, vec!["dolor", "ipsum", "lorem", "sit"]);
+}
+
+/// Excludes with the id from before it had the filename still exclude the report
+#[test]
+fn legacy_exclude() {
+    info!("legacy_exclude");
+    let config = mdlinker::config::Config::builder()
+        .pages_directory(PATHS[0].clone().into())
+        .other_directories(vec![PATHS[1].clone().into()])
+        .exclude(vec![format!("{}::ipsum", duplicate_alias::CODE).into()])
+        .build();
+    let report = get_report(&[], Some(config));
+    let aliases = report
+        .duplicate_aliases()
+        .iter()
+        .map(|duplicate| duplicate.alias().to_string())
+        .sorted()
+        .collect_vec();
+    assert_eq!(aliases, vec!["dolor", "lorem"]);
+}
+
+/// The span is that of the entry in the front matter, even when the alias is also in the body
+/// or is part of another alias
+#[test]
+fn span_of_front_matter_entry() {
+    info!("span_of_front_matter_entry");
+    let vault = Vault::new()
+        .with_page(&Page::new("ipsum"))
+        .with_page(
+            &Page::new("foo")
+                .with_alias("ipsum dolor")
+                .with_alias("Ipsum")
+                .with_line("- ipsum in the body"),
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let report = vault.run().expect("There should have been no error.");
+    let duplicate = report
+        .duplicate_aliases()
+        .into_iter()
+        .exactly_one()
+        .unwrap();
+    let span = duplicate.span().expect("The alias is in the front matter");
+    let content = vault.read("pages/foo.md").expect("It was written");
+    assert_eq!(
+        content,
+        "---\naliases: [ipsum dolor, Ipsum]\n---\n- ipsum in the body\n"
+    );
+    assert_eq!(span.offset(), content.find("Ipsum").unwrap());
+    assert_eq!(&content[span.offset()..span.offset() + span.len()], "Ipsum");
+}
@@ -0,0 +1,57 @@
+//! Files with the same content, see [`mdlinker::rules::duplicate_content::DuplicateContent`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+fn lint(files: InMemory) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .other_directories(vec![PathBuf::from("journals")])
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// Front matter is left out, so copies with different aliases are still duplicates
+#[test]
+fn same_body() {
+    info!("same_body");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- Some notes\n- More notes\n")
+        .with_file(
+            "pages/lorem (conflict).md",
+            "---\nalias: ipsum\n---\n\n- Some notes\n- More notes\n",
+        )
+        .with_file("journals/2024_11_01.md", "- Some notes\n- More notes\n")
+        .with_file("pages/dolor.md", "- Other notes\n");
+    let report = lint(files);
+    let duplicate_contents = report.duplicate_contents();
+    assert_eq!(duplicate_contents.len(), 1, "{duplicate_contents:?}");
+    let duplicate = &duplicate_contents[0];
+    let mut paths: Vec<PathBuf> = duplicate.others().to_vec();
+    paths.push(duplicate.path());
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("journals/2024_11_01.md"),
+            PathBuf::from("pages/lorem (conflict).md"),
+            PathBuf::from("pages/lorem.md"),
+        ]
+    );
+}
+
+/// Empty pages, like those made by fixing broken wikilinks, are not duplicates
+#[test]
+fn empty_pages() {
+    info!("empty_pages");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "")
+        .with_file("pages/ipsum.md", "\n")
+        .with_file("pages/dolor.md", "---\nalias: sit\n---\n");
+    let report = lint(files);
+    assert!(report.duplicate_contents().is_empty());
+}
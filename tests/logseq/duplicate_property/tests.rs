@@ -0,0 +1,55 @@
+//! Keys written twice in the front matter, see [`mdlinker::rules::duplicate_property::DuplicateProperty`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+#[test]
+fn duplicate_key() {
+    info!("duplicate_key");
+    let files = InMemory::new()
+        .with_file(
+            "pages/lorem.md",
+            "---\nalias: ipsum\ntitle: Lorem\nalias: dolor\ntags:\n  - alias\n---\n\n- Lorem\n",
+        )
+        .with_file("pages/foo.md", "- [[ipsum]] [[dolor]]\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    let duplicate_properties = report.duplicate_properties();
+    assert_eq!(duplicate_properties.len(), 1);
+    assert_eq!(duplicate_properties[0].key(), "alias");
+    assert_eq!(
+        duplicate_properties[0].path(),
+        PathBuf::from("pages/lorem.md")
+    );
+    assert_eq!(duplicate_properties[0].line_column(), Some((4, 1)));
+
+    // Only the first alias is used
+    let broken_wikilinks = report.broken_wikilinks();
+    assert_eq!(broken_wikilinks.len(), 1);
+    assert_eq!(broken_wikilinks[0].alias().to_string(), "dolor");
+}
+
+#[test]
+fn no_duplicate_keys() {
+    info!("no_duplicate_keys");
+    let files = InMemory::new().with_file(
+        "pages/lorem.md",
+        "---\nalias: ipsum\naliases:\n  - dolor\nurl: https://example.com\n---\n\n- Lorem\n",
+    );
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    assert!(report.duplicate_properties().is_empty());
+}
@@ -0,0 +1,253 @@
+//! The `prefix:`, `glob:` and `regex:` syntax of excludes, see [`mdlinker::rules::Exclude`], and
+//! finding the unused ones, see [`Linter::unused_excludes`], and the expired ones, see
+//! [`mdlinker::rules::expired_exclude::ExpiredExclude`]
+use std::{path::PathBuf, sync::Arc};
+
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use mdlinker::{
+    config::{self, Date, ExcludeEntry, NewConfigError},
+    file::provider::InMemory,
+    linter::{Linter, UnusedExcludes},
+    rules::{ErrorCode, Exclude, ExcludeError},
+    testutil::{Page, Vault, CONFIG_FILE},
+    OutputReport,
+};
+use tracing::info;
+
+fn run(exclude: &[&str]) -> Result<OutputReport, NewConfigError> {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[ipsum]] and [[dolor]]\n")
+        .with_file("pages/sit.md", "- [[amet]]\n");
+    Ok(Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .exclude(exclude.iter().map(ToString::to_string).collect())
+        .build()?
+        .run()
+        .expect("There should have been no error."))
+}
+
+fn broken(report: &OutputReport) -> Vec<String> {
+    let mut aliases: Vec<String> = report
+        .broken_wikilinks()
+        .iter()
+        .map(|x| x.alias().to_string())
+        .collect();
+    aliases.sort();
+    aliases
+}
+
+#[test]
+fn prefix() {
+    info!("prefix");
+    let report = run(&["prefix:CONTENT::wikilink::broken::lorem"]).expect("The excludes are valid");
+    assert_eq!(broken(&report), vec!["amet".to_owned()]);
+}
+
+#[test]
+fn regex() {
+    info!("regex");
+    let report = run(&["regex:::(ipsum|amet)$"]).expect("The excludes are valid");
+    assert_eq!(broken(&report), vec!["dolor".to_owned()]);
+}
+
+/// Globs match the whole id, with or without `glob:`
+#[test]
+fn glob() {
+    info!("glob");
+    let report = run(&[
+        "glob:content::wikilink::broken::sit::*",
+        "content::wikilink::broken::lorem",
+    ])
+    .expect("The excludes are valid");
+    assert_eq!(
+        broken(&report),
+        vec!["dolor".to_owned(), "ipsum".to_owned()]
+    );
+}
+
+/// Excludes which don't compile are refused when the config is loaded
+#[test]
+fn invalid() {
+    info!("invalid");
+    let out = run(&["content::wikilink::[broken"]);
+    assert!(
+        matches!(
+            out,
+            Err(NewConfigError::ExcludeError(ExcludeError::Glob { .. }))
+        ),
+        "{out:?}"
+    );
+    let out = run(&["regex:(ipsum"]);
+    assert!(
+        matches!(
+            out,
+            Err(NewConfigError::ExcludeError(ExcludeError::Regex { .. }))
+        ),
+        "{out:?}"
+    );
+    assert!(Exclude::new(&ErrorCode::new("prefix:[".to_owned())).is_ok());
+}
+
+/// Excludes and word pairs which match no report are unused, word pairs in either order
+#[test]
+fn unused() {
+    info!("unused");
+    let pair = |a: &str, b: &str| (a.to_owned(), b.to_owned());
+    let config = config::Config::builder()
+        .pages_directory(PathBuf::from(
+            "./tests/logseq/similar_filename/assets/pages",
+        ))
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .filename_match_threshold(1)
+        .exclude(vec![
+            ErrorCode::new("name::similar::foo::*".to_owned()),
+            ErrorCode::new("content::wikilink::broken::*".to_owned()),
+        ])
+        .ignore_word_pairs(vec![pair("barr", "bar"), pair("lorem", "ipsum")])
+        .build();
+    let linter = Linter::from_config(config);
+    let report = linter.run().expect("There should have been no error.");
+    let unused = linter.unused_excludes(&report);
+    assert_eq!(
+        unused,
+        UnusedExcludes {
+            exclude: vec![ErrorCode::new("content::wikilink::broken::*".to_owned())],
+            ignore_word_pairs: vec![pair("lorem", "ipsum")],
+        }
+    );
+}
+
+/// `--ignore-remaining` alone ignores every report, with a pattern only those whose id or rule
+/// matches it
+#[test]
+fn ignore_remaining_scope() {
+    use clap::Parser;
+    info!("ignore_remaining_scope");
+    let cli = CliConfig::parse_from(["mdlinker", "--ignore-remaining"]);
+    assert_eq!(cli.ignore_remaining.as_deref(), Some("*"));
+    let cli = CliConfig::parse_from(["mdlinker", "--ignore-remaining", "content::alias::unlinked"]);
+    assert_eq!(
+        cli.ignore_remaining.as_deref(),
+        Some("content::alias::unlinked")
+    );
+    assert_eq!(CliConfig::parse_from(["mdlinker"]).ignore_remaining, None);
+
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[ipsum]] and sit\n")
+        .with_file("pages/sit.md", "- Sit\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let config = config::Config::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .file_config(FileConfig::default())
+        .cli_config(cli)
+        .ignore_remaining(ErrorCode::new("content::alias::unlinked".to_owned()))
+        .build();
+    let ignored: Vec<bool> = report
+        .reports
+        .iter()
+        .map(|report| config.ignores_remaining(report))
+        .collect();
+    assert!(!report.unlinked_texts().is_empty());
+    assert_eq!(report.broken_wikilinks().len(), 1);
+    assert_eq!(
+        ignored.iter().filter(|x| **x).count(),
+        report.unlinked_texts().len()
+    );
+    assert!(report
+        .reports
+        .iter()
+        .filter(|report| config.ignores_remaining(report))
+        .all(|report| report.id().0.starts_with("content::alias::unlinked")));
+}
+
+/// Excludes which expired still exclude, but are reported, pointing at them in the config file
+#[test]
+fn expired() {
+    info!("expired");
+    let toml = "pages_directory = \"pages\"\nother_directories = []\nexclude = [\n    { code = \"content::wikilink::broken::lorem::ipsum\", reason = \"Writing it next week\", expires = \"2000-01-01\" },\n    { code = \"content::wikilink::broken::lorem::dolor\", expires = \"2999-01-01\" },\n]\n";
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_line("- [[ipsum]] and [[dolor]]"))
+        .with_file(CONFIG_FILE, toml)
+        .write()
+        .expect("The temporary directory is writable");
+    let file_config: FileConfig = toml::from_str(toml).expect("The config is valid");
+    assert_eq!(
+        file_config.exclude[0].reason(),
+        Some("Writing it next week")
+    );
+    let config = config::Config::builder()
+        .pages_directory(vault.pages_directory())
+        .exclude(
+            file_config
+                .exclude
+                .iter()
+                .map(|entry| ErrorCode::new(entry.code().to_owned()))
+                .collect(),
+        )
+        .file_config(file_config)
+        .cli_config(CliConfig {
+            config_path: vault.root().join(CONFIG_FILE),
+            ..CliConfig::default()
+        })
+        .build();
+    let linter = Linter::from_config(config);
+    let report = linter.run().expect("There should have been no error.");
+    assert!(broken(&report).is_empty());
+    // Library users may give the day, to check the excludes against another one
+    let later = linter
+        .clone()
+        .with_today("3000-01-01".parse().expect("The date is valid"))
+        .run()
+        .expect("There should have been no error.");
+    assert_eq!(later.expired_excludes().len(), 2);
+    let expired = report.expired_excludes();
+    assert_eq!(expired.len(), 1, "{expired:?}");
+    assert_eq!(expired[0].code(), "content::wikilink::broken::lorem::ipsum");
+    assert_eq!(
+        expired[0].expires(),
+        Date {
+            year: 2000,
+            month: 1,
+            day: 1
+        }
+    );
+    assert_eq!(
+        mdlinker::rules::ReportTrait::line_column(&expired[0]),
+        Some((4, 14))
+    );
+}
+
+/// Excludes are plain strings or tables, and dates are written like `2025-06-01`
+#[test]
+fn entries() {
+    info!("entries");
+    let file_config: FileConfig = toml::from_str(
+        "pages_directory = \"pages\"\nother_directories = []\nexclude = [\"name::similar::*\", { code = \"prefix:content\" }]\n",
+    )
+    .expect("The config is valid");
+    assert_eq!(
+        file_config.exclude,
+        vec![
+            ExcludeEntry::Pattern("name::similar::*".to_owned()),
+            ExcludeEntry::Annotated {
+                code: "prefix:content".to_owned(),
+                reason: None,
+                expires: None,
+            },
+        ]
+    );
+    let date: Date = "2025-06-01".parse().expect("The date is valid");
+    assert_eq!(date.to_string(), "2025-06-01");
+    assert!("2025-13-01".parse::<Date>().is_err());
+    assert!("June 1st".parse::<Date>().is_err());
+    assert!(Date::today() > date);
+}
@@ -0,0 +1,154 @@
+//! Reports as json and SARIF, with the edits of their fixes, see [`mdlinker::export`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::LinkStyle,
+    export::{self, ExportedReport},
+    file::provider::InMemory,
+    linter::Linter,
+    rules::TextEdit,
+};
+use tracing::info;
+
+const DOLOR: &str = "- lorem\n- [see](lorem.md)\n";
+
+fn linter() -> Linter {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- A page\n")
+        .with_file("pages/dolor.md", DOLOR);
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .link_style(LinkStyle::Wikilink)
+        .build()
+        .expect("The defaults are valid")
+}
+
+/// Make the edits of a fix, from the end so the ranges before stay the same
+fn apply(source: &str, edits: &[TextEdit]) -> String {
+    let mut edits = edits.to_vec();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+    let mut source = source.to_owned();
+    for edit in edits {
+        source.replace_range(edit.range, &edit.replacement);
+    }
+    source
+}
+
+fn exported(rule: &str) -> ExportedReport {
+    let linter = linter();
+    let report = linter.run().expect("There should have been no error.");
+    export::exported_reports(&report, linter.config())
+        .into_iter()
+        .find(|report| report.rule == rule)
+        .expect("The rule reported")
+}
+
+/// Unlinked text is fixed by putting brackets around it
+#[test]
+fn unlinked_text() {
+    info!("unlinked_text");
+    let report = exported("content::alias::unlinked");
+    assert_eq!(report.path, PathBuf::from("pages/dolor.md"));
+    assert_eq!(report.range, Some(2..7));
+    assert_eq!((report.line, report.column), (Some(1), Some(3)));
+    assert_eq!(
+        apply(DOLOR, &report.fix),
+        "- [[lorem]]\n- [see](lorem.md)\n"
+    );
+}
+
+/// A link written the other way is fixed by replacing it
+#[test]
+fn wrong_link_style() {
+    info!("wrong_link_style");
+    let report = exported("content::link::style");
+    assert_eq!(apply(DOLOR, &report.fix), "- lorem\n- [[see|lorem]]\n");
+}
+
+/// The fixes are SARIF replacements
+#[test]
+fn sarif() {
+    info!("sarif");
+    let linter = linter();
+    let report = linter.run().expect("There should have been no error.");
+    let sarif = export::to_sarif(&report, linter.config()).expect("Serializable");
+    let sarif: serde_json::Value = serde_json::from_str(&sarif).expect("Valid json");
+    assert_eq!(sarif["version"], "2.1.0");
+    let results = sarif["runs"][0]["results"]
+        .as_array()
+        .expect("There are results");
+    let unlinked = results
+        .iter()
+        .find(|result| result["ruleId"] == "content::alias::unlinked")
+        .expect("The unlinked text is a result");
+    let change = &unlinked["fixes"][0]["artifactChanges"][0];
+    assert_eq!(change["artifactLocation"]["uri"], "pages/dolor.md");
+    let replacements = change["replacements"].as_array().expect("An array");
+    assert_eq!(replacements.len(), 2);
+    assert_eq!(replacements[0]["deletedRegion"]["byteOffset"], 2);
+    assert_eq!(replacements[0]["deletedRegion"]["byteLength"], 0);
+    assert_eq!(replacements[0]["insertedContent"]["text"], "[[");
+    assert_eq!(replacements[1]["deletedRegion"]["byteOffset"], 7);
+}
+
+/// The fingerprint is exported, to be excluded, and is the SARIF partial fingerprint
+#[test]
+fn fingerprint() {
+    info!("fingerprint");
+    let linter = linter();
+    let report = linter.run().expect("There should have been no error.");
+    let unlinked = export::exported_reports(&report, linter.config())
+        .into_iter()
+        .find(|report| report.rule == "content::alias::unlinked")
+        .expect("The rule reported");
+    assert!(!unlinked.fingerprint.0.is_empty());
+    let json: serde_json::Value =
+        serde_json::from_str(&export::to_json(&report, linter.config()).expect("Serializable"))
+            .expect("Valid json");
+    assert!(json
+        .as_array()
+        .expect("An array")
+        .iter()
+        .any(|report| report["fingerprint"] == unlinked.fingerprint.0.as_str()));
+    let sarif = export::to_sarif(&report, linter.config()).expect("Serializable");
+    let sarif: serde_json::Value = serde_json::from_str(&sarif).expect("Valid json");
+    let result = sarif["runs"][0]["results"]
+        .as_array()
+        .expect("There are results")
+        .iter()
+        .find(|result| result["ruleId"] == "content::alias::unlinked")
+        .expect("The unlinked text is a result");
+    assert_eq!(
+        result["partialFingerprints"]["mdlinker/v1"],
+        unlinked.fingerprint.0.as_str()
+    );
+}
+
+/// Reports about filenames have no region in SARIF, rather than an empty one
+#[test]
+fn sarif_without_span() {
+    info!("sarif_without_span");
+    let files = InMemory::new()
+        .with_file("pages/lorem ipsum.md", "- A page\n")
+        .with_file("pages/lorem ipsun.md", "- Another page\n");
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid");
+    let report = linter.run().expect("There should have been no error.");
+    let sarif = export::to_sarif(&report, linter.config()).expect("Serializable");
+    let sarif: serde_json::Value = serde_json::from_str(&sarif).expect("Valid json");
+    let similar = sarif["runs"][0]["results"]
+        .as_array()
+        .expect("There are results")
+        .iter()
+        .find(|result| result["ruleId"] == "name::similar")
+        .expect("The similar filenames are a result");
+    let location = &similar["locations"][0]["physicalLocation"];
+    assert!(location.get("region").is_none(), "{location}");
+    assert!(location["artifactLocation"]["uri"]
+        .as_str()
+        .is_some_and(|uri| uri.starts_with("pages/lorem")));
+}
@@ -0,0 +1,89 @@
+//! [`mdlinker::rules::Fingerprint`]s stay the same when files are renamed and text is moved
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{Fingerprint, ReportTrait},
+    OutputReport,
+};
+use tracing::info;
+
+fn run(files: InMemory, exclude: Vec<String>) -> OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .exclude(exclude)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+fn before() -> InMemory {
+    InMemory::new().with_file("pages/lorem.md", "- Links to [[missing]]\n")
+}
+
+/// Renamed, and the link moved down a line with different spacing
+fn after() -> InMemory {
+    InMemory::new().with_file(
+        "pages/ipsum.md",
+        "- A new first line\n-   links   to [[missing]]\n",
+    )
+}
+
+#[test]
+fn survives_rename_and_move() {
+    info!("survives_rename_and_move");
+    let before = run(before(), vec![]).broken_wikilinks();
+    let after = run(after(), vec![]).broken_wikilinks();
+    assert_eq!(before.len(), 1);
+    assert_eq!(after.len(), 1);
+    assert_ne!(before[0].id(), after[0].id());
+    assert_eq!(before[0].fingerprint(), after[0].fingerprint());
+}
+
+#[test]
+fn changes_with_the_line() {
+    info!("changes_with_the_line");
+    let before = run(before(), vec![]).broken_wikilinks();
+    let changed = run(
+        InMemory::new().with_file("pages/lorem.md", "- Also links to [[missing]]\n"),
+        vec![],
+    )
+    .broken_wikilinks();
+    assert_ne!(before[0].fingerprint(), changed[0].fingerprint());
+}
+
+#[test]
+fn excluded_by_fingerprint() {
+    info!("excluded_by_fingerprint");
+    let fingerprint = run(before(), vec![]).broken_wikilinks()[0].fingerprint();
+    let report = run(after(), vec![fingerprint.0]);
+    assert!(report.broken_wikilinks().is_empty());
+}
+
+/// The hash never changes between platforms or versions, or baselines would break
+#[test]
+fn stable_hash() {
+    info!("stable_hash");
+    assert_eq!(Fingerprint::new("", &[]).0, "af63bd4c8601b7df");
+    assert_eq!(
+        Fingerprint::new("content::wikilink::broken", &["missing"]),
+        Fingerprint::new("content::wikilink::broken", &["missing"])
+    );
+    assert_ne!(
+        Fingerprint::new("rule", &["ab", "c"]),
+        Fingerprint::new("rule", &["a", "bc"])
+    );
+}
+
+/// Fingerprints are in the serialized reports
+#[test]
+fn serialized() {
+    info!("serialized");
+    let report = run(before(), vec![]);
+    let fingerprint = report.broken_wikilinks()[0].fingerprint();
+    let json = serde_json::to_string(&report).expect("Reports serialize");
+    assert!(json.contains(&format!("\"fingerprint\":\"{fingerprint}\"")));
+}
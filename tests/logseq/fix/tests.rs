@@ -0,0 +1,167 @@
+//! `--fix` over many files, which are fixed in parallel
+use mdlinker::{
+    linter::Linter,
+    testutil::{Page, TempVault, Vault},
+};
+use std::fs;
+use tracing::info;
+
+/// A vault with a page, and journals which mention it several times each
+fn vault(nb_journals: usize) -> TempVault {
+    (1..=nb_journals)
+        .fold(
+            Vault::new().with_page(&Page::new("lorem").with_line("- A page")),
+            |vault, day| {
+                vault.with_journal(
+                    &Page::new(&format!("2024_11_{day:02}"))
+                        .with_line(&format!("- lorem ipsum {day}"))
+                        .with_line("- more lorem")
+                        .with_line("- and lorem again"),
+                )
+            },
+        )
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+/// Every mention in every file is fixed, the fixes of a file don't undo each other
+#[test]
+fn many_files() {
+    info!("many_files");
+    let vault = vault(30);
+    let dir = vault.root();
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    assert!(report.unlinked_texts().is_empty(), "{:?}", report.reports);
+    for day in 1..=30 {
+        let journal = dir.join(format!("journals/2024_11_{day:02}.md"));
+        let fixed = fs::read_to_string(&journal).expect("The journal exists");
+        assert_eq!(
+            fixed,
+            format!("- [[lorem]] ipsum {day}\n- more [[lorem]]\n- and [[lorem]] again\n")
+        );
+    }
+}
+
+/// A dry run counts what the fixes change, then puts every file back
+#[test]
+fn dry_run() {
+    info!("dry_run");
+    let vault = vault(3);
+    let dir = vault.root();
+    let broken = dir.join("journals/2024_12_01.md");
+    fs::write(&broken, "- [[dolor]]\n").expect("Temp dir is writable");
+    let before: Vec<String> = (1..=3)
+        .map(|day| {
+            fs::read_to_string(dir.join(format!("journals/2024_11_{day:02}.md")))
+                .expect("The journal exists")
+        })
+        .collect();
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    config.dry_run = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    let stats = report.fix_stats().expect("This was a dry run");
+    assert_eq!(stats.created, 1, "{stats}");
+    assert_eq!(stats.modified, 3, "{stats}");
+    assert_eq!(stats.deleted, 0, "{stats}");
+    // Each mention changes its line, and the new page is empty without a template
+    assert_eq!(stats.insertions, 9, "{stats}");
+    assert_eq!(stats.deletions, 9, "{stats}");
+
+    // The reports are those before fixing
+    assert_eq!(report.unlinked_texts().len(), 9);
+    assert_eq!(report.broken_wikilinks().len(), 1);
+    for (day, before) in (1..=3).zip(before) {
+        let after = fs::read_to_string(dir.join(format!("journals/2024_11_{day:02}.md")))
+            .expect("The journal exists");
+        assert_eq!(after, before);
+    }
+    assert!(!dir.join("pages/dolor.md").exists());
+    assert_eq!(
+        fs::read_dir(dir.join("pages"))
+            .expect("The pages exist")
+            .count(),
+        1
+    );
+}
+
+/// Text which two rules would fix is only fixed by the first, the other is skipped
+#[test]
+fn conflicts() {
+    info!("conflicts");
+    let vault = vault(0);
+    let dir = vault.root();
+    let page = dir.join("pages/dolor.md");
+    fs::write(&page, "- See [lorem](lorem.md)\n").expect("Temp dir is writable");
+    let mut config = vault.config();
+    config.link_style = Some(mdlinker::config::LinkStyle::Wikilink);
+    config.fix = true;
+    config.no_vcs = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    // The unlinked text in the link, and the link written the other way
+    let conflicts = report.fix_conflicts();
+    assert_eq!(conflicts.len(), 1, "{conflicts:?}");
+    assert_eq!(conflicts[0].path, page);
+    assert_ne!(conflicts[0].skipped, conflicts[0].fixed);
+    let fixed = fs::read_to_string(&page).expect("The page exists");
+    assert_eq!(fixed.matches("[[").count(), 1, "{fixed:?}");
+}
+
+/// Fixing unlinked text once leaves nothing to fix again
+#[test]
+fn verify() {
+    info!("verify");
+    let vault = vault(3);
+    let dir = vault.root();
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    config.verify = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    let non_idempotent_fixes = report.non_idempotent_fixes();
+    assert!(non_idempotent_fixes.is_empty(), "{non_idempotent_fixes:?}");
+    // The fixes made a second time were undone
+    let fixed = fs::read_to_string(dir.join("journals/2024_11_01.md")).expect("The journal exists");
+    assert_eq!(
+        fixed,
+        "- [[lorem]] ipsum 1\n- more [[lorem]]\n- and [[lorem]] again\n"
+    );
+}
+
+/// A report which only appears after fixing, like unlinked text for the page a broken wikilink's
+/// fix created, is not fixed when verifying, as fixing it would be its first fix
+#[test]
+fn verify_new_report() {
+    info!("verify_new_report");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_link("dolor"))
+        .with_page(&Page::new("ipsum").with_line("- dolor sit amet"))
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    config.verify = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    assert_eq!(report.unlinked_texts().len(), 1, "{:?}", report.reports);
+    let non_idempotent_fixes = report.non_idempotent_fixes();
+    assert!(non_idempotent_fixes.is_empty(), "{non_idempotent_fixes:?}");
+    assert_eq!(
+        vault.read("pages/ipsum.md").expect("The page exists"),
+        "- dolor sit amet\n"
+    );
+}
@@ -0,0 +1,118 @@
+//! The properties of the front matter, as rules see them with [`FrontMatterVisitor`]
+use std::{cell::RefCell, path::Path, rc::Rc};
+
+use comrak::{arena_tree::Node, nodes::Ast};
+use mdlinker::{
+    file::content::{
+        front_matter::{alias_spans, FrontMatter, FrontMatterVisitor},
+        source_map::SourceMap,
+        wikilink::Alias,
+    },
+    rules::ErrorCode,
+    visitor::{parse_source, FinalizeError, Finalized, VisitError, Visitor},
+};
+use tracing::info;
+
+/// Keeps the front matter of the last file
+#[derive(Default)]
+struct LastFrontMatter {
+    front_matter_visitor: FrontMatterVisitor,
+    last: FrontMatter,
+}
+
+impl Visitor for LastFrontMatter {
+    fn name(&self) -> &'static str {
+        "LastFrontMatter"
+    }
+    fn _visit(&mut self, node: &Node<RefCell<Ast>>, source: &SourceMap) -> Result<(), VisitError> {
+        self.front_matter_visitor.visit(node, source)
+    }
+    fn _finalize_file(&mut self, source: &str, path: &Path) -> Result<(), FinalizeError> {
+        self.last = self.front_matter_visitor.front_matter.clone();
+        self.front_matter_visitor.finalize_file(source, path)
+    }
+    fn _finalize(&mut self, _exclude: &[ErrorCode]) -> Result<Finalized, FinalizeError> {
+        Ok(Finalized::default())
+    }
+}
+
+fn front_matter(source: &str) -> FrontMatter {
+    let visitor = Rc::new(RefCell::new(LastFrontMatter::default()));
+    parse_source(Path::new("pages/lorem.md"), source, vec![visitor.clone()])
+        .expect("The front matter is valid");
+    let last = visitor.borrow().last.clone();
+    last
+}
+
+#[test]
+fn properties() {
+    info!("properties");
+    let front_matter = front_matter(
+        "---\nalias: ipsum\ntags: [dolor, sit]\nid: 42\ndate: 2024-11-01\nstatus: draft\n---\n\n- Lorem\n",
+    );
+    assert_eq!(front_matter.aliases, vec![Alias::new("ipsum")]);
+    assert_eq!(
+        front_matter.tags,
+        vec![Alias::new("dolor"), Alias::new("sit")]
+    );
+    assert_eq!(front_matter.id.as_deref(), Some("42"));
+    assert_eq!(front_matter.date.as_deref(), Some("2024-11-01"));
+    assert!(!front_matter.no_autolink);
+    assert_eq!(
+        front_matter.properties.keys().collect::<Vec<_>>(),
+        vec!["status"]
+    );
+    assert_eq!(
+        front_matter.properties["status"],
+        serde_yaml::Value::String("draft".to_owned())
+    );
+}
+
+#[test]
+fn no_front_matter() {
+    info!("no_front_matter");
+    assert_eq!(front_matter("- Lorem\n"), FrontMatter::default());
+}
+
+/// The text under the span of each alias, in the order of the aliases
+fn spanned(source: &str) -> Vec<(String, &str)> {
+    alias_spans(source)
+        .into_iter()
+        .map(|(alias, range)| (alias.to_string(), &source[range]))
+        .collect()
+}
+
+#[test]
+fn alias_spans_of_each_form() {
+    info!("alias_spans_of_each_form");
+    assert_eq!(
+        spanned("---\nalias: Lorem,  ipsum \n---\n- lorem ipsum\n"),
+        vec![("ipsum".to_owned(), "ipsum"), ("lorem".to_owned(), "Lorem")]
+    );
+    assert_eq!(
+        spanned("---\ntitle: x\naliases: [lorem, 'ipsum', \"dolor sit\"]\n---\n"),
+        vec![
+            ("dolor sit".to_owned(), "dolor sit"),
+            ("ipsum".to_owned(), "ipsum"),
+            ("lorem".to_owned(), "lorem")
+        ]
+    );
+    assert_eq!(
+        spanned("---\r\naliases:\r\n  - lorem\r\n  - \"ipsum, dolor\"\r\n---\r\n"),
+        vec![
+            ("ipsum, dolor".to_owned(), "ipsum, dolor"),
+            ("lorem".to_owned(), "lorem")
+        ]
+    );
+}
+
+/// Aliases which are not written as they are read have no span, and repeated keys are not read
+#[test]
+fn alias_spans_left_out() {
+    info!("alias_spans_left_out");
+    assert_eq!(
+        spanned("---\naliases: [\"ips\\u0075m\", lorem, 'it''s']\naliases: dolor\n---\n"),
+        vec![("lorem".to_owned(), "lorem")]
+    );
+    assert!(spanned("- alias: lorem\n").is_empty());
+}
@@ -0,0 +1,116 @@
+//! Front matter keys with a more common synonym, see
+//! [`mdlinker::rules::front_matter_key::FrontMatterKey`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::KeySynonyms,
+    file::provider::InMemory,
+    linter::Linter,
+    rules::ReportTrait,
+    testutil::{Page, Vault},
+};
+use tracing::info;
+
+fn lint(files: InMemory, key_synonyms: Vec<KeySynonyms>) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .key_synonyms(key_synonyms)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+fn files() -> InMemory {
+    InMemory::new()
+        .with_file("pages/lorem.md", "---\ntags: a\n---\n- lorem\n")
+        .with_file("pages/ipsum.md", "---\ntags: b\n---\n- ipsum\n")
+        .with_file("pages/dolor.md", "---\nid: 1\ntag: c\n---\n- dolor\n")
+}
+
+/// With no canonical key, the one most files use is
+#[test]
+fn minority_key() {
+    info!("minority_key");
+    let front_matter_keys = lint(files(), Vec::new()).front_matter_keys();
+    assert_eq!(front_matter_keys.len(), 1, "{front_matter_keys:#?}");
+    let front_matter_key = &front_matter_keys[0];
+    assert_eq!(front_matter_key.path(), PathBuf::from("pages/dolor.md"));
+    assert_eq!(front_matter_key.key(), "tag");
+    assert_eq!(front_matter_key.canonical(), "tags");
+    assert_eq!(
+        front_matter_key.counts(),
+        [("tags".to_owned(), 2), ("tag".to_owned(), 1)]
+    );
+    assert_eq!(front_matter_key.line_column(), Some((3, 1)));
+}
+
+/// The configured canonical key is used even when fewer files use it
+#[test]
+fn configured_canonical() {
+    info!("configured_canonical");
+    let key_synonyms = vec![KeySynonyms {
+        keys: vec!["tag".to_owned(), "tags".to_owned()],
+        canonical: Some("tag".to_owned()),
+    }];
+    let mut keys: Vec<_> = lint(files(), key_synonyms)
+        .front_matter_keys()
+        .iter()
+        .map(|front_matter_key| front_matter_key.path())
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            PathBuf::from("pages/ipsum.md"),
+            PathBuf::from("pages/lorem.md")
+        ]
+    );
+}
+
+/// There is no usual key when two are as common
+#[test]
+fn tie() {
+    info!("tie");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "---\nalias: a\n---\n- lorem\n")
+        .with_file("pages/ipsum.md", "---\naliases: b\n---\n- ipsum\n");
+    assert!(lint(files, Vec::new()).front_matter_keys().is_empty());
+}
+
+#[test]
+fn fix() {
+    info!("fix");
+    let vault = Vault::new()
+        .with_page(
+            &Page::new("lorem")
+                .with_property("created", "2024")
+                .with_line("- lorem"),
+        )
+        .with_page(
+            &Page::new("ipsum")
+                .with_property("created", "2023")
+                .with_line("- ipsum"),
+        )
+        .with_page(
+            &Page::new("dolor")
+                .with_property("id", "1")
+                .with_property("date-created", "2022")
+                .with_line("- dolor"),
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let linter = vault.linter();
+    let front_matter_keys = linter
+        .run()
+        .expect("Linting should not fail")
+        .front_matter_keys();
+    assert_eq!(front_matter_keys.len(), 1, "{front_matter_keys:#?}");
+    assert_eq!(front_matter_keys[0].fix(linter.config()).unwrap(), Some(()));
+    assert_eq!(
+        vault.read("pages/dolor.md").unwrap(),
+        "---\nid: 1\ncreated: 2022\n---\n- dolor\n"
+    );
+    assert!(linter.run().unwrap().front_matter_keys().is_empty());
+}
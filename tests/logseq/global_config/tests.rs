@@ -0,0 +1,97 @@
+//! The user's config below the one of the vault, see [`FileConfig::with_global`]
+use std::path::PathBuf;
+
+use mdlinker::{
+    config::{
+        self,
+        cli::{ColorChoice, Config as CliConfig},
+        file::Config as FileConfig,
+        ExcludeEntry, Level, NewConfigError, SeverityOverride, SimilarityAlgorithm,
+    },
+    testutil::{TempVault, Vault, CONFIG_FILE},
+};
+use tracing::info;
+
+/// The config of a vault, and a global config next to it with `global` in it
+fn vault(global: &str) -> TempVault {
+    Vault::new()
+        .with_file(
+            CONFIG_FILE,
+            "pages_directory = \"pages\"\nother_directories = []\nhierarchy_separator = \".\"\nseverity = [{ rule = \"name::similar\", level = \"error\" }]\n",
+        )
+        .with_file("config.toml", global)
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+/// Keys the vault doesn't set come from the global config, and its severities come first
+#[test]
+fn vault_has_priority() {
+    info!("vault_has_priority");
+    let dir = vault("hierarchy_separator = \"___\"\nsimilarity_algorithm = \"token_set\"\ncolor = \"never\"\nseverity = [{ rule = \"name::similar\", level = \"warning\" }]\n");
+    let (vault, global) = (dir.root().join(CONFIG_FILE), dir.root().join("config.toml"));
+    let config = FileConfig::with_global(&vault, Some(&global)).expect("Both configs are valid");
+    assert_eq!(config.hierarchy_separator.as_deref(), Some("."));
+    assert_eq!(
+        config.similarity_algorithm,
+        Some(SimilarityAlgorithm::TokenSet)
+    );
+    assert_eq!(config.color, Some(ColorChoice::Never));
+    let severity = |level| SeverityOverride {
+        rule: Some("name::similar".to_owned()),
+        path: None,
+        level,
+    };
+    assert_eq!(
+        config.severity,
+        vec![severity(Level::Warning), severity(Level::Error)]
+    );
+}
+
+/// No global config is the same as an empty one, and a broken one names the file
+#[test]
+fn missing_or_invalid() {
+    info!("missing_or_invalid");
+    let dir = vault("color = \"never\n");
+    let (vault, global) = (dir.root().join(CONFIG_FILE), dir.root().join("config.toml"));
+    let config = FileConfig::with_global(&vault, Some(&vault.with_file_name("missing.toml")))
+        .expect("The vault config is valid");
+    assert_eq!(config.color, None);
+    let out = FileConfig::with_global(&vault, Some(&global));
+    assert!(
+        matches!(&out, Err(NewConfigError::GlobalFileDoesNotParseError { path, .. }) if *path == global),
+        "{out:?}"
+    );
+}
+
+/// Saving an exclude doesn't copy the excludes of the global config into the vault's config
+#[test]
+fn save_with_global_exclude() {
+    info!("save_with_global_exclude");
+    let dir =
+        vault("exclude = [\"name::similar::*\"]\nignore_word_pairs = [[\"lorem\", \"ipsum\"]]\n");
+    let (vault, global) = (dir.root().join(CONFIG_FILE), dir.root().join("config.toml"));
+    let mut file_config =
+        FileConfig::with_global(&vault, Some(&global)).expect("Both configs are valid");
+    assert_eq!(file_config.exclude.len(), 1);
+    file_config.exclude.push(ExcludeEntry::Pattern(
+        "content::wikilink::broken::*".to_owned(),
+    ));
+    let config = config::Config::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .file_config(file_config)
+        .cli_config(CliConfig {
+            config_path: vault.clone(),
+            ..CliConfig::default()
+        })
+        .build();
+    config.save_config().expect("The config is writable");
+    let saved = FileConfig::new(&vault).expect("The saved config is valid");
+    assert_eq!(
+        saved.exclude,
+        vec![ExcludeEntry::Pattern(
+            "content::wikilink::broken::*".to_owned()
+        )]
+    );
+    assert!(saved.ignore_word_pairs.is_empty());
+}
@@ -0,0 +1,89 @@
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::{config, graph};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/graph/assets/pages/");
+}
+
+fn get_graph(labels: bool, namespace: Option<&str>) -> mdlinker::graph::Graph {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    let graph = graph(&config, labels, namespace).expect("There should have been no error.");
+    debug!("{graph:?}");
+    graph
+}
+
+/// Edges between the same two pages are merged
+#[test]
+fn nodes_and_edges() {
+    info!("nodes_and_edges");
+    let graph = get_graph(false, None);
+    assert_eq!(graph.nodes.len(), 3);
+    let mut edges: Vec<(PathBuf, PathBuf)> = graph
+        .edges
+        .iter()
+        .map(|edge| (edge.source.clone(), edge.target.clone()))
+        .collect();
+    edges.sort();
+    assert_eq!(
+        edges,
+        vec![
+            (PAGES.join("dolor.md"), PAGES.join("lorem___ipsum.md")),
+            (PAGES.join("lorem.md"), PAGES.join("dolor.md")),
+            (PAGES.join("lorem.md"), PAGES.join("lorem___ipsum.md")),
+            (PAGES.join("lorem___ipsum.md"), PAGES.join("lorem.md")),
+        ]
+    );
+    assert!(graph.edges.iter().all(|edge| edge.aliases.is_empty()));
+}
+
+#[test]
+fn labels_are_aliases() {
+    info!("labels_are_aliases");
+    let graph = get_graph(true, None);
+    let edge = graph
+        .edges
+        .iter()
+        .find(|edge| edge.source == PAGES.join("dolor.md"))
+        .expect("dolor links to sit");
+    assert_eq!(edge.aliases, vec!["sit".to_owned()]);
+    let dot = graph.to_dot();
+    assert!(
+        dot.contains(&format!(
+            "\"{}\" -> \"{}\" [label=\"sit\"];",
+            PAGES.join("dolor.md").display(),
+            PAGES.join("lorem___ipsum.md").display()
+        )),
+        "{dot}"
+    );
+}
+
+/// The namespace page and its children, but not other pages
+#[test]
+fn namespace_filter() {
+    info!("namespace_filter");
+    let graph = get_graph(false, Some("lorem"));
+    let mut nodes: Vec<String> = graph.nodes.iter().map(|x| x.label.clone()).collect();
+    nodes.sort();
+    assert_eq!(nodes, vec!["lorem".to_owned(), "lorem___ipsum".to_owned()]);
+    assert_eq!(graph.edges.len(), 2);
+}
+
+#[test]
+fn graphml() {
+    info!("graphml");
+    let graphml = get_graph(true, Some("lorem/ipsum")).to_graphml();
+    assert!(graphml.starts_with("<?xml"), "{graphml}");
+    assert!(
+        graphml.contains("<data key=\"label\">lorem___ipsum</data>"),
+        "{graphml}"
+    );
+    assert!(!graphml.contains("<edge"), "{graphml}");
+}
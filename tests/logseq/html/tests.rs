@@ -0,0 +1,72 @@
+use std::{path::PathBuf, str::FromStr};
+
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use mdlinker::config;
+use tracing::{debug, info};
+
+use crate::common::get_report;
+
+lazy_static! {
+    static ref PATHS: Vec<String> = vec![
+        "./tests/logseq/html/assets/pages/".to_string(),
+        "./tests/logseq/html/assets/journals/".to_string()
+    ];
+}
+
+fn config(skip_html: bool) -> config::Config {
+    config::Config::builder()
+        .pages_directory(
+            PathBuf::from_str("./tests/logseq/html/assets/pages/").expect("This is a constant"),
+        )
+        .other_directories(vec![PathBuf::from_str(
+            "./tests/logseq/html/assets/journals/",
+        )
+        .expect("This is a constant")])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .skip_html(skip_html)
+        .build()
+}
+
+/// Only the `lorem` outside of the html should be found
+#[test]
+fn unlinked_text_skips_html() {
+    info!("unlinked_text_skips_html");
+    let report = get_report(PATHS.as_slice(), None);
+    for unlinked_text in &report.unlinked_texts() {
+        debug!("{unlinked_text:#?}");
+    }
+    let err = report.unlinked_texts().into_iter().exactly_one().unwrap();
+    let source = std::fs::read_to_string("./tests/logseq/html/assets/journals/2024_11_01.md")
+        .expect("This exists at compile time");
+    let expected = source.find("and lorem here").unwrap() + "and ".len();
+    assert_eq!(err.span.offset(), expected);
+    assert_eq!(err.span.len(), 5);
+}
+
+/// Tags inside of html elements are not tags
+#[test]
+fn broken_wikilink_skips_html() {
+    info!("broken_wikilink_skips_html");
+    let report = get_report(PATHS.as_slice(), None);
+    for broken_wikilink in &report.broken_wikilinks() {
+        debug!("{broken_wikilink:#?}");
+    }
+    assert!(report.broken_wikilinks().is_empty());
+}
+
+/// With `skip_html` off, the text inside inline html elements is checked too
+/// Html blocks are still never parsed as markdown
+#[test]
+fn html_not_skipped() {
+    info!("html_not_skipped");
+    let report = get_report(PATHS.as_slice(), Some(config(false)));
+    for unlinked_text in &report.unlinked_texts() {
+        debug!("{unlinked_text:#?}");
+    }
+    assert_eq!(report.unlinked_texts().len(), 2);
+    assert_eq!(report.broken_wikilinks().len(), 1);
+}
@@ -0,0 +1,40 @@
+//! Pages with too many wikilinks, see [`mdlinker::rules::hub_page::HubPage`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+fn lint(max_outbound_links: Option<usize>) -> mdlinker::OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/index.md", "- [[a]] [[b]] [[a]] #c\n- [[c]] [[d]]\n")
+        .with_file("pages/small.md", "- [[a]] [[b]]\n")
+        .with_file("pages/a.md", "")
+        .with_file("pages/b.md", "")
+        .with_file("pages/c.md", "")
+        .with_file("pages/d.md", "");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .maybe_max_outbound_links(max_outbound_links)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// Repeated wikilinks and tags are not counted, and the first link over the maximum is labeled
+#[test]
+fn too_many_links() {
+    info!("too_many_links");
+    let hub_pages = lint(Some(3)).hub_pages();
+    assert_eq!(hub_pages.len(), 1);
+    assert_eq!(hub_pages[0].path(), PathBuf::from("pages/index.md"));
+    assert_eq!(hub_pages[0].nb_links(), 4);
+    assert_eq!(hub_pages[0].line_column(), Some((2, 9)));
+}
+
+#[test]
+fn off_by_default() {
+    info!("off_by_default");
+    assert!(lint(None).hub_pages().is_empty());
+}
@@ -0,0 +1,125 @@
+//! Wikilinks to files which are not linted, see [`mdlinker::rules::ignored_wikilink::IgnoredWikilink`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+fn files() -> InMemory {
+    InMemory::new()
+        .with_file("pages/lorem.md", "- See [[meeting]] and [[old]]\n")
+        .with_file("pages/templates/meeting.md", "- [[missing]]\n")
+        .with_file("pages/archive/old.md", "- [[missing]]\n")
+}
+
+/// Ignored files are not linted, but wikilinks to them resolve and are reported
+#[test]
+fn links_to_ignored() {
+    info!("links_to_ignored");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files()))
+        .ignore_paths(vec![
+            "pages/templates/**".to_owned(),
+            "pages/archive/**".to_owned(),
+        ])
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert!(report.broken_wikilinks().is_empty(), "{:?}", report.reports);
+    let mut ignored: Vec<(String, PathBuf)> = report
+        .ignored_wikilinks()
+        .iter()
+        .map(|wikilink| {
+            (
+                wikilink.alias().to_string(),
+                wikilink.target().to_path_buf(),
+            )
+        })
+        .collect();
+    ignored.sort();
+    assert_eq!(
+        ignored,
+        vec![
+            (
+                "meeting".to_owned(),
+                PathBuf::from("pages/templates/meeting.md")
+            ),
+            ("old".to_owned(), PathBuf::from("pages/archive/old.md")),
+        ]
+    );
+    assert_eq!(
+        report.ignored_wikilinks()[0].path(),
+        PathBuf::from("pages/lorem.md")
+    );
+}
+
+#[test]
+fn allowed_links() {
+    info!("allowed_links");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files()))
+        .ignore_paths(vec![
+            "pages/templates/**".to_owned(),
+            "pages/archive/**".to_owned(),
+        ])
+        .allow_links_to_ignored(vec!["pages/templates/**".to_owned()])
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let ignored = report.ignored_wikilinks();
+    assert_eq!(ignored.len(), 1);
+    assert_eq!(ignored[0].alias().to_string(), "old");
+}
+
+/// A report about several files is kept while one of them is linted, whichever sorts first, and
+/// dropped once they are all ignored
+#[test]
+fn similar_to_ignored() {
+    info!("similar_to_ignored");
+    let similar_filenames = |ignore_paths: &[&str]| {
+        let files = InMemory::new()
+            .with_file("pages/archive/lorem.md", "- Lorem\n")
+            .with_file("pages/loremm.md", "- Loremm\n")
+            .with_file("pages/zarchive/loremmm.md", "- Loremmm\n");
+        let report = Linter::builder()
+            .pages_directory(PathBuf::from("pages"))
+            .files(Arc::new(files))
+            .filename_match_threshold(1)
+            .ignore_paths(ignore_paths.iter().map(ToString::to_string).collect())
+            .build()
+            .expect("The defaults are valid")
+            .run()
+            .expect("There should have been no error.");
+        let mut pairs: Vec<Vec<PathBuf>> = report
+            .similar_filenames()
+            .iter()
+            .map(ReportTrait::paths)
+            .collect();
+        pairs.sort();
+        pairs
+    };
+    let all = similar_filenames(&[]);
+    assert!(
+        all.iter().any(
+            |pair| pair.contains(&PathBuf::from("pages/archive/lorem.md"))
+                && pair.contains(&PathBuf::from("pages/loremm.md"))
+        ),
+        "{all:?}"
+    );
+    assert!(
+        all.iter().any(
+            |pair| pair.contains(&PathBuf::from("pages/zarchive/loremmm.md"))
+                && pair.contains(&PathBuf::from("pages/loremm.md"))
+        ),
+        "{all:?}"
+    );
+    assert_eq!(
+        similar_filenames(&["pages/archive/**", "pages/zarchive/**"]),
+        all.into_iter()
+            .filter(|pair| pair.contains(&PathBuf::from("pages/loremm.md")))
+            .collect::<Vec<_>>()
+    );
+}
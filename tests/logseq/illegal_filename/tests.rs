@@ -0,0 +1,100 @@
+//! Filenames which break on some platforms, see
+//! [`mdlinker::rules::illegal_filename::IllegalFilename`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{config::Platform, file::provider::InMemory, linter::Linter, testutil::Vault};
+use tracing::info;
+
+fn lint(files: InMemory, filename_platforms: Vec<Platform>) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .filename_platforms(filename_platforms)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+fn files() -> InMemory {
+    InMemory::new()
+        .with_file("pages/lorem: ipsum.md", "")
+        .with_file("pages/dolor#sit.md", "")
+        .with_file("pages/amet .md", "")
+        .with_file("pages/con.md", "")
+        .with_file("pages/consectetur.md", "")
+}
+
+/// Every platform, by default
+#[test]
+fn all_platforms() {
+    info!("all_platforms");
+    let mut illegal_filenames = lint(files(), vec![]).illegal_filenames();
+    illegal_filenames.sort_by_key(|illegal| illegal.filename().to_owned());
+    let found: Vec<_> = illegal_filenames
+        .iter()
+        .map(|illegal| {
+            (
+                illegal.filename(),
+                illegal.platforms().to_vec(),
+                illegal.suggestion(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("amet ", vec![Platform::Windows], "amet"),
+            ("con", vec![Platform::Windows], "con_"),
+            ("dolor#sit", vec![Platform::Url], "dolor-sit"),
+            (
+                "lorem: ipsum",
+                vec![Platform::Windows, Platform::Macos],
+                "lorem- ipsum"
+            ),
+        ]
+    );
+}
+
+/// Only the characters which break on the platforms in the config are reported
+#[test]
+fn url_only() {
+    info!("url_only");
+    let illegal_filenames = lint(files(), vec![Platform::Url]).illegal_filenames();
+    assert_eq!(illegal_filenames.len(), 1, "{illegal_filenames:#?}");
+    assert_eq!(illegal_filenames[0].filename(), "dolor#sit");
+}
+
+/// Fixing renames the file to the suggestion, and rewrites the wikilinks to its old name to its
+/// new one, as written in the filename
+#[test]
+fn fix_renames() {
+    info!("fix_renames");
+    let vault = Vault::new()
+        .with_file("pages/lorem: ipsum.md", "- Lorem\n")
+        .with_file("pages/dolor.md", "- See [[Lorem: ipsum]]\n")
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("There should have been no error.");
+    assert!(
+        report.illegal_filenames().is_empty(),
+        "{:?}",
+        report.reports
+    );
+    assert!(!vault.pages_directory().join("lorem: ipsum.md").exists());
+    assert_eq!(
+        vault
+            .read("pages/lorem- ipsum.md")
+            .expect("The page was renamed"),
+        "- Lorem\n"
+    );
+    assert_eq!(
+        vault.read("pages/dolor.md").expect("The page exists"),
+        "- See [[lorem- ipsum]]\n"
+    );
+}
@@ -0,0 +1,68 @@
+//! Linting files which are not on the filesystem, with [`mdlinker::file::provider::InMemory`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    file::provider::{FileProvider, InMemory},
+    linter::Linter,
+    rules::ReportTrait,
+};
+use tracing::info;
+
+fn files() -> InMemory {
+    InMemory::new()
+        .with_file(
+            "vault/pages/lorem.md",
+            "- Lorem links to [[ipsum]] and [[missing]]\n",
+        )
+        .with_file("vault/pages/ipsum.md", "- Ipsum\n")
+        .with_file("vault/pages/foo.md", "---\nalias: ipsum\n---\n\n- Foo\n")
+        .with_file("vault/pages/.hidden/bar.md", "- [[nowhere]]\n")
+        .with_file("elsewhere/baz.md", "- [[nowhere]]\n")
+}
+
+#[test]
+fn lists_files_under_directories() {
+    info!("lists_files_under_directories");
+    let listed = files().files(&[PathBuf::from("vault/pages")]);
+    assert_eq!(
+        listed,
+        vec![
+            PathBuf::from("vault/pages/foo.md"),
+            PathBuf::from("vault/pages/ipsum.md"),
+            PathBuf::from("vault/pages/lorem.md"),
+        ]
+    );
+}
+
+#[test]
+fn lints_in_memory() {
+    info!("lints_in_memory");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("vault/pages"))
+        .files(Arc::new(files()))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    let broken_wikilinks = report.broken_wikilinks();
+    assert_eq!(broken_wikilinks.len(), 1);
+    assert_eq!(broken_wikilinks[0].alias().to_string(), "missing");
+    assert_eq!(
+        broken_wikilinks[0].path(),
+        PathBuf::from("vault/pages/lorem.md")
+    );
+
+    let duplicate_aliases = report.duplicate_aliases();
+    assert_eq!(duplicate_aliases.len(), 1);
+    assert_eq!(duplicate_aliases[0].alias().to_string(), "ipsum");
+}
+
+/// Files which are not in memory are not read from the filesystem instead
+#[test]
+fn missing_file() {
+    info!("missing_file");
+    assert!(files()
+        .read(&PathBuf::from("vault/pages/dolor.md"))
+        .is_err());
+}
@@ -0,0 +1,108 @@
+//! The index saved to `.mdlinker/index.json`, see [`mdlinker::index_file::IndexFile`]
+use std::{fs, path::PathBuf};
+
+use mdlinker::{
+    config::Config,
+    file::content::wikilink::Alias,
+    index_file::IndexFile,
+    links,
+    linter::Linter,
+    testutil::{Page, TempVault, Vault},
+};
+use tracing::info;
+
+/// A vault with `lorem.md` linking to `ipsum.md`, which saves its index
+fn vault() -> (TempVault, Config) {
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_link("ipsum"))
+        .with_page(&Page::new("ipsum").with_line("- A page"))
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.write_index = true;
+    (vault, config)
+}
+
+fn write(config: &Config) -> IndexFile {
+    let report = Linter::from_config(config.clone())
+        .run()
+        .expect("There should have been no error.");
+    let index = IndexFile::new(&report, config);
+    index.write(config).expect("Temp dir is writable");
+    index
+}
+
+fn files(dir: &std::path::Path) -> Vec<PathBuf> {
+    vec![dir.join("ipsum.md"), dir.join("lorem.md")]
+}
+
+/// The index is in the root directory, and is read back while nothing changed
+#[test]
+fn read_back() {
+    info!("read_back");
+    let (vault, config) = vault();
+    let dir = vault.pages_directory();
+    let index = write(&config);
+    assert_eq!(
+        IndexFile::path(&config),
+        vault.root().join(".mdlinker/index.json")
+    );
+    assert_eq!(
+        index.alias_table.get(&Alias::new("ipsum")),
+        Some(&dir.join("ipsum.md"))
+    );
+    let read = IndexFile::read(&config, &files(&dir)).expect("Nothing changed");
+    assert_eq!(read.files, index.files);
+}
+
+/// A changed, new or removed file makes the index stale
+#[test]
+fn stale() {
+    info!("stale");
+    let (vault, config) = vault();
+    let dir = vault.pages_directory();
+    write(&config);
+    fs::write(dir.join("ipsum.md"), "- A longer page\n").expect("Temp dir is writable");
+    assert!(IndexFile::read(&config, &files(&dir)).is_none());
+    write(&config);
+    let mut more = files(&dir);
+    more.push(dir.join("dolor.md"));
+    fs::write(dir.join("dolor.md"), "- New\n").expect("Temp dir is writable");
+    assert!(IndexFile::read(&config, &more).is_none());
+    assert!(IndexFile::read(&config, &files(&dir)[..1]).is_none());
+}
+
+/// An index made with other options is stale, even if no file changed
+#[test]
+fn other_config() {
+    info!("other_config");
+    let (vault, mut config) = vault();
+    let dir = vault.pages_directory();
+    write(&config);
+    assert!(IndexFile::read(&config, &files(&dir)).is_some());
+    config.skip_html = !config.skip_html;
+    assert!(IndexFile::read(&config, &files(&dir)).is_none());
+    // Options of the run don't change the index
+    config.skip_html = !config.skip_html;
+    config.fix = true;
+    assert!(IndexFile::read(&config, &files(&dir)).is_some());
+}
+
+/// `mdlinker links` reads the fresh index instead of parsing the files
+#[test]
+fn links_reads_the_index() {
+    info!("links_reads_the_index");
+    let (vault, config) = vault();
+    let dir = vault.pages_directory();
+    let mut index = write(&config);
+    let lorem = dir.join("lorem.md");
+    index.link_index.0.get_mut(&lorem).expect("Indexed").clear();
+    index.write(&config).expect("Temp dir is writable");
+    let saved = links(&config, None).expect("There should have been no error.");
+    assert_eq!(saved.get(&lorem).map(Vec::len), Some(0));
+    // Without the option, the files are parsed
+    let mut config = config;
+    config.write_index = false;
+    let parsed = links(&config, None).expect("There should have been no error.");
+    assert_eq!(parsed.get(&lorem).map(Vec::len), Some(1));
+}
@@ -0,0 +1,106 @@
+//! [`mdlinker::OutputReport::alias_table`] and [`mdlinker::OutputReport::link_index`], run over the
+//! assets of the backlinks tests
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::{config, file::content::wikilink::Alias, lib, links, OutputReport};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/backlinks/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/backlinks/assets/journals/");
+}
+
+fn get_report() -> OutputReport {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    lib(&config).expect("There should have been no error.")
+}
+
+/// Filenames and front matter aliases both resolve to their file
+#[test]
+fn alias_table() {
+    info!("alias_table");
+    let report = get_report();
+    let alias_table = report.alias_table();
+    debug!("{alias_table:?}");
+    assert_eq!(
+        alias_table.get(&Alias::new("lorem")),
+        Some(&PAGES.join("lorem.md"))
+    );
+    assert_eq!(
+        alias_table.get(&Alias::new("ipsum")),
+        Some(&PAGES.join("lorem.md"))
+    );
+    assert_eq!(
+        alias_table.get(&Alias::new("dolor")),
+        Some(&PAGES.join("dolor.md"))
+    );
+    assert_eq!(alias_table.get(&Alias::new("missing")), None);
+}
+
+/// Links are in the order they appear, and broken links have no target
+#[test]
+fn link_index() {
+    info!("link_index");
+    let report = get_report();
+    let link_index = report.link_index();
+    debug!("{link_index:?}");
+    let links: Vec<(String, Option<PathBuf>, usize, usize)> = link_index
+        .get(&JOURNALS.join("2024_11_01.md"))
+        .expect("Every file is in the index")
+        .iter()
+        .map(|link| {
+            (
+                link.alias.to_string(),
+                link.target.clone(),
+                link.line,
+                link.column,
+            )
+        })
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            ("lorem".to_owned(), Some(PAGES.join("lorem.md")), 1, 9),
+            ("ipsum".to_owned(), Some(PAGES.join("lorem.md")), 1, 24),
+            ("missing".to_owned(), None, 2, 11),
+        ]
+    );
+    assert!(link_index
+        .get(&PAGES.join("lorem.md"))
+        .expect("Every file is in the index")
+        .is_empty());
+}
+
+/// `mdlinker links --file`, as tsv, with tags marked and broken links unresolved
+#[test]
+fn links_tsv() {
+    info!("links_tsv");
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    let journal = JOURNALS.join("2024_11_01.md");
+    let links = links(&config, Some(&journal)).expect("There should have been no error.");
+    assert_eq!(links.0.len(), 1);
+    let journal = journal.display();
+    let lorem = PAGES.join("lorem.md");
+    let lorem = lorem.display();
+    assert_eq!(
+        links.to_tsv(),
+        format!(
+            "source\tline\tcolumn\toffset\tlength\talias\ttag\ttarget\n\
+             {journal}\t1\t9\t8\t9\tlorem\tfalse\t{lorem}\n\
+             {journal}\t1\t24\t23\t5\tipsum\ttrue\t{lorem}\n\
+             {journal}\t2\t11\t39\t11\tmissing\tfalse\tUNRESOLVED\n"
+        )
+    );
+}
@@ -0,0 +1,143 @@
+//! Linting one document in memory with [`mdlinker::lint_source`]
+use std::{collections::BTreeMap, path::PathBuf};
+
+use mdlinker::{
+    config::{cli::Config as CliConfig, file::Config as FileConfig, Config},
+    file::content::wikilink::Alias,
+    lint_source,
+    rules::{broken_wikilink, code_wikilink, duplicate_property},
+    try_lint_source, OutputErrors,
+};
+use tracing::info;
+
+fn config() -> Config {
+    Config::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .code_wikilinks(true)
+        .cli_config(CliConfig::default())
+        .file_config(FileConfig::default())
+        .build()
+}
+
+/// The text under the span of the first report of the rule `code`
+fn spanned<'a>(contents: &'a str, code: &str) -> &'a str {
+    let reports = lint_source("lorem.md", contents, &BTreeMap::new(), &config());
+    let report = reports
+        .iter()
+        .find(|report| report.id().0.starts_with(&format!("{code}::")))
+        .unwrap_or_else(|| panic!("{code} should have been reported"));
+    assert_eq!(report.path(), PathBuf::from("pages/lorem.md"));
+    let span = report.span().expect("The report has a span");
+    &contents[span.offset()..span.offset() + span.len()]
+}
+
+#[test]
+fn spans() {
+    info!("spans");
+    let contents = "---\ntitle: lorem\ntitle: again\n---\n- [[ipsum]] and `[[code]]`\n";
+    assert_eq!(spanned(contents, broken_wikilink::CODE), "[[ipsum]]");
+    assert_eq!(spanned(contents, code_wikilink::CODE), "[[code]]");
+    assert_eq!(spanned(contents, duplicate_property::CODE), "title");
+}
+
+/// Links to the document itself resolve, through its filename and its aliases
+#[test]
+fn own_aliases() {
+    info!("own_aliases");
+    let reports = lint_source(
+        "lorem.md",
+        "---\naliases: ipsum\n---\n- [[lorem]] [[ipsum]]\n",
+        &BTreeMap::new(),
+        &config(),
+    );
+    assert!(
+        !reports
+            .iter()
+            .any(|report| report.id().0.starts_with(broken_wikilink::CODE)),
+        "{reports:?}"
+    );
+}
+
+/// Links to the pages of the alias table resolve, and only the document is reported on
+#[test]
+fn alias_table() {
+    info!("alias_table");
+    let aliases = BTreeMap::from([
+        (Alias::new("ipsum"), PathBuf::from("pages/ipsum.md")),
+        (Alias::new("Dolor \"sit\""), PathBuf::from("pages/ipsum.md")),
+        (Alias::new("日本"), PathBuf::from("pages/japan.md")),
+        (Alias::new("lorem"), PathBuf::from("pages/lorem.md")),
+    ]);
+    let reports = lint_source(
+        "lorem.md",
+        "- [[ipsum]] [[dolor \"sit\"]] [[amet]]\n",
+        &aliases,
+        &config(),
+    );
+    let broken: Vec<_> = reports
+        .iter()
+        .filter(|report| report.id().0.starts_with(broken_wikilink::CODE))
+        .collect();
+    assert_eq!(broken.len(), 1, "{reports:?}");
+    assert!(broken[0].id().0.contains("amet"), "{broken:?}");
+    assert!(
+        reports
+            .iter()
+            .all(|report| report.path() == PathBuf::from("pages/lorem.md")),
+        "{reports:?}"
+    );
+}
+
+/// Documents which can't be parsed have no reports, rather than failing
+#[test]
+fn unparsable() {
+    info!("unparsable");
+    let aliases = BTreeMap::new();
+    assert!(lint_source("lorem.md", "- [[日本]]\n", &aliases, &config()).is_empty());
+    assert!(lint_source("lorem.md", "---\n: [\n---\n", &aliases, &config()).is_empty());
+    assert!(matches!(
+        try_lint_source("lorem.md", "- [[日本]]\n", &aliases, &config()),
+        Err(OutputErrors::ParseError(_))
+    ));
+}
+
+/// Every span of every report is in the document, for documents made of every pair of
+/// fragments
+#[test]
+fn spans_in_bounds() {
+    info!("spans_in_bounds");
+    let fragments = [
+        "",
+        "---\naliases: [ipsum, dolor]\ntitle: a\ntitle: b\n---\n",
+        "- lorem ipsum\n",
+        "- [[ipsum]] [[Dolor]] [[missing]]\n",
+        "`[[code]]`\n",
+        "```\n[[fenced]]\n```\n",
+        "| [[a\\|b]] | c |\n|---|---|\n| [[d]] | e |\n",
+        "- #tag #[[other tag]]\n",
+        "<div>[[html]]</div>\n",
+        "\r\n- [[crlf]]\r\n",
+        "[see](ipsum.md) ((6720a5b1-0000-0000-0000-000000000000))\n",
+        "[[",
+        "]]\n",
+        "- \u{200b}lorem\n",
+    ];
+    let aliases = BTreeMap::from([(Alias::new("ipsum"), PathBuf::from("pages/ipsum.md"))]);
+    let config = config();
+    for first in fragments {
+        for second in fragments {
+            let contents = format!("{first}{second}");
+            for report in lint_source("lorem.md", &contents, &aliases, &config) {
+                let Some(span) = report.span() else {
+                    continue;
+                };
+                let end = span.offset() + span.len();
+                assert!(end <= contents.len(), "{report:?} in {contents:?}");
+                assert!(
+                    contents.is_char_boundary(span.offset()) && contents.is_char_boundary(end),
+                    "{report:?} in {contents:?}"
+                );
+            }
+        }
+    }
+}
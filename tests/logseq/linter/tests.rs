@@ -0,0 +1,100 @@
+//! The [`mdlinker::linter::Linter`] api, run over the assets of other tests
+use lazy_static::lazy_static;
+use mdlinker::{
+    linter::Linter,
+    progress::{Pass, ProgressSink},
+    rules::Report,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tracing::info;
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/broken_wikilink/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/broken_wikilink/assets/journals/");
+}
+
+#[test]
+fn plain_values() {
+    info!("plain_values");
+    let report = Linter::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert_eq!(report.broken_wikilinks().len(), 5);
+}
+
+#[test]
+fn rule_toggles() {
+    info!("rule_toggles");
+    let report = Linter::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .broken_wikilink(false)
+        .unlinked_text(false)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert!(report.broken_wikilinks().is_empty());
+    assert!(report.unlinked_texts().is_empty());
+}
+
+/// The separator is used in the pattern which converts aliases to filenames
+#[test]
+fn invalid_hierarchy_separator() {
+    info!("invalid_hierarchy_separator");
+    assert!(Linter::builder()
+        .pages_directory(PAGES.clone())
+        .hierarchy_separator("(".to_owned())
+        .build()
+        .is_err());
+}
+
+/// Remembers the events it is sent
+#[derive(Default)]
+struct Recorder {
+    passes: Mutex<Vec<Pass>>,
+    files: Mutex<Vec<PathBuf>>,
+    reports: Mutex<usize>,
+}
+
+impl ProgressSink for Recorder {
+    fn pass_started(&self, pass: Pass, _total: Option<u64>) {
+        self.passes.lock().unwrap().push(pass);
+    }
+    fn file_parsed(&self, path: &Path) {
+        self.files.lock().unwrap().push(path.to_path_buf());
+    }
+    fn report_emitted(&self, _report: &Report) {
+        *self.reports.lock().unwrap() += 1;
+    }
+}
+
+#[test]
+fn progress_events() {
+    info!("progress_events");
+    let recorder = Arc::new(Recorder::default());
+    let report = Linter::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .progress(recorder.clone())
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    assert_eq!(
+        *recorder.passes.lock().unwrap(),
+        vec![Pass::SimilarFilenames, Pass::Aliases, Pass::Links]
+    );
+    // Each file is parsed once for aliases and once for links
+    let files = recorder.files.lock().unwrap();
+    assert_eq!(files.len(), 10);
+    assert!(files.contains(&JOURNALS.join("2024_11_01.md")));
+    assert_eq!(*recorder.reports.lock().unwrap(), report.reports.len());
+}
@@ -1,5 +1,66 @@
+mod alias_casing;
+mod alias_index;
+mod aliases;
+mod ambiguous_wikilink;
+mod backlinks;
+mod blockquote;
+mod broken_reference;
 mod broken_wikilink;
+mod code_wikilink;
 pub mod common;
+mod completed_tasks;
+mod config_format;
+mod confusable_character;
+mod crlf;
+mod custom_rule;
+mod dendron;
+#[cfg(feature = "git")]
+mod diff_base;
 mod duplicate_alias;
+mod duplicate_content;
+mod duplicate_property;
+mod exclude;
+mod export;
+mod fingerprint;
+mod fix;
+mod front_matter;
+mod front_matter_key;
+mod global_config;
+mod graph;
+mod html;
+mod hub_page;
+mod ignored_wikilink;
+mod illegal_filename;
+mod in_memory;
+mod index_file;
+mod link_index;
+mod lint_source;
+mod linter;
+mod markdown;
+mod merge;
+mod mv;
+mod namespace;
+mod new;
+mod no_vcs;
+mod notebook;
+mod obsidian;
+mod plan;
+mod query;
+mod relative_wikilink;
+mod relref;
+#[cfg(feature = "review")]
+mod review;
+mod rule_timeout;
+mod serialize;
+mod severity;
 mod similar_filename;
+mod source_memory_limit;
+mod spans;
+mod symlinks;
+mod table;
+mod tune_similarity;
+mod underlinked_page;
+mod unknown_tag;
 mod unlinked_text;
+mod vault;
+mod wrong_link_style;
@@ -0,0 +1,83 @@
+//! Parsing with the markdown extensions of [`mdlinker::config::MarkdownExtensions`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::MarkdownExtensions, file::provider::InMemory, linter::Linter, OutputReport,
+};
+use tracing::info;
+
+fn run(source: &str, markdown: MarkdownExtensions) -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", source)
+        .with_file("pages/ipsum.md", "- Ipsum\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .markdown(markdown)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// The unlinked texts of `ipsum` in `lorem.md`
+fn ipsum_in_lorem(report: &OutputReport) -> usize {
+    report
+        .unlinked_texts()
+        .iter()
+        .filter(|x| x.alias().to_string() == "ipsum")
+        .filter(|x| mdlinker::rules::ReportTrait::path(*x).ends_with("lorem.md"))
+        .count()
+}
+
+/// Footnote labels are text without the extension, and may be reported as unlinked text
+#[test]
+fn footnotes() {
+    info!("footnotes");
+    let source = "- Lorem[^ipsum] and [^dolor]\n\n[^ipsum]: Sit\n";
+    let off = run(source, MarkdownExtensions::default());
+    assert_eq!(ipsum_in_lorem(&off), 1);
+    let on = run(
+        source,
+        MarkdownExtensions {
+            footnotes: true,
+            ..MarkdownExtensions::default()
+        },
+    );
+    assert_eq!(ipsum_in_lorem(&on), 0);
+}
+
+/// Without a delimiter, front matter is text
+#[test]
+fn front_matter_delimiter() {
+    info!("front_matter_delimiter");
+    let source = "---\nalias: ipsum\n---\n- Lorem\n";
+    let report = run(source, MarkdownExtensions::default());
+    assert_eq!(ipsum_in_lorem(&report), 0);
+    assert_eq!(report.duplicate_aliases().len(), 1);
+    let report = run(
+        source,
+        MarkdownExtensions {
+            front_matter_delimiter: String::new(),
+            ..MarkdownExtensions::default()
+        },
+    );
+    assert!(report.duplicate_aliases().is_empty());
+}
+
+/// The extensions are read from a `[markdown]` table, with the rest left as they are
+#[test]
+fn config() {
+    info!("config");
+    let config: mdlinker::config::file::Config = toml::from_str(
+        "pages_directory = \"pages\"\nother_directories = []\n\n[markdown]\nfootnotes = true\n",
+    )
+    .expect("The config is valid");
+    assert_eq!(
+        config.markdown,
+        Some(MarkdownExtensions {
+            footnotes: true,
+            ..MarkdownExtensions::default()
+        })
+    );
+}
@@ -0,0 +1,102 @@
+//! Merging one page into another, see [`mdlinker::merge::merge`]
+use std::fs;
+
+use mdlinker::{
+    config::Config,
+    merge::{merge, MergeError},
+    testutil::{TempVault, Vault},
+};
+use tracing::info;
+
+/// A vault with `files` in it
+fn vault(files: &[(&str, &str)]) -> TempVault {
+    files
+        .iter()
+        .fold(Vault::new(), |vault, (path, content)| {
+            vault.with_file(path, content)
+        })
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+fn config(vault: &TempVault) -> Config {
+    let mut config = vault.config();
+    config.no_vcs = true;
+    config
+}
+
+/// The body is appended, the name and aliases become aliases, and links keep their text
+#[test]
+fn merge_pages() {
+    info!("merge_pages");
+    let vault = vault(&[
+        (
+            "pages/lorem.md",
+            "---\nalias: lorem-alias\nstatus: draft\n---\n- Lorem body [[ipsum]]\n",
+        ),
+        (
+            "pages/ipsum.md",
+            "---\nalias: Ipsum Alias\n---\n- Ipsum body\n",
+        ),
+        (
+            "journals/2024_11_01.md",
+            "- [[lorem]] and [[Text|lorem-alias]] and #lorem\n",
+        ),
+    ]);
+    let (dir, config) = (vault.root(), config(&vault));
+    let merged = merge(
+        &config,
+        &dir.join("pages/lorem.md"),
+        &dir.join("pages/ipsum.md"),
+    )
+    .expect("The merge is valid");
+    assert_eq!(merged.aliases, vec!["lorem", "lorem-alias"]);
+    assert_eq!(merged.rewrites.len(), 3, "{merged}");
+    assert!(!dir.join("pages/lorem.md").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join("pages/ipsum.md")).expect("The page exists"),
+        "---\nalias: Ipsum Alias, lorem, lorem-alias\nstatus: draft\n---\n- Ipsum body\n- Lorem body [[ipsum]]\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("journals/2024_11_01.md")).expect("The journal exists"),
+        "- [[lorem|ipsum]] and [[Text|ipsum]] and #[[ipsum]]\n"
+    );
+}
+
+/// Aliases are written in a list when that's how they were written
+#[test]
+fn merge_into_page_without_front_matter() {
+    info!("merge_into_page_without_front_matter");
+    let vault = vault(&[
+        ("pages/amet.md", "---\naliases: [sit]\n---\n- Sit body\n"),
+        ("pages/dolor.md", "- Dolor\n"),
+        ("journals/2024_11_01.md", "- Nothing\n"),
+    ]);
+    let (dir, config) = (vault.root(), config(&vault));
+    merge(
+        &config,
+        &dir.join("pages/amet.md"),
+        &dir.join("pages/dolor.md"),
+    )
+    .expect("The merge is valid");
+    assert_eq!(
+        fs::read_to_string(dir.join("pages/dolor.md")).expect("The page exists"),
+        "---\naliases:\n  - amet\n  - sit\n---\n- Dolor\n- Sit body\n"
+    );
+}
+
+#[test]
+fn merge_errors() {
+    info!("merge_errors");
+    let vault = vault(&[
+        ("pages/lorem.md", "- Lorem\n"),
+        ("journals/2024_11_01.md", "- Nothing\n"),
+    ]);
+    let (dir, config) = (vault.root(), config(&vault));
+    let lorem = dir.join("pages/lorem.md");
+    let out = merge(&config, &lorem, &lorem);
+    assert!(matches!(out, Err(MergeError::SamePage)), "{out:?}");
+    let out = merge(&config, &dir.join("pages/missing.md"), &lorem);
+    assert!(matches!(out, Err(MergeError::NotAPage(_))), "{out:?}");
+    assert!(lorem.exists());
+}
@@ -0,0 +1,107 @@
+//! Moving a directory and rewriting the links into and out of it, see [`mdlinker::mv::mv`]
+use std::fs;
+
+use mdlinker::{
+    config::{Config, LinkResolution},
+    mv::{mv, MoveError},
+    testutil::{TempVault, Vault},
+};
+use tracing::info;
+
+/// A vault with `files` in it
+fn vault(files: &[(&str, &str)]) -> TempVault {
+    files
+        .iter()
+        .fold(Vault::new(), |vault, (path, content)| {
+            vault.with_file(path, content)
+        })
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+/// The config of the vault, where every file at its root is a page
+fn config(vault: &TempVault, link_resolution: LinkResolution) -> Config {
+    let mut config = vault.config();
+    config.pages_directory = vault.root().to_path_buf();
+    config.link_resolution = link_resolution;
+    config.no_vcs = true;
+    config
+}
+
+/// Links into the directory follow it, relative links out of it are rewritten from where it ends up,
+/// and wikilinks to aliases are left alone
+#[test]
+fn absolute_links_are_rewritten() {
+    info!("absolute_links_are_rewritten");
+    let vault = vault(&[
+            ("projects/lorem.md", "- Lorem, see [the index](../index.md)\n"),
+            ("projects/sub/ipsum.md", "- Ipsum\n"),
+            (
+                "index.md",
+                "- [[projects/lorem]] [[Ipsum|projects/sub/ipsum]] [lorem](projects/lorem.md#top) [[lorem]]\n",
+            ),
+        ]);
+    let (dir, config) = (vault.root(), config(&vault, LinkResolution::Absolute));
+    let moved = mv(
+        &config,
+        &dir.join("projects"),
+        &dir.join("archive/projects"),
+    )
+    .expect("The move is valid");
+    assert_eq!(moved.rewrites.len(), 4, "{moved}");
+    assert!(!dir.join("projects").exists());
+    assert_eq!(
+        fs::read_to_string(dir.join("index.md")).expect("The index exists"),
+        "- [[archive/projects/lorem]] [[Ipsum|archive/projects/sub/ipsum]] [lorem](archive/projects/lorem.md#top) [[lorem]]\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("archive/projects/lorem.md")).expect("The page was moved"),
+        "- Lorem, see [the index](../../index.md)\n"
+    );
+    assert!(dir.join("archive/projects/sub/ipsum.md").exists());
+}
+
+/// Relative wikilinks between files which stay the same distance apart are left alone
+#[test]
+fn relative_links_are_rewritten() {
+    info!("relative_links_are_rewritten");
+    let vault = vault(&[
+        ("projects/lorem.md", "- [[../index]]\n"),
+        ("index.md", "- [[projects/lorem]]\n"),
+    ]);
+    let (dir, config) = (vault.root(), config(&vault, LinkResolution::Relative));
+    let moved = mv(&config, &dir.join("projects"), &dir.join("work")).expect("The move is valid");
+    let rewrites: Vec<(String, String)> = moved
+        .rewrites
+        .iter()
+        .map(|rewrite| (rewrite.from.clone(), rewrite.to.clone()))
+        .collect();
+    assert_eq!(
+        rewrites,
+        vec![("projects/lorem".to_owned(), "work/lorem".to_owned())]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("work/lorem.md")).expect("The page was moved"),
+        "- [[../index]]\n"
+    );
+}
+
+/// Nothing is moved onto something which exists
+#[test]
+fn existing_destination_is_refused() {
+    info!("existing_destination_is_refused");
+    let vault = vault(&[
+        ("projects/lorem.md", "- Lorem\n"),
+        ("work/ipsum.md", "- Ipsum\n"),
+    ]);
+    let (dir, config) = (vault.root(), config(&vault, LinkResolution::Absolute));
+    let out = mv(&config, &dir.join("projects"), &dir.join("work"));
+    assert!(matches!(out, Err(MoveError::AlreadyExists(_))), "{out:?}");
+    assert!(dir.join("projects/lorem.md").exists());
+    let out = mv(
+        &config,
+        &dir.join("projects/lorem.md"),
+        &dir.join("lorem.md"),
+    );
+    assert!(matches!(out, Err(MoveError::NotADirectory(_))), "{out:?}");
+}
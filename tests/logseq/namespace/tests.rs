@@ -0,0 +1,87 @@
+//! Rules for the pages of a namespace, see [`mdlinker::config::Namespace`]
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::{Namespace, NamespaceRule},
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{similar_filename, unlinked_text, ReportTrait},
+};
+use miette::Severity;
+use tracing::info;
+
+fn namespace(prefix: &str, rule: &str, setting: NamespaceRule) -> Namespace {
+    Namespace {
+        prefix: prefix.to_owned(),
+        rules: BTreeMap::from([(rule.to_owned(), setting)]),
+    }
+}
+
+/// Similar filenames are only off when both files are in the namespace
+#[test]
+fn similar_filenames_off_in_namespace() {
+    info!("similar_filenames_off_in_namespace");
+    let files = InMemory::new()
+        .with_file("pages/people___johnathan smith.md", "- lorem\n")
+        .with_file("pages/people___johnathan smyth.md", "- ipsum\n")
+        .with_file("pages/projects___garden planning.md", "- dolor\n")
+        .with_file("pages/projects___garden plannings.md", "- sit\n");
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .namespace(vec![namespace(
+            "people",
+            similar_filename::CODE,
+            NamespaceRule::Off,
+        )])
+        .build()
+        .expect("The defaults are valid");
+    let similar_filenames = linter
+        .run()
+        .expect("There should have been no error.")
+        .similar_filenames();
+    assert!(!similar_filenames.is_empty());
+    assert!(
+        similar_filenames.iter().all(|report| report
+            .paths()
+            .iter()
+            .all(|path| path.to_string_lossy().contains("projects___"))),
+        "{similar_filenames:#?}"
+    );
+}
+
+#[test]
+fn severity_in_namespace() {
+    info!("severity_in_namespace");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- A page\n")
+        .with_file("pages/drafts.md", "- About lorem\n")
+        .with_file("pages/drafts___ipsum.md", "- Also about lorem\n")
+        .with_file("pages/dolor.md", "- Lorem again\n");
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .namespace(vec![namespace(
+            "Drafts",
+            "content::alias::*",
+            NamespaceRule::Advice,
+        )])
+        .build()
+        .expect("The defaults are valid");
+    let report = linter.run().expect("There should have been no error.");
+    let mut severities: Vec<(PathBuf, Severity)> = report
+        .reports
+        .iter()
+        .filter(|report| report.id().0.starts_with(unlinked_text::CODE))
+        .map(|report| (report.path(), linter.config().severity(report)))
+        .collect();
+    severities.sort_by_key(|(path, _)| path.clone());
+    assert_eq!(
+        severities,
+        vec![
+            (PathBuf::from("pages/dolor.md"), Severity::Error),
+            (PathBuf::from("pages/drafts.md"), Severity::Advice),
+            (PathBuf::from("pages/drafts___ipsum.md"), Severity::Advice),
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+//! Creating pages, see [`mdlinker::new::new_page`]
+use std::fs;
+
+use mdlinker::{
+    config::Config,
+    linter::Linter,
+    new::{new_page, NewPageError},
+    testutil::{Page, TempVault, Vault},
+};
+use tracing::info;
+
+/// A vault with a page, a journal, and a template outside of them
+fn vault() -> TempVault {
+    Vault::new()
+        .with_page(&Page::new("lorem").with_line("- Lorem"))
+        .with_file("journals/2024_11_01.md", "- Today")
+        .with_file("templates/page.md", "title:: {{title}}\n- \n")
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+/// The config of the vault, with its template
+fn config(vault: &TempVault) -> Config {
+    let mut config = vault.config();
+    config.new_file_template = Some(vault.root().join("templates/page.md"));
+    config.no_vcs = true;
+    config
+}
+
+/// The page is named with alias_to_filename in its case, and linked from the journal
+#[test]
+fn new_page_from_template() {
+    info!("new_page_from_template");
+    let vault = vault();
+    let (dir, config) = (vault.root(), config(&vault));
+    let journal = dir.join("journals/2024_11_01.md");
+    let page = new_page(&config, "Ipsum/Dolor", Some(&journal)).expect("The page is new");
+    assert_eq!(page.path, dir.join("pages/Ipsum___Dolor.md"));
+    assert_eq!(
+        fs::read_to_string(&page.path).expect("The page was created"),
+        "title:: Ipsum/Dolor\n- \n"
+    );
+    assert_eq!(
+        fs::read_to_string(&journal).expect("The journal exists"),
+        "- Today\n- [[Ipsum/Dolor]]\n"
+    );
+}
+
+/// A page can't be created for an alias which already resolves
+#[test]
+fn new_page_already_exists() {
+    info!("new_page_already_exists");
+    let vault = vault();
+    let config = config(&vault);
+    let out = new_page(&config, "Lorem", None);
+    assert!(
+        matches!(out, Err(NewPageError::AlreadyExists { .. })),
+        "{out:?}"
+    );
+}
+
+/// The broken wikilink fix creates pages from the template too
+#[test]
+fn broken_wikilink_fix_uses_template() {
+    info!("broken_wikilink_fix_uses_template");
+    let vault = vault();
+    fs::write(vault.root().join("journals/2024_11_01.md"), "- [[sit]]\n")
+        .expect("Temp dir is writable");
+    let mut config = config(&vault);
+    config.fix = true;
+    Linter::from_config(config)
+        .run()
+        .expect("Fixing should not fail");
+    assert_eq!(
+        vault
+            .read("pages/sit.md")
+            .expect("The fix created the page"),
+        "title:: sit\n- \n"
+    );
+}
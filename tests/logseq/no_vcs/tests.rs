@@ -0,0 +1,39 @@
+use mdlinker::{file::backup::BACKUP_DIRECTORY, linter::Linter, testutil::Vault};
+use std::{fs, path::PathBuf};
+use tracing::info;
+
+const ASSETS: &str = "./tests/logseq/no_vcs/assets/";
+
+/// Fixing doesn't look at git, and the original file is backed up once, before any fixes
+#[test]
+fn fix_backs_up_files() {
+    info!("fix_backs_up_files");
+    let mut vault = Vault::new();
+    for file in ["pages/lorem.md", "journals/2024_11_01.md"] {
+        let content = fs::read_to_string(PathBuf::from(ASSETS).join(file))
+            .expect("This exists at compile time");
+        vault = vault.with_file(file, &content);
+    }
+    let vault = vault.write().expect("The temporary directory is writable");
+    let journal = "journals/2024_11_01.md";
+    let original = vault.read(journal).expect("The journal exists");
+    let mut config = vault.config();
+    config.fix = true;
+    config.no_vcs = true;
+    let report = Linter::from_config(config)
+        .run()
+        .expect("Fixing should not need git");
+    assert!(report.unlinked_texts().is_empty(), "{:?}", report.reports);
+    let fixed = vault.read(journal).expect("The journal exists");
+    assert_eq!(fixed, "- [[lorem]] ipsum\n- more [[lorem]]\n");
+
+    let runs: Vec<PathBuf> = fs::read_dir(vault.root().join("journals").join(BACKUP_DIRECTORY))
+        .expect("The backup directory was made")
+        .map(|entry| entry.expect("Readable").path())
+        .collect();
+    assert_eq!(runs.len(), 1);
+    let backup = fs::read_to_string(runs[0].join("2024_11_01.md")).expect("The backup exists");
+    assert_eq!(backup, original);
+    // Files which were not edited are not backed up
+    assert!(!vault.pages_directory().join(BACKUP_DIRECTORY).exists());
+}
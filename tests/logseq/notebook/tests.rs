@@ -0,0 +1,90 @@
+use lazy_static::lazy_static;
+use mdlinker::config::{cli::Config as CliConfig, file::Config as FileConfig, Config};
+use mdlinker::export::ExportedReport;
+use mdlinker::file::content::notebook::NotebookMarkdown;
+use mdlinker::rules::{broken_wikilink, filter_code, unlinked_text, Report, ThirdPassReport};
+
+use crate::common::get_report;
+use tracing::{debug, info};
+
+use itertools::Itertools;
+
+lazy_static! {
+    static ref PATHS: Vec<String> = vec![
+        "./tests/logseq/notebook/assets/pages/".to_string(),
+        "./tests/logseq/notebook/assets/journals/".to_string()
+    ];
+}
+
+const NOTEBOOK: &str = "./tests/logseq/notebook/assets/pages/research.ipynb";
+
+/// Only the markdown cells are linted, so `sit` in the code cell is not a broken wikilink
+#[test]
+fn broken_wikilinks_in_markdown_cells() {
+    info!("broken_wikilinks_in_markdown_cells");
+    let report = get_report(PATHS.as_slice(), None);
+    for broken_wikilink in &report.broken_wikilinks() {
+        debug!("{broken_wikilink:#?}");
+    }
+    let err = report.broken_wikilinks().into_iter().exactly_one().unwrap();
+    assert!(!filter_code(
+        vec![err],
+        &format!("{}::research::dolor", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+/// The notebook's filename and front matter aliases are in the alias table
+#[test]
+fn notebook_aliases_resolve() {
+    info!("notebook_aliases_resolve");
+    let report = get_report(PATHS.as_slice(), None);
+    assert!(filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::2024_11_01", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+/// The span of unlinked text maps back to the last cell of the notebook
+#[test]
+fn unlinked_text_span_maps_to_cell() {
+    info!("unlinked_text_span_maps_to_cell");
+    let report = get_report(PATHS.as_slice(), None);
+    let err_list = filter_code(
+        report.unlinked_texts(),
+        &format!("{}::research::ipsum", unlinked_text::CODE).into(),
+    );
+    let err = err_list.iter().exactly_one().unwrap();
+    let json = std::fs::read_to_string(NOTEBOOK).expect("This exists at compile time");
+    let notebook = NotebookMarkdown::new(&json).expect("This is a valid notebook");
+    assert_eq!(notebook.locate(err.span.offset()), Some((2, 13)));
+    assert_eq!(err.span.len(), 5);
+}
+
+/// Exported reports give the line and column within the cell, like editors show notebooks
+#[test]
+fn exported_position_in_cell() {
+    info!("exported_position_in_cell");
+    let config = Config::builder()
+        .pages_directory(PATHS[0].clone().into())
+        .other_directories(vec![PATHS[1].clone().into()])
+        .cli_config(CliConfig::default())
+        .file_config(FileConfig::default())
+        .build();
+    let report = get_report(PATHS.as_slice(), Some(config.clone()));
+    let err = filter_code(
+        report.unlinked_texts(),
+        &format!("{}::research::ipsum", unlinked_text::CODE).into(),
+    )
+    .into_iter()
+    .exactly_one()
+    .unwrap();
+    let exported = ExportedReport::new(
+        &report,
+        &Report::ThirdPass(ThirdPassReport::UnlinkedText(err)),
+        &config,
+    );
+    assert_eq!(exported.cell, Some(2));
+    assert_eq!((exported.line, exported.column), (Some(1), Some(14)));
+}
@@ -0,0 +1,126 @@
+//! A vault with Obsidian settings in `.obsidian/app.json`
+use std::{fs, path::PathBuf};
+
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use config::{obsidian, LinkResolution, Partial};
+use itertools::Itertools;
+use mdlinker::config;
+use mdlinker::rules::{broken_wikilink, filter_code, ReportTrait};
+use mdlinker::testutil::Vault;
+use tracing::{debug, info};
+
+use crate::common::get_report;
+
+const ROOT: &str = "./tests/logseq/obsidian/assets";
+
+/// The config a user would get by running mdlinker at the root of the vault
+fn vault_config(root: PathBuf) -> config::Config {
+    let obsidian = obsidian::Config::new(&root)
+        .expect("The app.json is valid")
+        .expect("This is an obsidian vault");
+    config::Config::builder()
+        .pages_directory(root.clone())
+        .root_directory(root)
+        .maybe_new_files_directory(obsidian.new_files_directory())
+        .maybe_attachments_directory(obsidian.attachments_directory())
+        .maybe_link_resolution(obsidian.link_resolution())
+        .maybe_wikilinks(obsidian.wikilinks())
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build()
+}
+
+/// Absolute paths and attachments resolve, so only the missing page is broken
+#[test]
+fn absolute_links_and_attachments_resolve() {
+    info!("absolute_links_and_attachments_resolve");
+    let report = get_report(&[], Some(vault_config(PathBuf::from(ROOT))));
+    for broken_wikilink in &report.broken_wikilinks() {
+        debug!("{broken_wikilink:?}");
+    }
+    assert_eq!(report.broken_wikilinks().len(), 1);
+    assert!(!filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::lorem::missing", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+/// Without the vault settings, paths are not aliases, so they are broken
+#[test]
+fn alias_only_does_not_resolve_paths() {
+    info!("alias_only_does_not_resolve_paths");
+    let mut config = vault_config(PathBuf::from(ROOT));
+    config.link_resolution = LinkResolution::AliasOnly;
+    let report = get_report(&[], Some(config));
+    for broken_wikilink in &report.broken_wikilinks() {
+        debug!("{broken_wikilink:?}");
+    }
+    assert!(!filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::lorem::notes/projects/ipsum", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+    assert!(!filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::ipsum::notes/lorem", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+    // Attachments still resolve by their file name
+    assert!(filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::lorem::diagram.png", broken_wikilink::CODE).into()
+    )
+    .is_empty());
+}
+
+/// The fix creates the page in the vault's folder for new notes
+#[test]
+fn fix_creates_page_in_new_files_directory() {
+    info!("fix_creates_page_in_new_files_directory");
+    let mut vault = Vault::new();
+    for file in [".obsidian/app.json", "notes/lorem.md"] {
+        let content = fs::read_to_string(PathBuf::from(ROOT).join(file))
+            .expect("This exists at compile time");
+        vault = vault.with_file(file, &content);
+    }
+    let vault = vault.write().expect("The temporary directory is writable");
+    let dir = vault.root().to_path_buf();
+
+    let report = get_report(&[], Some(vault_config(dir.clone())));
+    let err = filter_code(
+        report.broken_wikilinks(),
+        &format!("{}::lorem::missing", broken_wikilink::CODE).into(),
+    )
+    .into_iter()
+    .exactly_one()
+    .unwrap();
+    err.fix(&vault_config(dir.clone()))
+        .expect("The fix should work");
+
+    assert!(dir.join("notes").join("missing.md").is_file());
+    assert!(!dir.join("missing.md").exists());
+}
+
+/// The link resolution can be set in the config file too, which takes priority over the vault's
+#[test]
+fn link_resolution_from_config_file() {
+    info!("link_resolution_from_config_file");
+    for (value, expected) in [
+        ("alias_only", LinkResolution::AliasOnly),
+        ("shortest_path", LinkResolution::ShortestPath),
+        ("relative", LinkResolution::Relative),
+        ("absolute", LinkResolution::Absolute),
+    ] {
+        let file_config: FileConfig = toml::from_str(&format!(
+            "pages_directory = \"pages\"\nother_directories = []\nlink_resolution = \"{value}\""
+        ))
+        .expect("This is a valid config file");
+        assert_eq!(file_config.link_resolution(), Some(expected));
+    }
+    let file_config: FileConfig =
+        toml::from_str("pages_directory = \"pages\"\nother_directories = []")
+            .expect("This is a valid config file");
+    assert_eq!(file_config.link_resolution(), None);
+}
@@ -0,0 +1,107 @@
+//! The passes run for the rules which are on, see [`mdlinker::plan::Plan`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    file::{content::wikilink::Alias, provider::InMemory},
+    linter::Linter,
+    plan::{Input, Plan},
+    progress::Pass,
+    rules::ThirdPassRule,
+};
+use strum::IntoEnumIterator;
+use tracing::info;
+
+fn linter() -> Linter {
+    let files = InMemory::new()
+        .with_file(
+            "pages/lorem.md",
+            "---\nalias: dolor\n---\n- [[ipsum]] and sit\n",
+        )
+        .with_file("pages/ipsum.md", "- A page about lorem\n")
+        .with_file("pages/sit.md", "- [[missing]]\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The config is valid")
+}
+
+/// Every rule of the second pass turned off
+fn without_content_rules(mut linter: Linter) -> Linter {
+    linter.broken_wikilink = false;
+    linter.unlinked_text = false;
+    linter.broken_relref = false;
+    linter.unknown_tag = false;
+    linter.duplicate_property = false;
+    linter.duplicate_content = false;
+    linter.ignored_wikilink = false;
+    linter.hub_page = false;
+    linter.underlinked_page = false;
+    linter.alias_casing = false;
+    linter.confusable_character = false;
+    linter.illegal_filename = false;
+    linter.front_matter_key = false;
+    linter.wrong_link_style = false;
+    linter.code_wikilink = false;
+    linter.broken_reference = false;
+    linter
+}
+
+#[test]
+fn defaults() {
+    info!("defaults");
+    let plan = Plan::new(&linter());
+    for input in Input::iter() {
+        assert!(plan.needs(input), "{input:?}");
+        assert!(plan.runs(input.pass()), "{input:?}");
+    }
+    assert!(plan.rules.contains(&ThirdPassRule::BrokenWikilink));
+    assert!(!plan.rules.contains(&ThirdPassRule::RelativeWikilink));
+    assert!(!plan.rules.contains(&ThirdPassRule::CodeWikilink));
+}
+
+/// With only filename rules on, the content of the files is never parsed
+#[test]
+fn only_filenames() {
+    info!("only_filenames");
+    let mut linter = without_content_rules(linter());
+    linter.duplicate_alias = false;
+    let plan = Plan::new(&linter);
+    assert!(plan.rules.is_empty());
+    assert!(plan.runs(Pass::SimilarFilenames));
+    assert!(!plan.runs(Pass::Aliases));
+    assert!(!plan.runs(Pass::Links));
+    let report = linter.run().expect("There should have been no error.");
+    assert!(report.alias_table().is_empty());
+    assert!(report.link_index().0.is_empty());
+}
+
+/// The duplicate aliases are found without the second pass
+#[test]
+fn only_aliases() {
+    info!("only_aliases");
+    let mut linter = without_content_rules(linter());
+    linter.similar_filename = false;
+    let plan = Plan::new(&linter);
+    assert!(!plan.runs(Pass::SimilarFilenames));
+    assert!(plan.runs(Pass::Aliases));
+    assert!(!plan.runs(Pass::Links));
+    let report = linter.run().expect("There should have been no error.");
+    assert!(report.alias_table().contains_key(&Alias::new("dolor")));
+    assert!(report.link_index().0.is_empty());
+}
+
+/// The alias index is only filled in for the rules which read it
+#[test]
+fn without_alias_index() {
+    info!("without_alias_index");
+    let mut linter = linter();
+    linter.broken_wikilink = false;
+    linter.unlinked_text = false;
+    let plan = Plan::new(&linter);
+    assert!(!plan.needs(Input::AliasIndex));
+    assert!(plan.runs(Pass::Links));
+    let report = linter.run().expect("There should have been no error.");
+    assert!(report.alias_index().alias_table().is_empty());
+    assert!(!report.link_index().0.is_empty());
+}
@@ -0,0 +1,84 @@
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::query::{Reference, ReferenceKind};
+use mdlinker::{config, query};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/query/assets/pages/");
+    static ref JOURNALS: PathBuf = PathBuf::from("./tests/logseq/query/assets/journals/");
+}
+
+fn get_query(alias: &str) -> mdlinker::query::Query {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .other_directories(vec![JOURNALS.clone()])
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build();
+    let query = query(&config, alias).expect("There should have been no error.");
+    debug!("{query}");
+    query
+}
+
+/// Both the page named ipsum and the page with the alias ipsum define it
+#[test]
+fn definition_and_duplicates() {
+    info!("definition_and_duplicates");
+    let query = get_query("ipsum");
+    let mut definitions: Vec<PathBuf> = query.duplicates.clone();
+    definitions.extend(query.definition.clone());
+    definitions.sort();
+    assert_eq!(
+        definitions,
+        vec![PAGES.join("ipsum.md"), PAGES.join("lorem.md")]
+    );
+    assert_eq!(query.duplicates.len(), 1);
+}
+
+#[test]
+fn references() {
+    info!("references");
+    let query = get_query("IPSUM");
+    let journal = JOURNALS.join("2024_11_01.md");
+    let journal_references: Vec<Reference> = query
+        .references
+        .iter()
+        .filter(|x| x.path == journal)
+        .cloned()
+        .collect();
+    assert_eq!(
+        journal_references,
+        vec![
+            Reference {
+                path: journal.clone(),
+                line: 1,
+                column: 10,
+                kind: ReferenceKind::Wikilink
+            },
+            Reference {
+                path: journal.clone(),
+                line: 2,
+                column: 11,
+                kind: ReferenceKind::Tag
+            },
+            Reference {
+                path: journal.clone(),
+                line: 3,
+                column: 13,
+                kind: ReferenceKind::Unlinked
+            },
+        ]
+    );
+}
+
+#[test]
+fn undefined_alias() {
+    info!("undefined_alias");
+    let query = get_query("dolor");
+    assert!(query.definition.is_none());
+    assert!(query.references.is_empty());
+    assert!(query.to_string().contains("not defined"));
+}
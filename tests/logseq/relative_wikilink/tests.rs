@@ -0,0 +1,75 @@
+//! Wikilinks which look like relative paths are warnings with their own code
+use std::{path::PathBuf, sync::Arc};
+
+use itertools::Itertools;
+use mdlinker::{
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{relative_wikilink, Report},
+    OutputReport,
+};
+use miette::Severity;
+use tracing::info;
+
+fn run(exclude: Vec<String>) -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- Lorem\n")
+        .with_file(
+            "pages/notes.md",
+            "- [[./lorem]] [[../ipsum]] [[lorem]] [[dolor]]\n",
+        );
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .exclude(exclude)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+#[test]
+fn relative_wikilinks_are_warnings() {
+    info!("relative_wikilinks_are_warnings");
+    let report = run(vec![]);
+    let aliases = report
+        .relative_wikilinks()
+        .iter()
+        .map(|relative_wikilink| relative_wikilink.alias().to_string())
+        .sorted()
+        .collect_vec();
+    assert_eq!(aliases, vec!["../ipsum", "./lorem"]);
+    for relative_wikilink in report
+        .reports
+        .iter()
+        .filter(|report| matches!(report, Report::ThirdPass(_)))
+        .filter(|report| report.id().0.starts_with(relative_wikilink::CODE))
+    {
+        assert_eq!(relative_wikilink.severity(), Severity::Warning);
+    }
+    // They are not also broken wikilinks
+    let broken = report
+        .broken_wikilinks()
+        .iter()
+        .map(|broken_wikilink| broken_wikilink.alias().to_string())
+        .collect_vec();
+    assert_eq!(broken, vec!["dolor"]);
+}
+
+#[test]
+fn exclude_relative_wikilinks() {
+    info!("exclude_relative_wikilinks");
+    let report = run(vec![format!("{}::*", relative_wikilink::CODE)]);
+    assert!(report.relative_wikilinks().is_empty());
+    assert_eq!(report.broken_wikilinks().len(), 1);
+}
+
+#[test]
+fn looks_relative() {
+    info!("looks_relative");
+    let alias = mdlinker::file::content::wikilink::Alias::new;
+    assert!(relative_wikilink::looks_relative(&alias("./lorem")));
+    assert!(relative_wikilink::looks_relative(&alias("lorem/../ipsum")));
+    assert!(!relative_wikilink::looks_relative(&alias("lorem/ipsum")));
+    assert!(!relative_wikilink::looks_relative(&alias("lorem.ipsum")));
+}
@@ -0,0 +1,97 @@
+//! A Hugo or Zola content directory, linked with `relref` shortcodes and `@/` links
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::config;
+use mdlinker::rules::{broken_relref, filter_code};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use crate::common::get_report;
+
+lazy_static! {
+    static ref CONTENT: PathBuf = PathBuf::from("./tests/logseq/relref/assets/content/");
+}
+
+fn relref_config(relref: bool) -> config::Config {
+    config::Config::builder()
+        .pages_directory(CONTENT.clone())
+        .relref(relref)
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .build()
+}
+
+#[test]
+fn number_of_broken_relrefs() {
+    info!("number_of_broken_relrefs");
+    let report = get_report(&[], Some(relref_config(true)));
+    for broken_relref in &report.broken_relrefs() {
+        debug!("{broken_relref:?}");
+    }
+    assert_eq!(report.broken_relrefs().len(), 2);
+}
+
+#[test]
+fn missing_relref_is_broken() {
+    info!("missing_relref_is_broken");
+    let report = get_report(&[], Some(relref_config(true)));
+    assert!(!filter_code(
+        report.broken_relrefs(),
+        &format!("{}::first::missing.md", broken_relref::CODE).into()
+    )
+    .is_empty());
+}
+
+#[test]
+fn missing_zola_link_is_broken() {
+    info!("missing_zola_link_is_broken");
+    let report = get_report(&[], Some(relref_config(true)));
+    assert!(!filter_code(
+        report.broken_relrefs(),
+        &format!("{}::second::blog/gone.md", broken_relref::CODE).into()
+    )
+    .is_empty());
+}
+
+/// Relative to the page, relative to the root, sections, bundles and anchors all resolve
+#[test]
+fn existing_relrefs_resolve() {
+    info!("existing_relrefs_resolve");
+    let report = get_report(&[], Some(relref_config(true)));
+    for target in [
+        "_index::blog/first.md",
+        "_index::/docs",
+        "_index::../_index.md",
+        "first::second.md",
+        "first::bundle",
+        "second::blog/first.md",
+    ] {
+        assert!(
+            filter_code(
+                report.broken_relrefs(),
+                &format!("{}::{target}", broken_relref::CODE).into()
+            )
+            .is_empty(),
+            "{target}"
+        );
+    }
+}
+
+#[test]
+fn relrefs_in_code_are_ignored() {
+    info!("relrefs_in_code_are_ignored");
+    let report = get_report(&[], Some(relref_config(true)));
+    assert!(filter_code(
+        report.broken_relrefs(),
+        &format!("{}::first::in-code", broken_relref::CODE).into()
+    )
+    .is_empty());
+}
+
+#[test]
+fn relref_is_opt_in() {
+    info!("relref_is_opt_in");
+    let report = get_report(&[], Some(relref_config(false)));
+    assert!(report.broken_relrefs().is_empty());
+}
@@ -0,0 +1,83 @@
+//! Going through reports one at a time, see [`mdlinker::review::Review`]
+use std::fs;
+
+use mdlinker::{
+    config::file::Config as FileConfig,
+    review::{Review, Row},
+    testutil::{Page, TempVault, Vault, CONFIG_FILE},
+};
+use tracing::info;
+
+/// A page, and a journal with a broken wikilink and a mention of the page
+fn vault() -> TempVault {
+    Vault::new()
+        .with_page(&Page::new("lorem").with_line("- A page"))
+        .with_journal(
+            &Page::new("2024_11_01")
+                .with_link("ipsum")
+                .with_line("- about lorem"),
+        )
+        .write()
+        .expect("The temporary directory is writable")
+}
+
+/// Reports are grouped by rule, then file
+#[test]
+fn rows_are_grouped() {
+    info!("rows_are_grouped");
+    let vault = vault();
+    let review = Review::new(vault.config()).expect("Linting should not fail");
+    let journal = vault.root().join("journals/2024_11_01.md");
+    assert_eq!(
+        review.rows(),
+        vec![
+            Row::Rule("content::alias::unlinked".to_owned()),
+            Row::File(journal.clone()),
+            Row::Report(0),
+            Row::Rule("content::wikilink::broken".to_owned()),
+            Row::File(journal),
+            Row::Report(1),
+        ]
+    );
+}
+
+/// Ignoring a report excludes it in the config file, and moves on to the next one
+#[test]
+fn ignore_excludes() {
+    info!("ignore_excludes");
+    let vault = vault();
+    let mut review = Review::new(vault.config()).expect("Linting should not fail");
+    let id = review.selected().expect("There are reports").id();
+    review.ignore().expect("The config is writable");
+    assert_eq!(review.reports().len(), 1);
+    assert_ne!(
+        review.selected().map(mdlinker::rules::Report::id),
+        Some(id.clone())
+    );
+    let saved: FileConfig = toml::from_str(&vault.read(CONFIG_FILE).expect("The config was saved"))
+        .expect("The config is valid");
+    assert_eq!(saved.exclude, vec![id.0.into()]);
+}
+
+/// Excludes are added to the config file without losing its comments and formatting
+#[test]
+fn ignore_keeps_the_config_file() {
+    info!("ignore_keeps_the_config_file");
+    let vault = vault();
+    let toml = "# My notes\npages_directory = \"pages\" # where pages go\nother_directories = []\n\nexclude = [\n    \"name::similar::*\", # noisy\n]\n";
+    fs::write(vault.root().join(CONFIG_FILE), toml).expect("Temp dir is writable");
+    let mut review = Review::new(vault.config()).expect("Linting should not fail");
+    let id = review.selected().expect("There are reports").id();
+    review.ignore().expect("The config is writable");
+    let saved = vault.read(CONFIG_FILE).expect("The config was saved");
+    assert!(
+        saved.starts_with("# My notes\npages_directory = \"pages\" # where pages go\n"),
+        "{saved}"
+    );
+    assert!(saved.contains("\"name::similar::*\", # noisy"), "{saved}");
+    let saved: FileConfig = toml::from_str(&saved).expect("The config is valid");
+    assert_eq!(
+        saved.exclude,
+        vec!["name::similar::*".to_owned().into(), id.0.into()]
+    );
+}
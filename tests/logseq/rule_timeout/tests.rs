@@ -0,0 +1,68 @@
+//! Rules stopped once they run out of time, see [`mdlinker::config::Config::rule_timeout`]
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use mdlinker::{
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{broken_wikilink, ReportTrait},
+    OutputReport,
+};
+use tracing::info;
+
+fn lint(rule_timeout: Option<u64>) -> OutputReport {
+    let mut files = InMemory::new();
+    for i in 0..5 {
+        files = files
+            .with_file(format!("pages/lorem{i}.md"), format!("- [[missing{i}]]\n"))
+            .with_file(format!("pages/sit amet{i}.md"), "- ipsum\n");
+    }
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .maybe_rule_timeout(rule_timeout)
+        .build()
+        .expect("The config is valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+#[test]
+fn no_timeout() {
+    info!("no_timeout");
+    let report = lint(None);
+    assert!(report.timed_out().is_empty());
+    assert_eq!(report.broken_wikilinks().len(), 5);
+}
+
+#[test]
+fn long_timeout() {
+    info!("long_timeout");
+    let report = lint(Some(3600));
+    assert!(report.timed_out().is_empty());
+    assert_eq!(report.broken_wikilinks().len(), 5);
+}
+
+/// Without any time, each rule gets through its first file, or its first filename for
+/// SimilarFilename, and the others still run
+#[test]
+fn zero_timeout() {
+    info!("zero_timeout");
+    let report = lint(Some(0));
+    let rules: Vec<&str> = report
+        .timed_out()
+        .iter()
+        .map(|timed_out| timed_out.rule.as_str())
+        .collect();
+    assert!(rules.contains(&"SimilarFilename"), "{rules:?}");
+    assert!(rules.contains(&"BrokenWikilink"), "{rules:?}");
+    assert!(report
+        .timed_out()
+        .iter()
+        .all(|timed_out| timed_out.timeout == Duration::ZERO));
+    let broken_wikilinks = report.broken_wikilinks();
+    assert_eq!(broken_wikilinks.len(), 1);
+    assert!(broken_wikilinks[0]
+        .id()
+        .0
+        .starts_with(broken_wikilink::CODE));
+}
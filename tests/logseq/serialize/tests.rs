@@ -0,0 +1,56 @@
+use crate::common::get_report;
+use lazy_static::lazy_static;
+use mdlinker::OutputReport;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PATHS: Vec<String> = vec![
+        "./tests/logseq/serialize/assets/pages/".to_string(),
+        "./tests/logseq/serialize/assets/journals/".to_string()
+    ];
+}
+
+fn ids(report: &OutputReport) -> Vec<String> {
+    report.reports.iter().map(|x| x.id().0).collect()
+}
+
+/// Reports come back the same after a round trip through json
+#[test]
+fn json_round_trip() {
+    info!("json_round_trip");
+    let report = get_report(PATHS.as_slice(), None);
+    debug!("{:?}", ids(&report));
+    assert!(!report.broken_wikilinks().is_empty());
+    assert!(!report.unlinked_texts().is_empty());
+    assert!(!report.duplicate_aliases().is_empty());
+
+    let json = serde_json::to_string(&report).expect("Reports serialize");
+    let deserialized: OutputReport = serde_json::from_str(&json).expect("Reports deserialize");
+    assert_eq!(ids(&deserialized), ids(&report));
+    for (before, after) in report.reports.iter().zip(&deserialized.reports) {
+        assert_eq!(after.path(), before.path());
+        assert_eq!(after.span(), before.span());
+        assert_eq!(after.line_column(), before.line_column());
+    }
+    assert_eq!(
+        serde_json::to_string(&deserialized).expect("Reports serialize"),
+        json
+    );
+}
+
+/// The content of the files can be left out
+#[test]
+fn json_without_sources() {
+    info!("json_without_sources");
+    let report = get_report(PATHS.as_slice(), None);
+    let json = serde_json::to_string(&report.clone().without_sources()).expect("Reports serialize");
+    debug!("{json}");
+    assert!(!json.contains("is not a page"));
+    assert!(json.contains("2024_11_01.md"));
+    let deserialized: OutputReport = serde_json::from_str(&json).expect("Reports deserialize");
+    assert_eq!(ids(&deserialized), ids(&report));
+    for (before, after) in report.reports.iter().zip(&deserialized.reports) {
+        assert_eq!(after.path(), before.path());
+        assert_eq!(after.span(), before.span());
+    }
+}
@@ -0,0 +1,74 @@
+//! Changing the severity of rules by path, see [`mdlinker::config::SeverityOverride`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::{Level, SeverityOverride},
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{unlinked_text, Report},
+};
+use miette::Severity;
+use tracing::info;
+
+#[test]
+fn escalate_in_path() {
+    info!("escalate_in_path");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- A page\n")
+        .with_file("pages/notes/ipsum.md", "- About lorem\n")
+        .with_file("pages/published/dolor.md", "- Also about lorem\n");
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .severity(vec![
+            SeverityOverride {
+                rule: Some(unlinked_text::CODE.to_owned()),
+                path: None,
+                level: Level::Warning,
+            },
+            SeverityOverride {
+                rule: Some("content::alias::*".to_owned()),
+                path: Some("pages/published/**".to_owned()),
+                level: Level::Error,
+            },
+        ])
+        .build()
+        .expect("The defaults are valid");
+    let report = linter.run().expect("There should have been no error.");
+
+    let mut severities: Vec<(PathBuf, Severity)> = report
+        .reports
+        .iter()
+        .filter(|report| matches!(report, Report::ThirdPass(_)))
+        .map(|report| (report.path(), linter.config().severity(report)))
+        .collect();
+    severities.sort_by_key(|(path, _)| path.clone());
+    assert_eq!(
+        severities,
+        vec![
+            (PathBuf::from("pages/notes/ipsum.md"), Severity::Warning),
+            (PathBuf::from("pages/published/dolor.md"), Severity::Error),
+        ]
+    );
+}
+
+#[test]
+fn rule_severity_without_overrides() {
+    info!("rule_severity_without_overrides");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- A page\n")
+        .with_file("pages/ipsum.md", "- About lorem\n");
+    let linter = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid");
+    let report = linter.run().expect("There should have been no error.");
+
+    let unlinked_text = report
+        .reports
+        .iter()
+        .find(|report| matches!(report, Report::ThirdPass(_)))
+        .expect("lorem is not linked");
+    assert_eq!(linter.config().severity(unlinked_text), Severity::Error);
+}
@@ -1,12 +1,18 @@
 use crate::common::get_report;
 use config::cli::Config as CliConfig;
 use config::file::Config as FileConfig;
+use itertools::Itertools;
 use lazy_static::lazy_static;
-use log::info;
-use mdlinker::rules::similar_filename::SimilarFilename;
+use mdlinker::config::SimilarityAlgorithm;
+use mdlinker::ngrams::Ngram;
+use mdlinker::rules::{
+    similar_filename::{similarity, SimilarFilename},
+    ReportTrait,
+};
 use mdlinker::{config, lib};
 use regex::Regex;
 use std::{path::PathBuf, str::FromStr};
+use tracing::info;
 
 lazy_static! {
     static ref PATHS: Vec<String> =
@@ -34,7 +40,12 @@ fn groups_first_element_same() {
 
     let report = get_report(PATHS.as_slice(), Some(config));
 
-    assert_eq!(report.reports.len(), 2, "{:?}", report.reports);
+    // `fooo` is similar to the `foo` of both `foo` and `foo/bar`, and `barr` to `bar`
+    assert_eq!(report.reports.len(), 3, "{:?}", report.reports);
+    assert!(report
+        .similar_filenames()
+        .iter()
+        .all(|similar| similar.id().0 != "name::similar::foo::foo___bar"));
 }
 
 /// [`foo.md`](./assets/logseq/pages/foo.md) and [`fooo.md`](./assets/logseq/pages/fooo.md) should conflict because
@@ -120,3 +131,259 @@ fn test_logseq_same_group() {
         );
     }
 }
+
+#[test]
+fn test_getters() {
+    let spacing = Regex::new("-|_|\\s").expect("Constant");
+    let foo = PathBuf::from("./pages/foo.md");
+    let fooo = PathBuf::from("./pages/fooo.md");
+    let report = SimilarFilename::new(
+        &foo,
+        &Ngram::new(&["foo"]),
+        &fooo,
+        &Ngram::new(&["fooo"]),
+        &spacing,
+        42,
+    )
+    .expect("The ngrams are in the filenames");
+    assert_eq!(report.path(), foo);
+    assert_eq!(report.file1_path(), foo);
+    assert_eq!(report.file2_path(), fooo);
+    assert_eq!(report.file1_ngram().to_string(), "foo");
+    assert_eq!(report.file2_ngram().to_string(), "fooo");
+    assert_eq!(report.score(), 42);
+    assert_eq!(report.span(), None);
+    assert_eq!(report.line_column(), None);
+}
+
+/// The files are put in order, so a pair found either way around is the same
+#[test]
+fn canonical_order() {
+    let spacing = Regex::new("-|_|\\s").expect("Constant");
+    let foo = PathBuf::from("./pages/foo.md");
+    let fooo = PathBuf::from("./pages/fooo.md");
+    let report = SimilarFilename::new(
+        &fooo,
+        &Ngram::new(&["fooo"]),
+        &foo,
+        &Ngram::new(&["foo"]),
+        &spacing,
+        42,
+    )
+    .expect("The ngrams are in the filenames");
+    assert_eq!(report.file1_path(), foo);
+    assert_eq!(report.file2_path(), fooo);
+    assert_eq!(report.file1_ngram().to_string(), "foo");
+    assert_eq!(report.file2_ngram().to_string(), "fooo");
+}
+
+/// Every pair of similar ngrams in the same two files is one report, whichever file they were found in first
+#[test]
+fn merged_pairs() {
+    info!("merged_pairs");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/foo bar.md", "")
+        .with_file("pages/fooo barr.md", "");
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .filename_match_threshold(1)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let similar_filenames = report.similar_filenames();
+    assert_eq!(similar_filenames.len(), 1, "{similar_filenames:#?}");
+    let similar_filename = &similar_filenames[0];
+    assert_eq!(
+        similar_filename.file1_path(),
+        PathBuf::from("pages/foo bar.md")
+    );
+    assert_eq!(
+        similar_filename.file2_path(),
+        PathBuf::from("pages/fooo barr.md")
+    );
+    assert_eq!(similar_filename.other_ngrams().len(), 2);
+    let labels = miette::Diagnostic::labels(similar_filename)
+        .expect("There are labels")
+        .count();
+    assert_eq!(labels, 6);
+}
+
+/// Files in different directories are only compared without
+/// [`config::file::Config::similar_filename_same_directory_only`]
+#[test]
+fn same_directory_only() {
+    info!("same_directory_only");
+    let run = |same_directory_only: bool| {
+        let files = mdlinker::file::provider::InMemory::new()
+            .with_file("pages/apple.md", "")
+            .with_file("pages/applee.md", "")
+            .with_file("journals/appple.md", "");
+        mdlinker::linter::Linter::builder()
+            .pages_directory("pages".into())
+            .other_directories(vec!["journals".into()])
+            .files(std::sync::Arc::new(files))
+            .filename_match_threshold(1)
+            .similar_filename_same_directory_only(same_directory_only)
+            .build()
+            .expect("The defaults are valid")
+            .run()
+            .expect("There should have been no error.")
+            .similar_filenames()
+    };
+    let similar_filenames = run(false);
+    assert!(
+        similar_filenames
+            .iter()
+            .any(|similar| similar.file1_path().starts_with("journals")
+                != similar.file2_path().starts_with("journals")),
+        "{similar_filenames:#?}"
+    );
+    let similar_filenames = run(true);
+    assert!(!similar_filenames.is_empty());
+    for similar in &similar_filenames {
+        assert_eq!(
+            similar.file1_path().parent(),
+            similar.file2_path().parent(),
+            "{similar:#?}"
+        );
+    }
+}
+
+/// Dates are not similar to each other, as numbers are left out of ngrams by default
+#[test]
+fn dates_are_not_similar() {
+    info!("dates_are_not_similar");
+    let run = |ngram_ignore_pattern: Option<String>| {
+        let files = mdlinker::file::provider::InMemory::new()
+            .with_file("journals/2024_1_1.md", "")
+            .with_file("journals/2024_1_11.md", "");
+        mdlinker::linter::Linter::builder()
+            .pages_directory("pages".into())
+            .other_directories(vec!["journals".into()])
+            .files(std::sync::Arc::new(files))
+            .filename_match_threshold(1)
+            .maybe_ngram_ignore_pattern(ngram_ignore_pattern)
+            .build()
+            .expect("The defaults are valid")
+            .run()
+            .expect("There should have been no error.")
+            .similar_filenames()
+    };
+    assert!(run(None).is_empty());
+    // Without ignoring anything, the days are similar
+    assert!(!run(Some("^$".to_owned())).is_empty());
+}
+
+/// Every algorithm but skim scores from 0 to 100
+#[test]
+fn similarity_algorithms() {
+    info!("similarity_algorithms");
+    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
+    for algorithm in [
+        SimilarityAlgorithm::Levenshtein,
+        SimilarityAlgorithm::JaroWinkler,
+        SimilarityAlgorithm::TokenSet,
+    ] {
+        assert_eq!(similarity(algorithm, &matcher, "foo", "foo"), Some(100));
+        assert_eq!(similarity(algorithm, &matcher, "abc", "xyz"), Some(0));
+        let score = similarity(algorithm, &matcher, "foo", "fooo").unwrap();
+        assert!(0 < score && score < 100, "{algorithm:?} scored {score}");
+    }
+    // The order of the words doesn't matter
+    assert_eq!(
+        similarity(
+            SimilarityAlgorithm::TokenSet,
+            &matcher,
+            "foo bar",
+            "bar foo"
+        ),
+        Some(100)
+    );
+}
+
+/// Scores are compared to the threshold with the configured algorithm
+#[test]
+fn similarity_algorithm_threshold() {
+    info!("similarity_algorithm_threshold");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/apple.md", "")
+        .with_file("pages/applee.md", "");
+    let similar_filenames = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .filename_match_threshold(80)
+        .similarity_algorithm(SimilarityAlgorithm::Levenshtein)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+        .similar_filenames();
+    let similar_filename = similar_filenames.iter().exactly_one().unwrap();
+    // 1 edit in 6 characters
+    assert_eq!(similar_filename.score(), 83);
+}
+
+/// Camel cased filenames are split into words, and the words are found in the filenames
+#[test]
+fn split_camel_case() {
+    info!("split_camel_case");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/MyProjectNotes.md", "")
+        .with_file("pages/project note.md", "");
+    let similar_filenames = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .filename_match_threshold(1)
+        .split_camel_case(true)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+        .similar_filenames();
+    let similar_filename = similar_filenames.iter().exactly_one().unwrap();
+    let ngrams = [
+        similar_filename.file1_ngram().to_string(),
+        similar_filename.file2_ngram().to_string(),
+    ];
+    assert!(
+        ngrams.contains(&"project notes".to_string()),
+        "{similar_filename:#?}"
+    );
+    let labels = miette::Diagnostic::labels(similar_filename)
+        .expect("There are labels")
+        .collect::<Vec<_>>();
+    assert!(labels.len() >= 2, "{labels:?}");
+}
+
+/// When files share an ngram, each of them is compared to the other files
+#[test]
+fn shared_ngram() {
+    info!("shared_ngram");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/lorem.md", "")
+        .with_file("pages/ipsum lorem.md", "")
+        .with_file("pages/loremm.md", "");
+    let similar_filenames = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .filename_match_threshold(1)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+        .similar_filenames();
+    let ids: Vec<String> = similar_filenames
+        .iter()
+        .map(|similar| similar.id().0)
+        .collect();
+    assert!(
+        ids.contains(&"name::similar::lorem::loremm".to_string()),
+        "{ids:?}"
+    );
+    assert!(
+        ids.contains(&"name::similar::ipsum lorem::loremm".to_string()),
+        "{ids:?}"
+    );
+}
@@ -0,0 +1,45 @@
+//! Reports kept without the content of their file past a limit, see
+//! [`mdlinker::config::Config::source_memory_limit`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, OutputReport};
+use tracing::info;
+
+fn lint(source_memory_limit: Option<usize>) -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[missing]] and ipsum\n")
+        .with_file("pages/ipsum.md", "- [[gone]]\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .maybe_source_memory_limit(source_memory_limit)
+        .build()
+        .expect("The config is valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+#[test]
+fn under_the_limit() {
+    info!("under_the_limit");
+    let report = lint(None);
+    assert_eq!(report.elided_sources(), 0);
+    assert!(report.reports.iter().all(|report| report.source_len() > 0));
+}
+
+/// Every report is still there, with its file, span, and line and column
+#[test]
+fn over_the_limit() {
+    info!("over_the_limit");
+    let kept = lint(None);
+    let report = lint(Some(0));
+    assert_eq!(report.reports.len(), kept.reports.len());
+    assert_eq!(report.broken_wikilinks().len(), 2);
+    assert_eq!(report.elided_sources(), report.reports.len());
+    for (report, kept) in report.reports.iter().zip(&kept.reports) {
+        assert_eq!(report.source_len(), 0);
+        assert_eq!(report.path(), kept.path());
+        assert_eq!(report.span(), kept.span());
+        assert_eq!(report.line_column(), kept.line_column());
+    }
+}
@@ -0,0 +1,146 @@
+//! The spans of each rule in a file whose front matter is several lines long, see
+//! [`mdlinker::file::content::source_map::SourceMap`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::LinkStyle,
+    file::provider::InMemory,
+    linter::Linter,
+    rules::{
+        alias_casing, broken_reference, broken_wikilink, code_wikilink, duplicate_property,
+        front_matter_key, ignored_wikilink, relative_wikilink, unknown_tag, unlinked_text,
+        wrong_link_style,
+    },
+    OutputReport,
+};
+use tracing::info;
+
+/// A page with a folded scalar and a list in its front matter, and something for each rule in
+/// its body
+const IPSUM: &str = "---
+title: ipsum
+description: >
+  A long description
+  over several lines
+aliases: [sit, amet]
+tags:
+  - one
+  - two
+tag: three
+title: again
+---
+- lorem and [[missing]] and #unknowntag
+- `[[code]]` and [see](lorem.md)
+- [[Lorem]] [[Lorem]] [[lorem]] [[LOREM]]
+- ((6720a5b1-0000-0000-0000-000000000000))
+- [[./lorem]] [[old]]
+";
+
+fn lint() -> OutputReport {
+    let files = InMemory::new()
+        .with_file(
+            "pages/lorem.md",
+            "---\ntags: [one]\n---\n- A page about [[Lorem]]\n",
+        )
+        .with_file("pages/archive/old.md", "- An old page\n")
+        .with_file("pages/sub/ipsum.md", IPSUM);
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .link_style(LinkStyle::Wikilink)
+        .known_tags(vec!["known".to_owned()])
+        .code_wikilink(true)
+        .ignore_paths(vec!["pages/archive/**".to_owned()])
+        .build()
+        .expect("The config is valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// The text under the span of the first report of the rule `code` in `ipsum.md`
+fn spanned(code: &str) -> &'static str {
+    let report = lint();
+    let report = report
+        .reports
+        .iter()
+        .find(|report| {
+            report.id().0.starts_with(&format!("{code}::"))
+                && report.path() == PathBuf::from("pages/sub/ipsum.md")
+        })
+        .unwrap_or_else(|| panic!("{code} should have been reported"));
+    let span = report.span().expect("The report has a span");
+    &IPSUM[span.offset()..span.offset() + span.len()]
+}
+
+#[test]
+fn broken_wikilink() {
+    info!("broken_wikilink");
+    assert_eq!(spanned(broken_wikilink::CODE), "[[missing]]");
+}
+
+#[test]
+fn unlinked_text() {
+    info!("unlinked_text");
+    assert_eq!(spanned(unlinked_text::CODE), "lorem");
+}
+
+#[test]
+fn unknown_tag() {
+    info!("unknown_tag");
+    assert_eq!(spanned(unknown_tag::CODE), "unknowntag");
+}
+
+#[test]
+fn code_wikilink() {
+    info!("code_wikilink");
+    assert_eq!(spanned(code_wikilink::CODE), "[[code]]");
+}
+
+#[test]
+fn wrong_link_style() {
+    info!("wrong_link_style");
+    assert_eq!(spanned(wrong_link_style::CODE), "[see](lorem.md)");
+}
+
+/// The first link whose casing differs from the most common one
+#[test]
+fn alias_casing() {
+    info!("alias_casing");
+    let span = spanned(alias_casing::CODE);
+    assert_eq!(span, "lorem");
+    assert!(IPSUM.contains(&format!("[[{span}]]")));
+}
+
+#[test]
+fn broken_reference() {
+    info!("broken_reference");
+    assert_eq!(
+        spanned(broken_reference::CODE),
+        "6720a5b1-0000-0000-0000-000000000000"
+    );
+}
+
+#[test]
+fn relative_wikilink() {
+    info!("relative_wikilink");
+    assert_eq!(spanned(relative_wikilink::CODE), "[[./lorem]]");
+}
+
+#[test]
+fn ignored_wikilink() {
+    info!("ignored_wikilink");
+    assert_eq!(spanned(ignored_wikilink::CODE), "[[old]]");
+}
+
+/// Spans in the front matter itself
+#[test]
+fn duplicate_property() {
+    info!("duplicate_property");
+    assert_eq!(spanned(duplicate_property::CODE), "title");
+}
+
+#[test]
+fn front_matter_key() {
+    info!("front_matter_key");
+    assert_eq!(spanned(front_matter_key::CODE), "tag");
+}
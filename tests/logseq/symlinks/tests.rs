@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use lazy_static::lazy_static;
+use mdlinker::file::get_files;
+
+use crate::common::get_report;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PATHS: Vec<String> = vec![
+        "./tests/logseq/symlinks/assets/pages/".to_string(),
+        "./tests/logseq/symlinks/assets/linked_pages/".to_string(),
+        "./tests/logseq/symlinks/assets/journals/".to_string()
+    ];
+}
+
+/// `linked_pages` is a symlink to `pages`, and `journals/lorem.md` is a symlink to `pages/lorem.md`
+/// so each file should only be found once
+#[test]
+fn symlinked_files_are_deduplicated() {
+    info!("symlinked_files_are_deduplicated");
+    let dirs: Vec<PathBuf> = PATHS.iter().map(PathBuf::from).collect();
+    let files = get_files(&dirs);
+    debug!("{files:#?}");
+    assert_eq!(files.len(), 2, "{files:?}");
+}
+
+/// If the same file is parsed twice its aliases would collide with themselves
+#[test]
+fn symlinked_files_do_not_duplicate_aliases() {
+    info!("symlinked_files_do_not_duplicate_aliases");
+    let report = get_report(PATHS.as_slice(), None);
+    for duplicate_alias in &report.duplicate_aliases() {
+        debug!("{duplicate_alias:#?}");
+    }
+    assert!(report.duplicate_aliases().is_empty());
+}
+
+/// Overlapping directories should also be deduplicated
+#[test]
+fn overlapping_directories_are_deduplicated() {
+    info!("overlapping_directories_are_deduplicated");
+    let dirs = vec![
+        PathBuf::from("./tests/logseq/symlinks/assets/pages/"),
+        PathBuf::from("./tests/logseq/symlinks/assets/"),
+    ];
+    let files = get_files(&dirs);
+    debug!("{files:#?}");
+    assert_eq!(files.len(), 2, "{files:?}");
+}
@@ -0,0 +1,109 @@
+//! Unlinked text in tables, see [`mdlinker::config::MarkdownExtensions::table`]
+use std::{fs, path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::{MarkdownExtensions, UnlinkedTextOptions},
+    file::provider::InMemory,
+    linter::Linter,
+    rules::ReportTrait,
+    testutil::{Page, Vault},
+    OutputReport,
+};
+use tracing::info;
+
+const SOURCE: &str = "\
+| Name | Notes |
+|------|-------|
+| x \\| ipsum | dolor \\| sit \\| ipsum |
+";
+
+fn table() -> MarkdownExtensions {
+    MarkdownExtensions {
+        table: true,
+        ..MarkdownExtensions::default()
+    }
+}
+
+fn run(options: UnlinkedTextOptions) -> OutputReport {
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", SOURCE)
+        .with_file("pages/ipsum.md", "- Ipsum\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .markdown(table())
+        .unlinked_text_options(options)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// The spans of unlinked text in a cell are past the backslashes of its escaped pipes
+#[test]
+fn spans() {
+    info!("spans");
+    let report = run(UnlinkedTextOptions::default());
+    let spans: Vec<&str> = report
+        .unlinked_texts()
+        .iter()
+        .filter(|x| x.path().ends_with("lorem.md"))
+        .map(|x| &SOURCE[x.span.offset()..x.span.offset() + x.span.len()])
+        .collect();
+    assert_eq!(spans, vec!["ipsum", "ipsum"]);
+}
+
+/// Skipping tables skips every cell
+#[test]
+fn skip_tables() {
+    info!("skip_tables");
+    let report = run(UnlinkedTextOptions {
+        skip_tables: true,
+        ..UnlinkedTextOptions::default()
+    });
+    assert!(report
+        .unlinked_texts()
+        .iter()
+        .all(|x| !x.path().ends_with("lorem.md")));
+}
+
+/// Fixing wraps the alias in the cell, and leaves spans which are no longer the alias alone
+#[test]
+fn fix() {
+    info!("fix");
+    let vault = Vault::new()
+        .with_file("pages/lorem.md", SOURCE)
+        .with_page(&Page::new("ipsum").with_line("- Ipsum"))
+        .write()
+        .expect("The temporary directory is writable");
+    let lorem = vault.pages_directory().join("lorem.md");
+    let mut config = vault.config();
+    config.markdown = table();
+    let linter = Linter::from_config(config);
+    let report = linter.run().expect("There should have been no error.");
+    let unlinked_texts: Vec<_> = report
+        .unlinked_texts()
+        .into_iter()
+        .filter(|x| x.path().ends_with("lorem.md"))
+        .collect();
+    assert_eq!(unlinked_texts.len(), 2);
+
+    // The last first, so the offsets of the other stay the same
+    unlinked_texts[0]
+        .fix(linter.config())
+        .expect("The fix should work");
+    let fixed = fs::read_to_string(&lorem).expect("The file exists");
+    assert_eq!(
+        fixed,
+        SOURCE.replace("sit \\| ipsum |", "sit \\| [[ipsum]] |"),
+        "{fixed:?}"
+    );
+
+    // The file changed under the other, so it is left alone
+    fs::write(&lorem, SOURCE.replace("x \\| ipsum", "xy \\| ipsum")).expect("Temp dir is writable");
+    unlinked_texts[1]
+        .fix(linter.config())
+        .expect("The fix should work");
+    let fixed = fs::read_to_string(&lorem).expect("The file exists");
+    assert!(!fixed.contains("[["), "{fixed:?}");
+}
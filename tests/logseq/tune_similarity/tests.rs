@@ -0,0 +1,60 @@
+use config::cli::Config as CliConfig;
+use config::file::Config as FileConfig;
+use lazy_static::lazy_static;
+use mdlinker::{config, tune::ScoreDistribution, tune_similarity};
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+lazy_static! {
+    static ref PAGES: PathBuf = PathBuf::from("./tests/logseq/similar_filename/assets/pages/");
+}
+
+fn get_distribution(max_pairs: usize) -> ScoreDistribution {
+    let config = config::Config::builder()
+        .pages_directory(PAGES.clone())
+        .file_config(FileConfig::default())
+        .cli_config(CliConfig::default())
+        .similarity_algorithm(config::SimilarityAlgorithm::Levenshtein)
+        .filename_match_threshold(50)
+        .build();
+    let distribution =
+        tune_similarity(&config, max_pairs).expect("There should have been no error.");
+    debug!("{distribution}");
+    distribution
+}
+
+/// Every pair is in the histogram, and the quantiles are sorted
+#[test]
+fn histogram_and_quantiles() {
+    info!("histogram_and_quantiles");
+    let distribution = get_distribution(100_000);
+    assert!(distribution.nb_pairs > 0);
+    assert_eq!(distribution.nb_sampled, distribution.nb_pairs);
+    assert_eq!(distribution.nb_unmatched, 0);
+    let in_histogram: usize = distribution
+        .histogram
+        .iter()
+        .map(|(_, _, count)| count)
+        .sum();
+    assert_eq!(in_histogram, distribution.nb_pairs);
+    let scores: Vec<i64> = distribution
+        .quantiles
+        .iter()
+        .map(|(_, score)| *score)
+        .collect();
+    assert!(scores.is_sorted(), "{scores:?}");
+    assert!(!distribution.near_threshold.is_empty());
+    assert!(distribution
+        .near_threshold
+        .iter()
+        .all(|pair| pair.file != pair.other_file));
+}
+
+/// Only some pairs are scored on large vaults
+#[test]
+fn sampled() {
+    info!("sampled");
+    let distribution = get_distribution(2);
+    assert!(distribution.nb_pairs > 2);
+    assert_eq!(distribution.nb_sampled, 2);
+}
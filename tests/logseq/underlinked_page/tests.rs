@@ -0,0 +1,80 @@
+//! Pages which too few pages link to, see [`mdlinker::rules::underlinked_page::UnderlinkedPage`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::MinInboundLinks, file::provider::InMemory, linter::Linter, rules::ReportTrait,
+};
+use tracing::info;
+
+fn lint(min_inbound_links: Vec<MinInboundLinks>) -> mdlinker::OutputReport {
+    let files = InMemory::new()
+        .with_file(
+            "pages/published___lorem.md",
+            "- Lorem [[published/ipsum]]\n",
+        )
+        .with_file(
+            "pages/published___ipsum.md",
+            "- Ipsum [[published/ipsum]]\n",
+        )
+        .with_file(
+            "pages/dolor.md",
+            "- [[published/lorem]] [[published/lorem]]\n",
+        )
+        .with_file("pages/sit.md", "- [[published/lorem]]\n");
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .min_inbound_links(min_inbound_links)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// Links from the page itself, and repeated links, are not counted
+#[test]
+fn too_few_links() {
+    info!("too_few_links");
+    let underlinked_pages = lint(vec![MinInboundLinks {
+        glob: "pages/published___*".to_owned(),
+        min: 2,
+    }])
+    .underlinked_pages();
+    assert_eq!(underlinked_pages.len(), 1);
+    assert_eq!(
+        underlinked_pages[0].path(),
+        PathBuf::from("pages/published___ipsum.md")
+    );
+    assert_eq!(
+        underlinked_pages[0].linked_from(),
+        [PathBuf::from("pages/published___lorem.md")]
+    );
+}
+
+/// The first glob which matches is used
+#[test]
+fn first_match() {
+    info!("first_match");
+    let underlinked_pages = lint(vec![
+        MinInboundLinks {
+            glob: "pages/published___lorem.md".to_owned(),
+            min: 3,
+        },
+        MinInboundLinks {
+            glob: "pages/published___*".to_owned(),
+            min: 1,
+        },
+    ])
+    .underlinked_pages();
+    assert_eq!(underlinked_pages.len(), 1);
+    assert_eq!(
+        underlinked_pages[0].path(),
+        PathBuf::from("pages/published___lorem.md")
+    );
+}
+
+#[test]
+fn off_by_default() {
+    info!("off_by_default");
+    assert!(lint(vec![]).underlinked_pages().is_empty());
+}
@@ -0,0 +1,98 @@
+//! Tags which are not one of the known tags, see [`mdlinker::rules::unknown_tag::UnknownTag`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{file::provider::InMemory, linter::Linter, rules::ReportTrait};
+use tracing::info;
+
+#[test]
+fn known_tags() {
+    info!("known_tags");
+    let files = InMemory::new()
+        .with_file(
+            "pages/lorem.md",
+            "- Lorem #project and #projct\n- #[[idea]]\n",
+        )
+        .with_file("pages/ipsum.md", "- Ipsum #Idea #misc\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .known_tags(vec!["project".to_owned(), "idea".to_owned()])
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    let mut unknown_tags = report.unknown_tags();
+    unknown_tags.sort_by_key(|x| x.tag().to_string());
+    assert_eq!(unknown_tags.len(), 2);
+    assert_eq!(unknown_tags[0].tag().to_string(), "misc");
+    assert_eq!(unknown_tags[0].suggestion(), None);
+    assert_eq!(unknown_tags[0].path(), PathBuf::from("pages/ipsum.md"));
+    assert_eq!(unknown_tags[1].tag().to_string(), "projct");
+    assert_eq!(
+        unknown_tags[1].suggestion().map(ToString::to_string),
+        Some("project".to_owned())
+    );
+    assert_eq!(unknown_tags[1].line_column(), Some((1, 23)));
+}
+
+#[test]
+fn tags_page() {
+    info!("tags_page");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- Lorem #project and #idea\n")
+        .with_file("pages/tags.md", "- #project\n- [[idea]]\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(
+            files.clone().with_file("pages/ipsum.md", "- #other\n"),
+        ))
+        .tags_page("tags".to_owned())
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    let unknown_tags = report.unknown_tags();
+    assert_eq!(unknown_tags.len(), 1);
+    assert_eq!(unknown_tags[0].tag().to_string(), "other");
+}
+
+#[test]
+fn off_without_known_tags() {
+    info!("off_without_known_tags");
+    let files = InMemory::new().with_file("pages/lorem.md", "- Lorem #project\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    assert!(report.unknown_tags().is_empty());
+}
+
+/// An unknown tag without a page is not also reported as a broken wikilink,
+/// but a known tag without a page is
+#[test]
+fn not_also_broken() {
+    info!("not_also_broken");
+    let files = InMemory::new().with_file("pages/lorem.md", "- Lorem #project and #projct\n");
+    let report = Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .known_tags(vec!["project".to_owned()])
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+
+    assert_eq!(report.unknown_tags().len(), 1);
+    let broken: Vec<String> = report
+        .broken_wikilinks()
+        .iter()
+        .map(|x| x.alias().to_string())
+        .collect();
+    assert_eq!(broken, vec!["project".to_owned()]);
+}
@@ -3,9 +3,10 @@ use std::fs;
 use lazy_static::lazy_static;
 use mdlinker::rules::unlinked_text;
 
-use log::{debug, info};
-use mdlinker::rules::filter_code;
+use mdlinker::rules::{filter_code, ReportTrait};
+use mdlinker::testutil::{Page, Vault};
 use miette::SourceOffset;
+use tracing::{debug, info};
 
 use crate::common::get_report;
 
@@ -101,3 +102,277 @@ fn icazyvey_exists_and_is_not_wikilink_in_journal() {
     assert_eq!(err.span.offset(), offset.offset());
     assert_eq!(err.span.len(), 8);
 }
+
+/// The text matched as unlinked in `content`, when the only other page is `pages/foo.md`
+fn unlinked_in(content: &str) -> Vec<String> {
+    unlinked_texts_in(content, false)
+        .iter()
+        .map(|unlinked_text| {
+            let span = unlinked_text.span;
+            content[span.offset()..span.offset() + span.len()].to_string()
+        })
+        .collect()
+}
+
+/// The unlinked texts in `content`, when the only other page is `pages/foo.md`
+fn unlinked_texts_in(content: &str, inflections: bool) -> Vec<unlinked_text::UnlinkedText> {
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/foo.md", "- Foo\n")
+        .with_file("pages/notes.md", content);
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .unlinked_text_inflections(inflections)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    report
+        .unlinked_texts()
+        .into_iter()
+        .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+        .collect()
+}
+
+/// Punctuation after or around the alias ends the word
+#[test]
+fn unlinked_text_next_to_punctuation() {
+    info!("unlinked_text_next_to_punctuation");
+    assert_eq!(unlinked_in("- See foo, then\n"), vec!["foo"]);
+    assert_eq!(unlinked_in("- The end is foo.\n"), vec!["foo"]);
+    assert_eq!(unlinked_in("- Aside (foo) here\n"), vec!["foo"]);
+    assert_eq!(unlinked_in("- Foo at the start\n"), vec!["Foo"]);
+}
+
+/// Quotes around the alias end the word
+#[test]
+fn unlinked_text_in_quotes() {
+    info!("unlinked_text_in_quotes");
+    assert_eq!(unlinked_in("- Say \"foo\" twice\n"), vec!["foo"]);
+    assert_eq!(unlinked_in("- Say 'foo' twice\n"), vec!["foo"]);
+}
+
+/// Part of a hyphenated word, or of a longer word, is not the alias
+#[test]
+fn unlinked_text_in_hyphenated_word() {
+    info!("unlinked_text_in_hyphenated_word");
+    assert!(unlinked_in("- A foo-bar here\n").is_empty());
+    assert!(unlinked_in("- A bar-foo here\n").is_empty());
+    assert!(unlinked_in("- A foobar here\n").is_empty());
+    assert!(unlinked_in("- A #foo here\n").is_empty());
+    assert_eq!(unlinked_in("- A foo - bar here\n"), vec!["foo"]);
+}
+
+/// Plurals and possessives are only matched when inflections are turned on
+#[test]
+fn unlinked_text_inflections() {
+    info!("unlinked_text_inflections");
+    let content = "- Two foos\n- Some fooes\n- A foo's friend\n";
+    assert!(unlinked_texts_in(content, false).is_empty());
+    let unlinked_texts = unlinked_texts_in(content, true);
+    let matched = unlinked_texts
+        .iter()
+        .map(|unlinked_text| {
+            let span = unlinked_text.span;
+            (
+                &content[span.offset()..span.offset() + span.len()],
+                unlinked_text.alias().to_string(),
+                unlinked_text.inflection(),
+            )
+        })
+        .sorted()
+        .collect_vec();
+    assert_eq!(
+        matched,
+        vec![
+            ("foo's", "foo".to_string(), "'s"),
+            ("fooes", "foo".to_string(), "es"),
+            ("foos", "foo".to_string(), "s"),
+        ]
+    );
+    assert!(unlinked_texts_in("- Some foosball\n", true).is_empty());
+}
+
+/// Only the alias is wrapped when an inflection is fixed
+#[test]
+fn fix_unlinked_text_inflection() {
+    info!("fix_unlinked_text_inflection");
+    let vault = Vault::new()
+        .with_page(&Page::new("foo").with_line("- Foo"))
+        .with_page(&Page::new("notes").with_line("- Two foos here"))
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.unlinked_text_inflections = true;
+    let unlinked_text = mdlinker::linter::Linter::from_config(config.clone())
+        .run()
+        .expect("There should have been no error.")
+        .unlinked_texts()
+        .into_iter()
+        .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+        .exactly_one()
+        .unwrap();
+    assert_eq!(unlinked_text.fix(&config).unwrap(), Some(()));
+    assert_eq!(
+        vault.read("pages/notes.md").expect("The file was fixed"),
+        "- Two [[foo]]s here\n"
+    );
+}
+
+/// With grouping, every mention of an alias in a file is one report with a label on each
+#[test]
+fn group_unlinked_text() {
+    info!("group_unlinked_text");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/foo.md", "- Foo\n")
+        .with_file(
+            "pages/notes.md",
+            "- A foo here\n- And foo there\n- Foo again\n",
+        );
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .group_unlinked_text(true)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let unlinked_text = report
+        .unlinked_texts()
+        .into_iter()
+        .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+        .exactly_one()
+        .unwrap();
+    assert_eq!(
+        unlinked_text.id(),
+        format!("{}::notes::foo", unlinked_text::CODE).into()
+    );
+    assert_eq!(unlinked_text.line_column(), Some((1, 5)));
+    let offsets = unlinked_text
+        .spans()
+        .iter()
+        .map(miette::SourceSpan::offset)
+        .collect_vec();
+    assert_eq!(offsets, vec![4, 19, 31]);
+    assert_eq!(
+        miette::Diagnostic::labels(&unlinked_text).map(Iterator::count),
+        Some(3)
+    );
+}
+
+/// Fix every grouped mention, or only the first
+#[test]
+fn fix_grouped_unlinked_text() {
+    info!("fix_grouped_unlinked_text");
+    for (fix, expected) in [
+        (
+            mdlinker::config::UnlinkedTextFix::All,
+            "- A [[foo]] here\n- And [[foo]]s there\n",
+        ),
+        (
+            mdlinker::config::UnlinkedTextFix::First,
+            "- A [[foo]] here\n- And foos there\n",
+        ),
+    ] {
+        let vault = Vault::new()
+            .with_page(&Page::new("foo").with_line("- Foo"))
+            .with_page(
+                &Page::new("notes")
+                    .with_line("- A foo here")
+                    .with_line("- And foos there"),
+            )
+            .write()
+            .expect("The temporary directory is writable");
+        let mut config = vault.config();
+        config.unlinked_text_inflections = true;
+        config.group_unlinked_text = true;
+        config.unlinked_text_fix = fix;
+        let linter = mdlinker::linter::Linter::from_config(config);
+        let unlinked_text = linter
+            .run()
+            .expect("There should have been no error.")
+            .unlinked_texts()
+            .into_iter()
+            .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+            .exactly_one()
+            .unwrap();
+        assert_eq!(unlinked_text.fix(linter.config()).unwrap(), Some(()));
+        assert_eq!(
+            vault.read("pages/notes.md").expect("The file was fixed"),
+            expected
+        );
+    }
+}
+
+/// Only the first mention in each scope is reported, and none after a link in the scope
+#[test]
+fn link_once_per() {
+    use mdlinker::config::LinkOncePer;
+    info!("link_once_per");
+    let content =
+        "# One\n\nA foo.\n\nAnother foo\nand foo.\n\n# Two\n\nSee [[foo]].\n\nThen foo.\n";
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/foo.md", "- Foo\n")
+        .with_file("pages/notes.md", content);
+    for (link_once_per, expected) in [
+        (None, vec!["A foo.", "Another foo", "and foo.", "Then foo."]),
+        (Some(LinkOncePer::File), vec!["A foo."]),
+        (Some(LinkOncePer::Section), vec!["A foo."]),
+        (
+            Some(LinkOncePer::Paragraph),
+            vec!["A foo.", "Another foo", "Then foo."],
+        ),
+    ] {
+        let report = mdlinker::linter::Linter::builder()
+            .pages_directory("pages".into())
+            .files(std::sync::Arc::new(files.clone()))
+            .maybe_link_once_per(link_once_per)
+            .build()
+            .expect("The defaults are valid")
+            .run()
+            .expect("There should have been no error.");
+        let lines = report
+            .unlinked_texts()
+            .iter()
+            .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+            .map(|unlinked_text| {
+                let (line, _) = unlinked_text.line_column().expect("Has a span");
+                content.lines().nth(line - 1).expect("The line exists")
+            })
+            .sorted_by_key(|line| content.find(line))
+            .collect_vec();
+        assert_eq!(lines, expected, "{link_once_per:?}");
+    }
+}
+
+/// The aliases of a page with `no-autolink: true` in its front matter are never suggested
+#[test]
+fn no_autolink() {
+    info!("no_autolink");
+    let files = mdlinker::file::provider::InMemory::new()
+        .with_file("pages/foo.md", "- Foo\n")
+        .with_file(
+            "pages/the.md",
+            "---\nalias: a\nno-autolink: true\n---\n\n- The\n",
+        )
+        .with_file(
+            "pages/notes.md",
+            "- The foo\n- A foo\n- See [[the]] and [[a]]\n",
+        );
+    let report = mdlinker::linter::Linter::builder()
+        .pages_directory("pages".into())
+        .files(std::sync::Arc::new(files))
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.");
+    let aliases = report
+        .unlinked_texts()
+        .iter()
+        .filter(|unlinked_text| unlinked_text.path().ends_with("notes.md"))
+        .map(|unlinked_text| unlinked_text.alias().to_string())
+        .collect_vec();
+    assert_eq!(aliases, vec!["foo", "foo"]);
+    // The page can still be linked to
+    assert!(report.broken_wikilinks().is_empty());
+}
@@ -0,0 +1,87 @@
+//! Vaults described in the test, with [`mdlinker::testutil::Vault`]
+use std::path::PathBuf;
+
+use mdlinker::{
+    rules::ReportTrait,
+    testutil::{Page, Vault, PAGES_DIRECTORY},
+};
+use tracing::info;
+
+#[test]
+fn page_content() {
+    info!("page_content");
+    let page = Page::new("lorem/ipsum")
+        .with_alias("dolor")
+        .with_alias("sit")
+        .with_property("title", "Ipsum")
+        .with_link("amet")
+        .with_line("- text");
+    assert_eq!(page.path(), PathBuf::from("lorem/ipsum.md"));
+    assert_eq!(
+        page.content(),
+        "---\naliases: [dolor, sit]\ntitle: Ipsum\n---\n- [[amet]]\n- text\n"
+    );
+    assert_eq!(
+        Page::new("lorem").with_link("ipsum").content(),
+        "- [[ipsum]]\n"
+    );
+}
+
+#[test]
+fn lints() {
+    info!("lints");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_alias("ipsum").with_link("missing"))
+        .with_page(&Page::new("dolor").with_alias("ipsum").with_link("lorem"))
+        .write()
+        .expect("The temporary directory is writable");
+    let report = vault.run().expect("There should have been no error.");
+
+    let broken_wikilinks = report.broken_wikilinks();
+    assert_eq!(broken_wikilinks.len(), 1);
+    assert_eq!(broken_wikilinks[0].alias().to_string(), "missing");
+    assert_eq!(
+        broken_wikilinks[0].path(),
+        vault.pages_directory().join("lorem.md")
+    );
+    let duplicate_aliases = report.duplicate_aliases();
+    assert_eq!(duplicate_aliases.len(), 1);
+    assert_eq!(duplicate_aliases[0].alias().to_string(), "ipsum");
+}
+
+/// Journals are linted, other files are only written
+#[test]
+fn journals_and_files() {
+    info!("journals_and_files");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem"))
+        .with_journal(&Page::new("2024_01_01").with_link("lorem").with_link("gone"))
+        .with_file("assets/notes.md", "- [[nowhere]]\n")
+        .write()
+        .expect("The temporary directory is writable");
+    assert_eq!(
+        vault.read("assets/notes.md").expect("It was written"),
+        "- [[nowhere]]\n"
+    );
+    let report = vault.run().expect("There should have been no error.");
+    let broken: Vec<String> = report
+        .broken_wikilinks()
+        .iter()
+        .map(|broken| broken.alias().to_string())
+        .collect();
+    assert_eq!(broken, vec!["gone"]);
+}
+
+/// Each vault has its own directory, which is removed with it
+#[test]
+fn removed() {
+    info!("removed");
+    let first = Vault::new().write().expect("Writable");
+    let second = Vault::new().write().expect("Writable");
+    assert_ne!(first.root(), second.root());
+    let root = first.root().to_path_buf();
+    assert!(root.join(PAGES_DIRECTORY).is_dir());
+    drop(first);
+    assert!(!root.exists());
+    assert!(second.root().exists());
+}
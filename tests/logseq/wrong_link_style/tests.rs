@@ -0,0 +1,123 @@
+//! Links to pages written the other way, see [`mdlinker::rules::wrong_link_style::WrongLinkStyle`]
+use std::{path::PathBuf, sync::Arc};
+
+use mdlinker::{
+    config::LinkStyle,
+    file::provider::InMemory,
+    linter::Linter,
+    rules::ReportTrait,
+    testutil::{Page, Vault},
+};
+use tracing::info;
+
+fn lint(files: InMemory, link_style: Option<LinkStyle>) -> mdlinker::OutputReport {
+    Linter::builder()
+        .pages_directory(PathBuf::from("pages"))
+        .files(Arc::new(files))
+        .maybe_link_style(link_style)
+        .build()
+        .expect("The defaults are valid")
+        .run()
+        .expect("There should have been no error.")
+}
+
+/// Links to websites are not links to pages
+#[test]
+fn markdown_links() {
+    info!("markdown_links");
+    let files = InMemory::new()
+        .with_file("pages/lorem ipsum.md", "- lorem\n")
+        .with_file(
+            "pages/dolor.md",
+            "- [Lorem Ipsum](lorem%20ipsum.md) [[dolor]]\n- [see](lorem%20ipsum.md#a) [web](https://example.com/page.md)\n",
+        );
+    let mut wrong_link_styles = lint(files, Some(LinkStyle::Wikilink)).wrong_link_styles();
+    wrong_link_styles.sort_by_key(ReportTrait::line_column);
+    let links: Vec<_> = wrong_link_styles
+        .iter()
+        .map(|report| (report.written(), report.replacement()))
+        .collect();
+    assert_eq!(
+        links,
+        [
+            ("[Lorem Ipsum](lorem%20ipsum.md)", Some("[[Lorem Ipsum]]")),
+            ("[see](lorem%20ipsum.md#a)", Some("[[see|lorem ipsum]]")),
+        ]
+    );
+    assert_eq!(wrong_link_styles[0].line_column(), Some((1, 3)));
+}
+
+/// Tags are not links to pages, and wikilinks to pages which don't exist can't be fixed
+#[test]
+fn wikilinks() {
+    info!("wikilinks");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- lorem\n")
+        .with_file("pages/sub/ipsum.md", "- [[Lorem]] #[[tag]] #tag\n")
+        .with_file("pages/dolor.md", "- [[Text|lorem]] [[sit]]\n");
+    let mut links: Vec<_> = lint(files, Some(LinkStyle::Markdown))
+        .wrong_link_styles()
+        .iter()
+        .map(|report| {
+            (
+                report.path(),
+                report.written().to_owned(),
+                report.replacement().map(ToOwned::to_owned),
+            )
+        })
+        .collect();
+    links.sort();
+    assert_eq!(
+        links,
+        [
+            (
+                PathBuf::from("pages/dolor.md"),
+                "[[Text|lorem]]".to_owned(),
+                Some("[Text](lorem.md)".to_owned())
+            ),
+            (PathBuf::from("pages/dolor.md"), "[[sit]]".to_owned(), None),
+            (
+                PathBuf::from("pages/sub/ipsum.md"),
+                "[[Lorem]]".to_owned(),
+                Some("[Lorem](../lorem.md)".to_owned())
+            ),
+        ]
+    );
+}
+
+#[test]
+fn no_link_style() {
+    info!("no_link_style");
+    let files = InMemory::new()
+        .with_file("pages/lorem.md", "- [[ipsum]]\n")
+        .with_file("pages/ipsum.md", "- [lorem](lorem.md)\n");
+    assert!(lint(files, None).wrong_link_styles().is_empty());
+}
+
+/// Every link in the file is fixed with the first
+#[test]
+fn fix() {
+    info!("fix");
+    let vault = Vault::new()
+        .with_page(&Page::new("lorem").with_line("- lorem"))
+        .with_page(
+            &Page::new("ipsum").with_line("- [Lorem](lorem.md) and [the lorem page](lorem.md)"),
+        )
+        .write()
+        .expect("The temporary directory is writable");
+    let mut config = vault.config();
+    config.link_style = Some(LinkStyle::Wikilink);
+    let linter = Linter::from_config(config);
+    let wrong_link_styles = linter
+        .run()
+        .expect("Linting should not fail")
+        .wrong_link_styles();
+    assert_eq!(wrong_link_styles.len(), 2, "{wrong_link_styles:#?}");
+    assert_eq!(wrong_link_styles[0].fix(linter.config()).unwrap(), Some(()));
+    assert_eq!(wrong_link_styles[1].fix(linter.config()).unwrap(), None);
+    assert_eq!(
+        vault.read("pages/ipsum.md").unwrap(),
+        "- [[Lorem]] and [[the lorem page|lorem]]\n"
+    );
+    assert!(linter.run().unwrap().wrong_link_styles().is_empty());
+}